@@ -0,0 +1,8 @@
+//! The parts of the browser's CEF app every process needs, shared by the
+//! browser executable and the helper that runs its subprocesses on macOS.
+
+mod render_process;
+mod scheme;
+
+pub use render_process::*;
+pub use scheme::*;
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use cef_ui::{
+    Browser, DictionaryValue, Frame, ListValue, LoadHandler, ProcessId, ProcessMessage,
+    RenderProcessHandler, RenderProcessHandlerCallbacks, V8Context, V8Handler, V8HandlerCallbacks,
+    V8PropertyAttribute, V8Value,
+};
+
+/// Process message carrying a `window.cefQuery` call to the browser process,
+/// with the query's id and request as arguments.
+pub const QUERY_MESSAGE: &str = "cefQuery";
+
+/// Process message carrying a `window.cefQueryCancel` call to the browser
+/// process, with the query's id as its argument.
+pub const CANCEL_MESSAGE: &str = "cefQueryCancel";
+
+/// Native functions sending the messages, taken off `window` by the binding.
+const SEND_FUNCTION: &str = "__cefQuerySend";
+const CANCEL_FUNCTION: &str = "__cefQueryCancelSend";
+
+/// Key of the scripts in a browser's `extra_info`.
+const SCRIPTS_KEY: &str = "scripts";
+
+/// Defines `window.cefQuery({ request, onSuccess, onFailure })` and
+/// `window.cefQueryCancel(id)` on top of the native functions. Answers come
+/// back from the browser process through `__cefQueryResolve`.
+const BINDING_SCRIPT: &str = r#"(() => {
+  const send = window.__cefQuerySend;
+  const cancel = window.__cefQueryCancelSend;
+  delete window.__cefQuerySend;
+  delete window.__cefQueryCancelSend;
+  if (window.cefQuery || !send || !cancel) return;
+
+  const pending = new Map();
+  let nextId = 1;
+
+  window.cefQuery = ({ request, onSuccess, onFailure }) => {
+    const id = nextId++;
+    pending.set(id, { onSuccess, onFailure });
+    send(id, String(request));
+    return id;
+  };
+
+  window.cefQueryCancel = (id) => {
+    if (pending.delete(id)) cancel(id);
+  };
+
+  Object.defineProperty(window, "__cefQueryResolve", {
+    value: (id, success, response, errorCode) => {
+      const query = pending.get(id);
+      if (!query) return;
+
+      pending.delete(id);
+      if (success) query.onSuccess && query.onSuccess(response);
+      else query.onFailure && query.onFailure(errorCode, response);
+    },
+  });
+})();"#;
+
+/// `extra_info` for creating a browser whose main frames run `scripts`, in
+/// order, as soon as each document's context exists.
+pub fn extra_info(scripts: &[&str]) -> DictionaryValue {
+    let list = ListValue::new();
+    if let Err(e) = list.set_size(scripts.len()) {
+        eprintln!("Error sizing page scripts: {}", e);
+    }
+
+    for (index, script) in scripts.iter().enumerate() {
+        if let Err(e) = list.set_string(index, script) {
+            eprintln!("Error adding page script: {}", e);
+        }
+    }
+
+    let info = DictionaryValue::new();
+    if let Err(e) = info.set_list(SCRIPTS_KEY, list) {
+        eprintln!("Error adding page scripts: {}", e);
+    }
+
+    info
+}
+
+/// The scripts in `extra_info`, if it has any.
+fn scripts(extra_info: &DictionaryValue) -> Vec<String> {
+    let Ok(Some(list)) = extra_info.get_list(SCRIPTS_KEY) else {
+        return Vec::new();
+    };
+
+    (0..list.len().unwrap_or(0))
+        .filter_map(|index| list.get_string(index).ok().flatten())
+        .collect()
+}
+
+/// Define `window.cefQuery` in a freshly created context of `frame`, before
+/// any of the page's scripts run.
+fn install_binding(frame: &Frame, context: &V8Context) {
+    let window = match context.get_global() {
+        Ok(Some(window)) => window,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("Error getting window for cefQuery: {}", e);
+            return;
+        }
+    };
+
+    let handler = V8Handler::new(QueryHandler {
+        frame: frame.clone(),
+    });
+    for name in [SEND_FUNCTION, CANCEL_FUNCTION] {
+        let function = V8Value::new_function(name, handler.clone());
+        if let Err(e) = window.set_value_bykey(name, function, V8PropertyAttribute::None) {
+            eprintln!("Error defining {}: {}", name, e);
+        }
+    }
+
+    if let Err(e) = frame.execute_java_script(BINDING_SCRIPT, "", 0) {
+        eprintln!("Error injecting cefQuery: {}", e);
+    }
+}
+
+/// Sends the binding's calls to the browser process as messages from the
+/// frame it's defined in, which is how the browser knows who's asking.
+struct QueryHandler {
+    frame: Frame,
+}
+
+impl QueryHandler {
+    /// The message for a call of `name` with `arguments`: the query's id,
+    /// then its request unless it's cancelled.
+    fn message(name: &str, arguments: &[V8Value]) -> Result<ProcessMessage, String> {
+        let id = arguments
+            .first()
+            .filter(|id| id.is_int().unwrap_or(false))
+            .and_then(|id| id.get_int_value().ok())
+            .ok_or("The query id must be an integer")?;
+
+        let (message, request) = match name {
+            SEND_FUNCTION => {
+                let request = arguments
+                    .get(1)
+                    .filter(|request| request.is_string().unwrap_or(false))
+                    .and_then(|request| request.get_string_value().ok())
+                    .ok_or("The query request must be a string")?;
+                (ProcessMessage::new(QUERY_MESSAGE), Some(request))
+            }
+            _ => (ProcessMessage::new(CANCEL_MESSAGE), None),
+        };
+
+        let list = message
+            .get_argument_list()
+            .map_err(|e| e.to_string())?
+            .ok_or("The query message has no arguments")?;
+        list.set_int(0, id).map_err(|e| e.to_string())?;
+        if let Some(request) = request {
+            list.set_string(1, &request).map_err(|e| e.to_string())?;
+        }
+
+        Ok(message)
+    }
+}
+
+impl V8HandlerCallbacks for QueryHandler {
+    fn execute(
+        &mut self,
+        name: &str,
+        _object: V8Value,
+        arguments: &[V8Value],
+        _retval: &mut Option<V8Value>,
+        exception: &mut Option<String>,
+    ) -> bool {
+        if name != SEND_FUNCTION && name != CANCEL_FUNCTION {
+            return false;
+        }
+
+        let sent = Self::message(name, arguments).and_then(|message| {
+            self.frame
+                .send_process_message(ProcessId::Browser, message)
+                .map_err(|e| e.to_string())
+        });
+        if let Err(e) = sent {
+            *exception = Some(e);
+        }
+
+        true
+    }
+}
+
+/// The render process handler. CEF asks the app for it every time it needs
+/// it, so the app should create one and hand out clones.
+pub fn render_process_handler() -> RenderProcessHandler {
+    RenderProcessHandler::new(BrowserRenderProcessHandler::default())
+}
+
+/// Defines `window.cefQuery` in every main frame, then runs the scripts its
+/// browser was created with, before any of the page's own.
+#[derive(Default)]
+struct BrowserRenderProcessHandler {
+    /// Scripts by browser identifier.
+    scripts: HashMap<i32, Vec<String>>,
+}
+
+impl RenderProcessHandlerCallbacks for BrowserRenderProcessHandler {
+    fn on_web_kit_initialized(&mut self) {}
+
+    fn on_browser_created(&mut self, browser: Browser, extra_info: Option<DictionaryValue>) {
+        let (Ok(id), Some(extra_info)) = (browser.get_identifier(), extra_info) else {
+            return;
+        };
+
+        self.scripts.insert(id, scripts(&extra_info));
+    }
+
+    fn on_browser_destroyed(&mut self, browser: Browser) {
+        if let Ok(id) = browser.get_identifier() {
+            self.scripts.remove(&id);
+        }
+    }
+
+    fn get_load_handler(&mut self) -> Option<LoadHandler> {
+        None
+    }
+
+    fn on_context_created(&mut self, browser: Browser, frame: Frame, context: V8Context) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+
+        install_binding(&frame, &context);
+
+        let Some(scripts) = browser
+            .get_identifier()
+            .ok()
+            .and_then(|id| self.scripts.get(&id))
+        else {
+            return;
+        };
+
+        for script in scripts {
+            if let Err(e) = frame.execute_java_script(script, "", 0) {
+                eprintln!("Error running page script: {}", e);
+            }
+        }
+    }
+
+    fn on_context_released(&mut self, _browser: Browser, _frame: Frame, _context: V8Context) {}
+
+    fn on_process_message_received(
+        &mut self,
+        _browser: Browser,
+        _frame: Frame,
+        _source_process: ProcessId,
+        _message: ProcessMessage,
+    ) -> bool {
+        false
+    }
+}
@@ -0,0 +1,17 @@
+use cef_ui::{SchemeOptions, SchemeRegistrar};
+
+/// Scheme serving the bundled assets, e.g. `app://assets/icons/close.svg`.
+pub const APP_SCHEME: &str = "app";
+
+/// Add the `app://` scheme. Every process has to register it, the same way,
+/// before CEF starts.
+pub fn register_schemes(registrar: &mut SchemeRegistrar) {
+    let options = SchemeOptions::Standard
+        | SchemeOptions::Secure
+        | SchemeOptions::CorsEnabled
+        | SchemeOptions::FetchEnabled;
+
+    if let Err(e) = registrar.add_custom_scheme(APP_SCHEME, options) {
+        eprintln!("Error registering {}:// scheme: {}", APP_SCHEME, e);
+    }
+}
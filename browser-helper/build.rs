@@ -1,7 +1,16 @@
 use anyhow::Result;
-use cef_ui_util::link_cef_helper;
+use cef_ui_util::{link_cef, link_cef_helper};
 
 fn main() -> Result<()> {
+    // The helper's app calls into CEF, so it links against it like the
+    // browser does.
+    link_cef()?;
     link_cef_helper()?;
+
+    // Helpers live in `<app>.app/Contents/Frameworks/<helper>.app/Contents/MacOS`,
+    // three levels below the framework.
+    #[cfg(target_os = "macos")]
+    println!("cargo:rustc-link-arg=-Wl,-rpath,@executable_path/../../..");
+
     Ok(())
 }
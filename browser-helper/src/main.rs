@@ -1,5 +1,44 @@
-use cef_ui_helper::run;
+use std::ffi::c_void;
+
+use cef_ui::{
+    App, AppCallbacks, BrowserProcessHandler, CommandLine, RenderProcessHandler, SchemeRegistrar,
+};
+use cef_ui_helper::run_with_app;
+
+/// The browser's app as its subprocesses need it: the same custom schemes
+/// and, for renderers, the same render process handler. Only subprocesses
+/// run here, so there's nothing for the browser process.
+struct HelperAppCallbacks {
+    render_process: RenderProcessHandler,
+}
+
+impl AppCallbacks for HelperAppCallbacks {
+    fn on_before_command_line_processing(
+        &mut self,
+        _process_type: Option<&str>,
+        _command_line: Option<CommandLine>,
+    ) {
+    }
+
+    fn on_register_custom_schemes(&mut self, mut registrar: SchemeRegistrar) {
+        browser_common::register_schemes(&mut registrar);
+    }
+
+    fn get_browser_process_handler(&mut self) -> Option<BrowserProcessHandler> {
+        None
+    }
+
+    fn get_render_process_handler(&mut self) -> Option<RenderProcessHandler> {
+        Some(self.render_process.clone())
+    }
+}
 
 fn main() {
-    run(true);
+    run_with_app(true, || {
+        let app = App::new(HelperAppCallbacks {
+            render_process: browser_common::render_process_handler(),
+        });
+
+        unsafe { app.into_raw() as *mut c_void }
+    });
 }
@@ -0,0 +1,8 @@
+// Runs in the hidden background page for as long as the browser is open,
+// whatever the tabs are doing. Talk to the browser with
+// `background.postMessage(name, payload)` and
+// `background.onMessage(name, (payload) => ...)`.
+
+background.onMessage("ping", (payload) => {
+  background.postMessage("pong", payload);
+});
@@ -0,0 +1,211 @@
+// A small Readability-style article extractor. It scores block elements by
+// the amount of prose they contain, picks the best container and strips it
+// down to the article content.
+class Readability {
+  static UNLIKELY = /banner|breadcrumbs|combx|comment|community|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|related|remark|replies|rss|shoutbox|sidebar|skyscraper|social|sponsor|supplemental|ad-break|agegate|pagination|pager|popup|yom-remote/i;
+  static MAYBE = /and|article|body|column|content|main|shadow/i;
+  static POSITIVE = /article|body|content|entry|hentry|h-entry|main|page|pagination|post|text|blog|story/i;
+  static NEGATIVE = /-ad-|hidden|^hid$| hid$| hid |^hid |banner|combx|comment|com-|contact|footer|gdpr|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|widget/i;
+  static REMOVE = "script, style, noscript, iframe, form, nav, aside, footer, header, button, input, select, textarea, svg, canvas, object, embed";
+  static SCORED = "p, pre, td, blockquote, li, section, h2, h3";
+  static ATTRIBUTES = ["href", "src", "srcset", "alt", "title", "colspan", "rowspan"];
+
+  constructor(doc, baseUrl = doc.baseURI) {
+    this.doc = doc;
+    this.baseUrl = baseUrl;
+    this.scores = new Map();
+  }
+
+  parse() {
+    const title = this.title();
+    const byline = this.byline();
+    const body = this.doc.body;
+    if (!body) return null;
+
+    body.querySelectorAll(Readability.REMOVE).forEach((element) => element.remove());
+    this.removeUnlikely(body);
+
+    const top = this.topCandidate(body);
+    if (!top) return null;
+
+    const content = this.collect(top);
+    this.clean(content);
+
+    if (this.textLength(content) < 250) return null;
+
+    return { title, byline, content: content.innerHTML };
+  }
+
+  title() {
+    const meta = this.doc.querySelector('meta[property="og:title"], meta[name="twitter:title"]');
+    return (meta && meta.content.trim()) || this.doc.title.trim();
+  }
+
+  byline() {
+    const meta = this.doc.querySelector('meta[name="author"], meta[property="article:author"]');
+    if (meta && meta.content && !/^https?:/.test(meta.content)) return meta.content.trim();
+
+    const element = this.doc.querySelector('[rel="author"], [itemprop="author"], .byline, .author');
+    const text = element && element.textContent.trim();
+    return text && text.length < 100 ? text : null;
+  }
+
+  removeUnlikely(root) {
+    root.querySelectorAll("*").forEach((element) => {
+      if (element === root || !element.isConnected) return;
+
+      const match = `${element.className} ${element.id}`;
+      if (
+        Readability.UNLIKELY.test(match) &&
+        !Readability.MAYBE.test(match) &&
+        !element.closest("article, main, table, code, pre")
+      ) {
+        element.remove();
+      }
+    });
+  }
+
+  weight(element) {
+    let weight = 0;
+
+    for (const value of [element.className, element.id]) {
+      if (typeof value !== "string" || !value) continue;
+      if (Readability.NEGATIVE.test(value)) weight -= 25;
+      if (Readability.POSITIVE.test(value)) weight += 25;
+    }
+
+    switch (element.tagName) {
+      case "ARTICLE":
+      case "MAIN":
+        return weight + 25;
+      case "DIV":
+        return weight + 5;
+      case "PRE":
+      case "TD":
+      case "BLOCKQUOTE":
+        return weight + 3;
+      case "ADDRESS":
+      case "OL":
+      case "UL":
+      case "DL":
+      case "DD":
+      case "DT":
+      case "LI":
+      case "FORM":
+        return weight - 3;
+      case "H1":
+      case "H2":
+      case "H3":
+      case "H4":
+      case "H5":
+      case "H6":
+      case "TH":
+        return weight - 5;
+      default:
+        return weight;
+    }
+  }
+
+  score(element, amount) {
+    if (!this.scores.has(element)) this.scores.set(element, this.weight(element));
+    this.scores.set(element, this.scores.get(element) + amount);
+  }
+
+  topCandidate(root) {
+    root.querySelectorAll(Readability.SCORED).forEach((element) => {
+      const text = element.textContent.trim();
+      if (text.length < 25) return;
+
+      const amount = 1 + text.split(/[,，]/).length + Math.min(Math.floor(text.length / 100), 3);
+      const parent = element.parentElement;
+      if (!parent) return;
+
+      this.score(parent, amount);
+      if (parent.parentElement) this.score(parent.parentElement, amount / 2);
+    });
+
+    let top = null;
+    let topScore = 0;
+
+    for (const [element, score] of this.scores) {
+      const adjusted = score * (1 - this.linkDensity(element));
+      this.scores.set(element, adjusted);
+
+      if (adjusted > topScore) {
+        top = element;
+        topScore = adjusted;
+      }
+    }
+
+    return top || root;
+  }
+
+  collect(top) {
+    const content = this.doc.createElement("div");
+    const topScore = this.scores.get(top) || 0;
+    const threshold = Math.max(10, topScore * 0.2);
+    const siblings = top.parentElement ? [...top.parentElement.children] : [top];
+
+    for (const sibling of siblings) {
+      let append = sibling === top || (this.scores.get(sibling) || 0) >= threshold;
+
+      if (!append && sibling.tagName === "P") {
+        const text = sibling.textContent.trim();
+        const density = this.linkDensity(sibling);
+        append = (text.length > 80 && density < 0.25) || (text.length > 0 && density === 0 && /\.( |$)/.test(text));
+      }
+
+      if (append) content.appendChild(sibling.cloneNode(true));
+    }
+
+    return content;
+  }
+
+  clean(content) {
+    content.querySelectorAll("div, section, table, ul, ol").forEach((element) => {
+      if (!element.isConnected) return;
+
+      const text = this.textLength(element);
+      const images = element.querySelectorAll("img").length;
+      const density = this.linkDensity(element);
+
+      if ((text < 25 && images === 0) || density > 0.5 || (this.weight(element) < 0 && text < 200)) {
+        element.remove();
+      }
+    });
+
+    content.querySelectorAll("*").forEach((element) => {
+      for (const attribute of [...element.attributes]) {
+        if (!Readability.ATTRIBUTES.includes(attribute.name)) {
+          element.removeAttribute(attribute.name);
+        }
+      }
+
+      for (const name of ["href", "src"]) {
+        const value = element.getAttribute(name);
+        if (value && !value.startsWith("javascript:")) {
+          try {
+            element.setAttribute(name, new URL(value, this.baseUrl).href);
+          } catch (_) {
+            element.removeAttribute(name);
+          }
+        } else if (value) {
+          element.removeAttribute(name);
+        }
+      }
+    });
+  }
+
+  textLength(element) {
+    return element.textContent.replace(/\s+/g, " ").trim().length;
+  }
+
+  linkDensity(element) {
+    const length = this.textLength(element);
+    if (!length) return 0;
+
+    let links = 0;
+    element.querySelectorAll("a").forEach((link) => (links += this.textLength(link)));
+    return links / length;
+  }
+}
@@ -0,0 +1,156 @@
+use std::{borrow::Cow, ffi::CStr, fmt};
+
+use anyhow::{bail, Result};
+use gpui::{div, prelude::*, px, Context, DismissEvent, EventEmitter, SharedString, Window};
+
+use crate::{button::button, locale::t, theme::ActiveTheme};
+
+/// The oldest CEF the browser runs on. The bindings are generated from the
+/// CEF 121 headers, and older frameworks lack entry points and struct fields
+/// they call into, crashing the browser somewhere down the line instead of
+/// at startup.
+const MIN_CEF_VERSION: (u32, u32, u32) = (121, 0, 0);
+
+/// Where CEF builds can be downloaded.
+const CEF_DOWNLOADS: &str = "https://cef-builds.spotifycdn.com/index.html";
+
+/// A CEF build and the Chromium it's based on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CefVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// Chromium's major, minor, build and patch numbers.
+    pub chromium: (u32, u32, u32, u32),
+}
+
+impl CefVersion {
+    /// The version of the CEF framework loaded at runtime, which may not be
+    /// the one the browser was built against.
+    pub fn runtime() -> Self {
+        // Entries of `cef_version_info`, as `CEF_VERSION_MAJOR` onwards.
+        let entry = |index| unsafe { cef_ui_sys::cef_version_info(index) }.max(0) as u32;
+
+        Self {
+            major: entry(0),
+            minor: entry(1),
+            patch: entry(2),
+            chromium: (entry(4), entry(5), entry(6), entry(7)),
+        }
+    }
+
+    fn number(&self) -> (u32, u32, u32) {
+        (self.major, self.minor, self.patch)
+    }
+
+    pub fn chromium(&self) -> String {
+        let (major, minor, build, patch) = self.chromium;
+        format!("{}.{}.{}.{}", major, minor, build, patch)
+    }
+}
+
+impl fmt::Display for CefVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The CEF headers the browser was built against, e.g.
+/// `121.3.15+g4d3b0b4+chromium-121.0.6167.184`.
+pub fn built_against() -> Cow<'static, str> {
+    CStr::from_bytes_with_nul(cef_ui_sys::CEF_VERSION)
+        .map(CStr::to_string_lossy)
+        .unwrap_or_default()
+}
+
+/// Make sure the CEF framework loaded is new enough to run the browser,
+/// before anything calls into it.
+pub fn check_cef_version() -> Result<CefVersion> {
+    let version = CefVersion::runtime();
+    if version.number() < MIN_CEF_VERSION {
+        let (major, minor, patch) = MIN_CEF_VERSION;
+        bail!(
+            "The installed CEF framework is version {}, but the browser needs CEF {}.{}.{} or \
+             newer. Rebuild the app bundle with `cargo cef-build-debug` to install the CEF it \
+             was built against ({}), or download that build from {} and replace the framework.",
+            version,
+            major,
+            minor,
+            patch,
+            built_against(),
+            CEF_DOWNLOADS,
+        );
+    }
+
+    Ok(version)
+}
+
+/// Modal showing the versions of the browser, CEF and Chromium.
+pub struct AboutDialog {
+    cef: CefVersion,
+}
+
+impl AboutDialog {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self {
+            cef: CefVersion::runtime(),
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for AboutDialog {}
+
+impl Render for AboutDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let row = |label: SharedString, value: String| {
+            div()
+                .flex()
+                .justify_between()
+                .gap_4()
+                .child(div().text_color(theme.text_secondary).child(label))
+                .child(value)
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("about.title")))
+                    .child(row(
+                        t!("about.browser_version"),
+                        env!("CARGO_PKG_VERSION").to_string(),
+                    ))
+                    .child(row(t!("about.cef_version"), self.cef.to_string()))
+                    .child(row(t!("about.chromium_version"), self.cef.chromium()))
+                    .child(
+                        div()
+                            .text_color(theme.text_disabled)
+                            .child(t!("about.built_against", version = built_against())),
+                    )
+                    .child(
+                        div().flex().justify_end().child(
+                            button("about-close", t!("about.close"), true, cx)
+                                .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                        ),
+                    ),
+            )
+    }
+}
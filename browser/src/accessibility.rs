@@ -0,0 +1,562 @@
+// Only the macOS bridge reads the tree so far.
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{DictionaryValue, State, Value};
+use gpui::{point, size, App as GpuiApp, Bounds, Global, Pixels, Window};
+
+use crate::{tabs::TabManager, BrowserState};
+
+/// How many offset containers deep a node's position is followed, in case
+/// a broken update makes them loop.
+const MAX_OFFSET_DEPTH: usize = 64;
+
+/// A node of the page's accessibility tree, as Chromium sends it.
+#[derive(Clone, Debug, Default)]
+pub struct AccessibilityNode {
+    pub id: i32,
+    /// Chromium's name for the role, like `button` or `staticText`.
+    pub role: String,
+    pub name: String,
+    pub value: String,
+    pub description: String,
+    pub children: Vec<i32>,
+    /// Position relative to `offset_container`, or to the page without one,
+    /// in logical pixels.
+    location: Bounds<f32>,
+    offset_container: Option<i32>,
+}
+
+impl AccessibilityNode {
+    fn from_value(node: &DictionaryValue) -> Result<Self> {
+        let attributes = node.get_dictionary("attributes")?;
+        let attribute = |key: &str| -> Result<String> {
+            Ok(match &attributes {
+                Some(attributes) => attributes.get_string(key)?.unwrap_or_default(),
+                None => String::new(),
+            })
+        };
+
+        let mut children = Vec::new();
+        if let Some(child_ids) = node.get_list("child_ids")? {
+            for index in 0..child_ids.len()? {
+                children.push(child_ids.get_int(index)?);
+            }
+        }
+
+        let mut this = Self {
+            id: node.get_int("id")?,
+            role: node.get_string("role")?.unwrap_or_default(),
+            name: attribute("name")?,
+            value: attribute("value")?,
+            description: attribute("description")?,
+            children,
+            ..Default::default()
+        };
+        if let Some(location) = node.get_dictionary("location")? {
+            this.location = read_bounds(&location)?;
+        }
+        if node.has_key("offset_container_id")? {
+            this.offset_container = Some(node.get_int("offset_container_id")?);
+        }
+
+        Ok(this)
+    }
+
+    /// Whether the node only holds its children's place, like a wrapper
+    /// `div`, and screen readers should see its children instead.
+    pub fn is_ignored(&self) -> bool {
+        matches!(self.role.as_str(), "ignored" | "none" | "genericContainer")
+            && self.name.is_empty()
+    }
+}
+
+fn read_bounds(bounds: &DictionaryValue) -> Result<Bounds<f32>> {
+    Ok(Bounds::new(
+        point(
+            bounds.get_double("x")? as f32,
+            bounds.get_double("y")? as f32,
+        ),
+        size(
+            bounds.get_double("width")? as f32,
+            bounds.get_double("height")? as f32,
+        ),
+    ))
+}
+
+#[derive(Default)]
+struct AccessibilityTreeInner {
+    /// Whether the active tab is asked to send its tree, which it is while a
+    /// screen reader is running.
+    enabled: bool,
+    /// Identifier of the browser sending the tree.
+    browser_id: Option<i32>,
+    /// Chromium's identifier of the main frame's tree. Trees of iframes are
+    /// left out.
+    tree_id: Option<String>,
+    root: Option<i32>,
+    focus: Option<i32>,
+    nodes: HashMap<i32, AccessibilityNode>,
+    changed: bool,
+}
+
+impl AccessibilityTreeInner {
+    /// Drop the descendants of `id`, which an update is about to replace.
+    fn clear_children(&mut self, id: i32) {
+        let Some(node) = self.nodes.get_mut(&id) else {
+            return;
+        };
+
+        let mut stack = std::mem::take(&mut node.children);
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes.remove(&id) {
+                stack.extend(node.children);
+            }
+        }
+    }
+}
+
+/// The accessibility tree of the active tab, mirrored from the updates CEF
+/// sends the render handler, for the platform's screen reader APIs.
+#[derive(Clone, Default)]
+pub struct AccessibilityTree(Arc<Mutex<AccessibilityTreeInner>>);
+
+impl Global for AccessibilityTree {}
+
+impl AccessibilityTree {
+    /// Apply the nodes in a tree change CEF sent.
+    pub fn on_tree_change(&self, value: Value) -> Result<()> {
+        let Some(change) = value.get_dictionary()? else {
+            return Ok(());
+        };
+        let (Some(tree_id), Some(updates)) = (
+            change.get_string("ax_tree_id")?,
+            change.get_list("updates")?,
+        ) else {
+            return Ok(());
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if !inner.enabled {
+            return Ok(());
+        }
+
+        for index in 0..updates.len()? {
+            let Some(update) = updates.get_dictionary(index)? else {
+                continue;
+            };
+
+            let tree_data = match update.get_bool("has_tree_data")? {
+                true => update.get_dictionary("tree_data")?,
+                false => None,
+            };
+
+            // The first tree without a parent after a reset is the main
+            // frame's.
+            if inner.tree_id.is_none() {
+                let Some(tree_data) = tree_data.as_ref() else {
+                    continue;
+                };
+                if !tree_data
+                    .get_string("parent_tree_id")?
+                    .unwrap_or_default()
+                    .is_empty()
+                {
+                    continue;
+                }
+                inner.tree_id = Some(tree_id.clone());
+            }
+            if inner.tree_id.as_deref() != Some(tree_id.as_str()) {
+                continue;
+            }
+
+            if let Some(tree_data) = tree_data {
+                if tree_data.has_key("focus_id")? {
+                    inner.focus = Some(tree_data.get_int("focus_id")?);
+                }
+            }
+            if update.has_key("node_id_to_clear")? {
+                let id = update.get_int("node_id_to_clear")?;
+                inner.clear_children(id);
+            }
+            if update.has_key("root_id")? {
+                inner.root = Some(update.get_int("root_id")?);
+            }
+
+            if let Some(nodes) = update.get_list("nodes")? {
+                for index in 0..nodes.len()? {
+                    if let Some(node) = nodes.get_dictionary(index)? {
+                        let node = AccessibilityNode::from_value(&node)?;
+                        inner.nodes.insert(node.id, node);
+                    }
+                }
+            }
+
+            inner.changed = true;
+        }
+
+        Ok(())
+    }
+
+    /// Move the nodes in a location change CEF sent.
+    pub fn on_location_change(&self, value: Value) -> Result<()> {
+        let Some(changes) = value.get_list()? else {
+            return Ok(());
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        for index in 0..changes.len()? {
+            let Some(change) = changes.get_dictionary(index)? else {
+                continue;
+            };
+            if change.get_string("ax_tree_id")? != inner.tree_id {
+                continue;
+            }
+            let Some(location) = change.get_dictionary("new_location")? else {
+                continue;
+            };
+
+            let id = change.get_int("id")?;
+            let offset_container = match location.has_key("offset_container_id")? {
+                true => Some(location.get_int("offset_container_id")?),
+                false => None,
+            };
+            let bounds = match location.get_dictionary("bounds")? {
+                Some(bounds) => read_bounds(&bounds)?,
+                None => continue,
+            };
+
+            if let Some(node) = inner.nodes.get_mut(&id) {
+                node.location = bounds;
+                node.offset_container = offset_container;
+                inner.changed = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the tree changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Forget the tree, to start over from the next one sent.
+    fn reset(&self, enabled: bool, browser_id: Option<i32>) {
+        *self.0.lock().unwrap() = AccessibilityTreeInner {
+            enabled,
+            browser_id,
+            changed: true,
+            ..Default::default()
+        };
+    }
+
+    /// The root node, if a tree has been sent.
+    pub fn root(&self) -> Option<i32> {
+        self.0.lock().unwrap().root
+    }
+
+    /// The focused node, if any.
+    pub fn focus(&self) -> Option<i32> {
+        self.0.lock().unwrap().focus
+    }
+
+    pub fn node(&self, id: i32) -> Option<AccessibilityNode> {
+        self.0.lock().unwrap().nodes.get(&id).cloned()
+    }
+
+    /// Where `id` is in the page, in logical pixels, following its offset
+    /// containers.
+    pub fn page_bounds(&self, id: i32) -> Option<Bounds<f32>> {
+        let inner = self.0.lock().unwrap();
+        let node = inner.nodes.get(&id)?;
+
+        let mut bounds = node.location;
+        let mut container = node.offset_container;
+        for _ in 0..MAX_OFFSET_DEPTH {
+            let Some(node) = container.and_then(|id| inner.nodes.get(&id)) else {
+                break;
+            };
+            bounds.origin += node.location.origin;
+            container = node.offset_container;
+        }
+
+        Some(bounds)
+    }
+}
+
+/// Have only the active tab send its accessibility tree, and only while a
+/// screen reader is running, since building it slows the page down.
+/// Switching tabs starts over with the new tab's tree.
+fn update_state(cx: &mut GpuiApp) {
+    let tree = cx.global::<AccessibilityTree>().clone();
+    let enabled = platform::screen_reader_running();
+    let active = cx.global::<BrowserState>().browser.clone();
+    let browser_id = active
+        .as_ref()
+        .and_then(|browser| browser.get_identifier().ok());
+
+    {
+        let inner = tree.0.lock().unwrap();
+        if (inner.enabled, inner.browser_id) == (enabled, browser_id) {
+            return;
+        }
+    }
+
+    for tab in cx.global::<TabManager>().tabs() {
+        let state = match enabled && tab.browser.get_identifier().ok() == browser_id {
+            true => State::Enabled,
+            false => State::Disabled,
+        };
+        if let Err(e) = tab
+            .browser
+            .get_host()
+            .and_then(|host| host.set_accessibility_state(state))
+        {
+            eprintln!("Error setting accessibility state: {}", e);
+        }
+    }
+
+    tree.reset(enabled, browser_id);
+}
+
+/// Follow screen readers starting and stopping and the active tab changing,
+/// and hand the platform the tree when it changed. `page` is where the page
+/// is drawn in `window`.
+pub fn poll(window: &mut Window, page: Bounds<Pixels>, cx: &mut GpuiApp) {
+    update_state(cx);
+
+    let tree = cx.global::<AccessibilityTree>().clone();
+    platform::sync(window, page, &tree);
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::cell::Cell;
+
+    use core_foundation::{base::TCFType, string::CFString};
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use gpui::{Bounds, Pixels, Window};
+    use objc::{
+        class, msg_send,
+        runtime::{Object, BOOL, NO, YES},
+        sel, sel_impl,
+    };
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    use super::{AccessibilityNode, AccessibilityTree};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        fn NSAccessibilityPostNotification(element: *mut Object, notification: *mut Object);
+    }
+
+    thread_local! {
+        /// Where the page was on screen when the elements were last built.
+        /// Their frames are in screen coordinates, so moving the window
+        /// means building them again.
+        static LAST_FRAME: Cell<Option<(f64, f64, f64, f64)>> = const { Cell::new(None) };
+    }
+
+    /// Whether VoiceOver is on.
+    pub fn screen_reader_running() -> bool {
+        unsafe {
+            let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let running: BOOL = msg_send![workspace, isVoiceOverEnabled];
+            running == YES
+        }
+    }
+
+    /// The `NSAccessibility` role for a Chromium role.
+    fn ns_role(role: &str) -> &'static str {
+        match role {
+            "button" | "toggleButton" => "AXButton",
+            "checkBox" | "switch" => "AXCheckBox",
+            "radioButton" => "AXRadioButton",
+            "link" => "AXLink",
+            "heading" => "AXHeading",
+            "image" | "svgRoot" => "AXImage",
+            "textField" | "searchBox" | "textFieldWithComboBox" => "AXTextField",
+            "staticText" | "labelText" => "AXStaticText",
+            "comboBoxSelect" | "popUpButton" => "AXPopUpButton",
+            "slider" => "AXSlider",
+            "list" | "listBox" => "AXList",
+            "table" | "grid" => "AXTable",
+            "row" => "AXRow",
+            "cell" | "gridCell" | "columnHeader" | "rowHeader" => "AXCell",
+            "rootWebArea" | "webArea" => "AXWebArea",
+            _ => "AXGroup",
+        }
+    }
+
+    /// Converts rectangles in the page to screen coordinates.
+    struct Screen {
+        view: *mut Object,
+        window: *mut Object,
+        page: Bounds<Pixels>,
+        flipped: bool,
+        view_height: f64,
+    }
+
+    impl Screen {
+        unsafe fn frame(&self, bounds: Bounds<f32>) -> CGRect {
+            let x = f32::from(self.page.origin.x) + bounds.origin.x;
+            let y = f32::from(self.page.origin.y) + bounds.origin.y;
+            let (width, height) = (bounds.size.width as f64, bounds.size.height as f64);
+
+            // GPUI measures from the top of the view, AppKit from the bottom
+            // unless the view is flipped.
+            let y = match self.flipped {
+                true => y as f64,
+                false => self.view_height - y as f64 - height,
+            };
+            let rect = CGRect::new(&CGPoint::new(x as f64, y), &CGSize::new(width, height));
+            let nil: *mut Object = std::ptr::null_mut();
+            let in_window: CGRect = msg_send![self.view, convertRect: rect toView: nil];
+            msg_send![self.window, convertRectToScreen: in_window]
+        }
+    }
+
+    /// Build the element for `id`, or for its children when it's ignored,
+    /// appending them to `siblings`. Returns the focused element, if it's
+    /// among them.
+    unsafe fn build(
+        tree: &AccessibilityTree,
+        screen: &Screen,
+        id: i32,
+        parent: *mut Object,
+        siblings: *mut Object,
+    ) -> Option<*mut Object> {
+        let node: AccessibilityNode = tree.node(id)?;
+        if node.role == "inlineTextBox" {
+            return None;
+        }
+
+        if node.is_ignored() && tree.root() != Some(id) {
+            let mut focused = None;
+            for child in node.children {
+                focused = build(tree, screen, child, parent, siblings).or(focused);
+            }
+            return focused;
+        }
+
+        let element: *mut Object = msg_send![class!(NSAccessibilityElement), new];
+        let role = CFString::new(ns_role(&node.role));
+        let _: () =
+            msg_send![element, setAccessibilityRole: role.as_concrete_TypeRef() as *mut Object];
+        if !node.name.is_empty() {
+            let label = CFString::new(&node.name);
+            let _: () = msg_send![element, setAccessibilityLabel: label.as_concrete_TypeRef() as *mut Object];
+        }
+        if !node.value.is_empty() {
+            let value = CFString::new(&node.value);
+            let _: () = msg_send![element, setAccessibilityValue: value.as_concrete_TypeRef() as *mut Object];
+        }
+        if !node.description.is_empty() {
+            let help = CFString::new(&node.description);
+            let _: () =
+                msg_send![element, setAccessibilityHelp: help.as_concrete_TypeRef() as *mut Object];
+        }
+        if let Some(bounds) = tree.page_bounds(id) {
+            let _: () = msg_send![element, setAccessibilityFrame: screen.frame(bounds)];
+        }
+        let _: () = msg_send![element, setAccessibilityParent: parent];
+
+        let mut focused = None;
+        if tree.focus() == Some(id) {
+            let _: () = msg_send![element, setAccessibilityFocused: YES];
+            focused = Some(element);
+        }
+
+        let children: *mut Object = msg_send![class!(NSMutableArray), new];
+        for child in node.children {
+            focused = build(tree, screen, child, element, children).or(focused);
+        }
+        let _: () = msg_send![element, setAccessibilityChildren: children];
+        let _: () = msg_send![children, release];
+
+        let _: () = msg_send![siblings, addObject: element];
+        let _: () = msg_send![element, release];
+        focused
+    }
+
+    /// Hang elements for the tree off the window's view, where VoiceOver
+    /// finds them, when the tree changed or the window moved.
+    pub fn sync(window: &mut Window, page: Bounds<Pixels>, tree: &AccessibilityTree) {
+        let Ok(handle) = HasWindowHandle::window_handle(window) else {
+            return;
+        };
+        let RawWindowHandle::AppKit(handle) = handle.as_raw() else {
+            return;
+        };
+        let view = handle.ns_view.as_ptr() as *mut Object;
+
+        unsafe {
+            let ns_window: *mut Object = msg_send![view, window];
+            if ns_window.is_null() {
+                return;
+            }
+            let view_bounds: CGRect = msg_send![view, bounds];
+            let flipped: BOOL = msg_send![view, isFlipped];
+            let screen = Screen {
+                view,
+                window: ns_window,
+                page,
+                flipped: flipped != NO,
+                view_height: view_bounds.size.height,
+            };
+
+            let origin = screen.frame(Bounds::default()).origin;
+            let frame = Some((
+                origin.x,
+                origin.y,
+                f32::from(page.size.width) as f64,
+                f32::from(page.size.height) as f64,
+            ));
+            let moved = LAST_FRAME.with(|last| last.replace(frame)) != frame;
+            if !tree.take_changed() && !moved {
+                return;
+            }
+
+            let children: *mut Object = msg_send![class!(NSMutableArray), new];
+            let focused = match tree.root() {
+                Some(root) => build(tree, &screen, root, view, children),
+                None => None,
+            };
+            let _: () = msg_send![view, setAccessibilityChildren: children];
+            let _: () = msg_send![children, release];
+
+            let layout_changed = CFString::from_static_string("AXLayoutChanged");
+            NSAccessibilityPostNotification(
+                view,
+                layout_changed.as_concrete_TypeRef() as *mut Object,
+            );
+            if let Some(focused) = focused {
+                let focus_changed = CFString::from_static_string("AXFocusedUIElementChanged");
+                NSAccessibilityPostNotification(
+                    focused,
+                    focus_changed.as_concrete_TypeRef() as *mut Object,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use gpui::{Bounds, Pixels, Window};
+
+    use super::AccessibilityTree;
+
+    /// Screen readers aren't supported on this platform yet.
+    pub fn screen_reader_running() -> bool {
+        false
+    }
+
+    pub fn sync(_window: &mut Window, _page: Bounds<Pixels>, _tree: &AccessibilityTree) {}
+}
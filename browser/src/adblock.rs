@@ -0,0 +1,62 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use gpui::Global;
+use url::Url;
+
+/// Ad and tracker hosts whose requests are cancelled. Subdomains match too.
+const BLOCKED_HOSTS: &[&str] = &[
+    "doubleclick.net",
+    "googlesyndication.com",
+    "googleadservices.com",
+    "adservice.google.com",
+    "amazon-adsystem.com",
+    "adnxs.com",
+    "adsrvr.org",
+    "criteo.com",
+    "moatads.com",
+    "outbrain.com",
+    "pubmatic.com",
+    "rubiconproject.com",
+    "scorecardresearch.com",
+    "taboola.com",
+];
+
+/// Whether ad requests are blocked, shared with the resource request handler.
+#[derive(Clone, Default)]
+pub struct AdBlock(Arc<AtomicBool>);
+
+impl Global for AdBlock {}
+
+impl AdBlock {
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether a request for `url` should be cancelled.
+    pub fn should_block(&self, url: &str) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+
+        let Some(host) = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        else {
+            return false;
+        };
+
+        BLOCKED_HOSTS.iter().any(|blocked| {
+            host == *blocked
+                || host
+                    .strip_suffix(blocked)
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+}
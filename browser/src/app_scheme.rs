@@ -0,0 +1,222 @@
+use std::path::{Component, Path, PathBuf};
+
+use browser_common::APP_SCHEME as SCHEME;
+use cef_ui::{
+    register_scheme_handler_factory, Browser, Callback, ErrorCode, Frame, Request, ResourceHandler,
+    ResourceHandlerCallbacks, ResourceReadCallback, ResourceSkipCallback, Response,
+    SchemeHandlerFactory, SchemeHandlerFactoryCallbacks,
+};
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+/// Host under `app://` that maps onto the assets directory.
+const ASSETS_HOST: &str = "assets";
+
+/// Serve `app://` requests from `base`. Call once CEF has been initialized.
+pub fn register_handler(base: PathBuf) {
+    let factory = SchemeHandlerFactory::new(AppSchemeHandlerFactory { base });
+
+    if !register_scheme_handler_factory(SCHEME, None, factory) {
+        eprintln!("Error registering {}:// scheme handler", SCHEME);
+    }
+}
+
+struct AppSchemeHandlerFactory {
+    base: PathBuf,
+}
+
+impl AppSchemeHandlerFactory {
+    /// Map `app://assets/<path>` onto a file under the assets directory,
+    /// refusing anything that would escape it.
+    fn resolve(&self, url: &str) -> Option<PathBuf> {
+        let url = Url::parse(url).ok()?;
+        if url.host_str() != Some(ASSETS_HOST) {
+            return None;
+        }
+
+        let path = percent_decode_str(url.path()).decode_utf8().ok()?;
+        let path = Path::new(path.trim_start_matches('/'));
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        Some(self.base.join(path))
+    }
+}
+
+impl SchemeHandlerFactoryCallbacks for AppSchemeHandlerFactory {
+    fn create(
+        &mut self,
+        _browser: Option<Browser>,
+        _frame: Option<Frame>,
+        _scheme_name: &str,
+        request: Request,
+    ) -> Option<ResourceHandler> {
+        let path = request.get_url().ok().and_then(|url| self.resolve(&url));
+
+        Some(ResourceHandler::new(AssetResourceHandler {
+            path,
+            data: Vec::new(),
+            offset: 0,
+        }))
+    }
+}
+
+/// Streams a single asset file back to the page.
+struct AssetResourceHandler {
+    path: Option<PathBuf>,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl ResourceHandlerCallbacks for AssetResourceHandler {
+    fn open(&mut self, _request: Request, handle_request: &mut bool, _callback: Callback) -> bool {
+        *handle_request = true;
+
+        if let Some(path) = &self.path {
+            match std::fs::read(path) {
+                Ok(data) => self.data = data,
+                Err(e) => {
+                    eprintln!("Error loading {}: {}", path.display(), e);
+                    self.path = None;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn get_response_headers(
+        &mut self,
+        response: Response,
+        response_length: &mut i64,
+        _redirect_url: &mut Option<String>,
+    ) {
+        let result = match &self.path {
+            Some(path) => {
+                *response_length = self.data.len() as i64;
+
+                response
+                    .set_status(200)
+                    .and_then(|_| response.set_mime_type(mime_type(path)))
+            }
+            None => {
+                *response_length = 0;
+
+                response
+                    .set_status(404)
+                    .and_then(|_| response.set_error(ErrorCode::FileNotFound))
+            }
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error setting response headers: {}", e);
+        }
+    }
+
+    fn skip(
+        &mut self,
+        bytes_to_skip: i64,
+        bytes_skipped: &mut i64,
+        _callback: ResourceSkipCallback,
+    ) -> bool {
+        let skipped = (bytes_to_skip.max(0) as usize).min(self.data.len() - self.offset);
+
+        self.offset += skipped;
+        *bytes_skipped = skipped as i64;
+
+        true
+    }
+
+    fn read(
+        &mut self,
+        data_out: &mut [u8],
+        bytes_read: &mut i32,
+        _callback: ResourceReadCallback,
+    ) -> bool {
+        let remaining = &self.data[self.offset..];
+        let count = remaining.len().min(data_out.len());
+
+        data_out[..count].copy_from_slice(&remaining[..count]);
+        self.offset += count;
+        *bytes_read = count as i32;
+
+        count > 0
+    }
+
+    fn cancel(&mut self) {
+        self.data = Vec::new();
+        self.offset = 0;
+    }
+}
+
+/// Guess the MIME type from the file extension.
+fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "txt" => "text/plain",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factory() -> AppSchemeHandlerFactory {
+        AppSchemeHandlerFactory {
+            base: PathBuf::from("/assets"),
+        }
+    }
+
+    #[test]
+    fn resolves_paths_under_the_assets_host() {
+        assert_eq!(
+            factory().resolve("app://assets/pages/index.html"),
+            Some(PathBuf::from("/assets/pages/index.html"))
+        );
+        assert_eq!(factory().resolve("app://other/index.html"), None);
+    }
+
+    #[test]
+    fn resolves_encoded_paths() {
+        assert_eq!(
+            factory().resolve("app://assets/my%20file.html"),
+            Some(PathBuf::from("/assets/my file.html"))
+        );
+        assert_eq!(
+            factory().resolve("app://assets/caf%C3%A9.png"),
+            Some(PathBuf::from("/assets/café.png"))
+        );
+    }
+
+    #[test]
+    fn refuses_paths_escaping_the_assets() {
+        assert_eq!(factory().resolve("app://assets/..%2Fsecret"), None);
+        assert_eq!(factory().resolve("app://assets/a/..%2F..%2Fsecret"), None);
+        assert_eq!(factory().resolve("app://assets/%FF"), None);
+    }
+}
@@ -0,0 +1,1067 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use cef_ui::{
+    AuthCallback, Browser, Frame, Request, Response, UrlRequest, UrlRequestClient,
+    UrlRequestClientCallbacks, UrlRequestFlags, UrlRequestStatus,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::{write::GzEncoder, Compression};
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, EventEmitter, Global, SharedString, Window,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{button::button, locale::t, settings::Settings, theme::ActiveTheme, BrowserState};
+
+/// Responses larger than this are left out of a saved page, so a video
+/// playing on it doesn't have to be kept in memory.
+const MAX_RESOURCE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Prefix of the console message the serialize script reports the page's
+/// HTML with.
+const HTML_PREFIX: &str = "__cef_save_page__:";
+
+/// Prefix of the console message the resources script reports what the
+/// page loaded with.
+const RESOURCES_PREFIX: &str = "__cef_save_resources__:";
+
+/// Reports the page's HTML with every `src` and `href` made absolute, so the
+/// copy still finds what it links to wherever it's saved.
+const SERIALIZE_SCRIPT: &str = r#"
+(() => {
+  const root = document.documentElement.cloneNode(true);
+  for (const element of root.querySelectorAll("[src], [href]")) {
+    for (const name of ["src", "href"]) {
+      const value = element.getAttribute(name);
+      if (value === null) continue;
+      try {
+        element.setAttribute(name, new URL(value, document.baseURI).href);
+      } catch {}
+    }
+  }
+  const doctype = document.doctype
+    ? new XMLSerializer().serializeToString(document.doctype) + "\n"
+    : "";
+  console.log("$PREFIX" + JSON.stringify(doctype + root.outerHTML));
+})();
+"#;
+
+/// Reports the page's URL and everything it loaded since, as the browser
+/// timed it.
+const RESOURCES_SCRIPT: &str = r#"
+(() => {
+  const urls = performance.getEntriesByType("resource").map((entry) => entry.name);
+  console.log("$PREFIX" + JSON.stringify([location.href, ...urls]));
+})();
+"#;
+
+/// What "Save page as" writes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    /// The page's HTML on its own.
+    HtmlOnly,
+    /// The HTML, with what it loads saved next to it in a `_files` folder.
+    Complete,
+    /// Every response the page loads, in a gzipped WARC file.
+    Warc,
+}
+
+impl SaveFormat {
+    const ALL: [SaveFormat; 3] = [SaveFormat::HtmlOnly, SaveFormat::Complete, SaveFormat::Warc];
+
+    fn label(&self) -> SharedString {
+        match self {
+            SaveFormat::HtmlOnly => t!("archive.html_only"),
+            SaveFormat::Complete => t!("archive.complete"),
+            SaveFormat::Warc => t!("archive.warc"),
+        }
+    }
+
+    fn description(&self) -> SharedString {
+        match self {
+            SaveFormat::HtmlOnly => t!("archive.html_only_description"),
+            SaveFormat::Complete => t!("archive.complete_description"),
+            SaveFormat::Warc => t!("archive.warc_description"),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::HtmlOnly | SaveFormat::Complete => "html",
+            SaveFormat::Warc => "warc.gz",
+        }
+    }
+}
+
+/// A response kept for a saved page, as it'll go in a WARC `response`
+/// record.
+pub struct WarcRecord {
+    target_uri: String,
+    date: DateTime<Utc>,
+    /// The response's status line and headers, as they'd be sent over HTTP
+    /// for `body`.
+    http_headers: String,
+    body: Vec<u8>,
+}
+
+impl WarcRecord {
+    fn new(url: String, response: &Response) -> Self {
+        let status = response.get_status().unwrap_or_default();
+        let status_text = response.get_status_text().unwrap_or_default();
+        let mut http_headers = format!("HTTP/1.1 {} {}\r\n", status, status_text);
+
+        // CEF hands over bodies already decoded, which the encoding headers
+        // wouldn't describe.
+        let mut headers = response
+            .get_header_map()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| {
+                !["content-encoding", "content-length", "transfer-encoding"]
+                    .contains(&name.to_ascii_lowercase().as_str())
+            })
+            .collect::<Vec<_>>();
+        headers.sort();
+        for (name, values) in headers {
+            for value in values {
+                http_headers.push_str(&format!("{}: {}\r\n", name, value));
+            }
+        }
+
+        Self {
+            target_uri: url,
+            date: Utc::now(),
+            http_headers,
+            body: Vec::new(),
+        }
+    }
+
+    /// Write the record in WARC 1.1 format.
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let block_headers = format!(
+            "{}Content-Length: {}\r\n\r\n",
+            self.http_headers,
+            self.body.len()
+        );
+        let length = block_headers.len() + self.body.len();
+
+        write!(
+            out,
+            "WARC/1.1\r\n\
+             WARC-Type: response\r\n\
+             WARC-Record-ID: {}\r\n\
+             WARC-Date: {}\r\n\
+             WARC-Target-URI: {}\r\n\
+             Content-Type: application/http;msgtype=response\r\n\
+             Content-Length: {}\r\n\r\n",
+            record_id(&[
+                self.target_uri.as_bytes(),
+                self.date.to_rfc3339().as_bytes()
+            ]),
+            warc_date(&self.date),
+            self.target_uri,
+            length
+        )?;
+        out.write_all(block_headers.as_bytes())?;
+        out.write_all(&self.body)?;
+        out.write_all(b"\r\n\r\n")
+    }
+}
+
+/// Write the `warcinfo` record that starts a WARC file.
+fn write_warcinfo(out: &mut impl Write, file_name: &str) -> io::Result<()> {
+    let date = Utc::now();
+    let fields = format!(
+        "software: {} {}\r\nformat: WARC File Format 1.1\r\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    write!(
+        out,
+        "WARC/1.1\r\n\
+         WARC-Type: warcinfo\r\n\
+         WARC-Record-ID: {}\r\n\
+         WARC-Date: {}\r\n\
+         WARC-Filename: {}\r\n\
+         Content-Type: application/warc-fields\r\n\
+         Content-Length: {}\r\n\r\n{}\r\n\r\n",
+        record_id(&[file_name.as_bytes(), date.to_rfc3339().as_bytes()]),
+        warc_date(&date),
+        file_name,
+        fields.len(),
+        fields
+    )
+}
+
+fn warc_date(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A `urn:uuid:` for a record, made from a hash of `parts` in the shape of a
+/// version 4 UUID.
+fn record_id(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut bytes = hasher.finalize();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "<urn:uuid:{}-{}-{}-{}-{}>",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// How far saving a resource of the page has got.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ResourceState {
+    Pending,
+    Saved,
+    /// Too large, or it couldn't be fetched again.
+    Skipped,
+}
+
+/// Where the save in progress has got to.
+#[derive(Clone, PartialEq)]
+pub enum SaveStatus {
+    Loading,
+    Writing,
+    Saved(PathBuf),
+    Failed(String),
+}
+
+struct SaveJob {
+    id: u64,
+    browser_id: i32,
+    format: SaveFormat,
+    destination: PathBuf,
+    /// URLs of what the page loaded, in the order they were requested.
+    resources: Vec<(String, ResourceState)>,
+    /// The saved responses, by their index in `resources`.
+    records: Vec<Option<WarcRecord>>,
+    html: Option<String>,
+    /// Whether the page reported what it loaded, so `resources` is complete.
+    listed: bool,
+    status: SaveStatus,
+}
+
+impl SaveJob {
+    /// Whether everything to be saved has arrived.
+    fn is_ready(&self) -> bool {
+        self.status == SaveStatus::Loading
+            && self.listed
+            && self
+                .resources
+                .iter()
+                .all(|(_, state)| *state != ResourceState::Pending)
+            && (self.format == SaveFormat::Warc || self.html.is_some())
+    }
+}
+
+#[derive(Default)]
+struct PageArchiverInner {
+    job: Option<SaveJob>,
+    next_job: u64,
+    changed: bool,
+}
+
+/// Saves the current page to disk. For complete pages and WARC archives the
+/// page lists what it loaded, and each of those is fetched again in the
+/// page's request context, mostly from the cache. The page itself is left
+/// alone, so its form and scroll state survive and nothing is resubmitted.
+#[derive(Clone, Default)]
+pub struct PageArchiver(Arc<Mutex<PageArchiverInner>>);
+
+impl Global for PageArchiver {}
+
+impl PageArchiver {
+    /// Start saving `browser`'s page to `destination` as `format`, replacing
+    /// any save in progress.
+    fn start(&self, browser: &Browser, format: SaveFormat, destination: PathBuf) {
+        let Ok(browser_id) = browser.get_identifier() else {
+            return;
+        };
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            let id = inner.next_job;
+            inner.next_job += 1;
+            inner.job = Some(SaveJob {
+                id,
+                browser_id,
+                format,
+                destination,
+                resources: Vec::new(),
+                records: Vec::new(),
+                html: None,
+                listed: format == SaveFormat::HtmlOnly,
+                status: SaveStatus::Loading,
+            });
+            inner.changed = true;
+        }
+
+        let result = browser.get_main_frame().and_then(|frame| {
+            let Some(frame) = frame else {
+                return Ok(());
+            };
+            if format != SaveFormat::Warc {
+                serialize(&frame)?;
+            }
+            if format != SaveFormat::HtmlOnly {
+                list_resources(&frame)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.fail(e.to_string());
+        }
+    }
+
+    fn cancel(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.job = None;
+        inner.changed = true;
+    }
+
+    fn fail(&self, error: String) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(job) = inner.job.as_mut() {
+            job.status = SaveStatus::Failed(error);
+            inner.changed = true;
+        }
+    }
+
+    /// Fetch each of the `urls` the page being saved in `browser` loaded,
+    /// keeping the responses.
+    fn fetch_resources(&self, browser: &Browser, urls: Vec<String>) {
+        let (job_id, fetches) = {
+            let mut inner = self.0.lock().unwrap();
+            let Some(job) = inner.job.as_mut() else {
+                return;
+            };
+            if Some(job.browser_id) != browser.get_identifier().ok() || job.listed {
+                return;
+            }
+
+            let mut fetches = Vec::new();
+            for url in resources_to_save(job.format, urls) {
+                fetches.push((job.resources.len(), url.clone()));
+                job.resources.push((url, ResourceState::Pending));
+                job.records.push(None);
+            }
+            job.listed = true;
+            inner.changed = true;
+            (job.id, fetches)
+        };
+
+        let context = browser
+            .get_host()
+            .and_then(|host| host.get_request_context())
+            .ok();
+        for (index, url) in fetches {
+            let request = Request::new();
+            let result = request
+                .set_url(&url)
+                .and_then(|_| request.set_method("GET"))
+                .and_then(|_| request.set_flags(UrlRequestFlags::AllowStoredCredentials));
+            if let Err(e) = result {
+                eprintln!("Error fetching {} to save: {}", url, e);
+                self.resource_done(job_id, index, None);
+                continue;
+            }
+
+            let client = UrlRequestClient::new(ArchivingClient {
+                archiver: self.clone(),
+                job: job_id,
+                index,
+                url,
+                body: Some(Vec::new()),
+            });
+            UrlRequest::new(request, client, context.clone());
+        }
+        self.write_if_ready();
+    }
+
+    /// Pick up the page's HTML from the serialize script, or what it loaded
+    /// from the resources script. Returns true if `message` was either, so
+    /// it can be kept out of the console.
+    pub fn on_console_message(&self, browser: &Browser, message: &str) -> bool {
+        if let Some(json) = message.strip_prefix(RESOURCES_PREFIX) {
+            match serde_json::from_str::<Vec<String>>(json) {
+                Ok(urls) => self.fetch_resources(browser, urls),
+                Err(e) => self.fail(e.to_string()),
+            }
+            return true;
+        }
+        let Some(json) = message.strip_prefix(HTML_PREFIX) else {
+            return false;
+        };
+
+        let html = match serde_json::from_str::<String>(json) {
+            Ok(html) => html,
+            Err(e) => {
+                self.fail(e.to_string());
+                return true;
+            }
+        };
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            let Some(job) = inner.job.as_mut() else {
+                return true;
+            };
+            if Some(job.browser_id) != browser.get_identifier().ok() {
+                return true;
+            }
+
+            job.html = Some(html);
+        }
+        self.write_if_ready();
+        true
+    }
+
+    /// A resource was fetched, or given up on if `record` is `None`.
+    fn resource_done(&self, job_id: u64, index: usize, record: Option<WarcRecord>) {
+        {
+            let mut inner = self.0.lock().unwrap();
+            let Some(job) = inner.job.as_mut().filter(|job| job.id == job_id) else {
+                return;
+            };
+
+            job.resources[index].1 = match record {
+                Some(_) => ResourceState::Saved,
+                None => ResourceState::Skipped,
+            };
+            job.records[index] = record;
+            inner.changed = true;
+        }
+        self.write_if_ready();
+    }
+
+    /// Write the page out on a background thread once all of it has arrived.
+    fn write_if_ready(&self) {
+        let mut guard = self.0.lock().unwrap();
+        let inner = &mut *guard;
+        let Some(job) = inner.job.as_mut().filter(|job| job.is_ready()) else {
+            return;
+        };
+
+        job.status = SaveStatus::Writing;
+        inner.changed = true;
+
+        let job_id = job.id;
+        let format = job.format;
+        let destination = job.destination.clone();
+        let records = job.records.drain(..).flatten().collect::<Vec<_>>();
+        let html = job.html.take().unwrap_or_default();
+
+        let archiver = self.clone();
+        std::thread::spawn(move || {
+            let result = match format {
+                SaveFormat::HtmlOnly => fs::write(&destination, html).map_err(Into::into),
+                SaveFormat::Complete => write_complete(&destination, html, &records),
+                SaveFormat::Warc => write_warc(&destination, &records),
+            };
+
+            let mut inner = archiver.0.lock().unwrap();
+            let Some(job) = inner.job.as_mut().filter(|job| job.id == job_id) else {
+                return;
+            };
+
+            job.status = match result {
+                Ok(()) => SaveStatus::Saved(destination),
+                Err(e) => {
+                    eprintln!("Error saving page: {}", e);
+                    SaveStatus::Failed(e.to_string())
+                }
+            };
+            inner.changed = true;
+        });
+    }
+
+    /// The save in progress: what the page loaded so far, and its status.
+    fn progress(&self) -> Option<(Vec<(String, ResourceState)>, SaveStatus)> {
+        let inner = self.0.lock().unwrap();
+        let job = inner.job.as_ref()?;
+
+        Some((job.resources.clone(), job.status.clone()))
+    }
+
+    /// Whether the save in progress moved on since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+/// Ask `frame` to report its HTML.
+fn serialize(frame: &Frame) -> Result<()> {
+    let script = SERIALIZE_SCRIPT.replace("$PREFIX", HTML_PREFIX);
+    frame.execute_java_script(&script, "", 0)
+}
+
+/// Which of `urls`, as the resources script reported them, are saved as
+/// `format`: each web URL once, leaving out the page itself for a complete
+/// page, whose HTML is taken from the DOM instead.
+fn resources_to_save(format: SaveFormat, urls: Vec<String>) -> Vec<String> {
+    let skip = match format {
+        SaveFormat::Complete => 1,
+        SaveFormat::HtmlOnly | SaveFormat::Warc => 0,
+    };
+
+    let mut saved = Vec::<String>::new();
+    for url in urls.into_iter().skip(skip) {
+        if (url.starts_with("http://") || url.starts_with("https://")) && !saved.contains(&url) {
+            saved.push(url);
+        }
+    }
+    saved
+}
+
+/// Ask `frame` to report what it loaded.
+fn list_resources(frame: &Frame) -> Result<()> {
+    let script = RESOURCES_SCRIPT.replace("$PREFIX", RESOURCES_PREFIX);
+    frame.execute_java_script(&script, "", 0)
+}
+
+/// Write `records` to `destination` as a WARC file, each record gzipped on
+/// its own so readers can seek to any of them.
+fn write_warc(destination: &Path, records: &[WarcRecord]) -> Result<()> {
+    let mut file = BufWriter::new(File::create(destination)?);
+    let file_name = destination
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut encoder = GzEncoder::new(&mut file, Compression::default());
+    write_warcinfo(&mut encoder, &file_name)?;
+    encoder.finish()?;
+
+    for record in records {
+        let mut encoder = GzEncoder::new(&mut file, Compression::default());
+        record.write(&mut encoder)?;
+        encoder.finish()?;
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+/// Write `html` to `destination` and `records` to a `_files` folder next to
+/// it, pointing the HTML's links to them at the saved copies.
+fn write_complete(destination: &Path, mut html: String, records: &[WarcRecord]) -> Result<()> {
+    let stem = destination
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let folder = format!("{}_files", file_name(&stem));
+    let dir = destination.with_file_name(&folder);
+    fs::create_dir_all(&dir)?;
+
+    let mut saved = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let name = asset_name(index, &record.target_uri);
+        fs::write(dir.join(&name), &record.body)?;
+        saved.push((record.target_uri.as_str(), format!("{}/{}", folder, name)));
+    }
+
+    // Longest first, so a URL isn't replaced inside a longer one that
+    // starts with it.
+    saved.sort_by_key(|(url, _)| std::cmp::Reverse(url.len()));
+    for (url, local) in saved {
+        html = html
+            .replace(url, &local)
+            .replace(&url.replace('&', "&amp;"), &local);
+    }
+
+    fs::write(destination, html)?;
+    Ok(())
+}
+
+/// `name` with anything that doesn't belong in a file name replaced.
+fn file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .take(64)
+        .collect::<String>();
+
+    match name.trim_matches('.').is_empty() {
+        true => String::from("page"),
+        false => name,
+    }
+}
+
+/// Name of the file the resource at `url` is saved as in a complete page's
+/// folder. The index keeps files with the same name apart.
+fn asset_name(index: usize, url: &str) -> String {
+    let last = url::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back().map(str::to_string))
+        })
+        .filter(|last| !last.is_empty())
+        .unwrap_or_else(|| String::from("resource"));
+
+    format!("{}-{}", index, file_name(&last))
+}
+
+/// Fetches a resource of the page being saved, keeping the response once
+/// it has all arrived.
+struct ArchivingClient {
+    archiver: PageArchiver,
+    job: u64,
+    index: usize,
+    url: String,
+    /// `None` once handed over, or if the response got too large.
+    body: Option<Vec<u8>>,
+}
+
+impl UrlRequestClientCallbacks for ArchivingClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let Some(body) = self.body.take() else {
+            return;
+        };
+
+        let record = match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) => {
+                request
+                    .get_response()
+                    .ok()
+                    .flatten()
+                    .map(|response| WarcRecord {
+                        body,
+                        ..WarcRecord::new(std::mem::take(&mut self.url), &response)
+                    })
+            }
+            _ => None,
+        };
+        self.archiver.resource_done(self.job, self.index, record);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, request: UrlRequest, data: &[u8]) {
+        let Some(body) = self.body.as_mut() else {
+            return;
+        };
+
+        body.extend_from_slice(data);
+        if body.len() > MAX_RESOURCE_BYTES {
+            self.body = None;
+            self.archiver.resource_done(self.job, self.index, None);
+            request.cancel().ok();
+        }
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+impl Drop for ArchivingClient {
+    /// A request dropped before it finished is left out of the page.
+    fn drop(&mut self) {
+        if self.body.take().is_some() {
+            self.archiver.resource_done(self.job, self.index, None);
+        }
+    }
+}
+
+/// Sheet for saving the current page: pick a format, then watch each
+/// resource being saved.
+pub struct SavePageSheet {
+    /// Whether a format was picked and a destination chosen.
+    started: bool,
+}
+
+impl SavePageSheet {
+    pub fn new() -> Self {
+        Self { started: false }
+    }
+
+    /// Ask where to save the page as `format`, then start saving it there.
+    fn choose(&mut self, format: SaveFormat, cx: &mut Context<Self>) {
+        let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+            return;
+        };
+        let title = cx
+            .global::<BrowserState>()
+            .current_page()
+            .map(|(_, title)| title)
+            .unwrap_or_default();
+
+        let suggested_name = format!("{}.{}", file_name(&title), format.extension());
+        let directory = cx.global::<Settings>().download_path.clone();
+        let destination = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(destination))) = destination.await else {
+                return;
+            };
+
+            this.update(cx, |this, cx| {
+                cx.global::<PageArchiver>()
+                    .start(&browser, format, destination);
+                this.started = true;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Drop the save, finished or not, and close the sheet.
+    fn close(&mut self, cx: &mut Context<Self>) {
+        cx.global::<PageArchiver>().cancel();
+        cx.emit(DismissEvent);
+    }
+
+    fn render_formats(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(div().text_sm().child(t!("archive.title")))
+            .children(
+                SaveFormat::ALL
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, format)| {
+                        div()
+                            .id(("save-page-format", index))
+                            .flex()
+                            .flex_col()
+                            .px_2()
+                            .py_1p5()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(theme.border)
+                            .cursor_pointer()
+                            .hover(|this| this.bg(theme.element_hover))
+                            .child(format.label())
+                            .child(
+                                div()
+                                    .text_color(theme.text_muted)
+                                    .child(format.description()),
+                            )
+                            .on_click(cx.listener(move |this, _, _, cx| this.choose(format, cx)))
+                    }),
+            )
+            .child(
+                div().flex().justify_end().child(
+                    button("save-page-cancel", t!("archive.cancel"), false, cx)
+                        .on_click(cx.listener(|this, _, _, cx| this.close(cx))),
+                ),
+            )
+    }
+
+    fn render_progress(
+        &mut self,
+        resources: Vec<(String, ResourceState)>,
+        status: SaveStatus,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+        let saved = resources
+            .iter()
+            .filter(|(_, state)| *state != ResourceState::Pending)
+            .count();
+
+        let (message, color) = match &status {
+            SaveStatus::Loading => (
+                t!("archive.loading", saved = saved, total = resources.len()),
+                theme.text_secondary,
+            ),
+            SaveStatus::Writing => (t!("archive.writing"), theme.text_secondary),
+            SaveStatus::Saved(path) => (
+                t!("archive.saved", path = path.display()),
+                theme.text_secondary,
+            ),
+            SaveStatus::Failed(error) => (t!("archive.failed", error = error), theme.error),
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(div().text_sm().child(t!("archive.title")))
+            .child(div().text_color(color).child(message))
+            .when(!resources.is_empty(), |this| {
+                this.child(
+                    div()
+                        .id("save-page-resources")
+                        .flex()
+                        .flex_col()
+                        .max_h(px(200.))
+                        .overflow_y_scroll()
+                        .children(resources.into_iter().map(|(url, state)| {
+                            let (mark, color) = match state {
+                                ResourceState::Pending => ("…", theme.text_muted),
+                                ResourceState::Saved => ("✓", theme.success),
+                                ResourceState::Skipped => ("–", theme.text_placeholder),
+                            };
+
+                            div()
+                                .flex()
+                                .gap_2()
+                                .child(div().w(px(12.)).text_color(color).child(mark))
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .overflow_hidden()
+                                        .text_ellipsis()
+                                        .whitespace_nowrap()
+                                        .text_color(theme.text_muted)
+                                        .child(url),
+                                )
+                        })),
+                )
+            })
+            .child(div().flex().justify_end().gap_2().map(|this| {
+                match status {
+                    SaveStatus::Loading | SaveStatus::Writing => this.child(
+                        button("save-page-cancel", t!("archive.cancel"), false, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.close(cx))),
+                    ),
+                    SaveStatus::Saved(path) => this
+                        .child(
+                            button("save-page-reveal", t!("archive.reveal"), false, cx)
+                                .on_click(move |_, _, cx| cx.reveal_path(&path)),
+                        )
+                        .child(
+                            button("save-page-done", t!("archive.done"), true, cx)
+                                .on_click(cx.listener(|this, _, _, cx| this.close(cx))),
+                        ),
+                    SaveStatus::Failed(_) => this.child(
+                        button("save-page-done", t!("archive.done"), true, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.close(cx))),
+                    ),
+                }
+            }))
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavePageSheet {}
+
+impl Render for SavePageSheet {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let progress = self
+            .started
+            .then(|| cx.global::<PageArchiver>().progress())
+            .flatten();
+
+        let content = match progress {
+            Some((resources, status)) => self
+                .render_progress(resources, status, cx)
+                .into_any_element(),
+            None => self.render_formats(cx).into_any_element(),
+        };
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(content),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use chrono::TimeZone;
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    fn record(url: &str, body: &[u8]) -> WarcRecord {
+        WarcRecord {
+            target_uri: url.to_string(),
+            date: Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap(),
+            http_headers: "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n".to_string(),
+            body: body.to_vec(),
+        }
+    }
+
+    /// The named header of a WARC record or HTTP message.
+    fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+        headers
+            .split("\r\n")
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+    }
+
+    #[test]
+    fn record_id_looks_like_a_v4_uuid() {
+        let id = record_id(&[b"https://example.com/", b"2024-03-01"]);
+        let uuid = id
+            .strip_prefix("<urn:uuid:")
+            .and_then(|id| id.strip_suffix('>'))
+            .unwrap();
+
+        let groups = uuid.split('-').map(str::len).collect::<Vec<_>>();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&uuid[14..15], "4");
+        assert!("89ab".contains(&uuid[19..20]));
+
+        assert_eq!(id, record_id(&[b"https://example.com/", b"2024-03-01"]));
+        assert_ne!(id, record_id(&[b"https://example.com/a", b"2024-03-01"]));
+    }
+
+    #[test]
+    fn response_record_counts_its_block() {
+        let mut out = Vec::new();
+        record("https://example.com/", b"<p>hi</p>")
+            .write(&mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let (warc_headers, rest) = out.split_once("\r\n\r\n").unwrap();
+        assert!(warc_headers.starts_with("WARC/1.1\r\n"));
+        assert_eq!(header(warc_headers, "WARC-Type"), Some("response"));
+        assert_eq!(
+            header(warc_headers, "WARC-Target-URI"),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            header(warc_headers, "WARC-Date"),
+            Some("2024-03-01T12:30:00Z")
+        );
+        assert_eq!(
+            header(warc_headers, "Content-Type"),
+            Some("application/http;msgtype=response")
+        );
+
+        // The block is followed by two CRLFs that aren't part of it.
+        let length = header(warc_headers, "Content-Length")
+            .unwrap()
+            .parse::<usize>()
+            .unwrap();
+        assert_eq!(&rest[length..], "\r\n\r\n");
+
+        let (http_headers, body) = rest[..length].split_once("\r\n\r\n").unwrap();
+        assert!(http_headers.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert_eq!(header(http_headers, "Content-Length"), Some("9"));
+        assert_eq!(body, "<p>hi</p>");
+    }
+
+    #[test]
+    fn warc_file_has_warcinfo_then_the_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("page.warc.gz");
+        let records = [
+            record("https://example.com/", b"<img src=a.png>"),
+            record("https://example.com/a.png", &[0x89, b'P', b'N', b'G']),
+        ];
+        write_warc(&destination, &records).unwrap();
+
+        let mut contents = Vec::new();
+        MultiGzDecoder::new(File::open(&destination).unwrap())
+            .read_to_end(&mut contents)
+            .unwrap();
+        let contents = String::from_utf8_lossy(&contents);
+
+        let types = contents
+            .split("WARC/1.1\r\n")
+            .filter_map(|record| header(record, "WARC-Type"))
+            .collect::<Vec<_>>();
+        assert_eq!(types, ["warcinfo", "response", "response"]);
+
+        let file_name = destination.file_name().unwrap().to_str().unwrap();
+        assert!(contents.contains(&format!("WARC-Filename: {}\r\n", file_name)));
+        assert!(contents.contains("WARC-Target-URI: https://example.com/a.png\r\n"));
+    }
+
+    #[test]
+    fn resources_are_saved_once_and_only_from_the_web() {
+        let urls = || {
+            [
+                "https://example.com/",
+                "https://example.com/style.css",
+                "data:image/png;base64,AAAA",
+                "https://example.com/style.css",
+                "http://cdn.example.com/app.js",
+                "blob:https://example.com/1234",
+            ]
+            .map(String::from)
+            .to_vec()
+        };
+
+        assert_eq!(
+            resources_to_save(SaveFormat::Warc, urls()),
+            [
+                "https://example.com/",
+                "https://example.com/style.css",
+                "http://cdn.example.com/app.js"
+            ]
+        );
+        assert_eq!(
+            resources_to_save(SaveFormat::Complete, urls()),
+            [
+                "https://example.com/style.css",
+                "http://cdn.example.com/app.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn asset_names_are_safe_and_distinct() {
+        assert_eq!(
+            asset_name(0, "https://example.com/img/logo.png?v=2"),
+            "0-logo.png"
+        );
+        assert_eq!(asset_name(1, "https://example.com/"), "1-resource");
+        assert_eq!(
+            asset_name(2, "https://example.com/a%20b:c.css"),
+            "2-a_20b_c.css"
+        );
+        assert_eq!(file_name(".."), "page");
+    }
+}
@@ -0,0 +1,469 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    f32::consts::PI,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::{AudioHandlerCallbacks, AudioParameters, Browser};
+use gpui::{
+    canvas, div, fill, point, prelude::*, px, size, svg, App as GpuiApp, Bounds, Context, Global,
+    Window,
+};
+use once_cell::sync::Lazy;
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::{theme::ActiveTheme, BrowserState};
+
+/// Samples transformed for each frame of the visualizer.
+const FFT_SIZE: usize = 1024;
+
+/// Frequency bars drawn by the visualizer.
+const BAR_COUNT: usize = 16;
+
+/// How often the visualizer redraws while audio is streaming.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Quietest level drawn, in dB below full scale.
+const FLOOR_DB: f32 = -70.;
+
+/// The transform run on each frame, planned once.
+static FFT: Lazy<Arc<dyn Fft<f32>>> = Lazy::new(|| FftPlanner::new().plan_fft_forward(FFT_SIZE));
+
+/// The audio one browser is playing.
+#[derive(Default)]
+struct AudioStream {
+    /// The most recent samples, downmixed to mono.
+    samples: VecDeque<f32>,
+    sample_rate: i32,
+}
+
+#[derive(Default)]
+struct AudioCaptureInner {
+    /// The streams of the browsers playing audio, by browser identifier.
+    streams: HashMap<i32, AudioStream>,
+    changed: bool,
+}
+
+/// Audio the open pages are playing, captured for the visualizer.
+#[derive(Clone, Default)]
+pub struct AudioCapture(Arc<Mutex<AudioCaptureInner>>);
+
+impl Global for AudioCapture {}
+
+impl AudioCapture {
+    /// Whether a stream started or stopped since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Whether any browser is playing audio.
+    fn any_streaming(&self) -> bool {
+        !self.0.lock().unwrap().streams.is_empty()
+    }
+
+    fn is_streaming(&self, browser_id: i32) -> bool {
+        self.0.lock().unwrap().streams.contains_key(&browser_id)
+    }
+
+    fn start(&self, browser_id: i32, sample_rate: i32) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.streams.insert(
+            browser_id,
+            AudioStream {
+                samples: VecDeque::new(),
+                sample_rate,
+            },
+        );
+        inner.changed = true;
+    }
+
+    /// Append a packet of planar samples, one slice per channel.
+    fn push(&self, browser_id: i32, data: &[&[f32]]) {
+        let Some(frames) = data.first().map(|channel| channel.len()) else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        let Some(stream) = inner.streams.get_mut(&browser_id) else {
+            return;
+        };
+
+        for frame in 0..frames {
+            let sum = data.iter().map(|channel| channel[frame]).sum::<f32>();
+            stream.samples.push_back(sum / data.len() as f32);
+        }
+
+        let excess = stream.samples.len().saturating_sub(FFT_SIZE);
+        stream.samples.drain(..excess);
+    }
+
+    fn stop(&self, browser_id: i32) {
+        let mut inner = self.0.lock().unwrap();
+
+        if inner.streams.remove(&browser_id).is_some() {
+            inner.changed = true;
+        }
+    }
+
+    /// Levels of the latest samples of a browser in `BAR_COUNT` bands spaced
+    /// evenly in pitch, each from 0 to 1.
+    fn levels(&self, browser_id: i32) -> Vec<f32> {
+        let (samples, sample_rate) = {
+            let inner = self.0.lock().unwrap();
+            let Some(stream) = inner
+                .streams
+                .get(&browser_id)
+                .filter(|stream| stream.samples.len() >= FFT_SIZE)
+            else {
+                return vec![0.; BAR_COUNT];
+            };
+
+            (
+                stream.samples.iter().copied().collect::<Vec<_>>(),
+                stream.sample_rate,
+            )
+        };
+
+        let magnitudes = spectrum(&samples);
+        let bin_hz = sample_rate.max(1) as f32 / FFT_SIZE as f32;
+
+        // Bands from 40 Hz to 16 kHz, each a constant ratio wider than the last.
+        let (low, high) = (40_f32, 16_000_f32.min(sample_rate as f32 / 2.));
+        let ratio = (high / low).powf(1. / BAR_COUNT as f32);
+
+        (0..BAR_COUNT)
+            .map(|band| {
+                let last = magnitudes.len() - 1;
+                let from = ((low * ratio.powi(band as i32) / bin_hz) as usize).min(last);
+                let to = ((low * ratio.powi(band as i32 + 1) / bin_hz) as usize).clamp(from, last);
+                let peak = magnitudes[from..=to].iter().copied().fold(0., f32::max);
+                let db = 20. * peak.max(f32::EPSILON).log10();
+
+                ((db - FLOOR_DB) / -FLOOR_DB).clamp(0., 1.)
+            })
+            .collect()
+    }
+}
+
+/// Receives the PCM audio of a browser.
+pub struct AudioCapturer {
+    capture: AudioCapture,
+}
+
+impl AudioCapturer {
+    pub fn new(capture: AudioCapture) -> Self {
+        Self { capture }
+    }
+}
+
+impl AudioHandlerCallbacks for AudioCapturer {
+    fn get_audio_parameters(&mut self, _browser: Browser, _params: &mut AudioParameters) -> bool {
+        true
+    }
+
+    fn on_audio_stream_started(
+        &mut self,
+        browser: Browser,
+        params: &AudioParameters,
+        _channels: i32,
+    ) {
+        if let Ok(id) = browser.get_identifier() {
+            self.capture.start(id, params.sample_rate);
+        }
+    }
+
+    fn on_audio_stream_packet(&mut self, browser: Browser, data: &[&[f32]], _pts: i64) {
+        if let Ok(id) = browser.get_identifier() {
+            self.capture.push(id, data);
+        }
+    }
+
+    fn on_audio_stream_stopped(&mut self, browser: Browser) {
+        if let Ok(id) = browser.get_identifier() {
+            self.capture.stop(id);
+        }
+    }
+
+    fn on_audio_stream_error(&mut self, browser: Browser, message: &str) {
+        eprintln!("Error capturing audio: {}", message);
+
+        if let Ok(id) = browser.get_identifier() {
+            self.capture.stop(id);
+        }
+    }
+}
+
+/// Magnitudes of the first half of the spectrum of `samples`, which must hold
+/// `FFT_SIZE` of them, scaled so a full-scale sine peaks at 1.
+fn spectrum(samples: &[f32]) -> Vec<f32> {
+    let n = samples.len();
+
+    let mut bins = samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let window = 0.5 - 0.5 * (2. * PI * i as f32 / n as f32).cos();
+            Complex::new(sample * window, 0.)
+        })
+        .collect::<Vec<_>>();
+    FFT.process(&mut bins);
+
+    // The Hann window halves the amplitude.
+    bins[..n / 2]
+        .iter()
+        .map(|bin| bin.norm() * 4. / n as f32)
+        .collect()
+}
+
+/// Whether the active tab is muted.
+fn is_muted(cx: &GpuiApp) -> bool {
+    cx.global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_host().ok())
+        .and_then(|host| host.is_audio_muted().ok())
+        .unwrap_or(false)
+}
+
+/// Mute or unmute the active tab.
+fn toggle_mute(cx: &GpuiApp) {
+    let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+        return;
+    };
+
+    let muted = !is_muted(cx);
+    if let Err(e) = browser
+        .get_host()
+        .and_then(|host| host.set_audio_muted(muted))
+    {
+        eprintln!("Error muting audio: {}", e);
+    }
+}
+
+/// Toolbar frequency bars for the audio the page is playing, with a button to
+/// mute it.
+pub struct AudioVisualizer {
+    capture: AudioCapture,
+}
+
+impl AudioVisualizer {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let capture = cx.global::<AudioCapture>().clone();
+
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(FRAME_INTERVAL).await;
+
+            let redrawn = this.update(cx, |this, cx| {
+                if this.capture.any_streaming() {
+                    cx.notify();
+                }
+            });
+
+            if redrawn.is_err() {
+                break;
+            }
+        })
+        .detach();
+
+        Self { capture }
+    }
+}
+
+impl Render for AudioVisualizer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        // Only the active tab's audio is shown.
+        let browser_id = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.get_identifier().ok());
+        let streaming = browser_id.is_some_and(|id| self.capture.is_streaming(id));
+        let muted = is_muted(cx);
+        let levels = match browser_id {
+            Some(id) => self.capture.levels(id),
+            None => vec![0.; BAR_COUNT],
+        };
+        let color = match muted {
+            true => theme.border,
+            false => theme.accent,
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .when(streaming, |this| {
+                this.child(
+                    canvas(
+                        |_, _, _| {},
+                        move |bounds, _, window, _| {
+                            let gap = px(1.);
+                            let width = (bounds.size.width - gap * (BAR_COUNT - 1) as f32)
+                                / BAR_COUNT as f32;
+
+                            for (index, level) in levels.into_iter().enumerate() {
+                                let height = (bounds.size.height * level).max(px(1.));
+                                let origin = point(
+                                    bounds.origin.x + (width + gap) * index as f32,
+                                    bounds.origin.y + bounds.size.height - height,
+                                );
+
+                                window.paint_quad(fill(
+                                    Bounds::new(origin, size(width, height)),
+                                    color,
+                                ));
+                            }
+                        },
+                    )
+                    .w(px(48.))
+                    .h(px(14.)),
+                )
+            })
+            .when(streaming || muted, |this| {
+                this.child(
+                    div()
+                        .id("audio-mute")
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .size(px(14.))
+                        .rounded_md()
+                        .cursor_pointer()
+                        .hover(|this| this.bg(theme.ghost_hover))
+                        .on_click(cx.listener(|_, _, _, cx| {
+                            toggle_mute(cx);
+                            cx.notify();
+                        }))
+                        .child(
+                            svg()
+                                .path(if muted {
+                                    "volume-x.svg"
+                                } else {
+                                    "volume-2.svg"
+                                })
+                                .size(px(14.))
+                                .text_color(theme.text),
+                        ),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `FFT_SIZE` samples of a sine of `amplitude` at `hz`.
+    fn sine(hz: f32, amplitude: f32, sample_rate: f32) -> Vec<f32> {
+        (0..FFT_SIZE)
+            .map(|i| amplitude * (2. * PI * hz * i as f32 / sample_rate).sin())
+            .collect()
+    }
+
+    /// A capture of browser 1 playing `samples`.
+    fn capture(samples: Vec<f32>, sample_rate: i32) -> AudioCapture {
+        let capture = AudioCapture::default();
+        capture.start(1, sample_rate);
+        capture.push(1, &[&samples]);
+        capture
+    }
+
+    fn loudest(levels: &[f32]) -> usize {
+        (0..levels.len())
+            .max_by(|&a, &b| levels[a].total_cmp(&levels[b]))
+            .unwrap()
+    }
+
+    #[test]
+    fn spectrum_has_half_as_many_bins_as_samples() {
+        assert_eq!(spectrum(&[0.; FFT_SIZE]).len(), FFT_SIZE / 2);
+    }
+
+    #[test]
+    fn spectrum_of_silence_is_flat() {
+        assert!(spectrum(&[0.; FFT_SIZE]).iter().all(|&bin| bin == 0.));
+    }
+
+    #[test]
+    fn spectrum_peaks_in_the_bin_of_a_sine() {
+        // A whole number of periods puts all of the sine in bin 32, apart
+        // from what the window spreads to its neighbours.
+        let sample_rate = FFT_SIZE as f32 * 10.;
+        let bins = spectrum(&sine(320., 1., sample_rate));
+
+        let peak = (0..bins.len())
+            .max_by(|&a, &b| bins[a].total_cmp(&bins[b]))
+            .unwrap();
+        assert_eq!(peak, 32);
+        assert!((bins[32] - 1.).abs() < 0.01, "peak is {}", bins[32]);
+        assert!((bins[31] - 0.5).abs() < 0.01 && (bins[33] - 0.5).abs() < 0.01);
+        assert!(bins[..30].iter().chain(&bins[35..]).all(|&bin| bin < 0.01));
+    }
+
+    #[test]
+    fn spectrum_scales_with_amplitude() {
+        let sample_rate = FFT_SIZE as f32 * 10.;
+        let bins = spectrum(&sine(1280., 0.25, sample_rate));
+
+        assert!((bins[128] - 0.25).abs() < 0.01, "peak is {}", bins[128]);
+    }
+
+    #[test]
+    fn levels_light_the_band_of_a_tone() {
+        // 1 kHz falls in the ninth of the bands spaced from 40 Hz to 16 kHz.
+        let levels = capture(sine(1000., 1., 48_000.), 48_000).levels(1);
+
+        assert_eq!(levels.len(), BAR_COUNT);
+        assert_eq!(loudest(&levels), 8);
+        assert!(levels[8] > 0.95);
+        assert!(levels[0] < 0.1 && levels[BAR_COUNT - 1] < 0.1);
+    }
+
+    #[test]
+    fn levels_are_zero_until_enough_samples_arrive() {
+        let levels = capture(vec![1.; FFT_SIZE - 1], 48_000).levels(1);
+        assert_eq!(levels, [0.; BAR_COUNT]);
+
+        let levels = capture(vec![0.; FFT_SIZE], 48_000).levels(1);
+        assert_eq!(levels, [0.; BAR_COUNT]);
+    }
+
+    #[test]
+    fn browsers_playing_at_once_keep_their_own_samples() {
+        let capture = AudioCapture::default();
+        capture.start(1, 48_000);
+        capture.start(2, 48_000);
+
+        // Packets of the two browsers arrive interleaved.
+        let (low, high) = (sine(1000., 1., 48_000.), sine(8000., 1., 48_000.));
+        for (low, high) in low.chunks(128).zip(high.chunks(128)) {
+            capture.push(1, &[low, low]);
+            capture.push(2, &[high, high]);
+        }
+
+        let (first, second) = (capture.levels(1), capture.levels(2));
+        assert_eq!(loudest(&first), 8);
+        assert_eq!(loudest(&second), 14);
+        assert!(first[14] < 0.1 && second[8] < 0.1);
+
+        // One of them stopping leaves the other streaming.
+        capture.stop(2);
+        assert!(capture.take_changed());
+        assert!(capture.is_streaming(1) && !capture.is_streaming(2));
+        assert_eq!(capture.levels(1), first);
+        assert_eq!(capture.levels(2), [0.; BAR_COUNT]);
+    }
+
+    #[test]
+    fn packets_of_a_browser_not_streaming_are_dropped() {
+        let capture = AudioCapture::default();
+        capture.push(1, &[&sine(1000., 1., 48_000.)]);
+
+        assert!(!capture.is_streaming(1));
+        assert_eq!(capture.levels(1), [0.; BAR_COUNT]);
+    }
+}
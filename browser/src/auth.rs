@@ -0,0 +1,246 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::AuthCallback;
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, Entity, EventEmitter, Focusable, Global,
+    Subscription, Window,
+};
+
+use crate::{
+    button::button,
+    locale::t,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+/// If CEF asks for credentials again this soon after we answered from the
+/// cache, the server rejected them and the user has to be asked instead.
+const REJECTED_WITHIN: Duration = Duration::from_secs(2);
+
+/// An authentication challenge waiting for the user.
+pub struct AuthRequest {
+    pub origin: String,
+    pub host: String,
+    pub realm: Option<String>,
+    pub is_proxy: bool,
+    callback: AuthCallback,
+}
+
+struct CachedCredentials {
+    username: String,
+    password: String,
+    last_used: Option<Instant>,
+}
+
+#[derive(Default)]
+struct CredentialStoreInner {
+    credentials: HashMap<String, CachedCredentials>,
+    pending: VecDeque<AuthRequest>,
+}
+
+/// Credentials entered by the user, cached per origin for the lifetime of
+/// the process. Shared between the CEF request handler and the UI.
+#[derive(Clone, Default)]
+pub struct CredentialStore(Arc<Mutex<CredentialStoreInner>>);
+
+impl Global for CredentialStore {}
+
+impl CredentialStore {
+    /// Answer the challenge from the cache if possible, otherwise queue it
+    /// so the UI can prompt the user.
+    pub fn request(
+        &self,
+        origin: &str,
+        host: &str,
+        realm: Option<&str>,
+        is_proxy: bool,
+        callback: AuthCallback,
+    ) {
+        let mut inner = self.0.lock().unwrap();
+
+        if let Some(cached) = inner.credentials.get_mut(origin) {
+            let rejected = cached
+                .last_used
+                .is_some_and(|last_used| last_used.elapsed() < REJECTED_WITHIN);
+
+            if !rejected {
+                cached.last_used = Some(Instant::now());
+
+                if let Err(e) = callback.cont(&cached.username, &cached.password) {
+                    eprintln!("Error continuing authentication: {}", e);
+                }
+
+                return;
+            }
+
+            inner.credentials.remove(origin);
+        }
+
+        inner.pending.push_back(AuthRequest {
+            origin: origin.to_string(),
+            host: host.to_string(),
+            realm: realm.map(str::to_string),
+            is_proxy,
+            callback,
+        });
+    }
+
+    /// The next challenge waiting for the user, if any.
+    pub fn take_pending(&self) -> Option<AuthRequest> {
+        self.0.lock().unwrap().pending.pop_front()
+    }
+
+    fn submit(&self, request: &AuthRequest, username: &str, password: &str) {
+        self.0.lock().unwrap().credentials.insert(
+            request.origin.clone(),
+            CachedCredentials {
+                username: username.to_string(),
+                password: password.to_string(),
+                last_used: Some(Instant::now()),
+            },
+        );
+
+        if let Err(e) = request.callback.cont(username, password) {
+            eprintln!("Error continuing authentication: {}", e);
+        }
+    }
+}
+
+/// Modal dialog asking for the username and password of a challenge.
+pub struct AuthDialog {
+    request: AuthRequest,
+    /// Whether the challenge was continued or cancelled.
+    answered: bool,
+    username: Entity<TextInput>,
+    password: Entity<TextInput>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl AuthDialog {
+    pub fn new(request: AuthRequest, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let username = cx.new(|cx| TextInput::new("", cx).placeholder(t!("auth.username")));
+        let password = cx.new(|cx| {
+            TextInput::new("", cx)
+                .placeholder(t!("auth.password"))
+                .masked(true)
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &username,
+                window,
+                |this, _, event, window, cx| match event {
+                    TextInputEvent::Confirm => window.focus(&this.password.focus_handle(cx)),
+                    TextInputEvent::Cancel => this.cancel(cx),
+                },
+            ),
+            cx.subscribe(&password, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.confirm(cx),
+                TextInputEvent::Cancel => this.cancel(cx),
+            }),
+        ];
+
+        window.focus(&username.focus_handle(cx));
+
+        Self {
+            request,
+            answered: false,
+            username,
+            password,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn confirm(&mut self, cx: &mut Context<Self>) {
+        let username = self.username.read(cx).text().to_string();
+        let password = self.password.read(cx).text().to_string();
+
+        cx.global::<CredentialStore>()
+            .submit(&self.request, &username, &password);
+        self.answered = true;
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if let Err(e) = self.request.callback.cancel() {
+            eprintln!("Error cancelling authentication: {}", e);
+        }
+
+        self.answered = true;
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Drop for AuthDialog {
+    fn drop(&mut self) {
+        // CEF holds the request until the callback is answered, so a dialog
+        // closed some other way, e.g. with its tab, cancels it.
+        if !self.answered {
+            self.request.callback.cancel().ok();
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for AuthDialog {}
+
+impl Render for AuthDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let message = match self.request.is_proxy {
+            true => t!("auth.proxy_requires", host = self.request.host),
+            false => t!("auth.host_requires", host = self.request.host),
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(320.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("auth.sign_in")))
+                    .child(div().text_color(theme.text_secondary).child(message))
+                    .when_some(self.request.realm.clone(), |this, realm| {
+                        this.child(
+                            div()
+                                .text_color(theme.text_muted)
+                                .child(t!("auth.realm", realm = realm)),
+                        )
+                    })
+                    .child(self.username.clone())
+                    .child(self.password.clone())
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("auth-cancel", t!("auth.cancel"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.cancel(cx))),
+                            )
+                            .child(
+                                button("auth-sign-in", t!("auth.sign_in"), true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.confirm(cx))),
+                            ),
+                    ),
+            )
+    }
+}
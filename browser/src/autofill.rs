@@ -0,0 +1,638 @@
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Frame, Request};
+use gpui::{
+    anchored, deferred, div, point, prelude::*, px, App as GpuiApp, Context, DismissEvent,
+    EventEmitter, Global, Pixels, SharedString, Window,
+};
+use serde::Deserialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    passwords::origin,
+    theme::ActiveTheme,
+};
+
+/// Values remembered per field, newest first.
+const MAX_VALUES: usize = 20;
+
+/// Suggestions offered at once.
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Fields never remembered, matched against lowercased field names.
+const SENSITIVE_FIELDS: [&str; 7] = ["pass", "pin", "card", "cvc", "cvv", "token", "csrf"];
+
+/// Asks the browser for suggestions as the user types in a text field, and
+/// defines `__cefAutofillFill(value)` to fill the field a suggestion was
+/// picked for. Relies on `window.cefQuery`.
+pub const AUTOFILL_SCRIPT: &str = r#"(() => {
+  if (window.__cefAutofillFill) return;
+
+  const types = ["text", "email", "tel", "search", "url", ""];
+  let target = null;
+
+  const fillable = (element) =>
+    element instanceof HTMLInputElement &&
+    types.includes(element.getAttribute("type") || "") &&
+    element.autocomplete !== "off" &&
+    (element.name || element.id);
+
+  const query = (element) => {
+    const rect = element.getBoundingClientRect();
+    window.cefQuery({
+      request: "autofill:" + JSON.stringify({
+        name: element.name || element.id,
+        value: element.value,
+        x: rect.left,
+        y: rect.bottom,
+        width: rect.width,
+      }),
+    });
+  };
+
+  const dismiss = () => window.cefQuery({ request: "autofillDismiss" });
+
+  document.addEventListener("focusin", (event) => {
+    if (!fillable(event.target)) return;
+    target = event.target;
+    query(target);
+  }, true);
+
+  document.addEventListener("input", (event) => {
+    if (event.target === target) query(target);
+  }, true);
+
+  document.addEventListener("focusout", (event) => {
+    if (event.target !== target) return;
+    target = null;
+    dismiss();
+  }, true);
+
+  Object.defineProperty(window, "__cefAutofillFill", {
+    value: (value) => {
+      const element = target || document.activeElement;
+      if (!fillable(element)) return;
+
+      element.value = value;
+      element.dispatchEvent(new Event("input", { bubbles: true }));
+      element.dispatchEvent(new Event("change", { bubbles: true }));
+    },
+  });
+})();"#;
+
+/// The fields of a form posted by `request`, in the order they were sent.
+/// Empty unless it's a URL-encoded POST.
+pub fn form_fields(request: &Request) -> Vec<(String, String)> {
+    if !request
+        .get_method()
+        .is_ok_and(|method| method.eq_ignore_ascii_case("POST"))
+    {
+        return Vec::new();
+    }
+
+    let is_form = request
+        .get_header_by_name("Content-Type")
+        .ok()
+        .flatten()
+        .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return Vec::new();
+    }
+
+    let Ok(Some(post_data)) = request.get_post_data() else {
+        return Vec::new();
+    };
+
+    let mut body = Vec::new();
+    for element in post_data.get_elements().unwrap_or_default() {
+        let mut bytes = vec![0; element.get_bytes_count().unwrap_or(0)];
+        if let Ok(count) = element.get_bytes(&mut bytes) {
+            body.extend_from_slice(&bytes[..count]);
+        }
+    }
+
+    url::form_urlencoded::parse(&body)
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect()
+}
+
+/// Saved values keyed by the origin of the page the form was on, then by
+/// field name.
+type FormData = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+struct FormDataStoreInner {
+    path: PathBuf,
+    data: FormData,
+}
+
+/// Values submitted in forms, persisted to a JSON file and offered back as
+/// suggestions on the same origin only.
+#[derive(Clone)]
+pub struct FormDataStore(Arc<Mutex<FormDataStoreInner>>);
+
+impl Global for FormDataStore {}
+
+impl FormDataStore {
+    /// Load form data from disk, starting empty if the file is missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let data = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse form data {:?}: {}", path, e);
+                FormData::new()
+            }),
+            Err(_) => FormData::new(),
+        };
+
+        Self(Arc::new(Mutex::new(FormDataStoreInner { path, data })))
+    }
+
+    /// Remember the fields of a form `frame` posted by `request`.
+    pub fn record_submission(&self, frame: &Frame, request: &Request) {
+        let Some(origin) = frame.get_url().ok().and_then(|url| origin(&url)) else {
+            return;
+        };
+
+        self.remember(&origin, form_fields(request));
+    }
+
+    /// Remember the non-sensitive fields submitted on `origin`.
+    fn remember(&self, origin: &str, fields: Vec<(String, String)>) {
+        let fields = fields
+            .into_iter()
+            .filter(|(name, value)| {
+                let name = name.to_lowercase();
+                !value.trim().is_empty()
+                    && !SENSITIVE_FIELDS
+                        .iter()
+                        .any(|sensitive| name.contains(sensitive))
+            })
+            .collect::<Vec<_>>();
+
+        if fields.is_empty() {
+            return;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+        let saved = inner.data.entry(origin.to_string()).or_default();
+        for (name, value) in fields {
+            let values = saved.entry(name).or_default();
+            values.retain(|saved| *saved != value);
+            values.insert(0, value);
+            values.truncate(MAX_VALUES);
+        }
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save form data {:?}: {}", inner.path, e);
+        }
+    }
+
+    /// Values saved on `origin` for `name` starting with what's typed so far.
+    fn suggestions(&self, origin: &str, name: &str, typed: &str) -> Vec<String> {
+        let typed = typed.to_lowercase();
+
+        self.0
+            .lock()
+            .unwrap()
+            .data
+            .get(origin)
+            .and_then(|fields| fields.get(name))
+            .map(|values| {
+                values
+                    .iter()
+                    .filter(|value| {
+                        let value = value.to_lowercase();
+                        value.starts_with(&typed) && value != typed
+                    })
+                    .take(MAX_SUGGESTIONS)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every saved field with its origin and values.
+    pub fn entries(&self) -> Vec<(String, String, Vec<String>)> {
+        self.0
+            .lock()
+            .unwrap()
+            .data
+            .iter()
+            .flat_map(|(origin, fields)| {
+                fields
+                    .iter()
+                    .map(|(name, values)| (origin.clone(), name.clone(), values.clone()))
+            })
+            .collect()
+    }
+
+    /// Forget one saved value of a field on `origin`.
+    pub fn remove(&self, origin: &str, name: &str, value: &str) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(fields) = inner.data.get_mut(origin) {
+            if let Some(values) = fields.get_mut(name) {
+                values.retain(|saved| saved != value);
+                if values.is_empty() {
+                    fields.remove(name);
+                }
+            }
+            if fields.is_empty() {
+                inner.data.remove(origin);
+            }
+        }
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save form data {:?}: {}", inner.path, e);
+        }
+    }
+
+    /// Forget all saved form data.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.data.clear();
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save form data {:?}: {}", inner.path, e);
+        }
+    }
+}
+
+impl FormDataStoreInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.data)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// The focused field as the page reports it, in view coordinates.
+#[derive(Deserialize)]
+struct FieldQuery {
+    name: String,
+    value: String,
+    x: f32,
+    y: f32,
+    width: f32,
+}
+
+/// Suggestions for the focused field, waiting to be shown.
+pub struct AutofillRequest {
+    /// Below the field, in view coordinates.
+    position: (f32, f32),
+    width: f32,
+    suggestions: Vec<String>,
+    frame: Frame,
+    /// The origin the suggestions were saved on.
+    origin: String,
+}
+
+#[derive(Default)]
+struct AutofillInner {
+    pending: Option<AutofillRequest>,
+    dismissed: bool,
+}
+
+/// Suggestion lists queued by the message router for the UI.
+#[derive(Clone, Default)]
+pub struct Autofill(Arc<Mutex<AutofillInner>>);
+
+impl Global for Autofill {}
+
+impl Autofill {
+    pub fn take_pending(&self) -> Option<AutofillRequest> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    pub fn take_dismissed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().dismissed)
+    }
+
+    fn dismiss(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.pending = None;
+        inner.dismissed = true;
+    }
+}
+
+/// Queue the suggestions for the field a page focused or typed in for the
+/// dropdown. The page itself is never told what they are; a value only
+/// reaches it once the user picks it.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    let field = match serde_json::from_str::<FieldQuery>(argument) {
+        Ok(field) => field,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid autofill query: {}", e));
+            return;
+        }
+    };
+
+    let frame = query.frame();
+    let autofill = cx.global::<Autofill>();
+    let Some(origin) = frame.get_url().ok().and_then(|url| origin(&url)) else {
+        autofill.dismiss();
+        query.success("");
+        return;
+    };
+
+    let suggestions = cx
+        .global::<FormDataStore>()
+        .suggestions(&origin, &field.name, &field.value);
+
+    if suggestions.is_empty() {
+        autofill.dismiss();
+    } else {
+        let mut inner = autofill.0.lock().unwrap();
+        inner.pending = Some(AutofillRequest {
+            position: (field.x, field.y),
+            width: field.width,
+            suggestions,
+            frame,
+            origin,
+        });
+        inner.dismissed = false;
+    }
+
+    query.success("");
+}
+
+/// The page's field lost focus.
+pub fn on_dismiss(cx: &GpuiApp, query: Query) {
+    cx.global::<Autofill>().dismiss();
+    query.success("");
+}
+
+/// Saved values for the focused field, drawn by GPUI under it.
+pub struct AutofillDropdown {
+    position: gpui::Point<Pixels>,
+    width: Pixels,
+    suggestions: Vec<SharedString>,
+    frame: Frame,
+    origin: String,
+}
+
+impl AutofillDropdown {
+    /// `origin` is where the page is drawn in the window.
+    pub fn new(request: AutofillRequest, origin: gpui::Point<Pixels>) -> Self {
+        let (x, y) = request.position;
+
+        Self {
+            position: origin + point(px(x), px(y)),
+            width: px(request.width),
+            suggestions: request.suggestions.into_iter().map(Into::into).collect(),
+            frame: request.frame,
+            origin: request.origin,
+        }
+    }
+
+    fn fill(&mut self, value: &str, cx: &mut Context<Self>) {
+        // The frame may have navigated to another site since the dropdown
+        // opened.
+        let current = self.frame.get_url().ok().and_then(|url| origin(&url));
+        if current.as_deref() != Some(self.origin.as_str()) {
+            cx.emit(DismissEvent);
+            return;
+        }
+
+        let value = serde_json::to_string(value).unwrap_or_default();
+        let script = format!(
+            "window.__cefAutofillFill && window.__cefAutofillFill({});",
+            value
+        );
+
+        if let Err(e) = self.frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error filling field: {}", e);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for AutofillDropdown {}
+
+impl Render for AutofillDropdown {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let items = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(index, suggestion)| {
+                let value = suggestion.clone();
+
+                div()
+                    .id(index)
+                    .px_3()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.element_selected))
+                    .on_click(cx.listener(move |this, _, _, cx| this.fill(&value, cx)))
+                    .child(suggestion.clone())
+            })
+            .collect::<Vec<_>>();
+
+        deferred(
+            anchored().position(self.position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .mt_1()
+                    .p_1()
+                    .min_w(self.width.max(px(180.)))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                    .children(items),
+            ),
+        )
+    }
+}
+
+/// Saved form data for the settings panel, with a button to forget each value.
+pub fn saved_data(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    let store = cx.global::<FormDataStore>().clone();
+    let entries = store.entries();
+    let empty = entries.is_empty();
+
+    let rows = entries
+        .into_iter()
+        .flat_map(|(origin, name, values)| {
+            values
+                .into_iter()
+                .map(move |value| (origin.clone(), name.clone(), value))
+        })
+        .enumerate()
+        .map(|(index, (origin, name, value))| {
+            let store = store.clone();
+
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(
+                    div()
+                        .flex_none()
+                        .w(px(120.))
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .text_color(theme.text_muted)
+                        .child(origin.clone()),
+                )
+                .child(
+                    div()
+                        .flex_none()
+                        .w(px(80.))
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .text_color(theme.text_muted)
+                        .child(name.clone()),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w(px(0.))
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .child(value.clone()),
+                )
+                .child(
+                    button(
+                        ("form-data-remove", index),
+                        t!("autofill.remove"),
+                        false,
+                        cx,
+                    )
+                    .on_click(move |_, window, _| {
+                        store.remove(&origin, &name, &value);
+                        window.refresh();
+                    }),
+                )
+        })
+        .collect::<Vec<_>>();
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .id("form-data")
+                .flex()
+                .flex_col()
+                .gap_1()
+                .max_h(px(120.))
+                .overflow_y_scroll()
+                .when(empty, |this| {
+                    this.child(
+                        div()
+                            .text_color(theme.text_disabled)
+                            .child(t!("autofill.empty")),
+                    )
+                })
+                .children(rows),
+        )
+        .when(!empty, |this| {
+            this.child(div().flex().child(
+                button("form-data-clear", t!("autofill.clear_all"), false, cx).on_click(
+                    move |_, window, _| {
+                        store.clear();
+                        window.refresh();
+                    },
+                ),
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn suggestions_are_kept_to_the_origin_they_were_saved_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FormDataStore::load(dir.path().join("form_data.json"));
+        store.remember(
+            "https://shop.example",
+            fields(&[("email", "ada@example.com")]),
+        );
+
+        assert_eq!(
+            store.suggestions("https://shop.example", "email", "a"),
+            ["ada@example.com"]
+        );
+        assert!(store
+            .suggestions("https://evil.example", "email", "")
+            .is_empty());
+        assert!(store
+            .suggestions("http://shop.example", "email", "")
+            .is_empty());
+    }
+
+    #[test]
+    fn sensitive_and_empty_fields_are_not_remembered() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FormDataStore::load(dir.path().join("form_data.json"));
+        store.remember(
+            "https://shop.example",
+            fields(&[
+                ("password", "hunter2"),
+                ("card_number", "4111"),
+                ("city", " "),
+            ]),
+        );
+
+        assert!(store.entries().is_empty());
+    }
+
+    #[test]
+    fn saved_values_survive_a_reload_and_can_be_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("form_data.json");
+        let store = FormDataStore::load(path.clone());
+        store.remember("https://a.example", fields(&[("city", "Paris")]));
+        store.remember("https://b.example", fields(&[("city", "Oslo")]));
+
+        let reloaded = FormDataStore::load(path.clone());
+        assert_eq!(
+            reloaded.entries(),
+            [
+                (
+                    String::from("https://a.example"),
+                    String::from("city"),
+                    vec![String::from("Paris")]
+                ),
+                (
+                    String::from("https://b.example"),
+                    String::from("city"),
+                    vec![String::from("Oslo")]
+                ),
+            ]
+        );
+
+        reloaded.remove("https://a.example", "city", "Paris");
+        let entries = FormDataStore::load(path).entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "https://b.example");
+    }
+}
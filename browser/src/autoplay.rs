@@ -0,0 +1,278 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use cef_ui::{Browser, CommandLine, DictionaryValue, Frame, Request, ResourceType};
+use gpui::{
+    div, prelude::*, px, svg, App as GpuiApp, Context, DismissEvent, Div, EventEmitter, Global,
+    Stateful, Window,
+};
+use url::Url;
+
+use crate::{
+    button::button, locale::t, message_router::Query, settings, theme::ActiveTheme, BrowserState,
+};
+
+/// Media with sound only plays once the user has interacted with the page.
+/// Muted media may still autoplay.
+const AUTOPLAY_POLICY: &str = "document-user-activation-required";
+
+/// File extensions of audio, video and streaming manifests.
+const MEDIA_EXTENSIONS: [&str; 13] = [
+    "mp4", "m4v", "webm", "ogg", "ogv", "oga", "mp3", "m4a", "aac", "flac", "wav", "m3u8", "mpd",
+];
+
+/// Reports media the page wasn't allowed to play, and defines
+/// `__autoplayResume()` to play it again. Relies on `window.cefQuery`.
+pub const AUTOPLAY_SCRIPT: &str = r#"(() => {
+  if (window.__autoplayResume) return;
+
+  const blocked = new Set();
+  let reported = false;
+
+  const report = (media) => {
+    blocked.add(media);
+    if (reported) return;
+
+    reported = true;
+    window.cefQuery({ request: "autoplayBlocked" });
+  };
+
+  const play = HTMLMediaElement.prototype.play;
+  HTMLMediaElement.prototype.play = function () {
+    const promise = play.call(this);
+    promise.catch((error) => {
+      if (error && error.name === "NotAllowedError") report(this);
+    });
+    return promise;
+  };
+
+  // Elements with the `autoplay` attribute just stay paused.
+  window.addEventListener("load", () => {
+    if (navigator.userActivation.hasBeenActive) return;
+
+    for (const media of document.querySelectorAll("audio[autoplay], video[autoplay]")) {
+      if (media.paused && !media.muted) report(media);
+    }
+  });
+
+  Object.defineProperty(window, "__autoplayResume", {
+    value: () => {
+      for (const media of blocked) play.call(media).catch(() => {});
+      blocked.clear();
+      reported = false;
+    },
+  });
+})();"#;
+
+/// Block media with sound from playing before the user interacts with the
+/// page. Chromium only reads the switch at startup.
+pub fn configure(command_line: &CommandLine) -> Result<()> {
+    command_line.append_switch_with_value("autoplay-policy", Some(AUTOPLAY_POLICY))
+}
+
+/// Whether `request` fetches audio or video, by its resource type or, for
+/// media fetched by script, its file extension.
+pub fn is_media(request: &Request) -> bool {
+    if request.get_resource_type().ok() == Some(ResourceType::Media) {
+        return true;
+    }
+
+    request
+        .get_url()
+        .ok()
+        .and_then(|url| Url::parse(&url).ok())
+        .and_then(|url| {
+            let (_, extension) = url.path().rsplit_once('.')?;
+            Some(MEDIA_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+        })
+        .unwrap_or(false)
+}
+
+/// The host of `url`, which autoplay is allowed by.
+fn domain(url: &str) -> Option<String> {
+    Url::parse(url).ok()?.host_str().map(str::to_string)
+}
+
+/// Play the media blocked in `frame`'s page as if the user had started it,
+/// which also lets the page play media from then on.
+fn resume(frame: &Frame) {
+    let Ok(host) = frame.get_browser().and_then(|browser| browser.get_host()) else {
+        return;
+    };
+
+    let params = DictionaryValue::new();
+    let set = params
+        .set_string(
+            "expression",
+            "window.__autoplayResume && window.__autoplayResume()",
+        )
+        .and_then(|_| params.set_bool("userGesture", true));
+    if let Err(e) =
+        set.and_then(|_| host.execute_dev_tools_method(0, "Runtime.evaluate", Some(params)))
+    {
+        eprintln!("Error resuming autoplay: {}", e);
+    }
+}
+
+/// Pages that had autoplay blocked, by browser id, until they navigate away.
+#[derive(Default)]
+pub struct BlockedAutoplay {
+    /// URL of the page each browser had media blocked on.
+    pages: HashMap<i32, String>,
+    changed: bool,
+}
+
+impl Global for BlockedAutoplay {}
+
+impl BlockedAutoplay {
+    /// Whether `browser`'s page had autoplay blocked.
+    pub fn is_blocked(&self, browser: &Browser) -> bool {
+        let (Ok(id), Some(url)) = (browser.get_identifier(), main_frame_url(browser)) else {
+            return false;
+        };
+
+        self.pages.get(&id) == Some(&url)
+    }
+
+    /// Whether a page had autoplay blocked since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+fn main_frame_url(browser: &Browser) -> Option<String> {
+    browser.get_main_frame().ok()??.get_url().ok()
+}
+
+/// Media was blocked in the query's page. Play it right away if its domain is
+/// allowed to autoplay, otherwise show the toolbar icon.
+pub fn on_query(cx: &mut GpuiApp, query: Query) {
+    let frame = query.frame();
+    let url = frame.get_url().unwrap_or_default();
+    let allowed = domain(&url).is_some_and(|domain| {
+        cx.global::<settings::Settings>()
+            .autoplay_allowed
+            .contains(&domain)
+    });
+
+    if allowed {
+        resume(&frame);
+    } else if let Ok(id) = frame
+        .get_browser()
+        .and_then(|browser| browser.get_identifier())
+    {
+        let blocked = cx.global_mut::<BlockedAutoplay>();
+        blocked.pages.insert(id, url);
+        blocked.changed = true;
+    }
+
+    query.success("");
+}
+
+/// The toolbar icon shown while the page has autoplay blocked.
+pub fn icon(cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("autoplay-blocked")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(
+            svg()
+                .path("volume-x.svg")
+                .size(px(14.0))
+                .text_color(theme.warning),
+        )
+}
+
+/// Popover below the toolbar offering to always allow autoplay on the page's
+/// domain.
+pub struct AutoplayPopover;
+
+impl AutoplayPopover {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+
+    fn allow(&mut self, cx: &mut Context<Self>) {
+        let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+            return;
+        };
+        let Some(domain) = main_frame_url(&browser).and_then(|url| domain(&url)) else {
+            return;
+        };
+
+        settings::update(cx, |settings| {
+            if !settings.autoplay_allowed.contains(&domain) {
+                settings.autoplay_allowed.push(domain);
+            }
+        });
+
+        if let Ok(Some(frame)) = browser.get_main_frame() {
+            resume(&frame);
+        }
+        if let Ok(id) = browser.get_identifier() {
+            cx.global_mut::<BlockedAutoplay>().pages.remove(&id);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for AutoplayPopover {}
+
+impl Render for AutoplayPopover {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let domain = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(main_frame_url)
+            .and_then(|url| domain(&url))
+            .unwrap_or_default();
+
+        div()
+            .absolute()
+            .top_0()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(320.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(div().text_sm().child(t!("autoplay.blocked")))
+            .child(t!("autoplay.tried_to_play", domain = domain))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        button(
+                            "autoplay-allow",
+                            t!("autoplay.always_allow", domain = domain),
+                            true,
+                            cx,
+                        )
+                        .on_click(cx.listener(|this, _, _, cx| this.allow(cx))),
+                    )
+                    .child(
+                        button("autoplay-dismiss", t!("autoplay.not_now"), false, cx)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::HashMap, ffi::c_void, rc::Rc};
+
+use cef_ui::{
+    AccessibilityHandler, AudioHandler, Browser, BrowserHost, BrowserSettings, Client,
+    ClientCallbacks, ContextMenuHandler, DisplayHandler, DisplayHandlerCallbacks, DragData,
+    DragOperations, FocusHandler, Frame, HorizontalAlignment, JsDialogHandler, KeyboardHandler,
+    LifeSpanHandler, LoadHandler, LogSeverity, PaintElementType, PermissionHandler, Point,
+    PrintHandler, ProcessId, ProcessMessage, Range, Rect, RenderHandler, RenderHandlerCallbacks,
+    RequestHandler, ScreenInfo, Size, TextInputMode, TouchHandleState, WindowInfo,
+};
+use gpui::{App as GpuiApp, Global};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    lifecycle::{CefLifecycleEvents, LifecycleEvent},
+    message_router::{MessageRouter, Query, ERROR_FAILED},
+};
+
+/// The hidden page background tasks run in. It loads `background.js`.
+const PAGE_URL: &str = "app://assets/background.html";
+
+/// Message the page posts once `background.js` has run.
+const READY_MESSAGE: &str = "__ready";
+
+type Handler = Rc<dyn Fn(&Value, &mut GpuiApp)>;
+
+/// A message posted by the background page.
+#[derive(Deserialize)]
+struct BackgroundMessage {
+    name: String,
+    #[serde(default)]
+    payload: Value,
+}
+
+/// A hidden browser running `background.js` for as long as the app is open,
+/// apart from the tabs, and the GPUI handlers for the messages it posts.
+#[derive(Default)]
+pub struct BackgroundTask {
+    browser: Option<Browser>,
+    ready: bool,
+    /// Messages sent before the page was ready for them.
+    queued: Vec<(String, Value)>,
+    handlers: HashMap<String, Vec<Handler>>,
+}
+
+impl Global for BackgroundTask {}
+
+impl BackgroundTask {
+    /// Send `payload` to the `background.onMessage(name, ...)` handlers in
+    /// `background.js`.
+    pub fn send_message(cx: &mut GpuiApp, name: &str, payload: Value) {
+        let task = cx.global_mut::<BackgroundTask>();
+        if !task.ready {
+            task.queued.push((name.to_string(), payload));
+            return;
+        }
+
+        if let Some(browser) = &task.browser {
+            dispatch(browser, name, &payload);
+        }
+    }
+
+    /// Call `handler` with the payload of every `background.postMessage(name,
+    /// payload)` from `background.js`.
+    pub fn on_message(
+        cx: &mut GpuiApp,
+        name: &str,
+        handler: impl Fn(&Value, &mut GpuiApp) + 'static,
+    ) {
+        cx.global_mut::<BackgroundTask>()
+            .handlers
+            .entry(name.to_string())
+            .or_default()
+            .push(Rc::new(handler));
+    }
+}
+
+/// Run the page's handlers for `name`.
+fn dispatch(browser: &Browser, name: &str, payload: &Value) {
+    let script = format!(
+        "window.__backgroundDispatch && window.__backgroundDispatch({}, {});",
+        Value::from(name),
+        payload
+    );
+
+    match browser.get_main_frame() {
+        Ok(Some(frame)) => {
+            if let Err(e) = frame.execute_java_script(&script, PAGE_URL, 0) {
+                eprintln!("Error sending background message: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Error getting background frame: {}", e),
+    }
+}
+
+/// Create the background browser. Call once CEF has been initialized.
+pub fn start(cx: &mut GpuiApp) {
+    let window_info = WindowInfo::new()
+        .window_name(&String::from("background"))
+        .windowless_rendering_enabled(true);
+    let client = Client::new(BackgroundClientCallbacks {
+        router: cx.global::<MessageRouter>().clone(),
+    });
+
+    let browser = BrowserHost::create_browser_sync(
+        &window_info,
+        client,
+        PAGE_URL,
+        &BrowserSettings::new(),
+        None,
+        None,
+    );
+
+    if let Err(e) = browser.get_host().and_then(|host| host.was_hidden(true)) {
+        eprintln!("Error hiding background page: {}", e);
+    }
+
+    cx.global_mut::<BackgroundTask>().browser = Some(browser);
+
+    // `background.js` answers a ping once it can take messages, which is
+    // when the page counts as started.
+    BackgroundTask::on_message(cx, "pong", |_, cx| {
+        cx.global::<CefLifecycleEvents>()
+            .record(LifecycleEvent::BackgroundReady);
+    });
+    BackgroundTask::send_message(cx, "ping", Value::Null);
+}
+
+/// Whether `frame` belongs to the background page.
+fn is_background_frame(cx: &GpuiApp, frame: &Frame) -> bool {
+    let Some(background) = cx.global::<BackgroundTask>().browser.as_ref() else {
+        return false;
+    };
+
+    match (frame.get_browser(), background.get_identifier()) {
+        (Ok(browser), Ok(id)) => browser.get_identifier().is_ok_and(|other| other == id),
+        _ => false,
+    }
+}
+
+/// Hand a message from the background page to its GPUI handlers.
+pub fn on_query(cx: &mut GpuiApp, query: Query, argument: &str) {
+    // Only the background page may post background messages.
+    if !is_background_frame(cx, &query.frame()) {
+        query.failure(ERROR_FAILED, "Not the background page");
+        return;
+    }
+
+    let message = match serde_json::from_str::<BackgroundMessage>(argument) {
+        Ok(message) => message,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid background message: {}", e));
+            return;
+        }
+    };
+
+    query.success("");
+
+    if message.name == READY_MESSAGE {
+        let task = cx.global_mut::<BackgroundTask>();
+        task.ready = true;
+
+        let queued = std::mem::take(&mut task.queued);
+        if let Some(browser) = task.browser.clone() {
+            for (name, payload) in queued {
+                dispatch(&browser, &name, &payload);
+            }
+        }
+
+        return;
+    }
+
+    let handlers = cx
+        .global::<BackgroundTask>()
+        .handlers
+        .get(&message.name)
+        .cloned()
+        .unwrap_or_default();
+
+    for handler in handlers {
+        handler(&message.payload, cx);
+    }
+}
+
+/// Client for the background browser. It only needs its console, which
+/// carries `window.cefQuery`, and a render handler that paints nowhere.
+struct BackgroundClientCallbacks {
+    router: MessageRouter,
+}
+
+impl ClientCallbacks for BackgroundClientCallbacks {
+    fn get_audio_handler(&mut self) -> Option<AudioHandler> {
+        None
+    }
+
+    fn get_context_menu_handler(&mut self) -> Option<ContextMenuHandler> {
+        None
+    }
+
+    fn get_display_handler(&mut self) -> Option<DisplayHandler> {
+        Some(DisplayHandler::new(BackgroundDisplayHandler))
+    }
+
+    fn get_focus_handler(&mut self) -> Option<FocusHandler> {
+        None
+    }
+
+    fn get_jsdialog_handler(&mut self) -> Option<JsDialogHandler> {
+        None
+    }
+
+    fn get_keyboard_handler(&mut self) -> Option<KeyboardHandler> {
+        None
+    }
+
+    fn get_life_span_handler(&mut self) -> Option<LifeSpanHandler> {
+        None
+    }
+
+    fn get_load_handler(&mut self) -> Option<LoadHandler> {
+        None
+    }
+
+    fn get_print_handler(&mut self) -> Option<PrintHandler> {
+        None
+    }
+
+    fn get_render_handler(&mut self) -> Option<RenderHandler> {
+        Some(RenderHandler::new(BackgroundRenderHandler))
+    }
+
+    fn get_permission_handler(&mut self) -> Option<PermissionHandler> {
+        None
+    }
+
+    fn get_request_handler(&mut self) -> Option<RequestHandler> {
+        None
+    }
+
+    fn on_process_message_received(
+        &mut self,
+        _browser: Browser,
+        frame: Frame,
+        _source_process: ProcessId,
+        message: ProcessMessage,
+    ) -> bool {
+        self.router.on_process_message(&frame, &message)
+    }
+}
+
+/// Logs everything the background page prints.
+struct BackgroundDisplayHandler;
+
+impl DisplayHandlerCallbacks for BackgroundDisplayHandler {
+    fn on_address_change(&mut self, _browser: Browser, _frame: Frame, _url: &str) {}
+
+    fn on_title_change(&mut self, _browser: Browser, _title: Option<String>) {}
+
+    fn on_favicon_urlchange(&mut self, _browser: Browser, _icon_urls: Vec<String>) {}
+
+    fn on_fullscreen_mode_change(&mut self, _browser: Browser, _fullscreen: bool) {}
+
+    fn on_tooltip(&mut self, _browser: Browser, _text: Option<String>) -> bool {
+        false
+    }
+
+    fn on_status_message(&mut self, _browser: Browser, _value: Option<String>) {}
+
+    fn on_console_message(
+        &mut self,
+        _browser: Browser,
+        _level: LogSeverity,
+        message: Option<String>,
+        source: Option<String>,
+        line: i32,
+    ) -> bool {
+        eprintln!(
+            "[background] {} ({}:{})",
+            message.unwrap_or_default(),
+            source.unwrap_or_default(),
+            line
+        );
+
+        true
+    }
+
+    fn on_auto_resize(&mut self, _browser: Browser, _new_size: &Size) -> bool {
+        false
+    }
+
+    fn on_loading_progress_change(&mut self, _browser: Browser, _progress: f64) {}
+
+    fn on_media_access_change(
+        &mut self,
+        _browser: Browser,
+        _has_video_access: bool,
+        _has_audio_access: bool,
+    ) {
+    }
+}
+
+/// Windowless browsers need a render handler, so this one reports a one
+/// pixel view and drops what's painted into it.
+struct BackgroundRenderHandler;
+
+impl RenderHandlerCallbacks for BackgroundRenderHandler {
+    fn get_accessibility_handler(&mut self) -> Option<AccessibilityHandler> {
+        None
+    }
+
+    fn get_root_screen_rect(&mut self, browser: Browser) -> Option<Rect> {
+        Some(self.get_view_rect(browser))
+    }
+
+    fn get_view_rect(&mut self, _browser: Browser) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        }
+    }
+
+    fn get_screen_point(&mut self, _browser: Browser, view: &Point) -> Option<Point> {
+        Some(*view)
+    }
+
+    fn get_screen_info(&mut self, _browser: Browser) -> Option<ScreenInfo> {
+        None
+    }
+
+    fn on_popup_show(&mut self, _browser: Browser, _show: bool) {}
+
+    fn on_popup_size(&mut self, _browser: Browser, _rect: &Rect) {}
+
+    fn on_paint(
+        &mut self,
+        _browser: Browser,
+        _element_type: PaintElementType,
+        _dirty_rects: &[Rect],
+        _buffer: &[u8],
+        _width: usize,
+        _height: usize,
+    ) {
+    }
+
+    fn on_accelerated_paint(
+        &mut self,
+        _browser: Browser,
+        _element_type: PaintElementType,
+        _dirty_rects: &[Rect],
+        _shared_handle: *mut c_void,
+    ) {
+    }
+
+    fn get_touch_handle_size(
+        &mut self,
+        _browser: Browser,
+        _orientation: HorizontalAlignment,
+    ) -> Size {
+        Size {
+            width: 0,
+            height: 0,
+        }
+    }
+
+    fn on_touch_handle_state_changed(&mut self, _browser: Browser, _state: &TouchHandleState) {}
+
+    fn start_dragging(
+        &mut self,
+        _browser: Browser,
+        _drag_data: DragData,
+        _allowed_ops: DragOperations,
+        _point: &Point,
+    ) -> bool {
+        false
+    }
+
+    fn update_drag_cursor(&mut self, _browser: Browser, _operation: DragOperations) {}
+
+    fn on_scroll_offset_changed(&mut self, _browser: Browser, _x: f64, _y: f64) {}
+
+    fn on_ime_composition_range_changed(
+        &mut self,
+        _browser: Browser,
+        _selected_range: &Range,
+        _character_bounds: &[Rect],
+    ) {
+    }
+
+    fn on_text_selection_changed(
+        &mut self,
+        _browser: Browser,
+        _selected_text: Option<String>,
+        _selected_range: &Range,
+    ) {
+    }
+
+    fn on_virtual_keyboard_requested(&mut self, _browser: Browser, _input_mode: TextInputMode) {}
+}
@@ -0,0 +1,301 @@
+use std::{
+    collections::VecDeque,
+    fs::{create_dir_all, read_to_string},
+    path::PathBuf,
+    process::exit,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use cef_ui::{Browser, ErrorCode, Frame};
+use gpui::{App as GpuiApp, Global};
+use image::{ImageFormat, RgbaImage};
+use serde::Serialize;
+
+use crate::{navigate, performance, BrowserState, Quit};
+
+/// Loads each of `DEFAULT_URLS` in turn instead of browsing, then quits,
+/// writing a JSON report of how each page did to stdout. For CI, to catch
+/// performance regressions. `--benchmark=urls.txt` loads the URLs listed in
+/// the file instead, one per line.
+const FLAG: &str = "--benchmark";
+
+const DEFAULT_URLS: [&str; 4] = [
+    "https://example.com",
+    "https://en.wikipedia.org/wiki/Web_browser",
+    "https://news.ycombinator.com",
+    "https://github.com",
+];
+
+/// Where each page's screenshot is saved, relative to the working directory.
+const SCREENSHOT_DIR: &str = "benchmark";
+
+/// How long a loaded page is left to paint before it's measured.
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// How long a page gets to load before it's given up on.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The URLs to benchmark, if asked to on the command line.
+pub fn requested() -> Option<Vec<String>> {
+    std::env::args().find_map(|arg| match arg.strip_prefix(FLAG)? {
+        "" => Some(DEFAULT_URLS.iter().map(|url| url.to_string()).collect()),
+        argument => {
+            let path = argument.strip_prefix('=')?;
+            match read_to_string(path) {
+                Ok(list) => Some(
+                    list.lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string)
+                        .collect(),
+                ),
+                Err(e) => {
+                    eprintln!("Error reading benchmark URLs from {}: {}", path, e);
+                    exit(1);
+                }
+            }
+        }
+    })
+}
+
+/// How one page did. Memory and CPU time are the browser process's, not the
+/// page's renderer's.
+#[derive(Serialize)]
+struct PageReport {
+    url: String,
+    /// From navigating to the first paint of the new page.
+    first_paint_ms: Option<f64>,
+    /// From navigating to the main frame's `on_load_end`.
+    load_ms: Option<f64>,
+    http_status: Option<i32>,
+    /// Resident memory once the page settled.
+    memory_kb: Option<u64>,
+    /// CPU time used from navigating until the page settled.
+    cpu_ms: Option<f64>,
+    screenshot: Option<PathBuf>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    pages: Vec<PageReport>,
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+/// The page being benchmarked.
+struct Run {
+    url: String,
+    /// Id of the browser loading it.
+    browser: i32,
+    started: Instant,
+    cpu_start: Option<f64>,
+    /// Set once the new page starts loading, so the old one's paints don't
+    /// count as its first.
+    committed: bool,
+    first_paint: Option<Instant>,
+    loaded: Option<(Instant, i32)>,
+    error: Option<ErrorCode>,
+    /// The latest paint, in BGRA, with its width and height.
+    frame: Option<(Vec<u8>, u32, u32)>,
+}
+
+impl Run {
+    fn is(&self, browser: &Browser) -> bool {
+        browser.get_identifier().ok() == Some(self.browser)
+    }
+
+    fn finish(self, index: usize) -> PageReport {
+        let since_start =
+            |time: Instant| milliseconds(time.saturating_duration_since(self.started));
+        let cpu_ms = self
+            .cpu_start
+            .zip(performance::cpu_time())
+            .map(|(start, end)| (end - start) * 1000.);
+
+        let (screenshot, screenshot_error) = match self.loaded.and(self.frame.as_ref()) {
+            Some(frame) => match save_screenshot(index, frame) {
+                Ok(path) => (Some(path), None),
+                Err(e) => (None, Some(format!("Error saving screenshot: {}", e))),
+            },
+            None => (None, None),
+        };
+        let error = match (self.loaded, self.error) {
+            (_, Some(error)) => Some(format!("{:?}", error)),
+            (None, None) => Some(format!("Timed out after {}s", TIMEOUT.as_secs())),
+            (Some(_), None) => screenshot_error,
+        };
+
+        PageReport {
+            first_paint_ms: self.first_paint.map(since_start),
+            load_ms: self.loaded.map(|(loaded, _)| since_start(loaded)),
+            http_status: self.loaded.map(|(_, status)| status),
+            memory_kb: performance::memory().map(|(rss_kb, _)| rss_kb),
+            cpu_ms,
+            screenshot,
+            error,
+            url: self.url,
+        }
+    }
+}
+
+/// Save a BGRA frame as the `index`th page's screenshot.
+fn save_screenshot(index: usize, frame: &(Vec<u8>, u32, u32)) -> Result<PathBuf> {
+    let (pixels, width, height) = frame;
+    let mut pixels = pixels.clone();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    let image = RgbaImage::from_raw(*width, *height, pixels)
+        .ok_or_else(|| anyhow!("The frame doesn't match its size"))?;
+
+    let dir = PathBuf::from(SCREENSHOT_DIR);
+    create_dir_all(&dir)?;
+    let path = dir.join(format!("{:02}.png", index + 1));
+    image.save_with_format(&path, ImageFormat::Png)?;
+
+    Ok(path)
+}
+
+#[derive(Default)]
+struct BenchmarkInner {
+    /// Set while benchmarking, from the command line.
+    active: bool,
+    /// Pages still to load, in order.
+    queue: VecDeque<String>,
+    running: Option<Run>,
+    reports: Vec<PageReport>,
+}
+
+/// Loads a list of pages one after another in the active tab, timing each
+/// and taking a screenshot once it settles. Only runs with `--benchmark`.
+#[derive(Clone, Default)]
+pub struct Benchmark(Arc<Mutex<BenchmarkInner>>);
+
+impl Global for Benchmark {}
+
+impl Benchmark {
+    pub fn on_load_start(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+        if let Some(run) = self.0.lock().unwrap().running.as_mut() {
+            if run.is(browser) {
+                run.committed = true;
+            }
+        }
+    }
+
+    pub fn on_load_end(&self, browser: &Browser, frame: &Frame, http_status_code: i32) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+        if let Some(run) = self.0.lock().unwrap().running.as_mut() {
+            if run.is(browser) && run.committed {
+                run.loaded.get_or_insert((Instant::now(), http_status_code));
+            }
+        }
+    }
+
+    pub fn on_load_error(&self, browser: &Browser, frame: &Frame, error: ErrorCode) {
+        // Navigating to the page aborts the one before.
+        if !frame.is_main().unwrap_or(false) || error == ErrorCode::Aborted {
+            return;
+        }
+        if let Some(run) = self.0.lock().unwrap().running.as_mut() {
+            if run.is(browser) {
+                run.error = Some(error);
+            }
+        }
+    }
+
+    /// Keep the page's latest frame for its screenshot.
+    pub fn on_paint(&self, browser: &Browser, buffer: &[u8], width: usize, height: usize) {
+        let mut inner = self.0.lock().unwrap();
+        let Some(run) = inner.running.as_mut() else {
+            return;
+        };
+        if !run.committed || !run.is(browser) {
+            return;
+        }
+
+        run.first_paint.get_or_insert_with(Instant::now);
+        run.frame = Some((buffer.to_vec(), width as u32, height as u32));
+    }
+}
+
+/// Start benchmarking `urls`, from the next poll.
+pub fn start(cx: &GpuiApp, urls: Vec<String>) {
+    let mut inner = cx.global::<Benchmark>().0.lock().unwrap();
+    inner.active = true;
+    inner.queue = urls.into();
+}
+
+/// Report on the page being benchmarked once it settled or timed out, then
+/// load the next one. After the last, write the report and quit.
+pub fn poll(cx: &mut GpuiApp) {
+    let benchmark = cx.global::<Benchmark>().clone();
+    let mut inner = benchmark.0.lock().unwrap();
+    if !inner.active {
+        return;
+    }
+
+    if let Some(run) = inner.running.as_ref() {
+        let settled = run
+            .loaded
+            .is_some_and(|(loaded, _)| loaded.elapsed() >= SETTLE_TIME);
+        if !settled && run.started.elapsed() < TIMEOUT {
+            return;
+        }
+
+        let index = inner.reports.len();
+        if let Some(run) = inner.running.take() {
+            inner.reports.push(run.finish(index));
+        }
+    }
+
+    // The first tab may not be open yet.
+    let Some(browser) = cx
+        .global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_identifier().ok())
+    else {
+        return;
+    };
+
+    match inner.queue.pop_front() {
+        Some(url) => {
+            inner.running = Some(Run {
+                url: url.clone(),
+                browser,
+                started: Instant::now(),
+                cpu_start: performance::cpu_time(),
+                committed: false,
+                first_paint: None,
+                loaded: None,
+                error: None,
+                frame: None,
+            });
+            drop(inner);
+            navigate(cx, &url);
+        }
+        None => {
+            inner.active = false;
+            let report = Report {
+                pages: std::mem::take(&mut inner.reports),
+            };
+            drop(inner);
+
+            match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("Error writing benchmark report: {}", e),
+            }
+            cx.dispatch_action(&Quit);
+        }
+    }
+}
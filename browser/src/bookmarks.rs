@@ -0,0 +1,266 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use gpui::{
+    anchored, deferred, div, prelude::*, px, App as GpuiApp, Context, Entity, Focusable, Global,
+    MouseButton, MouseDownEvent, Pixels, Point, SharedString, Subscription, Window,
+};
+
+use crate::{
+    locale::t,
+    navigate,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+}
+
+/// Bookmarks persisted to a JSON file.
+pub struct Bookmarks {
+    path: PathBuf,
+    items: Vec<Bookmark>,
+    pub bar_visible: bool,
+}
+
+impl Global for Bookmarks {}
+
+impl Bookmarks {
+    /// Load bookmarks from disk, starting empty if the file is missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let items = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse bookmarks {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path,
+            items,
+            bar_visible: false,
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.items)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    fn changed(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("Failed to save bookmarks {:?}: {}", self.path, e);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.items.iter()
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.items.iter().any(|bookmark| bookmark.url == url)
+    }
+
+    /// Bookmark the page, or remove the bookmark if it already exists.
+    pub fn toggle(&mut self, url: &str, title: &str) {
+        if self.contains(url) {
+            self.items.retain(|bookmark| bookmark.url != url);
+        } else {
+            self.items.push(Bookmark {
+                url: url.to_string(),
+                title: title.to_string(),
+            });
+        }
+
+        self.changed();
+    }
+
+    pub fn rename(&mut self, index: usize, title: &str) {
+        if let Some(bookmark) = self.items.get_mut(index) {
+            bookmark.title = title.to_string();
+            self.changed();
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.items.remove(index);
+            self.changed();
+        }
+    }
+}
+
+/// The bar below the toolbar showing every bookmark as a pill.
+pub struct BookmarksBar {
+    menu: Option<(usize, Point<Pixels>)>,
+    renaming: Option<(usize, Entity<TextInput>, Subscription)>,
+}
+
+impl BookmarksBar {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<Bookmarks>(|_, cx| cx.notify()).detach();
+
+        Self {
+            menu: None,
+            renaming: None,
+        }
+    }
+
+    fn start_rename(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(title) = cx
+            .global::<Bookmarks>()
+            .items
+            .get(index)
+            .map(|bookmark| bookmark.title.clone())
+        else {
+            return;
+        };
+
+        let input = cx.new(|cx| TextInput::new(title, cx));
+        let subscription = cx.subscribe(&input, move |this, input, event, cx| {
+            if let TextInputEvent::Confirm = event {
+                let title = input.read(cx).text().to_string();
+                cx.update_global::<Bookmarks, _>(|bookmarks, _| bookmarks.rename(index, &title));
+            }
+
+            this.renaming = None;
+            cx.notify();
+        });
+
+        window.focus(&input.focus_handle(cx));
+        self.renaming = Some((index, input, subscription));
+        self.menu = None;
+        cx.notify();
+    }
+
+    fn render_menu(
+        &self,
+        index: usize,
+        position: Point<Pixels>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let item = |id: &'static str, label: SharedString| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_sm()
+                .cursor_pointer()
+                .hover(|this| this.bg(theme.element_selected))
+                .child(label)
+        };
+
+        deferred(
+            anchored().position(position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .p_1()
+                    .min_w(px(120.))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                        this.menu = None;
+                        cx.notify();
+                    }))
+                    .child(
+                        item("bookmark-rename", t!("bookmarks.rename")).on_click(cx.listener(
+                            move |this, _, window, cx| {
+                                this.start_rename(index, window, cx);
+                            },
+                        )),
+                    )
+                    .child(
+                        item("bookmark-delete", t!("bookmarks.delete")).on_click(cx.listener(
+                            move |this, _, _, cx| {
+                                cx.update_global::<Bookmarks, _>(|bookmarks, _| {
+                                    bookmarks.remove(index)
+                                });
+                                this.menu = None;
+                                cx.notify();
+                            },
+                        )),
+                    ),
+            ),
+        )
+    }
+}
+
+impl Render for BookmarksBar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let bookmarks: Vec<Bookmark> = cx.global::<Bookmarks>().iter().cloned().collect();
+        let renaming = self
+            .renaming
+            .as_ref()
+            .map(|(index, input, _)| (*index, input.clone()));
+
+        div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .h_8()
+            .px_2()
+            .overflow_hidden()
+            .text_xs()
+            .text_color(theme.text_secondary)
+            .when(bookmarks.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_color(theme.text_placeholder)
+                        .child(t!("bookmarks.empty")),
+                )
+            })
+            .children(bookmarks.into_iter().enumerate().map(|(index, bookmark)| {
+                if let Some((_, input)) = renaming.as_ref().filter(|(i, _)| *i == index) {
+                    return div().child(input.clone()).into_any_element();
+                }
+
+                let url = bookmark.url.clone();
+
+                div()
+                    .id(("bookmark", index))
+                    .flex_none()
+                    .max_w(px(160.))
+                    .px_2()
+                    .py_0p5()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.element_selected))
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .text_ellipsis()
+                    .child(bookmark.title)
+                    .on_click(move |_, _, cx| navigate(cx, &url))
+                    .on_mouse_down(
+                        MouseButton::Right,
+                        cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                            this.menu = Some((index, event.position));
+                            cx.notify();
+                        }),
+                    )
+                    .into_any_element()
+            }))
+            .when_some(self.menu, |this, (index, position)| {
+                this.child(self.render_menu(index, position, cx))
+            })
+    }
+}
+
+/// Toggle the bookmark for the given page.
+pub fn toggle_bookmark(cx: &mut GpuiApp, url: &str, title: &str) {
+    cx.update_global::<Bookmarks, _>(|bookmarks, _| bookmarks.toggle(url, title));
+}
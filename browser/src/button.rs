@@ -0,0 +1,26 @@
+use gpui::{div, prelude::*, App as GpuiApp, Div, ElementId, SharedString, Stateful};
+
+use crate::theme::ActiveTheme;
+
+/// A text button used by dialogs and banners.
+pub fn button(
+    id: impl Into<ElementId>,
+    label: impl Into<SharedString>,
+    primary: bool,
+    cx: &GpuiApp,
+) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id(id)
+        .px_3()
+        .py_1()
+        .rounded_md()
+        .cursor_pointer()
+        .when(primary, |this| {
+            this.bg(theme.accent).text_color(theme.text_on_accent)
+        })
+        .when(!primary, |this| this.border_1().border_color(theme.border))
+        .hover(|this| this.opacity(0.85))
+        .child(label.into())
+}
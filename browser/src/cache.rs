@@ -0,0 +1,640 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use cef_ui::{
+    Browser, DevToolsMessageObserver, DevToolsMessageObserverCallbacks, DictionaryValue,
+    Registration,
+};
+use gpui::{div, prelude::*, px, relative, App as GpuiApp, Context, Global, Window};
+use url::Url;
+
+use crate::{button::button, locale::t, theme::ActiveTheme, BrowserState};
+
+/// Height of the cache panel.
+const PANEL_HEIGHT: f32 = 220.;
+
+/// Message id of the `Network.clearBrowserCache` call, so its result can be
+/// told apart from other observers' calls.
+const CLEAR_ALL_ID: i32 = 0x6361_0001;
+
+/// First bytes of every entry file of Chromium's simple cache backend.
+const SIMPLE_CACHE_MAGIC: u64 = 0xfcfb_6d1b_a772_5c30;
+
+/// Size of the header of an entry file, which the entry's key follows:
+/// the magic number, then the version, key length and key hash as `u32`s,
+/// padded to 8 bytes.
+const HEADER_LEN: usize = 24;
+
+/// A cached response.
+struct CacheEntry {
+    url: String,
+    size: u64,
+    /// The files it's stored in.
+    files: Vec<PathBuf>,
+}
+
+/// The cached responses of one origin.
+struct CacheOrigin {
+    origin: String,
+    entries: Vec<CacheEntry>,
+    size: u64,
+}
+
+/// Clearing under way, of one origin's responses or all of them.
+struct Clearing {
+    origin: Option<String>,
+    done: usize,
+    /// How many files are being removed, or 0 when clearing everything,
+    /// which doesn't report progress.
+    total: usize,
+}
+
+#[derive(Default)]
+struct CacheInspectorInner {
+    /// The disk cache of the active tab's request context, if it has one.
+    dir: Option<PathBuf>,
+    origins: Vec<CacheOrigin>,
+    clearing: Option<Clearing>,
+    /// The observer waiting for the cache to be cleared, by browser id.
+    observer: Option<(i32, Registration)>,
+    /// Set once the cache was cleared, for the panel to read it again.
+    stale: bool,
+    changed: bool,
+}
+
+/// The HTTP cache of the active tab's request context, read from the disk
+/// cache's entry files and cleared by origin or as a whole.
+#[derive(Clone, Default)]
+pub struct CacheInspector(Arc<Mutex<CacheInspectorInner>>);
+
+impl Global for CacheInspector {}
+
+/// Hears when the browser cache was cleared.
+struct CacheObserver {
+    inspector: CacheInspector,
+}
+
+impl DevToolsMessageObserverCallbacks for CacheObserver {
+    fn on_dev_tools_message(&mut self, _browser: Browser, _message: &[u8]) -> bool {
+        false
+    }
+
+    fn on_dev_tools_method_result(
+        &mut self,
+        _browser: Browser,
+        message_id: i32,
+        success: bool,
+        _result: &[u8],
+    ) {
+        if message_id != CLEAR_ALL_ID {
+            return;
+        }
+        if !success {
+            eprintln!("Error clearing the browser cache");
+        }
+
+        let mut inner = self.inspector.0.lock().unwrap();
+        inner.clearing = None;
+        inner.stale = true;
+        inner.changed = true;
+    }
+
+    fn on_dev_tools_event(&mut self, _browser: Browser, _method: &str, _params: &[u8]) {}
+
+    fn on_dev_tools_agent_attached(&mut self, _browser: Browser) {}
+
+    fn on_dev_tools_agent_detached(&mut self, _browser: Browser) {}
+}
+
+/// The URL an entry file caches, from the key after its header. HTTP cache
+/// keys may start with the sites the response is partitioned by, separated
+/// by spaces, so the URL is the last part.
+fn read_url(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut header = [0; HEADER_LEN];
+    file.read_exact(&mut header).ok()?;
+
+    let magic = u64::from_le_bytes(header[0..8].try_into().ok()?);
+    if magic != SIMPLE_CACHE_MAGIC {
+        return None;
+    }
+
+    let key_length = u32::from_le_bytes(header[12..16].try_into().ok()?) as usize;
+    let mut key = vec![0; key_length];
+    file.read_exact(&mut key).ok()?;
+
+    let key = String::from_utf8(key).ok()?;
+    key.rsplit(' ').next().map(str::to_string)
+}
+
+/// Read the entries of the disk cache in `dir`, grouped by origin, largest
+/// first. Each entry is stored as `<hash>_0`, with `<hash>_1` and `<hash>_s`
+/// for larger and sparse data.
+fn scan(dir: &Path) -> Vec<CacheOrigin> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut by_hash = HashMap::<String, (Option<String>, u64, Vec<PathBuf>)>::new();
+    for file in files.flatten() {
+        let path = file.path();
+        let Some((hash, stream)) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split_once('_'))
+        else {
+            continue;
+        };
+        if !matches!(stream, "0" | "1" | "s") {
+            continue;
+        }
+
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let entry = by_hash.entry(hash.to_string()).or_default();
+        if stream == "0" {
+            entry.0 = read_url(&path);
+        }
+        entry.1 += size;
+        entry.2.push(path);
+    }
+
+    let mut by_origin = BTreeMap::<String, Vec<CacheEntry>>::new();
+    for (url, size, files) in by_hash.into_values() {
+        let Some(url) = url else {
+            continue;
+        };
+        let origin = Url::parse(&url)
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_else(|_| url.clone());
+        by_origin
+            .entry(origin)
+            .or_default()
+            .push(CacheEntry { url, size, files });
+    }
+
+    let mut origins = by_origin
+        .into_iter()
+        .map(|(origin, mut entries)| {
+            entries.sort_by_key(|entry| Reverse(entry.size));
+            let size = entries.iter().map(|entry| entry.size).sum();
+            CacheOrigin {
+                origin,
+                entries,
+                size,
+            }
+        })
+        .collect::<Vec<_>>();
+    origins.sort_by_key(|origin| Reverse(origin.size));
+    origins
+}
+
+fn size_label(size: u64) -> String {
+    match size {
+        size if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / 1048576.),
+        size if size >= 1024 => format!("{:.1} kB", size as f64 / 1024.),
+        size => format!("{} B", size),
+    }
+}
+
+/// The entry files of the disk cache kept in `cache_path`.
+fn cache_dir(cache_path: &str) -> Option<PathBuf> {
+    (!cache_path.is_empty()).then(|| Path::new(cache_path).join("Cache").join("Cache_Data"))
+}
+
+impl CacheInspector {
+    /// Find the disk cache of the active tab, for the panel to read.
+    fn locate(&self, cx: &GpuiApp) -> Option<PathBuf> {
+        let dir = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.get_host().ok())
+            .and_then(|host| host.get_request_context().ok())
+            .and_then(|context| context.get_cache_path().ok())
+            .and_then(|path| cache_dir(&path));
+
+        self.0.lock().unwrap().dir = dir.clone();
+        dir
+    }
+
+    fn set_origins(&self, origins: Vec<CacheOrigin>) {
+        let mut inner = self.0.lock().unwrap();
+        inner.origins = origins;
+        inner.changed = true;
+    }
+
+    /// Start removing the responses cached for `origin`, as a list of the
+    /// files to remove. Also clears the origin's Cache API storage, which
+    /// the page may have filled as well.
+    fn start_clearing_origin(&self, cx: &GpuiApp, origin: &str) -> Vec<PathBuf> {
+        let mut inner = self.0.lock().unwrap();
+        if inner.clearing.is_some() {
+            return Vec::new();
+        }
+
+        let files = inner
+            .origins
+            .iter()
+            .filter(|cached| cached.origin == origin)
+            .flat_map(|cached| &cached.entries)
+            .flat_map(|entry| entry.files.iter().cloned())
+            .collect::<Vec<_>>();
+        inner.clearing = Some(Clearing {
+            origin: Some(origin.to_string()),
+            done: 0,
+            total: files.len(),
+        });
+        inner.changed = true;
+        drop(inner);
+
+        if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+            let params = [("origin", origin), ("storageTypes", "cache_storage")];
+            if let Err(e) = call(browser, 0, "Storage.clearDataForOrigin", &params) {
+                eprintln!("Error clearing Cache API storage: {}", e);
+            }
+        }
+
+        files
+    }
+
+    /// Note another file of the origin being cleared as removed.
+    fn cleared_file(&self) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(clearing) = inner.clearing.as_mut() {
+            clearing.done += 1;
+        }
+        inner.changed = true;
+    }
+
+    fn finish_clearing(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.clearing = None;
+        inner.changed = true;
+    }
+
+    /// Clear the whole HTTP cache of the active tab's request context. The
+    /// observer hears when it's done.
+    fn clear_all(&self, cx: &GpuiApp) {
+        let Some(browser) = cx.global::<BrowserState>().browser.as_ref() else {
+            return;
+        };
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            if inner.clearing.is_some() {
+                return;
+            }
+
+            if inner.observer.as_ref().map(|(id, _)| *id) != Some(id) {
+                inner.observer = None;
+                let registration = browser.get_host().and_then(|host| {
+                    host.add_dev_tools_message_observer(DevToolsMessageObserver::new(
+                        CacheObserver {
+                            inspector: self.clone(),
+                        },
+                    ))
+                });
+                match registration {
+                    Ok(Some(registration)) => inner.observer = Some((id, registration)),
+                    Ok(None) => return,
+                    Err(e) => {
+                        eprintln!("Error observing DevTools messages: {}", e);
+                        return;
+                    }
+                }
+            }
+
+            inner.clearing = Some(Clearing {
+                origin: None,
+                done: 0,
+                total: 0,
+            });
+            inner.changed = true;
+        }
+
+        if let Err(e) = call(browser, CLEAR_ALL_ID, "Network.clearBrowserCache", &[]) {
+            eprintln!("Error clearing the browser cache: {}", e);
+            self.finish_clearing();
+        }
+    }
+
+    /// Whether the cache was cleared since the last call, and should be
+    /// read again.
+    fn take_stale(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().stale)
+    }
+
+    /// Whether the entries or the clearing progress changed since the last
+    /// call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Stop observing the page while the panel is closed.
+    fn stop(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.observer = None;
+        inner.origins.clear();
+    }
+}
+
+/// Call the DevTools `method` on `browser` with string `params`.
+fn call(browser: &Browser, message_id: i32, method: &str, params: &[(&str, &str)]) -> Result<()> {
+    let dictionary = DictionaryValue::new();
+    for (key, value) in params {
+        dictionary.set_string(key, value)?;
+    }
+
+    browser
+        .get_host()?
+        .execute_dev_tools_method(message_id, method, Some(dictionary))?;
+    Ok(())
+}
+
+/// Collapsible panel below the page listing what the active tab's HTTP cache
+/// holds, by origin.
+pub struct CachePanel {
+    inspector: CacheInspector,
+    /// Origins whose entries are listed.
+    expanded: HashSet<String>,
+    scanning: bool,
+}
+
+impl CachePanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let mut panel = Self {
+            inspector: cx.global::<CacheInspector>().clone(),
+            expanded: HashSet::new(),
+            scanning: false,
+        };
+        panel.scan(cx);
+        panel
+    }
+
+    /// Read the cache's entries again, off the main thread.
+    fn scan(&mut self, cx: &mut Context<Self>) {
+        let Some(dir) = self.inspector.locate(cx) else {
+            self.inspector.set_origins(Vec::new());
+            return;
+        };
+
+        self.scanning = true;
+        let inspector = self.inspector.clone();
+        cx.spawn(async move |this, cx| {
+            let origins = cx
+                .background_executor()
+                .spawn(async move { scan(&dir) })
+                .await;
+            inspector.set_origins(origins);
+            this.update(cx, |this, cx| {
+                this.scanning = false;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Called when the inspector changed, to read the cache again once it
+    /// was cleared.
+    pub fn on_changed(&mut self, cx: &mut Context<Self>) {
+        if self.inspector.take_stale() {
+            self.scan(cx);
+        }
+        cx.notify();
+    }
+
+    /// Remove the responses cached for the active page's origin.
+    fn clear_site(&mut self, cx: &mut Context<Self>) {
+        let Some(origin) = cx
+            .global::<BrowserState>()
+            .current_page()
+            .and_then(|(url, _)| Url::parse(&url).ok())
+            .map(|url| url.origin().ascii_serialization())
+        else {
+            return;
+        };
+
+        let files = self.inspector.start_clearing_origin(cx, &origin);
+        let inspector = self.inspector.clone();
+        cx.spawn(async move |this, cx| {
+            let progress = inspector.clone();
+            cx.background_executor()
+                .spawn(async move {
+                    for file in files {
+                        if let Err(e) = fs::remove_file(&file) {
+                            eprintln!("Error removing cache entry {:?}: {}", file, e);
+                        }
+                        progress.cleared_file();
+                    }
+                })
+                .await;
+
+            inspector.finish_clearing();
+            this.update(cx, |this, cx| this.scan(cx)).ok();
+        })
+        .detach();
+    }
+}
+
+impl Drop for CachePanel {
+    fn drop(&mut self) {
+        self.inspector.stop();
+    }
+}
+
+impl Render for CachePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let inner = self.inspector.0.lock().unwrap();
+        let in_memory = inner.dir.is_none();
+        let total = inner.origins.iter().map(|origin| origin.size).sum::<u64>();
+        let progress = inner.clearing.as_ref().map(|clearing| {
+            let label = match clearing.origin.as_ref() {
+                Some(origin) => t!("cache.clearing_site", origin = origin),
+                None => t!("cache.clearing_all"),
+            };
+            let fraction =
+                (clearing.total > 0).then(|| clearing.done as f32 / clearing.total as f32);
+            (label, fraction)
+        });
+
+        let mut rows = Vec::new();
+        for (index, cached) in inner.origins.iter().enumerate() {
+            let expanded = self.expanded.contains(&cached.origin);
+            let origin = cached.origin.clone();
+
+            rows.push(
+                div()
+                    .id(("cache-origin", index))
+                    .flex()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.))
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.element_hover))
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        if !this.expanded.remove(&origin) {
+                            this.expanded.insert(origin.clone());
+                        }
+                        cx.notify();
+                    }))
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(12.))
+                            .text_color(theme.text_muted)
+                            .child(if expanded { "▾" } else { "▸" }),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.info)
+                            .child(cached.origin.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(72.))
+                            .text_color(theme.text_placeholder)
+                            .child(t!("cache.entries", count = cached.entries.len())),
+                    )
+                    .child(div().flex_none().w(px(72.)).child(size_label(cached.size)))
+                    .into_any_element(),
+            );
+
+            if expanded {
+                rows.extend(cached.entries.iter().map(|entry| {
+                    div()
+                        .flex()
+                        .gap_2()
+                        .pl(px(28.))
+                        .pr_2()
+                        .py(px(2.))
+                        .border_b_1()
+                        .border_color(theme.border_subtle)
+                        .child(
+                            div()
+                                .flex_1()
+                                .min_w(px(0.))
+                                .overflow_hidden()
+                                .text_ellipsis()
+                                .whitespace_nowrap()
+                                .child(entry.url.clone()),
+                        )
+                        .child(
+                            div()
+                                .flex_none()
+                                .w(px(72.))
+                                .text_color(theme.text_muted)
+                                .child(size_label(entry.size)),
+                        )
+                        .into_any_element()
+                }));
+            }
+        }
+        let empty = rows.is_empty();
+        drop(inner);
+
+        let clearing = progress.is_some();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("cache.title"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(theme.text_placeholder)
+                            .child(t!("cache.total", size = size_label(total))),
+                    )
+                    .when(!clearing && !in_memory, |this| {
+                        this.child(
+                            button("cache-clear-site", t!("cache.clear_site"), false, cx)
+                                .on_click(cx.listener(|this, _, _, cx| this.clear_site(cx))),
+                        )
+                        .child(
+                            button("cache-clear-all", t!("cache.clear_all"), false, cx).on_click(
+                                cx.listener(|this, _, _, cx| this.inspector.clear_all(cx)),
+                            ),
+                        )
+                    }),
+            )
+            .when_some(progress, |this, (label, fraction)| {
+                this.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .px_2()
+                        .py_1()
+                        .text_color(theme.text_secondary)
+                        .child(label)
+                        .when_some(fraction, |this, fraction| {
+                            this.child(
+                                div().flex_1().h_1().rounded_full().bg(theme.surface).child(
+                                    div()
+                                        .h_full()
+                                        .w(relative(fraction))
+                                        .rounded_full()
+                                        .bg(theme.accent),
+                                ),
+                            )
+                        }),
+                )
+            })
+            .child(
+                div()
+                    .id("cache-entries")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .font_family("Menlo")
+                    .when(empty, |this| {
+                        this.child(
+                            div()
+                                .px_2()
+                                .py_1()
+                                .text_color(theme.text_placeholder)
+                                .child(match (in_memory, self.scanning) {
+                                    (true, _) => t!("cache.in_memory"),
+                                    (false, true) => t!("cache.reading"),
+                                    (false, false) => t!("cache.empty"),
+                                }),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
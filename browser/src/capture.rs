@@ -0,0 +1,568 @@
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, DismissEvent, EventEmitter, Global, Window,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    permissions::{frame_origin, PermissionsStore},
+    theme::ActiveTheme,
+};
+
+/// Puts the device chosen in the capture sheet into `getUserMedia` and
+/// `getDisplayMedia` calls. CEF only grants or denies media access, so the
+/// device has to be picked by the page's own constraints: cameras by the name
+/// Chromium labels them with, screens and windows by a desktop capture source.
+/// Relies on `window.cefQuery`.
+pub const CAPTURE_SCRIPT: &str = r#"(() => {
+  const media = navigator.mediaDevices;
+  if (!media || media.__cefCapture) return;
+  Object.defineProperty(media, "__cefCapture", { value: true });
+
+  const getUserMedia = media.getUserMedia.bind(media);
+  const getDisplayMedia = media.getDisplayMedia && media.getDisplayMedia.bind(media);
+  const enumerateDevices = media.enumerateDevices.bind(media);
+
+  const choose = (kind) =>
+    new Promise((resolve, reject) => {
+      window.cefQuery({
+        request: "captureDevice:" + kind,
+        onSuccess: (response) => resolve(response ? JSON.parse(response) : null),
+        onFailure: (_, message) => reject(new DOMException(message, "NotAllowedError")),
+      });
+    });
+
+  // Devices only have labels once the page was let open a camera.
+  const cameraId = async (name) => {
+    let devices = await enumerateDevices();
+    if (devices.every((device) => !device.label)) {
+      const probe = await getUserMedia({ video: true });
+      probe.getTracks().forEach((track) => track.stop());
+      devices = await enumerateDevices();
+    }
+
+    const camera = devices.find(
+      (device) => device.kind === "videoinput" && device.label.startsWith(name)
+    );
+    return camera && camera.deviceId;
+  };
+
+  media.getUserMedia = async (constraints = {}) => {
+    const video = typeof constraints.video === "object" ? constraints.video : {};
+    if (!constraints.video || video.mandatory || video.deviceId) {
+      return getUserMedia(constraints);
+    }
+
+    const camera = await choose("camera");
+    const deviceId = camera && (await cameraId(camera.name));
+    if (!deviceId) return getUserMedia(constraints);
+
+    return getUserMedia({ ...constraints, video: { ...video, deviceId: { exact: deviceId } } });
+  };
+
+  if (getDisplayMedia) {
+    media.getDisplayMedia = async (constraints = {}) => {
+      const source = await choose("screen");
+      if (!source) return getDisplayMedia(constraints);
+
+      return getUserMedia({
+        audio: false,
+        video: { mandatory: { chromeMediaSource: "desktop", chromeMediaSourceId: source.id } },
+      });
+    };
+  }
+})();"#;
+
+/// What a page wants to capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureKind {
+    Camera,
+    /// A display or an app window, for `getDisplayMedia`.
+    Screen,
+}
+
+impl CaptureKind {
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "camera" => Some(CaptureKind::Camera),
+            "screen" => Some(CaptureKind::Screen),
+            _ => None,
+        }
+    }
+
+    /// Name of the permission the kind is allowed or blocked under.
+    fn permission(&self) -> &'static str {
+        match self {
+            CaptureKind::Camera => "camera",
+            CaptureKind::Screen => "screen",
+        }
+    }
+}
+
+/// A camera, display or window that can be captured.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaptureDevice {
+    /// The camera's unique identifier, or the desktop capture source of a
+    /// display or window, e.g. `screen:1:0` or `window:42:0`.
+    pub id: String,
+    pub name: String,
+}
+
+/// The devices of `kind` attached to this machine. Only listed on macOS;
+/// elsewhere pages get Chromium's default device.
+pub fn devices(kind: CaptureKind) -> Vec<CaptureDevice> {
+    #[cfg(target_os = "macos")]
+    return match kind {
+        CaptureKind::Camera => macos::cameras(),
+        CaptureKind::Screen => macos::displays()
+            .into_iter()
+            .chain(macos::windows())
+            .collect(),
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = kind;
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use core_foundation::{
+        array::CFArray,
+        base::{CFType, TCFType},
+        dictionary::CFDictionary,
+        number::CFNumber,
+        string::{CFString, CFStringRef},
+    };
+    use core_graphics::{
+        display::CGDisplay,
+        window::{
+            copy_window_info, kCGNullWindowID, kCGWindowLayer, kCGWindowListExcludeDesktopElements,
+            kCGWindowListOptionOnScreenOnly, kCGWindowName, kCGWindowNumber, kCGWindowOwnerName,
+            kCGWindowOwnerPID,
+        },
+    };
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    use super::CaptureDevice;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeVideo: *mut Object;
+    }
+
+    /// `NSString` is toll-free bridged with `CFString`.
+    unsafe fn ns_string(string: *mut Object) -> String {
+        if string.is_null() {
+            return String::new();
+        }
+
+        CFString::wrap_under_get_rule(string as CFStringRef).to_string()
+    }
+
+    /// The cameras `AVCaptureDevice` knows of, built in ones first.
+    pub fn cameras() -> Vec<CaptureDevice> {
+        unsafe {
+            let devices: *mut Object =
+                msg_send![class!(AVCaptureDevice), devicesWithMediaType: AVMediaTypeVideo];
+            if devices.is_null() {
+                return Vec::new();
+            }
+
+            let count: usize = msg_send![devices, count];
+            (0..count)
+                .map(|index| {
+                    let device: *mut Object = msg_send![devices, objectAtIndex: index];
+                    let id: *mut Object = msg_send![device, uniqueID];
+                    let name: *mut Object = msg_send![device, localizedName];
+
+                    CaptureDevice {
+                        id: ns_string(id),
+                        name: ns_string(name),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// The connected displays, the main one first.
+    pub fn displays() -> Vec<CaptureDevice> {
+        let mut displays = match CGDisplay::active_displays() {
+            Ok(displays) => displays,
+            Err(e) => {
+                eprintln!("Error listing displays: {}", e);
+                return Vec::new();
+            }
+        };
+        displays.sort_by_key(|id| !CGDisplay::new(*id).is_main());
+
+        displays
+            .into_iter()
+            .enumerate()
+            .map(|(index, id)| {
+                let display = CGDisplay::new(id);
+                let name = match display.is_main() {
+                    true => t!("capture.entire_screen").to_string(),
+                    false => t!("capture.display", number = index + 1).to_string(),
+                };
+
+                CaptureDevice {
+                    id: format!("screen:{}:0", id),
+                    name: format!(
+                        "{} ({}×{})",
+                        name,
+                        display.pixels_wide(),
+                        display.pixels_high()
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// The other apps' windows on screen, front to back.
+    pub fn windows() -> Vec<CaptureDevice> {
+        let Some(windows) = copy_window_info(
+            kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+            kCGNullWindowID,
+        ) else {
+            return Vec::new();
+        };
+        let windows: CFArray<CFDictionary<CFString, CFType>> =
+            unsafe { CFArray::wrap_under_get_rule(windows.as_concrete_TypeRef()) };
+
+        windows
+            .iter()
+            .filter_map(|window| {
+                let number = |key: CFStringRef| {
+                    window
+                        .find(key)
+                        .and_then(|value| value.downcast::<CFNumber>())
+                        .and_then(|value| value.to_i64())
+                };
+                let string = |key: CFStringRef| {
+                    window
+                        .find(key)
+                        .and_then(|value| value.downcast::<CFString>())
+                        .map(|value| value.to_string())
+                        .filter(|value| !value.is_empty())
+                };
+
+                // Menu bar items, the Dock and such sit above the normal layer.
+                let layer = number(unsafe { kCGWindowLayer })?;
+                let pid = number(unsafe { kCGWindowOwnerPID })?;
+                if layer != 0 || pid == std::process::id() as i64 {
+                    return None;
+                }
+
+                let id = number(unsafe { kCGWindowNumber })?;
+                let owner = string(unsafe { kCGWindowOwnerName })?;
+                // Window titles need the screen recording permission.
+                let name = match string(unsafe { kCGWindowName }) {
+                    Some(title) => format!("{} — {}", owner, title),
+                    None => owner,
+                };
+
+                Some(CaptureDevice {
+                    id: format!("window:{}:0", id),
+                    name,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A page waiting for the user to pick what it captures.
+pub struct CaptureRequest {
+    pub origin: String,
+    pub kind: CaptureKind,
+    pub devices: Vec<CaptureDevice>,
+    query: Query,
+}
+
+/// Device choices keyed by origin, then by permission name.
+type Choices = BTreeMap<String, BTreeMap<String, CaptureDevice>>;
+
+struct CaptureDevicesInner {
+    path: PathBuf,
+    choices: Choices,
+    pending: VecDeque<CaptureRequest>,
+}
+
+/// The camera, display or window each origin was let capture, persisted to a
+/// JSON file, and the pages waiting for the capture sheet.
+#[derive(Clone)]
+pub struct CaptureDevices(Arc<Mutex<CaptureDevicesInner>>);
+
+impl Global for CaptureDevices {}
+
+impl CaptureDevices {
+    /// Load device choices from disk, starting empty if the file is missing or
+    /// invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let choices = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse capture devices {:?}: {}", path, e);
+                Choices::new()
+            }),
+            Err(_) => Choices::new(),
+        };
+
+        Self(Arc::new(Mutex::new(CaptureDevicesInner {
+            path,
+            choices,
+            pending: VecDeque::new(),
+        })))
+    }
+
+    fn choice(&self, origin: &str, kind: CaptureKind) -> Option<CaptureDevice> {
+        self.0
+            .lock()
+            .unwrap()
+            .choices
+            .get(origin)
+            .and_then(|choices| choices.get(kind.permission()))
+            .cloned()
+    }
+
+    pub fn take_pending(&self) -> Option<CaptureRequest> {
+        self.0.lock().unwrap().pending.pop_front()
+    }
+
+    fn choose(&self, origin: &str, kind: CaptureKind, device: Option<&CaptureDevice>) {
+        let mut inner = self.0.lock().unwrap();
+        let choices = inner.choices.entry(origin.to_string()).or_default();
+
+        match device {
+            Some(device) => choices.insert(kind.permission().to_string(), device.clone()),
+            None => choices.remove(kind.permission()),
+        };
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save capture devices {:?}: {}", inner.path, e);
+        }
+    }
+}
+
+impl CaptureDevicesInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.choices)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// Answer a page with the device to capture: the one chosen before if it's
+/// still attached, otherwise whatever the user picks in the capture sheet.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    let Some(kind) = CaptureKind::parse(argument) else {
+        query.failure(ERROR_FAILED, &format!("Unknown capture kind: {}", argument));
+        return;
+    };
+
+    let Some(origin) = frame_origin(&query.frame()) else {
+        query.failure(ERROR_FAILED, "Capturing needs a page origin");
+        return;
+    };
+
+    let permissions = cx.global::<PermissionsStore>();
+    let store = cx.global::<CaptureDevices>();
+    let devices = devices(kind);
+
+    match permissions.decision(&origin, kind.permission()) {
+        Some(false) => {
+            query.failure(ERROR_FAILED, "Permission denied");
+            return;
+        }
+        Some(true) => {
+            let choice = store.choice(&origin, kind);
+            if devices.is_empty()
+                || choice
+                    .as_ref()
+                    .is_some_and(|choice| devices.contains(choice))
+            {
+                answer(query, choice.as_ref());
+                return;
+            }
+        }
+        None => {}
+    }
+
+    store.0.lock().unwrap().pending.push_back(CaptureRequest {
+        origin,
+        kind,
+        devices,
+        query,
+    });
+}
+
+fn answer(query: Query, device: Option<&CaptureDevice>) {
+    match device {
+        Some(device) => query.success(&json!({ "id": device.id, "name": device.name }).to_string()),
+        None => query.success(""),
+    }
+}
+
+/// Sheet over the page letting the user pick the camera, display or window a
+/// page captures, or block it.
+pub struct CaptureSheet {
+    request: Option<CaptureRequest>,
+    selected: usize,
+}
+
+impl CaptureSheet {
+    pub fn new(request: CaptureRequest) -> Self {
+        Self {
+            request: Some(request),
+            selected: 0,
+        }
+    }
+
+    fn decide(&mut self, allow: bool, cx: &mut Context<Self>) {
+        let Some(request) = self.request.take() else {
+            return;
+        };
+
+        let device = request.devices.get(self.selected).filter(|_| allow);
+        cx.global::<PermissionsStore>()
+            .remember(&request.origin, request.kind.permission(), allow);
+        cx.global::<CaptureDevices>()
+            .choose(&request.origin, request.kind, device);
+
+        match allow {
+            true => answer(request.query, device),
+            false => request.query.failure(ERROR_FAILED, "Permission denied"),
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for CaptureSheet {}
+
+impl Render for CaptureSheet {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let Some(request) = &self.request else {
+            return div();
+        };
+
+        let (title, empty) = match request.kind {
+            CaptureKind::Camera => (
+                t!("capture.camera_prompt", origin = request.origin),
+                t!("capture.no_cameras"),
+            ),
+            CaptureKind::Screen => (
+                t!("capture.screen_prompt", origin = request.origin),
+                t!("capture.nothing_to_share"),
+            ),
+        };
+
+        let devices = request
+            .devices
+            .iter()
+            .enumerate()
+            .map(|(index, device)| {
+                let selected = index == self.selected;
+
+                div()
+                    .id(("capture-device", index))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .when(selected, |this| this.bg(theme.accent.opacity(0.25)))
+                    .when(!selected, |this| {
+                        this.hover(|this| this.bg(theme.element_hover))
+                    })
+                    .child(
+                        div()
+                            .size(px(8.))
+                            .rounded_full()
+                            .border_1()
+                            .border_color(theme.text_secondary)
+                            .when(selected, |this| this.bg(theme.accent)),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(device.name.clone()),
+                    )
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.selected = index;
+                        cx.notify();
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(title))
+                    .when(devices.is_empty(), |this| {
+                        this.child(div().text_color(theme.text_secondary).child(empty))
+                    })
+                    .when(!devices.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .id("capture-devices")
+                                .flex()
+                                .flex_col()
+                                .max_h(px(240.))
+                                .overflow_y_scroll()
+                                .children(devices),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("capture-block", t!("capture.block"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.decide(false, cx))),
+                            )
+                            .child(
+                                button("capture-allow", t!("capture.allow"), true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.decide(true, cx))),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,469 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{BinaryValue, Browser, Callback, CertStatus, ErrorCode};
+use chrono::{DateTime, Utc};
+use gpui::{
+    div, prelude::*, px, svg, App as GpuiApp, Context, DismissEvent, Div, EventEmitter, Global,
+    Hsla, SharedString, Stateful, Window,
+};
+use sha2::{Digest, Sha256};
+use url::Url;
+use x509_parser::{parse_x509_certificate, x509::X509Name};
+
+use crate::{locale::t, theme::ActiveTheme, BrowserState};
+
+/// A certificate error waiting for the user to decide.
+pub struct CertificateError {
+    pub url: String,
+    pub host: String,
+    pub error: ErrorCode,
+    callback: Callback,
+}
+
+#[derive(Default)]
+struct CertificateExceptionsInner {
+    allowed: Vec<(String, ErrorCode)>,
+    pending: Option<CertificateError>,
+}
+
+/// Hosts the user chose to proceed to despite a certificate error.
+/// Shared between the CEF request handler and the UI.
+#[derive(Clone, Default)]
+pub struct CertificateExceptions(Arc<Mutex<CertificateExceptionsInner>>);
+
+impl Global for CertificateExceptions {}
+
+impl CertificateExceptions {
+    /// Continue straight away if the user already accepted this error for
+    /// the host, otherwise hold the request until the user decides.
+    pub fn request(&self, url: &str, error: ErrorCode, callback: Callback) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string());
+        let mut inner = self.0.lock().unwrap();
+
+        if inner.allowed.contains(&(host.clone(), error)) {
+            if let Err(e) = callback.cont() {
+                eprintln!("Error continuing request: {}", e);
+            }
+
+            return;
+        }
+
+        // Only one interstitial is shown at a time, a newer error replaces it.
+        if let Some(previous) = inner.pending.replace(CertificateError {
+            url: url.to_string(),
+            host,
+            error,
+            callback,
+        }) {
+            if let Err(e) = previous.callback.cancel() {
+                eprintln!("Error cancelling request: {}", e);
+            }
+        }
+    }
+
+    pub fn take_pending(&self) -> Option<CertificateError> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    fn allow(&self, host: &str, error: ErrorCode) {
+        self.0
+            .lock()
+            .unwrap()
+            .allowed
+            .push((host.to_string(), error));
+    }
+}
+
+/// Interstitial shown in place of the page when its certificate is invalid.
+pub struct CertificateInterstitial {
+    error: CertificateError,
+}
+
+impl CertificateInterstitial {
+    pub fn new(error: CertificateError) -> Self {
+        Self { error }
+    }
+
+    fn describe(&self) -> SharedString {
+        match self.error.error {
+            ErrorCode::CertDateInvalid => t!("certificates.date_invalid"),
+            ErrorCode::CertCommonNameInvalid => {
+                t!("certificates.common_name_invalid")
+            }
+            ErrorCode::CertAuthorityInvalid => {
+                t!("certificates.authority_invalid")
+            }
+            ErrorCode::CertRevoked => t!("certificates.revoked"),
+            ErrorCode::CertWeakSignatureAlgorithm | ErrorCode::CertWeakKey => {
+                t!("certificates.weak")
+            }
+            _ => t!("certificates.invalid"),
+        }
+    }
+
+    fn go_back(&mut self, cx: &mut Context<Self>) {
+        if let Err(e) = self.error.callback.cancel() {
+            eprintln!("Error cancelling request: {}", e);
+        }
+
+        if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+            if browser.can_go_back().unwrap_or(false) {
+                if let Err(e) = browser.go_back() {
+                    eprintln!("Error going back: {}", e);
+                }
+            }
+        }
+
+        cx.emit(DismissEvent);
+    }
+
+    fn proceed(&mut self, cx: &mut Context<Self>) {
+        cx.global::<CertificateExceptions>()
+            .allow(&self.error.host, self.error.error);
+
+        if let Err(e) = self.error.callback.cont() {
+            eprintln!("Error continuing request: {}", e);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for CertificateInterstitial {}
+
+impl Render for CertificateInterstitial {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.page_background)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .max_w(px(480.))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .child(
+                        div()
+                            .text_xl()
+                            .text_color(theme.text)
+                            .child(t!("certificates.title")),
+                    )
+                    .child(t!("certificates.attackers", host = self.error.host))
+                    .child(self.describe())
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.text_placeholder)
+                            .child(format!("{:?} — {}", self.error.error, self.error.url)),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .mt_2()
+                            .child(
+                                div()
+                                    .id("certificate-go-back")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .bg(theme.accent)
+                                    .text_color(theme.text_on_accent)
+                                    .hover(|this| this.opacity(0.85))
+                                    .child(t!("certificates.go_back"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.go_back(cx))),
+                            )
+                            .child(
+                                div()
+                                    .id("certificate-proceed")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .text_color(theme.text_muted)
+                                    .hover(|this| this.text_color(theme.text))
+                                    .child(t!("certificates.proceed"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.proceed(cx))),
+                            ),
+                    ),
+            )
+    }
+}
+
+/// One certificate of a site's chain, as the viewer shows it.
+struct CertificateDetails {
+    subject: String,
+    issuer: String,
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
+    /// SHA-256 of the DER encoding, as colon-separated hex.
+    fingerprint: String,
+    expired: bool,
+    self_signed: bool,
+}
+
+impl CertificateDetails {
+    fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, certificate) = parse_x509_certificate(der).ok()?;
+        let validity = certificate.validity();
+        let fingerprint = Sha256::digest(der)
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(":");
+
+        Some(Self {
+            subject: display_name(certificate.subject()),
+            issuer: display_name(certificate.issuer()),
+            valid_from: DateTime::from_timestamp(validity.not_before.timestamp(), 0),
+            valid_until: DateTime::from_timestamp(validity.not_after.timestamp(), 0),
+            fingerprint,
+            expired: !validity.is_valid(),
+            self_signed: certificate.subject() == certificate.issuer(),
+        })
+    }
+}
+
+/// The common name of `name`, or all of it when it has none.
+fn display_name(name: &X509Name) -> String {
+    name.iter_common_name()
+        .next()
+        .and_then(|common_name| common_name.as_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn binary_to_vec(value: &BinaryValue) -> Option<Vec<u8>> {
+    let mut data = vec![0; value.get_size().ok()?];
+    value.get_data(&mut data, 0).ok()?;
+    Some(data)
+}
+
+/// The certificate `browser`'s page was served with followed by its issuers,
+/// and whether the connection had certificate errors. None for pages that
+/// weren't loaded over TLS.
+fn certificate_chain(browser: &Browser) -> Option<(Vec<CertificateDetails>, bool)> {
+    let status = browser
+        .get_host()
+        .and_then(|host| host.get_visible_navigation_entry())
+        .and_then(|entry| entry.get_ssl_status())
+        .ok()?;
+    if !status.is_secure_connection().unwrap_or(false) {
+        return None;
+    }
+
+    let certificate = status.get_x509certificate().ok()?;
+    let der = binary_to_vec(&certificate.get_derencoded().ok()?)?;
+    let mut chain = vec![CertificateDetails::from_der(&der)?];
+    chain.extend(
+        certificate
+            .get_derencoded_issuer_chain()
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .filter_map(binary_to_vec)
+            .filter_map(|der| CertificateDetails::from_der(&der)),
+    );
+
+    // The errors the interstitial warns about; the rest are informational.
+    let errors = CertStatus::CommonNameInvalid
+        | CertStatus::DateInvalid
+        | CertStatus::AuthorityInvalid
+        | CertStatus::Revoked
+        | CertStatus::Invalid
+        | CertStatus::WeakSignatureAlgorithm
+        | CertStatus::WeakKey;
+    let errors = status
+        .get_cert_status()
+        .is_ok_and(|status| status.intersects(errors));
+
+    Some((chain, errors))
+}
+
+/// The padlock on the left of the omnibox for pages loaded over HTTPS.
+pub fn padlock(open: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("certificate-padlock")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .when(open, |this| this.bg(theme.element_selected))
+        .child(svg().path("lock.svg").size(px(12.0)).text_color(theme.text))
+}
+
+/// Popover below the padlock showing the TLS certificate of the current page
+/// and the chain of issuers vouching for it. Expired certificates are shown
+/// in red and a self-signed site certificate in orange.
+pub struct CertificateViewer {
+    host: String,
+    chain: Vec<CertificateDetails>,
+    /// Whether the connection had certificate errors the user proceeded past.
+    errors: bool,
+}
+
+impl CertificateViewer {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let state = cx.global::<BrowserState>();
+        let host = state
+            .current_page()
+            .and_then(|(url, _)| Url::parse(&url).ok())
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        let (chain, errors) = state
+            .browser
+            .as_ref()
+            .and_then(certificate_chain)
+            .unwrap_or_default();
+
+        Self {
+            host,
+            chain,
+            errors,
+        }
+    }
+
+    fn certificate(&self, index: usize, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        let certificate = &self.chain[index];
+        let leaf = index == 0;
+
+        let (color, warning) = if certificate.expired {
+            (Some(theme.error), Some(t!("certificates.viewer.expired")))
+        } else if leaf && self.errors {
+            (Some(theme.error), Some(t!("certificates.invalid")))
+        } else if leaf && certificate.self_signed {
+            (
+                Some(theme.warning),
+                Some(t!("certificates.viewer.self_signed")),
+            )
+        } else {
+            (None, None)
+        };
+        let date = |date: Option<DateTime<Utc>>| {
+            date.map(|date| date.format("%b %-d, %Y").to_string())
+                .unwrap_or_default()
+        };
+        let field = |label: SharedString, value: SharedString, color: Hsla| {
+            div()
+                .flex()
+                .flex_col()
+                .child(div().text_color(theme.text_muted).child(label))
+                .child(div().text_color(color).child(value))
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .p_3()
+            .rounded_md()
+            .border_1()
+            .border_color(color.unwrap_or(theme.border))
+            .bg(theme.surface_raised)
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(color.unwrap_or(theme.text))
+                    .child(certificate.subject.clone()),
+            )
+            .when_some(warning, |this, warning| {
+                this.child(div().text_color(color.unwrap_or(theme.text)).child(warning))
+            })
+            .child(field(
+                t!("certificates.viewer.issuer"),
+                certificate.issuer.clone().into(),
+                theme.text,
+            ))
+            .child(field(
+                t!("certificates.viewer.validity"),
+                t!(
+                    "certificates.viewer.valid_range",
+                    from = date(certificate.valid_from),
+                    until = date(certificate.valid_until)
+                ),
+                if certificate.expired {
+                    theme.error
+                } else {
+                    theme.text
+                },
+            ))
+            .child(field(
+                t!("certificates.viewer.fingerprint"),
+                certificate.fingerprint.clone().into(),
+                theme.text_secondary,
+            ))
+    }
+}
+
+impl EventEmitter<DismissEvent> for CertificateViewer {}
+
+impl Render for CertificateViewer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .top_0()
+            .left(px(150.))
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(360.))
+            .max_h(px(480.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .child(div().text_sm().child(t!("certificates.viewer.title")))
+                    .child(div().text_color(theme.text_muted).child(self.host.clone())),
+            )
+            .child(
+                div()
+                    .id("certificate-chain")
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .overflow_y_scroll()
+                    .when(self.chain.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_color(theme.text_placeholder)
+                                .child(t!("certificates.viewer.unavailable")),
+                        )
+                    })
+                    .when(self.chain.len() > 1, |this| {
+                        this.child(
+                            div()
+                                .text_color(theme.text_secondary)
+                                .child(t!("certificates.viewer.chain")),
+                        )
+                    })
+                    .children((0..self.chain.len()).map(|index| self.certificate(index, cx))),
+            )
+    }
+}
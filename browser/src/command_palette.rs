@@ -0,0 +1,600 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use gpui::{
+    div, prelude::*, px, Action, App as GpuiApp, Context, DismissEvent, EventEmitter, FocusHandle,
+    Focusable, Global, KeyDownEvent, MouseButton, SharedString, Subscription, Window,
+};
+
+use crate::{
+    locale::t, omnibox::highlighted, theme::ActiveTheme, CaptureFocusedElement, CloseTab, GoBack,
+    GoForward, NewIncognitoTab, NewTab, NewWindow, OpenDevTools, OpenExtensions, OpenSettings,
+    Print, Quit, Reload, SavePage, Screenshot, ToggleBookmarksBar, ToggleCachePanel, ToggleConsole,
+    ToggleCspInspector, ToggleNetworkPanel, TogglePerformancePanel, TogglePictureInPicture,
+    ToggleReaderMode, ToggleSplitView, ToggleStoragePanel, ViewSource,
+};
+
+/// Most recently used commands listed first.
+const MAX_RECENT: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Navigation,
+    Tabs,
+    View,
+    Tools,
+}
+
+impl Category {
+    const ALL: [Category; 4] = [
+        Category::Navigation,
+        Category::Tabs,
+        Category::View,
+        Category::Tools,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            Category::Navigation => t!("command_palette.navigation"),
+            Category::Tabs => t!("command_palette.tabs"),
+            Category::View => t!("command_palette.view"),
+            Category::Tools => t!("command_palette.tools"),
+        }
+    }
+}
+
+/// An action listed in the palette.
+struct Command {
+    category: Category,
+    /// Keys of the label and description in the locale files.
+    label: &'static str,
+    description: &'static str,
+    action: fn() -> Box<dyn Action>,
+}
+
+const COMMANDS: [Command; 26] = [
+    Command {
+        category: Category::Navigation,
+        label: "command_palette.commands.back.label",
+        description: "command_palette.commands.back.description",
+        action: || Box::new(GoBack),
+    },
+    Command {
+        category: Category::Navigation,
+        label: "command_palette.commands.forward.label",
+        description: "command_palette.commands.forward.description",
+        action: || Box::new(GoForward),
+    },
+    Command {
+        category: Category::Navigation,
+        label: "command_palette.commands.reload.label",
+        description: "command_palette.commands.reload.description",
+        action: || Box::new(Reload),
+    },
+    Command {
+        category: Category::Navigation,
+        label: "command_palette.commands.view_page_source.label",
+        description: "command_palette.commands.view_page_source.description",
+        action: || Box::new(ViewSource),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.new_tab.label",
+        description: "command_palette.commands.new_tab.description",
+        action: || Box::new(NewTab),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.new_incognito_tab.label",
+        description: "command_palette.commands.new_incognito_tab.description",
+        action: || Box::new(NewIncognitoTab),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.new_window.label",
+        description: "command_palette.commands.new_window.description",
+        action: || Box::new(NewWindow),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.close_tab.label",
+        description: "command_palette.commands.close_tab.description",
+        action: || Box::new(CloseTab),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.picture_in_picture.label",
+        description: "command_palette.commands.picture_in_picture.description",
+        action: || Box::new(TogglePictureInPicture),
+    },
+    Command {
+        category: Category::Tabs,
+        label: "command_palette.commands.split_view.label",
+        description: "command_palette.commands.split_view.description",
+        action: || Box::new(ToggleSplitView),
+    },
+    Command {
+        category: Category::View,
+        label: "command_palette.commands.bookmarks_bar.label",
+        description: "command_palette.commands.bookmarks_bar.description",
+        action: || Box::new(ToggleBookmarksBar),
+    },
+    Command {
+        category: Category::View,
+        label: "command_palette.commands.reader_mode.label",
+        description: "command_palette.commands.reader_mode.description",
+        action: || Box::new(ToggleReaderMode),
+    },
+    Command {
+        category: Category::View,
+        label: "command_palette.commands.settings.label",
+        description: "command_palette.commands.settings.description",
+        action: || Box::new(OpenSettings),
+    },
+    Command {
+        category: Category::View,
+        label: "command_palette.commands.extensions.label",
+        description: "command_palette.commands.extensions.description",
+        action: || Box::new(OpenExtensions),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.open_devtools.label",
+        description: "command_palette.commands.open_devtools.description",
+        action: || Box::new(OpenDevTools),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.console.label",
+        description: "command_palette.commands.console.description",
+        action: || Box::new(ToggleConsole),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.network_panel.label",
+        description: "command_palette.commands.network_panel.description",
+        action: || Box::new(ToggleNetworkPanel),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.performance_panel.label",
+        description: "command_palette.commands.performance_panel.description",
+        action: || Box::new(TogglePerformancePanel),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.csp_inspector.label",
+        description: "command_palette.commands.csp_inspector.description",
+        action: || Box::new(ToggleCspInspector),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.storage_panel.label",
+        description: "command_palette.commands.storage_panel.description",
+        action: || Box::new(ToggleStoragePanel),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.cache_panel.label",
+        description: "command_palette.commands.cache_panel.description",
+        action: || Box::new(ToggleCachePanel),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.take_screenshot.label",
+        description: "command_palette.commands.take_screenshot.description",
+        action: || Box::new(Screenshot),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.capture_focused_element.label",
+        description: "command_palette.commands.capture_focused_element.description",
+        action: || Box::new(CaptureFocusedElement),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.print.label",
+        description: "command_palette.commands.print.description",
+        action: || Box::new(Print),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.save_page.label",
+        description: "command_palette.commands.save_page.description",
+        action: || Box::new(SavePage),
+    },
+    Command {
+        category: Category::Tools,
+        label: "command_palette.commands.quit.label",
+        description: "command_palette.commands.quit.description",
+        action: || Box::new(Quit),
+    },
+];
+
+struct RecentCommandsInner {
+    path: PathBuf,
+    /// Names of the actions run from the palette, most recent first.
+    names: Vec<String>,
+}
+
+/// The commands last run from the palette, persisted to a JSON file.
+#[derive(Clone)]
+pub struct RecentCommands(Arc<Mutex<RecentCommandsInner>>);
+
+impl Global for RecentCommands {}
+
+impl RecentCommands {
+    /// Load the recent commands from disk, starting empty if the file is
+    /// missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let names = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse recent commands {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self(Arc::new(Mutex::new(RecentCommandsInner { path, names })))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.0.lock().unwrap().names.clone()
+    }
+
+    fn used(&self, name: &str) {
+        let mut inner = self.0.lock().unwrap();
+        inner.names.retain(|other| other != name);
+        inner.names.insert(0, name.to_string());
+        inner.names.truncate(MAX_RECENT);
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save recent commands {:?}: {}", inner.path, e);
+        }
+    }
+}
+
+impl RecentCommandsInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.names)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// A row of the palette.
+struct Item {
+    /// Index into `COMMANDS`.
+    command: usize,
+    /// Characters of the label matching what was typed.
+    matches: Vec<usize>,
+    /// Heading shown above the row, where a group starts.
+    heading: Option<SharedString>,
+}
+
+/// Modal at the top of the window listing the browser's actions. Typing
+/// filters them, and Enter runs the selected one.
+pub struct CommandPalette {
+    focus_handle: FocusHandle,
+    query: String,
+    items: Vec<Item>,
+    selected: usize,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CommandPalette {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let subscriptions =
+            vec![cx.on_blur(&focus_handle, window, |_, _, cx| cx.emit(DismissEvent))];
+
+        let mut palette = Self {
+            focus_handle,
+            query: String::new(),
+            items: Vec::new(),
+            selected: 0,
+            _subscriptions: subscriptions,
+        };
+        palette.filter(cx);
+        palette
+    }
+
+    /// List the commands matching what's typed, best first. Until something
+    /// is typed, the recently used ones come first, then every command by
+    /// category.
+    fn filter(&mut self, cx: &GpuiApp) {
+        self.items.clear();
+        self.selected = 0;
+
+        let query = self.query.trim();
+        if query.is_empty() {
+            let recent = cx
+                .global::<RecentCommands>()
+                .names()
+                .into_iter()
+                .filter_map(|name| {
+                    COMMANDS
+                        .iter()
+                        .position(|command| (command.action)().name() == name)
+                });
+            for (index, command) in recent.enumerate() {
+                self.items.push(Item {
+                    command,
+                    matches: Vec::new(),
+                    heading: (index == 0).then_some(t!("command_palette.recently_used")),
+                });
+            }
+
+            for category in Category::ALL {
+                let commands = COMMANDS
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, command)| command.category == category);
+                for (index, (command, _)) in commands.enumerate() {
+                    self.items.push(Item {
+                        command,
+                        matches: Vec::new(),
+                        heading: (index == 0).then_some(category.label()),
+                    });
+                }
+            }
+            return;
+        }
+
+        let matcher = SkimMatcherV2::default();
+        let mut matches: Vec<(i64, Item)> = COMMANDS
+            .iter()
+            .enumerate()
+            .filter_map(|(index, command)| {
+                let label_match = matcher.fuzzy_indices(&t!(command.label), query);
+                let description_score = matcher.fuzzy_match(&t!(command.description), query);
+                let score = label_match
+                    .as_ref()
+                    .map(|(score, _)| *score)
+                    .max(description_score)?;
+
+                Some((
+                    score,
+                    Item {
+                        command: index,
+                        matches: label_match.map(|(_, indices)| indices).unwrap_or_default(),
+                        heading: None,
+                    },
+                ))
+            })
+            .collect();
+
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.items.extend(matches.into_iter().map(|(_, item)| item));
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+
+        match keystroke.key.as_str() {
+            "up" => self.selected = self.selected.saturating_sub(1),
+            "down" => {
+                self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1));
+            }
+            "enter" => self.run(self.selected, window, cx),
+            "escape" => cx.emit(DismissEvent),
+            "backspace" => {
+                self.query.pop();
+                self.filter(cx);
+            }
+            _ => {
+                // Leave the browser's shortcuts alone.
+                if keystroke.modifiers.platform || keystroke.modifiers.control {
+                    return;
+                }
+
+                match &keystroke.key_char {
+                    Some(key_char) => {
+                        self.query.push_str(key_char);
+                        self.filter(cx);
+                    }
+                    None => return,
+                }
+            }
+        }
+
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    /// Run the command in the row at `index`, remembering it as recently
+    /// used, and close the palette.
+    fn run(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(item) = self.items.get(index) else {
+            return;
+        };
+
+        let action = (COMMANDS[item.command].action)();
+        cx.global::<RecentCommands>().used(action.name());
+        window.dispatch_action(action, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn render_item(
+        &self,
+        index: usize,
+        item: &Item,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let command = &COMMANDS[item.command];
+        let keys = window
+            .highest_precedence_binding_for_action(&*(command.action)())
+            .map(|binding| {
+                binding
+                    .keystrokes()
+                    .iter()
+                    .map(|keystroke| keystroke.unparse())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            });
+        let filtering = !self.query.trim().is_empty();
+
+        div()
+            .flex()
+            .flex_col()
+            .when_some(item.heading.clone(), |this, heading| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pt_2()
+                        .pb_1()
+                        .text_color(theme.text_placeholder)
+                        .child(heading),
+                )
+            })
+            .child(
+                div()
+                    .id(("command-palette-item", index))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .when(self.selected == index, |this| {
+                        this.bg(theme.element_selected)
+                    })
+                    .hover(|this| this.bg(theme.element_hover))
+                    .on_click(cx.listener(move |this, _, window, cx| this.run(index, window, cx)))
+                    .child(div().flex_none().child(highlighted(
+                        &t!(command.label),
+                        &item.matches,
+                        cx,
+                    )))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_ellipsis()
+                            .text_color(theme.text_placeholder)
+                            .child(t!(command.description)),
+                    )
+                    .when(filtering, |this| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .text_color(theme.text_placeholder)
+                                .child(command.category.label()),
+                        )
+                    })
+                    .when_some(keys, |this, keys| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .px_1()
+                                .rounded_sm()
+                                .bg(theme.element_hover)
+                                .text_color(theme.text_muted)
+                                .child(keys),
+                        )
+                    }),
+            )
+    }
+}
+
+impl EventEmitter<DismissEvent> for CommandPalette {}
+
+impl Focusable for CommandPalette {
+    fn focus_handle(&self, _: &GpuiApp) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| self.render_item(index, item, window, cx).into_any_element())
+            .collect::<Vec<_>>();
+        let theme = cx.theme();
+        let (query, color) = match self.query.is_empty() {
+            true => (
+                t!("command_palette.placeholder").to_string(),
+                theme.text_placeholder,
+            ),
+            false => (self.query.clone(), theme.text),
+        };
+        let empty = items.is_empty();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .flex_col()
+            .items_center()
+            .pt(px(48.))
+            .px_4()
+            .bg(theme.scrim)
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| cx.emit(DismissEvent)),
+            )
+            .child(
+                div()
+                    .id("command-palette")
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(Self::on_key_down))
+                    // Clicks inside the palette don't close it.
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .max_h(px(420.))
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .shadow_lg()
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .h_8()
+                            .px_3()
+                            .border_b_1()
+                            .border_color(theme.border)
+                            .text_sm()
+                            .text_color(color)
+                            .child(query),
+                    )
+                    .child(
+                        div()
+                            .id("command-palette-items")
+                            .flex()
+                            .flex_col()
+                            .p_1()
+                            .overflow_y_scroll()
+                            .children(items)
+                            .when(empty, |this| {
+                                this.child(
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .text_color(theme.text_placeholder)
+                                        .child(t!("command_palette.no_matches")),
+                                )
+                            }),
+                    ),
+            )
+    }
+}
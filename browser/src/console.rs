@@ -0,0 +1,366 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::LogSeverity;
+use chrono::{DateTime, Local};
+use gpui::{
+    div, prelude::*, px, rgb, App as GpuiApp, Context, Entity, Global, Hsla, KeyDownEvent,
+    ScrollHandle, SharedString, Subscription, Window,
+};
+use serde::Deserialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::Query,
+    source::{self, Span},
+    text_input::{TextInput, TextInputEvent},
+    theme::{ActiveTheme, Theme},
+    BrowserState,
+};
+
+/// Oldest messages are dropped beyond this many.
+const MAX_MESSAGES: usize = 1000;
+
+/// Height of the console panel.
+const PANEL_HEIGHT: f32 = 220.;
+
+/// Evaluates `$CODE` in the page's global scope, like a line typed into
+/// DevTools' console, and reports what it returned or threw as a
+/// `console.result` query. Relies on `window.cefQuery`.
+const EVAL_SCRIPT: &str = r#"(() => {
+  const describe = (value) => {
+    if (value === undefined) return "undefined";
+    if (typeof value === "function" || typeof value === "symbol" || typeof value === "bigint") {
+      return value.toString();
+    }
+    try {
+      return JSON.stringify(value, null, 2) ?? String(value);
+    } catch (_) {
+      return String(value);
+    }
+  };
+  const report = (ok, value) => window.cefQuery({
+    request: JSON.stringify({ cmd: "console.result", ok, value }),
+  });
+
+  try {
+    report(true, describe((0, eval)($CODE)));
+  } catch (error) {
+    report(false, String(error && error.stack ? error.stack : error));
+  }
+})();"#;
+
+/// What a line of the console is.
+enum MessageKind {
+    /// Logged by the page.
+    Log(LogSeverity),
+    /// Typed into the console to be evaluated.
+    Input,
+    /// What evaluating an input returned, highlighted as JSON.
+    Result(Vec<Vec<Span>>),
+    /// What evaluating an input threw.
+    Error,
+}
+
+/// A line of the console.
+pub struct ConsoleMessage {
+    kind: MessageKind,
+    message: String,
+    source: Option<String>,
+    line: i32,
+    time: DateTime<Local>,
+}
+
+impl ConsoleMessage {
+    fn color(&self, theme: &Theme) -> Hsla {
+        match self.kind {
+            MessageKind::Log(LogSeverity::Warning) => theme.warning,
+            MessageKind::Log(LogSeverity::Error | LogSeverity::Fatal) | MessageKind::Error => {
+                theme.error
+            }
+            MessageKind::Log(LogSeverity::Verbose) => theme.text_muted,
+            MessageKind::Input => theme.text_secondary,
+            _ => theme.text,
+        }
+    }
+
+    /// The script and line the message came from, e.g. `app.js:12`.
+    fn location(&self) -> Option<String> {
+        let source = self.source.as_deref().filter(|source| !source.is_empty())?;
+        let file = source.rsplit('/').next().unwrap_or(source);
+
+        Some(format!("{}:{}", file, self.line))
+    }
+}
+
+#[derive(Default)]
+struct ConsoleLogInner {
+    messages: VecDeque<ConsoleMessage>,
+    changed: bool,
+}
+
+/// Console messages captured from the page by the display handler.
+#[derive(Clone, Default)]
+pub struct ConsoleLog(Arc<Mutex<ConsoleLogInner>>);
+
+impl Global for ConsoleLog {}
+
+impl ConsoleLog {
+    pub fn push(&self, level: LogSeverity, message: String, source: Option<String>, line: i32) {
+        self.push_message(MessageKind::Log(level), message, source, line);
+    }
+
+    fn push_message(&self, kind: MessageKind, message: String, source: Option<String>, line: i32) {
+        let mut inner = self.0.lock().unwrap();
+
+        if inner.messages.len() == MAX_MESSAGES {
+            inner.messages.pop_front();
+        }
+
+        inner.messages.push_back(ConsoleMessage {
+            kind,
+            message,
+            source,
+            line,
+            time: Local::now(),
+        });
+        inner.changed = true;
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.messages.clear();
+        inner.changed = true;
+    }
+
+    /// Whether messages were added or cleared since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+#[derive(Deserialize)]
+struct EvalResult {
+    ok: bool,
+    value: String,
+}
+
+/// Show what evaluating an input returned or threw, from a `console.result`
+/// query.
+pub fn on_query(cx: &GpuiApp, query: Query, request: &str) {
+    let result = match serde_json::from_str::<EvalResult>(request) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error parsing console result: {}", e);
+            query.success("");
+            return;
+        }
+    };
+
+    let kind = match result.ok {
+        true => MessageKind::Result(source::highlight_lines(&result.value, "json")),
+        false => MessageKind::Error,
+    };
+    cx.global::<ConsoleLog>()
+        .push_message(kind, result.value, None, 0);
+    query.success("");
+}
+
+/// Collapsible panel below the page listing console messages, with a line
+/// at the bottom evaluating JavaScript in the page.
+pub struct ConsolePanel {
+    scroll_handle: ScrollHandle,
+    input: Entity<TextInput>,
+    /// Inputs evaluated so far, oldest first.
+    history: Vec<String>,
+    /// Which of `history` is in the input while going through it.
+    history_index: Option<usize>,
+    _subscription: Subscription,
+}
+
+impl ConsolePanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let input = cx.new(|cx| TextInput::new("", cx).placeholder(t!("console.evaluate")));
+        let subscription = cx.subscribe(&input, |this, _, event, cx| {
+            if let TextInputEvent::Confirm = event {
+                this.evaluate(cx);
+            }
+        });
+
+        Self {
+            scroll_handle: ScrollHandle::new(),
+            input,
+            history: Vec::new(),
+            history_index: None,
+            _subscription: subscription,
+        }
+    }
+
+    /// Run the input in the page's main frame. The result comes back as a
+    /// `console.result` query.
+    fn evaluate(&mut self, cx: &mut Context<Self>) {
+        let code = self.input.read(cx).text().trim().to_string();
+        if code.is_empty() {
+            return;
+        }
+
+        if self.history.last() != Some(&code) {
+            self.history.push(code.clone());
+        }
+        self.history_index = None;
+        self.input
+            .update(cx, |input, cx| input.set_text(String::new(), cx));
+        cx.global::<ConsoleLog>()
+            .push_message(MessageKind::Input, code.clone(), None, 0);
+
+        let frame = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.get_main_frame().ok().flatten());
+        let Some(frame) = frame else {
+            return;
+        };
+
+        let code = serde_json::to_string(&code).unwrap_or_default();
+        let script = EVAL_SCRIPT.replace("$CODE", &code);
+        if let Err(e) = frame.execute_java_script(&script, "console", 0) {
+            eprintln!("Error evaluating in console: {}", e);
+        }
+    }
+
+    /// Go back through the inputs evaluated with up, and forward with down.
+    fn on_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let index = match (event.keystroke.key.as_str(), self.history_index) {
+            ("up", None) => self.history.len().checked_sub(1),
+            ("up", Some(index)) => Some(index.saturating_sub(1)),
+            ("down", Some(index)) if index + 1 < self.history.len() => Some(index + 1),
+            ("down", _) => None,
+            _ => return,
+        };
+
+        self.history_index = index;
+        let text = index
+            .map(|index| self.history[index].clone())
+            .unwrap_or_default();
+        self.input.update(cx, |input, cx| input.set_text(text, cx));
+        cx.stop_propagation();
+    }
+
+    /// Show the newest messages.
+    pub fn scroll_to_bottom(&mut self, cx: &mut Context<Self>) {
+        self.scroll_handle.scroll_to_bottom();
+        cx.notify();
+    }
+}
+
+impl Render for ConsolePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let log = cx.global::<ConsoleLog>().clone();
+        let inner = log.0.lock().unwrap();
+        let rows = inner
+            .messages
+            .iter()
+            .map(|message| {
+                let content = match &message.kind {
+                    MessageKind::Result(lines) => {
+                        div().flex().flex_col().children(lines.iter().map(|spans| {
+                            div()
+                                .flex()
+                                .min_h(px(12.))
+                                .children(spans.iter().map(|span| {
+                                    div().text_color(rgb(span.color)).child(span.text.clone())
+                                }))
+                        }))
+                    }
+                    MessageKind::Input => div().child(format!("> {}", message.message)),
+                    _ => div().child(message.message.clone()),
+                };
+
+                div()
+                    .flex()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.))
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .text_color(message.color(theme))
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(theme.text_placeholder)
+                            .child(message.time.format("%H:%M:%S%.3f").to_string()),
+                    )
+                    .child(div().flex_1().min_w(px(0.)).child(content))
+                    .when_some(message.location(), |this, location| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .text_color(theme.text_placeholder)
+                                .child(location),
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+        let count: SharedString = format!("{} messages", inner.messages.len()).into();
+        drop(inner);
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("console.title"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(theme.text_placeholder)
+                            .child(count),
+                    )
+                    .child(
+                        button("console-clear", t!("console.clear"), false, cx)
+                            .on_click(move |_, _, _| log.clear()),
+                    ),
+            )
+            .child(
+                div()
+                    .id("console-messages")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll_handle)
+                    .font_family("Menlo")
+                    .children(rows),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .p_1()
+                    .border_t_1()
+                    .border_color(theme.border)
+                    .font_family("Menlo")
+                    .capture_key_down(cx.listener(Self::on_key_down))
+                    .child(self.input.clone()),
+            )
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use anyhow::Result;
+use cef_ui::LogSeverity;
+use chrono::{Days, Local, NaiveDate};
+use tracing::{level_filters::LevelFilter, subscriber::set_global_default};
+use tracing_subscriber::FmtSubscriber;
+
+/// Writes every message pages log to their console to a file in the cache
+/// directory, one per day, for looking into problems after the fact.
+const FLAG: &str = "--log-console";
+
+/// Days of logs kept, counting today's.
+const KEEP_DAYS: u64 = 7;
+
+/// Target of the events, to tell page messages from the browser's own.
+const TARGET: &str = "console";
+
+/// Whether console logging was asked for on the command line.
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == FLAG)
+}
+
+/// Start writing console messages to daily files in `dir`.
+pub fn init(dir: PathBuf) -> Result<()> {
+    fs::create_dir_all(&dir)?;
+    let logger = BrowserConsoleLogger { dir, file: None };
+
+    let subscriber = FmtSubscriber::builder()
+        .with_max_level(LevelFilter::INFO)
+        .with_ansi(false)
+        .with_writer(Mutex::new(logger))
+        .finish();
+    set_global_default(subscriber)?;
+
+    Ok(())
+}
+
+/// Log a message from a page's console, if console logging is on. Nothing
+/// is written otherwise.
+pub fn log(level: LogSeverity, message: &str, source: Option<&str>, line: i32) {
+    let source = source.unwrap_or_default();
+    match level {
+        LogSeverity::Warning => tracing::warn!(target: TARGET, source, line, "{}", message),
+        LogSeverity::Error | LogSeverity::Fatal => {
+            tracing::error!(target: TARGET, source, line, "{}", message)
+        }
+        _ => tracing::info!(target: TARGET, source, line, "{}", message),
+    }
+}
+
+/// Appends to `console-<date>.log` for the day each line is written,
+/// starting a new file at midnight and deleting ones older than
+/// `KEEP_DAYS`.
+struct BrowserConsoleLogger {
+    dir: PathBuf,
+    /// The file being written and the day it's for.
+    file: Option<(NaiveDate, File)>,
+}
+
+impl BrowserConsoleLogger {
+    fn path(&self, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join(format!("console-{}.log", date.format("%Y-%m-%d")))
+    }
+
+    /// The file for today, opening it if the day changed.
+    fn today(&mut self) -> io::Result<&mut File> {
+        let today = Local::now().date_naive();
+        if self.file.as_ref().is_none_or(|(date, _)| *date != today) {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path(today))?;
+            self.file = Some((today, file));
+
+            if let Err(e) = self.prune(today) {
+                eprintln!("Error removing old console logs: {}", e);
+            }
+        }
+
+        Ok(&mut self.file.as_mut().unwrap().1)
+    }
+
+    /// Delete the logs of days before the last `KEEP_DAYS`.
+    fn prune(&self, today: NaiveDate) -> io::Result<()> {
+        let Some(oldest) = today.checked_sub_days(Days::new(KEEP_DAYS - 1)) else {
+            return Ok(());
+        };
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let date = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("console-")?.strip_suffix(".log"))
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+            if date.is_some_and(|date| date < oldest) {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for BrowserConsoleLogger {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.today()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some((_, file)) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,593 @@
+use anyhow::Result;
+use std::{
+    collections::VecDeque,
+    io::Cursor,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    AuthCallback, Browser, ContextMenuMediaType, ContextMenuParams, EventFlags, MenuCommandId,
+    MenuId, MenuItemType, MenuModel, MouseButtonType, MouseEvent, Point, Request,
+    RunContextMenuCallback, UrlRequest, UrlRequestClient, UrlRequestClientCallbacks,
+    UrlRequestStatus,
+};
+use gpui::{
+    anchored, deferred, div, point, prelude::*, px, App as GpuiApp, ClipboardItem, Context,
+    DismissEvent, EventEmitter, Global, Image, ImageFormat, Pixels, SharedString, Window,
+};
+use image::DynamicImage;
+use url::Url;
+
+use crate::{
+    devtools, hang, locale::t, settings::Settings, source, tabs, theme::ActiveTheme,
+    translate::Translator, BrowserState,
+};
+
+/// Offsets from `MENU_ID_USER_FIRST` of the commands the browser handles itself.
+const OPEN_LINK_IN_NEW_TAB: u32 = 0;
+const COPY_LINK_ADDRESS: u32 = 1;
+const SAVE_IMAGE_AS: u32 = 2;
+const INSPECT_ELEMENT: u32 = 3;
+const VIEW_SOURCE: u32 = 4;
+const SEARCH_SELECTION: u32 = 5;
+const COPY_IMAGE: u32 = 6;
+const TRANSLATE_SELECTION: u32 = 7;
+
+/// Longest selection quoted in full in the search item's label.
+const MAX_QUOTED_CHARS: usize = 32;
+
+/// Commands CEF replaces a misspelled word with its suggestions for, in order.
+const SPELLING_SUGGESTIONS: [MenuId; 5] = [
+    MenuId::SpellCheckSuggestion0,
+    MenuId::SpellCheckSuggestion1,
+    MenuId::SpellCheckSuggestion2,
+    MenuId::SpellCheckSuggestion3,
+    MenuId::SpellCheckSuggestion4,
+];
+
+/// An entry of the menu as CEF built it.
+#[derive(Clone)]
+pub enum MenuItem {
+    Command {
+        id: i32,
+        label: SharedString,
+        enabled: bool,
+    },
+    Separator,
+}
+
+/// A context menu waiting to be shown.
+pub struct MenuRequest {
+    /// Where the menu was asked for, in view coordinates.
+    position: (i32, i32),
+    items: Vec<MenuItem>,
+    callback: RunContextMenuCallback,
+}
+
+/// A browser command picked from the menu that needs the UI to run.
+enum MenuAction {
+    OpenInNewTab(String),
+    CopyToClipboard(String),
+    Inspect(Point),
+    ViewSource(String),
+    Search(String),
+    Translate(String),
+    /// An image downloaded for "Copy image", as the server sent it.
+    CopyImage(Vec<u8>),
+    /// An image downloaded for "Save image as", with the URL it came from.
+    SaveImage {
+        url: String,
+        body: Vec<u8>,
+    },
+}
+
+#[derive(Default)]
+struct ContextMenusInner {
+    pending: Option<MenuRequest>,
+    dismissed: bool,
+    actions: VecDeque<MenuAction>,
+}
+
+/// Context menus and their commands, queued by the CEF handler for the UI.
+#[derive(Clone, Default)]
+pub struct ContextMenus(Arc<Mutex<ContextMenusInner>>);
+
+impl Global for ContextMenus {}
+
+impl ContextMenus {
+    /// Replace CEF's default items with the ones that fit what was clicked.
+    pub fn build(&self, browser: &Browser, params: &ContextMenuParams, model: &MenuModel) {
+        if let Err(e) = model.clear() {
+            eprintln!("Error clearing context menu: {}", e);
+            return;
+        }
+
+        let link_url = params.get_link_url().unwrap_or_default();
+        let is_image = params
+            .get_media_type()
+            .is_ok_and(|media_type| matches!(media_type, ContextMenuMediaType::Image))
+            && params.has_image_contents().unwrap_or(false);
+        let selection = selection_query(params);
+        let has_selection = !selection.is_empty();
+        let is_editable = params.is_editable().unwrap_or(false);
+        let misspelled_word = params.get_misspelled_word().unwrap_or_default();
+
+        let add = |id: MenuCommandId, label: &str| {
+            if let Err(e) = model.add_item(id, label) {
+                eprintln!("Error adding context menu item: {}", e);
+            }
+        };
+        let add_user = |offset: u32, label: &str| {
+            if let Some(id) = MenuCommandId::new_user_id(offset) {
+                add(id, label);
+            }
+        };
+        let separate = || {
+            if model.get_count().unwrap_or(0) > 0 {
+                model.add_separator().ok();
+            }
+        };
+
+        if !link_url.is_empty() {
+            add_user(
+                OPEN_LINK_IN_NEW_TAB,
+                &t!("context_menu.open_link_in_new_tab"),
+            );
+            add_user(COPY_LINK_ADDRESS, &t!("context_menu.copy_link_address"));
+        }
+
+        if is_image {
+            separate();
+            add_user(COPY_IMAGE, &t!("context_menu.copy_image"));
+            add_user(SAVE_IMAGE_AS, &t!("context_menu.save_image_as"));
+        }
+
+        if !misspelled_word.is_empty() {
+            separate();
+
+            let suggestions = params
+                .get_dictionary_suggestions()
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+
+            if suggestions.is_empty() {
+                add(
+                    MenuId::NoSpellingSuggestions.into(),
+                    &t!("context_menu.no_spelling_suggestions"),
+                );
+                model
+                    .set_enabled(MenuId::NoSpellingSuggestions.into(), false)
+                    .ok();
+            }
+
+            // CEF replaces the word, or adds it to the dictionary, for these.
+            for (id, suggestion) in SPELLING_SUGGESTIONS.into_iter().zip(&suggestions) {
+                add(id.into(), suggestion);
+            }
+
+            add(
+                MenuId::AddToDictionary.into(),
+                &t!("context_menu.add_to_dictionary"),
+            );
+        }
+
+        if is_editable {
+            separate();
+            add(MenuId::Cut.into(), &t!("context_menu.cut"));
+            add(MenuId::Copy.into(), &t!("context_menu.copy"));
+            add(MenuId::Paste.into(), &t!("context_menu.paste"));
+        } else if has_selection {
+            separate();
+            add(MenuId::Copy.into(), &t!("context_menu.copy"));
+        }
+
+        if has_selection {
+            let quoted = match selection.chars().count() > MAX_QUOTED_CHARS {
+                true => {
+                    let short: String = selection.chars().take(MAX_QUOTED_CHARS - 1).collect();
+                    format!("{}…", short.trim_end())
+                }
+                false => selection,
+            };
+            add_user(
+                SEARCH_SELECTION,
+                &t!("context_menu.search_for", text = quoted),
+            );
+            add_user(TRANSLATE_SELECTION, &t!("context_menu.translate"));
+        }
+
+        if link_url.is_empty() && !is_image && !is_editable && !has_selection {
+            add(MenuId::Back.into(), &t!("context_menu.back"));
+            add(MenuId::Forward.into(), &t!("context_menu.forward"));
+            add(MenuId::Reload.into(), &t!("context_menu.reload"));
+
+            model
+                .set_enabled(MenuId::Back.into(), browser.can_go_back().unwrap_or(false))
+                .ok();
+            model
+                .set_enabled(
+                    MenuId::Forward.into(),
+                    browser.can_go_forward().unwrap_or(false),
+                )
+                .ok();
+
+            separate();
+            add_user(VIEW_SOURCE, &t!("context_menu.view_source"));
+        }
+
+        separate();
+        add_user(INSPECT_ELEMENT, &t!("context_menu.inspect_element"));
+    }
+
+    /// Queue `model` for the UI to show. The UI answers through `callback`.
+    pub fn request(
+        &self,
+        params: &ContextMenuParams,
+        model: &MenuModel,
+        callback: RunContextMenuCallback,
+    ) {
+        let position = (
+            params.get_xcoord().unwrap_or(0),
+            params.get_ycoord().unwrap_or(0),
+        );
+        let items = (0..model.get_count().unwrap_or(0))
+            .filter_map(|index| match model.get_type_at(index).ok()? {
+                MenuItemType::Separator => Some(MenuItem::Separator),
+                _ => Some(MenuItem::Command {
+                    id: model.get_command_id_at(index).ok()??.into(),
+                    label: model.get_label_at(index).ok()??.replace('&', "").into(),
+                    enabled: model.is_enabled_at(index).unwrap_or(true),
+                }),
+            })
+            .collect();
+
+        let mut inner = self.0.lock().unwrap();
+        inner.pending = Some(MenuRequest {
+            position,
+            items,
+            callback,
+        });
+        inner.dismissed = false;
+    }
+
+    /// Run one of the browser's own commands. Returns false for the commands
+    /// CEF implements itself.
+    pub fn execute(&self, browser: &Browser, params: &ContextMenuParams, command_id: i32) -> bool {
+        let user_first = i32::from(MenuCommandId::from(MenuId::UserFirst));
+        let action = match u32::try_from(command_id - user_first) {
+            Ok(OPEN_LINK_IN_NEW_TAB) => {
+                MenuAction::OpenInNewTab(params.get_link_url().unwrap_or_default())
+            }
+            Ok(COPY_LINK_ADDRESS) => {
+                MenuAction::CopyToClipboard(params.get_link_url().unwrap_or_default())
+            }
+            Ok(offset @ (COPY_IMAGE | SAVE_IMAGE_AS)) => {
+                let url = params.get_source_url().unwrap_or_default();
+                self.fetch_image(browser, &url, offset == SAVE_IMAGE_AS);
+                return true;
+            }
+            Ok(INSPECT_ELEMENT) => MenuAction::Inspect(Point {
+                x: params.get_xcoord().unwrap_or(0),
+                y: params.get_ycoord().unwrap_or(0),
+            }),
+            Ok(VIEW_SOURCE) => MenuAction::ViewSource(params.get_page_url().unwrap_or_default()),
+            Ok(SEARCH_SELECTION) => MenuAction::Search(selection_query(params)),
+            Ok(TRANSLATE_SELECTION) => MenuAction::Translate(selection_query(params)),
+            _ => return false,
+        };
+
+        self.0.lock().unwrap().actions.push_back(action);
+        true
+    }
+
+    /// Download the image at `url` in `browser`'s request context, so it's
+    /// fetched with the page's cookies, then copy or save it.
+    fn fetch_image(&self, browser: &Browser, url: &str, save: bool) {
+        let request = Request::new();
+        if let Err(e) = request.set_url(url).and_then(|_| request.set_method("GET")) {
+            eprintln!("Error fetching image {}: {}", url, e);
+            return;
+        }
+
+        let client = UrlRequestClient::new(ImageRequestClient {
+            menus: self.clone(),
+            url: url.to_string(),
+            save,
+            body: Vec::new(),
+        });
+        let context = browser
+            .get_host()
+            .and_then(|host| host.get_request_context())
+            .ok();
+        UrlRequest::new(request, client, context);
+    }
+
+    /// The menu went away without the UI, e.g. because the page navigated.
+    pub fn dismiss(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.pending = None;
+        inner.dismissed = true;
+    }
+
+    pub fn take_pending(&self) -> Option<MenuRequest> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    pub fn take_dismissed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().dismissed)
+    }
+
+    /// Run the commands picked from the menu since the last call.
+    pub fn run_actions(&self, window: &Window, cx: &mut GpuiApp) {
+        loop {
+            let Some(action) = self.0.lock().unwrap().actions.pop_front() else {
+                break;
+            };
+
+            match action {
+                MenuAction::OpenInNewTab(url) => {
+                    if let Some(browser) = crate::new_browser(cx, &url) {
+                        tabs::add(cx, browser, false);
+                    }
+                }
+                MenuAction::CopyToClipboard(text) => {
+                    cx.write_to_clipboard(ClipboardItem::new_string(text))
+                }
+                MenuAction::Inspect(point) => devtools::inspect(window, cx, point),
+                MenuAction::ViewSource(url) => source::view(cx, &url),
+                MenuAction::Search(query) => {
+                    let url = cx.global::<Settings>().search_url(&query);
+                    if let Some(browser) = crate::new_browser(cx, &url) {
+                        tabs::add(cx, browser, true);
+                    }
+                }
+                MenuAction::Translate(text) => {
+                    let translator = cx.global::<Translator>().clone();
+                    translator.translate_selection(cx, text);
+                }
+                MenuAction::CopyImage(body) => match encode_png(&body) {
+                    Ok(png) => cx.write_to_clipboard(ClipboardItem::new_image(&Image::from_bytes(
+                        ImageFormat::Png,
+                        png,
+                    ))),
+                    Err(e) => eprintln!("Error copying image: {}", e),
+                },
+                MenuAction::SaveImage { url, body } => save_image(cx, &url, body),
+            }
+        }
+    }
+}
+
+/// Downloads an image picked in the menu, queueing it for the UI once it's
+/// all there.
+struct ImageRequestClient {
+    menus: ContextMenus,
+    url: String,
+    save: bool,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for ImageRequestClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        if !matches!(request.get_request_status(), Ok(UrlRequestStatus::Success)) {
+            eprintln!("Error downloading image {}", self.url);
+            return;
+        }
+
+        let body = std::mem::take(&mut self.body);
+        let action = match self.save {
+            true => MenuAction::SaveImage {
+                url: std::mem::take(&mut self.url),
+                body,
+            },
+            false => MenuAction::CopyImage(body),
+        };
+        self.menus.0.lock().unwrap().actions.push_back(action);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Decode a downloaded image and encode it again as PNG, which every
+/// clipboard takes.
+fn encode_png(body: &[u8]) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(body)?;
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Decode a downloaded image and write it to `path`, in the format its
+/// extension names. PNG is used for extensions that aren't image formats.
+fn write_image(body: &[u8], path: &Path) -> Result<()> {
+    let image = image::load_from_memory(body)?;
+    let format = image::ImageFormat::from_path(path).unwrap_or(image::ImageFormat::Png);
+
+    // JPEG has no alpha channel.
+    let image = match format {
+        image::ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()),
+        _ => image,
+    };
+    image.save_with_format(path, format)?;
+    Ok(())
+}
+
+/// Ask where to save an image downloaded from `url`, then save it there.
+fn save_image(cx: &mut GpuiApp, url: &str, body: Vec<u8>) {
+    let suggested_name = Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .next_back()
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "image.png".to_string());
+    let directory = cx.global::<Settings>().download_path.clone();
+    let destination = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+    cx.background_executor()
+        .spawn(async move {
+            let Ok(Ok(Some(destination))) = destination.await else {
+                return;
+            };
+
+            if let Err(e) = write_image(&body, &destination) {
+                eprintln!("Error saving image to {:?}: {}", destination, e);
+            }
+        })
+        .detach();
+}
+
+/// The selected text as a search query, its whitespace collapsed.
+fn selection_query(params: &ContextMenuParams) -> String {
+    params
+        .get_selection_text()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Forward a right click at `position`, in view coordinates, to the page, which
+/// asks for a context menu unless the page handles the click itself.
+pub fn right_click(cx: &GpuiApp, position: Point) {
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let event = MouseEvent {
+        x: position.x,
+        y: position.y,
+        modifiers: EventFlags::RightMouseButton,
+    };
+
+    for mouse_up in [false, true] {
+        if let Err(e) = host.send_mouse_click_event(&event, MouseButtonType::Right, mouse_up, 1) {
+            eprintln!("Error sending right click: {}", e);
+        }
+    }
+    hang::watch(cx);
+}
+
+/// A page's right-click menu, drawn by GPUI over the page.
+pub struct ContextMenu {
+    position: gpui::Point<Pixels>,
+    items: Vec<MenuItem>,
+    callback: Option<RunContextMenuCallback>,
+}
+
+impl ContextMenu {
+    /// `origin` is where the page is drawn in the window.
+    pub fn new(request: MenuRequest, origin: gpui::Point<Pixels>) -> Self {
+        let (x, y) = request.position;
+
+        Self {
+            position: origin + point(px(x as f32), px(y as f32)),
+            items: request.items,
+            callback: Some(request.callback),
+        }
+    }
+
+    fn select(&mut self, id: i32, cx: &mut Context<Self>) {
+        if let Some(callback) = self.callback.take() {
+            if let Err(e) = callback.cont(MenuCommandId::new(id), EventFlags::None) {
+                eprintln!("Error running context menu command: {}", e);
+            }
+        }
+
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if let Some(callback) = self.callback.take() {
+            if let Err(e) = callback.cancel() {
+                eprintln!("Error cancelling context menu: {}", e);
+            }
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Drop for ContextMenu {
+    fn drop(&mut self) {
+        // CEF waits on the callback, so never leave it hanging.
+        if let Some(callback) = self.callback.take() {
+            callback.cancel().ok();
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for ContextMenu {}
+
+impl Render for ContextMenu {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match item.clone() {
+                MenuItem::Separator => div()
+                    .id(index)
+                    .my_1()
+                    .h(px(1.))
+                    .bg(theme.border)
+                    .into_any_element(),
+                MenuItem::Command { id, label, enabled } => div()
+                    .id(index)
+                    .px_3()
+                    .py_1()
+                    .rounded_sm()
+                    .when(!enabled, |this| this.text_color(theme.text_disabled))
+                    .when(enabled, |this| {
+                        this.cursor_pointer()
+                            .hover(|this| this.bg(theme.element_selected))
+                            .on_click(cx.listener(move |this, _, _, cx| this.select(id, cx)))
+                    })
+                    .child(label)
+                    .into_any_element(),
+            })
+            .collect::<Vec<_>>();
+
+        deferred(
+            anchored().position(self.position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .p_1()
+                    .min_w(px(180.))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .on_mouse_down_out(cx.listener(|this, _, _, cx| this.cancel(cx)))
+                    .children(items),
+            ),
+        )
+    }
+}
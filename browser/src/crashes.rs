@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Browser, Frame, TerminationStatus};
+use gpui::{div, prelude::*, px, App as GpuiApp, Div, Global, SharedString};
+
+use crate::{button::button, locale::t, theme::ActiveTheme};
+
+/// Crashes of the same URL after which the crash page offers to stop
+/// reloading it.
+const MAX_CRASHES: u32 = 2;
+
+/// A tab whose renderer went away.
+#[derive(Clone)]
+pub struct CrashedPage {
+    pub url: String,
+    pub status: TerminationStatus,
+    /// How many times the URL has crashed since the browser started.
+    pub crashes: u32,
+}
+
+#[derive(Default)]
+struct ProcessMonitorInner {
+    /// Crashes of each URL since the browser started.
+    crash_counts: HashMap<String, u32>,
+    /// Crashed tabs by browser id, until they load again.
+    crashed: HashMap<i32, CrashedPage>,
+    changed: bool,
+}
+
+/// Renderer processes that terminated, shared with the request handler.
+/// Crashed tabs show a crash page until they're reloaded.
+#[derive(Clone, Default)]
+pub struct ProcessMonitor(Arc<Mutex<ProcessMonitorInner>>);
+
+impl Global for ProcessMonitor {}
+
+impl ProcessMonitor {
+    pub fn on_render_process_terminated(&self, browser: &Browser, status: TerminationStatus) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let url = browser
+            .get_main_frame()
+            .ok()
+            .flatten()
+            .and_then(|frame| frame.get_url().ok())
+            .unwrap_or_default();
+
+        let mut inner = self.0.lock().unwrap();
+        let crashes = inner.crash_counts.entry(url.clone()).or_default();
+        *crashes += 1;
+
+        let crashes = *crashes;
+        inner.crashed.insert(
+            id,
+            CrashedPage {
+                url,
+                status,
+                crashes,
+            },
+        );
+        inner.changed = true;
+    }
+
+    /// A crashed tab navigating starts a new renderer, so its crash page goes.
+    pub fn on_before_browse(&self, browser: &Browser, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            self.forget(browser);
+        }
+    }
+
+    /// The crash `browser`'s tab is showing, if its renderer went away.
+    pub fn crashed_page(&self, browser: &Browser) -> Option<CrashedPage> {
+        let id = browser.get_identifier().ok()?;
+        self.0.lock().unwrap().crashed.get(&id).cloned()
+    }
+
+    /// Whether a tab crashed or recovered since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    fn forget(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.crashed.remove(&id).is_some() {
+            inner.changed = true;
+        }
+    }
+}
+
+/// Load the crashed page again, in a new renderer.
+fn reload(cx: &GpuiApp, browser: &Browser) {
+    cx.global::<ProcessMonitor>().forget(browser);
+
+    if let Err(e) = browser.reload() {
+        eprintln!("Error reloading crashed page: {}", e);
+    }
+}
+
+/// Leave a page that keeps crashing, for the one before it or a blank page.
+fn give_up(cx: &GpuiApp, browser: &Browser) {
+    cx.global::<ProcessMonitor>().forget(browser);
+
+    let result = match browser.can_go_back() {
+        Ok(true) => browser.go_back(),
+        _ => browser.get_main_frame().and_then(|frame| match frame {
+            Some(frame) => frame.load_url("about:blank"),
+            None => Ok(()),
+        }),
+    };
+    if let Err(e) = result {
+        eprintln!("Error leaving crashed page: {}", e);
+    }
+}
+
+fn describe(status: TerminationStatus) -> SharedString {
+    match status {
+        TerminationStatus::ProcessOom => t!("crashes.out_of_memory"),
+        TerminationStatus::ProcessWasKilled => t!("crashes.killed"),
+        TerminationStatus::ProcessCrashed | TerminationStatus::AbnormalTermination => {
+            t!("crashes.crashed")
+        }
+    }
+}
+
+/// Shown in place of a tab whose renderer crashed.
+pub fn page(crash: CrashedPage, browser: Browser, cx: &GpuiApp) -> Div {
+    let theme = cx.theme();
+
+    let keeps_crashing = crash.crashes > MAX_CRASHES;
+
+    div()
+        .size_full()
+        .flex()
+        .items_center()
+        .justify_center()
+        .bg(theme.background)
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .gap_3()
+                .max_w(px(420.))
+                .text_sm()
+                .text_color(theme.text_secondary)
+                .child(
+                    div()
+                        .text_xl()
+                        .text_color(theme.text)
+                        .child(t!("crashes.title")),
+                )
+                .child(describe(crash.status))
+                .when(!crash.url.is_empty(), |this| {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.text_placeholder)
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(crash.url.clone()),
+                    )
+                })
+                .when(keeps_crashing, |this| {
+                    this.child(t!("crashes.crashed_times", count = crash.crashes))
+                })
+                .child(
+                    div()
+                        .flex()
+                        .gap_2()
+                        .mt_2()
+                        .child(
+                            button("crash-reload", t!("crashes.reload"), true, cx).on_click({
+                                let browser = browser.clone();
+                                move |_, _, cx| reload(cx, &browser)
+                            }),
+                        )
+                        .when(keeps_crashing, |this| {
+                            this.child(
+                                button("crash-give-up", t!("crashes.give_up"), false, cx)
+                                    .on_click(move |_, _, cx| give_up(cx, &browser)),
+                            )
+                        }),
+                ),
+        )
+}
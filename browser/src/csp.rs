@@ -0,0 +1,478 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    Browser, DevToolsMessageObserver, DevToolsMessageObserverCallbacks, Frame, LogSeverity,
+    Registration,
+};
+use gpui::{div, prelude::*, px, Context, Global, SharedString, Window};
+use serde::Deserialize;
+
+use crate::{console::ConsoleLog, locale::t, theme::ActiveTheme, BrowserState};
+
+/// Height of the CSP inspector panel.
+const PANEL_HEIGHT: f32 = 220.;
+
+/// Headers a document's policies arrive in, and whether they're report-only.
+const POLICY_HEADERS: [(&str, bool); 2] = [
+    ("content-security-policy", false),
+    ("content-security-policy-report-only", true),
+];
+
+/// One directive of a page's Content Security Policy, e.g. `script-src 'self'`.
+#[derive(Clone)]
+pub struct Directive {
+    pub name: String,
+    pub value: String,
+    /// Whether violations are only reported rather than blocked.
+    pub report_only: bool,
+}
+
+/// The policy a page was served with.
+#[derive(Clone, Default)]
+pub struct PagePolicy {
+    pub url: String,
+    pub directives: Vec<Directive>,
+}
+
+/// Split a policy header into its directives. Headers sent more than once
+/// arrive joined by newlines, and a header may hold several policies
+/// separated by commas.
+fn parse_policy(header: &str, report_only: bool) -> Vec<Directive> {
+    header
+        .split(['\n', ','])
+        .flat_map(|policy| policy.split(';'))
+        .filter_map(|directive| {
+            let directive = directive.trim();
+            let (name, value) = directive.split_once(' ').unwrap_or((directive, ""));
+
+            (!name.is_empty()).then(|| Directive {
+                name: name.to_lowercase(),
+                value: value.trim().to_string(),
+                report_only,
+            })
+        })
+        .collect()
+}
+
+/// What Chromium's reports of Trusted Types violations quote: the type a
+/// sink required, or the policy that couldn't be created.
+const TRUSTED_TYPES_MARKERS: [&str; 4] = [
+    "'TrustedHTML'",
+    "'TrustedScript'",
+    "'TrustedScriptURL'",
+    "TrustedTypePolicy",
+];
+
+fn is_trusted_types_violation(message: &str) -> bool {
+    TRUSTED_TYPES_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+/// Whether a console message is a report of a CSP or Trusted Types
+/// violation. Those are shown from the DevTools log instead, so they aren't
+/// listed twice.
+pub fn is_violation(message: &str) -> bool {
+    message.contains("Content Security Policy") || is_trusted_types_violation(message)
+}
+
+#[derive(Deserialize)]
+struct Response {
+    url: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ResponseReceived {
+    #[serde(rename = "type")]
+    resource_type: String,
+    response: Response,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogEntry {
+    source: String,
+    level: String,
+    text: String,
+    url: Option<String>,
+    line_number: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct EntryAdded {
+    entry: LogEntry,
+}
+
+#[derive(Default)]
+struct CspInspectorInner {
+    observers: HashMap<i32, Registration>,
+    /// Policies of the documents each browser received, frames included,
+    /// until its main frame finishes loading.
+    documents: HashMap<i32, Vec<PagePolicy>>,
+    /// The policy of the page each browser shows.
+    pages: HashMap<i32, PagePolicy>,
+    /// Trusted Types violations of the page each browser shows.
+    trusted_types: HashMap<i32, usize>,
+    changed: bool,
+}
+
+/// Content Security Policies of the open pages, read from their response
+/// headers over the DevTools protocol.
+#[derive(Clone, Default)]
+pub struct CspInspector(Arc<Mutex<CspInspectorInner>>);
+
+impl Global for CspInspector {}
+
+/// Receives document responses and log entries for one browser.
+struct PolicyObserver {
+    inspector: CspInspector,
+    console: ConsoleLog,
+}
+
+impl DevToolsMessageObserverCallbacks for PolicyObserver {
+    fn on_dev_tools_message(&mut self, _browser: Browser, _message: &[u8]) -> bool {
+        false
+    }
+
+    fn on_dev_tools_method_result(
+        &mut self,
+        _browser: Browser,
+        _message_id: i32,
+        _success: bool,
+        _result: &[u8],
+    ) {
+    }
+
+    fn on_dev_tools_event(&mut self, browser: Browser, method: &str, params: &[u8]) {
+        match method {
+            "Network.responseReceived" => {
+                if let Ok(event) = serde_json::from_slice::<ResponseReceived>(params) {
+                    self.inspector.on_response(&browser, event);
+                }
+            }
+            "Log.entryAdded" => {
+                if let Ok(EntryAdded { entry }) = serde_json::from_slice(params) {
+                    self.on_log_entry(&browser, entry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_dev_tools_agent_attached(&mut self, _browser: Browser) {}
+
+    fn on_dev_tools_agent_detached(&mut self, _browser: Browser) {}
+}
+
+impl PolicyObserver {
+    /// Show CSP and Trusted Types violations in the console, in red, and
+    /// count the page's Trusted Types ones.
+    fn on_log_entry(&self, browser: &Browser, entry: LogEntry) {
+        if !matches!(entry.source.as_str(), "security" | "violation") || !is_violation(&entry.text)
+        {
+            return;
+        }
+
+        let level = match entry.level.as_str() {
+            "warning" => LogSeverity::Warning,
+            _ => LogSeverity::Error,
+        };
+
+        // Chromium doesn't put the blocked value in the entry, only the type
+        // the sink required, which the text quotes.
+        let text = match is_trusted_types_violation(&entry.text) {
+            true => {
+                self.inspector.on_trusted_types_violation(browser);
+                format!("Trusted Types violation: {}", entry.text)
+            }
+            false => entry.text,
+        };
+
+        self.console.push(
+            level,
+            text,
+            entry.url,
+            entry.line_number.map(|line| line + 1).unwrap_or_default(),
+        );
+    }
+}
+
+impl CspInspector {
+    /// Start watching a new browser's responses and log. Has to happen before
+    /// its first navigation, or that page's headers are missed.
+    pub fn attach(&self, browser: &Browser, console: ConsoleLog) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let Ok(host) = browser.get_host() else {
+            return;
+        };
+
+        let observer = PolicyObserver {
+            inspector: self.clone(),
+            console,
+        };
+
+        match host.add_dev_tools_message_observer(DevToolsMessageObserver::new(observer)) {
+            Ok(Some(registration)) => {
+                self.0.lock().unwrap().observers.insert(id, registration);
+            }
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("Error observing DevTools messages: {}", e);
+                return;
+            }
+        }
+
+        for method in ["Network.enable", "Log.enable"] {
+            if let Err(e) = host.execute_dev_tools_method(0, method, None) {
+                eprintln!("Error calling {}: {}", method, e);
+            }
+        }
+    }
+
+    /// Forget a closed browser.
+    pub fn detach(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        inner.observers.remove(&id);
+        inner.documents.remove(&id);
+        inner.pages.remove(&id);
+        inner.trusted_types.remove(&id);
+    }
+
+    /// Start counting Trusted Types violations afresh for a new page.
+    pub fn on_load_start(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.trusted_types.remove(&id).is_some() {
+            inner.changed = true;
+        }
+    }
+
+    fn on_trusted_types_violation(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        *inner.trusted_types.entry(id).or_default() += 1;
+        inner.changed = true;
+    }
+
+    /// How many Trusted Types violations the page `browser` shows reported.
+    pub fn trusted_types_violations(&self, browser: &Browser) -> usize {
+        let Ok(id) = browser.get_identifier() else {
+            return 0;
+        };
+        self.0
+            .lock()
+            .unwrap()
+            .trusted_types
+            .get(&id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn on_response(&self, browser: &Browser, event: ResponseReceived) {
+        if event.resource_type != "Document" {
+            return;
+        }
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let directives = event
+            .response
+            .headers
+            .iter()
+            .flat_map(|(name, value)| {
+                let name = name.to_lowercase();
+
+                POLICY_HEADERS
+                    .iter()
+                    .filter(move |(header, _)| *header == name)
+                    .flat_map(|(_, report_only)| parse_policy(value, *report_only))
+            })
+            .collect();
+
+        self.0
+            .lock()
+            .unwrap()
+            .documents
+            .entry(id)
+            .or_default()
+            .push(PagePolicy {
+                url: event.response.url,
+                directives,
+            });
+    }
+
+    /// Settle on the policy of the page that finished loading in `frame`.
+    pub fn on_load_end(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+        let (Ok(id), Ok(url)) = (browser.get_identifier(), frame.get_url()) else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        let documents = inner.documents.remove(&id).unwrap_or_default();
+        let page = documents
+            .into_iter()
+            .rev()
+            .find(|document| document.url == url)
+            .unwrap_or(PagePolicy {
+                url,
+                directives: Vec::new(),
+            });
+
+        inner.pages.insert(id, page);
+        inner.changed = true;
+    }
+
+    fn page(&self, browser: &Browser) -> Option<PagePolicy> {
+        let id = browser.get_identifier().ok()?;
+        self.0.lock().unwrap().pages.get(&id).cloned()
+    }
+
+    /// Whether a page's policy was read or its Trusted Types violations
+    /// changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+/// Collapsible panel below the page listing the directives of its Content
+/// Security Policy.
+pub struct CspPanel;
+
+impl CspPanel {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl Render for CspPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let page = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| cx.global::<CspInspector>().page(browser))
+            .unwrap_or_default();
+
+        let rows = page
+            .directives
+            .iter()
+            .map(|directive| {
+                div()
+                    .flex()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.))
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(160.))
+                            .text_color(theme.info)
+                            .child(directive.name.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .text_color(theme.text)
+                            .child(directive.value.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .px_1()
+                            .rounded_sm()
+                            .when(directive.report_only, |this| {
+                                this.bg(theme.warning.opacity(0.15))
+                                    .text_color(theme.warning)
+                                    .child(t!("csp.report_only"))
+                            })
+                            .when(!directive.report_only, |this| {
+                                this.bg(theme.success.opacity(0.15))
+                                    .text_color(theme.success)
+                                    .child(t!("csp.enforced"))
+                            }),
+                    )
+            })
+            .collect::<Vec<_>>();
+        let summary: SharedString = match rows.len() {
+            0 => t!("csp.no_policy"),
+            count => t!("csp.directives", count = count),
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("csp.title"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.text_placeholder)
+                            .child(page.url),
+                    )
+                    .child(div().text_color(theme.text_placeholder).child(summary)),
+            )
+            .child(
+                div()
+                    .id("csp-directives")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .font_family("Menlo")
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_2()
+                                .text_color(theme.text_placeholder)
+                                .child(t!("csp.no_policy_description")),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
@@ -0,0 +1,240 @@
+use cef_ui::{
+    AudioHandler, Browser, BrowserSettings, Client, ClientCallbacks, ContextMenuHandler,
+    DisplayHandler, FocusHandler, Frame, JsDialogHandler, KeyboardHandler, LifeSpanHandler,
+    LoadHandler, NativeWindowHandle, PermissionHandler, Point, PrintHandler, ProcessId,
+    ProcessMessage, Rect, RenderHandler, RequestHandler, WindowInfo,
+};
+use cef_ui_sys::cef_window_handle_t;
+use gpui::{div, prelude::*, px, App as GpuiApp, Global, Window};
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+use crate::{button::button, locale::t, theme::ActiveTheme, BrowserState};
+
+/// Height of the DevTools panel when docked below the page.
+pub const DOCK_HEIGHT: f32 = 280.;
+
+/// Height of the header above the docked DevTools view.
+const DOCK_HEADER_HEIGHT: f32 = 28.;
+
+/// Whether DevTools opens docked below the page or in its own window.
+#[derive(Default)]
+pub struct DevTools {
+    pub docked: bool,
+}
+
+impl Global for DevTools {}
+
+/// Initial bounds of the DevTools window when it is not docked.
+pub fn window_bounds() -> Rect {
+    Rect {
+        x: 120,
+        y: 120,
+        width: 1024,
+        height: 720,
+    }
+}
+
+/// Whether DevTools is currently showing for the browser.
+pub fn is_open(cx: &GpuiApp) -> bool {
+    cx.global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_host().ok())
+        .is_some_and(|host| host.has_dev_tools().unwrap_or(false))
+}
+
+/// Open DevTools, either in its own window or docked into `window`.
+pub fn open(window: &Window, cx: &mut GpuiApp) {
+    show(window, cx, None);
+}
+
+/// Open DevTools with the element at `point`, in view coordinates, selected.
+pub fn inspect(window: &Window, cx: &mut GpuiApp, point: Point) {
+    show(window, cx, Some(point));
+}
+
+fn show(window: &Window, cx: &mut GpuiApp, inspect_element_at: Option<Point>) {
+    let Some(host) = cx
+        .global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_host().ok())
+    else {
+        return;
+    };
+
+    let window_info = WindowInfo::new().window_name(&String::from("DevTools"));
+    let parent = match cx.global::<DevTools>().docked {
+        true => native_handle(window),
+        false => None,
+    };
+    let window_info = match parent {
+        Some(parent) => dock(window_info, parent, window),
+        None => window_info.bounds(&window_bounds()),
+    };
+
+    if let Err(e) = host.show_dev_tools(
+        &window_info,
+        Client::new(DevToolsClientCallbacks),
+        &BrowserSettings::new(),
+        inspect_element_at,
+    ) {
+        eprintln!("Error opening DevTools: {}", e);
+    }
+}
+
+/// Move DevTools between its own window and the dock, reopening it if needed.
+pub fn set_docked(docked: bool, window: &Window, cx: &mut GpuiApp) {
+    let was_open = is_open(cx);
+
+    if was_open {
+        if let Some(host) = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.get_host().ok())
+        {
+            if let Err(e) = host.close_dev_tools() {
+                eprintln!("Error closing DevTools: {}", e);
+            }
+        }
+    }
+
+    cx.global_mut::<DevTools>().docked = docked;
+
+    if was_open {
+        open(window, cx);
+    }
+}
+
+/// Parent the DevTools view to the GPUI window, covering the dock panel.
+fn dock(window_info: WindowInfo, parent: NativeWindowHandle, window: &Window) -> WindowInfo {
+    let viewport = window.viewport_size();
+    let width = f32::from(viewport.width) as i32;
+    let height = (DOCK_HEIGHT - DOCK_HEADER_HEIGHT) as i32;
+
+    // AppKit positions views from the bottom left, X11 from the top left.
+    #[cfg(target_os = "macos")]
+    let (y, window_info) = (0, window_info.parent_view(parent));
+    #[cfg(not(target_os = "macos"))]
+    let (y, window_info) = (
+        f32::from(viewport.height) as i32 - height,
+        window_info.parent_window(parent),
+    );
+
+    window_info.bounds(&Rect {
+        x: 0,
+        y,
+        width,
+        height,
+    })
+}
+
+/// The native view of a GPUI window.
+fn native_handle(window: &Window) -> Option<NativeWindowHandle> {
+    let handle = HasWindowHandle::window_handle(window).ok()?;
+    let raw = match handle.as_raw() {
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::AppKit(handle) => handle.ns_view.as_ptr() as cef_window_handle_t,
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xlib(handle) => handle.window as cef_window_handle_t,
+        #[cfg(target_os = "linux")]
+        RawWindowHandle::Xcb(handle) => handle.window.get() as cef_window_handle_t,
+        _ => return None,
+    };
+
+    NativeWindowHandle::try_from(raw).ok()
+}
+
+/// Panel reserved below the page for the docked DevTools view.
+pub fn dock_panel(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_none()
+        .h(px(DOCK_HEIGHT))
+        .border_t_1()
+        .border_color(theme.border)
+        .bg(theme.page_background)
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .h(px(DOCK_HEADER_HEIGHT))
+                .px_2()
+                .text_xs()
+                .text_color(theme.text_secondary)
+                .child(t!("devtools.title"))
+                .child(
+                    button("devtools-detach", t!("devtools.detach"), false, cx)
+                        .on_click(|_, window, cx| set_docked(false, window, cx)),
+                ),
+        )
+        .child(div().flex_1())
+}
+
+/// Client for the DevTools browser, which CEF renders into its own view.
+struct DevToolsClientCallbacks;
+
+impl ClientCallbacks for DevToolsClientCallbacks {
+    fn get_audio_handler(&mut self) -> Option<AudioHandler> {
+        None
+    }
+
+    fn get_context_menu_handler(&mut self) -> Option<ContextMenuHandler> {
+        None
+    }
+
+    fn get_display_handler(&mut self) -> Option<DisplayHandler> {
+        None
+    }
+
+    fn get_focus_handler(&mut self) -> Option<FocusHandler> {
+        None
+    }
+
+    fn get_jsdialog_handler(&mut self) -> Option<JsDialogHandler> {
+        None
+    }
+
+    fn get_print_handler(&mut self) -> Option<PrintHandler> {
+        None
+    }
+
+    fn get_keyboard_handler(&mut self) -> Option<KeyboardHandler> {
+        None
+    }
+
+    fn get_life_span_handler(&mut self) -> Option<LifeSpanHandler> {
+        None
+    }
+
+    fn get_load_handler(&mut self) -> Option<LoadHandler> {
+        None
+    }
+
+    fn get_render_handler(&mut self) -> Option<RenderHandler> {
+        None
+    }
+
+    fn get_permission_handler(&mut self) -> Option<PermissionHandler> {
+        None
+    }
+
+    fn get_request_handler(&mut self) -> Option<RequestHandler> {
+        None
+    }
+
+    fn on_process_message_received(
+        &mut self,
+        _browser: Browser,
+        _frame: Frame,
+        _source_process: ProcessId,
+        _message: ProcessMessage,
+    ) -> bool {
+        false
+    }
+}
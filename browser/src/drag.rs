@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{BrowserHost, DragData, DragOperations, EventFlags, MouseEvent, Point};
+use gpui::{App as GpuiApp, CursorStyle, ExternalPaths, Global};
+
+use crate::BrowserState;
+
+struct PageDragInner {
+    /// Drag started by the page, waiting for the mouse to move.
+    source: Option<(DragData, DragOperations)>,
+    allowed_ops: DragOperations,
+    entered: bool,
+    from_page: bool,
+    position: Point,
+    operation: DragOperations,
+}
+
+impl Default for PageDragInner {
+    fn default() -> Self {
+        Self {
+            source: None,
+            allowed_ops: DragOperations::None,
+            entered: false,
+            from_page: false,
+            position: Point { x: 0, y: 0 },
+            operation: DragOperations::None,
+        }
+    }
+}
+
+/// Drag and drop state shared between the render handler and the UI.
+///
+/// GPUI can't start OS-level drags, so drags started by the page are played
+/// back into the page itself and can't be dropped on other apps.
+#[derive(Clone, Default)]
+pub struct PageDrag(Arc<Mutex<PageDragInner>>);
+
+impl Global for PageDrag {}
+
+impl PageDrag {
+    /// The page started dragging an image, link or selection.
+    pub fn start(&self, drag_data: DragData, allowed_ops: DragOperations) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.source = Some((drag_data, allowed_ops));
+        inner.operation = DragOperations::None;
+    }
+
+    /// The page reported what dropping at the current position would do.
+    pub fn set_operation(&self, operation: DragOperations) {
+        self.0.lock().unwrap().operation = operation;
+    }
+
+    /// Whether the page is dragging something.
+    pub fn is_page_dragging(&self) -> bool {
+        let inner = self.0.lock().unwrap();
+
+        inner.source.is_some() || (inner.entered && inner.from_page)
+    }
+
+    /// The cursor to show while dragging over the page.
+    pub fn cursor(&self) -> Option<CursorStyle> {
+        let inner = self.0.lock().unwrap();
+
+        if !inner.entered {
+            return None;
+        }
+
+        Some(if inner.operation.contains(DragOperations::Copy) {
+            CursorStyle::DragCopy
+        } else if inner.operation.contains(DragOperations::Link) {
+            CursorStyle::DragLink
+        } else if inner
+            .operation
+            .intersects(DragOperations::Move | DragOperations::Generic)
+        {
+            CursorStyle::ClosedHand
+        } else {
+            CursorStyle::OperationNotAllowed
+        })
+    }
+}
+
+/// Files from the OS are being dragged over the page at `position`.
+pub fn drag_files_over(cx: &GpuiApp, paths: &ExternalPaths, position: Point) {
+    let drag = cx.global::<PageDrag>().clone();
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let mut inner = drag.0.lock().unwrap();
+    if !inner.entered {
+        let drag_data = DragData::new();
+        for path in paths.paths() {
+            let display_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            if let Err(e) = drag_data.add_file(&path.to_string_lossy(), &display_name) {
+                eprintln!("Error adding dragged file: {}", e);
+            }
+        }
+
+        enter(&host, &mut inner, drag_data, DragOperations::Copy, position);
+        inner.from_page = false;
+    }
+
+    over(&host, &mut inner, position);
+}
+
+/// A drag started by the page moved to `position`.
+pub fn drag_source_over(cx: &GpuiApp, position: Point) {
+    let drag = cx.global::<PageDrag>().clone();
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let mut inner = drag.0.lock().unwrap();
+    if let Some((drag_data, allowed_ops)) = inner.source.take() {
+        // Dragged data can't carry file contents back into the page.
+        if let Err(e) = drag_data.reset_file_contents() {
+            eprintln!("Error resetting drag data: {}", e);
+        }
+
+        enter(&host, &mut inner, drag_data, allowed_ops, position);
+        inner.from_page = true;
+    }
+
+    if inner.entered {
+        over(&host, &mut inner, position);
+    }
+}
+
+/// The drag left the page without dropping.
+pub fn leave(cx: &GpuiApp) {
+    let drag = cx.global::<PageDrag>().clone();
+    let mut inner = drag.0.lock().unwrap();
+
+    if !std::mem::take(&mut inner.entered) {
+        return;
+    }
+
+    if let Some(host) = cx.global::<BrowserState>().host() {
+        if let Err(e) = host.drag_target_drag_leave() {
+            eprintln!("Error leaving drag: {}", e);
+        }
+    }
+}
+
+/// Drop whatever is being dragged at the last position.
+pub fn drop(cx: &GpuiApp) {
+    let drag = cx.global::<PageDrag>().clone();
+    let mut inner = drag.0.lock().unwrap();
+
+    inner.source = None;
+    if !std::mem::take(&mut inner.entered) {
+        return;
+    }
+
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let position = inner.position;
+    if let Err(e) = host.drag_target_drop(&mouse_event(position)) {
+        eprintln!("Error dropping: {}", e);
+    }
+
+    // Drags started by the page also need to be told they ended.
+    if inner.from_page {
+        if let Err(e) = host
+            .drag_source_ended_at(position.x, position.y, inner.operation)
+            .and_then(|_| host.drag_source_system_drag_ended())
+        {
+            eprintln!("Error ending drag: {}", e);
+        }
+    }
+}
+
+fn enter(
+    host: &BrowserHost,
+    inner: &mut PageDragInner,
+    drag_data: DragData,
+    allowed_ops: DragOperations,
+    position: Point,
+) {
+    if let Err(e) = host.drag_target_drag_enter(drag_data, &mouse_event(position), allowed_ops) {
+        eprintln!("Error entering drag: {}", e);
+        return;
+    }
+
+    inner.entered = true;
+    inner.allowed_ops = allowed_ops;
+}
+
+fn over(host: &BrowserHost, inner: &mut PageDragInner, position: Point) {
+    inner.position = position;
+
+    if let Err(e) = host.drag_target_drag_over(&mouse_event(position), inner.allowed_ops) {
+        eprintln!("Error dragging over: {}", e);
+    }
+}
+
+fn mouse_event(position: Point) -> MouseEvent {
+    MouseEvent {
+        x: position.x,
+        y: position.y,
+        modifiers: EventFlags::None,
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{ErrorCode, Frame};
+use gpui::{
+    div, prelude::*, px, svg, Context, DismissEvent, EventEmitter, Global, SharedString, Window,
+};
+use url::Url;
+
+use crate::{locale::t, navigate, settings::Settings, theme::ActiveTheme, BrowserState};
+
+/// The failures with a page of their own, in place of Chromium's.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ErrorTemplate {
+    DnsNotFound,
+    ConnectionRefused,
+    NoInternet,
+}
+
+impl ErrorTemplate {
+    fn for_error(error: ErrorCode) -> Option<Self> {
+        match error {
+            ErrorCode::NameNotResolved => Some(ErrorTemplate::DnsNotFound),
+            ErrorCode::ConnectionRefused => Some(ErrorTemplate::ConnectionRefused),
+            ErrorCode::InternetDisconnected => Some(ErrorTemplate::NoInternet),
+            _ => None,
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            ErrorTemplate::DnsNotFound => "search.svg",
+            ErrorTemplate::ConnectionRefused => "server-off.svg",
+            ErrorTemplate::NoInternet => "wifi-off.svg",
+        }
+    }
+
+    fn title(&self) -> SharedString {
+        match self {
+            ErrorTemplate::DnsNotFound => t!("error_pages.dns_not_found.title"),
+            ErrorTemplate::ConnectionRefused => t!("error_pages.connection_refused.title"),
+            ErrorTemplate::NoInternet => t!("error_pages.no_internet.title"),
+        }
+    }
+
+    fn description(&self, host: &str) -> SharedString {
+        match self {
+            ErrorTemplate::DnsNotFound => {
+                t!("error_pages.dns_not_found.description", host = host)
+            }
+            ErrorTemplate::ConnectionRefused => {
+                t!("error_pages.connection_refused.description", host = host)
+            }
+            ErrorTemplate::NoInternet => t!("error_pages.no_internet.description"),
+        }
+    }
+
+    /// What the user can check, as a list under the description.
+    fn hints(&self) -> Vec<SharedString> {
+        match self {
+            ErrorTemplate::DnsNotFound => vec![
+                t!("error_pages.dns_not_found.hint_spelling"),
+                t!("error_pages.dns_not_found.hint_dns"),
+            ],
+            ErrorTemplate::ConnectionRefused => vec![
+                t!("error_pages.connection_refused.hint_connection"),
+                t!("error_pages.connection_refused.hint_firewall"),
+            ],
+            ErrorTemplate::NoInternet => vec![
+                t!("error_pages.no_internet.hint_cables"),
+                t!("error_pages.no_internet.hint_wifi"),
+            ],
+        }
+    }
+}
+
+/// A main frame load that failed in a way with its own error page.
+pub struct LoadError {
+    pub url: String,
+    pub error: ErrorCode,
+}
+
+#[derive(Default)]
+struct ErrorPageHandlerInner {
+    pending: Option<LoadError>,
+    /// Set once a new navigation starts, so a stale error page goes away.
+    browsing: bool,
+}
+
+/// Failed main frame loads, waiting for the UI to show an error page styled
+/// like the browser instead of Chromium's.
+#[derive(Clone, Default)]
+pub struct ErrorPageHandler(Arc<Mutex<ErrorPageHandlerInner>>);
+
+impl Global for ErrorPageHandler {}
+
+impl ErrorPageHandler {
+    /// Note the main frame navigating away from a failed page.
+    pub fn on_before_browse(&self, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            self.0.lock().unwrap().browsing = true;
+        }
+    }
+
+    pub fn on_load_error(&self, frame: &Frame, error: ErrorCode, failed_url: &str) {
+        if !frame.is_main().unwrap_or(false) || ErrorTemplate::for_error(error).is_none() {
+            return;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+        inner.pending = Some(LoadError {
+            url: failed_url.to_string(),
+            error,
+        });
+        inner.browsing = false;
+    }
+
+    pub fn take_pending(&self) -> Option<LoadError> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    /// Whether the main frame started another navigation since the last call.
+    pub fn take_browsing(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().browsing)
+    }
+}
+
+/// Shown in place of a page that couldn't be loaded.
+pub struct ErrorPage {
+    error: LoadError,
+    template: ErrorTemplate,
+    /// The host of the page, or its URL if it has none.
+    host: String,
+}
+
+impl ErrorPage {
+    pub fn new(error: LoadError) -> Self {
+        let template = ErrorTemplate::for_error(error.error).unwrap_or(ErrorTemplate::NoInternet);
+        let host = Url::parse(&error.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| error.url.clone());
+
+        Self {
+            error,
+            template,
+            host,
+        }
+    }
+
+    fn retry(&mut self, cx: &mut Context<Self>) {
+        if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+            if let Err(e) = browser.reload() {
+                eprintln!("Error reloading: {}", e);
+            }
+        }
+
+        cx.emit(DismissEvent);
+    }
+
+    /// Look the host up with the search engine, for a site whose address
+    /// may be misspelled or that has moved.
+    fn search(&mut self, cx: &mut Context<Self>) {
+        let url = cx.global::<Settings>().search_url(&self.host);
+        navigate(cx, &url);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ErrorPage {}
+
+impl Render for ErrorPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let searchable = self.template != ErrorTemplate::NoInternet;
+
+        div()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.page_background)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .max_w(px(480.))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .child(
+                        svg()
+                            .path(self.template.icon())
+                            .size(px(32.))
+                            .text_color(theme.text_muted),
+                    )
+                    .child(
+                        div()
+                            .text_xl()
+                            .text_color(theme.text)
+                            .child(self.template.title()),
+                    )
+                    .child(self.template.description(&self.host))
+                    .child(
+                        div().flex().flex_col().gap_1().children(
+                            self.template
+                                .hints()
+                                .into_iter()
+                                .map(|hint| div().child(format!("• {}", hint))),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.text_placeholder)
+                            .child(format!("{:?} — {}", self.error.error, self.error.url)),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .mt_2()
+                            .child(
+                                div()
+                                    .id("error-page-retry")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .bg(theme.accent)
+                                    .text_color(theme.text_on_accent)
+                                    .hover(|this| this.opacity(0.85))
+                                    .child(t!("error_pages.try_again"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.retry(cx))),
+                            )
+                            .when(searchable, |this| {
+                                this.child(
+                                    div()
+                                        .id("error-page-search")
+                                        .px_3()
+                                        .py_1()
+                                        .rounded_md()
+                                        .cursor_pointer()
+                                        .text_color(theme.text_muted)
+                                        .hover(|this| this.text_color(theme.text))
+                                        .child(t!("error_pages.search", host = self.host))
+                                        .on_click(cx.listener(|this, _, _, cx| this.search(cx))),
+                                )
+                            }),
+                    ),
+            )
+    }
+}
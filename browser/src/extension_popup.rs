@@ -0,0 +1,412 @@
+use std::{
+    cell::Cell,
+    ops::Deref,
+    path::PathBuf,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    Browser, EventFlags, KeyEvent, KeyEventType, MouseButtonType, MouseEvent, WindowsKeyCode,
+};
+use gpui::{
+    anchored, canvas, deferred, div, img, prelude::*, px, App as GpuiApp, Bounds, Context, Corner,
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Global, ImageSource, KeyDownEvent,
+    KeyUpEvent, Keystroke, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+    Pixels, Point, RenderImage, ScrollWheelEvent, Size, Window,
+};
+
+use crate::{extensions::ExtensionAction, scroll, theme::ActiveTheme, view::ViewSlot};
+
+/// Where the open extension popup's page is drawn, and the popups whose
+/// browsers are still closing.
+#[derive(Clone)]
+pub struct ExtensionPopupView {
+    slot: ViewSlot,
+    closing: Arc<Mutex<Vec<i32>>>,
+}
+
+impl Default for ExtensionPopupView {
+    fn default() -> Self {
+        Self {
+            slot: ViewSlot::new(320, 400),
+            closing: Arc::default(),
+        }
+    }
+}
+
+impl Global for ExtensionPopupView {}
+
+impl Deref for ExtensionPopupView {
+    type Target = ViewSlot;
+
+    fn deref(&self) -> &ViewSlot {
+        &self.slot
+    }
+}
+
+impl ExtensionPopupView {
+    fn close(&self, browser: &Browser) {
+        if let Ok(id) = browser.get_identifier() {
+            self.closing.lock().unwrap().push(id);
+        }
+        self.set_browser(None);
+
+        if let Err(e) = browser.get_host().and_then(|host| host.close_browser(true)) {
+            eprintln!("Error closing extension popup: {}", e);
+        }
+    }
+
+    /// Whether `browser` was a popup's, closed by us or by its page calling
+    /// `window.close()`. Unlike the last page closing, it doesn't quit.
+    pub fn on_before_close(&self, browser: &Browser) -> bool {
+        if self.contains(browser) {
+            self.set_browser(None);
+            return true;
+        }
+
+        let Ok(id) = browser.get_identifier() else {
+            return false;
+        };
+        let mut closing = self.closing.lock().unwrap();
+        let count = closing.len();
+        closing.retain(|closing| *closing != id);
+        closing.len() != count
+    }
+}
+
+/// An extension's popup page, in its own windowless browser, under the
+/// toolbar button that opened it. Clicking outside it closes it; the mouse
+/// and keyboard go to its page while it's open.
+pub struct ExtensionPopup {
+    /// The extension the popup belongs to.
+    pub path: PathBuf,
+    browser: Browser,
+    position: Point<Pixels>,
+    size: Size<Pixels>,
+    image: Option<Arc<RenderImage>>,
+    bounds: Rc<Cell<Bounds<Pixels>>>,
+    focus_handle: FocusHandle,
+    closed: bool,
+}
+
+impl ExtensionPopup {
+    /// Load `action`'s popup page and show it below `position`, where its
+    /// button was clicked.
+    pub fn open(
+        action: &ExtensionAction,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut GpuiApp,
+    ) -> Option<Entity<Self>> {
+        let url = action.popup_url()?;
+        let context = action.extension.get_loader_context().ok().flatten();
+        let size = action.popup_size();
+
+        let view = cx.global::<ExtensionPopupView>().clone();
+        view.set_size(size);
+        let browser = crate::new_browser_in_context(cx, &url, context)?;
+        view.set_browser(Some(&browser));
+
+        let host = browser.get_host();
+        if let Err(e) = host.and_then(|host| {
+            host.was_resized()?;
+            host.set_focus(true)
+        }) {
+            eprintln!("Error showing extension popup: {}", e);
+        }
+
+        let path = action.path.clone();
+        Some(cx.new(|cx| {
+            let focus_handle = cx.focus_handle();
+            window.focus(&focus_handle);
+
+            Self {
+                path,
+                browser,
+                position,
+                size,
+                image: None,
+                bounds: Rc::new(Cell::new(Bounds::default())),
+                focus_handle,
+                closed: false,
+            }
+        }))
+    }
+
+    /// Close the popup's browser and the popover.
+    pub fn dismiss(&mut self, cx: &mut Context<Self>) {
+        if !self.closed {
+            self.closed = true;
+            cx.global::<ExtensionPopupView>().close(&self.browser);
+        }
+        cx.emit(DismissEvent);
+    }
+
+    /// Pick up the page's latest paint, and close once the page closed
+    /// itself.
+    pub fn poll(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let view = cx.global::<ExtensionPopupView>().clone();
+        if !self.closed && !view.contains(&self.browser) {
+            self.closed = true;
+            cx.emit(DismissEvent);
+            return;
+        }
+
+        if let Some(frame) = view.take_frame() {
+            if let Some(previous) = self.image.replace(frame) {
+                window.drop_image(previous).ok();
+            }
+            cx.notify();
+        }
+    }
+
+    fn mouse_event(&self, position: Point<Pixels>, modifiers: &Modifiers) -> MouseEvent {
+        let position = position - self.bounds.get().origin;
+
+        MouseEvent {
+            x: f32::from(position.x) as i32,
+            y: f32::from(position.y) as i32,
+            modifiers: event_flags(modifiers),
+        }
+    }
+
+    fn mouse_move(&self, event: &MouseMoveEvent) {
+        let mouse_event = self.mouse_event(event.position, &event.modifiers);
+        if let Err(e) = self
+            .browser
+            .get_host()
+            .and_then(|host| host.send_mouse_move_event(&mouse_event, false))
+        {
+            eprintln!("Error moving mouse over extension popup: {}", e);
+        }
+    }
+
+    fn mouse_click(
+        &self,
+        position: Point<Pixels>,
+        modifiers: &Modifiers,
+        mouse_up: bool,
+        click_count: usize,
+    ) {
+        let mouse_event = self.mouse_event(position, modifiers);
+        if let Err(e) = self.browser.get_host().and_then(|host| {
+            host.send_mouse_click_event(
+                &mouse_event,
+                MouseButtonType::Left,
+                mouse_up,
+                click_count as i32,
+            )
+        }) {
+            eprintln!("Error clicking extension popup: {}", e);
+        }
+    }
+
+    fn scroll_wheel(&self, event: &ScrollWheelEvent) {
+        let mouse_event = self.mouse_event(event.position, &event.modifiers);
+        let delta = event.delta.pixel_delta(px(scroll::LINE_HEIGHT));
+
+        if let Err(e) = self.browser.get_host().and_then(|host| {
+            host.send_mouse_wheel_event(
+                &mouse_event,
+                f32::from(delta.x) as i32,
+                f32::from(delta.y) as i32,
+            )
+        }) {
+            eprintln!("Error scrolling extension popup: {}", e);
+        }
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.key == "escape" {
+            self.dismiss(cx);
+            return;
+        }
+
+        let Some(key_code) = windows_key_code(keystroke) else {
+            return;
+        };
+        self.send_key(KeyEventType::RawKeyDown, key_code, 0, keystroke);
+
+        // Typed text goes to the page as characters.
+        let typed = !keystroke.modifiers.platform && !keystroke.modifiers.control;
+        if let Some(key_char) = keystroke.key_char.as_ref().filter(|_| typed) {
+            for character in key_char.encode_utf16() {
+                self.send_key(KeyEventType::Char, key_code, character, keystroke);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn key_up(&mut self, event: &KeyUpEvent, cx: &mut Context<Self>) {
+        if let Some(key_code) = windows_key_code(&event.keystroke) {
+            self.send_key(KeyEventType::KeyUp, key_code, 0, &event.keystroke);
+            cx.stop_propagation();
+        }
+    }
+
+    fn send_key(
+        &self,
+        event_type: KeyEventType,
+        key_code: WindowsKeyCode,
+        character: u16,
+        keystroke: &Keystroke,
+    ) {
+        let event = KeyEvent {
+            event_type,
+            modifiers: event_flags(&keystroke.modifiers),
+            windows_key_code: key_code,
+            native_key_code: 0,
+            is_system_key: false,
+            character,
+            unmodified_character: character,
+            focus_on_editable_field: false,
+        };
+
+        if let Err(e) = self
+            .browser
+            .get_host()
+            .and_then(|host| host.send_key_event(event))
+        {
+            eprintln!("Error sending key to extension popup: {}", e);
+        }
+    }
+}
+
+/// CEF's flags for the modifier keys held.
+pub fn event_flags(modifiers: &Modifiers) -> EventFlags {
+    let mut flags = EventFlags::None;
+    if modifiers.shift {
+        flags |= EventFlags::ShiftDown;
+    }
+    if modifiers.control {
+        flags |= EventFlags::ControlDown;
+    }
+    if modifiers.alt {
+        flags |= EventFlags::AltDown;
+    }
+    if modifiers.platform {
+        flags |= EventFlags::CommandDown;
+    }
+    flags
+}
+
+/// The Windows key code pages see for `keystroke`, for the keys they're
+/// sent.
+pub fn windows_key_code(keystroke: &Keystroke) -> Option<WindowsKeyCode> {
+    let key_code = match keystroke.key.as_str() {
+        "backspace" => WindowsKeyCode::Back,
+        "tab" => WindowsKeyCode::Tab,
+        "enter" => WindowsKeyCode::Return,
+        "space" => WindowsKeyCode::Space,
+        "pageup" => WindowsKeyCode::Prior,
+        "pagedown" => WindowsKeyCode::Next,
+        "end" => WindowsKeyCode::End,
+        "home" => WindowsKeyCode::Home,
+        "left" => WindowsKeyCode::Left,
+        "up" => WindowsKeyCode::Up,
+        "right" => WindowsKeyCode::Right,
+        "down" => WindowsKeyCode::Down,
+        "delete" => WindowsKeyCode::Delete,
+        key => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(key), None) if key.is_ascii_alphanumeric() => {
+                    WindowsKeyCode::from(key.to_ascii_uppercase())
+                }
+                _ => return None,
+            }
+        }
+    };
+
+    Some(key_code)
+}
+
+impl EventEmitter<DismissEvent> for ExtensionPopup {}
+
+impl Focusable for ExtensionPopup {
+    fn focus_handle(&self, _: &GpuiApp) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ExtensionPopup {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let bounds = self.bounds.clone();
+
+        deferred(
+            anchored()
+                .position(self.position)
+                .anchor(Corner::TopRight)
+                .snap_to_window_with_margin(px(8.))
+                .child(
+                    div()
+                        .id("extension-popup")
+                        .track_focus(&self.focus_handle)
+                        .relative()
+                        .mt_2()
+                        .w(self.size.width)
+                        .h(self.size.height)
+                        .overflow_hidden()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(theme.border)
+                        .bg(theme.surface)
+                        .on_mouse_down_out(cx.listener(|this, _, _, cx| this.dismiss(cx)))
+                        .on_mouse_move(
+                            cx.listener(|this, event: &MouseMoveEvent, _, _| {
+                                this.mouse_move(event)
+                            }),
+                        )
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|this, event: &MouseDownEvent, window, cx| {
+                                window.focus(&this.focus_handle);
+                                this.mouse_click(
+                                    event.position,
+                                    &event.modifiers,
+                                    false,
+                                    event.click_count,
+                                );
+                                cx.stop_propagation();
+                            }),
+                        )
+                        .on_mouse_up(
+                            MouseButton::Left,
+                            cx.listener(|this, event: &MouseUpEvent, _, _| {
+                                this.mouse_click(
+                                    event.position,
+                                    &event.modifiers,
+                                    true,
+                                    event.click_count,
+                                )
+                            }),
+                        )
+                        .on_scroll_wheel(cx.listener(|this, event: &ScrollWheelEvent, _, _| {
+                            this.scroll_wheel(event)
+                        }))
+                        .on_key_down(
+                            cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                                this.key_down(event, cx)
+                            }),
+                        )
+                        .on_key_up(
+                            cx.listener(|this, event: &KeyUpEvent, _, cx| this.key_up(event, cx)),
+                        )
+                        .when_some(self.image.clone(), |this, image| {
+                            this.child(img(ImageSource::Render(image)).size_full())
+                        })
+                        .child(
+                            canvas(
+                                move |page_bounds, _, _| bounds.set(page_bounds),
+                                |_, _, _, _| {},
+                            )
+                            .absolute()
+                            .size_full(),
+                        ),
+                ),
+        )
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::Frame;
+use gpui::{App as GpuiApp, Global};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use url::Url;
+
+use crate::message_router::{Query, ERROR_FAILED};
+
+/// Defines `chrome.storage.local` and `chrome.storage.sync` on extension
+/// pages. Each call becomes a `storage.*` query, answered from the database.
+/// Both promise and callback styles are supported. Does nothing on other
+/// pages. Relies on `window.cefQuery`.
+pub const STORAGE_SCRIPT: &str = r#"(() => {
+  if (window.__cefStorage || location.protocol !== "chrome-extension:") return;
+  Object.defineProperty(window, "__cefStorage", { value: true });
+
+  const query = (cmd, area, fields) => new Promise((resolve, reject) => {
+    window.cefQuery({
+      request: JSON.stringify({ cmd, area, ...fields }),
+      onSuccess: (response) => resolve(response ? JSON.parse(response) : undefined),
+      onFailure: (_, message) => reject(new Error(message)),
+    });
+  });
+
+  const listeners = new Set();
+  const notify = (area, changes) => {
+    if (Object.keys(changes).length === 0) return;
+    listeners.forEach((listener) => listener(changes, area));
+  };
+
+  const withCallback = (promise, callback) => {
+    if (typeof callback !== "function") return promise;
+    promise.then(callback, (error) => {
+      chrome.runtime && (chrome.runtime.lastError = error);
+      callback();
+      chrome.runtime && delete chrome.runtime.lastError;
+    });
+  };
+
+  const keysOf = (keys) => {
+    if (keys == null) return null;
+    if (typeof keys === "string") return [keys];
+    if (Array.isArray(keys)) return keys.map(String);
+    return Object.keys(keys);
+  };
+
+  const storageArea = (area) => ({
+    get: (keys, callback) => {
+      if (typeof keys === "function") [keys, callback] = [null, keys];
+      const defaults = keys && typeof keys === "object" && !Array.isArray(keys) ? keys : {};
+      const items = query("storage.get", area, { keys: keysOf(keys) })
+        .then((items) => ({ ...defaults, ...items }));
+      return withCallback(items, callback);
+    },
+    set: (items, callback) => withCallback(
+      query("storage.set", area, { items }).then((changes) => notify(area, changes)),
+      callback),
+    remove: (keys, callback) => withCallback(
+      query("storage.remove", area, { keys: keysOf(keys) || [] })
+        .then((changes) => notify(area, changes)),
+      callback),
+    clear: (callback) => withCallback(
+      query("storage.clear", area, {}).then((changes) => notify(area, changes)),
+      callback),
+  });
+
+  window.chrome = window.chrome || {};
+  window.chrome.storage = {
+    local: storageArea("local"),
+    sync: storageArea("sync"),
+    onChanged: {
+      addListener: (listener) => listeners.add(listener),
+      removeListener: (listener) => listeners.delete(listener),
+      hasListener: (listener) => listeners.has(listener),
+    },
+  };
+})();"#;
+
+/// Which of `chrome.storage`'s areas a query is for. Nothing is synced
+/// between devices, so `sync` is just kept apart from `local`.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StorageArea {
+    #[default]
+    Local,
+    Sync,
+}
+
+impl StorageArea {
+    fn table(&self) -> &'static str {
+        match self {
+            StorageArea::Local => "local",
+            StorageArea::Sync => "sync",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StorageRequest {
+    #[serde(default)]
+    area: StorageArea,
+    /// The keys to read or remove. `None` reads every key.
+    #[serde(default)]
+    keys: Option<Vec<String>>,
+    #[serde(default)]
+    items: Map<String, Value>,
+}
+
+/// `chrome.storage` for extensions, kept in a SQLite database with a table
+/// per area. Each row holds one key of one extension, with its value as
+/// JSON.
+#[derive(Clone)]
+pub struct WebExtensionStorage(Arc<Mutex<Connection>>);
+
+impl Global for WebExtensionStorage {}
+
+impl WebExtensionStorage {
+    /// Open the database at `path`, falling back to one in memory if it
+    /// can't be opened, so extensions keep working for this run.
+    pub fn load(path: &Path) -> Self {
+        let connection = Connection::open(path)
+            .and_then(|connection| {
+                create_tables(&connection)?;
+                Ok(connection)
+            })
+            .or_else(|e| {
+                eprintln!("Failed to open extension storage {:?}: {}", path, e);
+                let connection = Connection::open_in_memory()?;
+                create_tables(&connection)?;
+                Ok::<_, rusqlite::Error>(connection)
+            })
+            .expect("in-memory database");
+
+        Self(Arc::new(Mutex::new(connection)))
+    }
+
+    /// The values of `keys`, or of every key if `None`, that `extension_id`
+    /// stored. Keys that were never set are left out.
+    fn get(
+        &self,
+        extension_id: &str,
+        area: StorageArea,
+        keys: Option<&[String]>,
+    ) -> Result<Map<String, Value>> {
+        let connection = self.0.lock().unwrap();
+        let mut items = Map::new();
+
+        match keys {
+            Some(keys) => {
+                let mut statement = connection.prepare_cached(&format!(
+                    "SELECT value FROM {} WHERE extension_id = ?1 AND key = ?2",
+                    area.table()
+                ))?;
+                for key in keys {
+                    let value = statement
+                        .query_row(params![extension_id, key], |row| row.get::<_, Vec<u8>>(0))
+                        .optional()?;
+                    if let Some(value) = value {
+                        items.insert(key.clone(), serde_json::from_slice(&value)?);
+                    }
+                }
+            }
+            None => {
+                let mut statement = connection.prepare_cached(&format!(
+                    "SELECT key, value FROM {} WHERE extension_id = ?1",
+                    area.table()
+                ))?;
+                let rows = statement.query_map(params![extension_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })?;
+                for row in rows {
+                    let (key, value) = row?;
+                    items.insert(key, serde_json::from_slice(&value)?);
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Store `items`, returning the changes in `chrome.storage.onChanged`'s
+    /// shape: `{ key: { oldValue, newValue } }`.
+    fn set(
+        &self,
+        extension_id: &str,
+        area: StorageArea,
+        items: Map<String, Value>,
+    ) -> Result<Map<String, Value>> {
+        let keys = items.keys().cloned().collect::<Vec<_>>();
+        let old = self.get(extension_id, area, Some(&keys))?;
+
+        let mut connection = self.0.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let mut changes = Map::new();
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT OR REPLACE INTO {} (extension_id, key, value) VALUES (?1, ?2, ?3)",
+                area.table()
+            ))?;
+            for (key, value) in items {
+                statement.execute(params![extension_id, key, serde_json::to_vec(&value)?])?;
+
+                let mut change = Map::new();
+                if let Some(old) = old.get(&key) {
+                    change.insert("oldValue".to_string(), old.clone());
+                }
+                change.insert("newValue".to_string(), value);
+                changes.insert(key, Value::Object(change));
+            }
+        }
+        transaction.commit()?;
+
+        Ok(changes)
+    }
+
+    /// Delete `keys`, or every key of `extension_id` if `None`, returning
+    /// the changes like `set`.
+    fn remove(
+        &self,
+        extension_id: &str,
+        area: StorageArea,
+        keys: Option<&[String]>,
+    ) -> Result<Map<String, Value>> {
+        let old = self.get(extension_id, area, keys)?;
+
+        let connection = self.0.lock().unwrap();
+        match keys {
+            Some(keys) => {
+                let mut statement = connection.prepare_cached(&format!(
+                    "DELETE FROM {} WHERE extension_id = ?1 AND key = ?2",
+                    area.table()
+                ))?;
+                for key in keys {
+                    statement.execute(params![extension_id, key])?;
+                }
+            }
+            None => {
+                connection.execute(
+                    &format!("DELETE FROM {} WHERE extension_id = ?1", area.table()),
+                    params![extension_id],
+                )?;
+            }
+        }
+
+        Ok(old
+            .into_iter()
+            .map(|(key, value)| {
+                let mut change = Map::new();
+                change.insert("oldValue".to_string(), value);
+                (key, Value::Object(change))
+            })
+            .collect())
+    }
+}
+
+fn create_tables(connection: &Connection) -> rusqlite::Result<()> {
+    for area in [StorageArea::Local, StorageArea::Sync] {
+        connection.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    extension_id TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    value BLOB NOT NULL,
+                    PRIMARY KEY (extension_id, key)
+                )",
+                area.table()
+            ),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The id of the extension whose page `frame` shows, from its
+/// `chrome-extension://<id>/` URL.
+fn extension_id(frame: &Frame) -> Option<String> {
+    let url = Url::parse(&frame.get_url().ok()?).ok()?;
+    if url.scheme() != "chrome-extension" {
+        return None;
+    }
+
+    url.host_str().map(str::to_string)
+}
+
+/// Answer a `storage.get`, `storage.set`, `storage.remove` or
+/// `storage.clear` query from an extension page. Only extension pages may
+/// use storage, and each only sees its own keys.
+pub fn on_query(cx: &GpuiApp, query: Query, command: &str, request: &str) {
+    let Some(extension_id) = extension_id(&query.frame()) else {
+        query.failure(ERROR_FAILED, "Storage is only available to extensions");
+        return;
+    };
+
+    let storage = cx.global::<WebExtensionStorage>();
+    let result = serde_json::from_str::<StorageRequest>(request)
+        .map_err(|e| anyhow!("Invalid storage request: {}", e))
+        .and_then(|request| match command {
+            "storage.get" => storage.get(&extension_id, request.area, request.keys.as_deref()),
+            "storage.set" => storage.set(&extension_id, request.area, request.items),
+            "storage.remove" => storage.remove(
+                &extension_id,
+                request.area,
+                Some(request.keys.as_deref().unwrap_or_default()),
+            ),
+            _ => storage.remove(&extension_id, request.area, None),
+        });
+
+    match result {
+        Ok(items) => query.success(&Value::Object(items).to_string()),
+        Err(e) => query.failure(ERROR_FAILED, &e.to_string()),
+    }
+}
@@ -0,0 +1,584 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    Browser, ErrorCode, Extension, ExtensionHandler, ExtensionHandlerCallbacks, RequestContext,
+};
+use gpui::{
+    div, img, prelude::*, px, size, App as GpuiApp, Context, DismissEvent, Div, EventEmitter,
+    Global, Pixels, SharedString, Size, Stateful, Window,
+};
+use serde::Deserialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    settings::{self, Settings},
+    theme::ActiveTheme,
+};
+
+/// Size of an extension popup whose manifest doesn't give one.
+const DEFAULT_POPUP_SIZE: PopupSize = PopupSize {
+    width: 320,
+    height: 400,
+};
+
+/// The parts of an extension's `manifest.json` shown in the extensions panel,
+/// and its toolbar button.
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    version: String,
+    /// `browser_action` before Manifest V3.
+    #[serde(default, alias = "browser_action")]
+    action: Option<ManifestAction>,
+}
+
+/// An extension's toolbar button and the page it pops up.
+#[derive(Clone, Deserialize)]
+struct ManifestAction {
+    default_popup: Option<String>,
+    default_title: Option<String>,
+    default_icon: Option<ManifestIcon>,
+    /// Chrome sizes popups to their page, which windowless browsers can't,
+    /// so extensions say how big theirs is here.
+    default_popup_size: Option<PopupSize>,
+}
+
+/// A single icon, or icons by size like `{"16": "icon16.png"}`.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+enum ManifestIcon {
+    Path(String),
+    Sizes(BTreeMap<String, String>),
+}
+
+impl ManifestIcon {
+    /// The icon drawn at `size`, or the closest there is.
+    fn path(&self, size: u32) -> Option<&str> {
+        match self {
+            ManifestIcon::Path(path) => Some(path),
+            ManifestIcon::Sizes(sizes) => sizes
+                .iter()
+                .min_by_key(|(key, _)| key.parse::<u32>().unwrap_or(0).abs_diff(size))
+                .map(|(_, path)| path.as_str()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+struct PopupSize {
+    width: u32,
+    height: u32,
+}
+
+impl Manifest {
+    fn read(dir: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(dir.join("manifest.json"))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// An unpacked extension found in the extensions folder.
+#[derive(Clone)]
+pub struct ExtensionEntry {
+    path: PathBuf,
+    name: String,
+    version: String,
+    action: Option<ManifestAction>,
+    /// Why the extension couldn't be read or loaded.
+    error: Option<String>,
+    /// Set once CEF has loaded the extension.
+    extension: Option<Extension>,
+}
+
+impl ExtensionEntry {
+    fn status(&self, enabled: bool) -> SharedString {
+        match (&self.error, &self.extension) {
+            (Some(_), _) => t!("extensions.failed_to_load"),
+            (None, Some(_)) => t!("extensions.enabled"),
+            (None, None) if enabled => t!("extensions.loading"),
+            (None, None) => t!("extensions.disabled"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ExtensionsInner {
+    entries: Vec<ExtensionEntry>,
+    changed: bool,
+}
+
+/// Extensions in the extensions folder and whether CEF has loaded them.
+#[derive(Clone, Default)]
+pub struct Extensions(Arc<Mutex<ExtensionsInner>>);
+
+impl Global for Extensions {}
+
+impl Extensions {
+    fn update(&self, path: &Path, f: impl FnOnce(&mut ExtensionEntry)) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(entry) = inner.entries.iter_mut().find(|entry| entry.path == path) {
+            f(entry);
+            inner.changed = true;
+        }
+    }
+
+    fn entries(&self) -> Vec<ExtensionEntry> {
+        self.0.lock().unwrap().entries.clone()
+    }
+
+    fn entry(&self, path: &Path) -> Option<ExtensionEntry> {
+        self.0
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .find(|entry| entry.path == path)
+            .cloned()
+    }
+
+    /// Whether extensions loaded, failed or unloaded since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+/// Handles CEF's answer to loading, or unloading, the extension at `path`.
+/// CEF doesn't say which extension failed to load, so each load gets its own.
+struct ExtensionLoader {
+    extensions: Extensions,
+    path: PathBuf,
+}
+
+impl ExtensionHandlerCallbacks for ExtensionLoader {
+    fn on_extension_load_failed(&mut self, result: ErrorCode) {
+        self.extensions.update(&self.path, |entry| {
+            entry.error = Some(format!("{:?}", result));
+            entry.extension = None;
+        });
+    }
+
+    fn on_extension_loaded(&mut self, extension: Extension) {
+        self.extensions.update(&self.path, |entry| {
+            entry.error = None;
+            entry.extension = Some(extension);
+        });
+    }
+
+    fn on_extension_unloaded(&mut self, _extension: Extension) {
+        self.extensions
+            .update(&self.path, |entry| entry.extension = None);
+    }
+
+    fn get_active_browser(
+        &mut self,
+        _extension: Extension,
+        _browser: Browser,
+        _include_incognito: bool,
+    ) -> Option<Browser> {
+        None
+    }
+
+    fn can_access_browser(
+        &mut self,
+        _extension: Extension,
+        _browser: Browser,
+        include_incognito: bool,
+        _target_browser: Browser,
+    ) -> bool {
+        !include_incognito
+    }
+}
+
+/// Read the extensions in `dir`, one per subfolder.
+fn scan(dir: &Path) -> Vec<ExtensionEntry> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| {
+            let (name, version, action, error) = match Manifest::read(&path) {
+                Ok(manifest) => (manifest.name, manifest.version, manifest.action, None),
+                Err(e) => (
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    String::new(),
+                    None,
+                    Some(t!("extensions.invalid_manifest", error = e).to_string()),
+                ),
+            };
+
+            ExtensionEntry {
+                path,
+                name,
+                version,
+                action,
+                error,
+                extension: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+/// The toolbar button of a loaded extension with a popup.
+pub struct ExtensionAction {
+    pub path: PathBuf,
+    pub extension: Extension,
+    title: SharedString,
+    icon: Option<PathBuf>,
+    popup: String,
+    popup_size: PopupSize,
+}
+
+impl ExtensionAction {
+    /// The `chrome-extension://` URL of the popup page.
+    pub fn popup_url(&self) -> Option<String> {
+        let id = self.extension.get_identifier().ok()?;
+        Some(format!(
+            "chrome-extension://{}/{}",
+            id,
+            self.popup.trim_start_matches('/')
+        ))
+    }
+
+    pub fn popup_size(&self) -> Size<Pixels> {
+        size(
+            px(self.popup_size.width as f32),
+            px(self.popup_size.height as f32),
+        )
+    }
+
+    /// The button, showing the extension's icon or else its initial.
+    pub fn button(&self, open: bool, cx: &GpuiApp) -> Stateful<Div> {
+        let theme = cx.theme();
+        let id = SharedString::from(format!("extension-action-{}", self.path.display()));
+
+        div()
+            .id(id)
+            .flex()
+            .items_center()
+            .justify_center()
+            .size(px(20.))
+            .rounded_md()
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.text)
+            .when(open, |this| this.bg(theme.ghost_hover))
+            .hover(|this| this.bg(theme.ghost_hover))
+            .child(match &self.icon {
+                Some(icon) => img(icon.clone()).size(px(16.)).into_any_element(),
+                None => self
+                    .title
+                    .chars()
+                    .next()
+                    .unwrap_or('?')
+                    .to_uppercase()
+                    .to_string()
+                    .into_any_element(),
+            })
+    }
+}
+
+/// The toolbar buttons of the loaded extensions that have popups.
+pub fn actions(cx: &GpuiApp) -> Vec<ExtensionAction> {
+    cx.global::<Extensions>()
+        .entries()
+        .into_iter()
+        .filter_map(|entry| {
+            let extension = entry.extension?;
+            let action = entry.action?;
+            let popup = action.default_popup?;
+
+            Some(ExtensionAction {
+                title: action.default_title.unwrap_or(entry.name).into(),
+                icon: action
+                    .default_icon
+                    .as_ref()
+                    .and_then(|icon| icon.path(16))
+                    .map(|icon| entry.path.join(icon)),
+                popup,
+                popup_size: action.default_popup_size.unwrap_or(DEFAULT_POPUP_SIZE),
+                path: entry.path,
+                extension,
+            })
+        })
+        .collect()
+}
+
+/// Ask CEF to load the extension at `path`.
+fn load_extension(extensions: &Extensions, path: &Path) -> Result<()> {
+    let context = RequestContext::get_global_context()
+        .ok_or_else(|| anyhow!("No request context to load extensions into"))?;
+    let root_directory = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Extension path isn't valid UTF-8"))?;
+
+    let handler = ExtensionHandler::new(ExtensionLoader {
+        extensions: extensions.clone(),
+        path: path.to_path_buf(),
+    });
+
+    context.load_extension(root_directory, None, Some(handler))
+}
+
+/// Unload every extension, then scan the extensions folder from settings and
+/// load the extensions that are valid and not disabled.
+pub fn load_all(cx: &GpuiApp) {
+    let extensions = cx.global::<Extensions>().clone();
+    let settings = cx.global::<Settings>();
+
+    for entry in extensions.entries() {
+        if let Some(extension) = entry.extension {
+            extension.unload().ok();
+        }
+    }
+
+    let entries = scan(&settings.extensions_path);
+    {
+        let mut inner = extensions.0.lock().unwrap();
+        inner.entries = entries.clone();
+        inner.changed = true;
+    }
+
+    for entry in entries {
+        if entry.error.is_some() || settings.disabled_extensions.contains(&entry.path) {
+            continue;
+        }
+
+        if let Err(e) = load_extension(&extensions, &entry.path) {
+            eprintln!("Error loading extension {:?}: {}", entry.path, e);
+            extensions.update(&entry.path, |entry| entry.error = Some(e.to_string()));
+        }
+    }
+}
+
+/// Enable or disable the extension at `path`, remembering the choice.
+fn set_enabled(cx: &mut GpuiApp, path: &Path, enabled: bool) {
+    let extensions = cx.global::<Extensions>().clone();
+    let Some(entry) = extensions.entry(path) else {
+        return;
+    };
+
+    settings::update(cx, |settings| {
+        settings
+            .disabled_extensions
+            .retain(|disabled| disabled != path);
+        if !enabled {
+            settings.disabled_extensions.push(path.to_path_buf());
+        }
+    });
+
+    if enabled {
+        extensions.update(path, |entry| entry.error = None);
+        if let Err(e) = load_extension(&extensions, path) {
+            eprintln!("Error loading extension {:?}: {}", path, e);
+            extensions.update(path, |entry| entry.error = Some(e.to_string()));
+        }
+    } else if let Some(extension) = entry.extension {
+        if let Err(e) = extension.unload() {
+            eprintln!("Error unloading extension {:?}: {}", path, e);
+        }
+    } else {
+        extensions.update(path, |entry| entry.error = None);
+    }
+}
+
+/// Unload the extension at `path` and delete it from the extensions folder.
+fn remove(cx: &mut GpuiApp, path: &Path) {
+    let extensions = cx.global::<Extensions>().clone();
+
+    if let Some(extension) = extensions.entry(path).and_then(|entry| entry.extension) {
+        if let Err(e) = extension.unload() {
+            eprintln!("Error unloading extension {:?}: {}", path, e);
+        }
+    }
+
+    if let Err(e) = fs::remove_dir_all(path) {
+        eprintln!("Failed to remove {:?}: {}", path, e);
+        return;
+    }
+
+    {
+        let mut inner = extensions.0.lock().unwrap();
+        inner.entries.retain(|entry| entry.path != path);
+        inner.changed = true;
+    }
+
+    settings::update(cx, |settings| {
+        settings
+            .disabled_extensions
+            .retain(|disabled| disabled != path)
+    });
+}
+
+/// Modal listing the unpacked extensions and whether they're enabled.
+pub struct ExtensionsPanel;
+
+impl ExtensionsPanel {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for ExtensionsPanel {}
+
+impl Render for ExtensionsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let settings = cx.global::<Settings>().clone();
+        let entries = cx.global::<Extensions>().entries();
+        let folder: SharedString = settings.extensions_path.display().to_string().into();
+
+        let rows = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let enabled = !settings.disabled_extensions.contains(&entry.path);
+                let toggle_path = entry.path.clone();
+                let remove_path = entry.path.clone();
+
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .child(
+                                div().flex().gap_2().child(entry.name.clone()).child(
+                                    div()
+                                        .text_color(theme.text_placeholder)
+                                        .child(entry.version.clone()),
+                                ),
+                            )
+                            .child(
+                                div()
+                                    .text_color(match entry.error {
+                                        Some(_) => theme.error,
+                                        None => theme.text_disabled,
+                                    })
+                                    .child(match &entry.error {
+                                        Some(error) => {
+                                            format!("{}: {}", entry.status(enabled), error)
+                                        }
+                                        None => entry.status(enabled).to_string(),
+                                    }),
+                            ),
+                    )
+                    .child(
+                        button(
+                            ("extension-toggle", index),
+                            if enabled {
+                                t!("extensions.disable")
+                            } else {
+                                t!("extensions.enable")
+                            },
+                            false,
+                            cx,
+                        )
+                        .on_click(cx.listener(move |_, _, _, cx| {
+                            set_enabled(cx, &toggle_path, !enabled);
+                            cx.notify();
+                        })),
+                    )
+                    .child(
+                        button(
+                            ("extension-remove", index),
+                            t!("extensions.remove"),
+                            false,
+                            cx,
+                        )
+                        .on_click(cx.listener(move |_, _, _, cx| {
+                            remove(cx, &remove_path);
+                            cx.notify();
+                        })),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(440.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("extensions.title")))
+                    .child(
+                        div()
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.text_muted)
+                            .child(folder),
+                    )
+                    .child(
+                        div()
+                            .id("extensions-list")
+                            .flex()
+                            .flex_col()
+                            .max_h(px(320.))
+                            .overflow_y_scroll()
+                            .when(rows.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .py_2()
+                                        .text_color(theme.text_disabled)
+                                        .child(t!("extensions.empty")),
+                                )
+                            })
+                            .children(rows),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("extensions-reload", t!("extensions.reload"), false, cx)
+                                    .on_click(cx.listener(|_, _, _, cx| {
+                                        load_all(cx);
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                button("extensions-done", t!("extensions.done"), true, cx)
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    io::Cursor,
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    AuthCallback, Browser, Request, UrlRequest, UrlRequestClient, UrlRequestClientCallbacks,
+    UrlRequestStatus,
+};
+use gpui::{Global, Image, ImageFormat};
+use image::imageops::FilterType;
+use lru::LruCache;
+use resvg::{tiny_skia, usvg};
+
+use crate::incognito;
+
+/// Sizes each favicon is kept at: for standard and high density displays.
+const SMALL_SIZE: u32 = 16;
+const LARGE_SIZE: u32 = 32;
+
+/// Most favicons kept in memory. Older ones are read back from disk.
+const MAX_ENTRIES: usize = 200;
+
+/// A site's icon, scaled to both sizes tabs draw it at.
+#[derive(Clone)]
+pub struct Favicon {
+    small: Arc<Image>,
+    large: Arc<Image>,
+}
+
+impl Favicon {
+    /// The size for a display with `scale_factor` pixels per point.
+    pub fn at_scale(&self, scale_factor: f32) -> Arc<Image> {
+        match scale_factor > 1. {
+            true => self.large.clone(),
+            false => self.small.clone(),
+        }
+    }
+}
+
+struct FaviconCacheInner {
+    dir: PathBuf,
+    /// Favicon URL to icon, least recently drawn evicted first.
+    icons: LruCache<String, Favicon>,
+    /// Favicon URL of each tab's page, by browser identifier.
+    tabs: HashMap<i32, String>,
+    fetching: HashSet<String>,
+    changed: bool,
+}
+
+/// Icons of the pages open in tabs, fetched when CEF reports a page's favicon
+/// URL. Kept in memory up to `MAX_ENTRIES`, and on disk as PNG files named by
+/// a hash of the favicon URL.
+#[derive(Clone)]
+pub struct FaviconCache(Arc<Mutex<FaviconCacheInner>>);
+
+impl Global for FaviconCache {}
+
+impl FaviconCache {
+    pub fn load(dir: PathBuf) -> Self {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("Failed to create favicon cache {:?}: {}", dir, e);
+        }
+
+        Self(Arc::new(Mutex::new(FaviconCacheInner {
+            dir,
+            icons: LruCache::new(NonZeroUsize::new(MAX_ENTRIES).unwrap()),
+            tabs: HashMap::new(),
+            fetching: HashSet::new(),
+            changed: false,
+        })))
+    }
+
+    /// Use the first of the page's `icon_urls` for `browser`'s tab, fetching
+    /// it unless it's cached.
+    pub fn on_favicon_urlchange(&self, browser: &Browser, icon_urls: &[String]) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let Some(url) = icon_urls.first() else {
+            let mut inner = self.0.lock().unwrap();
+            inner.tabs.remove(&id);
+            inner.changed = true;
+            return;
+        };
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            inner.tabs.insert(id, url.clone());
+            inner.changed = true;
+
+            if inner.load(url) || !inner.fetching.insert(url.clone()) {
+                return;
+            }
+        }
+
+        self.fetch(browser, url);
+    }
+
+    /// Show the favicon a restored tab had before its page loads again.
+    pub fn restoring(&self, browser: &Browser, url: &str) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.load(url) {
+            inner.tabs.insert(id, url.to_string());
+        }
+    }
+
+    /// The favicon URL of `browser`'s page, if it has one.
+    pub fn url(&self, browser: &Browser) -> Option<String> {
+        let id = browser.get_identifier().ok()?;
+        self.0.lock().unwrap().tabs.get(&id).cloned()
+    }
+
+    /// The favicon of `browser`'s page, once it's been fetched.
+    pub fn favicon(&self, browser: &Browser) -> Option<Favicon> {
+        let id = browser.get_identifier().ok()?;
+        let mut inner = self.0.lock().unwrap();
+        let url = inner.tabs.get(&id)?.clone();
+
+        if !inner.load(&url) {
+            return None;
+        }
+        inner.icons.get(&url).cloned()
+    }
+
+    /// Forget a closed tab. Its icon stays cached for the next page using it.
+    pub fn remove(&self, browser: &Browser) {
+        if let Ok(id) = browser.get_identifier() {
+            self.0.lock().unwrap().tabs.remove(&id);
+        }
+    }
+
+    /// Whether a favicon arrived or a tab's changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Download the icon at `url` in `browser`'s request context, so
+    /// incognito tabs don't send their cookies elsewhere.
+    fn fetch(&self, browser: &Browser, url: &str) {
+        let request = Request::new();
+        if let Err(e) = request.set_url(url).and_then(|_| request.set_method("GET")) {
+            eprintln!("Error fetching favicon {}: {}", url, e);
+            self.0.lock().unwrap().fetching.remove(url);
+            return;
+        }
+
+        let client = UrlRequestClient::new(FaviconRequestClient {
+            cache: self.clone(),
+            url: url.to_string(),
+            persist: !incognito::is_incognito(browser),
+            body: Vec::new(),
+        });
+        let context = browser
+            .get_host()
+            .and_then(|host| host.get_request_context())
+            .ok();
+        UrlRequest::new(request, client, context);
+    }
+
+    fn insert(&self, url: String, body: &[u8], persist: bool) {
+        let result = decode(&url, body);
+
+        let mut inner = self.0.lock().unwrap();
+        inner.fetching.remove(&url);
+        let (small, large) = match result {
+            Ok(sizes) => sizes,
+            Err(e) => {
+                eprintln!("Error decoding favicon {}: {}", url, e);
+                return;
+            }
+        };
+
+        if persist {
+            for (size, png) in [(SMALL_SIZE, &small), (LARGE_SIZE, &large)] {
+                let path = inner.dir.join(file_name(&url, size));
+                if let Err(e) = fs::write(&path, png) {
+                    eprintln!("Failed to save favicon {:?}: {}", path, e);
+                }
+            }
+        }
+
+        inner.icons.put(
+            url,
+            Favicon {
+                small: Arc::new(Image::from_bytes(ImageFormat::Png, small)),
+                large: Arc::new(Image::from_bytes(ImageFormat::Png, large)),
+            },
+        );
+        inner.changed = true;
+    }
+}
+
+impl FaviconCacheInner {
+    /// Make sure the icon at `url` is in memory, reading it from disk if it
+    /// was evicted or saved by an earlier run. Returns whether it's there.
+    fn load(&mut self, url: &str) -> bool {
+        if self.icons.contains(url) {
+            return true;
+        }
+
+        let read = |size| fs::read(self.dir.join(file_name(url, size))).ok();
+        let (Some(small), Some(large)) = (read(SMALL_SIZE), read(LARGE_SIZE)) else {
+            return false;
+        };
+
+        self.icons.put(
+            url.to_string(),
+            Favicon {
+                small: Arc::new(Image::from_bytes(ImageFormat::Png, small)),
+                large: Arc::new(Image::from_bytes(ImageFormat::Png, large)),
+            },
+        );
+        true
+    }
+}
+
+/// Name of the file the icon at `url` is kept in at `size`.
+fn file_name(url: &str, size: u32) -> String {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+
+    format!("{:016x}-{}.png", hasher.finish(), size)
+}
+
+/// Decode an ICO, PNG or SVG favicon into PNGs at both sizes.
+fn decode(url: &str, body: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let is_svg = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .ends_with(".svg")
+        || body.trim_ascii_start().starts_with(b"<");
+    if is_svg {
+        let tree = usvg::Tree::from_data(body, &usvg::Options::default())?;
+        return Ok((
+            render_svg(&tree, SMALL_SIZE)?,
+            render_svg(&tree, LARGE_SIZE)?,
+        ));
+    }
+
+    let icon = image::load_from_memory(body)?;
+    let encode = |size| -> Result<Vec<u8>> {
+        let mut png = Cursor::new(Vec::new());
+        icon.resize(size, size, FilterType::Lanczos3)
+            .write_to(&mut png, image::ImageFormat::Png)?;
+        Ok(png.into_inner())
+    };
+
+    Ok((encode(SMALL_SIZE)?, encode(LARGE_SIZE)?))
+}
+
+fn render_svg(tree: &usvg::Tree, size: u32) -> Result<Vec<u8>> {
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size, size).ok_or_else(|| anyhow!("Favicon has no size"))?;
+    let scale = size as f32 / tree.size().width().max(tree.size().height());
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    Ok(pixmap.encode_png()?)
+}
+
+struct FaviconRequestClient {
+    cache: FaviconCache,
+    url: String,
+    /// Whether the icon may be written to disk, which it isn't for incognito
+    /// tabs.
+    persist: bool,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for FaviconRequestClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let url = std::mem::take(&mut self.url);
+        match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) => self.cache.insert(url, &self.body, self.persist),
+            _ => {
+                self.cache.0.lock().unwrap().fetching.remove(&url);
+            }
+        }
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
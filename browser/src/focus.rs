@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+use gpui::{App as GpuiApp, Global};
+
+use crate::BrowserState;
+
+#[derive(Default)]
+struct PageFocusInner {
+    got_focus: bool,
+    took_focus: bool,
+}
+
+/// Focus changes reported by the CEF focus handler for the UI to mirror.
+#[derive(Clone, Default)]
+pub struct PageFocus(Arc<Mutex<PageFocusInner>>);
+
+impl Global for PageFocus {}
+
+impl PageFocus {
+    /// The page received focus, e.g. from a script calling `focus()`.
+    pub fn got_focus(&self) {
+        self.0.lock().unwrap().got_focus = true;
+    }
+
+    /// The page handed focus back, e.g. by tabbing past its last element.
+    pub fn took_focus(&self) {
+        self.0.lock().unwrap().took_focus = true;
+    }
+
+    pub fn take_got_focus(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().got_focus)
+    }
+
+    pub fn take_took_focus(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().took_focus)
+    }
+}
+
+/// Tell CEF whether the page has keyboard focus.
+pub fn set_focus(cx: &GpuiApp, focus: bool) {
+    if let Some(host) = cx.global::<BrowserState>().host() {
+        if let Err(e) = host.set_focus(focus) {
+            eprintln!("Error setting browser focus: {}", e);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use cef_ui::{KeyEvent, KeyEventType};
+use gpui::{div, prelude::*, px, svg, App as GpuiApp, Div, KeyUpEvent, Keystroke, Stateful};
+
+use crate::{
+    extension_popup::{event_flags, windows_key_code},
+    focus,
+    locale::t,
+    theme::ActiveTheme,
+    BrowserState,
+};
+
+/// Whether the browser keeps `keystroke` in focus mode: quitting and closing
+/// the tab, so the user can't get stuck in a page.
+fn is_reserved(keystroke: &Keystroke) -> bool {
+    let modifiers = &keystroke.modifiers;
+    modifiers.secondary()
+        && !modifiers.shift
+        && !modifiers.alt
+        && matches!(keystroke.key.as_str(), "q" | "w")
+}
+
+/// Send a key to the page shown, as CEF would get it from the OS.
+fn send_key(cx: &GpuiApp, event_type: KeyEventType, keystroke: &Keystroke) -> bool {
+    let Some(key_code) = windows_key_code(keystroke) else {
+        return false;
+    };
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return false;
+    };
+
+    let send = |event_type, character| {
+        let event = KeyEvent {
+            event_type,
+            modifiers: event_flags(&keystroke.modifiers),
+            windows_key_code: key_code,
+            native_key_code: 0,
+            is_system_key: false,
+            character,
+            unmodified_character: character,
+            focus_on_editable_field: false,
+        };
+        if let Err(e) = host.send_key_event(event) {
+            eprintln!("Error sending key to page: {}", e);
+        }
+    };
+
+    send(event_type, 0);
+
+    // Typed text goes to the page as characters, shortcut or not, since
+    // the page is the one handling shortcuts.
+    if event_type == KeyEventType::RawKeyDown {
+        if let Some(key_char) = keystroke.key_char.as_ref() {
+            for character in key_char.encode_utf16() {
+                send(KeyEventType::Char, character);
+            }
+        }
+    }
+    true
+}
+
+/// Route key presses to the page instead of the browser's shortcuts while
+/// focus mode is on. Interceptors run before key bindings are matched, so
+/// stopping propagation here keeps GPUI from handling the key at all.
+pub fn intercept(cx: &mut GpuiApp) {
+    cx.intercept_keystrokes(|event, _, cx| {
+        if !cx.global::<BrowserState>().focus_mode || is_reserved(&event.keystroke) {
+            return;
+        }
+
+        if send_key(cx, KeyEventType::RawKeyDown, &event.keystroke) {
+            cx.stop_propagation();
+        }
+    })
+    .detach();
+}
+
+/// Let go of a key sent to the page in focus mode.
+pub fn key_up(event: &KeyUpEvent, cx: &mut GpuiApp) {
+    if !cx.global::<BrowserState>().focus_mode || is_reserved(&event.keystroke) {
+        return;
+    }
+
+    if send_key(cx, KeyEventType::KeyUp, &event.keystroke) {
+        cx.stop_propagation();
+    }
+}
+
+/// Turn focus mode on or off. Turning it on gives the page keyboard focus.
+pub fn toggle(cx: &mut GpuiApp) {
+    let state = cx.global_mut::<BrowserState>();
+    state.focus_mode = !state.focus_mode;
+
+    if state.focus_mode {
+        focus::set_focus(cx, true);
+    }
+}
+
+/// The padlock in the toolbar toggling focus mode, highlighted while it's on.
+pub fn icon(active: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("focus-mode")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(svg().path("lock.svg").size(px(14.0)).text_color(if active {
+            theme.accent
+        } else {
+            theme.text
+        }))
+}
+
+/// Badge over the top of the page while focus mode is on, saying where keys
+/// go and how to leave.
+pub fn badge(cx: &GpuiApp) -> Option<impl IntoElement> {
+    if !cx.global::<BrowserState>().focus_mode {
+        return None;
+    }
+    let theme = cx.theme();
+
+    Some(
+        div()
+            .absolute()
+            .top_2()
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1p5()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .bg(theme.accent)
+                    .text_xs()
+                    .text_color(theme.text_on_accent)
+                    .opacity(0.9)
+                    .child(
+                        svg()
+                            .path("lock.svg")
+                            .size(px(12.0))
+                            .text_color(theme.text_on_accent),
+                    )
+                    .child(t!("focus_mode.badge")),
+            ),
+    )
+}
@@ -0,0 +1,104 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::Browser;
+use gpui::{
+    ease_in_out, prelude::*, px, Animation, AnimationExt, App as GpuiApp, Div, ElementId, Global,
+};
+
+use crate::theme::ActiveTheme;
+
+/// How long the toolbar takes to slide away or back.
+const SLIDE_DURATION: Duration = Duration::from_millis(200);
+
+/// Pointer this close to the top of the screen brings the toolbar back.
+pub const REVEAL_EDGE: f32 = 4.;
+
+/// Height of the toolbar and tabs. The pointer moving below it hides them.
+pub const CHROME_HEIGHT: f32 = 40.;
+
+#[derive(Default)]
+struct PageFullscreenInner {
+    active: bool,
+    /// A change the page asked for, waiting for the window to follow.
+    pending: Option<bool>,
+}
+
+/// Whether a page went fullscreen through `requestFullscreen()`, shared with
+/// the display handler.
+#[derive(Clone, Default)]
+pub struct PageFullscreen(Arc<Mutex<PageFullscreenInner>>);
+
+impl Global for PageFullscreen {}
+
+impl PageFullscreen {
+    pub fn on_fullscreen_mode_change(&self, fullscreen: bool) {
+        let mut inner = self.0.lock().unwrap();
+        inner.active = fullscreen;
+        inner.pending = Some(fullscreen);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.0.lock().unwrap().active
+    }
+
+    /// Whether the window should enter or leave fullscreen, if the page asked
+    /// since the last call.
+    pub fn take_pending(&self) -> Option<bool> {
+        self.0.lock().unwrap().pending.take()
+    }
+}
+
+/// Ask the page to leave fullscreen. CEF reports the change back once the
+/// page has.
+pub fn exit(browser: &Browser) {
+    let Ok(Some(frame)) = browser.get_main_frame() else {
+        return;
+    };
+
+    let script = "document.fullscreenElement && document.exitFullscreen()";
+    if let Err(e) = frame.execute_java_script(script, "", 0) {
+        eprintln!("Error exiting fullscreen: {}", e);
+    }
+}
+
+/// Lay the toolbar over the page while it's fullscreen, sliding it up out of
+/// view unless `revealed`. `id` changes with each slide so it animates anew.
+pub fn chrome(
+    toolbar: Div,
+    id: impl Into<ElementId>,
+    fullscreen: bool,
+    revealed: bool,
+    cx: &GpuiApp,
+) -> impl IntoElement {
+    let theme = cx.theme();
+
+    // How far out of view the toolbar is, from 0 to 1.
+    let (from, to) = match (fullscreen, revealed) {
+        (false, _) => (0., 0.),
+        (true, true) => (1., 0.),
+        (true, false) => (0., 1.),
+    };
+
+    toolbar
+        .when(fullscreen, |this| {
+            this.absolute()
+                .left_0()
+                .right_0()
+                .pb(px(10.))
+                .bg(theme.background)
+        })
+        .with_animation(
+            id,
+            Animation::new(SLIDE_DURATION).with_easing(ease_in_out),
+            move |this, delta| {
+                let offset = from + (to - from) * delta;
+                match fullscreen {
+                    true => this.top(px(-(CHROME_HEIGHT + 10.) * offset)),
+                    false => this,
+                }
+            },
+        )
+}
@@ -0,0 +1,177 @@
+use gpui::{
+    px, App as GpuiApp, Global, NavigationDirection, ScrollDelta, ScrollWheelEvent, TouchPhase,
+    Window,
+};
+
+use crate::{settings::Settings, transitions::PageTransitions, BrowserState};
+
+/// Zoom levels, in CEF's 20% steps, per pixel of pinch.
+const ZOOM_PER_PIXEL: f64 = 0.01;
+
+/// Zoom levels for 25% and 500%, the most CEF will zoom out and in.
+const MIN_ZOOM_LEVEL: f64 = -7.6;
+const MAX_ZOOM_LEVEL: f64 = 8.8;
+
+/// Share of the zoom speed kept on each frame after the fingers lift.
+const ZOOM_FRICTION: f64 = 0.85;
+
+/// Zoom speed, in levels per frame, below which the zoom comes to rest.
+const MIN_ZOOM_VELOCITY: f64 = 0.001;
+
+/// What a trackpad gesture did.
+pub enum Gesture {
+    /// Nothing: the event should scroll the page as usual.
+    None,
+    /// The page was zoomed, and shouldn't also scroll.
+    Pinch,
+    /// The page went back or forward in its history.
+    Swipe,
+}
+
+/// State of the gesture in progress, and of the zoom still easing out after
+/// the last pinch.
+#[derive(Default)]
+pub struct Gestures {
+    /// Zoom levels applied on the last event or frame.
+    zoom_velocity: f64,
+    zooming: bool,
+    /// Horizontal distance swiped so far, in pixels.
+    swipe: f32,
+}
+
+impl Global for Gestures {}
+
+/// Handle a scroll event that may be part of a gesture. GPUI doesn't report
+/// magnification, so a pinch is read the way Chrome reads it: a scroll with
+/// control held, which is how precision touchpads deliver it.
+pub fn scroll_wheel(window: &mut Window, cx: &mut GpuiApp, event: &ScrollWheelEvent) -> Gesture {
+    if event.modifiers.control {
+        pinch(window, cx, event);
+        return Gesture::Pinch;
+    }
+
+    // Only trackpads report precise deltas; wheels can't swipe.
+    let ScrollDelta::Pixels(delta) = event.delta else {
+        return Gesture::None;
+    };
+
+    let threshold = cx.global::<Settings>().gesture_threshold as f32;
+    let gestures = cx.global_mut::<Gestures>();
+
+    match event.touch_phase {
+        TouchPhase::Started => gestures.swipe = 0.,
+        TouchPhase::Moved => {
+            // Leave mostly vertical scrolls to the page.
+            if delta.x.abs() > delta.y.abs() {
+                gestures.swipe += f32::from(delta.x);
+            }
+        }
+        TouchPhase::Ended => {
+            let swipe = std::mem::take(&mut gestures.swipe);
+            if swipe.abs() >= threshold {
+                // Fingers moving right pull the previous page into view.
+                let direction = match swipe > 0. {
+                    true => NavigationDirection::Back,
+                    false => NavigationDirection::Forward,
+                };
+
+                if navigate(cx, direction) {
+                    return Gesture::Swipe;
+                }
+            }
+        }
+    }
+
+    Gesture::None
+}
+
+/// Zoom the page along with the fingers, and let it glide to a stop once
+/// they lift.
+fn pinch(window: &mut Window, cx: &mut GpuiApp, event: &ScrollWheelEvent) {
+    let delta = f32::from(event.delta.pixel_delta(px(1.)).y) as f64 * ZOOM_PER_PIXEL;
+    let gestures = cx.global_mut::<Gestures>();
+
+    match event.touch_phase {
+        TouchPhase::Started => gestures.zoom_velocity = 0.,
+        TouchPhase::Moved => gestures.zoom_velocity = delta,
+        TouchPhase::Ended => {
+            if !gestures.zooming && gestures.zoom_velocity.abs() >= MIN_ZOOM_VELOCITY {
+                gestures.zooming = true;
+                window.on_next_frame(decelerate);
+            }
+            return;
+        }
+    }
+
+    zoom_by(cx, delta);
+}
+
+/// Keep zooming at a decreasing speed until the zoom comes to rest.
+fn decelerate(window: &mut Window, cx: &mut GpuiApp) {
+    let gestures = cx.global_mut::<Gestures>();
+    gestures.zoom_velocity *= ZOOM_FRICTION;
+
+    let velocity = gestures.zoom_velocity;
+    gestures.zooming = velocity.abs() >= MIN_ZOOM_VELOCITY;
+    if !gestures.zooming {
+        return;
+    }
+
+    window.on_next_frame(decelerate);
+    zoom_by(cx, velocity);
+}
+
+fn zoom_by(cx: &GpuiApp, delta: f64) {
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let result = host.get_zoom_level().and_then(|zoom_level| {
+        host.set_zoom_level((zoom_level + delta).clamp(MIN_ZOOM_LEVEL, MAX_ZOOM_LEVEL))
+    });
+
+    if let Err(e) = result {
+        eprintln!("Error zooming page: {}", e);
+    }
+}
+
+/// Go back or forward in the active tab, if it can. Returns whether it did.
+/// The page being left is kept to transition from once the new one paints.
+pub fn navigate(cx: &GpuiApp, direction: NavigationDirection) -> bool {
+    let state = cx.global::<BrowserState>();
+    if let Some(mock) = state.mock.as_ref() {
+        return match direction {
+            NavigationDirection::Back => mock.go_back(),
+            NavigationDirection::Forward => mock.go_forward(),
+        };
+    }
+    let Some(browser) = state.browser.as_ref() else {
+        return false;
+    };
+
+    let can_go = match direction {
+        NavigationDirection::Back => browser.can_go_back(),
+        NavigationDirection::Forward => browser.can_go_forward(),
+    };
+    if can_go.unwrap_or(false) {
+        cx.global::<PageTransitions>()
+            .begin(browser, direction, state.image.clone());
+    }
+
+    let result = match direction {
+        NavigationDirection::Back => browser
+            .can_go_back()
+            .and_then(|can| can.then(|| browser.go_back()).transpose()),
+        NavigationDirection::Forward => browser
+            .can_go_forward()
+            .and_then(|can| can.then(|| browser.go_forward()).transpose()),
+    };
+
+    match result {
+        Ok(navigated) => navigated.is_some(),
+        Err(e) => {
+            eprintln!("Error navigating: {}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::Browser;
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, DismissEvent, EventEmitter, Global, Window,
+};
+
+use crate::{button::button, locale::t, tabs, theme::ActiveTheme};
+
+/// How long a page has to show it's alive after being sent input.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Logged by the probe sent along with input. Only a page whose main thread
+/// is running gets to log it.
+const ALIVE_MESSAGE: &str = "__cef_alive__";
+
+#[derive(Default)]
+struct HangMonitorInner {
+    /// Browsers sent input that haven't painted or answered the probe since,
+    /// by id, with the number of the watch waiting on them.
+    waiting: HashMap<i32, u64>,
+    next_watch: u64,
+    /// The browser whose page stopped responding, while that's asked about.
+    unresponsive: Option<Browser>,
+    /// Browsers closed to kill their page, whose closing doesn't quit.
+    killed: Vec<i32>,
+    changed: bool,
+}
+
+/// Pages that stopped responding without their renderer crashing. A page
+/// that neither paints nor runs the probe within `TIMEOUT` of being sent input
+/// is taken for hung.
+#[derive(Clone, Default)]
+pub struct HangMonitor(Arc<Mutex<HangMonitorInner>>);
+
+impl Global for HangMonitor {}
+
+impl HangMonitor {
+    /// The page painted or answered the probe, so it isn't hung.
+    fn responded(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        inner.waiting.remove(&id);
+
+        let recovered = inner
+            .unresponsive
+            .as_ref()
+            .is_some_and(|unresponsive| unresponsive.get_identifier().ok() == Some(id));
+        if recovered {
+            inner.unresponsive = None;
+            inner.changed = true;
+        }
+    }
+
+    pub fn on_paint(&self, browser: &Browser) {
+        self.responded(browser);
+    }
+
+    /// Returns whether `message` is the probe's, which isn't shown.
+    pub fn on_console_message(&self, browser: &Browser, message: &str) -> bool {
+        if message != ALIVE_MESSAGE {
+            return false;
+        }
+
+        self.responded(browser);
+        true
+    }
+
+    /// Returns whether `browser` was closed to kill its page, so the browser
+    /// keeps running.
+    pub fn on_before_close(&self, browser: &Browser) -> bool {
+        let Ok(id) = browser.get_identifier() else {
+            return false;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        let killed = inner.killed.contains(&id);
+        inner.killed.retain(|killed| *killed != id);
+        killed
+    }
+
+    fn time_out(&self, browser: Browser, watch: u64) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.waiting.get(&id) != Some(&watch) {
+            return;
+        }
+
+        inner.waiting.remove(&id);
+        if inner.unresponsive.is_none() {
+            inner.unresponsive = Some(browser);
+            inner.changed = true;
+        }
+    }
+
+    /// The page that stopped responding, if one has.
+    pub fn unresponsive(&self) -> Option<Browser> {
+        self.0.lock().unwrap().unresponsive.clone()
+    }
+
+    /// Whether a page stopped or started responding again since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+/// Start watching the active page after sending it input, unless it's
+/// already being watched.
+pub fn watch(cx: &GpuiApp) {
+    let Some(browser) = cx.global::<crate::BrowserState>().browser.clone() else {
+        return;
+    };
+    let Ok(id) = browser.get_identifier() else {
+        return;
+    };
+
+    let monitor = cx.global::<HangMonitor>().clone();
+    let watch = {
+        let mut inner = monitor.0.lock().unwrap();
+        if inner.waiting.contains_key(&id) {
+            return;
+        }
+
+        let watch = inner.next_watch;
+        inner.next_watch += 1;
+        inner.waiting.insert(id, watch);
+        watch
+    };
+
+    // Input that changes nothing on screen doesn't paint, so the page is
+    // also asked to log something.
+    let probe = format!("console.log({:?})", ALIVE_MESSAGE);
+    if let Err(e) = browser
+        .get_main_frame()
+        .and_then(|frame| frame.map_or(Ok(()), |frame| frame.execute_java_script(&probe, "", 0)))
+    {
+        eprintln!("Error probing page: {}", e);
+    }
+
+    cx.spawn(async move |cx| {
+        cx.background_executor().timer(TIMEOUT).await;
+        monitor.time_out(browser, watch);
+    })
+    .detach();
+}
+
+/// Close the hung page's browser, which takes its renderer down with it,
+/// and load the page again in a new one in the same tab.
+fn kill(cx: &mut GpuiApp) {
+    let monitor = cx.global::<HangMonitor>().clone();
+    let Some(browser) = monitor.0.lock().unwrap().unresponsive.take() else {
+        return;
+    };
+
+    let url = browser
+        .get_main_frame()
+        .ok()
+        .flatten()
+        .and_then(|frame| frame.get_url().ok())
+        .unwrap_or_default();
+    let context = browser
+        .get_host()
+        .and_then(|host| host.get_request_context())
+        .ok();
+    let Some(replacement) = crate::new_browser_in_context(cx, &url, context) else {
+        return;
+    };
+    if !tabs::replace(cx, &browser, replacement) {
+        return;
+    }
+
+    if let Ok(id) = browser.get_identifier() {
+        monitor.0.lock().unwrap().killed.push(id);
+    }
+    if let Err(e) = browser.get_host().and_then(|host| host.close_browser(true)) {
+        eprintln!("Error killing page: {}", e);
+    }
+}
+
+/// Asks what to do about a page that stopped responding: wait for it, or
+/// kill it and load it again.
+pub struct UnresponsiveDialog {
+    url: String,
+}
+
+impl UnresponsiveDialog {
+    pub fn new(browser: &Browser) -> Self {
+        let url = browser
+            .get_main_frame()
+            .ok()
+            .flatten()
+            .and_then(|frame| frame.get_url().ok())
+            .unwrap_or_default();
+
+        Self { url }
+    }
+
+    /// Give the page more time. It's watched again on the next input.
+    fn wait(&mut self, cx: &mut Context<Self>) {
+        cx.global::<HangMonitor>().0.lock().unwrap().unresponsive = None;
+        cx.emit(DismissEvent);
+    }
+
+    fn kill(&mut self, cx: &mut Context<Self>) {
+        kill(cx);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for UnresponsiveDialog {}
+
+impl Render for UnresponsiveDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("hang.title")))
+                    .child(
+                        div()
+                            .text_color(theme.text_secondary)
+                            .child(t!("hang.description")),
+                    )
+                    .when(!self.url.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .overflow_hidden()
+                                .text_ellipsis()
+                                .text_color(theme.text_placeholder)
+                                .child(self.url.clone()),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("hang-wait", t!("hang.wait"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.wait(cx))),
+                            )
+                            .child(
+                                button("hang-kill", t!("hang.kill"), true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.kill(cx))),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Browser, Frame};
+use chrono::Local;
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+
+use crate::incognito;
+
+/// Most pages kept; the ones visited longest ago are forgotten first.
+const HISTORY_LIMIT: usize = 5000;
+
+/// A page the user has visited.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: String,
+    pub visits: u32,
+    /// Unix time of the last visit, in seconds.
+    pub last_visit: i64,
+}
+
+struct HistoryInner {
+    path: PathBuf,
+    /// Oldest visit first.
+    entries: Vec<HistoryEntry>,
+}
+
+/// Pages visited outside incognito tabs, persisted to a JSON file. Recorded
+/// by the CEF handlers and searched by the omnibox.
+#[derive(Clone)]
+pub struct History(Arc<Mutex<HistoryInner>>);
+
+impl Global for History {}
+
+impl History {
+    /// Load the history from disk, starting empty if the file is missing or
+    /// invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse history {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self(Arc::new(Mutex::new(HistoryInner { path, entries })))
+    }
+
+    /// Most recently visited first.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let inner = self.0.lock().unwrap();
+        inner.entries.iter().rev().cloned().collect()
+    }
+
+    /// Count a visit to the page that finished loading in `frame`.
+    pub fn on_load_end(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) || incognito::is_incognito(browser) {
+            return;
+        }
+
+        let Ok(url) = frame.get_url() else {
+            return;
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return;
+        }
+
+        // The title usually arrives while the page is still loading.
+        let title = browser
+            .get_host()
+            .and_then(|host| host.get_visible_navigation_entry())
+            .and_then(|entry| entry.get_title())
+            .ok()
+            .flatten()
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| url.clone());
+
+        let mut inner = self.0.lock().unwrap();
+        let entry = match inner.entries.iter().position(|entry| entry.url == url) {
+            Some(index) => inner.entries.remove(index),
+            None => HistoryEntry {
+                url,
+                title: String::new(),
+                visits: 0,
+                last_visit: 0,
+            },
+        };
+
+        inner.entries.push(HistoryEntry {
+            title,
+            visits: entry.visits + 1,
+            last_visit: Local::now().timestamp(),
+            ..entry
+        });
+
+        let excess = inner.entries.len().saturating_sub(HISTORY_LIMIT);
+        inner.entries.drain(..excess);
+        inner.changed();
+    }
+
+    /// Name the page shown in `browser` after its title.
+    pub fn on_title_change(&self, browser: &Browser, title: &str) {
+        let url = browser
+            .get_main_frame()
+            .ok()
+            .flatten()
+            .and_then(|frame| frame.get_url().ok());
+        let Some(url) = url else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        let Some(entry) = inner.entries.iter_mut().find(|entry| entry.url == url) else {
+            return;
+        };
+
+        if entry.title != title {
+            entry.title = title.to_string();
+            inner.changed();
+        }
+    }
+}
+
+impl HistoryInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    fn changed(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("Failed to save history {:?}: {}", self.path, e);
+        }
+    }
+}
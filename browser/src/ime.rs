@@ -0,0 +1,221 @@
+use std::{
+    ops::Range as StdRange,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Range, Rect, TextInputMode};
+use gpui::{
+    point, px, size, App as GpuiApp, Bounds, Context, EntityInputHandler, Global, Pixels,
+    UTF16Selection, Window,
+};
+
+use crate::BrowserState;
+
+struct ImeStateInner {
+    input_mode: TextInputMode,
+    selected_range: Range,
+    character_bounds: Vec<Rect>,
+}
+
+impl Default for ImeStateInner {
+    fn default() -> Self {
+        Self {
+            input_mode: TextInputMode::None,
+            selected_range: Range { from: 0, to: 0 },
+            character_bounds: Vec::new(),
+        }
+    }
+}
+
+/// Composition state reported by the render handler.
+#[derive(Clone, Default)]
+pub struct ImeState(Arc<Mutex<ImeStateInner>>);
+
+impl Global for ImeState {}
+
+impl ImeState {
+    /// The composition moved, with the bounds of each of its characters in
+    /// view coordinates.
+    pub fn set_composition_range(&self, selected_range: Range, character_bounds: &[Rect]) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.selected_range = selected_range;
+        inner.character_bounds = character_bounds.to_vec();
+    }
+
+    /// A field was focused or blurred in the page. `TextInputMode::None`
+    /// means nothing editable has focus.
+    pub fn set_input_mode(&self, input_mode: TextInputMode) {
+        self.0.lock().unwrap().input_mode = input_mode;
+    }
+
+    /// The kind of text the focused element in the page takes.
+    pub fn input_mode(&self) -> TextInputMode {
+        self.0.lock().unwrap().input_mode
+    }
+
+    /// Whether the focused element in the page accepts text.
+    pub fn is_editable(&self) -> bool {
+        self.0.lock().unwrap().input_mode != TextInputMode::None
+    }
+}
+
+/// Bridges the platform input method to the page's focused text field.
+#[derive(Default)]
+pub struct PageIme {
+    composition: Option<String>,
+}
+
+impl PageIme {
+    /// Drop any text being composed, e.g. when the page loses focus.
+    pub fn cancel(&mut self, cx: &GpuiApp) {
+        if self.composition.take().is_none() {
+            return;
+        }
+
+        if let Some(host) = cx.global::<BrowserState>().host() {
+            if let Err(e) = host.ime_cancel_composition() {
+                eprintln!("Error cancelling composition: {}", e);
+            }
+        }
+    }
+}
+
+impl EntityInputHandler for PageIme {
+    fn text_for_range(
+        &mut self,
+        range: StdRange<usize>,
+        adjusted_range: &mut Option<StdRange<usize>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<String> {
+        // Only the composition is known here, not the field's contents.
+        let utf16 = self
+            .composition
+            .as_ref()?
+            .encode_utf16()
+            .collect::<Vec<_>>();
+        let range = range.start.min(utf16.len())..range.end.min(utf16.len());
+
+        *adjusted_range = Some(range.clone());
+        Some(String::from_utf16_lossy(&utf16[range]))
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        let selected_range = cx.global::<ImeState>().0.lock().unwrap().selected_range;
+
+        Some(UTF16Selection {
+            range: selected_range.from as usize..selected_range.to as usize,
+            reversed: false,
+        })
+    }
+
+    fn marked_text_range(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<StdRange<usize>> {
+        self.composition
+            .as_ref()
+            .map(|composition| 0..composition.encode_utf16().count())
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.composition.take().is_none() {
+            return;
+        }
+
+        if let Some(host) = cx.global::<BrowserState>().host() {
+            if let Err(e) = host.ime_finish_composing_text(false) {
+                eprintln!("Error finishing composition: {}", e);
+            }
+        }
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range: Option<StdRange<usize>>,
+        text: &str,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.composition = None;
+
+        if let Some(host) = cx.global::<BrowserState>().host() {
+            if let Err(e) = host.ime_commit_text(text, range.map(to_cef_range), 0) {
+                eprintln!("Error committing text: {}", e);
+            }
+        }
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range: Option<StdRange<usize>>,
+        new_text: &str,
+        new_selected_range: Option<StdRange<usize>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.composition = Some(new_text.to_string()).filter(|text| !text.is_empty());
+
+        let Some(host) = cx.global::<BrowserState>().host() else {
+            return;
+        };
+
+        let result = if self.composition.is_some() {
+            host.ime_set_composition(
+                Some(new_text),
+                None,
+                range.map(to_cef_range),
+                new_selected_range.map(to_cef_range),
+            )
+        } else {
+            host.ime_cancel_composition()
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error updating composition: {}", e);
+        }
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: StdRange<usize>,
+        element_bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        let inner = cx.global::<ImeState>().0.lock().unwrap();
+        let rect = inner
+            .character_bounds
+            .get(range_utf16.start)
+            .or(inner.character_bounds.last())?;
+
+        // Place the candidate window under the composition.
+        Some(Bounds::new(
+            element_bounds.origin + point(px(rect.x as f32), px(rect.y as f32)),
+            size(px(rect.width as f32), px(rect.height as f32)),
+        ))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        _point: gpui::Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        None
+    }
+}
+
+fn to_cef_range(range: StdRange<usize>) -> Range {
+    Range {
+        from: range.start as u32,
+        to: range.end as u32,
+    }
+}
@@ -0,0 +1,70 @@
+use cef_ui::{Browser, CompletionCallback, RequestContext, RequestContextSettings};
+use gpui::{App as GpuiApp, Global};
+
+use crate::{settings::Settings, tabs, tabs::TabManager};
+
+/// The request context incognito tabs share. It keeps cookies, cache and
+/// storage in memory, apart from the normal tabs, and is created with the
+/// first incognito tab.
+#[derive(Default)]
+pub struct Incognito {
+    context: Option<RequestContext>,
+}
+
+impl Global for Incognito {}
+
+/// Whether `browser` was created in an incognito tab, or opened from one.
+/// Unlike the contexts of isolated sites, the incognito one has no cache path.
+pub fn is_incognito(browser: &Browser) -> bool {
+    browser
+        .get_host()
+        .and_then(|host| host.get_request_context())
+        .and_then(|context| Ok(!context.is_global()? && context.get_cache_path()?.is_empty()))
+        .unwrap_or(false)
+}
+
+/// The incognito request context, created if there isn't one yet.
+fn context(cx: &mut GpuiApp) -> Option<RequestContext> {
+    let incognito = cx.global_mut::<Incognito>();
+    if incognito.context.is_none() {
+        // Without a cache path nothing is written to disk.
+        let settings = RequestContextSettings::new().persist_session_cookies(false);
+        incognito.context = RequestContext::create_context(&settings, None);
+    }
+
+    incognito.context.clone()
+}
+
+/// Open an incognito tab showing the homepage.
+pub fn new_tab(cx: &mut GpuiApp) {
+    let Some(context) = context(cx) else {
+        eprintln!("Error creating incognito request context");
+        return;
+    };
+
+    let homepage = cx.global::<Settings>().homepage.clone();
+    if let Some(browser) = crate::new_browser_in_context(cx, &homepage, Some(context)) {
+        tabs::add(cx, browser, true);
+    }
+}
+
+/// Shut the incognito context down once its last tab has closed, dropping
+/// everything the tabs stored.
+pub fn close_if_unused(cx: &mut GpuiApp) {
+    let in_use = cx
+        .global::<TabManager>()
+        .tabs()
+        .iter()
+        .any(|tab| tab.incognito);
+    if in_use {
+        return;
+    }
+
+    let Some(context) = cx.global_mut::<Incognito>().context.take() else {
+        return;
+    };
+
+    if let Err(e) = context.close_all_connections(CompletionCallback::new(|| {})) {
+        eprintln!("Error closing incognito connections: {}", e);
+    }
+}
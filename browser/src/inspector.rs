@@ -0,0 +1,329 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{
+    Browser, DevToolsMessageObserver, DevToolsMessageObserverCallbacks, DictionaryValue, Point,
+    Registration,
+};
+use gpui::{div, prelude::*, px, rgba, svg, App as GpuiApp, CursorStyle, Div, Global, Stateful};
+use serde::Deserialize;
+
+use crate::{theme::ActiveTheme, BrowserState};
+
+/// Message ids of the DevTools calls the picker makes, so their results can
+/// be told apart from other observers' calls.
+const NODE_FOR_LOCATION_ID: i32 = 0x7069_0001;
+const BOX_MODEL_ID: i32 = 0x7069_0002;
+
+/// Colors of the highlighted box model areas, as in Chromium's inspector.
+const MARGIN_COLOR: u32 = 0xf6b26ba8;
+const BORDER_COLOR: u32 = 0xffe599a8;
+const PADDING_COLOR: u32 = 0x93c47d8c;
+const CONTENT_COLOR: u32 = 0x6fa8dca8;
+
+/// A quad of the box model as DevTools reports it: four corners clockwise
+/// from the top left, in CSS pixels.
+type Quad = [f64; 8];
+
+#[derive(Clone, Copy, Deserialize)]
+struct BoxModel {
+    content: Quad,
+    padding: Quad,
+    border: Quad,
+    margin: Quad,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeForLocation {
+    backend_node_id: i32,
+}
+
+#[derive(Deserialize)]
+struct BoxModelResult {
+    model: BoxModel,
+}
+
+#[derive(Default)]
+struct ElementPickerInner {
+    active: bool,
+    /// The node under the pointer and its box model, once DevTools reports it.
+    node: Option<i32>,
+    highlight: Option<BoxModel>,
+    /// View pixels per CSS pixel of the page being picked from.
+    zoom: f64,
+    /// The observer on the page being picked from, by browser id.
+    observer: Option<(i32, Registration)>,
+    changed: bool,
+}
+
+/// Element picker of the toolbar, highlighting the element under the pointer
+/// and opening DevTools on the one clicked.
+#[derive(Clone, Default)]
+pub struct ElementPicker(Arc<Mutex<ElementPickerInner>>);
+
+impl Global for ElementPicker {}
+
+/// Receives the hovered node and its box model for the picker.
+struct PickerObserver {
+    picker: ElementPicker,
+}
+
+impl DevToolsMessageObserverCallbacks for PickerObserver {
+    fn on_dev_tools_message(&mut self, _browser: Browser, _message: &[u8]) -> bool {
+        false
+    }
+
+    fn on_dev_tools_method_result(
+        &mut self,
+        browser: Browser,
+        message_id: i32,
+        success: bool,
+        result: &[u8],
+    ) {
+        match message_id {
+            NODE_FOR_LOCATION_ID => {
+                let node = success
+                    .then(|| serde_json::from_slice::<NodeForLocation>(result).ok())
+                    .flatten()
+                    .map(|node| node.backend_node_id);
+                self.picker.on_node(&browser, node);
+            }
+            BOX_MODEL_ID => {
+                let model = success
+                    .then(|| serde_json::from_slice::<BoxModelResult>(result).ok())
+                    .flatten()
+                    .map(|result| result.model);
+                self.picker.on_box_model(model);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_dev_tools_event(&mut self, _browser: Browser, _method: &str, _params: &[u8]) {}
+
+    fn on_dev_tools_agent_attached(&mut self, _browser: Browser) {}
+
+    fn on_dev_tools_agent_detached(&mut self, _browser: Browser) {}
+}
+
+impl ElementPicker {
+    pub fn is_active(&self) -> bool {
+        self.0.lock().unwrap().active
+    }
+
+    /// Start or stop picking from the current page.
+    pub fn toggle(&self, cx: &GpuiApp) {
+        match self.is_active() {
+            true => self.stop(),
+            false => {
+                if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+                    self.start(browser);
+                }
+            }
+        }
+    }
+
+    fn start(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let Ok(host) = browser.get_host() else {
+            return;
+        };
+
+        let observer = PickerObserver {
+            picker: self.clone(),
+        };
+        let registration =
+            match host.add_dev_tools_message_observer(DevToolsMessageObserver::new(observer)) {
+                Ok(Some(registration)) => registration,
+                Ok(None) => return,
+                Err(e) => {
+                    eprintln!("Error observing DevTools messages: {}", e);
+                    return;
+                }
+            };
+
+        // Each zoom level is 20% larger than the one below it.
+        let zoom = host
+            .get_zoom_level()
+            .map(|zoom_level| 1.2f64.powf(zoom_level))
+            .unwrap_or(1.);
+
+        let mut inner = self.0.lock().unwrap();
+        inner.active = true;
+        inner.node = None;
+        inner.highlight = None;
+        inner.zoom = zoom;
+        inner.observer = Some((id, registration));
+        inner.changed = true;
+    }
+
+    /// Leave picker mode, removing the highlight.
+    pub fn stop(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.active = false;
+        inner.node = None;
+        inner.highlight = None;
+        inner.observer = None;
+        inner.changed = true;
+    }
+
+    /// Find the element at `point`, in view coordinates, to highlight it.
+    pub fn hover(&self, cx: &GpuiApp, point: Point) {
+        let Some(browser) = cx.global::<BrowserState>().browser.as_ref() else {
+            return;
+        };
+
+        // Another tab took over since picking started.
+        let observed = self.0.lock().unwrap().observer.as_ref().map(|(id, _)| *id);
+        if browser.get_identifier().ok() != observed {
+            self.stop();
+            return;
+        }
+
+        let zoom = self.0.lock().unwrap().zoom;
+        let params = DictionaryValue::new();
+        let set = params
+            .set_int("x", (point.x as f64 / zoom) as i32)
+            .and_then(|_| params.set_int("y", (point.y as f64 / zoom) as i32))
+            .and_then(|_| params.set_bool("ignorePointerEventsNone", true));
+        if let Err(e) = set.and_then(|_| {
+            browser.get_host()?.execute_dev_tools_method(
+                NODE_FOR_LOCATION_ID,
+                "DOM.getNodeForLocation",
+                Some(params),
+            )
+        }) {
+            eprintln!("Error finding the hovered element: {}", e);
+        }
+    }
+
+    /// Ask for the box model of the hovered node, unless it's highlighted
+    /// already.
+    fn on_node(&self, browser: &Browser, node: Option<i32>) {
+        {
+            let mut inner = self.0.lock().unwrap();
+            if !inner.active || inner.node == node {
+                return;
+            }
+
+            inner.node = node;
+            if node.is_none() {
+                inner.highlight = None;
+                inner.changed = true;
+            }
+        }
+
+        let Some(node) = node else {
+            return;
+        };
+
+        let params = DictionaryValue::new();
+        if let Err(e) = params.set_int("backendNodeId", node).and_then(|_| {
+            browser.get_host()?.execute_dev_tools_method(
+                BOX_MODEL_ID,
+                "DOM.getBoxModel",
+                Some(params),
+            )
+        }) {
+            eprintln!("Error measuring the hovered element: {}", e);
+        }
+    }
+
+    fn on_box_model(&self, model: Option<BoxModel>) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.active {
+            inner.highlight = model;
+            inner.changed = true;
+        }
+    }
+
+    /// Whether the picker started, stopped or moved its highlight since the
+    /// last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// The highlight over the element under the pointer, in view coordinates.
+    pub fn highlight(&self) -> Option<impl IntoElement> {
+        let inner = self.0.lock().unwrap();
+        let model = inner.highlight?;
+        let scale = inner.zoom;
+
+        Some(
+            div()
+                .absolute()
+                .inset_0()
+                .child(ring(model.margin, model.border, scale, MARGIN_COLOR))
+                .child(ring(model.border, model.padding, scale, BORDER_COLOR))
+                .child(ring(model.padding, model.content, scale, PADDING_COLOR))
+                .child(area(model.content, scale).bg(rgba(CONTENT_COLOR))),
+        )
+    }
+}
+
+/// The bounds of `quad` as left, top, right and bottom, in view coordinates.
+fn bounds(quad: Quad, scale: f64) -> [f32; 4] {
+    let xs = [quad[0], quad[2], quad[4], quad[6]];
+    let ys = [quad[1], quad[3], quad[5], quad[7]];
+    let min = |values: [f64; 4]| values.into_iter().fold(f64::INFINITY, f64::min);
+    let max = |values: [f64; 4]| values.into_iter().fold(f64::NEG_INFINITY, f64::max);
+
+    [min(xs), min(ys), max(xs), max(ys)].map(|value| (value * scale) as f32)
+}
+
+/// A box over the bounds of `quad`.
+fn area(quad: Quad, scale: f64) -> Div {
+    let [left, top, right, bottom] = bounds(quad, scale);
+
+    div()
+        .absolute()
+        .left(px(left))
+        .top(px(top))
+        .w(px(right - left))
+        .h(px(bottom - top))
+}
+
+/// The part of `outer` around `inner`, drawn as the borders of a box.
+fn ring(outer: Quad, inner: Quad, scale: f64, color: u32) -> Div {
+    let [left, top, right, bottom] = bounds(outer, scale);
+    let [inner_left, inner_top, inner_right, inner_bottom] = bounds(inner, scale);
+
+    area(outer, scale)
+        .border_color(rgba(color))
+        .border_l(px(inner_left - left))
+        .border_t(px(inner_top - top))
+        .border_r(px(right - inner_right))
+        .border_b(px(bottom - inner_bottom))
+}
+
+/// The toolbar button toggling the picker, tinted while it's active.
+pub fn icon(active: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("element-picker")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(
+            svg()
+                .path("square-dashed-mouse-pointer.svg")
+                .size(px(14.0))
+                .text_color(if active { theme.accent } else { theme.text }),
+        )
+}
+
+/// Transparent layer over the page catching the pointer while picking.
+pub fn overlay() -> Stateful<Div> {
+    div()
+        .id("element-picker-overlay")
+        .absolute()
+        .inset_0()
+        .cursor(CursorStyle::Crosshair)
+}
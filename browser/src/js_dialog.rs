@@ -0,0 +1,232 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{JsDialogCallback, JsDialogType};
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, Entity, EventEmitter, Focusable, Global,
+    SharedString, Subscription, Window,
+};
+
+use crate::{
+    button::button,
+    locale::t,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+/// What the page is asking for.
+pub enum JsDialogKind {
+    Alert,
+    Confirm,
+    Prompt(String),
+    BeforeUnload { is_reload: bool },
+}
+
+/// A JavaScript dialog waiting for the user.
+pub struct JsDialogRequest {
+    kind: JsDialogKind,
+    origin: Option<String>,
+    message: String,
+    callback: JsDialogCallback,
+}
+
+#[derive(Default)]
+struct JsDialogsInner {
+    pending: VecDeque<JsDialogRequest>,
+    reset: bool,
+}
+
+/// JavaScript dialogs queued by the CEF handler for the UI to show.
+#[derive(Clone, Default)]
+pub struct JsDialogs(Arc<Mutex<JsDialogsInner>>);
+
+impl Global for JsDialogs {}
+
+impl JsDialogs {
+    /// Queue an `alert`, `confirm` or `prompt` call.
+    pub fn request(
+        &self,
+        dialog_type: JsDialogType,
+        origin: Option<String>,
+        message: Option<String>,
+        default_prompt_text: Option<String>,
+        callback: JsDialogCallback,
+    ) {
+        let kind = match dialog_type {
+            JsDialogType::Alert => JsDialogKind::Alert,
+            JsDialogType::Confirm => JsDialogKind::Confirm,
+            JsDialogType::Prompt => JsDialogKind::Prompt(default_prompt_text.unwrap_or_default()),
+        };
+
+        self.push(JsDialogRequest {
+            kind,
+            origin,
+            message: message.unwrap_or_default(),
+            callback,
+        });
+    }
+
+    /// Queue a "leave site?" confirmation.
+    pub fn request_before_unload(&self, is_reload: bool, callback: JsDialogCallback) {
+        self.push(JsDialogRequest {
+            kind: JsDialogKind::BeforeUnload { is_reload },
+            origin: None,
+            message: String::new(),
+            callback,
+        });
+    }
+
+    fn push(&self, request: JsDialogRequest) {
+        self.0.lock().unwrap().pending.push_back(request);
+    }
+
+    /// CEF cancelled all dialogs, e.g. because the page navigated away.
+    pub fn reset(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.pending.clear();
+        inner.reset = true;
+    }
+
+    pub fn take_pending(&self) -> Option<JsDialogRequest> {
+        self.0.lock().unwrap().pending.pop_front()
+    }
+
+    pub fn take_reset(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().reset)
+    }
+}
+
+/// Modal dialog for `alert`, `confirm`, `prompt` and `beforeunload`.
+pub struct JsDialog {
+    request: JsDialogRequest,
+    input: Option<Entity<TextInput>>,
+    _subscription: Option<Subscription>,
+}
+
+impl JsDialog {
+    pub fn new(request: JsDialogRequest, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let input = match &request.kind {
+            JsDialogKind::Prompt(default) => Some(cx.new(|cx| TextInput::new(default.clone(), cx))),
+            _ => None,
+        };
+        let subscription = input.as_ref().map(|input| {
+            window.focus(&input.focus_handle(cx));
+
+            cx.subscribe(input, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.close(true, cx),
+                TextInputEvent::Cancel => this.close(false, cx),
+            })
+        });
+
+        Self {
+            request,
+            input,
+            _subscription: subscription,
+        }
+    }
+
+    fn close(&mut self, success: bool, cx: &mut Context<Self>) {
+        let user_input = self
+            .input
+            .as_ref()
+            .filter(|_| success)
+            .map(|input| input.read(cx).text().to_string());
+
+        if let Err(e) = self.request.callback.cont(success, user_input.as_deref()) {
+            eprintln!("Error closing dialog: {}", e);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for JsDialog {}
+
+impl Render for JsDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let (title, message, confirm, cancel): (
+            SharedString,
+            SharedString,
+            SharedString,
+            Option<SharedString>,
+        ) = match &self.request.kind {
+            JsDialogKind::BeforeUnload { is_reload } => (
+                if *is_reload {
+                    t!("js_dialog.reload_site")
+                } else {
+                    t!("js_dialog.leave_site")
+                },
+                t!("js_dialog.unsaved_changes"),
+                if *is_reload {
+                    t!("js_dialog.reload")
+                } else {
+                    t!("js_dialog.leave")
+                },
+                Some(t!("js_dialog.stay")),
+            ),
+            kind => (
+                t!(
+                    "js_dialog.says",
+                    origin = self
+                        .request
+                        .origin
+                        .clone()
+                        .unwrap_or_else(|| t!("js_dialog.this_page").to_string())
+                ),
+                self.request.message.clone().into(),
+                t!("js_dialog.ok"),
+                match kind {
+                    JsDialogKind::Alert => None,
+                    _ => Some(t!("js_dialog.cancel")),
+                },
+            ),
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(title))
+                    .child(div().text_color(theme.text_secondary).child(message))
+                    .when_some(self.input.clone(), |this, input| this.child(input))
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .when_some(cancel, |this, cancel| {
+                                this.child(
+                                    button("js-dialog-cancel", cancel, false, cx).on_click(
+                                        cx.listener(|this, _, _, cx| this.close(false, cx)),
+                                    ),
+                                )
+                            })
+                            .child(
+                                button("js-dialog-confirm", confirm, true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.close(true, cx))),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::Frame;
+use gpui::{
+    div, prelude::*, px, relative, App as GpuiApp, Context, DismissEvent, EventEmitter, Global,
+    SharedString, Window,
+};
+
+use crate::{button::button, locale::t, message_router::Query, theme::ActiveTheme};
+
+/// Reports `DOMContentLoaded` of the first page as a `lifecycle.domReady`
+/// query, right away if the document already got that far. Relies on
+/// `window.cefQuery`.
+const DOM_READY_SCRIPT: &str = r#"(() => {
+  const report = () => window.cefQuery({ request: "lifecycle.domReady" });
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", report, { once: true });
+  } else {
+    report();
+  }
+})();"#;
+
+/// A step of starting up, recorded the first time it happens.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    CefInitStart,
+    CefInitDone,
+    BrowserCreated,
+    FirstPaint,
+    FirstDomReady,
+    LoadComplete,
+    BackgroundReady,
+}
+
+impl LifecycleEvent {
+    const ALL: [LifecycleEvent; 7] = [
+        LifecycleEvent::CefInitStart,
+        LifecycleEvent::CefInitDone,
+        LifecycleEvent::BrowserCreated,
+        LifecycleEvent::FirstPaint,
+        LifecycleEvent::FirstDomReady,
+        LifecycleEvent::LoadComplete,
+        LifecycleEvent::BackgroundReady,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            LifecycleEvent::CefInitStart => t!("lifecycle.cef_init_start"),
+            LifecycleEvent::CefInitDone => t!("lifecycle.cef_init_done"),
+            LifecycleEvent::BrowserCreated => t!("lifecycle.browser_created"),
+            LifecycleEvent::FirstPaint => t!("lifecycle.first_paint"),
+            LifecycleEvent::FirstDomReady => t!("lifecycle.first_dom_ready"),
+            LifecycleEvent::LoadComplete => t!("lifecycle.load_complete"),
+            LifecycleEvent::BackgroundReady => t!("lifecycle.background_ready"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CefLifecycleEventsInner {
+    /// When each step happened, indexed by `LifecycleEvent`.
+    times: [Option<Instant>; 7],
+}
+
+/// When the steps of starting the browser happened, for profiling startup.
+/// Only the first of each is kept, so later tabs don't move them.
+#[derive(Clone, Default)]
+pub struct CefLifecycleEvents(Arc<Mutex<CefLifecycleEventsInner>>);
+
+impl Global for CefLifecycleEvents {}
+
+impl CefLifecycleEvents {
+    pub fn record(&self, event: LifecycleEvent) {
+        self.0.lock().unwrap().times[event as usize].get_or_insert_with(Instant::now);
+    }
+
+    fn time(&self, event: LifecycleEvent) -> Option<Instant> {
+        self.0.lock().unwrap().times[event as usize]
+    }
+
+    pub fn on_after_created(&self) {
+        self.record(LifecycleEvent::BrowserCreated);
+    }
+
+    pub fn on_paint(&self) {
+        self.record(LifecycleEvent::FirstPaint);
+    }
+
+    /// Listen for the first page's DOM being ready.
+    pub fn on_load_start(&self, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) || self.time(LifecycleEvent::FirstDomReady).is_some() {
+            return;
+        }
+
+        let url = frame.get_url().unwrap_or_default();
+        if let Err(e) = frame.execute_java_script(DOM_READY_SCRIPT, &url, 0) {
+            eprintln!("Error listening for DOM ready: {}", e);
+        }
+    }
+
+    pub fn on_load_end(&self, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            self.record(LifecycleEvent::LoadComplete);
+        }
+    }
+
+    /// The recorded steps in order, with when each happened since CEF started
+    /// initializing.
+    fn offsets(&self) -> Vec<(LifecycleEvent, Option<Duration>)> {
+        let start = self.time(LifecycleEvent::CefInitStart);
+
+        LifecycleEvent::ALL
+            .iter()
+            .map(|event| {
+                let offset = start
+                    .zip(self.time(*event))
+                    .map(|(start, time)| time.saturating_duration_since(start));
+                (*event, offset)
+            })
+            .collect()
+    }
+}
+
+/// The first page's DOM is ready, from a `lifecycle.domReady` query.
+pub fn on_query(cx: &GpuiApp, query: Query) {
+    cx.global::<CefLifecycleEvents>()
+        .record(LifecycleEvent::FirstDomReady);
+    query.success("");
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.)
+}
+
+/// Modal with a waterfall of the startup steps, each bar spanning the time
+/// since the step before.
+pub struct LifecyclePanel;
+
+impl LifecyclePanel {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for LifecyclePanel {}
+
+impl Render for LifecyclePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let offsets = cx.global::<CefLifecycleEvents>().offsets();
+        let total = offsets
+            .iter()
+            .filter_map(|(_, offset)| *offset)
+            .max()
+            .unwrap_or_default();
+        let fraction = |duration: Duration| match total.is_zero() {
+            true => 0.,
+            false => (duration.as_secs_f64() / total.as_secs_f64()) as f32,
+        };
+
+        let mut previous = Duration::ZERO;
+        let rows =
+            offsets
+                .into_iter()
+                .map(|(event, offset)| {
+                    let bar = offset.map(|offset| {
+                        let start = previous.min(offset);
+                        previous = offset;
+                        (start, offset)
+                    });
+
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .h_6()
+                        .child(
+                            div()
+                                .flex_none()
+                                .w(px(120.))
+                                .text_color(theme.text_secondary)
+                                .child(event.label()),
+                        )
+                        .child(div().relative().flex_1().h_3().when_some(
+                            bar,
+                            |this, (start, end)| {
+                                this.child(
+                                    div()
+                                        .absolute()
+                                        .top_0()
+                                        .bottom_0()
+                                        .left(relative(fraction(start)))
+                                        .w(relative(fraction(end - start)))
+                                        .min_w(px(2.))
+                                        .rounded_sm()
+                                        .bg(theme.accent),
+                                )
+                            },
+                        ))
+                        .child(
+                            div()
+                                .flex_none()
+                                .w(px(72.))
+                                .text_color(theme.text_placeholder)
+                                .child(match bar {
+                                    Some((start, end)) => milliseconds(end - start),
+                                    None => t!("lifecycle.pending").to_string(),
+                                }),
+                        )
+                })
+                .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(480.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("lifecycle.title")))
+                    .child(div().flex().flex_col().children(rows))
+                    .child(
+                        div()
+                            .text_color(theme.text_placeholder)
+                            .child(t!("lifecycle.total", time = milliseconds(total))),
+                    )
+                    .child(
+                        div().flex().justify_end().child(
+                            button("lifecycle-done", t!("settings.done"), true, cx)
+                                .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                        ),
+                    ),
+            )
+    }
+}
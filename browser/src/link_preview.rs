@@ -0,0 +1,458 @@
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::{AuthCallback, Request, UrlRequest, UrlRequestClient, UrlRequestClientCallbacks};
+use gpui::{
+    div, img, prelude::*, px, App as GpuiApp, Global, Image, ImageFormat, ImageSource, ObjectFit,
+};
+use url::Url;
+
+use crate::theme::ActiveTheme;
+
+/// How long the pointer has to stay on a link before its page is fetched.
+const HOVER_DELAY: Duration = Duration::from_millis(300);
+
+/// The Open Graph tags are in the page's head, so no more than this is read.
+const MAX_HTML_BYTES: usize = 256 * 1024;
+
+/// Largest size of the card's image, in pixels.
+const IMAGE_WIDTH: u32 = 600;
+const IMAGE_HEIGHT: u32 = 315;
+
+/// Links whose preview is remembered, so hovering them again shows it at once.
+const MAX_CACHED: usize = 50;
+
+/// What a page's Open Graph tags say about it.
+#[derive(Clone, Default)]
+struct OpenGraph {
+    url: String,
+    title: String,
+    description: String,
+    image_url: Option<String>,
+    image: Option<Arc<Image>>,
+}
+
+impl OpenGraph {
+    /// Read the Open Graph tags from the start of the page at `url`, falling
+    /// back to the Twitter card tags, the description and the title.
+    fn parse(url: &str, html: &str) -> Self {
+        let mut tags = HashMap::new();
+        let lowercase = html.to_ascii_lowercase();
+
+        let mut rest = 0;
+        while let Some(start) = lowercase[rest..].find("<meta") {
+            let start = rest + start;
+            let Some(end) = lowercase[start..].find('>') else {
+                break;
+            };
+            let attributes = attributes(&html[start + 5..start + end]);
+            rest = start + end;
+
+            let key = attributes
+                .get("property")
+                .or_else(|| attributes.get("name"))
+                .map(|key| key.to_ascii_lowercase());
+            if let (Some(key), Some(content)) = (key, attributes.get("content")) {
+                tags.entry(key).or_insert_with(|| content.clone());
+            }
+        }
+
+        let title_tag = lowercase.find("<title").and_then(|start| {
+            let open = start + lowercase[start..].find('>')? + 1;
+            let close = open + lowercase[open..].find("</title")?;
+            Some(decode_entities(html[open..close].trim()))
+        });
+
+        let tag = |keys: &[&str]| keys.iter().find_map(|key| tags.get(*key).cloned());
+        let image_url = tag(&["og:image", "og:image:url", "twitter:image"])
+            .and_then(|image| Url::parse(url).ok()?.join(&image).ok())
+            .map(String::from);
+
+        Self {
+            url: url.to_string(),
+            title: tag(&["og:title", "twitter:title"])
+                .or(title_tag)
+                .unwrap_or_default(),
+            description: tag(&["og:description", "twitter:description", "description"])
+                .unwrap_or_default(),
+            image_url,
+            image: None,
+        }
+    }
+
+    fn host(&self) -> String {
+        Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// The attributes of a tag, from after its name to before its `>`.
+fn attributes(tag: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut chars = tag.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == '/' {
+            chars.next();
+            continue;
+        }
+
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if c == '=' || c.is_whitespace() || c == '/' {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let name = tag[start..end].to_ascii_lowercase();
+        if name.is_empty() {
+            chars.next();
+            continue;
+        }
+
+        if chars.peek().map(|&(_, c)| c) != Some('=') {
+            attributes.insert(name, String::new());
+            continue;
+        }
+        chars.next();
+
+        let value = match chars.peek().copied() {
+            Some((i, quote @ ('"' | '\''))) => {
+                chars.next();
+                let close = tag[i + 1..]
+                    .find(quote)
+                    .map_or(tag.len(), |close| i + 1 + close);
+                while chars.next_if(|&(j, _)| j <= close).is_some() {}
+                &tag[i + 1..close]
+            }
+            Some((i, _)) => {
+                let mut end = i;
+                while let Some((j, c)) = chars.next_if(|&(_, c)| !c.is_whitespace()) {
+                    end = j + c.len_utf8();
+                }
+                &tag[i..end]
+            }
+            None => "",
+        };
+        attributes.insert(name, decode_entities(value));
+    }
+
+    attributes
+}
+
+/// `text` with the character references pages commonly put in attributes
+/// replaced.
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[derive(Default)]
+struct LinkPreviewsInner {
+    /// The link under the pointer.
+    hovered: Option<String>,
+    /// Bumped whenever `hovered` changes, so late responses can be told
+    /// apart.
+    generation: u64,
+    /// A link just hovered, waiting out `HOVER_DELAY`.
+    scheduled: Option<(String, u64)>,
+    request: Option<UrlRequest>,
+    card: Option<OpenGraph>,
+    cache: HashMap<String, OpenGraph>,
+    changed: bool,
+}
+
+/// Previews of the page behind the link under the pointer, from its Open
+/// Graph tags. CEF reports hovered links as status messages.
+#[derive(Clone, Default)]
+pub struct LinkPreviews(Arc<Mutex<LinkPreviewsInner>>);
+
+impl Global for LinkPreviews {}
+
+impl LinkPreviews {
+    /// The pointer moved onto `value`'s link, or off links if it's `None`.
+    pub fn on_status_message(&self, value: Option<&str>) {
+        let link = value
+            .filter(|value| value.starts_with("http://") || value.starts_with("https://"))
+            .map(str::to_string);
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.hovered == link {
+            return;
+        }
+
+        inner.generation += 1;
+        inner.scheduled = None;
+        if let Some(request) = inner.request.take() {
+            request.cancel().ok();
+        }
+        inner.card = link
+            .as_ref()
+            .and_then(|link| inner.cache.get(link).cloned());
+        if inner.card.is_none() {
+            inner.scheduled = link.clone().map(|link| (link, inner.generation));
+        }
+        inner.hovered = link;
+        inner.changed = true;
+    }
+
+    /// The link to fetch once the pointer has stayed on it, with the
+    /// generation it was hovered in.
+    pub fn take_scheduled(&self) -> Option<(String, u64)> {
+        self.0.lock().unwrap().scheduled.take()
+    }
+
+    /// Whether the card appeared or went away since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Fetch the start of the page at `url`, unless the pointer has moved
+    /// on since `generation`.
+    fn fetch(&self, url: String, generation: u64) {
+        if self.0.lock().unwrap().generation != generation {
+            return;
+        }
+
+        let client = UrlRequestClient::new(PageClient {
+            previews: self.clone(),
+            url: url.clone(),
+            generation,
+            body: Vec::new(),
+        });
+        self.send(&url, generation, client);
+    }
+
+    fn send(&self, url: &str, generation: u64, client: UrlRequestClient) {
+        let request = Request::new();
+        if let Err(e) = request.set_url(url).and_then(|_| request.set_method("GET")) {
+            eprintln!("Error fetching link preview for {}: {}", url, e);
+            return;
+        }
+
+        let request = UrlRequest::new(request, client, None);
+        let mut inner = self.0.lock().unwrap();
+        if inner.generation == generation {
+            inner.request = Some(request);
+        } else {
+            request.cancel().ok();
+        }
+    }
+
+    /// Show `preview`, fetching its image first if it has one.
+    fn parsed(&self, preview: OpenGraph, generation: u64) {
+        match preview.image_url.clone() {
+            Some(image_url) if preview.image.is_none() => {
+                let client = UrlRequestClient::new(ImageClient {
+                    previews: self.clone(),
+                    preview: Some(preview),
+                    generation,
+                    body: Vec::new(),
+                });
+                self.send(&image_url, generation, client);
+            }
+            _ => self.finish(preview, generation),
+        }
+    }
+
+    fn finish(&self, preview: OpenGraph, generation: u64) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.cache.len() >= MAX_CACHED {
+            inner.cache.clear();
+        }
+        inner.cache.insert(preview.url.clone(), preview.clone());
+
+        if inner.generation == generation {
+            inner.request = None;
+            inner.card = Some(preview);
+            inner.changed = true;
+        }
+    }
+}
+
+/// Fetch the hovered link's page once the pointer has stayed on it for
+/// `HOVER_DELAY`.
+pub fn schedule(cx: &GpuiApp, url: String, generation: u64) {
+    let previews = cx.global::<LinkPreviews>().clone();
+
+    cx.spawn(async move |cx| {
+        cx.background_executor().timer(HOVER_DELAY).await;
+        previews.fetch(url, generation);
+    })
+    .detach();
+}
+
+/// Reads the start of a linked page for its Open Graph tags.
+struct PageClient {
+    previews: LinkPreviews,
+    url: String,
+    generation: u64,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for PageClient {
+    fn on_request_complete(&mut self, _request: UrlRequest) {
+        // Cut off requests still get parsed, as the head is all that's
+        // needed.
+        if self.body.is_empty() {
+            return;
+        }
+
+        let html = String::from_utf8_lossy(&self.body);
+        let preview = OpenGraph::parse(&self.url, &html);
+        if !preview.title.is_empty() {
+            self.previews.parsed(preview, self.generation);
+        }
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, request: UrlRequest, data: &[u8]) {
+        if self.body.len() >= MAX_HTML_BYTES {
+            return;
+        }
+
+        self.body.extend_from_slice(data);
+        if self.body.len() >= MAX_HTML_BYTES {
+            request.cancel().ok();
+        }
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Downloads a preview's image, showing the preview without it if it can't
+/// be had.
+struct ImageClient {
+    previews: LinkPreviews,
+    preview: Option<OpenGraph>,
+    generation: u64,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for ImageClient {
+    fn on_request_complete(&mut self, _request: UrlRequest) {
+        let Some(mut preview) = self.preview.take() else {
+            return;
+        };
+
+        match scale_down(&self.body) {
+            Ok(image) => preview.image = Some(image),
+            Err(e) => eprintln!("Error decoding link preview image: {}", e),
+        }
+        // Without an image URL, it isn't fetched again.
+        preview.image_url = None;
+        self.previews.finish(preview, self.generation);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+fn scale_down(body: &[u8]) -> anyhow::Result<Arc<Image>> {
+    let image = image::load_from_memory(body)?.thumbnail(IMAGE_WIDTH, IMAGE_HEIGHT);
+
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(Arc::new(Image::from_bytes(
+        ImageFormat::Png,
+        png.into_inner(),
+    )))
+}
+
+/// The card for the hovered link, in the corner of the page where its URL
+/// would show in a status bar.
+pub fn card(cx: &GpuiApp) -> Option<impl IntoElement> {
+    let preview = cx.global::<LinkPreviews>().0.lock().unwrap().card.clone()?;
+    let theme = cx.theme();
+
+    Some(
+        div()
+            .absolute()
+            .bottom_2()
+            .left_2()
+            .w(px(300.))
+            .flex()
+            .flex_col()
+            .overflow_hidden()
+            .rounded_lg()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .when_some(preview.image.clone(), |this, image| {
+                this.child(
+                    img(ImageSource::from(image))
+                        .w_full()
+                        .h(px(150.))
+                        .object_fit(ObjectFit::Cover),
+                )
+            })
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .p_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.text)
+                            .line_clamp(2)
+                            .child(preview.title.clone()),
+                    )
+                    .when(!preview.description.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_color(theme.text_secondary)
+                                .line_clamp(3)
+                                .child(preview.description.clone()),
+                        )
+                    })
+                    .child(div().text_color(theme.text_muted).child(preview.host())),
+            ),
+    )
+}
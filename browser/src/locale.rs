@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use gpui::{App as GpuiApp, SharedString};
+use once_cell::sync::Lazy;
+
+use crate::settings::Settings;
+
+/// Locale whose strings stand in for any a locale file leaves out.
+pub const FALLBACK_LOCALE: &str = "en-US";
+
+/// Key every locale file names its language under, in that language.
+const NAME_KEY: &str = "language.name";
+
+static LOCALE: Lazy<RwLock<LocaleSupport>> = Lazy::new(|| RwLock::new(LocaleSupport::default()));
+
+/// Look up the UI string for `key` in the current locale. Placeholders like
+/// `{count}` in the string are filled from `name = value` pairs:
+/// `t!("network.requests", count = 3)`.
+macro_rules! t {
+    ($key:expr) => {
+        $crate::locale::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::locale::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+pub(crate) use t;
+
+/// The UI strings of one locale, read from `assets/locales/<locale>.toml`.
+/// Nested tables flatten into dotted keys, so `close` under `[tabs]` is
+/// `tabs.close`.
+#[derive(Default)]
+pub struct LocaleSupport {
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl LocaleSupport {
+    /// Load `locale` from `dir`, with `FALLBACK_LOCALE` behind it.
+    fn load(dir: &Path, locale: &str) -> Self {
+        let fallback = read_strings(&dir.join(format!("{}.toml", FALLBACK_LOCALE)));
+        let strings = match locale == FALLBACK_LOCALE {
+            true => HashMap::new(),
+            false => read_strings(&dir.join(format!("{}.toml", locale))),
+        };
+
+        Self { strings, fallback }
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+    }
+
+    fn translate(&self, key: &str, args: &[(&str, String)]) -> SharedString {
+        let Some(string) = self.get(key) else {
+            return SharedString::from(key.to_string());
+        };
+
+        match args.is_empty() {
+            true => SharedString::from(string.to_string()),
+            false => fill(string, args).into(),
+        }
+    }
+}
+
+/// `template` with each `{name}` placeholder replaced by its value in `args`,
+/// in one pass so values are never searched for placeholders themselves.
+/// Placeholders without a value are left as they are.
+fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            let (_, value) = args.iter().find(|(other, _)| *other == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                filled.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+/// The strings in the locale file at `path`, empty if it's missing or
+/// invalid.
+fn read_strings(path: &Path) -> HashMap<String, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Failed to read locale {:?}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+    let table = match contents.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Failed to parse locale {:?}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+
+    let mut strings = HashMap::new();
+    flatten(&table, "", &mut strings);
+    strings
+}
+
+fn flatten(table: &toml::Table, prefix: &str, strings: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = match prefix.is_empty() {
+            true => key.clone(),
+            false => format!("{}.{}", prefix, key),
+        };
+        match value {
+            toml::Value::String(string) => {
+                strings.insert(key, string.clone());
+            }
+            toml::Value::Table(table) => flatten(table, &key, strings),
+            _ => eprintln!("Ignoring locale key {} that isn't a string", key),
+        }
+    }
+}
+
+/// Folder the locale files are bundled in.
+fn locales_dir() -> PathBuf {
+    crate::assets_dir().join("locales")
+}
+
+/// The locales there are files for, with each one's name for itself, sorted
+/// by locale.
+pub fn available() -> Vec<(String, String)> {
+    let dir = locales_dir();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut locales: Vec<(String, String)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+
+            let locale = path.file_stem()?.to_str()?.to_string();
+            let name = read_strings(&path)
+                .remove(NAME_KEY)
+                .unwrap_or_else(|| locale.clone());
+            Some((locale, name))
+        })
+        .collect();
+    locales.sort();
+    locales
+}
+
+/// The available locale closest to the OS's: the same one, else one for the
+/// same language, else `FALLBACK_LOCALE`.
+pub fn system_locale() -> String {
+    closest(sys_locale::get_locales(), &available())
+}
+
+/// The available locale closest to the first of `preferred` that has one.
+fn closest(preferred: impl IntoIterator<Item = String>, available: &[(String, String)]) -> String {
+    let has = |locale: &str| available.iter().any(|(other, _)| other == locale);
+
+    for locale in preferred {
+        // POSIX locales look like `zh_CN.UTF-8`.
+        let locale = locale
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-");
+        if has(&locale) {
+            return locale;
+        }
+
+        let language = locale.split('-').next().unwrap_or_default();
+        let same_language = available
+            .iter()
+            .find(|(other, _)| other.split('-').next() == Some(language));
+        if let Some((other, _)) = same_language {
+            return other.clone();
+        }
+    }
+
+    FALLBACK_LOCALE.to_string()
+}
+
+/// The string for `key` in the current locale, with `{name}` placeholders
+/// replaced by `args`. A key missing from every locale shows as itself.
+pub fn translate(key: &str, args: &[(&str, String)]) -> SharedString {
+    LOCALE.read().unwrap().translate(key, args)
+}
+
+/// Load the strings of the locale picked in settings, or the OS's if none
+/// is, and redraw the windows in them.
+pub fn init(cx: &mut GpuiApp) {
+    let locale = cx
+        .global::<Settings>()
+        .language
+        .clone()
+        .unwrap_or_else(system_locale);
+
+    *LOCALE.write().unwrap() = LocaleSupport::load(&locales_dir(), &locale);
+    cx.refresh_windows();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A folder of locale files with `contents`, by locale, removed when
+    /// dropped.
+    fn locales(contents: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (locale, contents) in contents {
+            fs::write(dir.path().join(format!("{}.toml", locale)), contents).unwrap();
+        }
+        dir
+    }
+
+    fn named(locales: &[&str]) -> Vec<(String, String)> {
+        locales
+            .iter()
+            .map(|locale| (locale.to_string(), locale.to_string()))
+            .collect()
+    }
+
+    fn preferred(locales: &[&str]) -> Vec<String> {
+        locales.iter().map(|locale| locale.to_string()).collect()
+    }
+
+    #[test]
+    fn missing_strings_fall_back_to_english() {
+        let dir = locales(&[
+            (
+                "en-US",
+                "[tabs]\nclose = \"Close\"\nnew_tab = \"New Tab\"\n",
+            ),
+            ("fr-FR", "[tabs]\nclose = \"Fermer\"\n"),
+        ]);
+        let locale = LocaleSupport::load(dir.path(), "fr-FR");
+
+        assert_eq!(locale.get("tabs.close"), Some("Fermer"));
+        assert_eq!(locale.get("tabs.new_tab"), Some("New Tab"));
+        assert_eq!(locale.translate("tabs.missing", &[]), "tabs.missing");
+    }
+
+    #[test]
+    fn a_missing_or_broken_locale_file_leaves_english() {
+        let dir = locales(&[
+            ("en-US", "[tabs]\nclose = \"Close\"\n"),
+            ("de-DE", "[tabs\nclose = \"Schließen\"\n"),
+        ]);
+
+        for locale in ["de-DE", "xx-XX"] {
+            assert_eq!(
+                LocaleSupport::load(dir.path(), locale).get("tabs.close"),
+                Some("Close")
+            );
+        }
+    }
+
+    #[test]
+    fn translate_fills_placeholders() {
+        let dir = locales(&[(
+            "en-US",
+            "[network]\nrequests = \"{count} requests, {count} shown\"\n",
+        )]);
+        let locale = LocaleSupport::load(dir.path(), FALLBACK_LOCALE);
+
+        assert_eq!(
+            locale.translate("network.requests", &[("count", 3.to_string())]),
+            "3 requests, 3 shown"
+        );
+        assert_eq!(
+            locale.translate("network.requests", &[]),
+            "{count} requests, {count} shown"
+        );
+    }
+
+    #[test]
+    fn fill_leaves_placeholders_inside_values_alone() {
+        let args = [
+            ("title", "Why {url} matters".to_string()),
+            ("url", "https://example.com".to_string()),
+        ];
+
+        assert_eq!(
+            fill("{title} ({url})", &args),
+            "Why {url} matters (https://example.com)"
+        );
+        assert_eq!(
+            fill("{missing} {{url}", &args),
+            "{missing} {https://example.com"
+        );
+    }
+
+    #[test]
+    fn closest_prefers_the_same_locale_then_the_same_language() {
+        let available = named(&["en-US", "zh-CN", "zh-TW"]);
+
+        assert_eq!(closest(preferred(&["zh-TW"]), &available), "zh-TW");
+        assert_eq!(closest(preferred(&["zh_TW.UTF-8"]), &available), "zh-TW");
+        assert_eq!(closest(preferred(&["zh-HK"]), &available), "zh-CN");
+        assert_eq!(closest(preferred(&["fr-FR", "zh-TW"]), &available), "zh-TW");
+    }
+
+    #[test]
+    fn closest_falls_back_to_english() {
+        let available = named(&["en-US", "zh-CN"]);
+
+        assert_eq!(closest(preferred(&["fr-FR", "de"]), &available), "en-US");
+        assert_eq!(closest(preferred(&[]), &available), "en-US");
+    }
+
+    #[test]
+    fn bundled_locales_only_translate_english_keys() {
+        let english = read_strings(&locales_dir().join(format!("{}.toml", FALLBACK_LOCALE)));
+        assert!(english.contains_key(NAME_KEY));
+
+        for (locale, _) in available() {
+            let strings = read_strings(&locales_dir().join(format!("{}.toml", locale)));
+            assert!(strings.contains_key(NAME_KEY), "{} has no name", locale);
+
+            let mut unknown = strings
+                .keys()
+                .filter(|key| !english.contains_key(*key))
+                .collect::<Vec<_>>();
+            unknown.sort();
+            assert!(
+                unknown.is_empty(),
+                "{} has unknown keys {:?}",
+                locale,
+                unknown
+            );
+        }
+    }
+}
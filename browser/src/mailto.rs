@@ -0,0 +1,388 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, ClipboardItem, Context, DismissEvent, Entity,
+    EventEmitter, Focusable, Global, SharedString, Subscription, Window,
+};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button::button,
+    locale::t,
+    settings::Settings,
+    tabs,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+const SCHEME: &str = "mailto:";
+
+/// What clicking an email link does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MailAction {
+    /// Hand the link to the system's mail app.
+    #[default]
+    DefaultClient,
+    /// Open `Settings::webmail_url` in a new tab.
+    WebMail,
+    /// Show the message in a form to edit before sending it with the mail
+    /// app.
+    Compose,
+}
+
+impl MailAction {
+    pub const ALL: [MailAction; 3] = [
+        MailAction::DefaultClient,
+        MailAction::WebMail,
+        MailAction::Compose,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            MailAction::DefaultClient => t!("mailto.default_client"),
+            MailAction::WebMail => t!("mailto.web_mail"),
+            MailAction::Compose => t!("mailto.compose"),
+        }
+    }
+}
+
+/// Decode `%XX` escapes. Unlike in forms, `+` stands for itself, as it may
+/// well be part of an address.
+fn decode(value: &str) -> String {
+    percent_decode_str(value).decode_utf8_lossy().into_owned()
+}
+
+fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// A message addressed by a `mailto:` URL, as in RFC 6068.
+#[derive(Clone, Default)]
+pub struct MailtoLink {
+    pub to: String,
+    pub cc: String,
+    pub bcc: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl MailtoLink {
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url
+            .get(..SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+            .map(|_| &url[SCHEME.len()..])?;
+        let (to, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut link = MailtoLink {
+            to: decode(to),
+            ..Default::default()
+        };
+        for (name, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let value = decode(value);
+            let list = match name.to_ascii_lowercase().as_str() {
+                "to" => &mut link.to,
+                "cc" => &mut link.cc,
+                "bcc" => &mut link.bcc,
+                "subject" => {
+                    link.subject = value;
+                    continue;
+                }
+                "body" => {
+                    link.body = value;
+                    continue;
+                }
+                _ => continue,
+            };
+            if !list.is_empty() {
+                list.push(',');
+            }
+            list.push_str(&value);
+        }
+
+        Some(link)
+    }
+
+    /// The `mailto:` URL for the message.
+    pub fn url(&self) -> String {
+        let fields = [
+            ("cc", &self.cc),
+            ("bcc", &self.bcc),
+            ("subject", &self.subject),
+            ("body", &self.body),
+        ];
+        let query = fields
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| format!("{}={}", name, encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let to = self
+            .to
+            .split(',')
+            .map(|address| encode(address.trim()).replace("%40", "@"))
+            .collect::<Vec<_>>()
+            .join(",");
+        match query.is_empty() {
+            true => format!("{}{}", SCHEME, to),
+            false => format!("{}{}?{}", SCHEME, to, query),
+        }
+    }
+}
+
+/// Email links the page tried to open, waiting for the UI to handle them
+/// the way the user picked in settings instead of as a navigation.
+#[derive(Clone, Default)]
+pub struct MailtoHandler(Arc<Mutex<VecDeque<MailtoLink>>>);
+
+impl Global for MailtoHandler {}
+
+impl MailtoHandler {
+    /// Queue the message `url` addresses, if it's a `mailto:` URL.
+    pub fn request(&self, url: &str) -> bool {
+        let Some(link) = MailtoLink::parse(url) else {
+            return false;
+        };
+
+        self.0.lock().unwrap().push_back(link);
+        true
+    }
+
+    pub fn take_requested(&self) -> Option<MailtoLink> {
+        self.0.lock().unwrap().pop_front()
+    }
+}
+
+/// Send `link` to web mail if that's picked in settings, otherwise to the
+/// system's mail app.
+pub fn open(cx: &mut GpuiApp, link: &MailtoLink) {
+    let settings = cx.global::<Settings>();
+    match settings.mail_action {
+        MailAction::WebMail => {
+            let url = settings.webmail_url.replace("%s", &encode(&link.url()));
+            tabs::open_url(cx, &url, None);
+        }
+        MailAction::DefaultClient | MailAction::Compose => cx.open_url(&link.url()),
+    }
+}
+
+/// Form showing an email link's message to edit, then send with the mail
+/// app or copy.
+pub struct ComposeSheet {
+    to: Entity<TextInput>,
+    cc: Entity<TextInput>,
+    subject: Entity<TextInput>,
+    body: Entity<TextInput>,
+    /// Kept as the link had it; the form has no field for it.
+    bcc: String,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ComposeSheet {
+    pub fn new(link: MailtoLink, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let to = cx.new(|cx| TextInput::new(link.to, cx).placeholder(t!("mailto.to")));
+        let cc = cx.new(|cx| TextInput::new(link.cc, cx).placeholder(t!("mailto.cc")));
+        let subject =
+            cx.new(|cx| TextInput::new(link.subject, cx).placeholder(t!("mailto.subject")));
+        let body = cx.new(|cx| {
+            TextInput::new(link.body, cx)
+                .placeholder(t!("mailto.body"))
+                .multi_line(true)
+        });
+
+        let subscriptions = [&to, &cc, &subject, &body]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe(input, |this, _, event, cx| match event {
+                    TextInputEvent::Confirm => this.send(cx),
+                    TextInputEvent::Cancel => cx.emit(DismissEvent),
+                })
+            })
+            .collect();
+
+        window.focus(&body.focus_handle(cx));
+
+        Self {
+            to,
+            cc,
+            subject,
+            body,
+            bcc: link.bcc,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn link(&self, cx: &GpuiApp) -> MailtoLink {
+        MailtoLink {
+            to: self.to.read(cx).text().trim().to_string(),
+            cc: self.cc.read(cx).text().trim().to_string(),
+            bcc: self.bcc.clone(),
+            subject: self.subject.read(cx).text().to_string(),
+            body: self.body.read(cx).text().to_string(),
+        }
+    }
+
+    fn send(&mut self, cx: &mut Context<Self>) {
+        let link = self.link(cx);
+        open(cx, &link);
+        cx.emit(DismissEvent);
+    }
+
+    fn copy(&mut self, cx: &mut Context<Self>) {
+        let link = self.link(cx);
+        let text = match link.subject.is_empty() {
+            true => link.body,
+            false => format!("{}\n\n{}", link.subject, link.body),
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ComposeSheet {}
+
+impl Render for ComposeSheet {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .w(px(400.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("mailto.title")))
+                    .child(self.to.clone())
+                    .child(self.cc.clone())
+                    .child(self.subject.clone())
+                    .child(self.body.clone())
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("compose-cancel", t!("mailto.cancel"), false, cx)
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                            )
+                            .child(
+                                button("compose-copy", t!("mailto.copy"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.copy(cx))),
+                            )
+                            .child(
+                                button("compose-send", t!("mailto.send"), true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.send(cx))),
+                            ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_every_field() {
+        let link = MailtoLink::parse(
+            "mailto:a@example.com?cc=b@example.com&bcc=c@example.com&subject=Hi&body=Hello",
+        )
+        .unwrap();
+
+        assert_eq!(link.to, "a@example.com");
+        assert_eq!(link.cc, "b@example.com");
+        assert_eq!(link.bcc, "c@example.com");
+        assert_eq!(link.subject, "Hi");
+        assert_eq!(link.body, "Hello");
+    }
+
+    #[test]
+    fn parse_takes_the_scheme_in_any_case() {
+        let link = MailtoLink::parse("MAILTO:a@example.com?SUBJECT=Hi").unwrap();
+
+        assert_eq!(link.to, "a@example.com");
+        assert_eq!(link.subject, "Hi");
+    }
+
+    #[test]
+    fn parse_rejects_other_schemes() {
+        assert!(MailtoLink::parse("https://example.com").is_none());
+        assert!(MailtoLink::parse("mail").is_none());
+    }
+
+    #[test]
+    fn parse_joins_repeated_addresses() {
+        let link = MailtoLink::parse(
+            "mailto:a@example.com?to=b@example.com&cc=c@example.com&cc=d@example.com",
+        )
+        .unwrap();
+
+        assert_eq!(link.to, "a@example.com,b@example.com");
+        assert_eq!(link.cc, "c@example.com,d@example.com");
+    }
+
+    #[test]
+    fn parse_decodes_escapes_but_not_plus() {
+        let link =
+            MailtoLink::parse("mailto:a+tag@example.com?subject=Caf%C3%A9%20menu&body=1+1%3D2%0A")
+                .unwrap();
+
+        assert_eq!(link.to, "a+tag@example.com");
+        assert_eq!(link.subject, "Café menu");
+        assert_eq!(link.body, "1+1=2\n");
+    }
+
+    #[test]
+    fn parse_keeps_broken_escapes() {
+        let link = MailtoLink::parse("mailto:?subject=100%&body=%zz%+5").unwrap();
+
+        assert_eq!(link.to, "");
+        assert_eq!(link.subject, "100%");
+        assert_eq!(link.body, "%zz%+5");
+    }
+
+    #[test]
+    fn url_round_trips() {
+        let link = MailtoLink {
+            to: "a@example.com, b@example.com".into(),
+            cc: String::new(),
+            bcc: "c@example.com".into(),
+            subject: "Café & more".into(),
+            body: "1+1=2\nbye".into(),
+        };
+        let url = link.url();
+
+        assert_eq!(
+            url,
+            "mailto:a@example.com,b@example.com?bcc=c%40example.com&subject=Caf%C3%A9%20%26%20more&body=1%2B1%3D2%0Abye"
+        );
+
+        let parsed = MailtoLink::parse(&url).unwrap();
+        assert_eq!(parsed.to, "a@example.com,b@example.com");
+        assert_eq!(parsed.bcc, link.bcc);
+        assert_eq!(parsed.subject, link.subject);
+        assert_eq!(parsed.body, link.body);
+    }
+}
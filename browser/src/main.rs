@@ -1,29 +1,231 @@
+mod about;
+mod accessibility;
+mod adblock;
+mod app_scheme;
+mod archive;
+mod audio;
+mod auth;
+mod autofill;
+mod autoplay;
+mod background;
+mod benchmark;
+mod bookmarks;
+mod button;
+mod cache;
+mod capture;
+mod certificates;
+mod command_palette;
+mod console;
+mod console_log;
+mod context_menu;
+mod crashes;
+mod csp;
+mod devtools;
+mod drag;
+mod error_pages;
+mod extension_popup;
+mod extension_storage;
+mod extensions;
+mod favicons;
+mod focus;
+mod focus_mode;
+mod fullscreen;
+mod gestures;
+mod hang;
+mod history;
+mod ime;
+mod incognito;
+mod inspector;
+mod js_dialog;
+mod lifecycle;
+mod link_preview;
+mod locale;
+mod mailto;
+mod media;
+mod memory;
+mod message_router;
+mod mock;
+mod mouse_gestures;
+mod network;
+mod notifications;
+mod offline;
+mod omnibox;
+mod overflow_menu;
+mod page_actions;
+mod passwords;
+mod pdf;
+mod performance;
+mod permissions;
+mod pip;
+mod popups;
+mod print;
+mod proxy;
+mod reader;
+mod sandbox;
+mod screenshot;
+mod scroll;
+mod secure_dns;
+mod session;
+mod settings;
+mod shortcuts;
+mod site_isolation;
+mod source;
+mod speech;
+mod speech_recognition;
+mod spellcheck;
+mod split;
+mod storage;
+mod tab_search;
+mod tabs;
+mod telemetry;
+mod text_input;
+mod theme;
+mod throttling;
+mod thumbnails;
+mod toast;
+mod touch_bar;
+mod transitions;
+mod translate;
+mod user_scripts;
+mod view;
+mod virtual_keyboard;
+mod webcompat;
+mod websocket;
+mod window_manager;
+mod workspaces;
+
+use accessibility::AccessibilityTree;
+use adblock::AdBlock;
 use anyhow::Result;
+use audio::{AudioCapture, AudioCapturer, AudioVisualizer};
+use autoplay::{AutoplayPopover, BlockedAutoplay};
 use std::{
+    cell::Cell,
+    collections::HashMap,
     fs::create_dir_all,
     os::raw::c_void,
     path::PathBuf,
     process::exit,
+    rc::Rc,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use cef_ui::{
-    AccessibilityHandler, App, AppCallbacks, Browser, BrowserHost, BrowserSettings, Client,
-    ClientCallbacks, CommandLine, Context, ContextMenuHandler, ContextMenuHandlerCallbacks,
-    ContextMenuParams, DictionaryValue, DragData, DragOperations, EventFlags, Frame,
-    HorizontalAlignment, KeyboardHandler, LifeSpanHandler, LifeSpanHandlerCallbacks, LogSeverity,
-    MainArgs, MenuCommandId, MenuModel, PaintElementType, Point, PopupFeatures,
-    QuickMenuEditStateFlags, Range, Rect, RenderHandler, RenderHandlerCallbacks,
-    RunContextMenuCallback, RunQuickMenuCallback, ScreenInfo, Settings, Size, TextInputMode,
-    TouchHandleState, WindowInfo, WindowOpenDisposition,
+    AccessibilityHandler, AccessibilityHandlerCallbacks, App, AppCallbacks, AudioHandler,
+    AuthCallback, Browser, BrowserHost, BrowserSettings, Callback, Client, ClientCallbacks,
+    CommandLine, Context, ContextMenuHandler, ContextMenuHandlerCallbacks, ContextMenuParams,
+    DictionaryValue, DisplayHandler, DisplayHandlerCallbacks, DragData, DragOperations, ErrorCode,
+    EventFlags, FocusHandler, FocusHandlerCallbacks, FocusSource, Frame, HorizontalAlignment,
+    JsDialogCallback, JsDialogHandler, JsDialogHandlerCallbacks, JsDialogType, KeyboardHandler,
+    LifeSpanHandler, LifeSpanHandlerCallbacks, LoadHandler, LoadHandlerCallbacks, LogSeverity,
+    MainArgs, MediaAccessCallback, MediaAccessPermissions, MenuCommandId, MenuModel,
+    PaintElementType, PermissionHandler, PermissionHandlerCallbacks, PermissionPromptCallback,
+    PermissionRequestResult, PermissionRequestTypes, Point, PopupFeatures, PrintHandler, ProcessId,
+    ProcessMessage, QuickMenuEditStateFlags, Range, Rect, RenderHandler, RenderHandlerCallbacks,
+    RenderProcessHandler, Request, RequestContext, RequestHandler, RequestHandlerCallbacks,
+    ResourceHandler, ResourceRequestHandler, ResourceRequestHandlerCallbacks, Response,
+    ResponseFilter, ReturnValue, RunContextMenuCallback, RunQuickMenuCallback, SchemeRegistrar,
+    ScreenInfo, SelectClientCertificateCallback, Settings, Size, SslInfo, TerminationStatus,
+    TextInputMode, TouchHandleState, UrlRequestStatus, Value, WindowInfo, WindowOpenDisposition,
+    X509Certificate,
 };
 
 use gpui::{
-    actions, div, img, linear_color_stop, linear_gradient, point, prelude::*, px, rgb, rgba, size,
-    svg, App as GpuiApp, Application, AssetSource, Bounds, Global, Image, ImageSource, KeyBinding,
-    SharedString, Window, WindowBounds, WindowOptions,
+    actions, canvas, deferred, div, img, linear_color_stop, linear_gradient, point, prelude::*, px,
+    relative, size, svg, App as GpuiApp, Application, AssetSource, Bounds, CursorStyle,
+    DismissEvent, DragMoveEvent, ElementInputHandler, Entity, ExternalPaths, FocusHandle,
+    Focusable, Global, Image, ImageSource, KeyDownEvent, MouseButton, MouseDownEvent,
+    MouseMoveEvent, NavigationDirection, Pixels, ScrollWheelEvent, SharedString, Subscription,
+    Window, WindowBounds, WindowHandle, WindowOptions,
 };
 
+use about::AboutDialog;
+use archive::{PageArchiver, SavePageSheet};
+use auth::{AuthDialog, CredentialStore};
+use autofill::{Autofill, AutofillDropdown, FormDataStore};
+use background::BackgroundTask;
+use benchmark::Benchmark;
+use bookmarks::{toggle_bookmark, Bookmarks, BookmarksBar};
+use button::button;
+use cache::{CacheInspector, CachePanel};
+use capture::{CaptureDevices, CaptureSheet};
+use certificates::{CertificateExceptions, CertificateInterstitial, CertificateViewer};
+use command_palette::{CommandPalette, RecentCommands};
+use console::{ConsoleLog, ConsolePanel};
+use context_menu::{ContextMenu, ContextMenus};
+use crashes::ProcessMonitor;
+use csp::{CspInspector, CspPanel};
+use devtools::DevTools;
+use drag::PageDrag;
+use error_pages::{ErrorPage, ErrorPageHandler};
+use extension_popup::{ExtensionPopup, ExtensionPopupView};
+use extension_storage::WebExtensionStorage;
+use extensions::{ExtensionAction, Extensions, ExtensionsPanel};
+use favicons::FaviconCache;
+use focus::PageFocus;
+use fullscreen::PageFullscreen;
+use gestures::{Gesture, Gestures};
+use hang::{HangMonitor, UnresponsiveDialog};
+use history::History;
+use ime::{ImeState, PageIme};
+use incognito::Incognito;
+use inspector::ElementPicker;
+use js_dialog::{JsDialog, JsDialogs};
+use lifecycle::{CefLifecycleEvents, LifecycleEvent};
+use link_preview::LinkPreviews;
+use locale::t;
+use mailto::{ComposeSheet, MailAction, MailtoHandler};
+use media::{MediaBar, MediaSessions, NowPlaying};
+use memory::{MemoryPressureHandler, MemoryPressureLevel};
+use message_router::MessageRouter;
+use mock::MockBrowser;
+use mouse_gestures::MouseGestureRecognizer;
+use network::{NetworkLog, NetworkPanel};
+use notifications::{NotificationCenter, NotificationToasts, Notifications};
+use offline::OfflineCache;
+use omnibox::Omnibox;
+use overflow_menu::OverflowMenu;
+use page_actions::{PageActionPopover, PageActions};
+use passwords::{PasswordManager, SavePasswordBar};
+use pdf::{PdfView, PdfViewer};
+use performance::{Performance, PerformancePanel};
+use permissions::{PermissionBanner, PermissionsStore};
+use pip::PictureInPicture;
+use popups::{BlockedPopupsState, PopupBlockedBar, Popups};
+use print::{PrintJobs, PrintPreview, PrintedPdf, Printer};
+use proxy::{ProxyErrors, ProxyInterstitial, ProxySwitch};
+use reader::ReaderMode;
+use sandbox::SandboxPolicy;
+use screenshot::ElementCaptures;
+use scroll::ScrollAnimator;
+use secure_dns::{SecureDns, SecurityPanel};
+use session::{RestoreSessionDialog, Session};
+use settings::SettingsPanel;
+use site_isolation::SiteIsolation;
+use source::{SourcePanel, SourceViewer};
+use speech::SpeechSynthesis;
+use speech_recognition::SpeechRecognizer;
+use spellcheck::SpellCheckLanguage;
+use split::{SplitPane, SplitView};
+use storage::{StorageInspector, StoragePanel};
+use tab_search::TabSearch;
+use tabs::{DraggedTab, TabManager, TabMenu};
+use theme::ActiveTheme;
+use throttling::NetworkThrottling;
+use thumbnails::ThumbnailCache;
+use toast::Toast;
+use touch_bar::TouchBar;
+use transitions::{PageTransition, PageTransitions};
+use translate::{TranslationPopover, Translator};
+use user_scripts::UserScripts;
+use view::PageView;
+use virtual_keyboard::KeyboardLayout;
+use webcompat::{ReportSheet, WebCompatReports};
+use websocket::WebSocketLog;
+use window_manager::{ActivateWindow, WindowManager};
+use workspaces::{SwitchWorkspace, WorkspaceSidebar};
+
 // Asset loader for SVG files
 struct Assets {
     base: PathBuf,
@@ -35,7 +237,7 @@ impl AssetSource for Assets {
         match std::fs::read(&full_path) {
             Ok(data) => Ok(Some(std::borrow::Cow::Owned(data))),
             Err(err) => {
-                println!("Failed to load asset: {:?} - Error: {}", full_path, err);
+                eprintln!("Failed to load asset: {:?} - Error: {}", full_path, err);
                 Err(err.into())
             }
         }
@@ -52,16 +254,16 @@ impl AssetSource for Assets {
                             file_name.into_string().ok().map(SharedString::from)
                         }
                         Err(err) => {
-                            println!("Error reading directory entry: {}", err);
+                            eprintln!("Error reading directory entry: {}", err);
                             None
                         }
                     })
                     .collect();
-                println!("Listed {} files in directory: {:?}", files.len(), full_path);
+                eprintln!("Listed {} files in directory: {:?}", files.len(), full_path);
                 Ok(files)
             }
             Err(err) => {
-                println!("Failed to list directory: {:?} - Error: {}", full_path, err);
+                eprintln!("Failed to list directory: {:?} - Error: {}", full_path, err);
                 Err(err.into())
             }
         }
@@ -71,302 +273,3037 @@ impl AssetSource for Assets {
 // Browser state that will be managed by GPUI
 struct BrowserState {
     browser: Option<Browser>,
+    /// Stands in for `browser` when run with `--mock-cef`.
+    mock: Option<MockBrowser>,
     context: Option<Context>,
     image: Option<Image>,
+    /// How short of memory the OS last said the machine is.
+    pressure_level: MemoryPressureLevel,
+    /// Share of the width the left pane takes in split view.
+    split_ratio: f32,
+    /// Whether key presses go to the page rather than the browser's
+    /// shortcuts, for web apps with shortcuts of their own.
+    focus_mode: bool,
+    /// The kind of text the page's focused field takes, `None` if nothing
+    /// editable has focus.
+    input_mode: TextInputMode,
+    /// Where the pointer last moved over the page, in view coordinates, for
+    /// inspecting the element under it.
+    last_cursor_pos: Point,
 }
 
 impl Global for BrowserState {}
 
+impl BrowserState {
+    /// No browser yet, the pointer at the page's top left.
+    fn new() -> Self {
+        Self {
+            browser: None,
+            mock: None,
+            context: None,
+            image: None,
+            pressure_level: MemoryPressureLevel::default(),
+            split_ratio: 0.5,
+            focus_mode: false,
+            input_mode: TextInputMode::None,
+            last_cursor_pos: Point { x: 0, y: 0 },
+        }
+    }
+
+    /// Note the pointer moving to `position` in the window, over a page drawn
+    /// at `origin`.
+    fn pointer_moved(&mut self, position: gpui::Point<Pixels>, origin: gpui::Point<Pixels>) {
+        self.last_cursor_pos = view_point(position, origin);
+    }
+
+    /// Where inspecting the element under the pointer looks, in view
+    /// coordinates: where the pointer last moved over the page.
+    fn inspect_element_at(&self) -> Point {
+        self.last_cursor_pos
+    }
+
+    fn host(&self) -> Option<BrowserHost> {
+        self.browser
+            .as_ref()
+            .and_then(|browser| browser.get_host().ok())
+    }
+
+    /// The URL and title of the page currently shown.
+    fn current_page(&self) -> Option<(String, String)> {
+        if let Some(mock) = self.mock.as_ref() {
+            let url = mock.url();
+            return Some((url.clone(), url));
+        }
+
+        let entry = self
+            .browser
+            .as_ref()?
+            .get_host()
+            .and_then(|host| host.get_visible_navigation_entry())
+            .ok()?;
+        let url = entry.get_url().ok()?;
+        let title = entry
+            .get_title()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| url.clone());
+
+        Some((url, title))
+    }
+}
+
+/// Load a URL in the main frame of the browser.
+fn navigate(cx: &mut GpuiApp, url: &str) {
+    if let Some(mock) = cx.global::<BrowserState>().mock.as_ref() {
+        mock.load_url(url);
+        return;
+    }
+
+    let frame = cx
+        .global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_main_frame().ok().flatten());
+
+    if let Some(frame) = frame {
+        if let Err(e) = frame.load_url(url) {
+            eprintln!("Error loading {}: {}", url, e);
+        }
+    }
+}
+
+/// Load the current page again.
+fn reload(cx: &GpuiApp) {
+    let state = cx.global::<BrowserState>();
+    if let Some(mock) = state.mock.as_ref() {
+        mock.reload();
+    } else if let Some(browser) = state.browser.as_ref() {
+        if let Err(e) = browser.reload() {
+            eprintln!("Error reloading: {}", e);
+        }
+    }
+}
+
 // SVG button component
 fn svg_button(
     svg_path: &str,
     size: f32,
     color: impl Into<gpui::Hsla>,
-    _on_click: impl Fn(&mut Window, &mut GpuiApp) + 'static,
+    on_click: impl Fn(&mut Window, &mut GpuiApp) + 'static,
+    cx: &GpuiApp,
 ) -> impl IntoElement {
+    let theme = cx.theme();
+
     let svg_path = svg_path.to_string();
     let color = color.into();
 
     div()
+        .id(SharedString::from(svg_path.clone()))
         .flex()
         .items_center()
         .justify_center()
         .size(px(size))
         .rounded_md()
         .cursor_pointer()
-        .hover(|this| this.bg(rgba(0x00000010)))
+        .hover(|this| this.bg(theme.ghost_hover))
+        .on_click(move |_, window, cx| on_click(window, cx))
         .child(svg().path(svg_path).size(px(size)).text_color(color))
 }
 
-struct WindowDemo {}
+struct WindowDemo {
+    bookmarks_bar: Entity<BookmarksBar>,
+    workspace_sidebar: Entity<WorkspaceSidebar>,
+    omnibox: Entity<Omnibox>,
+    audio_visualizer: Entity<AudioVisualizer>,
+    notification_toasts: Entity<NotificationToasts>,
+    notification_center: Option<(Entity<NotificationCenter>, Subscription)>,
+    security_panel: Option<(Entity<SecurityPanel>, Subscription)>,
+    autoplay_popover: Option<(Entity<AutoplayPopover>, Subscription)>,
+    translation_popover: Option<(Entity<TranslationPopover>, Subscription)>,
+    certificate_viewer: Option<(Entity<CertificateViewer>, Subscription)>,
+    page_action_popover: Option<(Entity<PageActionPopover>, Subscription)>,
+    extension_popup: Option<(Entity<ExtensionPopup>, Subscription)>,
+    auth_dialog: Option<(Entity<AuthDialog>, Subscription)>,
+    certificate_interstitial: Option<(Entity<CertificateInterstitial>, Subscription)>,
+    proxy_interstitial: Option<(Entity<ProxyInterstitial>, Subscription)>,
+    error_page: Option<(Entity<ErrorPage>, Subscription)>,
+    popup_blocked_bar: Option<(Entity<PopupBlockedBar>, Subscription)>,
+    save_password_bar: Option<(Entity<SavePasswordBar>, Subscription)>,
+    /// PDFs shown in place of pages, by the browser that loaded them.
+    pdf_views: HashMap<i32, Entity<PdfView>>,
+    permission_banner: Option<(Entity<PermissionBanner>, Subscription)>,
+    capture_sheet: Option<(Entity<CaptureSheet>, Subscription)>,
+    session_dialog: Option<(Entity<RestoreSessionDialog>, Subscription)>,
+    dev_tools_open: bool,
+    toast: Option<(Entity<Toast>, Subscription)>,
+    reader_available: bool,
+    reader_active: bool,
+    console_panel: Entity<ConsolePanel>,
+    console_visible: bool,
+    network_panel: Entity<NetworkPanel>,
+    network_visible: bool,
+    performance_panel: Option<Entity<PerformancePanel>>,
+    csp_panel: Option<Entity<CspPanel>>,
+    storage_panel: Option<Entity<StoragePanel>>,
+    cache_panel: Option<Entity<CachePanel>>,
+    js_dialog: Option<(Entity<JsDialog>, Subscription)>,
+    unresponsive_dialog: Option<(Entity<UnresponsiveDialog>, Subscription)>,
+    context_menu: Option<(Entity<ContextMenu>, Subscription)>,
+    autofill_dropdown: Option<(Entity<AutofillDropdown>, Subscription)>,
+    media_bar: Option<(Entity<MediaBar>, Subscription)>,
+    settings_panel: Option<(Entity<SettingsPanel>, Subscription)>,
+    command_palette: Option<(Entity<CommandPalette>, Subscription)>,
+    tab_search: Option<(Entity<TabSearch>, Subscription)>,
+    extensions_panel: Option<(Entity<ExtensionsPanel>, Subscription)>,
+    overflow_menu: Option<(Entity<OverflowMenu>, Subscription)>,
+    report_sheet: Option<(Entity<ReportSheet>, Subscription)>,
+    about_dialog: Option<(Entity<AboutDialog>, Subscription)>,
+    compose_sheet: Option<(Entity<ComposeSheet>, Subscription)>,
+    save_page_sheet: Option<(Entity<SavePageSheet>, Subscription)>,
+    print_preview: Option<(Entity<PrintPreview>, Subscription)>,
+    source_panel: Option<(Entity<SourcePanel>, Subscription)>,
+    split_pane: Option<(Entity<SplitPane>, Subscription)>,
+    /// Whether the split view divider is being dragged.
+    split_dragging: bool,
+    content_focus: FocusHandle,
+    page_ime: Entity<PageIme>,
+    page_editable: bool,
+    /// The on-screen keyboard shown for the focused field, in kiosks.
+    keyboard: Option<KeyboardLayout>,
+    content_bounds: Rc<Cell<Bounds<Pixels>>>,
+    drag_cursor: Option<CursorStyle>,
+    page_transition: Option<PageTransition>,
+    /// Whether the toolbar is shown over a fullscreen page.
+    chrome_revealed: bool,
+    /// Tells the toolbar's slides apart, so each one animates from the start.
+    chrome_slide: usize,
+    transitions: usize,
+    hovered_tab: Option<usize>,
+    tab_preview: Option<(usize, Option<Image>)>,
+    tab_menu: Option<(Entity<TabMenu>, Subscription)>,
+    _focus_subscriptions: Vec<Subscription>,
+}
+
+/// Convert a window position to view coordinates of a page drawn at
+/// `origin` in the window.
+fn view_point(position: gpui::Point<Pixels>, origin: gpui::Point<Pixels>) -> Point {
+    let position = position - origin;
 
-impl Render for WindowDemo {
-    fn render(
+    Point {
+        x: f32::from(position.x) as i32,
+        y: f32::from(position.y) as i32,
+    }
+}
+
+impl WindowDemo {
+    fn new(window: &mut Window, cx: &mut gpui::Context<Self>) -> Self {
+        cx.observe_global::<Bookmarks>(|_, cx| cx.notify()).detach();
+        cx.observe_global::<PictureInPicture>(|_, cx| cx.notify())
+            .detach();
+        cx.observe_global::<WindowManager>(|_, cx| cx.notify())
+            .detach();
+
+        // Closing the last window asks whether to quit first.
+        window.on_window_should_close(cx, window_manager::should_close);
+
+        // Follow the system from light to dark and back, when the theme is
+        // set to.
+        cx.observe_window_appearance(window, |_, window, cx| {
+            theme::on_appearance_changed(cx, window.appearance())
+        })
+        .detach();
+
+        // CEF calls back on its own threads, so pick up anything it queued
+        // for the UI on a timer.
+        cx.spawn_in(window, async move |this, cx| loop {
+            cx.background_executor()
+                .timer(Duration::from_millis(100))
+                .await;
+
+            if this
+                .update_in(cx, |this, window, cx| this.poll(window, cx))
+                .is_err()
+            {
+                break;
+            }
+        })
+        .detach();
+
+        // Without CEF, the mock browser paints frames of its own.
+        if cx.global::<BrowserState>().mock.is_some() {
+            cx.spawn(async move |this, cx| loop {
+                cx.background_executor().timer(mock::FRAME_INTERVAL).await;
+
+                if this
+                    .update(cx, |_, cx| {
+                        mock::paint(cx);
+                        cx.notify();
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            })
+            .detach();
+        }
+
+        // Mirror GPUI focus on the page content into CEF, so the page only
+        // shows a caret and takes keys while it's focused.
+        let content_focus = cx.focus_handle();
+        let omnibox = cx.new(|cx| Omnibox::new(window, cx));
+        let focus_subscriptions = vec![
+            // Hand the keyboard back to the page once the omnibox is done.
+            cx.subscribe_in(&omnibox, window, |this, _, _: &DismissEvent, window, _| {
+                window.focus(&this.content_focus)
+            }),
+            cx.on_focus_in(&content_focus, window, |_, _, cx| {
+                focus::set_focus(cx, true)
+            }),
+            cx.on_focus_out(&content_focus, window, |this, _, _, cx| {
+                this.page_ime.update(cx, |ime, cx| ime.cancel(cx));
+                focus::set_focus(cx, false);
+            }),
+            cx.observe_window_activation(window, |this, window, cx| {
+                // The window in front holds the tabs.
+                if window.is_window_active() {
+                    window_manager::switch_to(cx, window.window_handle().window_id());
+                }
+
+                let focused = window.is_window_active() && this.content_focus.is_focused(window);
+                focus::set_focus(cx, focused);
+            }),
+        ];
+
+        let session = cx.global::<Session>().clone();
+        let session_dialog = (session.crashed() && session.has_saved_tabs()).then(|| {
+            let dialog = cx.new(RestoreSessionDialog::new);
+            let subscription = cx.subscribe(&dialog, |this, _, _: &DismissEvent, cx| {
+                this.session_dialog = None;
+                cx.notify();
+            });
+
+            (dialog, subscription)
+        });
+
+        Self {
+            bookmarks_bar: cx.new(BookmarksBar::new),
+            workspace_sidebar: cx.new(WorkspaceSidebar::new),
+            omnibox,
+            audio_visualizer: cx.new(AudioVisualizer::new),
+            notification_toasts: cx.new(|_| NotificationToasts::new()),
+            notification_center: None,
+            security_panel: None,
+            autoplay_popover: None,
+            translation_popover: None,
+            certificate_viewer: None,
+            page_action_popover: None,
+            extension_popup: None,
+            auth_dialog: None,
+            certificate_interstitial: None,
+            proxy_interstitial: None,
+            error_page: None,
+            popup_blocked_bar: None,
+            save_password_bar: None,
+            pdf_views: HashMap::new(),
+            permission_banner: None,
+            capture_sheet: None,
+            session_dialog,
+            dev_tools_open: false,
+            toast: None,
+            reader_available: false,
+            reader_active: false,
+            console_panel: cx.new(ConsolePanel::new),
+            console_visible: false,
+            network_panel: cx.new(NetworkPanel::new),
+            network_visible: false,
+            performance_panel: None,
+            csp_panel: None,
+            storage_panel: None,
+            cache_panel: None,
+            js_dialog: None,
+            unresponsive_dialog: None,
+            context_menu: None,
+            autofill_dropdown: None,
+            media_bar: None,
+            settings_panel: None,
+            command_palette: None,
+            tab_search: None,
+            extensions_panel: None,
+            overflow_menu: None,
+            report_sheet: None,
+            about_dialog: None,
+            compose_sheet: None,
+            save_page_sheet: None,
+            print_preview: None,
+            source_panel: None,
+            split_pane: None,
+            split_dragging: false,
+            content_focus,
+            page_ime: cx.new(|_| PageIme::default()),
+            page_editable: false,
+            keyboard: None,
+            content_bounds: Rc::default(),
+            drag_cursor: None,
+            page_transition: None,
+            chrome_revealed: false,
+            chrome_slide: 0,
+            transitions: 0,
+            hovered_tab: None,
+            tab_preview: None,
+            tab_menu: None,
+            _focus_subscriptions: focus_subscriptions,
+        }
+    }
+
+    /// Replace the current toast, if any.
+    fn show_toast(
         &mut self,
-        _window: &mut Window,
-        cx: &mut gpui::Context<'_, WindowDemo>,
-    ) -> impl IntoElement {
-        let state = cx.global::<BrowserState>();
+        build: impl FnOnce(&mut gpui::Context<Toast>) -> Toast,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let toast = cx.new(build);
+        let subscription = cx.subscribe(&toast, |this, _, _: &DismissEvent, cx| {
+            this.toast = None;
+            cx.notify();
+        });
 
-        div()
-            .border_1()
-            .border_color(rgba(0xd3d9d92b))
-            .rounded_xl()
-            .bg(rgba(0x0404055e))
-            .size_full()
-            .justify_start()
-            .overflow_hidden()
-            .content_start()
-            .child(
-                div()
-                    .pl(px(84.)) // Left padding to clear traffic lights
-                    .pt(px(10.))
-                    .child(
-                        div()
-                            .flex()
-                            .items_center()
-                            .gap_2()
-                            .child(
-                                // Back button
-                                svg_button("back.svg", 14.0, rgb(0xf2f2f2), |_, _| {
-                                    println!("Back clicked!")
-                                }),
-                            )
-                            .child(
-                                // Forward button
-                                svg_button("forward.svg", 14.0, rgba(0xd3d9d92b), |_, _| {
-                                    println!("Forward clicked!")
-                                }),
-                            )
-                            .child(
-                                // Refresh button
-                                svg_button("rotate-cw.svg", 12.0, rgb(0xf2f2f2), |_, _| {
-                                    println!("Refresh clicked!")
-                                }),
-                            )
-                            .child(
-                                div()
-                                    .flex()
-                                    .border_1()
-                                    .border_color(rgba(0xd3d9d92b))
-                                    .rounded_md()
-                                    .h_8()
-                                    .w_64()
-                                    .items_center()
-                                    .child(
-                                        div()
-                                            .flex()
-                                            .p_3()
-                                            .h_full()
-                                            .items_center()
-                                            .justify_start()
-                                            .bg(linear_gradient(
-                                                150.,
-                                                linear_color_stop(rgba(0x2e2e2e1c), 0.1),
-                                                linear_color_stop(rgba(0x6161621c), 0.8),
-                                            ))
-                                            .w_full()
-                                            .rounded_md()
-                                            .children(vec![
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .h_full()
-                                                    .justify_center()
-                                                    .pr_2p5()
-                                                    .child(
-                                                        svg()
-                                                            .path("vercel.svg")
-                                                            .size(px(10.0))
-                                                            .text_color(rgb(0xfefefe)),
-                                                    ),
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .h_full()
-                                                    .justify_center()
-                                                    .text_color(rgb(0xd1d1d1))
-                                                    .text_xs()
-                                                    .text_center()
-                                                    .line_height(px(10.0))
-                                                    .mt(px(1.0))
-                                                    .child("vercel.com"),
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .h_full()
-                                                    .justify_center()
-                                                    .ml_auto()
-                                                    .child(
-                                                        svg()
-                                                            .path("close.svg")
-                                                            .size(px(10.0))
-                                                            .text_color(rgba(0xffffffb3)),
-                                                    ),
-                                            ]),
-                                    ),
-                            )
-                            .child(
-                                div()
-                                    .px_1()
-                                    .py_1()
-                                    .bg(linear_gradient(
-                                        150.,
-                                        linear_color_stop(rgba(0x2e2e2e1c), 0.05),
-                                        linear_color_stop(rgba(0x6161621c), 0.85),
-                                    ))
-                                    .border_1()
-                                    .border_color(rgba(0xd3d9d92b))
-                                    .rounded_md()
-                                    .items_center()
-                                    .justify_center()
-                                    .child(
-                                        svg()
-                                            .path("plus.svg")
-                                            .size(px(12.0))
-                                            .text_color(rgb(0xf2f2f2)),
-                                    ),
-                            ),
-                    ),
-            )
-            // Render the browser content
-            .child(
-                div()
-                    .flex()
-                    .flex_1()
-                    .bg(rgb(0xffffff))
-                    .items_center()
-                    .justify_center()
-                    .child(if let Some(image) = &state.image {
-                        div()
-                            .size_full()
-                            .child(img(ImageSource::from(Arc::new(image.clone()))))
-                    } else {
-                        div().child("Loading...")
-                    }),
-            )
+        self.toast = Some((toast, subscription));
+        cx.notify();
+    }
+
+    /// Let the user know where a screenshot was saved.
+    fn screenshot_saved(&mut self, result: Result<PathBuf>, cx: &mut gpui::Context<Self>) {
+        match result {
+            Ok(path) => self.show_toast(
+                |cx| {
+                    Toast::new(t!("toast.saved", path = path.display()), cx)
+                        .action(t!("toast.reveal_in_finder"), move |_, cx| {
+                            cx.reveal_path(&path)
+                        })
+                },
+                cx,
+            ),
+            Err(e) => {
+                eprintln!("Error saving screenshot: {}", e);
+                self.show_toast(
+                    |cx| Toast::new(t!("toast.screenshot_failed", error = e), cx),
+                    cx,
+                );
+            }
+        }
+    }
+
+    /// Ask where to keep a freshly printed PDF and move it there.
+    fn pdf_printed(&mut self, pdf: PrintedPdf, cx: &mut gpui::Context<Self>) {
+        if !pdf.ok {
+            print::discard(&pdf);
+            self.show_toast(|cx| Toast::new(t!("toast.printing_failed"), cx), cx);
+            return;
+        }
+
+        let directory = cx.global::<settings::Settings>().download_path.clone();
+        let destination = cx.prompt_for_new_path(&directory, Some(&pdf.suggested_name));
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(destination))) = destination.await else {
+                print::discard(&pdf);
+                return;
+            };
+
+            let result = print::save(&pdf, &destination);
+            this.update(cx, |this, cx| match result {
+                Ok(()) => this.show_toast(
+                    |cx| {
+                        Toast::new(t!("toast.saved", path = destination.display()), cx)
+                            .action(t!("toast.reveal_in_finder"), move |_, cx| {
+                                cx.reveal_path(&destination)
+                            })
+                    },
+                    cx,
+                ),
+                Err(e) => {
+                    eprintln!("Error saving PDF: {}", e);
+                    this.show_toast(
+                        |cx| Toast::new(t!("toast.saving_pdf_failed", error = e), cx),
+                        cx,
+                    );
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Convert a window position to the page's view coordinates.
+    fn view_point(&self, position: gpui::Point<Pixels>) -> Point {
+        view_point(position, self.content_bounds.get().origin)
+    }
+
+    /// Slide the toolbar over a fullscreen page when the pointer reaches the top
+    /// edge, and away once it leaves the toolbar.
+    fn reveal_chrome(&mut self, y: f32, cx: &mut gpui::Context<Self>) {
+        let revealed = match self.chrome_revealed {
+            false => y <= fullscreen::REVEAL_EDGE,
+            true => y <= fullscreen::CHROME_HEIGHT,
+        };
+
+        if revealed != self.chrome_revealed {
+            self.chrome_revealed = revealed;
+            self.chrome_slide += 1;
+            cx.notify();
+        }
+    }
+
+    /// Bring the page gone back or forward to in over the one it replaced.
+    fn transition_page(
+        &mut self,
+        direction: NavigationDirection,
+        previous: Option<Image>,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.transitions += 1;
+        let id = self.transitions;
+        let style = cx.global::<settings::Settings>().page_transition;
+        self.page_transition = Some(PageTransition::new(id, style, direction, previous));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(transitions::TRANSITION_DURATION)
+                .await;
+
+            this.update(cx, |this, cx| {
+                if this
+                    .page_transition
+                    .as_ref()
+                    .is_some_and(|transition| transition.id == id)
+                {
+                    this.page_transition = None;
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn open_tab_menu(
+        &mut self,
+        index: usize,
+        position: gpui::Point<Pixels>,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let menu = cx.new(|_| TabMenu::new(index, position));
+        let subscription = cx.subscribe(&menu, |this, _, _: &DismissEvent, cx| {
+            this.tab_menu = None;
+            cx.notify();
+        });
+
+        self.tab_menu = Some((menu, subscription));
+        cx.notify();
+    }
+
+    /// Show a preview of the tab at `index` once it's been hovered for a
+    /// moment, and hide it when the pointer leaves.
+    fn hover_tab(&mut self, index: usize, hovered: bool, cx: &mut gpui::Context<Self>) {
+        if !hovered {
+            if self.hovered_tab == Some(index) {
+                self.hovered_tab = None;
+                self.tab_preview = None;
+                cx.notify();
+            }
+            return;
+        }
+
+        self.hovered_tab = Some(index);
+        cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(thumbnails::HOVER_DELAY)
+                .await;
+
+            this.update(cx, |this, cx| {
+                if this.hovered_tab == Some(index) {
+                    this.show_tab_preview(index, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn show_tab_preview(&mut self, index: usize, cx: &mut gpui::Context<Self>) {
+        let Some(tab) = cx.global::<TabManager>().tabs().get(index) else {
+            return;
+        };
+
+        let thumbnail = cx.global::<ThumbnailCache>().thumbnail(&tab.browser);
+        self.tab_preview = Some((index, thumbnail));
+        cx.notify();
+    }
+
+    fn open_settings(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.settings_panel.is_some() {
+            return;
+        }
+
+        let panel = cx.new(SettingsPanel::new);
+        let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+            this.settings_panel = None;
+            cx.notify();
+        });
+
+        self.settings_panel = Some((panel, subscription));
+        cx.notify();
+    }
+
+    fn toggle_command_palette(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if self.command_palette.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let palette = cx.new(|cx| CommandPalette::new(window, cx));
+        let subscription = cx.subscribe(&palette, |this, _, _: &DismissEvent, cx| {
+            this.command_palette = None;
+            cx.notify();
+        });
+
+        window.focus(&palette.focus_handle(cx));
+        self.command_palette = Some((palette, subscription));
+        cx.notify();
+    }
+
+    fn toggle_tab_search(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if self.tab_search.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let search = cx.new(|cx| TabSearch::new(window, cx));
+        let subscription = cx.subscribe(&search, |this, _, _: &DismissEvent, cx| {
+            this.tab_search = None;
+            cx.notify();
+        });
+
+        window.focus(&search.focus_handle(cx));
+        self.tab_search = Some((search, subscription));
+        cx.notify();
+    }
+
+    /// Open the toolbar's overflow menu below `position`, or close it if it's
+    /// open.
+    fn toggle_overflow_menu(
+        &mut self,
+        position: gpui::Point<Pixels>,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        cx.notify();
+        if self.overflow_menu.take().is_some() {
+            return;
+        }
+
+        let menu = cx.new(|_| OverflowMenu::new(position));
+        let subscription = cx.subscribe(&menu, |this, _, _: &DismissEvent, cx| {
+            this.overflow_menu = None;
+            cx.notify();
+        });
+
+        self.overflow_menu = Some((menu, subscription));
+    }
+
+    /// Show the message an email link addresses in a form, replacing one
+    /// already open.
+    fn open_compose_sheet(
+        &mut self,
+        link: mailto::MailtoLink,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let sheet = cx.new(|cx| ComposeSheet::new(link, window, cx));
+        let subscription = cx.subscribe(&sheet, |this, _, _: &DismissEvent, cx| {
+            this.compose_sheet = None;
+            cx.notify();
+        });
+
+        self.compose_sheet = Some((sheet, subscription));
+        cx.notify();
+    }
+
+    fn open_about_dialog(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.about_dialog.is_some() {
+            return;
+        }
+
+        let dialog = cx.new(AboutDialog::new);
+        let subscription = cx.subscribe(&dialog, |this, _, _: &DismissEvent, cx| {
+            this.about_dialog = None;
+            cx.notify();
+        });
+
+        self.about_dialog = Some((dialog, subscription));
+        cx.notify();
+    }
+
+    fn open_report_sheet(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if self.report_sheet.is_some() {
+            return;
+        }
+
+        let sheet = cx.new(|cx| ReportSheet::new(window, cx));
+        let subscription = cx.subscribe(&sheet, |this, _, _: &DismissEvent, cx| {
+            this.report_sheet = None;
+            cx.notify();
+        });
+
+        self.report_sheet = Some((sheet, subscription));
+        cx.notify();
+    }
+
+    fn open_save_page_sheet(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.save_page_sheet.is_some() {
+            return;
+        }
+
+        let sheet = cx.new(|_| SavePageSheet::new());
+        let subscription = cx.subscribe(&sheet, |this, _, _: &DismissEvent, cx| {
+            this.save_page_sheet = None;
+            cx.notify();
+        });
+
+        self.save_page_sheet = Some((sheet, subscription));
+        cx.notify();
+    }
+
+    fn open_extensions(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.extensions_panel.is_some() {
+            return;
+        }
+
+        let panel = cx.new(ExtensionsPanel::new);
+        let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+            this.extensions_panel = None;
+            cx.notify();
+        });
+
+        self.extensions_panel = Some((panel, subscription));
+        cx.notify();
+    }
+
+    fn open_print_preview(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.print_preview.is_some() || cx.global::<BrowserState>().browser.is_none() {
+            return;
+        }
+
+        let preview = cx.new(PrintPreview::new);
+        let subscription = cx.subscribe(&preview, |this, _, _: &DismissEvent, cx| {
+            this.print_preview = None;
+            cx.notify();
+        });
+
+        self.print_preview = Some((preview, subscription));
+        cx.notify();
+    }
+
+    fn toggle_notification_center(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.notification_center.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let center = cx.new(NotificationCenter::new);
+        let subscription = cx.subscribe(&center, |this, _, _: &DismissEvent, cx| {
+            this.notification_center = None;
+            cx.notify();
+        });
+
+        self.notification_center = Some((center, subscription));
+        cx.notify();
+    }
+
+    fn toggle_security_panel(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.security_panel.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let panel = cx.new(SecurityPanel::new);
+        let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+            this.security_panel = None;
+            cx.notify();
+        });
+
+        self.security_panel = Some((panel, subscription));
+        cx.notify();
+    }
+
+    fn toggle_autoplay_popover(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.autoplay_popover.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let popover = cx.new(AutoplayPopover::new);
+        let subscription = cx.subscribe(&popover, |this, _, _: &DismissEvent, cx| {
+            this.autoplay_popover = None;
+            cx.notify();
+        });
+
+        self.autoplay_popover = Some((popover, subscription));
+        cx.notify();
+    }
+
+    fn open_translation_popover(&mut self, cx: &mut gpui::Context<Self>) {
+        let popover = cx.new(TranslationPopover::new);
+        let subscription = cx.subscribe(&popover, |this, _, _: &DismissEvent, cx| {
+            this.translation_popover = None;
+            cx.notify();
+        });
+
+        self.translation_popover = Some((popover, subscription));
+        cx.notify();
+    }
+
+    fn toggle_certificate_viewer(&mut self, cx: &mut gpui::Context<Self>) {
+        if self.certificate_viewer.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        let viewer = cx.new(CertificateViewer::new);
+        let subscription = cx.subscribe(&viewer, |this, _, _: &DismissEvent, cx| {
+            this.certificate_viewer = None;
+            cx.notify();
+        });
+
+        self.certificate_viewer = Some((viewer, subscription));
+        cx.notify();
+    }
+
+    /// Show the tab next to the active one in a pane on the right, or go back
+    /// to a single page.
+    fn toggle_split_view(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        cx.notify();
+        if self.split_pane.take().is_some() {
+            split::close(cx);
+            return;
+        }
+
+        let Some(browser) = split::open(cx) else {
+            return;
+        };
+        let pane = cx.new(|cx| SplitPane::new(browser, window, cx));
+        let subscription = cx.subscribe(&pane, |this, _, _: &DismissEvent, cx| {
+            this.split_pane = None;
+            split::close(cx);
+            cx.notify();
+        });
+
+        self.split_pane = Some((pane, subscription));
+    }
+
+    /// Move the split view divider to follow the pointer at `x`.
+    fn drag_split_divider(&mut self, x: Pixels, cx: &mut gpui::Context<Self>) {
+        let Some((pane, _)) = self.split_pane.as_ref() else {
+            return;
+        };
+
+        let left = self.content_bounds.get().left();
+        let width = pane.read(cx).bounds().right() - left;
+        if width > px(0.) {
+            split::set_ratio(cx, (x - left) / width);
+            cx.notify();
+        }
+    }
+
+    /// Open the popover of the page action at `index`, or close it if it's
+    /// already open.
+    fn toggle_page_action(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let open = self.page_action_popover.take();
+        cx.notify();
+
+        if open.is_some_and(|(popover, _)| popover.read(cx).action == index) {
+            return;
+        }
+
+        let Some(popover) = page_actions::open_popover(index, window, cx) else {
+            return;
+        };
+        let subscription = cx.subscribe(&popover, |this, _, _: &DismissEvent, cx| {
+            this.page_action_popover = None;
+            cx.notify();
+        });
+
+        self.page_action_popover = Some((popover, subscription));
+    }
+
+    /// Open the popup of the extension `action` is the button of, below
+    /// `position` where the button was pressed, or close it if it's open.
+    fn toggle_extension_popup(
+        &mut self,
+        action: &ExtensionAction,
+        position: gpui::Point<Pixels>,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        cx.notify();
+        if let Some((popup, _)) = self.extension_popup.take() {
+            let open = popup.read(cx).path == action.path;
+            popup.update(cx, |popup, cx| popup.dismiss(cx));
+            if open {
+                return;
+            }
+        }
+
+        let Some(popup) = ExtensionPopup::open(action, position, window, cx) else {
+            return;
+        };
+        let subscription = cx.subscribe(&popup, |this, _, _: &DismissEvent, cx| {
+            this.extension_popup = None;
+            cx.notify();
+        });
+
+        self.extension_popup = Some((popup, subscription));
+    }
+
+    fn poll(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        // The other windows' tabs are parked, with nothing to pick up.
+        let id = window.window_handle().window_id();
+        if !cx.global::<WindowManager>().is_live(id) {
+            return;
+        }
+        window_manager::set_title(window, cx);
+
+        view::update(cx, self.content_bounds.get().size, window.scale_factor());
+        accessibility::poll(window, self.content_bounds.get(), cx);
+
+        if let Some((pane, _)) = self.split_pane.as_ref() {
+            if split::check(cx) {
+                pane.update(cx, |pane, cx| pane.poll(window, cx));
+            } else {
+                self.split_pane = None;
+                cx.notify();
+            }
+        }
+
+        if let Some((popup, _)) = self.extension_popup.as_ref() {
+            popup.update(cx, |popup, cx| popup.poll(window, cx));
+        }
+
+        if touch_bar::poll(window, cx) {
+            window.focus(&self.omnibox.focus_handle(cx));
+        }
+        speech::poll(cx);
+        media::poll(cx);
+        benchmark::poll(cx);
+        if speech_recognition::poll(cx) {
+            cx.notify();
+        }
+
+        if let Some(level) = cx.global::<MemoryPressureHandler>().take_pending() {
+            if memory::on_pressure(cx, level) {
+                self.show_toast(|cx| Toast::new(t!("memory.freed"), cx), cx);
+            }
+        }
+
+        let page_url = cx
+            .global::<BrowserState>()
+            .current_page()
+            .map(|(url, _)| url)
+            .unwrap_or_default();
+        self.omnibox
+            .update(cx, |omnibox, cx| omnibox.set_page_url(page_url, cx));
+
+        while let Some(browser) = cx.global::<Popups>().take_pending_tab() {
+            tabs::add(cx, browser, true);
+            cx.notify();
+        }
+
+        if cx.global::<ProcessMonitor>().take_changed() {
+            cx.notify();
+        }
+
+        let ready = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| cx.global::<PageTransitions>().take_ready(browser));
+        if let Some((direction, previous)) = ready {
+            self.transition_page(direction, previous, cx);
+        }
+
+        let page_focus = cx.global::<PageFocus>().clone();
+        if page_focus.take_took_focus() && self.content_focus.is_focused(window) {
+            window.blur();
+        }
+
+        if page_focus.take_got_focus() && !self.content_focus.is_focused(window) {
+            window.focus(&self.content_focus);
+        }
+
+        if cx.global::<ConsoleLog>().take_changed() {
+            self.console_panel
+                .update(cx, |panel, cx| panel.scroll_to_bottom(cx));
+        }
+
+        let sockets_changed = cx.global::<WebSocketLog>().take_changed();
+        if (cx.global::<NetworkLog>().take_changed() || sockets_changed) && self.network_visible {
+            self.network_panel.update(cx, |_, cx| cx.notify());
+        }
+
+        if cx.global::<NetworkThrottling>().take_changed() {
+            cx.notify();
+        }
+
+        if cx.global::<CspInspector>().take_changed() {
+            if let Some(panel) = self.csp_panel.as_ref() {
+                panel.update(cx, |_, cx| cx.notify());
+            }
+            if let Some((panel, _)) = self.security_panel.as_ref() {
+                panel.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        if cx.global::<StorageInspector>().take_changed() {
+            if let Some(panel) = self.storage_panel.as_ref() {
+                panel.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        if cx.global::<CacheInspector>().take_changed() {
+            if let Some(panel) = self.cache_panel.as_ref() {
+                panel.update(cx, |panel, cx| panel.on_changed(cx));
+            }
+        }
+
+        if cx.global::<SecureDns>().take_changed() {
+            if let Some((panel, _)) = self.security_panel.as_ref() {
+                panel.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        if cx.global::<AudioCapture>().take_changed() {
+            self.audio_visualizer.update(cx, |_, cx| cx.notify());
+        }
+
+        let notifications = cx.global::<Notifications>().clone();
+        while let Some(id) = notifications.take_shown() {
+            self.notification_toasts
+                .update(cx, |toasts, cx| toasts.push(id, cx));
+            cx.notify();
+        }
+
+        for id in notifications.take_closed() {
+            self.notification_toasts
+                .update(cx, |toasts, cx| toasts.remove(id, cx));
+        }
+
+        if cx.global::<WebCompatReports>().take_changed() {
+            if let Some((sheet, _)) = self.report_sheet.as_ref() {
+                sheet.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        if cx.global::<FaviconCache>().take_changed() {
+            cx.notify();
+        }
+
+        let previews = cx.global::<LinkPreviews>().clone();
+        if let Some((url, generation)) = previews.take_scheduled() {
+            link_preview::schedule(cx, url, generation);
+        }
+        if previews.take_changed() {
+            cx.notify();
+        }
+
+        if cx.global::<PageArchiver>().take_changed() {
+            if let Some((sheet, _)) = self.save_page_sheet.as_ref() {
+                sheet.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        if cx.global::<Extensions>().take_changed() {
+            if let Some((panel, _)) = self.extensions_panel.as_ref() {
+                panel.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        let dialogs = cx.global::<JsDialogs>().clone();
+        if dialogs.take_reset() && self.js_dialog.take().is_some() {
+            cx.notify();
+        }
+
+        if self.js_dialog.is_none() {
+            if let Some(request) = dialogs.take_pending() {
+                let dialog = cx.new(|cx| JsDialog::new(request, window, cx));
+                let subscription = cx.subscribe(&dialog, |this, _, _: &DismissEvent, cx| {
+                    this.js_dialog = None;
+                    cx.notify();
+                });
+
+                self.js_dialog = Some((dialog, subscription));
+                cx.notify();
+            }
+        }
+
+        let hangs = cx.global::<HangMonitor>().clone();
+        if hangs.take_changed() {
+            self.unresponsive_dialog = hangs.unresponsive().map(|browser| {
+                let dialog = cx.new(|_| UnresponsiveDialog::new(&browser));
+                let subscription = cx.subscribe(&dialog, |this, _, _: &DismissEvent, cx| {
+                    this.unresponsive_dialog = None;
+                    cx.notify();
+                });
+
+                (dialog, subscription)
+            });
+            cx.notify();
+        }
+
+        let reader = cx.global::<ReaderMode>().clone();
+        if let Some(article) = reader.take_article() {
+            reader::show(cx, article);
+        }
+
+        let reader_available = reader.is_available();
+        let reader_active = reader.is_active(cx);
+        if (reader_available, reader_active) != (self.reader_available, self.reader_active) {
+            self.reader_available = reader_available;
+            self.reader_active = reader_active;
+            cx.notify();
+        }
+
+        if let Some(fullscreen) = cx.global::<PageFullscreen>().take_pending() {
+            if window.is_fullscreen() != fullscreen {
+                window.toggle_fullscreen();
+            }
+
+            self.chrome_revealed = false;
+            self.chrome_slide += 1;
+            cx.notify();
+        }
+
+        while let Some(query) = cx.global::<MessageRouter>().take_pending() {
+            message_router::handle(cx, query);
+        }
+
+        if cx.global_mut::<BlockedAutoplay>().take_changed() {
+            cx.notify();
+        }
+
+        let menus = cx.global::<ContextMenus>().clone();
+        if menus.take_dismissed() && self.context_menu.take().is_some() {
+            cx.notify();
+        }
+
+        if let Some(request) = menus.take_pending() {
+            let origin = self.content_bounds.get().origin;
+            let menu = cx.new(|_| ContextMenu::new(request, origin));
+            let subscription = cx.subscribe(&menu, |this, _, _: &DismissEvent, cx| {
+                this.context_menu = None;
+                cx.notify();
+            });
+
+            self.context_menu = Some((menu, subscription));
+            cx.notify();
+        }
+
+        menus.run_actions(window, cx);
+
+        let sources = cx.global::<SourceViewer>().clone();
+        while let Some(url) = sources.take_requested() {
+            source::view(cx, &url);
+        }
+
+        let mailto = cx.global::<MailtoHandler>().clone();
+        while let Some(link) = mailto.take_requested() {
+            match cx.global::<settings::Settings>().mail_action {
+                MailAction::Compose => self.open_compose_sheet(link, window, cx),
+                MailAction::DefaultClient | MailAction::WebMail => mailto::open(cx, &link),
+            }
+        }
+
+        let translator = cx.global::<Translator>().clone();
+        if translator.take_opened() {
+            self.open_translation_popover(cx);
+        }
+        if translator.take_changed() {
+            if let Some((popover, _)) = self.translation_popover.as_ref() {
+                popover.update(cx, |_, cx| cx.notify());
+            }
+        }
+
+        while let Some(document) = sources.take_loaded() {
+            match self.source_panel.as_ref() {
+                Some((panel, _)) => {
+                    panel.update(cx, |panel, cx| panel.set_document(document, cx));
+                }
+                None => {
+                    let panel = cx.new(|cx| SourcePanel::new(document, cx));
+                    let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+                        this.source_panel = None;
+                        cx.notify();
+                    });
+
+                    self.source_panel = Some((panel, subscription));
+                    cx.notify();
+                }
+            }
+        }
+
+        let media = cx.global::<MediaSessions>().clone();
+        if media.take_changed() {
+            match (&self.media_bar, media.is_active()) {
+                (Some((bar, _)), true) => bar.update(cx, |bar, cx| bar.show(cx)),
+                (Some((bar, _)), false) => bar.update(cx, |bar, cx| bar.hide(cx)),
+                (None, true) => {
+                    let bar = cx.new(MediaBar::new);
+                    let subscription = cx.subscribe(&bar, |this, _, _: &DismissEvent, cx| {
+                        this.media_bar = None;
+                        cx.notify();
+                    });
+
+                    self.media_bar = Some((bar, subscription));
+                    cx.notify();
+                }
+                (None, false) => {}
+            }
+        }
+
+        let autofill = cx.global::<Autofill>().clone();
+        if autofill.take_dismissed() && self.autofill_dropdown.take().is_some() {
+            cx.notify();
+        }
+
+        if let Some(request) = autofill.take_pending() {
+            let origin = self.content_bounds.get().origin;
+            let dropdown = cx.new(|_| AutofillDropdown::new(request, origin));
+            let subscription = cx.subscribe(&dropdown, |this, _, _: &DismissEvent, cx| {
+                this.autofill_dropdown = None;
+                cx.notify();
+            });
+
+            self.autofill_dropdown = Some((dropdown, subscription));
+            cx.notify();
+        }
+
+        let print_jobs = cx.global::<PrintJobs>().clone();
+        if let Some(pdf) = print_jobs.take_finished() {
+            match (pdf.preview, self.print_preview.as_ref()) {
+                (false, _) => self.pdf_printed(pdf, cx),
+                (true, Some((preview, _))) => {
+                    preview.update(cx, |preview, cx| preview.set_pdf(pdf, cx))
+                }
+                (true, None) => print::discard(&pdf),
+            }
+        }
+
+        if print_jobs.take_requested() {
+            self.open_print_preview(cx);
+        }
+
+        if let Some(bounds) = cx.global::<ElementCaptures>().take_pending() {
+            let result = screenshot::save_element(cx, &bounds);
+            self.screenshot_saved(result, cx);
+        }
+
+        let page_editable = cx.global::<ImeState>().is_editable();
+        if page_editable != self.page_editable {
+            self.page_editable = page_editable;
+            cx.notify();
+        }
+
+        let input_mode = cx.global::<ImeState>().input_mode();
+        if input_mode != cx.global::<BrowserState>().input_mode {
+            cx.global_mut::<BrowserState>().input_mode = input_mode;
+            self.keyboard = virtual_keyboard::layout_for(input_mode);
+            cx.notify();
+        }
+
+        let drag_cursor = cx.global::<PageDrag>().cursor();
+        if drag_cursor != self.drag_cursor {
+            self.drag_cursor = drag_cursor;
+            cx.notify();
+        }
+
+        if cx.global::<ElementPicker>().take_changed() {
+            cx.notify();
+        }
+
+        // DevTools can be closed from its own window.
+        let dev_tools_open = devtools::is_open(cx);
+        if dev_tools_open != self.dev_tools_open {
+            self.dev_tools_open = dev_tools_open;
+            cx.notify();
+        }
+
+        if self.auth_dialog.is_none() {
+            if let Some(request) = cx.global::<CredentialStore>().take_pending() {
+                let dialog = cx.new(|cx| AuthDialog::new(request, window, cx));
+                let subscription = cx.subscribe(&dialog, |this, _, _: &DismissEvent, cx| {
+                    this.auth_dialog = None;
+                    cx.notify();
+                });
+
+                self.auth_dialog = Some((dialog, subscription));
+                cx.notify();
+            }
+        }
+
+        if let Some(error) = cx.global::<CertificateExceptions>().take_pending() {
+            let interstitial = cx.new(|_| CertificateInterstitial::new(error));
+            let subscription = cx.subscribe(&interstitial, |this, _, _: &DismissEvent, cx| {
+                this.certificate_interstitial = None;
+                cx.notify();
+            });
+
+            self.certificate_interstitial = Some((interstitial, subscription));
+            cx.notify();
+        }
+
+        let proxy_errors = cx.global::<ProxyErrors>().clone();
+        if proxy_errors.take_browsing() && self.proxy_interstitial.take().is_some() {
+            cx.notify();
+        }
+        let error_pages = cx.global::<ErrorPageHandler>().clone();
+        if error_pages.take_browsing() && self.error_page.take().is_some() {
+            cx.notify();
+        }
+        let pdf_viewer = cx.global::<PdfViewer>().clone();
+        for browser_id in pdf_viewer.take_gone() {
+            if self.pdf_views.remove(&browser_id).is_some() {
+                cx.notify();
+            }
+        }
+        for file in pdf_viewer.take_pending() {
+            let browser_id = file.browser_id;
+            self.pdf_views
+                .insert(browser_id, cx.new(|cx| PdfView::new(file, cx)));
+            cx.notify();
+        }
+
+        let blocked_popups = cx.global::<BlockedPopupsState>().clone();
+        if blocked_popups.take_navigated() && self.popup_blocked_bar.take().is_some() {
+            cx.notify();
+        }
+        let blocked = blocked_popups.take_pending();
+        if !blocked.is_empty() {
+            match &self.popup_blocked_bar {
+                Some((bar, _)) => bar.update(cx, |bar, cx| bar.push(blocked, cx)),
+                None => {
+                    let bar = cx.new(|_| PopupBlockedBar::new(blocked));
+                    let subscription = cx.subscribe(&bar, |this, _, _: &DismissEvent, cx| {
+                        this.popup_blocked_bar = None;
+                        cx.notify();
+                    });
+
+                    self.popup_blocked_bar = Some((bar, subscription));
+                    cx.notify();
+                }
+            }
+        }
+
+        if let Some(credentials) = cx.global::<PasswordManager>().take_pending() {
+            let bar = cx.new(|_| SavePasswordBar::new(credentials));
+            let subscription = cx.subscribe(&bar, |this, _, _: &DismissEvent, cx| {
+                this.save_password_bar = None;
+                cx.notify();
+            });
+
+            self.save_password_bar = Some((bar, subscription));
+            cx.notify();
+        }
+
+        if let Some(error) = proxy_errors.take_pending() {
+            let interstitial = cx.new(|cx| ProxyInterstitial::new(error, cx));
+            let subscription = cx.subscribe(&interstitial, |this, _, _: &DismissEvent, cx| {
+                this.proxy_interstitial = None;
+                cx.notify();
+            });
+
+            self.proxy_interstitial = Some((interstitial, subscription));
+            cx.notify();
+        }
+
+        if let Some(error) = error_pages.take_pending() {
+            let page = cx.new(|_| ErrorPage::new(error));
+            let subscription = cx.subscribe(&page, |this, _, _: &DismissEvent, cx| {
+                this.error_page = None;
+                cx.notify();
+            });
+
+            self.error_page = Some((page, subscription));
+            cx.notify();
+        }
+
+        let permissions = cx.global::<PermissionsStore>().clone();
+        let dismissed = permissions.take_dismissed();
+
+        if let Some((banner, _)) = &self.permission_banner {
+            if banner
+                .read(cx)
+                .prompt_id()
+                .is_some_and(|prompt_id| dismissed.contains(&prompt_id))
+            {
+                self.permission_banner = None;
+                cx.notify();
+            }
+        }
+
+        if self.permission_banner.is_none() {
+            if let Some(request) = permissions.take_pending() {
+                let banner = cx.new(|_| PermissionBanner::new(request));
+                let subscription = cx.subscribe(&banner, |this, _, _: &DismissEvent, cx| {
+                    this.permission_banner = None;
+                    cx.notify();
+                });
+
+                self.permission_banner = Some((banner, subscription));
+                cx.notify();
+            }
+        }
+
+        if self.capture_sheet.is_none() {
+            if let Some(request) = cx.global::<CaptureDevices>().take_pending() {
+                let sheet = cx.new(|_| CaptureSheet::new(request));
+                let subscription = cx.subscribe(&sheet, |this, _, _: &DismissEvent, cx| {
+                    this.capture_sheet = None;
+                    cx.notify();
+                });
+
+                self.capture_sheet = Some((sheet, subscription));
+                cx.notify();
+            }
+        }
+    }
+}
+
+impl Render for WindowDemo {
+    fn render(
+        &mut self,
+        window: &mut Window,
+        cx: &mut gpui::Context<'_, WindowDemo>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let id = window.window_handle().window_id();
+        if !cx.global::<WindowManager>().is_live(id) {
+            return window_manager::parked(cx).into_any_element();
+        }
+
+        let state = cx.global::<BrowserState>();
+        let bookmarks = cx.global::<Bookmarks>();
+        let page = state.current_page();
+        let is_bookmarked = page
+            .as_ref()
+            .is_some_and(|(url, _)| bookmarks.contains(url));
+        let is_https = page
+            .as_ref()
+            .is_some_and(|(url, _)| url.starts_with("https://"));
+        let bookmarks_bar_visible = bookmarks.bar_visible;
+        let split_ratio = state.split_ratio;
+        let dev_tools_docked = cx.global::<DevTools>().docked;
+        let unread_notifications = cx.global::<Notifications>().unread();
+        let secure_dns_active = cx.global::<SecureDns>().is_active();
+        let offline = cx.global::<OfflineCache>().is_offline();
+        let throttled = cx.global::<NetworkThrottling>().is_active();
+        let page_fullscreen = cx.global::<PageFullscreen>().is_active();
+        let picking_element = cx.global::<ElementPicker>().is_active();
+        let focus_mode = state.focus_mode;
+        let autoplay_blocked = state
+            .browser
+            .as_ref()
+            .is_some_and(|browser| cx.global::<BlockedAutoplay>().is_blocked(browser));
+        let listening = cx.global::<SpeechRecognizer>().is_listening();
+        let translated = state
+            .browser
+            .as_ref()
+            .is_some_and(|browser| cx.global::<Translator>().is_translated(browser));
+        let tab_manager = cx.global::<TabManager>();
+        let tab_pills = tab_manager
+            .tabs()
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| {
+                let preview = self
+                    .tab_preview
+                    .clone()
+                    .filter(|(preview, _)| *preview == index);
+
+                let favicon = cx
+                    .global::<FaviconCache>()
+                    .favicon(&tab.browser)
+                    .map(|favicon| favicon.at_scale(window.scale_factor()));
+
+                tabs::tab_pill(
+                    index,
+                    tab.label(),
+                    favicon,
+                    index == tab_manager.active(),
+                    tab.incognito,
+                    cx,
+                )
+                .on_hover(cx.listener(move |this, hovered: &bool, _, cx| {
+                    this.hover_tab(index, *hovered, cx)
+                }))
+                .on_mouse_down(
+                    MouseButton::Right,
+                    cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                        this.open_tab_menu(index, event.position, cx)
+                    }),
+                )
+                .when_some(preview, |this, (_, thumbnail)| {
+                    this.child(deferred(thumbnails::preview(index, thumbnail, cx)).with_priority(1))
+                })
+            })
+            .collect::<Vec<_>>();
+        let tab_pills = tabs::strip(tab_manager, tab_pills, cx);
+        let incognito = tab_manager
+            .tabs()
+            .get(tab_manager.active())
+            .is_some_and(|tab| tab.incognito);
+        let page_actions = cx.global::<PageActions>();
+        let open_page_action = self
+            .page_action_popover
+            .as_ref()
+            .map(|(popover, _)| popover.read(cx).action);
+        let page_action_icons = page
+            .as_ref()
+            .map(|(url, _)| page_actions.matching(url))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|index| {
+                let action = page_actions.get(index)?;
+                let icon = page_actions::icon(
+                    index,
+                    action,
+                    (action.active)(cx),
+                    open_page_action == Some(index),
+                    cx,
+                );
+
+                Some(icon.on_click(cx.listener(move |this, _, window, cx| {
+                    this.toggle_page_action(index, window, cx)
+                })))
+            })
+            .collect::<Vec<_>>();
+        let open_extension = self
+            .extension_popup
+            .as_ref()
+            .map(|(popup, _)| popup.read(cx).path.clone());
+        // Toggled on mouse down, so a click on the open popup's button isn't
+        // also the click outside it that closes it.
+        let extension_buttons = extensions::actions(cx)
+            .into_iter()
+            .map(|action| {
+                let button = action.button(open_extension.as_ref() == Some(&action.path), cx);
+                button.on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                        this.toggle_extension_popup(&action, event.position, window, cx)
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+        let pip = cx.global::<PictureInPicture>();
+        let detached = state
+            .browser
+            .as_ref()
+            .is_some_and(|browser| pip.is_detached(browser));
+
+        div()
+            .relative()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_xl()
+            .bg(theme.toolbar)
+            .size_full()
+            .justify_start()
+            .overflow_hidden()
+            .content_start()
+            .flex()
+            .flex_col()
+            .on_action(cx.listener(|this, _: &ToggleConsole, _, cx| {
+                this.console_visible = !this.console_visible;
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleNetworkPanel, _, cx| {
+                this.network_visible = !this.network_visible;
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &TogglePerformancePanel, _, cx| {
+                // The panel only samples while it's open.
+                this.performance_panel = match this.performance_panel.take() {
+                    Some(_) => None,
+                    None => Some(cx.new(PerformancePanel::new)),
+                };
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCspInspector, _, cx| {
+                this.csp_panel = match this.csp_panel.take() {
+                    Some(_) => None,
+                    None => Some(cx.new(CspPanel::new)),
+                };
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleStoragePanel, _, cx| {
+                // The panel only observes the page while it's open.
+                this.storage_panel = match this.storage_panel.take() {
+                    Some(_) => None,
+                    None => Some(cx.new(StoragePanel::new)),
+                };
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCachePanel, _, cx| {
+                this.cache_panel = match this.cache_panel.take() {
+                    Some(_) => None,
+                    None => Some(cx.new(CachePanel::new)),
+                };
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCommandPalette, window, cx| {
+                this.toggle_command_palette(window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &ToggleTabSearch, window, cx| {
+                this.toggle_tab_search(window, cx)
+            }))
+            .on_action(|_: &GoBack, _, cx| {
+                gestures::navigate(cx, NavigationDirection::Back);
+            })
+            .on_action(|_: &GoForward, _, cx| {
+                gestures::navigate(cx, NavigationDirection::Forward);
+            })
+            .on_action(|_: &Reload, _, cx| reload(cx))
+            .on_action(|_: &NewTab, _, cx| {
+                let homepage = cx.global::<settings::Settings>().homepage.clone();
+                tabs::open_url(cx, &homepage, None);
+            })
+            .on_action(|action: &SwitchWorkspace, window, cx| {
+                workspaces::switch(cx, action.0);
+                window.refresh();
+            })
+            .on_action(|_: &CloseTab, _, cx| {
+                if !cx.global::<TabManager>().tabs().is_empty() {
+                    let active = cx.global::<TabManager>().active();
+                    tabs::close(cx, active);
+                }
+            })
+            .on_action(cx.listener(|this, _: &OpenSettings, _, cx| this.open_settings(cx)))
+            .on_action(cx.listener(|this, _: &OpenExtensions, _, cx| this.open_extensions(cx)))
+            .on_action(cx.listener(|this, _: &ShowAbout, _, cx| this.open_about_dialog(cx)))
+            .on_action(cx.listener(|this, _: &ReportBrokenSite, window, cx| {
+                this.open_report_sheet(window, cx)
+            }))
+            .on_action(cx.listener(|this, _: &Print, _, cx| this.open_print_preview(cx)))
+            .on_action(cx.listener(|this, _: &SavePage, _, cx| this.open_save_page_sheet(cx)))
+            .on_action(|_: &ViewSource, _, cx| {
+                if let Some((url, _)) = cx.global::<BrowserState>().current_page() {
+                    source::view(cx, &url);
+                }
+            })
+            .on_action(cx.listener(|_, _: &OpenDevTools, window, cx| {
+                devtools::open(window, cx);
+            }))
+            .on_action(cx.listener(|_, _: &InspectElement, window, cx| {
+                let point = cx.global::<BrowserState>().inspect_element_at();
+                devtools::inspect(window, cx, point);
+            }))
+            .on_action(cx.listener(|this, _: &Screenshot, _, cx| {
+                let result = screenshot::save_screenshot(cx);
+                this.screenshot_saved(result, cx);
+            }))
+            .on_action(cx.listener(|_, _: &CaptureFocusedElement, _, cx| {
+                screenshot::capture_element(cx, ":focus");
+            }))
+            .on_action(cx.listener(|_, _: &TogglePictureInPicture, _, cx| pip::toggle(cx)))
+            .on_action(cx.listener(|this, _: &ToggleSplitView, window, cx| {
+                this.toggle_split_view(window, cx)
+            }))
+            .on_key_up(cx.listener(|_, event, _, cx| focus_mode::key_up(event, cx)))
+            .on_key_down(cx.listener(move |_, event: &KeyDownEvent, _, cx| {
+                if event.keystroke.key != "escape" {
+                    return;
+                }
+
+                if picking_element {
+                    cx.global::<ElementPicker>().stop();
+                    cx.notify();
+                } else if page_fullscreen {
+                    if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+                        fullscreen::exit(browser);
+                    }
+                }
+            }))
+            .on_mouse_move(cx.listener(move |this, event: &MouseMoveEvent, _, cx| {
+                if page_fullscreen {
+                    this.reveal_chrome(f32::from(event.position.y), cx);
+                }
+            }))
+            .child(fullscreen::chrome(
+                div()
+                    .pl(px(84.)) // Left padding to clear traffic lights
+                    .pt(px(10.))
+                    .when(incognito, |this| {
+                        this.pb(px(10.)).bg(theme.toolbar_incognito)
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                // Back button
+                                svg_button(
+                                    "back.svg",
+                                    14.0,
+                                    theme.text,
+                                    |_, cx| {
+                                        gestures::navigate(cx, NavigationDirection::Back);
+                                    },
+                                    cx,
+                                ),
+                            )
+                            .child(
+                                // Forward button
+                                svg_button(
+                                    "forward.svg",
+                                    14.0,
+                                    theme.border,
+                                    |_, cx| {
+                                        gestures::navigate(cx, NavigationDirection::Forward);
+                                    },
+                                    cx,
+                                ),
+                            )
+                            .child(
+                                // Refresh button
+                                svg_button(
+                                    "rotate-cw.svg",
+                                    12.0,
+                                    theme.text,
+                                    |_, cx| reload(cx),
+                                    cx,
+                                ),
+                            )
+                            .when(is_https, |this| {
+                                this.child(
+                                    certificates::padlock(self.certificate_viewer.is_some(), cx)
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.toggle_certificate_viewer(cx)
+                                        })),
+                                )
+                            })
+                            .child(self.omnibox.clone())
+                            .children(tab_pills)
+                            .child(
+                                div()
+                                    .px_1()
+                                    .py_1()
+                                    .bg(linear_gradient(
+                                        150.,
+                                        linear_color_stop(theme.control_gradient.0, 0.05),
+                                        linear_color_stop(theme.control_gradient.1, 0.85),
+                                    ))
+                                    .border_1()
+                                    .border_color(theme.border)
+                                    .rounded_md()
+                                    .items_center()
+                                    .justify_center()
+                                    .child(
+                                        svg()
+                                            .path("plus.svg")
+                                            .size(px(12.0))
+                                            .text_color(theme.text),
+                                    ),
+                            )
+                            .children(page_action_icons)
+                            .children(extension_buttons)
+                            .child(
+                                // Bookmark button
+                                svg_button(
+                                    "star.svg",
+                                    14.0,
+                                    if is_bookmarked {
+                                        theme.warning
+                                    } else {
+                                        theme.text
+                                    },
+                                    move |_, cx| {
+                                        if let Some((url, title)) = &page {
+                                            toggle_bookmark(cx, url, title);
+                                        }
+                                    },
+                                    cx,
+                                ),
+                            )
+                            .child(
+                                // Picture in Picture button
+                                svg_button(
+                                    "picture-in-picture.svg",
+                                    14.0,
+                                    if detached { theme.accent } else { theme.text },
+                                    |_, cx| pip::toggle(cx),
+                                    cx,
+                                ),
+                            )
+                            .child(
+                                // Split view button
+                                svg_button(
+                                    "columns-2.svg",
+                                    14.0,
+                                    if self.split_pane.is_some() {
+                                        theme.accent
+                                    } else {
+                                        theme.text
+                                    },
+                                    |window, cx| {
+                                        window.dispatch_action(Box::new(ToggleSplitView), cx)
+                                    },
+                                    cx,
+                                ),
+                            )
+                            .child(self.audio_visualizer.clone())
+                            .when(listening, |this| {
+                                this.child(speech_recognition::indicator(cx))
+                            })
+                            .when(autoplay_blocked, |this| {
+                                this.child(autoplay::icon(cx).on_click(
+                                    cx.listener(|this, _, _, cx| this.toggle_autoplay_popover(cx)),
+                                ))
+                            })
+                            .child(translate::icon(translated, cx).on_click(cx.listener(
+                                |_, _, _, cx| {
+                                    translate::toggle_page(cx);
+                                    cx.notify();
+                                },
+                            )))
+                            .child(offline::toggle(offline, cx).on_click(cx.listener(
+                                |_, _, _, cx| {
+                                    cx.global::<OfflineCache>().toggle_offline();
+                                    cx.notify();
+                                },
+                            )))
+                            .when(throttled, |this| {
+                                // The conditions are picked in the network panel.
+                                this.child(throttling::badge(cx).on_click(cx.listener(
+                                    |this, _, _, cx| {
+                                        this.network_visible = true;
+                                        cx.notify();
+                                    },
+                                )))
+                            })
+                            .child(secure_dns::shield(secure_dns_active, cx).on_click(
+                                cx.listener(|this, _, _, cx| this.toggle_security_panel(cx)),
+                            ))
+                            .child(focus_mode::icon(focus_mode, cx).on_click(cx.listener(
+                                |_, _, _, cx| {
+                                    focus_mode::toggle(cx);
+                                    cx.notify();
+                                },
+                            )))
+                            .child(
+                                sandbox::badge(cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.open_settings(cx))),
+                            )
+                            .child(inspector::icon(picking_element, cx).on_click(cx.listener(
+                                |_, _, _, cx| {
+                                    cx.global::<ElementPicker>().toggle(cx);
+                                    cx.notify();
+                                },
+                            )))
+                            .child(
+                                // Notification center button
+                                div()
+                                    .id("notification-center")
+                                    .relative()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .size(px(14.0))
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .hover(|this| this.bg(theme.ghost_hover))
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.toggle_notification_center(cx)
+                                    }))
+                                    .child(
+                                        svg()
+                                            .path("bell.svg")
+                                            .size(px(14.0))
+                                            .text_color(theme.text),
+                                    )
+                                    .when(unread_notifications > 0, |this| {
+                                        this.child(
+                                            div()
+                                                .absolute()
+                                                .top(px(-1.))
+                                                .right(px(-1.))
+                                                .size(px(6.))
+                                                .rounded_full()
+                                                .bg(theme.accent),
+                                        )
+                                    }),
+                            )
+                            .when(self.dev_tools_open && !dev_tools_docked, |this| {
+                                this.child(
+                                    button("devtools-attach", t!("devtools.attach"), false, cx)
+                                        .on_click(|_, window, cx| {
+                                            devtools::set_docked(true, window, cx)
+                                        }),
+                                )
+                            })
+                            .child(
+                                // Overflow menu button. Toggled on mouse down, so
+                                // pressing it while the menu is open only closes it.
+                                div()
+                                    .id("overflow-menu")
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .size(px(14.0))
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .hover(|this| this.bg(theme.ghost_hover))
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|this, event: &MouseDownEvent, _, cx| {
+                                            this.toggle_overflow_menu(event.position, cx)
+                                        }),
+                                    )
+                                    .child(
+                                        svg()
+                                            .path("ellipsis-vertical.svg")
+                                            .size(px(14.0))
+                                            .text_color(theme.text),
+                                    ),
+                            ),
+                    ),
+                ("chrome-slide", self.chrome_slide),
+                page_fullscreen,
+                self.chrome_revealed,
+                cx,
+            ))
+            .when(bookmarks_bar_visible && !page_fullscreen, |this| {
+                this.child(self.bookmarks_bar.clone())
+            })
+            .when_some(self.popup_blocked_bar.as_ref(), |this, (bar, _)| {
+                this.child(bar.clone())
+            })
+            .when_some(self.save_password_bar.as_ref(), |this, (bar, _)| {
+                this.child(bar.clone())
+            })
+            .when_some(self.tab_menu.as_ref(), |this, (menu, _)| {
+                this.child(menu.clone())
+            })
+            // Render the browser content, between the workspaces and split
+            // view's right pane
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .when(!page_fullscreen, |this| {
+                        this.child(self.workspace_sidebar.clone())
+                    })
+                    .when(self.split_dragging, |this| {
+                        this.cursor_col_resize()
+                            .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                                this.drag_split_divider(event.position.x, cx)
+                            }))
+                            .on_mouse_up(
+                                MouseButton::Left,
+                                cx.listener(|this, _, _, cx| {
+                                    this.split_dragging = false;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child(
+                        div()
+                            .track_focus(&self.content_focus)
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|this, _, window, _| window.focus(&this.content_focus)),
+                            )
+                            .on_drag_move(cx.listener(
+                                |this, event: &DragMoveEvent<ExternalPaths>, _, cx| {
+                                    if event.bounds.contains(&event.event.position) {
+                                        let position = this.view_point(event.event.position);
+                                        drag::drag_files_over(cx, event.drag(cx), position);
+                                    } else {
+                                        drag::leave(cx);
+                                    }
+                                },
+                            ))
+                            .on_drop(cx.listener(|_, _: &ExternalPaths, _, cx| drag::drop(cx)))
+                            // A tab dragged below the tab strip tears out into its own window.
+                            .on_drop(|tab: &DraggedTab, window, cx| {
+                                let position = window.bounds().origin + window.mouse_position();
+                                tabs::tear_out(cx, tab, position);
+                                window.refresh();
+                            })
+                            .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                                cx.global_mut::<BrowserState>().pointer_moved(
+                                    event.position,
+                                    this.content_bounds.get().origin,
+                                );
+                                if cx.global::<PageDrag>().is_page_dragging() {
+                                    drag::drag_source_over(cx, this.view_point(event.position));
+                                }
+                                if mouse_gestures::mouse_move(cx, event.position) {
+                                    cx.notify();
+                                }
+                            }))
+                            .on_scroll_wheel(cx.listener(
+                                |this, event: &ScrollWheelEvent, window, cx| {
+                                    match gestures::scroll_wheel(window, cx, event) {
+                                        Gesture::Pinch => return,
+                                        Gesture::Swipe | Gesture::None => {}
+                                    }
+
+                                    let position = this.view_point(event.position);
+                                    scroll::scroll_wheel(window, cx, position, &event.delta);
+                                },
+                            ))
+                            .on_mouse_down(
+                                MouseButton::Navigate(NavigationDirection::Back),
+                                |_, _, cx| {
+                                    gestures::navigate(cx, NavigationDirection::Back);
+                                },
+                            )
+                            .on_mouse_down(
+                                MouseButton::Navigate(NavigationDirection::Forward),
+                                |_, _, cx| {
+                                    gestures::navigate(cx, NavigationDirection::Forward);
+                                },
+                            )
+                            .on_mouse_down(
+                                MouseButton::Right,
+                                cx.listener(|this, event: &MouseDownEvent, _, cx| {
+                                    // With mouse gestures on, the page gets the right
+                                    // click once the button is let go without one.
+                                    if !mouse_gestures::mouse_down(cx, event.position) {
+                                        context_menu::right_click(
+                                            cx,
+                                            this.view_point(event.position),
+                                        );
+                                    }
+                                }),
+                            )
+                            .on_mouse_up(
+                                MouseButton::Right,
+                                cx.listener(|this, _, window, cx| {
+                                    if let Some(start) = mouse_gestures::mouse_up(window, cx) {
+                                        context_menu::right_click(cx, this.view_point(start));
+                                    }
+                                    cx.notify();
+                                }),
+                            )
+                            .on_mouse_up_out(
+                                MouseButton::Right,
+                                cx.listener(|_, _, window, cx| {
+                                    mouse_gestures::mouse_up(window, cx);
+                                    cx.notify();
+                                }),
+                            )
+                            .on_mouse_up(
+                                MouseButton::Left,
+                                cx.listener(|_, _, _, cx| {
+                                    if cx.global::<PageDrag>().is_page_dragging() {
+                                        drag::drop(cx);
+                                    }
+                                }),
+                            )
+                            .when_some(self.drag_cursor, |this, cursor| this.cursor(cursor))
+                            .children(mouse_gestures::arrow(cx))
+                            .children(link_preview::card(cx))
+                            .children(focus_mode::badge(cx))
+                            .children(
+                                self.keyboard
+                                    .map(|layout| virtual_keyboard::panel(layout, cx)),
+                            )
+                            .relative()
+                            .flex()
+                            .flex_1()
+                            .bg(theme.viewport)
+                            .items_center()
+                            .justify_center()
+                            .child({
+                                let page = if let Some((interstitial, _)) =
+                                    &self.certificate_interstitial
+                                {
+                                    div().size_full().child(interstitial.clone())
+                                } else if let Some((interstitial, _)) = &self.proxy_interstitial {
+                                    div().size_full().child(interstitial.clone())
+                                } else if let Some((page, _)) = &self.error_page {
+                                    div().size_full().child(page.clone())
+                                } else if let Some((crash, browser)) =
+                                    state.browser.as_ref().and_then(|browser| {
+                                        let crash =
+                                            cx.global::<ProcessMonitor>().crashed_page(browser)?;
+                                        Some((crash, browser.clone()))
+                                    })
+                                {
+                                    crashes::page(crash, browser, cx)
+                                } else if let Some(view) =
+                                    state.browser.as_ref().and_then(|browser| {
+                                        self.pdf_views.get(&browser.get_identifier().ok()?)
+                                    })
+                                {
+                                    div().size_full().child(view.clone())
+                                } else if detached {
+                                    div().size_full().child(pip::placeholder(cx))
+                                } else if let Some(image) = &state.image {
+                                    div()
+                                        .size_full()
+                                        // The image is in physical pixels; drawing it into the
+                                        // logical page area keeps it sharp on HiDPI displays.
+                                        .child(
+                                            img(ImageSource::from(Arc::new(image.clone())))
+                                                .size_full(),
+                                        )
+                                } else {
+                                    div().child(t!("main.loading"))
+                                };
+
+                                match &self.page_transition {
+                                    Some(transition) => transition.render(page).into_any_element(),
+                                    None => page.into_any_element(),
+                                }
+                            })
+                            .child({
+                                let content_bounds = self.content_bounds.clone();
+                                let editable = self.page_editable;
+                                let focus = self.content_focus.clone();
+                                let ime = self.page_ime.clone();
+
+                                canvas(
+                                    move |bounds, _, _| content_bounds.set(bounds),
+                                    move |bounds, _, window, cx| {
+                                        // Route the platform input method to the focused field.
+                                        if editable {
+                                            window.handle_input(
+                                                &focus,
+                                                ElementInputHandler::new(bounds, ime),
+                                                cx,
+                                            )
+                                        }
+                                    },
+                                )
+                                .absolute()
+                                .size_full()
+                            })
+                            .when(picking_element, |this| {
+                                this.child(
+                                    inspector::overlay()
+                                        .on_mouse_move(cx.listener(
+                                            |this, event: &MouseMoveEvent, _, cx| {
+                                                let point = this.view_point(event.position);
+                                                cx.global::<ElementPicker>().hover(cx, point);
+                                            },
+                                        ))
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, event: &MouseDownEvent, window, cx| {
+                                                    let point = this.view_point(event.position);
+                                                    cx.global::<ElementPicker>().stop();
+                                                    devtools::inspect(window, cx, point);
+                                                    cx.stop_propagation();
+                                                    cx.notify();
+                                                },
+                                            ),
+                                        )
+                                        .children(cx.global::<ElementPicker>().highlight()),
+                                )
+                            })
+                            .when_some(self.permission_banner.as_ref(), |this, (banner, _)| {
+                                this.child(banner.clone())
+                            })
+                            .when_some(self.capture_sheet.as_ref(), |this, (sheet, _)| {
+                                this.child(sheet.clone())
+                            })
+                            .when_some(self.session_dialog.as_ref(), |this, (dialog, _)| {
+                                this.child(dialog.clone())
+                            })
+                            .when_some(self.auth_dialog.as_ref(), |this, (dialog, _)| {
+                                this.child(dialog.clone())
+                            })
+                            .when_some(self.js_dialog.as_ref(), |this, (dialog, _)| {
+                                this.child(dialog.clone())
+                            })
+                            .when_some(self.unresponsive_dialog.as_ref(), |this, (dialog, _)| {
+                                this.child(dialog.clone())
+                            })
+                            .when_some(self.context_menu.as_ref(), |this, (menu, _)| {
+                                this.child(menu.clone())
+                            })
+                            .when_some(self.autofill_dropdown.as_ref(), |this, (dropdown, _)| {
+                                this.child(dropdown.clone())
+                            })
+                            .when_some(self.settings_panel.as_ref(), |this, (panel, _)| {
+                                this.child(panel.clone())
+                            })
+                            .when_some(self.extensions_panel.as_ref(), |this, (panel, _)| {
+                                this.child(panel.clone())
+                            })
+                            .when_some(self.report_sheet.as_ref(), |this, (sheet, _)| {
+                                this.child(sheet.clone())
+                            })
+                            .when_some(self.about_dialog.as_ref(), |this, (dialog, _)| {
+                                this.child(dialog.clone())
+                            })
+                            .when_some(self.compose_sheet.as_ref(), |this, (sheet, _)| {
+                                this.child(sheet.clone())
+                            })
+                            .when_some(self.save_page_sheet.as_ref(), |this, (sheet, _)| {
+                                this.child(sheet.clone())
+                            })
+                            .when_some(self.print_preview.as_ref(), |this, (preview, _)| {
+                                this.child(preview.clone())
+                            })
+                            .when_some(self.source_panel.as_ref(), |this, (panel, _)| {
+                                this.child(panel.clone())
+                            })
+                            .when_some(self.media_bar.as_ref(), |this, (bar, _)| {
+                                this.child(bar.clone())
+                            })
+                            .when_some(self.toast.as_ref(), |this, (toast, _)| {
+                                this.child(toast.clone())
+                            })
+                            .child(self.notification_toasts.clone())
+                            .when_some(self.notification_center.as_ref(), |this, (center, _)| {
+                                this.child(center.clone())
+                            })
+                            .when_some(self.security_panel.as_ref(), |this, (panel, _)| {
+                                this.child(panel.clone())
+                            })
+                            .when_some(self.autoplay_popover.as_ref(), |this, (popover, _)| {
+                                this.child(popover.clone())
+                            })
+                            .when_some(self.translation_popover.as_ref(), |this, (popover, _)| {
+                                this.child(popover.clone())
+                            })
+                            .when_some(self.page_action_popover.as_ref(), |this, (popover, _)| {
+                                this.child(popover.clone())
+                            })
+                            .when_some(self.certificate_viewer.as_ref(), |this, (viewer, _)| {
+                                this.child(viewer.clone())
+                            })
+                            .when_some(self.extension_popup.as_ref(), |this, (popup, _)| {
+                                this.child(popup.clone())
+                            })
+                            .when_some(self.overflow_menu.as_ref(), |this, (menu, _)| {
+                                this.child(menu.clone())
+                            })
+                            .when_some(self.command_palette.as_ref(), |this, (palette, _)| {
+                                this.child(palette.clone())
+                            })
+                            .when_some(self.tab_search.as_ref(), |this, (search, _)| {
+                                this.child(search.clone())
+                            })
+                            .when(self.split_pane.is_some(), |this| {
+                                this.flex_none().w(relative(split_ratio))
+                            }),
+                    )
+                    .when_some(self.split_pane.as_ref(), |this, (pane, _)| {
+                        this.child(split::divider(cx).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.split_dragging = true;
+                                cx.stop_propagation();
+                                cx.notify();
+                            }),
+                        ))
+                        .child(pane.clone())
+                    }),
+            )
+            .when(self.console_visible, |this| {
+                this.child(self.console_panel.clone())
+            })
+            .when(self.network_visible, |this| {
+                this.child(self.network_panel.clone())
+            })
+            .when_some(self.performance_panel.clone(), |this, panel| {
+                this.child(panel)
+            })
+            .when_some(self.csp_panel.clone(), |this, panel| this.child(panel))
+            .when_some(self.storage_panel.clone(), |this, panel| this.child(panel))
+            .when_some(self.cache_panel.clone(), |this, panel| this.child(panel))
+            .when(self.dev_tools_open && dev_tools_docked, |this| {
+                this.child(devtools::dock_panel(cx))
+            })
+            .into_any_element()
+    }
+}
+
+actions!(
+    window,
+    [
+        Quit,
+        ToggleBookmarksBar,
+        OpenDevTools,
+        InspectElement,
+        Screenshot,
+        CaptureFocusedElement,
+        ToggleReaderMode,
+        ToggleConsole,
+        ToggleNetworkPanel,
+        TogglePerformancePanel,
+        ToggleCspInspector,
+        ToggleStoragePanel,
+        ToggleCachePanel,
+        Print,
+        SavePage,
+        OpenSettings,
+        OpenExtensions,
+        NewIncognitoTab,
+        TogglePictureInPicture,
+        ToggleSplitView,
+        ViewSource,
+        ToggleCommandPalette,
+        ToggleTabSearch,
+        ReportBrokenSite,
+        ShowAbout,
+        GoBack,
+        GoForward,
+        Reload,
+        NewTab,
+        CloseTab,
+        NewWindow
+    ]
+);
+
+// CEF Handlers
+pub struct MyContextMenuHandler {
+    menus: ContextMenus,
+}
+
+impl ContextMenuHandlerCallbacks for MyContextMenuHandler {
+    fn on_before_context_menu(
+        &mut self,
+        browser: Browser,
+        _frame: Frame,
+        params: ContextMenuParams,
+        model: MenuModel,
+    ) {
+        self.menus.build(&browser, &params, &model);
+    }
+
+    fn run_context_menu(
+        &mut self,
+        _browser: Browser,
+        _frame: Frame,
+        params: ContextMenuParams,
+        model: MenuModel,
+        callback: RunContextMenuCallback,
+    ) -> bool {
+        // Draw the menu with GPUI instead of CEF's native one.
+        self.menus.request(&params, &model, callback);
+        true
+    }
+
+    fn on_context_menu_command(
+        &mut self,
+        browser: Browser,
+        _frame: Frame,
+        params: ContextMenuParams,
+        command_id: MenuCommandId,
+        _event_flags: EventFlags,
+    ) -> bool {
+        self.menus.execute(&browser, &params, command_id.into())
+    }
+
+    fn on_context_menu_dismissed(&mut self, _browser: Browser, _frame: Frame) {
+        self.menus.dismiss();
+    }
+
+    fn run_quick_menu(
+        &mut self,
+        _browser: Browser,
+        _frame: Frame,
+        _location: &Point,
+        _size: &Size,
+        _edit_state_flags: QuickMenuEditStateFlags,
+        _callback: RunQuickMenuCallback,
+    ) -> bool {
+        false
+    }
+
+    fn on_quick_menu_command(
+        &mut self,
+        _browser: Browser,
+        _frame: Frame,
+        _command_id: MenuCommandId,
+        _event_flags: EventFlags,
+    ) -> bool {
+        false
+    }
+
+    fn on_quick_menu_dismissed(&mut self, _browser: Browser, _frame: Frame) {}
+}
+
+pub struct MyLifeSpanHandlerCallbacks {
+    popups: Popups,
+    extension_popup: ExtensionPopupView,
+    blocked_popups: BlockedPopupsState,
+    csp: CspInspector,
+    console: ConsoleLog,
+    hangs: HangMonitor,
+    lifecycle: CefLifecycleEvents,
+    mailto: MailtoHandler,
+    pdf: PdfViewer,
+}
+
+impl LifeSpanHandlerCallbacks for MyLifeSpanHandlerCallbacks {
+    unsafe fn on_before_popup(
+        &mut self,
+        browser: Browser,
+        frame: Frame,
+        target_url: Option<String>,
+        target_frame_name: Option<String>,
+        _target_disposition: WindowOpenDisposition,
+        _user_gesture: bool,
+        popup_features: PopupFeatures,
+        window_info: &mut WindowInfo,
+        _client: &mut Option<Client>,
+        _settings: &mut BrowserSettings,
+        _extra_info: &mut Option<DictionaryValue>,
+        _no_javascript_access: &mut bool,
+    ) -> bool {
+        // An email link opening a window goes wherever settings say instead.
+        if target_url
+            .as_deref()
+            .is_some_and(|url| self.mailto.request(url))
+        {
+            return true;
+        }
+
+        let blocked = self
+            .popups
+            .on_before_popup(target_url.clone(), &popup_features, window_info);
+        if blocked {
+            self.blocked_popups
+                .block(&browser, &frame, target_url, target_frame_name);
+        }
+
+        blocked
+    }
+
+    fn on_before_dev_tools_popup(
+        &mut self,
+        _browser: Browser,
+        window_info: &mut WindowInfo,
+        _client: &mut Option<Client>,
+        _settings: &mut BrowserSettings,
+        _extra_info: &mut Option<DictionaryValue>,
+        _use_default_window: &mut bool,
+    ) {
+        // DevTools opened by the page itself gets its own window.
+        let info = std::mem::replace(window_info, WindowInfo::new());
+        *window_info = info
+            .window_name(&String::from("DevTools"))
+            .bounds(&devtools::window_bounds());
+    }
+
+    fn on_after_created(&mut self, browser: Browser) {
+        self.lifecycle.on_after_created();
+        self.csp.attach(&browser, self.console.clone());
+        self.popups.on_after_created(browser);
+    }
+
+    fn do_close(&mut self, _browser: Browser) -> bool {
+        false
+    }
+
+    fn on_before_close(&mut self, browser: Browser) {
+        self.csp.detach(&browser);
+        self.pdf.on_before_close(&browser);
+
+        // Closing a popup tab or window, an extension's popup, or a tab to
+        // kill its hung page leaves the browser running.
+        if browser.is_popup().unwrap_or(false)
+            || self.extension_popup.on_before_close(&browser)
+            || self.hangs.on_before_close(&browser)
+        {
+            return;
+        }
+
+        // Quit CEF when browser closes
+        unsafe {
+            cef_ui_sys::cef_quit_message_loop();
+        }
+    }
+}
+
+pub struct MyDisplayHandler {
+    history: History,
+    fullscreen: PageFullscreen,
+    captures: ElementCaptures,
+    reader: ReaderMode,
+    console: ConsoleLog,
+    session: Session,
+    hangs: HangMonitor,
+    archiver: PageArchiver,
+    favicons: FaviconCache,
+    link_previews: LinkPreviews,
+}
+
+impl DisplayHandlerCallbacks for MyDisplayHandler {
+    fn on_address_change(&mut self, _browser: Browser, _frame: Frame, _url: &str) {}
+
+    fn on_title_change(&mut self, browser: Browser, title: Option<String>) {
+        if let Some(title) = title {
+            self.history.on_title_change(&browser, &title);
+        }
+    }
+
+    fn on_favicon_urlchange(&mut self, browser: Browser, icon_urls: Vec<String>) {
+        self.favicons.on_favicon_urlchange(&browser, &icon_urls);
+    }
+
+    fn on_fullscreen_mode_change(&mut self, _browser: Browser, fullscreen: bool) {
+        self.fullscreen.on_fullscreen_mode_change(fullscreen);
+    }
+
+    fn on_tooltip(&mut self, _browser: Browser, _text: Option<String>) -> bool {
+        false
+    }
+
+    fn on_status_message(&mut self, _browser: Browser, value: Option<String>) {
+        self.link_previews.on_status_message(value.as_deref());
+    }
+
+    fn on_console_message(
+        &mut self,
+        browser: Browser,
+        level: LogSeverity,
+        message: Option<String>,
+        source: Option<String>,
+        line: i32,
+    ) -> bool {
+        let message = message.unwrap_or_default();
+
+        // Reports from our own injected scripts aren't shown.
+        if self.captures.on_console_message(&message)
+            || self.reader.on_console_message(&message)
+            || self.session.on_console_message(&browser, &message)
+            || self.hangs.on_console_message(&browser, &message)
+            || self.archiver.on_console_message(&browser, &message)
+        {
+            return true;
+        }
+
+        console_log::log(level, &message, source.as_deref(), line);
+
+        // The CSP inspector reports violations from the DevTools log.
+        if csp::is_violation(&message) {
+            return false;
+        }
+
+        self.console.push(level, message, source, line);
+        false
+    }
+
+    fn on_auto_resize(&mut self, _browser: Browser, _new_size: &Size) -> bool {
+        false
+    }
+
+    fn on_loading_progress_change(&mut self, _browser: Browser, _progress: f64) {}
+
+    fn on_media_access_change(
+        &mut self,
+        _browser: Browser,
+        _has_video_access: bool,
+        _has_audio_access: bool,
+    ) {
+    }
+}
+
+pub struct MyFocusHandler {
+    focus: PageFocus,
+}
+
+impl FocusHandlerCallbacks for MyFocusHandler {
+    fn on_take_focus(&mut self, _browser: Browser, _next: bool) {
+        self.focus.took_focus();
+    }
+
+    fn on_set_focus(&mut self, _browser: Browser, _source: FocusSource) -> bool {
+        false
+    }
+
+    fn on_got_focus(&mut self, _browser: Browser) {
+        self.focus.got_focus();
+    }
+}
+
+pub struct MyJsDialogHandler {
+    dialogs: JsDialogs,
+}
+
+impl JsDialogHandlerCallbacks for MyJsDialogHandler {
+    fn on_jsdialog(
+        &mut self,
+        _browser: Browser,
+        origin_url: Option<String>,
+        dialog_type: JsDialogType,
+        message_text: Option<String>,
+        default_prompt_text: Option<String>,
+        callback: JsDialogCallback,
+        _suppress_message: &mut bool,
+    ) -> bool {
+        self.dialogs.request(
+            dialog_type,
+            origin_url,
+            message_text,
+            default_prompt_text,
+            callback,
+        );
+        true
+    }
+
+    fn on_before_unload_dialog(
+        &mut self,
+        _browser: Browser,
+        _message_text: Option<String>,
+        is_reload: bool,
+        callback: JsDialogCallback,
+    ) -> bool {
+        self.dialogs.request_before_unload(is_reload, callback);
+        true
     }
-}
 
-actions!(window, [Quit]);
+    fn on_reset_dialog_state(&mut self, _browser: Browser) {
+        self.dialogs.reset();
+    }
 
-// CEF Handlers
-pub struct MyContextMenuHandler;
+    fn on_dialog_closed(&mut self, _browser: Browser) {}
+}
 
-impl ContextMenuHandlerCallbacks for MyContextMenuHandler {
-    fn on_before_context_menu(
+pub struct MyLoadHandler {
+    history: History,
+    csp: CspInspector,
+    reader: ReaderMode,
+    session: Session,
+    proxy_errors: ProxyErrors,
+    error_pages: ErrorPageHandler,
+    transitions: PageTransitions,
+    user_scripts: UserScripts,
+    lifecycle: CefLifecycleEvents,
+    benchmark: Benchmark,
+    translator: Translator,
+}
+
+impl LoadHandlerCallbacks for MyLoadHandler {
+    fn on_loading_state_change(
         &mut self,
         _browser: Browser,
-        _frame: Frame,
-        _params: ContextMenuParams,
-        model: MenuModel,
+        _is_loading: bool,
+        _can_go_back: bool,
+        _can_go_forward: bool,
+    ) {
+    }
+
+    fn on_load_start(&mut self, browser: Browser, frame: Frame) {
+        self.transitions.on_load_start(&browser, &frame);
+        self.lifecycle.on_load_start(&frame);
+        self.csp.on_load_start(&browser, &frame);
+        self.benchmark.on_load_start(&browser, &frame);
+        self.translator.on_load_start(&browser, &frame);
+    }
+
+    fn on_load_end(&mut self, browser: Browser, frame: Frame, http_status_code: i32) {
+        self.reader.on_load_end(&frame);
+        self.session.on_load_end(&browser, &frame);
+        self.history.on_load_end(&browser, &frame);
+        self.csp.on_load_end(&browser, &frame);
+        self.user_scripts.on_load_end(&frame);
+        self.lifecycle.on_load_end(&frame);
+        self.benchmark
+            .on_load_end(&browser, &frame, http_status_code);
+    }
+
+    fn on_load_error(
+        &mut self,
+        browser: Browser,
+        frame: Frame,
+        error_code: ErrorCode,
+        _error_text: Option<String>,
+        failed_url: &str,
     ) {
-        // Prevent context menus from spawning
-        if let Err(e) = model.clear() {
-            eprintln!("Error clearing context menu: {}", e);
+        self.benchmark.on_load_error(&browser, &frame, error_code);
+        self.proxy_errors
+            .on_load_error(&frame, error_code, failed_url);
+        self.error_pages
+            .on_load_error(&frame, error_code, failed_url);
+    }
+}
+
+pub struct MyResourceRequestHandler {
+    ad_block: AdBlock,
+    offline: OfflineCache,
+    throttling: NetworkThrottling,
+    pdf: PdfViewer,
+    network: NetworkLog,
+    websockets: WebSocketLog,
+}
+
+impl ResourceRequestHandlerCallbacks for MyResourceRequestHandler {
+    fn on_before_resource_load(
+        &mut self,
+        _browser: Option<Browser>,
+        _frame: Option<Frame>,
+        request: Request,
+        _callback: Callback,
+    ) -> ReturnValue {
+        match request.get_url() {
+            Ok(url) if self.ad_block.should_block(&url) => ReturnValue::Cancel,
+            _ => {
+                self.websockets.on_upgrade(&request);
+                self.network.on_request(&request);
+                if autoplay::is_media(&request) {
+                    self.network.mark_media(&request);
+                }
+                ReturnValue::Continue
+            }
         }
     }
 
-    fn run_context_menu(
+    fn get_resource_handler(
         &mut self,
-        _browser: Browser,
-        _frame: Frame,
-        _params: ContextMenuParams,
-        _model: MenuModel,
-        _callback: RunContextMenuCallback,
+        browser: Option<Browser>,
+        _frame: Option<Frame>,
+        request: Request,
+    ) -> Option<ResourceHandler> {
+        self.offline
+            .resource_handler(&request)
+            .or_else(|| self.throttling.resource_handler(browser.as_ref(), &request))
+    }
+
+    fn on_resource_response(
+        &mut self,
+        _browser: Option<Browser>,
+        _frame: Option<Frame>,
+        request: Request,
+        response: Response,
     ) -> bool {
+        self.network.on_response(&request, &response);
         false
     }
 
-    fn on_context_menu_command(
+    fn get_resource_response_filter(
         &mut self,
-        _browser: Browser,
-        _frame: Frame,
-        _params: ContextMenuParams,
-        _command_id: MenuCommandId,
-        _event_flags: EventFlags,
+        browser: Option<Browser>,
+        _frame: Option<Frame>,
+        request: Request,
+        response: Response,
+    ) -> Option<ResponseFilter> {
+        self.pdf
+            .response_filter(browser.as_ref(), &request, &response)
+            .or_else(|| {
+                self.offline
+                    .response_filter(browser.as_ref(), &request, &response)
+            })
+    }
+
+    fn on_resource_load_complete(
+        &mut self,
+        _browser: Option<Browser>,
+        _frame: Option<Frame>,
+        request: Request,
+        _response: Response,
+        status: UrlRequestStatus,
+        received_content_length: i64,
+    ) {
+        self.network
+            .on_complete(&request, status, received_content_length);
+    }
+}
+
+pub struct MyRequestHandler {
+    process_monitor: ProcessMonitor,
+    credentials: CredentialStore,
+    certificates: CertificateExceptions,
+    ad_block: AdBlock,
+    offline: OfflineCache,
+    throttling: NetworkThrottling,
+    network: NetworkLog,
+    websockets: WebSocketLog,
+    form_data: FormDataStore,
+    passwords: PasswordManager,
+    sources: SourceViewer,
+    mailto: MailtoHandler,
+    proxy_errors: ProxyErrors,
+    error_pages: ErrorPageHandler,
+    blocked_popups: BlockedPopupsState,
+    pdf: PdfViewer,
+}
+
+impl RequestHandlerCallbacks for MyRequestHandler {
+    fn on_before_browse(
+        &mut self,
+        browser: Browser,
+        frame: Frame,
+        request: Request,
+        _user_gesture: bool,
+        is_redirect: bool,
     ) -> bool {
+        self.process_monitor.on_before_browse(&browser, &frame);
+        self.proxy_errors.on_before_browse(&frame);
+        self.error_pages.on_before_browse(&frame);
+        self.blocked_popups.on_before_browse(&frame);
+        self.pdf.on_before_browse(&browser, &frame);
+
+        // Source is shown in a panel of its own, not Chromium's viewer.
+        let url = request.get_url().unwrap_or_default();
+        if self.sources.request(&url) {
+            return true;
+        }
+
+        // Email links go wherever settings say instead.
+        if self.mailto.request(&url) {
+            return true;
+        }
+
+        // A submitted form navigates with its fields as the POST body. Nothing
+        // typed in an incognito tab is remembered.
+        if !is_redirect && !incognito::is_incognito(&browser) {
+            self.form_data.record_submission(&frame, &request);
+            self.passwords.record_submission(&frame, &request);
+        }
+
         false
     }
 
-    fn on_context_menu_dismissed(&mut self, _browser: Browser, _frame: Frame) {}
-
-    fn run_quick_menu(
+    fn on_open_urlfrom_tab(
         &mut self,
         _browser: Browser,
         _frame: Frame,
-        _location: &Point,
-        _size: &Size,
-        _edit_state_flags: QuickMenuEditStateFlags,
-        _callback: RunQuickMenuCallback,
+        _target_url: &str,
+        _target_disposition: WindowOpenDisposition,
+        _user_gesture: bool,
     ) -> bool {
         false
     }
 
-    fn on_quick_menu_command(
+    fn get_resource_request_handler(
         &mut self,
         _browser: Browser,
         _frame: Frame,
-        _command_id: MenuCommandId,
-        _event_flags: EventFlags,
+        _request: Request,
+        _is_navigation: bool,
+        _is_download: bool,
+        _request_initiator: &str,
+        _disable_default_handling: &mut bool,
+    ) -> Option<ResourceRequestHandler> {
+        Some(ResourceRequestHandler::new(MyResourceRequestHandler {
+            ad_block: self.ad_block.clone(),
+            offline: self.offline.clone(),
+            throttling: self.throttling.clone(),
+            pdf: self.pdf.clone(),
+            network: self.network.clone(),
+            websockets: self.websockets.clone(),
+        }))
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _browser: Browser,
+        origin_url: &str,
+        is_proxy: bool,
+        host: &str,
+        port: u16,
+        realm: Option<&str>,
+        _scheme: Option<&str>,
+        callback: AuthCallback,
+    ) -> bool {
+        // Proxies are keyed by host since they aren't tied to the page origin.
+        let origin = if is_proxy {
+            format!("{}:{}", host, port)
+        } else {
+            origin_url.to_string()
+        };
+
+        self.credentials
+            .request(&origin, host, realm, is_proxy, callback);
+        true
+    }
+
+    fn on_certificate_error(
+        &mut self,
+        _browser: Browser,
+        cert_error: ErrorCode,
+        request_url: &str,
+        _ssl_info: SslInfo,
+        callback: Callback,
+    ) -> bool {
+        self.certificates.request(request_url, cert_error, callback);
+        true
+    }
+
+    fn on_select_client_certificate(
+        &mut self,
+        _browser: Browser,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _certificates: &[X509Certificate],
+        _callback: SelectClientCertificateCallback,
     ) -> bool {
         false
     }
 
-    fn on_quick_menu_dismissed(&mut self, _browser: Browser, _frame: Frame) {}
+    fn on_render_view_ready(&mut self, _browser: Browser) {}
+
+    fn on_render_process_terminated(&mut self, browser: Browser, status: TerminationStatus) {
+        self.process_monitor
+            .on_render_process_terminated(&browser, status);
+        telemetry::capture_renderer_crash(status);
+    }
+
+    fn on_document_available_in_main_frame(&mut self, _browser: Browser) {}
 }
 
-pub struct MyLifeSpanHandlerCallbacks;
+pub struct MyPermissionHandler {
+    permissions: PermissionsStore,
+}
 
-impl LifeSpanHandlerCallbacks for MyLifeSpanHandlerCallbacks {
-    unsafe fn on_before_popup(
+impl PermissionHandlerCallbacks for MyPermissionHandler {
+    fn on_request_media_access_permission(
         &mut self,
         _browser: Browser,
         _frame: Frame,
-        _target_url: Option<String>,
-        _target_frame_name: Option<String>,
-        _target_disposition: WindowOpenDisposition,
-        _user_gesture: bool,
-        _popup_features: PopupFeatures,
-        _window_info: &mut WindowInfo,
-        _client: &mut Option<Client>,
-        _settings: &mut BrowserSettings,
-        _extra_info: &mut Option<DictionaryValue>,
-        _no_javascript_access: &mut bool,
+        requesting_origin: &str,
+        requested_permissions: MediaAccessPermissions,
+        callback: MediaAccessCallback,
     ) -> bool {
-        true // Block popups
+        self.permissions
+            .request_media(requesting_origin, requested_permissions, callback);
+        true
     }
 
-    fn on_before_dev_tools_popup(
+    fn on_show_permission_prompt(
         &mut self,
         _browser: Browser,
-        _window_info: &mut WindowInfo,
-        _client: &mut Option<Client>,
-        _settings: &mut BrowserSettings,
-        _extra_info: &mut Option<DictionaryValue>,
-        _use_default_window: &mut bool,
+        prompt_id: u64,
+        requesting_origin: &str,
+        requested_permissions: PermissionRequestTypes,
+        callback: PermissionPromptCallback,
+    ) -> bool {
+        self.permissions.request_prompt(
+            prompt_id,
+            requesting_origin,
+            requested_permissions,
+            callback,
+        );
+        true
+    }
+
+    fn on_dismiss_permission_prompt(
+        &mut self,
+        _browser: Browser,
+        prompt_id: u64,
+        _result: PermissionRequestResult,
     ) {
+        self.permissions.dismiss(prompt_id);
     }
+}
 
-    fn on_after_created(&mut self, _browser: Browser) {}
+pub struct MyClientCallbacks {
+    history: History,
+    credentials: CredentialStore,
+    certificates: CertificateExceptions,
+    permissions: PermissionsStore,
+    captures: ElementCaptures,
+    reader: ReaderMode,
+    console: ConsoleLog,
+    dialogs: JsDialogs,
+    focus: PageFocus,
+    ime: ImeState,
+    drag: PageDrag,
+    thumbnails: ThumbnailCache,
+    transitions: PageTransitions,
+    accessibility: AccessibilityTree,
+    split: SplitView,
+    extension_popup: ExtensionPopupView,
+    user_scripts: UserScripts,
+    sources: SourceViewer,
+    mailto: MailtoHandler,
+    popups: Popups,
+    blocked_popups: BlockedPopupsState,
+    fullscreen: PageFullscreen,
+    csp: CspInspector,
+    router: MessageRouter,
+    session: Session,
+    process_monitor: ProcessMonitor,
+    ad_block: AdBlock,
+    offline: OfflineCache,
+    throttling: NetworkThrottling,
+    pdf: PdfViewer,
+    proxy_errors: ProxyErrors,
+    error_pages: ErrorPageHandler,
+    menus: ContextMenus,
+    hangs: HangMonitor,
+    archiver: PageArchiver,
+    favicons: FaviconCache,
+    link_previews: LinkPreviews,
+    network: NetworkLog,
+    websockets: WebSocketLog,
+    view: PageView,
+    form_data: FormDataStore,
+    passwords: PasswordManager,
+    audio: AudioCapture,
+    print_jobs: PrintJobs,
+    lifecycle: CefLifecycleEvents,
+    benchmark: Benchmark,
+    translator: Translator,
+}
 
-    fn do_close(&mut self, _browser: Browser) -> bool {
-        false
+impl ClientCallbacks for MyClientCallbacks {
+    fn get_audio_handler(&mut self) -> Option<AudioHandler> {
+        Some(AudioHandler::new(AudioCapturer::new(self.audio.clone())))
     }
 
-    fn on_before_close(&mut self, _browser: Browser) {
-        // Quit CEF when browser closes
-        unsafe {
-            cef_ui_sys::cef_quit_message_loop();
-        }
+    fn get_print_handler(&mut self) -> Option<PrintHandler> {
+        Some(PrintHandler::new(Printer::new(self.print_jobs.clone())))
     }
-}
-
-pub struct MyClientCallbacks;
 
-impl ClientCallbacks for MyClientCallbacks {
     fn get_context_menu_handler(&mut self) -> Option<ContextMenuHandler> {
-        Some(ContextMenuHandler::new(MyContextMenuHandler {}))
+        Some(ContextMenuHandler::new(MyContextMenuHandler {
+            menus: self.menus.clone(),
+        }))
+    }
+
+    fn get_display_handler(&mut self) -> Option<DisplayHandler> {
+        Some(DisplayHandler::new(MyDisplayHandler {
+            history: self.history.clone(),
+            fullscreen: self.fullscreen.clone(),
+            captures: self.captures.clone(),
+            reader: self.reader.clone(),
+            console: self.console.clone(),
+            session: self.session.clone(),
+            hangs: self.hangs.clone(),
+            archiver: self.archiver.clone(),
+            favicons: self.favicons.clone(),
+            link_previews: self.link_previews.clone(),
+        }))
+    }
+
+    fn get_focus_handler(&mut self) -> Option<FocusHandler> {
+        Some(FocusHandler::new(MyFocusHandler {
+            focus: self.focus.clone(),
+        }))
+    }
+
+    fn get_jsdialog_handler(&mut self) -> Option<JsDialogHandler> {
+        Some(JsDialogHandler::new(MyJsDialogHandler {
+            dialogs: self.dialogs.clone(),
+        }))
     }
 
     fn get_keyboard_handler(&mut self) -> Option<KeyboardHandler> {
@@ -374,41 +3311,117 @@ impl ClientCallbacks for MyClientCallbacks {
     }
 
     fn get_life_span_handler(&mut self) -> Option<LifeSpanHandler> {
-        Some(LifeSpanHandler::new(MyLifeSpanHandlerCallbacks {}))
+        Some(LifeSpanHandler::new(MyLifeSpanHandlerCallbacks {
+            popups: self.popups.clone(),
+            extension_popup: self.extension_popup.clone(),
+            blocked_popups: self.blocked_popups.clone(),
+            csp: self.csp.clone(),
+            console: self.console.clone(),
+            hangs: self.hangs.clone(),
+            lifecycle: self.lifecycle.clone(),
+            mailto: self.mailto.clone(),
+            pdf: self.pdf.clone(),
+        }))
+    }
+
+    fn get_load_handler(&mut self) -> Option<LoadHandler> {
+        Some(LoadHandler::new(MyLoadHandler {
+            history: self.history.clone(),
+            csp: self.csp.clone(),
+            reader: self.reader.clone(),
+            session: self.session.clone(),
+            proxy_errors: self.proxy_errors.clone(),
+            error_pages: self.error_pages.clone(),
+            transitions: self.transitions.clone(),
+            user_scripts: self.user_scripts.clone(),
+            lifecycle: self.lifecycle.clone(),
+            benchmark: self.benchmark.clone(),
+            translator: self.translator.clone(),
+        }))
     }
 
     fn get_render_handler(&mut self) -> Option<RenderHandler> {
-        Some(RenderHandler::new(MyRenderHandler::new()))
+        Some(RenderHandler::new(MyRenderHandler {
+            view: self.view.clone(),
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            ime: self.ime.clone(),
+            drag: self.drag.clone(),
+            thumbnails: self.thumbnails.clone(),
+            transitions: self.transitions.clone(),
+            accessibility: self.accessibility.clone(),
+            split: self.split.clone(),
+            extension_popup: self.extension_popup.clone(),
+            hangs: self.hangs.clone(),
+            lifecycle: self.lifecycle.clone(),
+            benchmark: self.benchmark.clone(),
+        }))
+    }
+
+    fn get_permission_handler(&mut self) -> Option<PermissionHandler> {
+        Some(PermissionHandler::new(MyPermissionHandler {
+            permissions: self.permissions.clone(),
+        }))
+    }
+
+    fn get_request_handler(&mut self) -> Option<RequestHandler> {
+        Some(RequestHandler::new(MyRequestHandler {
+            process_monitor: self.process_monitor.clone(),
+            credentials: self.credentials.clone(),
+            certificates: self.certificates.clone(),
+            ad_block: self.ad_block.clone(),
+            offline: self.offline.clone(),
+            throttling: self.throttling.clone(),
+            network: self.network.clone(),
+            websockets: self.websockets.clone(),
+            form_data: self.form_data.clone(),
+            passwords: self.passwords.clone(),
+            sources: self.sources.clone(),
+            mailto: self.mailto.clone(),
+            proxy_errors: self.proxy_errors.clone(),
+            error_pages: self.error_pages.clone(),
+            blocked_popups: self.blocked_popups.clone(),
+            pdf: self.pdf.clone(),
+        }))
+    }
+
+    fn on_process_message_received(
+        &mut self,
+        _browser: Browser,
+        frame: Frame,
+        _source_process: ProcessId,
+        message: ProcessMessage,
+    ) -> bool {
+        self.router.on_process_message(&frame, &message)
     }
 }
 
 /// Render handler for windowless rendering
 pub struct MyRenderHandler {
-    view_size: Arc<Mutex<Size>>,
+    view: PageView,
     buffer: Arc<Mutex<Vec<u8>>>,
-}
-
-impl MyRenderHandler {
-    fn new() -> Self {
-        Self {
-            view_size: Arc::new(Mutex::new(Size {
-                width: 1024,
-                height: 768,
-            })),
-            buffer: Arc::new(Mutex::new(Vec::new())),
-        }
-    }
+    ime: ImeState,
+    drag: PageDrag,
+    thumbnails: ThumbnailCache,
+    transitions: PageTransitions,
+    accessibility: AccessibilityTree,
+    split: SplitView,
+    extension_popup: ExtensionPopupView,
+    hangs: HangMonitor,
+    lifecycle: CefLifecycleEvents,
+    benchmark: Benchmark,
 }
 
 impl RenderHandlerCallbacks for MyRenderHandler {
     fn get_view_rect(&mut self, browser: Browser) -> Rect {
-        let size = *self.view_size.lock().unwrap();
-        Rect {
-            x: 0,
-            y: 0,
-            width: size.width,
-            height: size.height,
+        // Logical pixels; CEF scales the paint buffer by the screen info's
+        // device scale factor.
+        if self.split.contains(&browser) {
+            return self.split.rect();
         }
+        if self.extension_popup.contains(&browser) {
+            return self.extension_popup.rect();
+        }
+        self.view.rect()
     }
 
     fn get_screen_point(&mut self, browser: Browser, view: &Point) -> Option<Point> {
@@ -419,7 +3432,7 @@ impl RenderHandlerCallbacks for MyRenderHandler {
     fn get_screen_info(&mut self, browser: Browser) -> Option<ScreenInfo> {
         let rect = self.get_view_rect(browser);
         let mut info = ScreenInfo {
-            device_scale_factor: 1.0,
+            device_scale_factor: self.view.scale_factor(),
             depth: 32,
             depth_per_component: 8,
             is_monochrome: false,
@@ -438,33 +3451,40 @@ impl RenderHandlerCallbacks for MyRenderHandler {
         width: usize,
         height: usize,
     ) {
+        self.hangs.on_paint(&browser);
+        self.lifecycle.on_paint();
+
         // Print first few items in the buffer
-        println!(
+        eprintln!(
             "Paint event - Element type: {:?}, Width: {}, Height: {}",
             element_type, width, height
         );
-        println!(
+        eprintln!(
             "First 10 bytes of buffer: {:?}",
             &buffer[..std::cmp::min(10, buffer.len())]
         );
 
-        // Store the buffer data
+        // Store the buffer data. It's in physical pixels, so `width` and
+        // `height` are the view rect's size times the device scale factor.
         let mut current_buffer = self.buffer.lock().unwrap();
         current_buffer.clear();
         current_buffer.extend_from_slice(buffer);
 
-        // Update view size if needed
-        let mut current_size = self.view_size.lock().unwrap();
-        if current_size.width != width as i32 || current_size.height != height as i32 {
-            *current_size = Size {
-                width: width as i32,
-                height: height as i32,
-            };
+        // Popups like select menus aren't part of the tab's picture.
+        if element_type == PaintElementType::View {
+            self.thumbnails.on_paint(&browser, buffer, width, height);
+            self.transitions.on_paint(&browser);
+            self.split.on_paint(&browser, buffer, width, height);
+            self.extension_popup
+                .on_paint(&browser, buffer, width, height);
+            self.benchmark.on_paint(&browser, buffer, width, height);
         }
     }
 
     fn get_accessibility_handler(&mut self) -> Option<AccessibilityHandler> {
-        None
+        Some(AccessibilityHandler::new(MyAccessibilityHandler {
+            accessibility: self.accessibility.clone(),
+        }))
     }
 
     fn get_root_screen_rect(&mut self, browser: Browser) -> Option<Rect> {
@@ -511,11 +3531,12 @@ impl RenderHandlerCallbacks for MyRenderHandler {
         allowed_ops: DragOperations,
         point: &Point,
     ) -> bool {
-        false
+        self.drag.start(drag_data, allowed_ops);
+        true
     }
 
     fn update_drag_cursor(&mut self, browser: Browser, operation: DragOperations) {
-        // Update drag cursor
+        self.drag.set_operation(operation);
     }
 
     fn on_scroll_offset_changed(&mut self, browser: Browser, x: f64, y: f64) {
@@ -528,7 +3549,8 @@ impl RenderHandlerCallbacks for MyRenderHandler {
         selected_range: &Range,
         character_bounds: &[Rect],
     ) {
-        // Handle IME composition range changes
+        self.ime
+            .set_composition_range(*selected_range, character_bounds);
     }
 
     fn on_text_selection_changed(
@@ -541,23 +3563,94 @@ impl RenderHandlerCallbacks for MyRenderHandler {
     }
 
     fn on_virtual_keyboard_requested(&mut self, browser: Browser, input_mode: TextInputMode) {
-        // Handle virtual keyboard requests
+        self.ime.set_input_mode(input_mode);
+    }
+}
+
+/// Hands the accessibility tree the page sends to `AccessibilityTree`.
+pub struct MyAccessibilityHandler {
+    accessibility: AccessibilityTree,
+}
+
+impl AccessibilityHandlerCallbacks for MyAccessibilityHandler {
+    fn on_accessibility_tree_change(&mut self, value: Value) {
+        if let Err(e) = self.accessibility.on_tree_change(value) {
+            eprintln!("Error reading accessibility tree: {}", e);
+        }
+    }
+
+    fn on_accessibility_location_change(&mut self, value: Value) {
+        if let Err(e) = self.accessibility.on_location_change(value) {
+            eprintln!("Error reading accessibility locations: {}", e);
+        }
     }
 }
 
-pub struct MyAppCallbacks;
+pub struct MyAppCallbacks {
+    render_process: RenderProcessHandler,
+    spellcheck: SpellCheckLanguage,
+    /// URI template of the DNS over HTTPS resolver, if it's on.
+    dns_over_https: Option<String>,
+    /// Proxy switch to start with, if not the system's proxy.
+    proxy: Option<ProxySwitch>,
+    sandbox: SandboxPolicy,
+}
 
 impl AppCallbacks for MyAppCallbacks {
     fn on_before_command_line_processing(
         &mut self,
-        _process_type: Option<&str>,
-        _command_line: Option<CommandLine>,
+        process_type: Option<&str>,
+        command_line: Option<CommandLine>,
     ) {
+        // Only the browser process decides on spell checking, DNS, proxy,
+        // autoplay and sandboxing.
+        if process_type.is_some() {
+            return;
+        }
+
+        if let Some(command_line) = command_line {
+            if let Err(e) = autoplay::configure(&command_line) {
+                eprintln!("Error configuring autoplay: {}", e);
+            }
+
+            if let Err(e) = spellcheck::configure(&command_line, self.spellcheck) {
+                eprintln!("Error configuring spell check: {}", e);
+            }
+
+            if let Some(template) = &self.dns_over_https {
+                if let Err(e) = secure_dns::configure(&command_line, template) {
+                    eprintln!("Error configuring secure DNS: {}", e);
+                }
+            }
+
+            if let Some(switch) = &self.proxy {
+                if let Err(e) = proxy::configure(&command_line, switch) {
+                    eprintln!("Error configuring proxy: {}", e);
+                }
+            }
+
+            if let Err(e) = sandbox::configure(&command_line, &self.sandbox) {
+                eprintln!("Error configuring sandbox: {}", e);
+            }
+        }
+    }
+
+    fn on_register_custom_schemes(&mut self, mut registrar: SchemeRegistrar) {
+        browser_common::register_schemes(&mut registrar);
     }
 
     fn get_browser_process_handler(&mut self) -> Option<cef_ui::BrowserProcessHandler> {
         None
     }
+
+    fn get_render_process_handler(&mut self) -> Option<RenderProcessHandler> {
+        Some(self.render_process.clone())
+    }
+}
+
+/// Where the UI's bundled assets live, also served over `app://assets/`.
+fn assets_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets")
 }
 
 pub fn get_root_cache_dir() -> Result<PathBuf> {
@@ -566,17 +3659,35 @@ pub fn get_root_cache_dir() -> Result<PathBuf> {
     Ok(path)
 }
 
-fn initialize_cef() -> Result<Context, Box<dyn std::error::Error>> {
+fn initialize_cef(
+    spellcheck: SpellCheckLanguage,
+    dns_over_https: Option<String>,
+    proxy: Option<ProxySwitch>,
+    sandbox: SandboxPolicy,
+) -> Result<Context, Box<dyn std::error::Error>> {
     let root_cache_dir = get_root_cache_dir()?;
     let main_args = MainArgs::new()?;
 
+    // An old framework would crash somewhere inside CEF instead.
+    about::check_cef_version()?;
+
+    if let Err(e) = spellcheck::install_dictionaries(&assets_dir(), &root_cache_dir) {
+        eprintln!("Failed to install dictionaries: {}", e);
+    }
+
     let settings = Settings::new()
         .log_severity(LogSeverity::Info)
         .root_cache_path(&root_cache_dir)?
         .windowless_rendering_enabled(true)
-        .no_sandbox(false);
+        .no_sandbox(!sandbox.process_sandbox);
 
-    let app = App::new(MyAppCallbacks {});
+    let app = App::new(MyAppCallbacks {
+        render_process: browser_common::render_process_handler(),
+        spellcheck,
+        dns_over_https,
+        proxy,
+        sandbox,
+    });
     let context = Context::new(main_args, settings, Some(app));
 
     // Check if this is a CEF subprocess
@@ -587,96 +3698,352 @@ fn initialize_cef() -> Result<Context, Box<dyn std::error::Error>> {
     // Initialize CEF
     context.initialize()?;
 
+    app_scheme::register_handler(assets_dir());
+
     Ok(context)
 }
 
-fn create_browser() -> Result<Browser, Box<dyn std::error::Error>> {
+fn create_browser(
+    callbacks: MyClientCallbacks,
+    url: &str,
+    request_context: Option<RequestContext>,
+) -> Result<Browser, Box<dyn std::error::Error>> {
     let window_info = WindowInfo::new()
         .window_name(&String::from("browser"))
         .windowless_rendering_enabled(true);
 
     let browser_settings = BrowserSettings::new();
 
-    let client = Client::new(MyClientCallbacks);
+    let client = Client::new(callbacks);
 
     // BrowserHost::create_browser_sync returns Browser directly, not Result
     let browser = BrowserHost::create_browser_sync(
         &window_info,
         client,
-        "https://www.google.com",
+        url,
         &browser_settings,
-        None,
-        None,
+        Some(page_scripts()),
+        request_context,
     );
 
     Ok(browser)
 }
 
+/// What the render process runs in a tab's main frames as each is created,
+/// before the page's own scripts.
+fn page_scripts() -> DictionaryValue {
+    browser_common::extra_info(&[
+        autofill::AUTOFILL_SCRIPT,
+        media::MEDIA_SCRIPT,
+        capture::CAPTURE_SCRIPT,
+        autoplay::AUTOPLAY_SCRIPT,
+        notifications::NOTIFICATION_SCRIPT,
+        websocket::WEBSOCKET_SCRIPT,
+        pip::PIP_SCRIPT,
+        extension_storage::STORAGE_SCRIPT,
+        speech::SPEECH_SCRIPT,
+        speech_recognition::RECOGNITION_SCRIPT,
+        translate::TRANSLATE_SCRIPT,
+    ])
+}
+
+fn client_callbacks(cx: &GpuiApp) -> MyClientCallbacks {
+    MyClientCallbacks {
+        history: cx.global::<History>().clone(),
+        credentials: cx.global::<CredentialStore>().clone(),
+        certificates: cx.global::<CertificateExceptions>().clone(),
+        permissions: cx.global::<PermissionsStore>().clone(),
+        captures: cx.global::<ElementCaptures>().clone(),
+        reader: cx.global::<ReaderMode>().clone(),
+        console: cx.global::<ConsoleLog>().clone(),
+        dialogs: cx.global::<JsDialogs>().clone(),
+        focus: cx.global::<PageFocus>().clone(),
+        ime: cx.global::<ImeState>().clone(),
+        drag: cx.global::<PageDrag>().clone(),
+        thumbnails: cx.global::<ThumbnailCache>().clone(),
+        transitions: cx.global::<PageTransitions>().clone(),
+        accessibility: cx.global::<AccessibilityTree>().clone(),
+        split: cx.global::<SplitView>().clone(),
+        extension_popup: cx.global::<ExtensionPopupView>().clone(),
+        user_scripts: cx.global::<UserScripts>().clone(),
+        sources: cx.global::<SourceViewer>().clone(),
+        mailto: cx.global::<MailtoHandler>().clone(),
+        popups: cx.global::<Popups>().clone(),
+        blocked_popups: cx.global::<BlockedPopupsState>().clone(),
+        fullscreen: cx.global::<PageFullscreen>().clone(),
+        csp: cx.global::<CspInspector>().clone(),
+        router: cx.global::<MessageRouter>().clone(),
+        session: cx.global::<Session>().clone(),
+        process_monitor: cx.global::<ProcessMonitor>().clone(),
+        ad_block: cx.global::<AdBlock>().clone(),
+        offline: cx.global::<OfflineCache>().clone(),
+        throttling: cx.global::<NetworkThrottling>().clone(),
+        pdf: cx.global::<PdfViewer>().clone(),
+        proxy_errors: cx.global::<ProxyErrors>().clone(),
+        error_pages: cx.global::<ErrorPageHandler>().clone(),
+        menus: cx.global::<ContextMenus>().clone(),
+        hangs: cx.global::<HangMonitor>().clone(),
+        archiver: cx.global::<PageArchiver>().clone(),
+        favicons: cx.global::<FaviconCache>().clone(),
+        link_previews: cx.global::<LinkPreviews>().clone(),
+        network: cx.global::<NetworkLog>().clone(),
+        websockets: cx.global::<WebSocketLog>().clone(),
+        view: cx.global::<PageView>().clone(),
+        form_data: cx.global::<FormDataStore>().clone(),
+        passwords: cx.global::<PasswordManager>().clone(),
+        audio: cx.global::<AudioCapture>().clone(),
+        print_jobs: cx.global::<PrintJobs>().clone(),
+        lifecycle: cx.global::<CefLifecycleEvents>().clone(),
+        benchmark: cx.global::<Benchmark>().clone(),
+        translator: cx.global::<Translator>().clone(),
+    }
+}
+
+/// Create a browser for a new tab showing `url`.
+pub fn new_browser(cx: &GpuiApp, url: &str) -> Option<Browser> {
+    new_browser_in_context(cx, url, None)
+}
+
+/// Create a browser for a new tab showing `url` in `request_context`, or the
+/// one site isolation picks for it if `None`.
+pub fn new_browser_in_context(
+    cx: &GpuiApp,
+    url: &str,
+    request_context: Option<RequestContext>,
+) -> Option<Browser> {
+    let request_context = request_context.or_else(|| site_isolation::context_for(cx, url));
+    match create_browser(client_callbacks(cx), url, request_context) {
+        Ok(browser) => Some(browser),
+        Err(e) => {
+            eprintln!("Error creating browser: {}", e);
+            None
+        }
+    }
+}
+
 fn initialize_browser_in_context(cx: &mut GpuiApp) -> Result<(), Box<dyn std::error::Error>> {
-    let context = initialize_cef()?;
-    let browser = create_browser()?;
+    let settings = cx.global::<settings::Settings>();
+    let secure_dns = SecureDns::new(settings);
+    let lifecycle = cx.global::<CefLifecycleEvents>().clone();
+    lifecycle.record(LifecycleEvent::CefInitStart);
+    let context = initialize_cef(
+        settings.spellcheck_language,
+        secure_dns.template(),
+        proxy::switch(settings),
+        settings.sandbox,
+    )?;
+    lifecycle.record(LifecycleEvent::CefInitDone);
+    cx.set_global(secure_dns);
+    cx.global_mut::<BrowserState>().context = Some(context);
+
+    source::register_handler(cx.global::<SourceViewer>().clone());
+
+    extensions::load_all(cx);
+    background::start(cx);
+
+    // After a crash the user is asked before the old tabs come back.
+    let session = cx.global::<Session>().clone();
+    if !cx.global::<settings::Settings>().restore_session {
+        session.discard_saved_tabs();
+    } else if !session.crashed() {
+        session::restore(cx);
+    }
 
-    let state = cx.global_mut::<BrowserState>();
-    state.context = Some(context);
-    state.browser = Some(browser);
+    if cx.global::<TabManager>().tabs().is_empty() {
+        let homepage = cx.global::<settings::Settings>().homepage.clone();
+        let context = site_isolation::context_for(cx, &homepage);
+        let browser = create_browser(client_callbacks(cx), &homepage, context)?;
+        tabs::add(cx, browser, true);
+    }
 
     Ok(())
 }
 
+/// Open a browser window, tracked by the `WindowManager`, with its top left
+/// corner at `origin` on screen, or centered if `None`.
+fn open_browser_window(
+    cx: &mut GpuiApp,
+    origin: Option<gpui::Point<Pixels>>,
+) -> Option<WindowHandle<WindowDemo>> {
+    let window_size = size(px(800.0), px(600.0));
+    let bounds = match origin {
+        Some(origin) => Bounds::new(origin, window_size),
+        None => Bounds::centered(None, window_size, cx),
+    };
+
+    let window = cx
+        .open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                window_background: gpui::WindowBackgroundAppearance::Blurred,
+                titlebar: Some(gpui::TitlebarOptions {
+                    appears_transparent: true,
+                    traffic_light_position: Some(point(px(16.0), px(18.0))),
+                    title: Some(SharedString::from("CEF Browser Demo")),
+                }),
+                ..Default::default()
+            },
+            |window, cx| {
+                cx.new(|cx| {
+                    cx.observe_window_bounds(window, move |_, window, _| {
+                        eprintln!("Window bounds changed: {:?}", window.bounds());
+                    })
+                    .detach();
+
+                    WindowDemo::new(window, cx)
+                })
+            },
+        )
+        .inspect_err(|e| eprintln!("Error opening window: {:?}", e))
+        .ok()?;
+
+    window_manager::register(cx, window);
+    Some(window)
+}
+
 fn try_main() -> Result<()> {
     Application::new()
-        .with_assets(Assets {
-            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
-        })
+        .with_assets(Assets { base: assets_dir() })
         .run(|cx: &mut GpuiApp| {
             // Initialize browser state in GPUI context
-            cx.set_global(BrowserState {
-                browser: None,
-                context: None,
-                image: None,
-            });
+            cx.set_global(BrowserState::new());
+            cx.set_global(CredentialStore::default());
+            cx.set_global(CertificateExceptions::default());
+            cx.set_global(ProxyErrors::default());
+            cx.set_global(ErrorPageHandler::default());
+            cx.set_global(DevTools::default());
+            cx.set_global(ElementCaptures::default());
+            cx.set_global(ReaderMode::default());
+            cx.set_global(ConsoleLog::default());
+            cx.set_global(JsDialogs::default());
+            cx.set_global(PageFocus::default());
+            cx.set_global(ImeState::default());
+            cx.set_global(PageDrag::default());
+            cx.set_global(ThumbnailCache::default());
+            cx.set_global(AccessibilityTree::default());
+            let memory = MemoryPressureHandler::default();
+            memory.start();
+            cx.set_global(memory);
+            cx.set_global(Popups::default());
+            cx.set_global(BlockedAutoplay::default());
+            cx.set_global(PageFullscreen::default());
+            cx.set_global(PdfViewer::default());
+            cx.set_global(AdBlock::default());
+            cx.set_global(MessageRouter::default());
+            cx.set_global(ContextMenus::default());
+            cx.set_global(SourceViewer::default());
+            cx.set_global(MailtoHandler::default());
+            cx.set_global(Autofill::default());
+            cx.set_global(MediaSessions::default());
+            cx.set_global(NowPlaying::default());
+            cx.set_global(AudioCapture::default());
+            cx.set_global(NetworkLog::default());
+            cx.set_global(WebSocketLog::default());
+            cx.set_global(NetworkThrottling::default());
+            cx.set_global(Performance::default());
+            cx.set_global(CspInspector::default());
+            cx.set_global(StorageInspector::default());
+            cx.set_global(CacheInspector::default());
+            cx.set_global(SpeechSynthesis::default());
+            cx.set_global(SpeechRecognizer::default());
+            cx.set_global(Translator::default());
+            cx.set_global(PrintJobs::default());
+            cx.set_global(PageView::default());
+            cx.set_global(SplitView::default());
+            cx.set_global(ExtensionPopupView::default());
+            cx.set_global(TouchBar::default());
+            cx.set_global(WebCompatReports::default());
+            cx.set_global(Extensions::default());
+            cx.set_global(TabManager::default());
+            cx.set_global(Incognito::default());
+            cx.set_global(BackgroundTask::default());
+            cx.set_global(PictureInPicture::default());
+            cx.set_global(ScrollAnimator::default());
+            cx.set_global(Gestures::default());
+            cx.set_global(MouseGestureRecognizer::default());
+            cx.set_global(HangMonitor::default());
+            cx.set_global(PageArchiver::default());
+            cx.set_global(LinkPreviews::default());
+            cx.set_global(PageActions::default());
+            cx.set_global(ElementPicker::default());
+            cx.set_global(ProcessMonitor::default());
+            cx.set_global(PageTransitions::default());
+            cx.set_global(UserScripts::default());
+            cx.set_global(WindowManager::default());
+            cx.set_global(CefLifecycleEvents::default());
+            cx.set_global(Benchmark::default());
+            page_actions::register_builtin(cx);
 
-            // Initialize CEF and browser
-            if let Err(e) = initialize_browser_in_context(cx) {
-                eprintln!("Failed to initialize browser: {:?}", e);
-                return;
+            match get_root_cache_dir() {
+                Ok(dir) => {
+                    cx.set_global(Bookmarks::load(dir.join("bookmarks.json")));
+                    cx.set_global(History::load(dir.join("history.json")));
+                    cx.set_global(PermissionsStore::load(dir.join("permissions.json")));
+                    cx.set_global(BlockedPopupsState::load(dir.join("blocked_popups.json")));
+                    cx.set_global(CaptureDevices::load(dir.join("capture_devices.json")));
+                    cx.set_global(FormDataStore::load(dir.join("form_data.json")));
+                    cx.set_global(PasswordManager::load(dir.join("passwords.bin")));
+                    cx.set_global(Notifications::load(dir.join("notifications.json")));
+                    cx.set_global(OfflineCache::load(dir.join("offline_cache")));
+                    cx.set_global(FaviconCache::load(dir.join("favicons")));
+                    cx.set_global(WebExtensionStorage::load(&dir.join("storage.db")));
+                    cx.set_global(SiteIsolation::new(dir.join("isolated")));
+                    cx.set_global(RecentCommands::load(dir.join("recent_commands.json")));
+                    settings::init(cx, dir.join("settings.toml"));
+                    cx.set_global(Session::load(dir));
+                }
+                Err(e) => {
+                    eprintln!("Failed to open cache directory: {:?}", e);
+                    return;
+                }
             }
 
-            let bounds = Bounds::centered(None, size(px(800.0), px(600.0)), cx);
+            // Crash reporting starts first, to hear about CEF failing to.
+            telemetry::init(cx);
 
-            cx.open_window(
-                WindowOptions {
-                    window_bounds: Some(WindowBounds::Windowed(bounds)),
-                    window_background: gpui::WindowBackgroundAppearance::Blurred,
-                    titlebar: Some(gpui::TitlebarOptions {
-                        appears_transparent: true,
-                        traffic_light_position: Some(point(px(16.0), px(18.0))),
-                        title: Some(SharedString::from("CEF Browser Demo")),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                },
-                |window, cx| {
-                    cx.new(|cx| {
-                        cx.observe_window_bounds(window, move |_, window, _| {
-                            println!("Window bounds changed: {:?}", window.bounds());
-                        })
-                        .detach();
+            if console_log::requested() {
+                let logs = get_root_cache_dir()
+                    .and_then(|dir| console_log::init(dir.join("console_logs")));
+                if let Err(e) = logs {
+                    eprintln!("Failed to start logging console messages: {:?}", e);
+                }
+            }
 
-                        WindowDemo {}
-                    })
-                },
-            )
-            .unwrap();
+            // Initialize CEF and browser, or the mock browser standing in for
+            // them. The mock and benchmarks leave the saved session alone.
+            let mock = mock::requested();
+            let benchmark = benchmark::requested();
+            let benchmarking = benchmark.is_some();
+            if let Some(pattern) = mock {
+                let homepage = cx.global::<settings::Settings>().homepage.clone();
+                cx.global_mut::<BrowserState>().mock = Some(MockBrowser::new(&homepage, pattern));
+            } else if let Err(e) = initialize_browser_in_context(cx) {
+                eprintln!("Failed to initialize browser: {:?}", e);
+                telemetry::capture_error(&anyhow::anyhow!("Failed to initialize browser: {}", e));
+                return;
+            } else if !benchmarking {
+                session::autosave(cx);
+            }
 
-            cx.activate(true);
+            let Some(window) = open_browser_window(cx, None) else {
+                return;
+            };
 
-            // Run the message loop
-            if let Some(context) = cx.global::<BrowserState>().context.as_ref() {
-                context.run_message_loop();
+            cx.activate(true);
+            if let Some(urls) = benchmark {
+                benchmark::start(cx, urls);
             }
+            window
+                .update(cx, |_, window, cx| telemetry::ask(window, cx))
+                .ok();
+
+            // Global actions are registered before the message loop, which
+            // only returns once CEF shuts down.
+            cx.on_action(move |_: &Quit, cx| {
+                if mock.is_none() && !benchmarking {
+                    cx.global::<Session>().clone().end(cx);
+                }
 
-            cx.on_action(|_: &Quit, cx| {
                 // Cleanup using GPUI's global state
                 let state = cx.global_mut::<BrowserState>();
                 if let Some(context) = state.context.take() {
@@ -684,7 +4051,22 @@ fn try_main() -> Result<()> {
                 }
                 cx.quit();
             });
-            cx.bind_keys([KeyBinding::new("cmd-q", Quit, None)]);
+            cx.on_action(|_: &ToggleBookmarksBar, cx| {
+                cx.update_global::<Bookmarks, _>(|bookmarks, _| {
+                    bookmarks.bar_visible = !bookmarks.bar_visible;
+                });
+            });
+            cx.on_action(|_: &ToggleReaderMode, cx| reader::toggle(cx));
+            cx.on_action(|_: &NewIncognitoTab, cx| incognito::new_tab(cx));
+            cx.on_action(|_: &NewWindow, cx| window_manager::new_window(cx));
+            cx.on_action(|action: &ActivateWindow, cx| window_manager::activate(cx, action.0));
+            shortcuts::bind(cx);
+            focus_mode::intercept(cx);
+
+            // Run the message loop
+            if let Some(context) = cx.global::<BrowserState>().context.as_ref() {
+                context.run_message_loop();
+            }
         });
 
     Ok(())
@@ -696,3 +4078,52 @@ fn main() {
         exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_point_is_relative_to_the_page() {
+        let origin = point(px(0.), px(72.));
+
+        assert_eq!(view_point(origin, origin), Point { x: 0, y: 0 });
+        assert_eq!(
+            view_point(point(px(120.), px(172.)), origin),
+            Point { x: 120, y: 100 }
+        );
+    }
+
+    #[test]
+    fn view_point_drops_fractions_of_a_pixel() {
+        let origin = point(px(8.5), px(40.25));
+
+        assert_eq!(
+            view_point(point(px(20.), px(60.)), origin),
+            Point { x: 11, y: 19 }
+        );
+    }
+
+    #[test]
+    fn inspecting_looks_where_the_pointer_last_was_over_the_page() {
+        let mut state = BrowserState::new();
+        assert_eq!(state.inspect_element_at(), Point { x: 0, y: 0 });
+
+        let origin = point(px(0.), px(72.));
+        state.pointer_moved(point(px(10.), px(80.)), origin);
+        state.pointer_moved(point(px(120.5), px(172.)), origin);
+        assert_eq!(state.inspect_element_at(), Point { x: 120, y: 100 });
+    }
+
+    #[test]
+    fn view_point_above_the_page_is_negative() {
+        // The toolbar sits above the page, so the pointer can be over it
+        // when inspecting.
+        let origin = point(px(0.), px(72.));
+
+        assert_eq!(
+            view_point(point(px(30.), px(12.)), origin),
+            Point { x: 30, y: -60 }
+        );
+    }
+}
@@ -0,0 +1,833 @@
+use anyhow::Result;
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::{
+    AuthCallback, Frame, Request, UrlRequest, UrlRequestClient, UrlRequestClientCallbacks,
+    UrlRequestStatus,
+};
+use gpui::{
+    div, ease_in_out, img, prelude::*, px, Animation, AnimationExt, App as GpuiApp, Context,
+    DismissEvent, EventEmitter, Global, Image, ImageFormat, ImageSource, SharedString, Window,
+};
+use serde::Deserialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    theme::ActiveTheme,
+};
+
+/// How long the media bar takes to slide in or out.
+const SLIDE_DURATION: Duration = Duration::from_millis(200);
+
+/// Seconds skipped back or forward.
+const SKIP_SECONDS: f64 = 10.;
+
+/// Volume change per click.
+const VOLUME_STEP: f64 = 0.1;
+
+/// Largest size of a session's artwork, in pixels, enough for Control
+/// Center at double density.
+const ARTWORK_SIZE: u32 = 256;
+
+/// Reports the audio or video element the page last played, with the
+/// metadata and actions the page gave `navigator.mediaSession`, and defines
+/// `__cefMedia(command, value)` to control it. CEF's `MediaRouter` only
+/// knows about cast sinks, so playback is followed from the page. Relies on
+/// `window.cefQuery`.
+pub const MEDIA_SCRIPT: &str = r#"(() => {
+  if (window.__cefMedia) return;
+
+  let current = null;
+  const session = navigator.mediaSession;
+  const handlers = new Map();
+
+  const artwork = (metadata) => {
+    const images = (metadata && metadata.artwork) || [];
+    const largest = [...images].sort((a, b) =>
+      parseInt(b.sizes || "0", 10) - parseInt(a.sizes || "0", 10))[0];
+    try {
+      return largest ? new URL(largest.src, location.href).href : "";
+    } catch (_) {
+      return "";
+    }
+  };
+
+  const report = (active) => {
+    const metadata = session && session.metadata;
+    const state = current && active
+      ? {
+          active: true,
+          playing: !current.paused,
+          title: (metadata && metadata.title) || document.title,
+          artist: (metadata && metadata.artist) || "",
+          album: (metadata && metadata.album) || "",
+          artwork: artwork(metadata),
+          volume: current.volume,
+          muted: current.muted,
+          previous_track: handlers.has("previoustrack"),
+          next_track: handlers.has("nexttrack"),
+        }
+      : { active: false };
+
+    window.cefQuery({ request: "media:" + JSON.stringify(state) });
+  };
+
+  if (session) {
+    const setActionHandler = session.setActionHandler.bind(session);
+    session.setActionHandler = (action, handler) => {
+      if (handler) handlers.set(action, handler);
+      else handlers.delete(action);
+      try {
+        setActionHandler(action, handler);
+      } catch (_) {}
+      if (current) report(true);
+    };
+
+    const metadata = Object.getOwnPropertyDescriptor(MediaSession.prototype, "metadata");
+    if (metadata && metadata.set) {
+      Object.defineProperty(session, "metadata", {
+        get: () => metadata.get.call(session),
+        set: (value) => {
+          metadata.set.call(session, value);
+          if (current) report(true);
+        },
+        configurable: true,
+      });
+    }
+  }
+
+  // Runs the page's own handler for `action` if it set one.
+  const run = (action, fallback) => {
+    const handler = handlers.get(action);
+    if (handler) handler({ action });
+    else if (fallback) fallback();
+  };
+
+  for (const type of ["play", "pause", "volumechange"]) {
+    document.addEventListener(type, (event) => {
+      if (!(event.target instanceof HTMLMediaElement)) return;
+      if (type === "play") current = event.target;
+      if (event.target === current) report(true);
+    }, true);
+  }
+
+  document.addEventListener("ended", (event) => {
+    if (event.target === current) report(false);
+  }, true);
+
+  window.addEventListener("pagehide", () => {
+    if (current) report(false);
+  });
+
+  Object.defineProperty(window, "__cefMedia", {
+    value: (command, value) => {
+      if (!current) return;
+
+      if (command === "toggle") command = current.paused ? "play" : "pause";
+      if (command === "play") run("play", () => current.play());
+      if (command === "pause") run("pause", () => current.pause());
+      if (command === "previoustrack" || command === "nexttrack") run(command);
+      if (command === "seek") current.currentTime += value;
+      if (command === "volume") {
+        current.muted = false;
+        current.volume = Math.min(1, Math.max(0, current.volume + value));
+      }
+    },
+  });
+})();"#;
+
+/// Playback state as the page reports it.
+#[derive(Clone, Deserialize)]
+struct MediaState {
+    active: bool,
+    #[serde(default)]
+    playing: bool,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    album: String,
+    /// URL of the largest `MediaMetadata` artwork, or empty.
+    #[serde(default)]
+    artwork: String,
+    #[serde(default)]
+    volume: f64,
+    #[serde(default)]
+    muted: bool,
+    /// Whether the page handles the previous and next track actions.
+    #[serde(default)]
+    previous_track: bool,
+    #[serde(default)]
+    next_track: bool,
+}
+
+/// Media playing in a page, and the frame to send its controls to.
+#[derive(Clone)]
+struct MediaSession {
+    state: MediaState,
+    frame: Frame,
+}
+
+/// A session's artwork, scaled down to `ARTWORK_SIZE`.
+#[derive(Clone)]
+struct Artwork {
+    url: String,
+    png: Vec<u8>,
+    image: Arc<Image>,
+}
+
+#[derive(Default)]
+struct MediaSessionsInner {
+    session: Option<MediaSession>,
+    artwork: Option<Artwork>,
+    /// URL of the artwork being downloaded.
+    fetching: Option<String>,
+    changed: bool,
+}
+
+/// The media session reported by pages, shared with the media bar.
+#[derive(Clone, Default)]
+pub struct MediaSessions(Arc<Mutex<MediaSessionsInner>>);
+
+impl Global for MediaSessions {}
+
+impl MediaSessions {
+    /// Whether media started, changed or stopped since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.0.lock().unwrap().session.is_some()
+    }
+
+    fn session(&self) -> Option<MediaSession> {
+        self.0.lock().unwrap().session.clone()
+    }
+
+    /// The session's artwork, once it's downloaded.
+    fn artwork(&self) -> Option<Artwork> {
+        let inner = self.0.lock().unwrap();
+        let url = &inner.session.as_ref()?.state.artwork;
+        inner.artwork.clone().filter(|artwork| artwork.url == *url)
+    }
+
+    /// Download the artwork at `url` in `frame`'s request context, unless
+    /// it's already here or on its way.
+    fn fetch_artwork(&self, frame: &Frame, url: &str) {
+        {
+            let mut inner = self.0.lock().unwrap();
+            let have = inner.artwork.as_ref().map(|artwork| artwork.url.as_str());
+            if url.is_empty() || have == Some(url) || inner.fetching.as_deref() == Some(url) {
+                return;
+            }
+            inner.fetching = Some(url.to_string());
+        }
+
+        let request = Request::new();
+        if let Err(e) = request.set_url(url).and_then(|_| request.set_method("GET")) {
+            eprintln!("Error fetching media artwork {}: {}", url, e);
+            self.0.lock().unwrap().fetching = None;
+            return;
+        }
+
+        let client = UrlRequestClient::new(ArtworkRequestClient {
+            sessions: self.clone(),
+            url: url.to_string(),
+            body: Vec::new(),
+        });
+        let context = frame
+            .get_browser()
+            .and_then(|browser| browser.get_host())
+            .and_then(|host| host.get_request_context())
+            .ok();
+        UrlRequest::new(request, client, context);
+    }
+
+    fn insert_artwork(&self, url: String, body: &[u8]) {
+        let result = scale_artwork(body);
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.fetching.as_ref() == Some(&url) {
+            inner.fetching = None;
+        }
+        match result {
+            Ok(png) => {
+                inner.artwork = Some(Artwork {
+                    url,
+                    image: Arc::new(Image::from_bytes(ImageFormat::Png, png.clone())),
+                    png,
+                });
+                inner.changed = true;
+            }
+            Err(e) => eprintln!("Error decoding media artwork {}: {}", url, e),
+        }
+    }
+
+    /// Run one of the page's media commands.
+    fn control(&self, command: &str, value: f64) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let script = format!(
+            "window.__cefMedia && window.__cefMedia({:?}, {});",
+            command, value
+        );
+
+        if let Err(e) = session.frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error controlling media: {}", e);
+        }
+    }
+}
+
+/// Record the playback state a page reported.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    let state = match serde_json::from_str::<MediaState>(argument) {
+        Ok(state) => state,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid media state: {}", e));
+            return;
+        }
+    };
+
+    let sessions = cx.global::<MediaSessions>();
+    let frame = query.frame();
+    sessions.fetch_artwork(&frame, &state.artwork);
+    {
+        let mut inner = sessions.0.lock().unwrap();
+        inner.session = state.active.then(|| MediaSession { state, frame });
+        inner.changed = true;
+    }
+
+    query.success("");
+}
+
+/// Scale artwork down to a PNG of at most `ARTWORK_SIZE` a side.
+fn scale_artwork(body: &[u8]) -> Result<Vec<u8>> {
+    let artwork = image::load_from_memory(body)?.thumbnail(ARTWORK_SIZE, ARTWORK_SIZE);
+
+    let mut png = Cursor::new(Vec::new());
+    artwork.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+struct ArtworkRequestClient {
+    sessions: MediaSessions,
+    url: String,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for ArtworkRequestClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let url = std::mem::take(&mut self.url);
+        match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) => self.sessions.insert_artwork(url, &self.body),
+            _ => {
+                let mut inner = self.sessions.0.lock().unwrap();
+                if inner.fetching.as_ref() == Some(&url) {
+                    inner.fetching = None;
+                }
+            }
+        }
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// What the system's Now Playing controls show.
+#[derive(Clone, Default, PartialEq)]
+struct NowPlayingState {
+    title: String,
+    artist: String,
+    album: String,
+    playing: bool,
+    previous_track: bool,
+    next_track: bool,
+    /// URL of the artwork shown, to tell when it changes.
+    artwork_url: Option<String>,
+}
+
+/// The session as last published to the system, kept to tell when it needs
+/// publishing again.
+#[derive(Default)]
+pub struct NowPlaying(Option<NowPlayingState>);
+
+impl Global for NowPlaying {}
+
+/// Publish the media session to the system's Now Playing controls, in
+/// Control Center and on the Touch Bar, and run the commands they sent
+/// since the last call.
+pub fn poll(cx: &mut GpuiApp) {
+    let sessions = cx.global::<MediaSessions>().clone();
+    for command in platform::take_commands() {
+        sessions.control(command, 0.);
+    }
+
+    let artwork = sessions.artwork();
+    let state = sessions.session().map(|session| NowPlayingState {
+        title: session.state.title,
+        artist: session.state.artist,
+        album: session.state.album,
+        playing: session.state.playing,
+        previous_track: session.state.previous_track,
+        next_track: session.state.next_track,
+        artwork_url: artwork.as_ref().map(|artwork| artwork.url.clone()),
+    });
+
+    let now_playing = cx.global_mut::<NowPlaying>();
+    if now_playing.0 != state {
+        platform::publish(
+            state.as_ref(),
+            artwork.as_ref().map(|artwork| artwork.png.as_slice()),
+        );
+        now_playing.0 = state;
+    }
+}
+
+/// Floating controls for the media playing in a page, slid in from the bottom
+/// of the window while it plays.
+pub struct MediaBar {
+    sessions: MediaSessions,
+    hiding: bool,
+}
+
+impl MediaBar {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            sessions: cx.global::<MediaSessions>().clone(),
+            hiding: false,
+        }
+    }
+
+    /// Keep the bar up, sliding it back in if it was on its way out.
+    pub fn show(&mut self, cx: &mut Context<Self>) {
+        self.hiding = false;
+        cx.notify();
+    }
+
+    /// Slide the bar out, then dismiss it.
+    pub fn hide(&mut self, cx: &mut Context<Self>) {
+        if self.hiding {
+            return;
+        }
+
+        self.hiding = true;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(SLIDE_DURATION).await;
+            this.update(cx, |this, cx| {
+                if this.hiding {
+                    cx.emit(DismissEvent);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl EventEmitter<DismissEvent> for MediaBar {}
+
+impl Render for MediaBar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let state = self.sessions.session().map(|session| session.state);
+        let artwork = self.sessions.artwork().map(|artwork| artwork.image);
+        let playing = state.as_ref().is_some_and(|state| state.playing);
+        let artist = state
+            .as_ref()
+            .map(|state| state.artist.clone())
+            .filter(|artist| !artist.is_empty());
+        let previous_track = state.as_ref().is_some_and(|state| state.previous_track);
+        let next_track = state.as_ref().is_some_and(|state| state.next_track);
+        let title: SharedString = state
+            .as_ref()
+            .map(|state| state.title.clone())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| t!("media.title").to_string())
+            .into();
+        let volume: SharedString = match &state {
+            Some(state) if state.muted => t!("media.muted"),
+            Some(state) => format!("{:.0}%", state.volume * 100.).into(),
+            None => "".into(),
+        };
+
+        let control = |id: &'static str, label: SharedString, command: &'static str, value| {
+            let sessions = self.sessions.clone();
+            button(id, label, false, cx).on_click(move |_, _, _| sessions.control(command, value))
+        };
+
+        let (animation_id, from, to) = match self.hiding {
+            false => ("media-bar-in", 1., 0.),
+            true => ("media-bar-out", 0., 1.),
+        };
+
+        div()
+            .absolute()
+            .bottom_4()
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .py_2()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .shadow_lg()
+                    .text_xs()
+                    .text_color(theme.text)
+                    .when_some(artwork, |this, artwork| {
+                        this.child(
+                            img(ImageSource::from(artwork))
+                                .flex_none()
+                                .size(px(32.))
+                                .rounded_md(),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .max_w(px(220.))
+                            .overflow_hidden()
+                            .child(
+                                div()
+                                    .overflow_hidden()
+                                    .text_ellipsis()
+                                    .whitespace_nowrap()
+                                    .child(title),
+                            )
+                            .when_some(artist, |this, artist| {
+                                this.child(
+                                    div()
+                                        .overflow_hidden()
+                                        .text_ellipsis()
+                                        .whitespace_nowrap()
+                                        .text_color(theme.text_muted)
+                                        .child(artist),
+                                )
+                            }),
+                    )
+                    .when(previous_track, |this| {
+                        this.child(control(
+                            "media-previous-track",
+                            t!("media.previous_track"),
+                            "previoustrack",
+                            0.,
+                        ))
+                    })
+                    .child(control("media-back", "−10s".into(), "seek", -SKIP_SECONDS))
+                    .child(control(
+                        "media-toggle",
+                        if playing {
+                            t!("media.pause")
+                        } else {
+                            t!("media.play")
+                        },
+                        "toggle",
+                        0.,
+                    ))
+                    .child(control(
+                        "media-forward",
+                        "+10s".into(),
+                        "seek",
+                        SKIP_SECONDS,
+                    ))
+                    .when(next_track, |this| {
+                        this.child(control(
+                            "media-next-track",
+                            t!("media.next_track"),
+                            "nexttrack",
+                            0.,
+                        ))
+                    })
+                    .child(control(
+                        "media-volume-down",
+                        "−".into(),
+                        "volume",
+                        -VOLUME_STEP,
+                    ))
+                    .child(div().w(px(40.)).text_center().child(volume))
+                    .child(control(
+                        "media-volume-up",
+                        "+".into(),
+                        "volume",
+                        VOLUME_STEP,
+                    )),
+            )
+            .with_animation(
+                animation_id,
+                Animation::new(SLIDE_DURATION).with_easing(ease_in_out),
+                move |this, delta| {
+                    let offset = from + (to - from) * delta;
+                    this.mb(px(-64. * offset)).opacity(1. - offset)
+                },
+            )
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{
+        cell::Cell,
+        ptr,
+        sync::{Mutex, Once},
+    };
+
+    use block::ConcreteBlock;
+    use core_foundation::{base::TCFType, string::CFString};
+    use core_graphics::geometry::CGSize;
+    use objc::{
+        class,
+        declare::ClassDecl,
+        msg_send,
+        runtime::{Class, Object, Sel, NO, YES},
+        sel, sel_impl,
+    };
+
+    use super::NowPlayingState;
+
+    /// `MPRemoteCommandHandlerStatusSuccess`.
+    const HANDLER_SUCCESS: isize = 0;
+
+    /// `MPNowPlayingPlaybackState*`.
+    const PLAYBACK_PLAYING: usize = 1;
+    const PLAYBACK_PAUSED: usize = 2;
+    const PLAYBACK_STOPPED: usize = 3;
+
+    #[link(name = "MediaPlayer", kind = "framework")]
+    extern "C" {
+        static MPMediaItemPropertyTitle: *mut Object;
+        static MPMediaItemPropertyArtist: *mut Object;
+        static MPMediaItemPropertyAlbumTitle: *mut Object;
+        static MPMediaItemPropertyArtwork: *mut Object;
+        static MPNowPlayingInfoPropertyPlaybackRate: *mut Object;
+    }
+
+    /// Media commands sent from Control Center, the Touch Bar or media keys,
+    /// since the last poll.
+    static COMMANDS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    thread_local! {
+        /// Receives the remote commands. Lives as long as the browser.
+        static TARGET: *mut Object = unsafe { register_target() };
+
+        /// The artwork image published, released when it's replaced.
+        static ARTWORK: Cell<*mut Object> = const { Cell::new(ptr::null_mut()) };
+    }
+
+    fn target_class() -> &'static Class {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let mut decl = ClassDecl::new("BrowserRemoteCommandTarget", class!(NSObject))
+                .expect("remote command target class already registered");
+            unsafe {
+                decl.add_method(
+                    sel!(play:),
+                    play as extern "C" fn(&Object, Sel, *mut Object) -> isize,
+                );
+                decl.add_method(
+                    sel!(pause:),
+                    pause as extern "C" fn(&Object, Sel, *mut Object) -> isize,
+                );
+                decl.add_method(
+                    sel!(toggle:),
+                    toggle as extern "C" fn(&Object, Sel, *mut Object) -> isize,
+                );
+                decl.add_method(
+                    sel!(previousTrack:),
+                    previous_track as extern "C" fn(&Object, Sel, *mut Object) -> isize,
+                );
+                decl.add_method(
+                    sel!(nextTrack:),
+                    next_track as extern "C" fn(&Object, Sel, *mut Object) -> isize,
+                );
+            }
+            decl.register();
+        });
+
+        Class::get("BrowserRemoteCommandTarget").unwrap()
+    }
+
+    fn push(command: &'static str) -> isize {
+        COMMANDS.lock().unwrap().push(command);
+        HANDLER_SUCCESS
+    }
+
+    extern "C" fn play(_this: &Object, _cmd: Sel, _event: *mut Object) -> isize {
+        push("play")
+    }
+
+    extern "C" fn pause(_this: &Object, _cmd: Sel, _event: *mut Object) -> isize {
+        push("pause")
+    }
+
+    extern "C" fn toggle(_this: &Object, _cmd: Sel, _event: *mut Object) -> isize {
+        push("toggle")
+    }
+
+    extern "C" fn previous_track(_this: &Object, _cmd: Sel, _event: *mut Object) -> isize {
+        push("previoustrack")
+    }
+
+    extern "C" fn next_track(_this: &Object, _cmd: Sel, _event: *mut Object) -> isize {
+        push("nexttrack")
+    }
+
+    /// Make the target and add it to the remote commands the browser
+    /// handles.
+    unsafe fn register_target() -> *mut Object {
+        let target: *mut Object = msg_send![target_class(), new];
+        let center: *mut Object = msg_send![class!(MPRemoteCommandCenter), sharedCommandCenter];
+
+        let commands = [
+            (sel!(playCommand), sel!(play:)),
+            (sel!(pauseCommand), sel!(pause:)),
+            (sel!(togglePlayPauseCommand), sel!(toggle:)),
+            (sel!(previousTrackCommand), sel!(previousTrack:)),
+            (sel!(nextTrackCommand), sel!(nextTrack:)),
+        ];
+        for (command, action) in commands {
+            let command: *mut Object = msg_send![center, performSelector: command];
+            let _: () = msg_send![command, addTarget: target action: action];
+        }
+
+        target
+    }
+
+    pub fn take_commands() -> Vec<&'static str> {
+        std::mem::take(&mut *COMMANDS.lock().unwrap())
+    }
+
+    /// An `NSImage` of a PNG, owned.
+    unsafe fn ns_image(png: &[u8]) -> *mut Object {
+        let data: *mut Object = msg_send![
+            class!(NSData),
+            dataWithBytes: png.as_ptr()
+            length: png.len()
+        ];
+        let image: *mut Object = msg_send![class!(NSImage), alloc];
+        msg_send![image, initWithData: data]
+    }
+
+    /// Show `state` in the system's Now Playing controls, or clear them
+    /// when nothing plays.
+    pub fn publish(state: Option<&NowPlayingState>, artwork: Option<&[u8]>) {
+        // The commands are handled from the first time something plays.
+        TARGET.with(|_| {});
+
+        unsafe {
+            let center: *mut Object = msg_send![class!(MPNowPlayingInfoCenter), defaultCenter];
+            let Some(state) = state else {
+                let _: () = msg_send![center, setNowPlayingInfo: ptr::null_mut::<Object>()];
+                let _: () = msg_send![center, setPlaybackState: PLAYBACK_STOPPED];
+                return;
+            };
+
+            let commands: *mut Object =
+                msg_send![class!(MPRemoteCommandCenter), sharedCommandCenter];
+            for (command, enabled) in [
+                (sel!(previousTrackCommand), state.previous_track),
+                (sel!(nextTrackCommand), state.next_track),
+            ] {
+                let command: *mut Object = msg_send![commands, performSelector: command];
+                let _: () = msg_send![command, setEnabled: if enabled { YES } else { NO }];
+            }
+
+            let info: *mut Object = msg_send![class!(NSMutableDictionary), dictionary];
+            for (key, value) in [
+                (MPMediaItemPropertyTitle, &state.title),
+                (MPMediaItemPropertyArtist, &state.artist),
+                (MPMediaItemPropertyAlbumTitle, &state.album),
+            ] {
+                if value.is_empty() {
+                    continue;
+                }
+                let value = CFString::new(value);
+                let _: () = msg_send![
+                    info,
+                    setObject: value.as_concrete_TypeRef() as *mut Object
+                    forKey: key
+                ];
+            }
+
+            let rate: *mut Object = msg_send![
+                class!(NSNumber),
+                numberWithDouble: if state.playing { 1. } else { 0. }
+            ];
+            let _: () =
+                msg_send![info, setObject: rate forKey: MPNowPlayingInfoPropertyPlaybackRate];
+
+            let image = artwork.map_or(ptr::null_mut(), |png| ns_image(png));
+            if !image.is_null() {
+                let size: CGSize = msg_send![image, size];
+                let handler = ConcreteBlock::new(move |_size: CGSize| image).copy();
+                let artwork: *mut Object = msg_send![class!(MPMediaItemArtwork), alloc];
+                let artwork: *mut Object =
+                    msg_send![artwork, initWithBoundsSize: size requestHandler: &*handler];
+                let _: () = msg_send![info, setObject: artwork forKey: MPMediaItemPropertyArtwork];
+                let _: () = msg_send![artwork, release];
+            }
+
+            let _: () = msg_send![center, setNowPlayingInfo: info];
+            let playback = match state.playing {
+                true => PLAYBACK_PLAYING,
+                false => PLAYBACK_PAUSED,
+            };
+            let _: () = msg_send![center, setPlaybackState: playback];
+
+            // The old artwork's handler went with the info it replaced.
+            let old = ARTWORK.with(|artwork| artwork.replace(image));
+            if !old.is_null() {
+                let _: () = msg_send![old, release];
+            }
+        }
+    }
+}
+
+/// Only macOS has Now Playing controls hooked up.
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::NowPlayingState;
+
+    pub fn take_commands() -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    pub fn publish(_state: Option<&NowPlayingState>, _artwork: Option<&[u8]>) {}
+}
@@ -0,0 +1,176 @@
+// Only macOS reports memory pressure so far.
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use std::sync::{Arc, Mutex};
+
+use cef_ui::CookieManager;
+use gpui::{App as GpuiApp, Global};
+
+use crate::{pip::PictureInPicture, tabs::TabManager, thumbnails::ThumbnailCache, BrowserState};
+
+/// How short of memory the OS says the machine is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressureLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Listens for the OS's memory pressure notifications, which arrive on a
+/// thread of their own, and keeps the latest for the UI to act on.
+#[derive(Clone, Default)]
+pub struct MemoryPressureHandler(Arc<Mutex<Option<MemoryPressureLevel>>>);
+
+impl Global for MemoryPressureHandler {}
+
+impl MemoryPressureHandler {
+    /// Start listening. Does nothing where the OS doesn't report memory
+    /// pressure.
+    pub fn start(&self) {
+        platform::watch(self.clone());
+    }
+
+    fn on_pressure(&self, level: MemoryPressureLevel) {
+        *self.0.lock().unwrap() = Some(level);
+    }
+
+    /// The level the OS reported since the last call, if it reported one.
+    pub fn take_pending(&self) -> Option<MemoryPressureLevel> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Adjust to the memory pressure changing to `level`. Above normal, the tabs
+/// out of sight are muted and their last frames dropped, and cookies are
+/// flushed to disk in case the OS ends the browser. Returns whether anything
+/// was freed.
+pub fn on_pressure(cx: &mut GpuiApp, level: MemoryPressureLevel) -> bool {
+    cx.global_mut::<BrowserState>().pressure_level = level;
+    let thumbnails = cx.global::<ThumbnailCache>().clone();
+    thumbnails.set_pressure_level(level);
+
+    if level == MemoryPressureLevel::Normal {
+        return false;
+    }
+
+    let active = cx.global::<BrowserState>().browser.clone();
+    let pip = cx.global::<PictureInPicture>();
+    for tab in cx.global::<TabManager>().tabs() {
+        let visible = pip.is_detached(&tab.browser)
+            || active
+                .as_ref()
+                .is_some_and(|active| active.is_same(tab.browser.clone()).unwrap_or(false));
+        if visible {
+            continue;
+        }
+
+        if let Err(e) = tab
+            .browser
+            .get_host()
+            .and_then(|host| host.set_audio_muted(true))
+        {
+            eprintln!("Error muting tab: {}", e);
+        }
+        thumbnails.discard_frame(&tab.browser);
+    }
+
+    if let Some(cookies) = CookieManager::get_global_manager(None) {
+        if let Err(e) = cookies.flush_store(None) {
+            eprintln!("Error flushing cookies: {}", e);
+        }
+    }
+
+    true
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    use super::{MemoryPressureHandler, MemoryPressureLevel};
+
+    /// `DISPATCH_MEMORYPRESSURE_*` flags in the source's data.
+    const DISPATCH_MEMORYPRESSURE_NORMAL: usize = 0x01;
+    const DISPATCH_MEMORYPRESSURE_WARN: usize = 0x02;
+    const DISPATCH_MEMORYPRESSURE_CRITICAL: usize = 0x04;
+
+    /// `QOS_CLASS_UTILITY`, for the queue the source calls back on.
+    const QOS_CLASS_UTILITY: isize = 0x11;
+
+    #[repr(C)]
+    struct DispatchSourceType {
+        _private: [u8; 0],
+    }
+
+    extern "C" {
+        /// `DISPATCH_SOURCE_TYPE_MEMORYPRESSURE` points at this.
+        static _dispatch_source_type_memorypressure: DispatchSourceType;
+
+        fn dispatch_get_global_queue(identifier: isize, flags: usize) -> *mut c_void;
+        fn dispatch_source_create(
+            source_type: *const DispatchSourceType,
+            handle: usize,
+            mask: usize,
+            queue: *mut c_void,
+        ) -> *mut c_void;
+        fn dispatch_set_context(object: *mut c_void, context: *mut c_void);
+        fn dispatch_source_set_event_handler_f(
+            source: *mut c_void,
+            handler: extern "C" fn(*mut c_void),
+        );
+        fn dispatch_source_get_data(source: *mut c_void) -> usize;
+        fn dispatch_resume(object: *mut c_void);
+    }
+
+    /// What the source's handler is called with. Lives as long as the
+    /// browser.
+    struct Registration {
+        source: *mut c_void,
+        handler: MemoryPressureHandler,
+    }
+
+    extern "C" fn on_event(context: *mut c_void) {
+        let registration = unsafe { &*(context as *const Registration) };
+        let data = unsafe { dispatch_source_get_data(registration.source) };
+
+        let level = if data & DISPATCH_MEMORYPRESSURE_CRITICAL != 0 {
+            MemoryPressureLevel::Critical
+        } else if data & DISPATCH_MEMORYPRESSURE_WARN != 0 {
+            MemoryPressureLevel::Warning
+        } else {
+            MemoryPressureLevel::Normal
+        };
+        registration.handler.on_pressure(level);
+    }
+
+    pub fn watch(handler: MemoryPressureHandler) {
+        unsafe {
+            let queue = dispatch_get_global_queue(QOS_CLASS_UTILITY, 0);
+            let source = dispatch_source_create(
+                &_dispatch_source_type_memorypressure,
+                0,
+                DISPATCH_MEMORYPRESSURE_NORMAL
+                    | DISPATCH_MEMORYPRESSURE_WARN
+                    | DISPATCH_MEMORYPRESSURE_CRITICAL,
+                queue,
+            );
+            if source.is_null() {
+                eprintln!("Failed to listen for memory pressure");
+                return;
+            }
+
+            let registration = Box::new(Registration { source, handler });
+            dispatch_set_context(source, Box::into_raw(registration) as *mut c_void);
+            dispatch_source_set_event_handler_f(source, on_event);
+            dispatch_resume(source);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::MemoryPressureHandler;
+
+    pub fn watch(_handler: MemoryPressureHandler) {}
+}
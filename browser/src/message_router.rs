@@ -0,0 +1,231 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use browser_common::{APP_SCHEME, CANCEL_MESSAGE, QUERY_MESSAGE};
+use cef_ui::{Frame, ProcessMessage};
+use gpui::{App as GpuiApp, Global, PathPromptOptions};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use crate::{
+    autofill, autoplay, background, capture, console, extension_storage, lifecycle, media,
+    notifications, pip, speech, speech_recognition, theme::ActiveTheme, translate, websocket,
+};
+
+/// Error code for a command nobody handles.
+const ERROR_UNKNOWN_COMMAND: i32 = -1;
+
+/// Error code for a command that couldn't complete.
+pub const ERROR_FAILED: i32 = -2;
+
+/// Error code for a command the page isn't allowed to send.
+const ERROR_NOT_ALLOWED: i32 = -3;
+
+/// Commands reaching past the page, only for the browser's own pages and
+/// extensions. The rest back web APIs the injected scripts define for every
+/// page, and check who's asking themselves where it matters.
+const TRUSTED_COMMANDS: &[&str] = &[
+    "background",
+    "getTheme",
+    "openFile",
+    "storage.get",
+    "storage.set",
+    "storage.remove",
+    "storage.clear",
+];
+
+/// A request given as a JSON object, naming its command in `cmd`.
+#[derive(Deserialize)]
+struct JsonRequest {
+    cmd: String,
+}
+
+/// A `window.cefQuery` call waiting for an answer.
+pub struct Query {
+    id: i32,
+    request: String,
+    frame: Frame,
+}
+
+impl Query {
+    /// The frame the query came from.
+    pub fn frame(&self) -> Frame {
+        self.frame.clone()
+    }
+
+    /// Whether this is query `id` of `frame`. Ids are only unique per frame.
+    fn is(&self, frame: &Frame, id: i32) -> bool {
+        self.id == id && self.frame.get_identifier().ok() == frame.get_identifier().ok()
+    }
+
+    /// Call the page's `onSuccess` with `response`.
+    pub fn success(self, response: &str) {
+        self.resolve(true, response, 0);
+    }
+
+    /// Call the page's `onFailure` with `error_code` and `error_message`.
+    pub fn failure(self, error_code: i32, error_message: &str) {
+        self.resolve(false, error_message, error_code);
+    }
+
+    fn resolve(self, success: bool, response: &str, error_code: i32) {
+        let response = serde_json::to_string(response).unwrap_or_default();
+        let script = format!(
+            "window.__cefQueryResolve && window.__cefQueryResolve({}, {}, {}, {});",
+            self.id, success, response, error_code
+        );
+
+        if let Err(e) = self.frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error answering cefQuery: {}", e);
+        }
+    }
+}
+
+/// Queries sent by pages through `window.cefQuery`, queued by the client as
+/// their process messages arrive for the UI to answer.
+#[derive(Clone, Default)]
+pub struct MessageRouter(Arc<Mutex<VecDeque<Query>>>);
+
+impl Global for MessageRouter {}
+
+impl MessageRouter {
+    /// Pick up a query or cancellation the binding sent from `frame`. Returns
+    /// true if `message` was one.
+    pub fn on_process_message(&self, frame: &Frame, message: &ProcessMessage) -> bool {
+        let name = message.get_name().unwrap_or_default();
+        if name != QUERY_MESSAGE && name != CANCEL_MESSAGE {
+            return false;
+        }
+
+        // The binding is only defined in main frames.
+        if !frame.is_main().unwrap_or(false) {
+            return true;
+        }
+
+        let Ok(Some(arguments)) = message.get_argument_list() else {
+            return true;
+        };
+        let Ok(id) = arguments.get_int(0) else {
+            return true;
+        };
+
+        let mut queries = self.0.lock().unwrap();
+        match name == QUERY_MESSAGE {
+            true => match arguments.get_string(1) {
+                Ok(Some(request)) => queries.push_back(Query {
+                    id,
+                    request,
+                    frame: frame.clone(),
+                }),
+                _ => eprintln!("Error reading cefQuery request"),
+            },
+            false => queries.retain(|query| !query.is(frame, id)),
+        }
+
+        true
+    }
+
+    pub fn take_pending(&self) -> Option<Query> {
+        self.0.lock().unwrap().pop_front()
+    }
+}
+
+/// Run the command named by a query. Requests are a command name, optionally
+/// followed by `:` and an argument, e.g. `"openFile:multiple"`, or a JSON
+/// object naming its command in `cmd`, which is passed whole as the argument.
+pub fn handle(cx: &mut GpuiApp, query: Query) {
+    let (command, argument) = match serde_json::from_str::<JsonRequest>(&query.request) {
+        Ok(request) => (request.cmd, query.request.clone()),
+        Err(_) => match query.request.split_once(':') {
+            Some((command, argument)) => (command.to_string(), argument.to_string()),
+            None => (query.request.clone(), String::new()),
+        },
+    };
+
+    if TRUSTED_COMMANDS.contains(&command.as_str()) && !is_trusted(&query.frame) {
+        query.failure(
+            ERROR_NOT_ALLOWED,
+            &format!("Not allowed to send {}", command),
+        );
+        return;
+    }
+
+    match command.as_str() {
+        "autofill" => autofill::on_query(cx, query, &argument),
+        "autofillDismiss" => autofill::on_dismiss(cx, query),
+        "autoplayBlocked" => autoplay::on_query(cx, query),
+        "background" => background::on_query(cx, query, &argument),
+        "captureDevice" => capture::on_query(cx, query, &argument),
+        "console.result" => console::on_query(cx, query, &argument),
+        "getTheme" => get_theme(cx, query),
+        "lifecycle.domReady" => lifecycle::on_query(cx, query),
+        "media" => media::on_query(cx, query, &argument),
+        "notification" => notifications::on_query(cx, query, &argument),
+        "notificationClose" => notifications::on_close(cx, query, &argument),
+        "pip" => pip::on_query(cx, query, &argument),
+        "translate.chunk" => translate::on_query(cx, query, &argument),
+        "websocket" => websocket::on_query(cx, query, &argument),
+        "speech.speak" | "speech.cancel" | "speech.pause" | "speech.resume" => {
+            speech::on_query(cx, query, &command, &argument)
+        }
+        "recognition.start" | "recognition.stop" | "recognition.abort" => {
+            speech_recognition::on_query(cx, query, &command, &argument)
+        }
+        "storage.get" | "storage.set" | "storage.remove" | "storage.clear" => {
+            extension_storage::on_query(cx, query, &command, &argument)
+        }
+        "openFile" => open_file(cx, query, argument == "multiple"),
+        _ => query.failure(
+            ERROR_UNKNOWN_COMMAND,
+            &format!("Unknown command: {}", command),
+        ),
+    }
+}
+
+/// Whether `frame` shows one of the browser's own `app://` pages or an
+/// extension's.
+fn is_trusted(frame: &Frame) -> bool {
+    frame
+        .get_url()
+        .ok()
+        .and_then(|url| Url::parse(&url).ok())
+        .is_some_and(|url| matches!(url.scheme(), APP_SCHEME | "chrome-extension"))
+}
+
+/// Answer with the theme the browser is drawn in, e.g. `{"appearance":"dark"}`.
+fn get_theme(cx: &GpuiApp, query: Query) {
+    let appearance = match cx.theme().dark {
+        true => "dark",
+        false => "light",
+    };
+
+    query.success(&json!({ "appearance": appearance }).to_string());
+}
+
+/// Ask the user to pick files and answer with their paths.
+fn open_file(cx: &mut GpuiApp, query: Query, multiple: bool) {
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple,
+        prompt: None,
+    });
+
+    cx.spawn(async move |_| match paths.await {
+        Ok(Ok(paths)) => {
+            let paths = paths
+                .unwrap_or_default()
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+
+            query.success(&json!(paths).to_string());
+        }
+        Ok(Err(e)) => query.failure(ERROR_FAILED, &e.to_string()),
+        Err(_) => query.failure(ERROR_FAILED, "File picker was closed"),
+    })
+    .detach();
+}
@@ -0,0 +1,184 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use gpui::{App as GpuiApp, Image, ImageFormat};
+use image::{DynamicImage, RgbaImage};
+
+use crate::{view::PageView, BrowserState};
+
+/// Runs the browser without CEF, painting a stand-in page instead, for
+/// working on the UI. `--mock-cef=solid` paints a solid color instead of the
+/// test pattern.
+const FLAG: &str = "--mock-cef";
+
+/// How often the stand-in page paints, for 60 frames a second.
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Colors of the test pattern's bars, as in SMPTE color bars.
+const BARS: [[u8; 3]; 7] = [
+    [0xc0, 0xc0, 0xc0],
+    [0xc0, 0xc0, 0x00],
+    [0x00, 0xc0, 0xc0],
+    [0x00, 0xc0, 0x00],
+    [0xc0, 0x00, 0xc0],
+    [0xc0, 0x00, 0x00],
+    [0x00, 0x00, 0xc0],
+];
+
+/// What the stand-in page paints.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MockPattern {
+    /// One color, changing with each page loaded.
+    Solid,
+    /// Color bars with a band sweeping across them, so each frame differs.
+    TestPattern,
+}
+
+/// The mock mode asked for on the command line, if any.
+pub fn requested() -> Option<MockPattern> {
+    std::env::args().find_map(|arg| match arg.strip_prefix(FLAG)? {
+        "" => Some(MockPattern::TestPattern),
+        "=solid" => Some(MockPattern::Solid),
+        _ => None,
+    })
+}
+
+struct MockBrowserInner {
+    url: String,
+    back: Vec<String>,
+    forward: Vec<String>,
+    pattern: MockPattern,
+    /// Pages loaded so far, counting reloads, which picks the solid color.
+    loads: u32,
+    frame: u32,
+}
+
+/// Stand-in for the CEF browser in mock mode. Navigating only changes the
+/// URL it reports, and each frame is painted from the pattern.
+#[derive(Clone)]
+pub struct MockBrowser(Arc<Mutex<MockBrowserInner>>);
+
+impl MockBrowser {
+    pub fn new(url: &str, pattern: MockPattern) -> Self {
+        Self(Arc::new(Mutex::new(MockBrowserInner {
+            url: url.to_string(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            pattern,
+            loads: 0,
+            frame: 0,
+        })))
+    }
+
+    pub fn url(&self) -> String {
+        self.0.lock().unwrap().url.clone()
+    }
+
+    pub fn load_url(&self, url: &str) {
+        let mut inner = self.0.lock().unwrap();
+        let previous = std::mem::replace(&mut inner.url, url.to_string());
+        inner.back.push(previous);
+        inner.forward.clear();
+        inner.loads += 1;
+    }
+
+    /// Go back a page, if there's one to go back to. Returns whether it did.
+    pub fn go_back(&self) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        let Some(url) = inner.back.pop() else {
+            return false;
+        };
+
+        let current = std::mem::replace(&mut inner.url, url);
+        inner.forward.push(current);
+        inner.loads += 1;
+        true
+    }
+
+    /// Go forward a page, if there's one to go forward to. Returns whether
+    /// it did.
+    pub fn go_forward(&self) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        let Some(url) = inner.forward.pop() else {
+            return false;
+        };
+
+        let current = std::mem::replace(&mut inner.url, url);
+        inner.back.push(current);
+        inner.loads += 1;
+        true
+    }
+
+    pub fn reload(&self) {
+        self.0.lock().unwrap().loads += 1;
+    }
+
+    /// The next frame, in CEF's BGRA layout.
+    fn paint(&self, width: usize, height: usize) -> Vec<u8> {
+        let mut inner = self.0.lock().unwrap();
+        inner.frame = inner.frame.wrapping_add(1);
+
+        let mut buffer = vec![0xff; width * height * 4];
+        match inner.pattern {
+            MockPattern::Solid => {
+                let [r, g, b] = BARS[inner.loads as usize % BARS.len()];
+                for pixel in buffer.chunks_exact_mut(4) {
+                    pixel[..3].copy_from_slice(&[b, g, r]);
+                }
+            }
+            MockPattern::TestPattern => {
+                let band = inner.frame as usize * 4 % width.max(1);
+                for (index, pixel) in buffer.chunks_exact_mut(4).enumerate() {
+                    let x = index % width;
+                    let [r, g, b] = match x.abs_diff(band) < 8 {
+                        true => [0xff, 0xff, 0xff],
+                        false => BARS[x * BARS.len() / width],
+                    };
+                    pixel[..3].copy_from_slice(&[b, g, r]);
+                }
+            }
+        }
+
+        buffer
+    }
+}
+
+/// Paint the next frame of the mock browser, as CEF would through the render
+/// handler, at the page area's size.
+pub fn paint(cx: &mut GpuiApp) {
+    let Some(mock) = cx.global::<BrowserState>().mock.clone() else {
+        return;
+    };
+
+    let view = cx.global::<PageView>();
+    let rect = view.rect();
+    let scale_factor = view.scale_factor();
+    let width = (rect.width as f32 * scale_factor) as usize;
+    let height = (rect.height as f32 * scale_factor) as usize;
+
+    let buffer = mock.paint(width, height);
+    on_paint(cx, buffer, width, height);
+}
+
+/// Show a frame painted by the mock browser as the page.
+fn on_paint(cx: &mut GpuiApp, mut buffer: Vec<u8>, width: usize, height: usize) {
+    for pixel in buffer.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    let Some(pixels) = RgbaImage::from_raw(width as u32, height as u32, buffer) else {
+        return;
+    };
+
+    let mut bmp = Cursor::new(Vec::new());
+    if let Err(e) = DynamicImage::ImageRgba8(pixels).write_to(&mut bmp, image::ImageFormat::Bmp) {
+        eprintln!("Error encoding mock frame: {}", e);
+        return;
+    }
+
+    cx.global_mut::<BrowserState>().image =
+        Some(Image::from_bytes(ImageFormat::Bmp, bmp.into_inner()));
+}
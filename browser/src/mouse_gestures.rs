@@ -0,0 +1,375 @@
+use std::f32::consts::PI;
+
+use gpui::{
+    anchored, deferred, div, point, prelude::*, px, radians, svg, App as GpuiApp, Global,
+    NavigationDirection, Pixels, Point, SharedString, Transformation, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button::button,
+    gestures,
+    locale::t,
+    settings::{self, Settings},
+    theme::ActiveTheme,
+    CloseTab, NewTab,
+};
+
+/// Distances offered for a stroke to count as a gesture, in pixels.
+const THRESHOLDS: [u32; 3] = [30, 50, 80];
+
+/// Size of the arrow shown under the pointer during a gesture.
+const ARROW_SIZE: f32 = 40.;
+
+/// What a gesture does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GestureAction {
+    Nothing,
+    Back,
+    Forward,
+    NewTab,
+    CloseTab,
+}
+
+impl GestureAction {
+    const ALL: [GestureAction; 5] = [
+        GestureAction::Nothing,
+        GestureAction::Back,
+        GestureAction::Forward,
+        GestureAction::NewTab,
+        GestureAction::CloseTab,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            GestureAction::Nothing => t!("mouse_gestures.actions.nothing"),
+            GestureAction::Back => t!("mouse_gestures.actions.back"),
+            GestureAction::Forward => t!("mouse_gestures.actions.forward"),
+            GestureAction::NewTab => t!("mouse_gestures.actions.new_tab"),
+            GestureAction::CloseTab => t!("mouse_gestures.actions.close_tab"),
+        }
+    }
+
+    fn run(&self, window: &mut Window, cx: &mut GpuiApp) {
+        match self {
+            GestureAction::Nothing => {}
+            GestureAction::Back => {
+                gestures::navigate(cx, NavigationDirection::Back);
+            }
+            GestureAction::Forward => {
+                gestures::navigate(cx, NavigationDirection::Forward);
+            }
+            GestureAction::NewTab => window.dispatch_action(Box::new(NewTab), cx),
+            GestureAction::CloseTab => window.dispatch_action(Box::new(CloseTab), cx),
+        }
+    }
+}
+
+/// Which way a stroke went.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            Direction::Left => t!("mouse_gestures.left"),
+            Direction::Right => t!("mouse_gestures.right"),
+            Direction::Up => t!("mouse_gestures.up"),
+            Direction::Down => t!("mouse_gestures.down"),
+        }
+    }
+
+    /// Turn of the right-pointing arrow to point this way.
+    fn angle(&self) -> f32 {
+        match self {
+            Direction::Right => 0.,
+            Direction::Down => PI / 2.,
+            Direction::Left => PI,
+            Direction::Up => -PI / 2.,
+        }
+    }
+}
+
+/// Mouse gesture preferences: what each stroke with the right button held
+/// does, and how far it has to go.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseGestureSettings {
+    pub enabled: bool,
+    /// Pixels a stroke has to cover to count.
+    pub threshold: u32,
+    pub left: GestureAction,
+    pub right: GestureAction,
+    pub up: GestureAction,
+    pub down: GestureAction,
+}
+
+impl MouseGestureSettings {
+    fn action(&self, direction: Direction) -> GestureAction {
+        match direction {
+            Direction::Left => self.left,
+            Direction::Right => self.right,
+            Direction::Up => self.up,
+            Direction::Down => self.down,
+        }
+    }
+
+    fn action_mut(&mut self, direction: Direction) -> &mut GestureAction {
+        match direction {
+            Direction::Left => &mut self.left,
+            Direction::Right => &mut self.right,
+            Direction::Up => &mut self.up,
+            Direction::Down => &mut self.down,
+        }
+    }
+}
+
+impl Default for MouseGestureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 50,
+            left: GestureAction::Back,
+            right: GestureAction::Forward,
+            up: GestureAction::CloseTab,
+            down: GestureAction::NewTab,
+        }
+    }
+}
+
+/// A stroke in progress, in window coordinates.
+struct Stroke {
+    start: Point<Pixels>,
+    current: Point<Pixels>,
+}
+
+impl Stroke {
+    /// The way the stroke went, once it's gone further than `threshold`
+    /// along its main axis.
+    fn direction(&self, threshold: u32) -> Option<Direction> {
+        let delta = self.current - self.start;
+        let (x, y) = (f32::from(delta.x), f32::from(delta.y));
+
+        if x.abs().max(y.abs()) <= threshold as f32 {
+            return None;
+        }
+
+        Some(match x.abs() >= y.abs() {
+            true if x > 0. => Direction::Right,
+            true => Direction::Left,
+            false if y > 0. => Direction::Down,
+            false => Direction::Up,
+        })
+    }
+}
+
+/// Recognizes strokes drawn over the page with the right button held.
+#[derive(Default)]
+pub struct MouseGestureRecognizer(Option<Stroke>);
+
+impl Global for MouseGestureRecognizer {}
+
+/// Start tracking a stroke where the right button went down. Returns whether
+/// gestures are on; when they're off the page gets the right click at once.
+pub fn mouse_down(cx: &mut GpuiApp, position: Point<Pixels>) -> bool {
+    if !cx.global::<Settings>().mouse_gestures.enabled {
+        return false;
+    }
+
+    cx.global_mut::<MouseGestureRecognizer>().0 = Some(Stroke {
+        start: position,
+        current: position,
+    });
+    true
+}
+
+/// Follow the pointer while a stroke is in progress. Returns whether one is,
+/// for the arrow to be redrawn.
+pub fn mouse_move(cx: &mut GpuiApp, position: Point<Pixels>) -> bool {
+    match cx.global_mut::<MouseGestureRecognizer>().0.as_mut() {
+        Some(stroke) => {
+            stroke.current = position;
+            true
+        }
+        None => false,
+    }
+}
+
+/// End the stroke, running its gesture if it went far enough. Otherwise
+/// returns where it started, for the page to get the right click there.
+pub fn mouse_up(window: &mut Window, cx: &mut GpuiApp) -> Option<Point<Pixels>> {
+    let stroke = cx.global_mut::<MouseGestureRecognizer>().0.take()?;
+    let settings = cx.global::<Settings>().mouse_gestures.clone();
+
+    match stroke.direction(settings.threshold) {
+        Some(direction) => {
+            settings.action(direction).run(window, cx);
+            None
+        }
+        None => Some(stroke.start),
+    }
+}
+
+/// The arrow following the pointer once a stroke counts as a gesture,
+/// pointing the way it went and naming what letting go will do.
+pub fn arrow(cx: &GpuiApp) -> Option<impl IntoElement> {
+    let stroke = cx.global::<MouseGestureRecognizer>().0.as_ref()?;
+    let settings = &cx.global::<Settings>().mouse_gestures;
+    let direction = stroke.direction(settings.threshold)?;
+    let action = settings.action(direction);
+    let theme = cx.theme();
+
+    Some(deferred(
+        anchored()
+            .position(stroke.current - point(px(ARROW_SIZE / 2.), px(ARROW_SIZE / 2.)))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_1()
+                    .opacity(0.7)
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .size(px(ARROW_SIZE))
+                            .rounded_full()
+                            .bg(theme.surface)
+                            .child(
+                                svg()
+                                    .path("forward.svg")
+                                    .size(px(ARROW_SIZE / 2.))
+                                    .text_color(match action {
+                                        GestureAction::Nothing => theme.text_muted,
+                                        _ => theme.accent,
+                                    })
+                                    .with_transformation(Transformation::rotate(radians(
+                                        direction.angle(),
+                                    ))),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .px_1p5()
+                            .rounded_md()
+                            .bg(theme.surface)
+                            .text_xs()
+                            .text_color(theme.text)
+                            .child(action.label()),
+                    ),
+            ),
+    ))
+}
+
+/// The mouse gesture preferences, for the settings panel.
+pub fn settings(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+    let current = cx.global::<Settings>().mouse_gestures.clone();
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .child(
+            div().flex().gap_2().children(
+                [
+                    (true, t!("mouse_gestures.on")),
+                    (false, t!("mouse_gestures.off")),
+                ]
+                .map(|(enabled, label)| {
+                    button(
+                        ("mouse-gestures-enabled", enabled as usize),
+                        label,
+                        enabled == current.enabled,
+                        cx,
+                    )
+                    .on_click(move |_, window, cx| {
+                        settings::update(cx, |settings| settings.mouse_gestures.enabled = enabled);
+                        window.refresh();
+                    })
+                }),
+            ),
+        )
+        .when(current.enabled, |this| {
+            this.child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w(px(64.))
+                            .text_color(theme.text_muted)
+                            .child(t!("mouse_gestures.distance")),
+                    )
+                    .children(THRESHOLDS.map(|threshold| {
+                        button(
+                            ("mouse-gestures-threshold", threshold as usize),
+                            format!("{} px", threshold),
+                            threshold == current.threshold,
+                            cx,
+                        )
+                        .on_click(move |_, window, cx| {
+                            settings::update(cx, |settings| {
+                                settings.mouse_gestures.threshold = threshold
+                            });
+                            window.refresh();
+                        })
+                    })),
+            )
+            .children(
+                Direction::ALL
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row, direction)| {
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .w(px(64.))
+                                    .text_color(theme.text_muted)
+                                    .child(direction.label()),
+                            )
+                            .children(GestureAction::ALL.into_iter().enumerate().map(
+                                |(column, action)| {
+                                    button(
+                                        (
+                                            "mouse-gestures-action",
+                                            row * GestureAction::ALL.len() + column,
+                                        ),
+                                        action.label(),
+                                        action == current.action(direction),
+                                        cx,
+                                    )
+                                    .on_click(
+                                        move |_, window, cx| {
+                                            settings::update(cx, |settings| {
+                                                *settings.mouse_gestures.action_mut(direction) =
+                                                    action
+                                            });
+                                            window.refresh();
+                                        },
+                                    )
+                                },
+                            ))
+                    }),
+            )
+        })
+}
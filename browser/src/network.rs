@@ -0,0 +1,643 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::{Request, Response, UrlRequestStatus};
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, Div, Entity, Global, Hsla, SharedString,
+    Stateful, Window,
+};
+
+use crate::{
+    button::button,
+    locale::t,
+    theme::{ActiveTheme, Theme},
+    throttling::ThrottlingSelector,
+    websocket::{WebSocketConnection, WebSocketLog},
+};
+
+/// Oldest requests are dropped beyond this many.
+const MAX_ENTRIES: usize = 1000;
+
+/// Height of the network panel.
+const PANEL_HEIGHT: f32 = 260.;
+
+/// Width of the headers pane shown for the selected request.
+const DETAIL_WIDTH: f32 = 360.;
+
+/// A request made by the page, filled in as its response arrives.
+#[derive(Clone)]
+pub struct NetworkEntry {
+    id: u64,
+    method: String,
+    url: String,
+    status: Option<i32>,
+    size: Option<i64>,
+    started: Instant,
+    duration: Option<Duration>,
+    failed: bool,
+    /// Whether the request fetches audio or video.
+    media: bool,
+    request_headers: Vec<(String, String)>,
+    response_headers: Vec<(String, String)>,
+}
+
+impl NetworkEntry {
+    fn color(&self, theme: &Theme) -> Hsla {
+        match self.status {
+            _ if self.failed => theme.error,
+            None => theme.text_muted,
+            Some(200..=299) => theme.text,
+            Some(300..=399) => theme.info,
+            Some(400..=499) => theme.warning,
+            Some(_) => theme.error,
+        }
+    }
+
+    fn status_label(&self) -> String {
+        match self.status {
+            Some(status) if status > 0 => status.to_string(),
+            _ if self.failed => t!("network.failed").to_string(),
+            _ => t!("network.pending").to_string(),
+        }
+    }
+
+    fn size_label(&self) -> String {
+        match self.size {
+            Some(size) if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / 1048576.),
+            Some(size) if size >= 1024 => format!("{:.1} kB", size as f64 / 1024.),
+            Some(size) => format!("{} B", size),
+            None => String::new(),
+        }
+    }
+
+    fn time_label(&self) -> String {
+        self.duration
+            .map(|duration| format!("{} ms", duration.as_millis()))
+            .unwrap_or_default()
+    }
+}
+
+/// Flatten a header map into name/value pairs sorted by name.
+fn headers(map: HashMap<String, Vec<String>>) -> Vec<(String, String)> {
+    let mut headers = map
+        .into_iter()
+        .flat_map(|(name, values)| values.into_iter().map(move |value| (name.clone(), value)))
+        .collect::<Vec<_>>();
+
+    headers.sort();
+    headers
+}
+
+#[derive(Default)]
+struct NetworkLogInner {
+    entries: VecDeque<NetworkEntry>,
+    changed: bool,
+}
+
+/// Requests recorded by the resource request handler.
+#[derive(Clone, Default)]
+pub struct NetworkLog(Arc<Mutex<NetworkLogInner>>);
+
+impl Global for NetworkLog {}
+
+impl NetworkLog {
+    /// Record a request about to be sent.
+    pub fn on_request(&self, request: &Request) {
+        let Ok(id) = request.get_identifier() else {
+            return;
+        };
+
+        let entry = NetworkEntry {
+            id,
+            method: request.get_method().unwrap_or_default(),
+            url: request.get_url().unwrap_or_default(),
+            status: None,
+            size: None,
+            started: Instant::now(),
+            duration: None,
+            failed: false,
+            media: false,
+            request_headers: headers(request.get_header_map().unwrap_or_default()),
+            response_headers: Vec::new(),
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.entries.len() == MAX_ENTRIES {
+            inner.entries.pop_front();
+        }
+
+        inner.entries.push_back(entry);
+        inner.changed = true;
+    }
+
+    /// Record the status and headers of a request's response.
+    pub fn on_response(&self, request: &Request, response: &Response) {
+        self.update(request, |entry| {
+            entry.status = response.get_status().ok();
+            entry.response_headers = headers(response.get_header_map().unwrap_or_default());
+        });
+    }
+
+    /// Record how a request ended and how much it received.
+    pub fn on_complete(&self, request: &Request, status: UrlRequestStatus, size: i64) {
+        self.update(request, |entry| {
+            entry.size = Some(size);
+            entry.duration = Some(entry.started.elapsed());
+            entry.failed = status != UrlRequestStatus::Success;
+        });
+    }
+
+    /// Mark a recorded request as fetching audio or video.
+    pub fn mark_media(&self, request: &Request) {
+        self.update(request, |entry| entry.media = true);
+    }
+
+    fn update(&self, request: &Request, f: impl FnOnce(&mut NetworkEntry)) {
+        let Ok(id) = request.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if let Some(entry) = inner.entries.iter_mut().rev().find(|entry| entry.id == id) {
+            f(entry);
+            inner.changed = true;
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.entries.clear();
+        inner.changed = true;
+    }
+
+    fn entries(&self) -> Vec<NetworkEntry> {
+        self.0.lock().unwrap().entries.iter().cloned().collect()
+    }
+
+    /// Whether requests were added, updated or cleared since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+}
+
+/// A column the requests can be sorted by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Url,
+    Status,
+    Size,
+}
+
+/// Collapsible panel below the page listing the page's requests.
+pub struct NetworkPanel {
+    /// Column and whether it's ascending; requests are in the order they were
+    /// made otherwise.
+    sort: Option<(SortColumn, bool)>,
+    selected: Option<u64>,
+    /// Id of the WebSocket whose messages are shown instead of a request.
+    selected_socket: Option<String>,
+    throttling: Entity<ThrottlingSelector>,
+}
+
+impl NetworkPanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            sort: None,
+            selected: None,
+            selected_socket: None,
+            throttling: cx.new(ThrottlingSelector::new),
+        }
+    }
+
+    /// Sort by `column`, flipping the order if it's already sorted by it.
+    fn sort_by(&mut self, column: SortColumn, cx: &mut Context<Self>) {
+        self.sort = match self.sort {
+            Some((sorted, ascending)) if sorted == column => Some((column, !ascending)),
+            _ => Some((column, true)),
+        };
+        cx.notify();
+    }
+
+    fn header(
+        &self,
+        column: SortColumn,
+        label: SharedString,
+        cx: &mut Context<Self>,
+    ) -> Stateful<Div> {
+        let theme = cx.theme();
+
+        let arrow = match self.sort {
+            Some((sorted, true)) if sorted == column => " ▲",
+            Some((sorted, false)) if sorted == column => " ▼",
+            _ => "",
+        };
+
+        div()
+            .id(label.clone())
+            .cursor_pointer()
+            .hover(|this| this.text_color(theme.text))
+            .on_click(cx.listener(move |this, _, _, cx| this.sort_by(column, cx)))
+            .child(format!("{}{}", label, arrow))
+    }
+
+    fn render_detail(&self, entry: &NetworkEntry, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let section = |title: SharedString, headers: &[(String, String)]| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .child(div().text_color(theme.text_secondary).child(title))
+                .children(headers.iter().map(|(name, value)| {
+                    div()
+                        .flex()
+                        .gap_2()
+                        .child(
+                            div()
+                                .flex_none()
+                                .text_color(theme.text_muted)
+                                .child(format!("{}:", name)),
+                        )
+                        .child(div().flex_1().min_w(px(0.)).child(value.clone()))
+                }))
+        };
+
+        div()
+            .id("network-detail")
+            .flex()
+            .flex_col()
+            .flex_none()
+            .gap_3()
+            .w(px(DETAIL_WIDTH))
+            .p_2()
+            .border_l_1()
+            .border_color(theme.border)
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .flex()
+                    .items_start()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .text_color(theme.text)
+                            .child(format!("{} {}", entry.method, entry.url)),
+                    )
+                    .child(
+                        button("network-detail-close", t!("network.close"), false, cx).on_click(
+                            cx.listener(|this, _, _, cx| {
+                                this.selected = None;
+                                cx.notify();
+                            }),
+                        ),
+                    ),
+            )
+            .child(section(
+                t!("network.request_headers"),
+                &entry.request_headers,
+            ))
+            .child(section(
+                t!("network.response_headers"),
+                &entry.response_headers,
+            ))
+    }
+}
+
+impl NetworkPanel {
+    fn render_socket_detail(
+        &self,
+        connection: &WebSocketConnection,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .id("network-socket-detail")
+            .flex()
+            .flex_col()
+            .flex_none()
+            .gap_2()
+            .w(px(DETAIL_WIDTH))
+            .p_2()
+            .border_l_1()
+            .border_color(theme.border)
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .flex()
+                    .items_start()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .text_color(theme.text)
+                            .child(connection.url.clone()),
+                    )
+                    .child(
+                        button("network-socket-close", t!("network.close"), false, cx).on_click(
+                            cx.listener(|this, _, _, cx| {
+                                this.selected_socket = None;
+                                cx.notify();
+                            }),
+                        ),
+                    ),
+            )
+            .when(connection.messages.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_color(theme.text_disabled)
+                        .child(t!("network.no_messages")),
+                )
+            })
+            .children(connection.messages.iter().map(|message| {
+                div()
+                    .flex()
+                    .gap_2()
+                    .font_family("Menlo")
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(if message.outgoing {
+                                theme.info
+                            } else {
+                                theme.success
+                            })
+                            .child(if message.outgoing { "↑" } else { "↓" }),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .text_color(theme.text)
+                            .child(message.payload.clone()),
+                    )
+            }))
+    }
+}
+
+/// A WebSocket connection as a row of the request list, its message count in
+/// the size column.
+fn socket_row(connection: &WebSocketConnection, selected: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    let (status, color) = match connection.closed {
+        Some(code) => (t!("network.closed", code = code), theme.text_muted),
+        None if connection.open => (t!("network.open"), theme.success),
+        None => (t!("network.pending"), theme.text_muted),
+    };
+
+    div()
+        .id(SharedString::from(format!(
+            "network-socket-{}",
+            connection.id
+        )))
+        .flex()
+        .gap_2()
+        .px_2()
+        .py(px(2.))
+        .border_b_1()
+        .border_color(theme.border_subtle)
+        .text_color(color)
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.element_hover))
+        .when(selected, |this| this.bg(theme.element_selected))
+        .child(div().flex_none().w(px(56.)).child("WS"))
+        .child(
+            div()
+                .flex_1()
+                .min_w(px(0.))
+                .overflow_hidden()
+                .text_ellipsis()
+                .whitespace_nowrap()
+                .child(connection.url.clone()),
+        )
+        .child(div().flex_none().w(px(56.)).child(status))
+        .child(
+            div().flex_none().w(px(72.)).child(
+                div()
+                    .px_1()
+                    .rounded_sm()
+                    .bg(theme.element_selected)
+                    .child(t!("network.messages", count = connection.messages.len())),
+            ),
+        )
+        .child(div().flex_none().w(px(72.)))
+}
+
+impl Render for NetworkPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let log = cx.global::<NetworkLog>().clone();
+        let mut entries = log.entries();
+
+        if let Some((column, ascending)) = self.sort {
+            entries.sort_by(|a, b| {
+                let order = match column {
+                    SortColumn::Url => a.url.cmp(&b.url),
+                    SortColumn::Status => a.status.cmp(&b.status),
+                    SortColumn::Size => a.size.cmp(&b.size),
+                };
+
+                if ascending {
+                    order
+                } else {
+                    order.reverse()
+                }
+            });
+        }
+
+        let sockets = cx.global::<WebSocketLog>().clone();
+        let connections = sockets.connections();
+        let open_sockets = sockets.open_count();
+        let selected_socket = self.selected_socket.as_ref().and_then(|id| {
+            connections
+                .iter()
+                .find(|connection| connection.id == *id)
+                .cloned()
+        });
+        let socket_rows = connections
+            .iter()
+            .map(|connection| {
+                let id = connection.id.clone();
+
+                socket_row(connection, self.selected_socket.as_ref() == Some(&id), cx).on_click(
+                    cx.listener(move |this, _, _, cx| {
+                        this.selected = None;
+                        this.selected_socket = Some(id.clone());
+                        cx.notify();
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let selected = self
+            .selected
+            .and_then(|id| entries.iter().find(|entry| entry.id == id).cloned());
+        let count = t!("network.requests", count = entries.len());
+
+        let rows = entries
+            .iter()
+            .map(|entry| {
+                let id = entry.id;
+
+                div()
+                    .id(("network-entry", id as usize))
+                    .flex()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.))
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .text_color(entry.color(theme))
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.element_hover))
+                    .when(self.selected == Some(id), |this| {
+                        this.bg(theme.element_selected)
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.selected = Some(id);
+                        this.selected_socket = None;
+                        cx.notify();
+                    }))
+                    .child(div().flex_none().w(px(56.)).child(entry.method.clone()))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .whitespace_nowrap()
+                            .child(entry.url.clone()),
+                    )
+                    .when(entry.media, |this| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .px_1()
+                                .rounded_sm()
+                                .bg(theme.media.opacity(0.15))
+                                .text_color(theme.media)
+                                .child(t!("network.media")),
+                        )
+                    })
+                    .child(div().flex_none().w(px(56.)).child(entry.status_label()))
+                    .child(div().flex_none().w(px(72.)).child(entry.size_label()))
+                    .child(div().flex_none().w(px(72.)).child(entry.time_label()))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("network.title"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(theme.text_placeholder)
+                            .child(count),
+                    )
+                    .when(open_sockets > 0, |this| {
+                        this.child(
+                            div()
+                                .px_1p5()
+                                .rounded_full()
+                                .bg(theme.success.opacity(0.35))
+                                .text_color(theme.text)
+                                .child(match open_sockets {
+                                    1 => t!("network.open_websockets_one"),
+                                    count => t!("network.open_websockets", count = count),
+                                }),
+                        )
+                    })
+                    .child(self.throttling.clone())
+                    .child(
+                        button("network-clear", t!("network.clear"), false, cx).on_click(
+                            move |_, _, _| {
+                                log.clear();
+                                sockets.clear();
+                            },
+                        ),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .child(
+                                div()
+                                    .flex()
+                                    .gap_2()
+                                    .px_2()
+                                    .py_1()
+                                    .border_b_1()
+                                    .border_color(theme.border)
+                                    .text_color(theme.text_muted)
+                                    .child(div().flex_none().w(px(56.)).child(t!("network.method")))
+                                    .child(div().flex_1().min_w(px(0.)).child(self.header(
+                                        SortColumn::Url,
+                                        "URL".into(),
+                                        cx,
+                                    )))
+                                    .child(div().flex_none().w(px(56.)).child(self.header(
+                                        SortColumn::Status,
+                                        t!("network.status"),
+                                        cx,
+                                    )))
+                                    .child(div().flex_none().w(px(72.)).child(self.header(
+                                        SortColumn::Size,
+                                        t!("network.size"),
+                                        cx,
+                                    )))
+                                    .child(div().flex_none().w(px(72.)).child(t!("network.time"))),
+                            )
+                            .child(
+                                div()
+                                    .id("network-entries")
+                                    .flex_1()
+                                    .overflow_y_scroll()
+                                    .font_family("Menlo")
+                                    .children(socket_rows)
+                                    .children(rows),
+                            ),
+                    )
+                    .when_some(selected, |this, entry| {
+                        this.child(self.render_detail(&entry, cx))
+                    })
+                    .when_some(selected_socket, |this, connection| {
+                        this.child(self.render_socket_detail(&connection, cx))
+                    }),
+            )
+    }
+}
@@ -0,0 +1,596 @@
+use anyhow::Result;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::Frame;
+use chrono::{Local, TimeZone};
+use gpui::{
+    div, prelude::*, px, rgb, svg, App as GpuiApp, Context, DismissEvent, EventEmitter, Global,
+    Window,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    permissions::{frame_origin, PermissionsStore},
+    tabs,
+    tabs::TabManager,
+    theme::ActiveTheme,
+};
+
+/// How long a notification stays on screen.
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+
+/// Notifications kept in the notification center.
+const HISTORY_LIMIT: usize = 100;
+
+/// Replaces `window.Notification` with one shown by the browser. Permission
+/// still goes through the page's own `Notification.requestPermission`, which
+/// CEF turns into a permission prompt. Relies on `window.cefQuery`.
+pub const NOTIFICATION_SCRIPT: &str = r#"(() => {
+  if (window.__cefNotification) return;
+
+  const Native = window.Notification;
+  const shown = new Map();
+
+  class Notification extends EventTarget {
+    #id = 0;
+
+    constructor(title, options = {}) {
+      super();
+      this.title = String(title);
+      this.body = options.body ? String(options.body) : "";
+      this.data = options.data ?? null;
+      this.onclick = this.onshow = this.onclose = this.onerror = null;
+
+      window.cefQuery({
+        request: "notification:" + JSON.stringify({ title: this.title, body: this.body }),
+        onSuccess: (id) => {
+          this.#id = Number(id);
+          shown.set(this.#id, (type) => this.#dispatch(type));
+          this.#dispatch("show");
+        },
+        onFailure: () => this.#dispatch("error"),
+      });
+    }
+
+    static get permission() {
+      return Native ? Native.permission : "denied";
+    }
+
+    static requestPermission(callback) {
+      if (!Native) return Promise.resolve("denied");
+      return Native.requestPermission(callback);
+    }
+
+    close() {
+      if (!shown.has(this.#id)) return;
+      window.cefQuery({ request: "notificationClose:" + this.#id });
+      window.__cefNotification(this.#id, "close");
+    }
+
+    #dispatch(type) {
+      const event = new Event(type);
+      this.dispatchEvent(event);
+      if (typeof this["on" + type] === "function") this["on" + type](event);
+    }
+  }
+
+  window.Notification = Notification;
+
+  Object.defineProperty(window, "__cefNotification", {
+    value: (id, type) => {
+      const dispatch = shown.get(id);
+      if (!dispatch) return;
+      if (type === "close") shown.delete(id);
+      dispatch(type);
+    },
+  });
+})();"#;
+
+/// A notification as the notification center lists it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: u64,
+    pub origin: String,
+    pub title: String,
+    pub body: String,
+    /// When it was shown, in seconds since the Unix epoch.
+    pub time: i64,
+}
+
+impl Notification {
+    /// The origin without its scheme, e.g. `example.com`.
+    fn site(&self) -> String {
+        Url::parse(&self.origin)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_else(|| self.origin.clone())
+    }
+
+    fn time_label(&self) -> String {
+        Local
+            .timestamp_opt(self.time, 0)
+            .single()
+            .map(|time| time.format("%b %-d, %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// What a page sends to show a notification.
+#[derive(Deserialize)]
+struct NotificationRequest {
+    title: String,
+    #[serde(default)]
+    body: String,
+}
+
+struct NotificationsInner {
+    path: PathBuf,
+    history: Vec<Notification>,
+    /// The frames that showed notifications, for click and close events.
+    frames: HashMap<u64, Frame>,
+    shown: VecDeque<u64>,
+    closed: Vec<u64>,
+    unread: usize,
+}
+
+/// Notifications shown by pages, persisted to a JSON file. Shared between the
+/// message router and the UI.
+#[derive(Clone)]
+pub struct Notifications(Arc<Mutex<NotificationsInner>>);
+
+impl Global for Notifications {}
+
+impl Notifications {
+    /// Load the history from disk, starting empty if the file is missing or
+    /// invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let history = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse notifications {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self(Arc::new(Mutex::new(NotificationsInner {
+            path,
+            history,
+            frames: HashMap::new(),
+            shown: VecDeque::new(),
+            closed: Vec::new(),
+            unread: 0,
+        })))
+    }
+
+    /// Newest first.
+    pub fn history(&self) -> Vec<Notification> {
+        self.0
+            .lock()
+            .unwrap()
+            .history
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<Notification> {
+        let inner = self.0.lock().unwrap();
+        inner.history.iter().find(|n| n.id == id).cloned()
+    }
+
+    /// Notifications shown since the notification center was last opened.
+    pub fn unread(&self) -> usize {
+        self.0.lock().unwrap().unread
+    }
+
+    pub fn mark_read(&self) {
+        self.0.lock().unwrap().unread = 0;
+    }
+
+    pub fn take_shown(&self) -> Option<u64> {
+        self.0.lock().unwrap().shown.pop_front()
+    }
+
+    /// Notifications the page closed itself since the last call.
+    pub fn take_closed(&self) -> Vec<u64> {
+        std::mem::take(&mut self.0.lock().unwrap().closed)
+    }
+
+    fn add(&self, origin: String, request: NotificationRequest, frame: Frame) -> u64 {
+        let mut inner = self.0.lock().unwrap();
+        // Cleared notifications may still have frames waiting for events.
+        let id = inner
+            .history
+            .iter()
+            .map(|n| n.id)
+            .chain(inner.frames.keys().copied())
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        inner.history.push(Notification {
+            id,
+            origin,
+            title: request.title,
+            body: request.body,
+            time: Local::now().timestamp(),
+        });
+
+        let excess = inner.history.len().saturating_sub(HISTORY_LIMIT);
+        inner.history.drain(..excess);
+        inner.frames.insert(id, frame);
+        inner.shown.push_back(id);
+        inner.unread += 1;
+        inner.changed();
+
+        id
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.history.clear();
+        inner.unread = 0;
+        inner.changed();
+    }
+
+    /// Fire `type` on the page's `Notification` object, if its page is still
+    /// open.
+    fn dispatch(&self, id: u64, event: &str) -> Option<Frame> {
+        let frame = self.0.lock().unwrap().frames.get(&id).cloned()?;
+        let script = format!(
+            "window.__cefNotification && window.__cefNotification({}, {:?});",
+            id, event
+        );
+
+        if let Err(e) = frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error sending notification event: {}", e);
+        }
+
+        Some(frame)
+    }
+}
+
+impl NotificationsInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.history)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    fn changed(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("Failed to save notifications {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Show a notification for a page allowed to send them.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    let request = match serde_json::from_str::<NotificationRequest>(argument) {
+        Ok(request) => request,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid notification: {}", e));
+            return;
+        }
+    };
+
+    let frame = query.frame();
+    let Some(origin) = frame_origin(&frame) else {
+        query.failure(ERROR_FAILED, "Notifications need a page origin");
+        return;
+    };
+
+    if !cx
+        .global::<PermissionsStore>()
+        .is_allowed(&origin, "notifications")
+    {
+        query.failure(ERROR_FAILED, "Notifications are not allowed");
+        return;
+    }
+
+    let id = cx.global::<Notifications>().add(origin, request, frame);
+    query.success(&id.to_string());
+}
+
+/// The page closed a notification it showed.
+pub fn on_close(cx: &GpuiApp, query: Query, argument: &str) {
+    if let Ok(id) = argument.parse::<u64>() {
+        cx.global::<Notifications>()
+            .0
+            .lock()
+            .unwrap()
+            .closed
+            .push(id);
+    }
+
+    query.success("");
+}
+
+/// Switch to the tab that showed a notification and tell the page it was
+/// clicked.
+fn open(cx: &mut GpuiApp, id: u64) {
+    let Some(frame) = cx.global::<Notifications>().dispatch(id, "click") else {
+        return;
+    };
+
+    let Ok(id) = frame
+        .get_browser()
+        .and_then(|browser| browser.get_identifier())
+    else {
+        return;
+    };
+
+    let index = cx.global::<TabManager>().tabs().iter().position(|tab| {
+        tab.browser
+            .get_identifier()
+            .is_ok_and(|identifier| identifier == id)
+    });
+
+    if let Some(index) = index {
+        tabs::activate(cx, index);
+    }
+}
+
+/// A notification card with the app icon, where it came from, its title and
+/// its body.
+fn card(notification: &Notification, cx: &GpuiApp) -> gpui::Div {
+    let theme = cx.theme();
+
+    div()
+        .flex()
+        .items_start()
+        .gap_2()
+        .child(
+            div()
+                .flex()
+                .flex_none()
+                .items_center()
+                .justify_center()
+                .size(px(24.))
+                .rounded_md()
+                .bg(rgb(0x000000))
+                .child(
+                    svg()
+                        .path("vercel.svg")
+                        .size(px(12.))
+                        .text_color(theme.text),
+                ),
+        )
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_1()
+                .min_w(px(0.))
+                .gap_0p5()
+                .child(
+                    div()
+                        .flex()
+                        .gap_2()
+                        .text_color(theme.text_placeholder)
+                        .child(notification.site())
+                        .child(notification.time_label()),
+                )
+                .child(
+                    div()
+                        .font_weight(gpui::FontWeight::SEMIBOLD)
+                        .child(notification.title.clone()),
+                )
+                .when(!notification.body.is_empty(), |this| {
+                    this.child(
+                        div()
+                            .text_color(theme.text_muted)
+                            .child(notification.body.clone()),
+                    )
+                }),
+        )
+}
+
+/// Notifications on their way through the top right of the page, newest at
+/// the top.
+pub struct NotificationToasts {
+    visible: Vec<u64>,
+}
+
+impl NotificationToasts {
+    pub fn new() -> Self {
+        Self {
+            visible: Vec::new(),
+        }
+    }
+
+    /// Show a notification for `NOTIFICATION_DURATION`.
+    pub fn push(&mut self, id: u64, cx: &mut Context<Self>) {
+        self.visible.insert(0, id);
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(NOTIFICATION_DURATION).await;
+            this.update(cx, |this, cx| this.remove(id, cx)).ok();
+        })
+        .detach();
+    }
+
+    pub fn remove(&mut self, id: u64, cx: &mut Context<Self>) {
+        self.visible.retain(|visible| *visible != id);
+        cx.notify();
+    }
+}
+
+impl Render for NotificationToasts {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let notifications = cx.global::<Notifications>().clone();
+
+        div()
+            .absolute()
+            .top_3()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .w(px(320.))
+            .children(self.visible.iter().filter_map(|id| {
+                let notification = notifications.get(*id)?;
+                let id = notification.id;
+
+                Some(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_2()
+                        .p_3()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(theme.border)
+                        .bg(theme.surface)
+                        .shadow_lg()
+                        .text_xs()
+                        .text_color(theme.text)
+                        .child(card(&notification, cx))
+                        .child(
+                            div()
+                                .flex()
+                                .justify_end()
+                                .gap_2()
+                                .child(
+                                    button(
+                                        ("notification-close", id as usize),
+                                        t!("notifications.close"),
+                                        false,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |this, _, _, cx| {
+                                            cx.global::<Notifications>().dispatch(id, "close");
+                                            this.remove(id, cx);
+                                        },
+                                    )),
+                                )
+                                .child(
+                                    button(
+                                        ("notification-open", id as usize),
+                                        t!("notifications.open"),
+                                        true,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |this, _, _, cx| {
+                                            open(cx, id);
+                                            this.remove(id, cx);
+                                        },
+                                    )),
+                                ),
+                        ),
+                )
+            }))
+    }
+}
+
+/// Panel listing the notifications pages have shown.
+pub struct NotificationCenter;
+
+impl NotificationCenter {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.global::<Notifications>().mark_read();
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for NotificationCenter {}
+
+impl Render for NotificationCenter {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let history = cx.global::<Notifications>().history();
+
+        let rows = history
+            .iter()
+            .map(|notification| {
+                let id = notification.id;
+
+                card(notification, cx)
+                    .id(("notification", id as usize))
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |_, _, _, cx| {
+                        open(cx, id);
+                        cx.emit(DismissEvent);
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .top_0()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(360.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(div().text_sm().child(t!("notifications.title")))
+            .child(
+                div()
+                    .id("notifications-list")
+                    .flex()
+                    .flex_col()
+                    .max_h(px(360.))
+                    .overflow_y_scroll()
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .py_2()
+                                .text_color(theme.text_disabled)
+                                .child(t!("notifications.empty")),
+                        )
+                    })
+                    .children(rows),
+            )
+            .child(
+                div()
+                    .flex()
+                    .justify_end()
+                    .gap_2()
+                    .child(
+                        button(
+                            "notifications-clear",
+                            t!("notifications.clear_all"),
+                            false,
+                            cx,
+                        )
+                        .on_click(cx.listener(|_, _, _, cx| {
+                            cx.global::<Notifications>().clear();
+                            cx.notify();
+                        })),
+                    )
+                    .child(
+                        button("notifications-done", t!("notifications.done"), true, cx)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+    }
+}
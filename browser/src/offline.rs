@@ -0,0 +1,372 @@
+use anyhow::Result;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    Browser, Callback, ErrorCode, Request, ResourceHandler, ResourceHandlerCallbacks,
+    ResourceReadCallback, ResourceSkipCallback, Response, ResponseFilter, ResponseFilterCallbacks,
+    ResponseFilterStatus,
+};
+use gpui::{div, prelude::*, px, App as GpuiApp, Div, Global, Stateful};
+use serde::{Deserialize, Serialize};
+
+use crate::{incognito, locale::t, theme::ActiveTheme};
+
+/// Responses larger than this aren't kept, so video and big downloads don't
+/// crowd out pages.
+const MAX_ENTRY_BYTES: usize = 8 * 1024 * 1024;
+
+/// Most bytes kept in all; the responses cached longest ago go first.
+const MAX_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// File listing the cached URLs, oldest first, next to the bodies.
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Serialize, Deserialize)]
+struct IndexEntry {
+    url: String,
+    mime_type: String,
+    /// Name of the file holding the body.
+    file: String,
+}
+
+#[derive(Default)]
+struct OfflineCacheInner {
+    dir: PathBuf,
+    /// URL to body and MIME type.
+    entries: HashMap<String, (Vec<u8>, String)>,
+    /// Cached URLs, oldest first.
+    order: Vec<String>,
+    size: usize,
+    offline: bool,
+}
+
+/// Responses seen while browsing online, served back while the browser is
+/// offline. Stored as one file per body in a directory of the cache.
+#[derive(Clone, Default)]
+pub struct OfflineCache(Arc<Mutex<OfflineCacheInner>>);
+
+impl Global for OfflineCache {}
+
+impl OfflineCache {
+    /// Load the cache from `dir`, starting empty if there's none.
+    pub fn load(dir: PathBuf) -> Self {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("Failed to create offline cache {:?}: {}", dir, e);
+        }
+
+        let index: Vec<IndexEntry> = match fs::read_to_string(dir.join(INDEX_FILE)) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse offline cache index: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        let mut inner = OfflineCacheInner {
+            dir,
+            ..Default::default()
+        };
+        for entry in index {
+            // Bodies that went missing are dropped from the index.
+            if let Ok(body) = fs::read(inner.dir.join(&entry.file)) {
+                inner.size += body.len();
+                inner.order.push(entry.url.clone());
+                inner.entries.insert(entry.url, (body, entry.mime_type));
+            }
+        }
+
+        Self(Arc::new(Mutex::new(inner)))
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.0.lock().unwrap().offline
+    }
+
+    pub fn toggle_offline(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.offline = !inner.offline;
+    }
+
+    /// While offline, serve `request` from the cache, failing it as if the
+    /// network were down if it isn't there. Online, `None` lets it load.
+    pub fn resource_handler(&self, request: &Request) -> Option<ResourceHandler> {
+        let inner = self.0.lock().unwrap();
+        if !inner.offline {
+            return None;
+        }
+
+        let url = request.get_url().ok()?;
+        if !is_cacheable_url(&url) {
+            return None;
+        }
+
+        let cached = inner.entries.get(&url).cloned();
+        Some(ResourceHandler::new(CachedResourceHandler {
+            cached,
+            offset: 0,
+        }))
+    }
+
+    /// While online, keep a copy of successful GET responses to web pages, other
+    /// than ones incognito tabs load or ones asking not to be stored.
+    pub fn response_filter(
+        &self,
+        browser: Option<&Browser>,
+        request: &Request,
+        response: &Response,
+    ) -> Option<ResponseFilter> {
+        if self.is_offline() || browser.is_some_and(incognito::is_incognito) {
+            return None;
+        }
+
+        let url = request.get_url().ok()?;
+        let cacheable = is_cacheable_url(&url)
+            && request.get_method().ok()? == "GET"
+            && response.get_status().ok()? == 200
+            && !response
+                .get_header_by_name("Cache-Control")
+                .unwrap_or_default()
+                .contains("no-store");
+        if !cacheable {
+            return None;
+        }
+
+        Some(ResponseFilter::new(CachingFilter {
+            cache: self.clone(),
+            url,
+            mime_type: response.get_mime_type().unwrap_or_default(),
+            body: Vec::new(),
+            too_large: false,
+        }))
+    }
+
+    fn insert(&self, url: String, body: Vec<u8>, mime_type: String) {
+        let mut inner = self.0.lock().unwrap();
+
+        if let Some((old, _)) = inner.entries.remove(&url) {
+            inner.size -= old.len();
+            inner.order.retain(|cached| *cached != url);
+        }
+
+        let path = inner.dir.join(file_name(&url));
+        if let Err(e) = fs::write(&path, &body) {
+            eprintln!("Failed to save {:?} to the offline cache: {}", url, e);
+            return;
+        }
+
+        inner.size += body.len();
+        inner.order.push(url.clone());
+        inner.entries.insert(url, (body, mime_type));
+
+        while inner.size > MAX_CACHE_BYTES && !inner.order.is_empty() {
+            let oldest = inner.order.remove(0);
+            if let Some((body, _)) = inner.entries.remove(&oldest) {
+                inner.size -= body.len();
+                fs::remove_file(inner.dir.join(file_name(&oldest))).ok();
+            }
+        }
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save offline cache index: {}", e);
+        }
+    }
+}
+
+impl OfflineCacheInner {
+    fn save(&self) -> Result<()> {
+        let index = self
+            .order
+            .iter()
+            .filter_map(|url| {
+                let (_, mime_type) = self.entries.get(url)?;
+
+                Some(IndexEntry {
+                    url: url.clone(),
+                    mime_type: mime_type.clone(),
+                    file: file_name(url),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let json = serde_json::to_string_pretty(&index)?;
+        fs::write(self.dir.join(INDEX_FILE), json)?;
+        Ok(())
+    }
+}
+
+fn is_cacheable_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Name of the file a URL's body is kept in.
+fn file_name(url: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+/// Passes a response through unchanged, keeping a copy for the cache once it
+/// has all arrived.
+struct CachingFilter {
+    cache: OfflineCache,
+    url: String,
+    mime_type: String,
+    body: Vec<u8>,
+    too_large: bool,
+}
+
+impl ResponseFilterCallbacks for CachingFilter {
+    fn init_filter(&mut self) -> bool {
+        true
+    }
+
+    fn filter(
+        &mut self,
+        data_in: Option<&[u8]>,
+        data_in_read: &mut usize,
+        data_out: &mut [u8],
+        data_out_written: &mut usize,
+    ) -> ResponseFilterStatus {
+        let Some(data_in) = data_in else {
+            *data_in_read = 0;
+            *data_out_written = 0;
+
+            if !self.too_large {
+                self.cache.insert(
+                    std::mem::take(&mut self.url),
+                    std::mem::take(&mut self.body),
+                    std::mem::take(&mut self.mime_type),
+                );
+            }
+
+            return ResponseFilterStatus::Done;
+        };
+
+        let count = data_in.len().min(data_out.len());
+        data_out[..count].copy_from_slice(&data_in[..count]);
+        *data_in_read = count;
+        *data_out_written = count;
+
+        if !self.too_large {
+            self.body.extend_from_slice(&data_in[..count]);
+            if self.body.len() > MAX_ENTRY_BYTES {
+                self.too_large = true;
+                self.body = Vec::new();
+            }
+        }
+
+        ResponseFilterStatus::NeedMoreData
+    }
+}
+
+/// Serves a cached response, or fails like a request made without a network.
+struct CachedResourceHandler {
+    cached: Option<(Vec<u8>, String)>,
+    offset: usize,
+}
+
+impl ResourceHandlerCallbacks for CachedResourceHandler {
+    fn open(&mut self, _request: Request, handle_request: &mut bool, _callback: Callback) -> bool {
+        *handle_request = true;
+        true
+    }
+
+    fn get_response_headers(
+        &mut self,
+        response: Response,
+        response_length: &mut i64,
+        _redirect_url: &mut Option<String>,
+    ) {
+        let result = match &self.cached {
+            Some((body, mime_type)) => {
+                *response_length = body.len() as i64;
+
+                response
+                    .set_status(200)
+                    .and_then(|_| response.set_mime_type(mime_type))
+            }
+            None => {
+                *response_length = 0;
+
+                response.set_error(ErrorCode::InternetDisconnected)
+            }
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error setting response headers: {}", e);
+        }
+    }
+
+    fn skip(
+        &mut self,
+        bytes_to_skip: i64,
+        bytes_skipped: &mut i64,
+        _callback: ResourceSkipCallback,
+    ) -> bool {
+        let length = self.cached.as_ref().map_or(0, |(body, _)| body.len());
+        let skipped = (bytes_to_skip.max(0) as usize).min(length - self.offset);
+
+        self.offset += skipped;
+        *bytes_skipped = skipped as i64;
+
+        true
+    }
+
+    fn read(
+        &mut self,
+        data_out: &mut [u8],
+        bytes_read: &mut i32,
+        _callback: ResourceReadCallback,
+    ) -> bool {
+        let Some((body, _)) = &self.cached else {
+            *bytes_read = 0;
+            return false;
+        };
+
+        let remaining = &body[self.offset..];
+        let count = remaining.len().min(data_out.len());
+
+        data_out[..count].copy_from_slice(&remaining[..count]);
+        self.offset += count;
+        *bytes_read = count as i32;
+
+        count > 0
+    }
+
+    fn cancel(&mut self) {
+        self.cached = None;
+        self.offset = 0;
+    }
+}
+
+/// The t!("offline.offline") toggle in the toolbar, highlighted while pages are served
+/// from the cache.
+pub fn toggle(offline: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("offline")
+        .flex()
+        .items_center()
+        .h(px(18.))
+        .px_1p5()
+        .rounded_md()
+        .cursor_pointer()
+        .text_xs()
+        .when(offline, |this| {
+            this.bg(theme.warning.opacity(0.2))
+                .text_color(theme.warning)
+        })
+        .when(!offline, |this| {
+            this.text_color(theme.text_muted)
+                .hover(|this| this.bg(theme.ghost_hover))
+        })
+        .child(t!("offline.offline"))
+}
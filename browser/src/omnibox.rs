@@ -0,0 +1,496 @@
+use std::ops::Range;
+
+use anyhow::anyhow;
+use cef_ui::Browser;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use gpui::{
+    deferred, div, prelude::*, px, App as GpuiApp, ClipboardItem, Context, DismissEvent,
+    EventEmitter, FocusHandle, Focusable, FontWeight, HighlightStyle, KeyDownEvent, SharedString,
+    StyledText, Subscription, Window,
+};
+
+use crate::{
+    bookmarks::Bookmarks, history::History, locale::t, navigate, settings::Settings,
+    theme::ActiveTheme,
+};
+
+/// Most history and bookmark matches shown below the field.
+const MAX_MATCHES: usize = 8;
+
+/// What picking a suggestion does.
+#[derive(Clone, Copy, PartialEq)]
+enum SuggestionKind {
+    /// Go to what was typed, as a URL.
+    Navigate,
+    /// Search for what was typed with the configured search engine.
+    Search,
+    History,
+    Bookmark,
+}
+
+/// A row of the omnibox dropdown.
+struct Suggestion {
+    kind: SuggestionKind,
+    title: String,
+    url: String,
+    /// Characters of `title` and `url` matching what was typed.
+    title_matches: Vec<usize>,
+    url_matches: Vec<usize>,
+    score: i64,
+}
+
+/// The address field in the toolbar. It shows the page's URL, and while
+/// focused, matches what's typed against the history and bookmarks.
+pub struct Omnibox {
+    focus_handle: FocusHandle,
+    /// What's typed, or the page's URL while the field isn't focused.
+    text: String,
+    page_url: String,
+    /// Whether the whole text is selected, so typing replaces it.
+    select_all: bool,
+    open: bool,
+    suggestions: Vec<Suggestion>,
+    /// The highlighted row, none until something is typed or picked with
+    /// the arrow keys.
+    selected: Option<usize>,
+    /// The browser it navigates, or the active tab's if `None`.
+    browser: Option<Browser>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Omnibox {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let subscriptions = vec![
+            cx.on_focus(&focus_handle, window, |this, _, cx| {
+                this.text = this.page_url.clone();
+                this.select_all = true;
+                this.open = true;
+                this.suggest(cx);
+            }),
+            cx.on_blur(&focus_handle, window, |this, _, cx| this.close(cx)),
+        ];
+
+        Self {
+            focus_handle,
+            text: String::new(),
+            page_url: String::new(),
+            select_all: false,
+            open: false,
+            suggestions: Vec::new(),
+            selected: None,
+            browser: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// Navigate `browser` instead of the active tab, like split view's right
+    /// pane does.
+    pub fn with_browser(mut self, browser: Browser) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// Show the URL of the page now in the active tab, unless the user is
+    /// typing.
+    pub fn set_page_url(&mut self, url: String, cx: &mut Context<Self>) {
+        if url == self.page_url {
+            return;
+        }
+
+        if !self.open {
+            self.text = url.clone();
+        }
+        self.page_url = url;
+        cx.notify();
+    }
+
+    fn close(&mut self, cx: &mut Context<Self>) {
+        self.open = false;
+        self.select_all = false;
+        self.text = self.page_url.clone();
+        self.suggestions.clear();
+        cx.notify();
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+
+        match keystroke.key.as_str() {
+            "up" => self.selected = self.selected.map(|index| index.saturating_sub(1)),
+            "down" => {
+                let last = self.suggestions.len().saturating_sub(1);
+                self.selected = Some(self.selected.map_or(0, |index| (index + 1).min(last)));
+            }
+            "enter" => self.go(self.selected, window, cx),
+            "escape" => {
+                window.blur();
+                cx.emit(DismissEvent);
+            }
+            "backspace" => {
+                match self.select_all {
+                    true => self.text.clear(),
+                    false => {
+                        self.text.pop();
+                    }
+                }
+                self.select_all = false;
+                self.suggest(cx);
+            }
+            "v" if keystroke.modifiers.platform => {
+                let pasted = cx.read_from_clipboard().and_then(|item| item.text());
+                if let Some(pasted) = pasted {
+                    self.type_text(&pasted.replace('\n', " "), cx);
+                }
+            }
+            "c" if keystroke.modifiers.platform => {
+                cx.write_to_clipboard(ClipboardItem::new_string(self.text.clone()));
+            }
+            "a" if keystroke.modifiers.platform => self.select_all = true,
+            _ => {
+                // Leave the browser's shortcuts alone.
+                if keystroke.modifiers.platform || keystroke.modifiers.control {
+                    return;
+                }
+
+                match &keystroke.key_char {
+                    Some(key_char) => self.type_text(key_char, cx),
+                    None => return,
+                }
+            }
+        }
+
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    fn type_text(&mut self, text: &str, cx: &mut Context<Self>) {
+        if std::mem::take(&mut self.select_all) {
+            self.text.clear();
+        }
+
+        self.text.push_str(text);
+        self.suggest(cx);
+    }
+
+    /// Fill the dropdown for what's typed: where Enter goes, then the history
+    /// and bookmarks matching it best. Until something is typed, the most
+    /// visited pages are shown instead.
+    fn suggest(&mut self, cx: &mut Context<Self>) {
+        self.suggestions.clear();
+
+        let query = self.text.trim();
+        if self.select_all || query.is_empty() {
+            self.selected = None;
+            let mut entries = cx.global::<History>().entries();
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.visits));
+
+            self.suggestions
+                .extend(
+                    entries
+                        .into_iter()
+                        .take(MAX_MATCHES)
+                        .map(|entry| Suggestion {
+                            kind: SuggestionKind::History,
+                            title: entry.title,
+                            url: entry.url,
+                            title_matches: Vec::new(),
+                            url_matches: Vec::new(),
+                            score: 0,
+                        }),
+                );
+            return;
+        }
+
+        self.selected = Some(0);
+        let search_url = cx.global::<Settings>().search_url(query);
+
+        if let Some(url) = typed_url(query) {
+            self.suggestions
+                .push(suggestion(SuggestionKind::Navigate, query.to_string(), url));
+        }
+        self.suggestions.push(suggestion(
+            SuggestionKind::Search,
+            t!("omnibox.search_for", query = query).to_string(),
+            search_url,
+        ));
+
+        let matcher = SkimMatcherV2::default();
+        let bookmarks = cx
+            .global::<Bookmarks>()
+            .iter()
+            .map(|bookmark| {
+                (
+                    SuggestionKind::Bookmark,
+                    bookmark.title.clone(),
+                    bookmark.url.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let history = cx
+            .global::<History>()
+            .entries()
+            .into_iter()
+            .map(|entry| (SuggestionKind::History, entry.title, entry.url));
+
+        let mut matches: Vec<Suggestion> = Vec::new();
+        for (kind, title, url) in bookmarks.into_iter().chain(history) {
+            // Bookmarks come first, so a bookmarked page isn't listed twice.
+            if matches.iter().any(|other| other.url == url) {
+                continue;
+            }
+
+            let title_match = matcher.fuzzy_indices(&title, query);
+            let url_match = matcher.fuzzy_indices(&url, query);
+            let score = match (&title_match, &url_match) {
+                (None, None) => continue,
+                _ => title_match
+                    .as_ref()
+                    .map(|(score, _)| *score)
+                    .max(url_match.as_ref().map(|(score, _)| *score))
+                    .unwrap_or_default(),
+            };
+
+            matches.push(Suggestion {
+                kind,
+                title,
+                url,
+                title_matches: title_match.map(|(_, indices)| indices).unwrap_or_default(),
+                url_matches: url_match.map(|(_, indices)| indices).unwrap_or_default(),
+                score,
+            });
+        }
+
+        // Stable, so equal scores keep bookmarks and recent visits first.
+        matches.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.score));
+        self.suggestions
+            .extend(matches.into_iter().take(MAX_MATCHES));
+    }
+
+    /// Load the suggestion at `index`, if any, and leave the field.
+    fn go(&mut self, index: Option<usize>, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(suggestion) = index.and_then(|index| self.suggestions.get(index)) {
+            let url = suggestion.url.clone();
+            match self.browser.as_ref() {
+                Some(browser) => {
+                    if let Err(e) = browser
+                        .get_main_frame()
+                        .and_then(|frame| frame.ok_or_else(|| anyhow!("No main frame")))
+                        .and_then(|frame| frame.load_url(&url))
+                    {
+                        eprintln!("Error loading {}: {}", url, e);
+                    }
+                }
+                None => navigate(cx, &url),
+            }
+            self.page_url = url;
+        }
+
+        window.blur();
+        cx.emit(DismissEvent);
+    }
+
+    fn render_suggestion(
+        &self,
+        index: usize,
+        suggestion: &Suggestion,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let icon = match suggestion.kind {
+            SuggestionKind::Navigate => "→",
+            SuggestionKind::Search => "⌕",
+            SuggestionKind::History => "◷",
+            SuggestionKind::Bookmark => "★",
+        };
+        let show_url = matches!(
+            suggestion.kind,
+            SuggestionKind::History | SuggestionKind::Bookmark
+        );
+
+        div()
+            .id(("omnibox-suggestion", index))
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .when(self.selected == Some(index), |this| {
+                this.bg(theme.element_selected)
+            })
+            .hover(|this| this.bg(theme.element_hover))
+            .on_click(cx.listener(move |this, _, window, cx| this.go(Some(index), window, cx)))
+            .child(
+                div()
+                    .flex_none()
+                    .w_4()
+                    .text_color(theme.text_placeholder)
+                    .child(icon),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .text_ellipsis()
+                    .child(highlighted(
+                        &suggestion.title,
+                        &suggestion.title_matches,
+                        cx,
+                    )),
+            )
+            .when(show_url, |this| {
+                this.child(
+                    div()
+                        .flex_shrink()
+                        .max_w(px(160.))
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .text_ellipsis()
+                        .text_color(theme.text_placeholder)
+                        .child(highlighted(&suggestion.url, &suggestion.url_matches, cx)),
+                )
+            })
+    }
+}
+
+fn suggestion(kind: SuggestionKind, title: String, url: String) -> Suggestion {
+    Suggestion {
+        kind,
+        title,
+        url,
+        title_matches: Vec::new(),
+        url_matches: Vec::new(),
+        score: 0,
+    }
+}
+
+/// The URL `query` names, if it looks like one rather than a search.
+fn typed_url(query: &str) -> Option<String> {
+    if query.contains(char::is_whitespace) {
+        return None;
+    }
+
+    if url::Url::parse(query).is_ok_and(|url| url.has_host() || url.scheme() == "about") {
+        return Some(query.to_string());
+    }
+
+    let host = query.split('/').next().unwrap_or_default();
+    let looks_like_host = host.contains('.') && !host.starts_with('.') && !host.ends_with('.');
+    (looks_like_host || host.starts_with("localhost")).then(|| format!("https://{}", query))
+}
+
+/// `text` with the characters at `matches` in bold white.
+pub fn highlighted(text: &str, matches: &[usize], cx: &GpuiApp) -> StyledText {
+    let theme = cx.theme();
+
+    let highlights = text
+        .char_indices()
+        .enumerate()
+        .filter(|(index, _)| matches.contains(index))
+        .map(|(_, (start, c))| {
+            let range: Range<usize> = start..start + c.len_utf8();
+            (
+                range,
+                HighlightStyle {
+                    color: Some(theme.text_emphasis),
+                    font_weight: Some(FontWeight::BOLD),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    StyledText::new(SharedString::from(text.to_string())).with_highlights(highlights)
+}
+
+impl EventEmitter<DismissEvent> for Omnibox {}
+
+impl Focusable for Omnibox {
+    fn focus_handle(&self, _: &GpuiApp) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Omnibox {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.focus_handle.is_focused(window);
+        let suggestions = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(index, suggestion)| {
+                self.render_suggestion(index, suggestion, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+        let theme = cx.theme();
+        let (content, color) = match self.text.is_empty() {
+            true => (
+                t!("omnibox.placeholder").to_string(),
+                theme.text_placeholder,
+            ),
+            false => (self.text.clone(), theme.text),
+        };
+
+        div()
+            .relative()
+            .flex_none()
+            .w(px(280.))
+            .child(
+                div()
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(Self::on_key_down))
+                    .flex()
+                    .items_center()
+                    .h_8()
+                    .px_2()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(if focused {
+                        theme.text_placeholder
+                    } else {
+                        theme.border
+                    })
+                    .bg(theme.background)
+                    .text_xs()
+                    .text_color(color)
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .child(
+                        div()
+                            .when(self.select_all && focused, |this| {
+                                this.bg(theme.accent.opacity(0.4))
+                            })
+                            .child(content),
+                    ),
+            )
+            .when(self.open && !suggestions.is_empty(), |this| {
+                this.child(
+                    deferred(
+                        div()
+                            .absolute()
+                            .top(px(36.))
+                            .left_0()
+                            .flex()
+                            .flex_col()
+                            .w(px(420.))
+                            .p_1()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(theme.surface)
+                            .shadow_lg()
+                            .text_xs()
+                            .text_color(theme.text_secondary)
+                            .children(suggestions),
+                    )
+                    .with_priority(1),
+                )
+            })
+    }
+}
@@ -0,0 +1,110 @@
+use gpui::{
+    anchored, deferred, div, prelude::*, px, Action, Context, Corner, DismissEvent, EventEmitter,
+    Pixels, Point, Window,
+};
+
+use crate::{
+    locale::t, theme::ActiveTheme, NewIncognitoTab, OpenExtensions, OpenSettings, Print,
+    ReportBrokenSite, SavePage, ShowAbout, ToggleStoragePanel, ViewSource,
+};
+
+/// An entry of the menu: the key of its label and the action it runs.
+struct MenuItem {
+    label: &'static str,
+    action: fn() -> Box<dyn Action>,
+}
+
+const ITEMS: [MenuItem; 9] = [
+    MenuItem {
+        label: "overflow_menu.new_incognito_tab",
+        action: || Box::new(NewIncognitoTab),
+    },
+    MenuItem {
+        label: "overflow_menu.print",
+        action: || Box::new(Print),
+    },
+    MenuItem {
+        label: "overflow_menu.save_page",
+        action: || Box::new(SavePage),
+    },
+    MenuItem {
+        label: "overflow_menu.view_source",
+        action: || Box::new(ViewSource),
+    },
+    MenuItem {
+        label: "overflow_menu.storage",
+        action: || Box::new(ToggleStoragePanel),
+    },
+    MenuItem {
+        label: "overflow_menu.extensions",
+        action: || Box::new(OpenExtensions),
+    },
+    MenuItem {
+        label: "overflow_menu.settings",
+        action: || Box::new(OpenSettings),
+    },
+    MenuItem {
+        label: "overflow_menu.report_broken_site",
+        action: || Box::new(ReportBrokenSite),
+    },
+    MenuItem {
+        label: "overflow_menu.about",
+        action: || Box::new(ShowAbout),
+    },
+];
+
+/// Menu under the toolbar's "⋮" button, for what has no button of its own.
+pub struct OverflowMenu {
+    position: Point<Pixels>,
+}
+
+impl OverflowMenu {
+    pub fn new(position: Point<Pixels>) -> Self {
+        Self { position }
+    }
+}
+
+impl EventEmitter<DismissEvent> for OverflowMenu {}
+
+impl Render for OverflowMenu {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        deferred(
+            anchored()
+                .position(self.position)
+                .anchor(Corner::TopRight)
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .mt_1()
+                        .p_1()
+                        .min_w(px(180.))
+                        .rounded_md()
+                        .border_1()
+                        .border_color(theme.border)
+                        .bg(theme.surface)
+                        .text_xs()
+                        .text_color(theme.text)
+                        .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                        .children(ITEMS.iter().enumerate().map(|(index, item)| {
+                            let action = item.action;
+
+                            div()
+                                .id(("overflow-menu-item", index))
+                                .px_3()
+                                .py_1()
+                                .rounded_sm()
+                                .cursor_pointer()
+                                .hover(|this| this.bg(theme.element_selected))
+                                .child(t!(item.label))
+                                .on_click(cx.listener(move |_, _, window, cx| {
+                                    window.dispatch_action(action(), cx);
+                                    cx.emit(DismissEvent);
+                                }))
+                        })),
+                ),
+        )
+    }
+}
@@ -0,0 +1,198 @@
+use std::rc::Rc;
+
+use gpui::{
+    div, prelude::*, px, svg, AnyView, App as GpuiApp, Context, DismissEvent, Div, Entity,
+    EventEmitter, Global, SharedString, Stateful, Window,
+};
+
+use crate::{
+    locale::t,
+    passwords::{has_saved_password, FillPasswordPopover},
+    reader::{ReaderMode, ReaderPopover},
+    theme::ActiveTheme,
+};
+
+type BuildPopover = Rc<dyn Fn(&mut Window, &mut Context<PageActionPopover>) -> AnyView>;
+
+/// An icon shown on the right of the toolbar for the pages it applies to.
+/// Clicking it opens a popover below the toolbar.
+pub struct PageAction {
+    pub name: SharedString,
+    /// Path of the icon in the app's assets.
+    pub icon: SharedString,
+    /// Whether the action applies to the page at a URL.
+    pub condition: fn(&str) -> bool,
+    /// Whether the action is on for the current page, which tints its icon.
+    pub active: fn(&GpuiApp) -> bool,
+    build_popover: BuildPopover,
+}
+
+impl PageAction {
+    /// An action opening the popover built by `build`. The popover closes
+    /// when it emits `DismissEvent`.
+    pub fn new<V: Render + EventEmitter<DismissEvent>>(
+        name: impl Into<SharedString>,
+        icon: impl Into<SharedString>,
+        condition: fn(&str) -> bool,
+        build: impl Fn(&mut Window, &mut Context<V>) -> V + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            icon: icon.into(),
+            condition,
+            active: |_| false,
+            build_popover: Rc::new(move |window, cx| {
+                let view = cx.new(|cx| build(window, cx));
+                cx.subscribe(&view, |_, _, _: &DismissEvent, cx| cx.emit(DismissEvent))
+                    .detach();
+
+                view.into()
+            }),
+        }
+    }
+
+    pub fn active(mut self, active: fn(&GpuiApp) -> bool) -> Self {
+        self.active = active;
+        self
+    }
+}
+
+/// The registered page actions, in the order their icons are shown.
+#[derive(Default)]
+pub struct PageActions {
+    actions: Vec<PageAction>,
+}
+
+impl Global for PageActions {}
+
+impl PageActions {
+    /// Indices of the actions applying to the page at `url`.
+    pub fn matching(&self, url: &str) -> Vec<usize> {
+        (0..self.actions.len())
+            .filter(|&index| (self.actions[index].condition)(url))
+            .collect()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&PageAction> {
+        self.actions.get(index)
+    }
+}
+
+/// Add a page action after the ones already registered. Its name has to be
+/// unique; registering a name again replaces that action.
+pub fn register(cx: &mut GpuiApp, action: PageAction) {
+    let actions = &mut cx.global_mut::<PageActions>().actions;
+
+    match actions.iter_mut().find(|other| other.name == action.name) {
+        Some(other) => *other = action,
+        None => actions.push(action),
+    }
+}
+
+/// Register the page actions built into the browser.
+pub fn register_builtin(cx: &mut GpuiApp) {
+    register(
+        cx,
+        PageAction::new(
+            t!("page_actions.reader_mode"),
+            "book-open.svg",
+            is_web_page,
+            |_, cx| ReaderPopover::new(cx),
+        )
+        .active(|cx| cx.global::<ReaderMode>().is_active(cx)),
+    );
+    register(
+        cx,
+        PageAction::new(
+            t!("page_actions.fill_password"),
+            "key.svg",
+            is_web_page,
+            |_, cx| FillPasswordPopover::new(cx),
+        )
+        .active(has_saved_password),
+    );
+}
+
+fn is_web_page(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// The toolbar icon for the action at `index`.
+pub fn icon(
+    index: usize,
+    action: &PageAction,
+    active: bool,
+    open: bool,
+    cx: &GpuiApp,
+) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id(("page-action", index))
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .when(open, |this| this.bg(theme.element_selected))
+        .child(
+            svg()
+                .path(action.icon.clone())
+                .size(px(14.0))
+                .text_color(if active { theme.accent } else { theme.text }),
+        )
+}
+
+/// Floating panel below the toolbar holding a page action's popover.
+pub struct PageActionPopover {
+    /// Index of the action it belongs to.
+    pub action: usize,
+    name: SharedString,
+    content: AnyView,
+}
+
+/// Build the popover of the action at `index`, if there is one.
+pub fn open_popover(
+    index: usize,
+    window: &mut Window,
+    cx: &mut GpuiApp,
+) -> Option<Entity<PageActionPopover>> {
+    let action = cx.global::<PageActions>().get(index)?;
+    let name = action.name.clone();
+    let build = action.build_popover.clone();
+
+    Some(cx.new(|cx| PageActionPopover {
+        action: index,
+        name,
+        content: build(window, cx),
+    }))
+}
+
+impl EventEmitter<DismissEvent> for PageActionPopover {}
+
+impl Render for PageActionPopover {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .top_0()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(280.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(div().text_sm().child(self.name.clone()))
+            .child(self.content.clone())
+    }
+}
@@ -0,0 +1,785 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, Result};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Frame, Request};
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, ClipboardItem, Context, DismissEvent, EventEmitter,
+    Global, Window,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::{autofill, button::button, locale::t, theme::ActiveTheme, BrowserState};
+
+/// Keychain item holding the key the password store is encrypted with.
+#[cfg(target_os = "macos")]
+const KEYCHAIN_SERVICE: &str = "Browser Safe Storage";
+#[cfg(target_os = "macos")]
+const KEYCHAIN_ACCOUNT: &str = "passwords";
+
+/// Types saved credentials into the first password field of the page and
+/// the username field before it, the way a user would.
+const FILL_SCRIPT: &str = r#"(() => {
+  const password = document.querySelector("input[type=password]");
+  if (!password) return;
+
+  const fields = Array.from((password.form || document).querySelectorAll(
+    "input[autocomplete=username], input[type=email], input[type=text]"));
+  const username = fields.reverse().find((field) =>
+    field.compareDocumentPosition(password) & Node.DOCUMENT_POSITION_FOLLOWING);
+
+  const setValue = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, "value").set;
+  const type = (field, value) => {
+    setValue.call(field, value);
+    field.dispatchEvent(new Event("input", { bubbles: true }));
+    field.dispatchEvent(new Event("change", { bubbles: true }));
+  };
+
+  if (username) type(username, $USERNAME);
+  type(password, $PASSWORD);
+})();"#;
+
+/// Length of the random nonce stored in front of the encrypted credentials.
+const NONCE_LEN: usize = 12;
+
+/// Field names taken for the password, lowercased.
+const PASSWORD_FIELDS: [&str; 3] = ["pass", "pwd", "secret"];
+
+/// Field names taken for the username, lowercased, most likely first.
+const USERNAME_FIELDS: [&str; 5] = ["user", "email", "login", "account", "name"];
+
+/// Credentials saved for a site.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedPassword {
+    /// E.g. `https://example.com`.
+    pub origin: String,
+    pub username: String,
+    pub password: String,
+}
+
+struct PasswordManagerInner {
+    path: PathBuf,
+    /// Missing if the key couldn't be read, in which case nothing is saved.
+    cipher: Option<Aes256Gcm>,
+    /// Set if the store couldn't be decrypted or moved aside, in which case
+    /// nothing is saved over it.
+    unreadable: Option<anyhow::Error>,
+    passwords: Vec<SavedPassword>,
+    /// Credentials just submitted, waiting for the user to save them.
+    pending: Option<SavedPassword>,
+}
+
+/// Passwords submitted in sign-in forms, kept in a file encrypted with
+/// AES-256-GCM. Shared between the CEF request handler and the UI.
+#[derive(Clone)]
+pub struct PasswordManager(Arc<Mutex<PasswordManagerInner>>);
+
+impl Global for PasswordManager {}
+
+impl PasswordManager {
+    /// Load the saved passwords from `path` with the key from
+    /// `encryption_key`.
+    pub fn load(path: PathBuf) -> Self {
+        let cipher = encryption_key(&path).and_then(|key| {
+            Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("Key has the wrong length"))
+        });
+        let cipher = match cipher {
+            Ok(cipher) => Some(cipher),
+            Err(e) => {
+                eprintln!("Failed to get the password store key: {}", e);
+                None
+            }
+        };
+
+        Self::load_with(path, cipher)
+    }
+
+    /// Load the saved passwords from `path` encrypted with `cipher`, starting
+    /// empty if the file is missing. A file that can't be decrypted is moved
+    /// aside to `passwords.bad` rather than overwritten by the next save.
+    fn load_with(path: PathBuf, cipher: Option<Aes256Gcm>) -> Self {
+        let mut unreadable = None;
+        let passwords = match (&cipher, fs::read(&path)) {
+            (Some(cipher), Ok(bytes)) => decrypt(cipher, &bytes).unwrap_or_else(|e| {
+                eprintln!("Failed to read passwords {:?}: {}", path, e);
+                if let Err(e) = fs::rename(&path, path.with_extension("bad")) {
+                    unreadable = Some(anyhow!("Failed to move the unreadable store aside: {}", e));
+                }
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+
+        Self(Arc::new(Mutex::new(PasswordManagerInner {
+            path,
+            cipher,
+            unreadable,
+            passwords,
+            pending: None,
+        })))
+    }
+
+    /// Offer to save the username and password of a sign-in form `frame`
+    /// posted by `request`, unless they're saved already.
+    pub fn record_submission(&self, frame: &Frame, request: &Request) {
+        let Some(origin) = frame.get_url().ok().and_then(|url| origin(&url)) else {
+            return;
+        };
+
+        let fields = autofill::form_fields(request);
+        let is_field = |name: &str, patterns: &[&str]| {
+            let name = name.to_lowercase();
+            patterns.iter().any(|pattern| name.contains(pattern))
+        };
+
+        let Some((password_field, password)) = fields
+            .iter()
+            .find(|(name, value)| is_field(name, &PASSWORD_FIELDS) && !value.is_empty())
+        else {
+            return;
+        };
+        let username = USERNAME_FIELDS
+            .iter()
+            .find_map(|pattern| {
+                fields.iter().find(|(name, value)| {
+                    name != password_field && is_field(name, &[pattern]) && !value.is_empty()
+                })
+            })
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+
+        let mut inner = self.0.lock().unwrap();
+        let saved = inner.passwords.iter().any(|saved| {
+            saved.origin == origin && saved.username == username && saved.password == *password
+        });
+        if !saved {
+            inner.pending = Some(SavedPassword {
+                origin,
+                username,
+                password: password.clone(),
+            });
+        }
+    }
+
+    pub fn take_pending(&self) -> Option<SavedPassword> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    /// Save `credentials`, replacing the password saved for the same username
+    /// on the same site.
+    pub fn save(&self, credentials: SavedPassword) {
+        let mut inner = self.0.lock().unwrap();
+        inner.passwords.retain(|saved| {
+            saved.origin != credentials.origin || saved.username != credentials.username
+        });
+        inner.passwords.push(credentials);
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save passwords {:?}: {}", inner.path, e);
+        }
+    }
+
+    /// The credentials saved for `origin`, e.g. `https://example.com`.
+    pub fn saved(&self, origin: &str) -> Vec<(String, String)> {
+        self.0
+            .lock()
+            .unwrap()
+            .passwords
+            .iter()
+            .filter(|saved| saved.origin == origin)
+            .map(|saved| (saved.username.clone(), saved.password.clone()))
+            .collect()
+    }
+
+    /// Every saved password, sorted by site.
+    pub fn entries(&self) -> Vec<SavedPassword> {
+        let mut passwords = self.0.lock().unwrap().passwords.clone();
+        passwords.sort_by(|a, b| (&a.origin, &a.username).cmp(&(&b.origin, &b.username)));
+        passwords
+    }
+
+    /// Forget the password saved for `username` on `origin`.
+    pub fn remove(&self, origin: &str, username: &str) {
+        let mut inner = self.0.lock().unwrap();
+        inner
+            .passwords
+            .retain(|saved| saved.origin != origin || saved.username != username);
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save passwords {:?}: {}", inner.path, e);
+        }
+    }
+}
+
+impl PasswordManagerInner {
+    /// Write the passwords encrypted under a fresh nonce, stored in front of
+    /// them.
+    fn save(&self) -> Result<()> {
+        let Some(cipher) = &self.cipher else {
+            return Err(anyhow!("No key to encrypt them with"));
+        };
+        if let Some(e) = &self.unreadable {
+            return Err(anyhow!("Not overwriting the store: {}", e));
+        }
+
+        let json = serde_json::to_vec(&self.passwords)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let encrypted = cipher
+            .encrypt(&nonce, json.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt passwords"))?;
+
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&encrypted);
+        fs::write(&self.path, bytes)?;
+        Ok(())
+    }
+}
+
+fn decrypt(cipher: &Aes256Gcm, bytes: &[u8]) -> Result<Vec<SavedPassword>> {
+    if bytes.len() < NONCE_LEN {
+        return Err(anyhow!("File is truncated"));
+    }
+
+    let (nonce, encrypted) = bytes.split_at(NONCE_LEN);
+    let json = cipher
+        .decrypt(Nonce::from_slice(nonce), encrypted)
+        .map_err(|_| anyhow!("Wrong key or corrupted file"))?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// The key the password store is encrypted with, kept in the login Keychain
+/// and created the first time it's needed. Any other failure, e.g. a denied
+/// prompt or a locked keychain, is returned rather than replacing the key.
+#[cfg(target_os = "macos")]
+fn encryption_key(_store: &Path) -> Result<Vec<u8>> {
+    use security_framework::passwords::{get_generic_password, set_generic_password};
+
+    /// `errSecItemNotFound`.
+    const ITEM_NOT_FOUND: i32 = -25300;
+
+    match get_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT) {
+        Ok(key) => return Ok(key),
+        Err(e) if e.code() == ITEM_NOT_FOUND => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let key = Aes256Gcm::generate_key(OsRng).to_vec();
+    set_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, &key)?;
+    Ok(key)
+}
+
+/// The key the password store is encrypted with. Without a Keychain it's
+/// kept in a file next to the store, readable only by the user, and created
+/// only if there's none yet.
+#[cfg(not(target_os = "macos"))]
+fn encryption_key(store: &Path) -> Result<Vec<u8>> {
+    use std::io::{ErrorKind, Write};
+
+    let path = store.with_extension("key");
+    match fs::read(&path) {
+        Ok(key) => return Ok(key),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let key = Aes256Gcm::generate_key(OsRng).to_vec();
+    options.open(&path)?.write_all(&key)?;
+    Ok(key)
+}
+
+/// The origin of `url` if it's a web page, which passwords are saved by.
+pub fn origin(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.origin().ascii_serialization())
+}
+
+/// The host of `origin`, to show passwords by.
+fn host(origin: &str) -> String {
+    Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| origin.to_string())
+}
+
+/// Information bar below the toolbar offering to save the password just
+/// submitted.
+pub struct SavePasswordBar {
+    credentials: SavedPassword,
+}
+
+impl SavePasswordBar {
+    pub fn new(credentials: SavedPassword) -> Self {
+        Self { credentials }
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        cx.global::<PasswordManager>()
+            .save(self.credentials.clone());
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavePasswordBar {}
+
+impl Render for SavePasswordBar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let host = host(&self.credentials.origin);
+        let label = match self.credentials.username.is_empty() {
+            true => t!("passwords.save_prompt", host = host),
+            false => t!(
+                "passwords.save_prompt_for",
+                username = self.credentials.username,
+                host = host
+            ),
+        };
+
+        div()
+            .flex()
+            .flex_none()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .bg(theme.surface)
+            .border_b_1()
+            .border_color(theme.border)
+            .text_xs()
+            .text_color(theme.text)
+            .child(
+                div()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .child(label),
+            )
+            .child(
+                button("password-dismiss", t!("passwords.not_now"), false, cx)
+                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+            )
+            .child(
+                button("password-save", t!("passwords.save"), true, cx)
+                    .on_click(cx.listener(|this, _, _, cx| this.save(cx))),
+            )
+    }
+}
+
+/// Popover of the fill password page action, offering the passwords saved
+/// for the current site.
+pub struct FillPasswordPopover {
+    /// The site the passwords were saved for, which the page has to still
+    /// be on when one is filled.
+    origin: Option<String>,
+    credentials: Vec<(String, String)>,
+}
+
+impl FillPasswordPopover {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let origin = current_origin(cx);
+        let credentials = origin
+            .as_ref()
+            .map(|origin| cx.global::<PasswordManager>().saved(origin))
+            .unwrap_or_default();
+
+        Self {
+            origin,
+            credentials,
+        }
+    }
+}
+
+/// The origin of the current page, e.g. `https://example.com`.
+fn current_origin(cx: &GpuiApp) -> Option<String> {
+    cx.global::<BrowserState>()
+        .current_page()
+        .and_then(|(url, _)| origin(&url))
+}
+
+/// Whether a password is saved for the current page, so the fill password
+/// page action is worth using.
+pub fn has_saved_password(cx: &GpuiApp) -> bool {
+    current_origin(cx)
+        .is_some_and(|origin| !cx.global::<PasswordManager>().saved(&origin).is_empty())
+}
+
+/// Fill the sign-in form of the current page, if it's still on the site
+/// `saved_for`. The page may have navigated since the popover opened.
+fn fill(cx: &GpuiApp, saved_for: &str, username: &str, password: &str) {
+    let frame = cx
+        .global::<BrowserState>()
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.get_main_frame().ok().flatten());
+
+    let Some(frame) = frame else {
+        return;
+    };
+    let frame_origin = frame.get_url().ok().and_then(|url| origin(&url));
+    if frame_origin.as_deref() != Some(saved_for) {
+        return;
+    }
+
+    let script = FILL_SCRIPT
+        .replace("$USERNAME", &Value::from(username).to_string())
+        .replace("$PASSWORD", &Value::from(password).to_string());
+    if let Err(e) = frame.execute_java_script(&script, "", 0) {
+        eprintln!("Error filling password: {}", e);
+    }
+}
+
+impl EventEmitter<DismissEvent> for FillPasswordPopover {}
+
+impl Render for FillPasswordPopover {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let Some(origin) = self.origin.clone().filter(|_| !self.credentials.is_empty()) else {
+            return div()
+                .text_color(theme.text_secondary)
+                .child(t!("passwords.no_saved_password"));
+        };
+
+        div().flex().flex_col().gap_3().children(
+            self.credentials.clone().into_iter().enumerate().map(
+                |(index, (username, password))| {
+                    let label = match username.is_empty() {
+                        true => t!("passwords.sign_in"),
+                        false => t!("passwords.sign_in_as", username = username),
+                    };
+                    let origin = origin.clone();
+
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(div().flex_1().text_color(theme.text_secondary).child(label))
+                        .child(
+                            button(("fill-password", index), t!("passwords.fill"), true, cx)
+                                .on_click(cx.listener(move |_, _, _, cx| {
+                                    fill(cx, &origin, &username, &password);
+                                    cx.emit(DismissEvent);
+                                })),
+                        )
+                },
+            ),
+        )
+    }
+}
+
+/// Saved passwords listed in settings, each hidden until revealed.
+pub struct SavedPasswords {
+    /// Origin and username of the passwords shown in the clear.
+    revealed: HashSet<(String, String)>,
+}
+
+impl SavedPasswords {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self {
+            revealed: HashSet::new(),
+        }
+    }
+}
+
+impl Render for SavedPasswords {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let entries = cx.global::<PasswordManager>().entries();
+        let empty = entries.is_empty();
+
+        let rows = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, saved)| {
+                let key = (saved.origin.clone(), saved.username.clone());
+                let revealed = self.revealed.contains(&key);
+                let password = match revealed {
+                    true => saved.password.clone(),
+                    false => "•".repeat(saved.password.chars().count().min(12)),
+                };
+                let site = host(&saved.origin);
+
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(120.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.text_muted)
+                            .child(site),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(saved.username.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(96.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(password),
+                    )
+                    .child(
+                        button(
+                            ("password-reveal", index),
+                            if revealed {
+                                t!("passwords.hide")
+                            } else {
+                                t!("passwords.reveal")
+                            },
+                            false,
+                            cx,
+                        )
+                        .on_click({
+                            let key = key.clone();
+                            cx.listener(move |this, _, _, cx| {
+                                if !this.revealed.remove(&key) {
+                                    this.revealed.insert(key.clone());
+                                }
+                                cx.notify();
+                            })
+                        }),
+                    )
+                    .child(
+                        button(("password-copy", index), t!("passwords.copy"), false, cx).on_click(
+                            {
+                                let password = saved.password.clone();
+                                move |_, _, cx: &mut GpuiApp| {
+                                    cx.write_to_clipboard(ClipboardItem::new_string(
+                                        password.clone(),
+                                    ))
+                                }
+                            },
+                        ),
+                    )
+                    .child(
+                        button(
+                            ("password-delete", index),
+                            t!("passwords.delete"),
+                            false,
+                            cx,
+                        )
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            cx.global::<PasswordManager>().remove(&key.0, &key.1);
+                            this.revealed.remove(&key);
+                            cx.notify();
+                        })),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("saved-passwords")
+            .flex()
+            .flex_col()
+            .gap_1()
+            .max_h(px(120.))
+            .overflow_y_scroll()
+            .when(empty, |this| {
+                this.child(
+                    div()
+                        .text_color(theme.text_disabled)
+                        .child(t!("passwords.empty")),
+                )
+            })
+            .children(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A store path of its own for each test, with no key next to it yet.
+    /// The folder is removed when the `TempDir` is dropped.
+    fn store() -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passwords.bin");
+        (dir, path)
+    }
+
+    fn credentials(username: &str, password: &str) -> SavedPassword {
+        SavedPassword {
+            origin: String::from("https://example.com"),
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn random_cipher() -> Aes256Gcm {
+        Aes256Gcm::new(&Aes256Gcm::generate_key(OsRng))
+    }
+
+    #[test]
+    fn saved_passwords_survive_a_reload() {
+        let (_dir, path) = store();
+        let cipher = random_cipher();
+
+        let passwords = PasswordManager::load_with(path.clone(), Some(cipher.clone()));
+        assert!(passwords.entries().is_empty());
+        passwords.save(credentials("ada", "hunter2"));
+        passwords.save(credentials("grace", "correct horse"));
+        // Saving again for a username replaces its password.
+        passwords.save(credentials("ada", "hunter3"));
+
+        let bytes = fs::read(&path).unwrap();
+        let plaintext = |text: &str| bytes.windows(text.len()).any(|w| w == text.as_bytes());
+        assert!(!plaintext("hunter3"));
+        assert!(!plaintext("example.com"));
+
+        let reloaded = PasswordManager::load_with(path.clone(), Some(cipher.clone()));
+        let mut saved = reloaded.saved("https://example.com");
+        saved.sort();
+        assert_eq!(
+            saved,
+            [
+                (String::from("ada"), String::from("hunter3")),
+                (String::from("grace"), String::from("correct horse")),
+            ]
+        );
+        assert!(reloaded.saved("https://example.org").is_empty());
+
+        reloaded.remove("https://example.com", "ada");
+        let entries = PasswordManager::load_with(path.clone(), Some(cipher)).entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].username, "grace");
+    }
+
+    #[test]
+    fn each_save_uses_a_fresh_nonce() {
+        let (_dir, path) = store();
+        let passwords = PasswordManager::load_with(path.clone(), Some(random_cipher()));
+
+        passwords.save(credentials("ada", "hunter2"));
+        let first = fs::read(&path).unwrap();
+        passwords.save(credentials("ada", "hunter2"));
+        let second = fs::read(&path).unwrap();
+
+        assert_ne!(first[..NONCE_LEN], second[..NONCE_LEN]);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn decrypt_round_trips_what_save_wrote() {
+        let cipher = random_cipher();
+        let (_dir, path) = store();
+        let inner = PasswordManagerInner {
+            path,
+            cipher: Some(cipher.clone()),
+            unreadable: None,
+            passwords: vec![credentials("ada", "hunter2")],
+            pending: None,
+        };
+        inner.save().unwrap();
+
+        let bytes = fs::read(&inner.path).unwrap();
+        let passwords = decrypt(&cipher, &bytes).unwrap();
+        assert_eq!(passwords.len(), 1);
+        assert_eq!(passwords[0].origin, "https://example.com");
+        assert_eq!(passwords[0].username, "ada");
+        assert_eq!(passwords[0].password, "hunter2");
+    }
+
+    #[test]
+    fn decrypt_rejects_bad_files() {
+        let cipher = random_cipher();
+        let (_dir, path) = store();
+        let inner = PasswordManagerInner {
+            path,
+            cipher: Some(cipher.clone()),
+            unreadable: None,
+            passwords: vec![credentials("ada", "hunter2")],
+            pending: None,
+        };
+        inner.save().unwrap();
+        let bytes = fs::read(&inner.path).unwrap();
+
+        assert!(decrypt(&random_cipher(), &bytes).is_err());
+        assert!(decrypt(&cipher, &bytes[..NONCE_LEN - 1]).is_err());
+
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&cipher, &tampered).is_err());
+    }
+
+    #[test]
+    fn unreadable_store_survives_a_later_save() {
+        let (_dir, path) = store();
+        let cipher = random_cipher();
+        PasswordManager::load_with(path.clone(), Some(cipher.clone()))
+            .save(credentials("ada", "hunter2"));
+
+        let mut tampered = fs::read(&path).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        fs::write(&path, &tampered).unwrap();
+
+        let passwords = PasswordManager::load_with(path.clone(), Some(cipher.clone()));
+        assert!(passwords.entries().is_empty());
+        passwords.save(credentials("grace", "correct horse"));
+
+        assert_eq!(fs::read(path.with_extension("bad")).unwrap(), tampered);
+        let entries = PasswordManager::load_with(path.clone(), Some(cipher)).entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].username, "grace");
+    }
+
+    // On macOS the key is kept in the Keychain instead.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn key_file_is_private_and_kept() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, path) = store();
+        let key = encryption_key(&path).unwrap();
+        let key_path = path.with_extension("key");
+        let mode = fs::metadata(&key_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(encryption_key(&path).unwrap(), key);
+
+        // A key that can't be read isn't replaced.
+        fs::remove_file(&key_path).unwrap();
+        fs::create_dir(&key_path).unwrap();
+        assert!(encryption_key(&path).is_err());
+        assert!(key_path.is_dir());
+    }
+
+    #[test]
+    fn origin_is_for_web_pages_only() {
+        assert_eq!(
+            origin("https://example.com:8443/login?next=/"),
+            Some(String::from("https://example.com:8443"))
+        );
+        assert_eq!(origin("file:///etc/passwd"), None);
+        assert_eq!(origin("app://assets/settings.html"), None);
+    }
+}
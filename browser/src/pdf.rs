@@ -0,0 +1,729 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    io::Cursor,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+};
+
+use cef_ui::{
+    Browser, Frame, Request, ResourceType, Response, ResponseFilter, ResponseFilterCallbacks,
+    ResponseFilterStatus,
+};
+use gpui::{
+    div, img, prelude::*, px, rgb, Context, Global, Image, ImageFormat, ImageSource, RenderImage,
+    ScrollHandle, SharedString, Window,
+};
+use image::{DynamicImage, Frame as ImageFrame, RgbaImage};
+use pdfium_render::prelude::{PdfDocument, PdfPageIndex, PdfRenderConfig, Pdfium};
+use url::Url;
+
+use crate::{button::button, locale::t, settings::Settings, theme::ActiveTheme};
+
+const PDF_MIME_TYPE: &str = "application/pdf";
+
+/// PDFs larger than this are left to Chromium.
+const MAX_PDF_BYTES: usize = 64 * 1024 * 1024;
+
+/// Width of a page at 100% zoom.
+const PAGE_WIDTH: f32 = 720.;
+
+/// Space between pages.
+const PAGE_GAP: f32 = 12.;
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.;
+const ZOOM_STEP: f32 = 0.25;
+
+/// A PDF a tab navigated to, saved to a file in the user's cache.
+pub struct PdfFile {
+    pub url: String,
+    pub path: PathBuf,
+    /// Identifier of the browser that loaded it.
+    pub browser_id: i32,
+}
+
+#[derive(Default)]
+struct PdfViewerInner {
+    /// PDFs loaded since the last call, waiting for a viewer.
+    pending: Vec<PdfFile>,
+    /// Browsers whose page went away since the last call, by navigating or
+    /// closing, so their viewer goes away with the PDF.
+    gone: HashSet<i32>,
+}
+
+/// PDFs loaded in main frames, taken from their responses so they can be
+/// shown without Chromium's PDF plugin.
+#[derive(Clone, Default)]
+pub struct PdfViewer(Arc<Mutex<PdfViewerInner>>);
+
+impl Global for PdfViewer {}
+
+impl PdfViewer {
+    /// Keep the body of a PDF loaded as a page for the viewer, instead of
+    /// handing it to Chromium.
+    pub fn response_filter(
+        &self,
+        browser: Option<&Browser>,
+        request: &Request,
+        response: &Response,
+    ) -> Option<ResponseFilter> {
+        let is_pdf = request.get_resource_type().ok() == Some(ResourceType::MainFrame)
+            && response.get_status().ok()? == 200
+            && response.get_mime_type().ok()? == PDF_MIME_TYPE;
+        if !is_pdf {
+            return None;
+        }
+
+        Some(ResponseFilter::new(PdfFilter {
+            viewer: self.clone(),
+            url: request.get_url().ok()?,
+            browser_id: browser?.get_identifier().ok()?,
+            body: Vec::new(),
+            too_large: false,
+        }))
+    }
+
+    fn finish(&self, url: String, browser_id: i32, body: &[u8]) {
+        let Some(dir) = pdf_dir() else {
+            eprintln!("Failed to save PDF {}: no cache directory", url);
+            return;
+        };
+        let path = dir.join(file_name(browser_id, &url));
+
+        if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, body)) {
+            eprintln!("Failed to save PDF {:?}: {}", path, e);
+            return;
+        }
+
+        self.0.lock().unwrap().pending.push(PdfFile {
+            url,
+            path,
+            browser_id,
+        });
+    }
+
+    /// Note `browser`'s main frame navigating away from its PDF.
+    pub fn on_before_browse(&self, browser: &Browser, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            self.gone(browser);
+        }
+    }
+
+    /// Note `browser` closing, with its PDF.
+    pub fn on_before_close(&self, browser: &Browser) {
+        self.gone(browser);
+    }
+
+    fn gone(&self, browser: &Browser) {
+        let Ok(browser_id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        inner.pending.retain(|file| {
+            let kept = file.browser_id != browser_id;
+            if !kept {
+                fs::remove_file(&file.path).ok();
+            }
+            kept
+        });
+        inner.gone.insert(browser_id);
+    }
+
+    pub fn take_pending(&self) -> Vec<PdfFile> {
+        std::mem::take(&mut self.0.lock().unwrap().pending)
+    }
+
+    /// Browsers whose page went away since the last call.
+    pub fn take_gone(&self) -> HashSet<i32> {
+        std::mem::take(&mut self.0.lock().unwrap().gone)
+    }
+}
+
+/// Where PDFs being viewed are kept: a folder in the user's own cache, so
+/// other users can't read them or put files in their place.
+fn pdf_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join(env!("CARGO_PKG_NAME")).join("pdf"))
+}
+
+/// Name of the file a PDF's body is kept in. Tabs showing the same PDF get a
+/// file each, so closing one leaves the other's alone.
+fn file_name(browser_id: i32, url: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+
+    format!("{}-{:016x}.pdf", browser_id, hasher.finish())
+}
+
+/// Swallows a PDF response, keeping the body for the viewer. Chromium is left
+/// with an empty page for the viewer to cover.
+struct PdfFilter {
+    viewer: PdfViewer,
+    url: String,
+    browser_id: i32,
+    body: Vec<u8>,
+    too_large: bool,
+}
+
+impl ResponseFilterCallbacks for PdfFilter {
+    fn init_filter(&mut self) -> bool {
+        true
+    }
+
+    fn filter(
+        &mut self,
+        data_in: Option<&[u8]>,
+        data_in_read: &mut usize,
+        _data_out: &mut [u8],
+        data_out_written: &mut usize,
+    ) -> ResponseFilterStatus {
+        *data_out_written = 0;
+
+        let Some(data_in) = data_in else {
+            *data_in_read = 0;
+
+            if !self.too_large {
+                self.viewer
+                    .finish(std::mem::take(&mut self.url), self.browser_id, &self.body);
+            }
+
+            return ResponseFilterStatus::Done;
+        };
+
+        *data_in_read = data_in.len();
+
+        if !self.too_large {
+            self.body.extend_from_slice(data_in);
+            if self.body.len() > MAX_PDF_BYTES {
+                self.too_large = true;
+                self.body = Vec::new();
+            }
+        }
+
+        ResponseFilterStatus::NeedMoreData
+    }
+}
+
+/// A rendered page and its height relative to its width.
+pub struct RenderedPage {
+    pub image: Arc<Image>,
+    pub aspect: f32,
+}
+
+/// Load PDFium, from next to the executable if it's bundled there, otherwise
+/// from the system.
+fn bind() -> Result<Pdfium> {
+    let dir = std::env::current_exe()?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&dir))
+        .or_else(|_| Pdfium::bind_to_system_library())?;
+
+    Ok(Pdfium::new(bindings))
+}
+
+/// Render every page of the PDF at `path`, `width` pixels wide.
+pub fn render(path: &Path, width: i32) -> Result<Vec<RenderedPage>> {
+    let pdfium = bind()?;
+    let document = pdfium.load_pdf_from_file(path, None)?;
+    let config = PdfRenderConfig::new().set_target_width(width);
+
+    let pages = document
+        .pages()
+        .iter()
+        .map(|page| {
+            let bitmap = page.render_with_config(&config)?;
+            let (width, height) = (bitmap.width() as u32, bitmap.height() as u32);
+            let pixels = RgbaImage::from_raw(width, height, bitmap.as_rgba_bytes())
+                .ok_or_else(|| anyhow!("Page doesn't match its size"))?;
+
+            let mut png = Cursor::new(Vec::new());
+            DynamicImage::ImageRgba8(pixels).write_to(&mut png, image::ImageFormat::Png)?;
+
+            Ok(RenderedPage {
+                image: Arc::new(Image::from_bytes(ImageFormat::Png, png.into_inner())),
+                aspect: height as f32 / width.max(1) as f32,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(pages)
+}
+
+/// A page to render, how wide in pixels, and where to send it.
+type PageRequest = (usize, i32, mpsc::Sender<Result<RgbaImage>>);
+
+/// A PDF open on a thread of its own, which PDFium stays on, rendering pages
+/// as they're asked for.
+struct PdfRenderer {
+    requests: mpsc::Sender<PageRequest>,
+}
+
+impl PdfRenderer {
+    /// Open the PDF at `path`, returning the renderer and each page's height
+    /// relative to its width. The thread ends when the renderer is dropped.
+    fn open(path: PathBuf) -> Result<(Self, Vec<f32>)> {
+        let (requests, received) = mpsc::channel::<PageRequest>();
+        let (opened, aspects) = mpsc::channel::<Result<Vec<f32>>>();
+
+        std::thread::spawn(move || {
+            let pdfium = match bind() {
+                Ok(pdfium) => pdfium,
+                Err(e) => {
+                    opened.send(Err(e)).ok();
+                    return;
+                }
+            };
+            let document = match pdfium.load_pdf_from_file(&path, None) {
+                Ok(document) => document,
+                Err(e) => {
+                    opened.send(Err(e.into())).ok();
+                    return;
+                }
+            };
+
+            let aspects = document
+                .pages()
+                .iter()
+                .map(|page| page.height().value / page.width().value.max(1.))
+                .collect();
+            opened.send(Ok(aspects)).ok();
+
+            for (index, width, reply) in received {
+                reply.send(render_page(&document, index, width)).ok();
+            }
+        });
+
+        let aspects = aspects.recv()??;
+        Ok((Self { requests }, aspects))
+    }
+
+    /// Render page `index`, `width` pixels wide, waiting for it.
+    fn render(&self, index: usize, width: i32) -> Result<RgbaImage> {
+        let (reply, rendered) = mpsc::channel();
+        self.requests
+            .send((index, width, reply))
+            .map_err(|_| anyhow!("The PDF was closed"))?;
+
+        rendered.recv()?
+    }
+}
+
+/// Render page `index` of `document`, `width` pixels wide, in the BGRA
+/// layout GPUI draws images from.
+fn render_page(document: &PdfDocument<'_>, index: usize, width: i32) -> Result<RgbaImage> {
+    let page = document.pages().get(PdfPageIndex::try_from(index)?)?;
+    let bitmap = page.render_with_config(&PdfRenderConfig::new().set_target_width(width))?;
+
+    let mut pixels = bitmap.as_rgba_bytes();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    RgbaImage::from_raw(bitmap.width() as u32, bitmap.height() as u32, pixels)
+        .ok_or_else(|| anyhow!("Page doesn't match its size"))
+}
+
+/// The pages of a PDF laid out `width` wide that show between `top` and
+/// `bottom` of the scrolled view.
+fn visible_pages(aspects: &[f32], width: f32, top: f32, bottom: f32) -> Range<usize> {
+    let mut visible = None::<Range<usize>>;
+    let mut page_top = PAGE_GAP;
+
+    for (index, aspect) in aspects.iter().enumerate() {
+        let page_bottom = page_top + width * aspect;
+        if page_bottom >= top && page_top <= bottom {
+            visible = Some(visible.map_or(index, |visible| visible.start)..index + 1);
+        }
+        page_top = page_bottom + PAGE_GAP;
+    }
+
+    visible.unwrap_or_default()
+}
+
+/// Shows a PDF's pages in place of the page, with zoom and download. Only
+/// the pages in view, and a screen either side, are rendered, at the size
+/// they're shown at.
+pub struct PdfView {
+    file: PdfFile,
+    renderer: Option<Arc<PdfRenderer>>,
+    /// Each page's height relative to its width.
+    aspects: Vec<f32>,
+    /// The pages rendered, with the width in pixels they were rendered at.
+    pages: HashMap<usize, (i32, Arc<RenderImage>)>,
+    /// The pages being rendered, with the width asked for.
+    rendering: HashMap<usize, i32>,
+    /// Renders replaced since the last frame, to drop from GPUI's atlas.
+    replaced: Vec<Arc<RenderImage>>,
+    loading: bool,
+    error: Option<String>,
+    zoom: f32,
+    scroll: ScrollHandle,
+    /// Where the PDF was downloaded to, or why it couldn't be.
+    download: Option<String>,
+}
+
+impl PdfView {
+    pub fn new(file: PdfFile, cx: &mut Context<Self>) -> Self {
+        let path = file.path.clone();
+        let opened = cx.background_spawn(async move { PdfRenderer::open(path) });
+
+        cx.spawn(async move |this, cx| {
+            let result = opened.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok((renderer, aspects)) => {
+                        this.renderer = Some(Arc::new(renderer));
+                        this.aspects = aspects;
+                    }
+                    Err(e) => this.error = Some(e.to_string()),
+                }
+
+                this.loading = false;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+
+        Self {
+            file,
+            renderer: None,
+            aspects: Vec::new(),
+            pages: HashMap::new(),
+            rendering: HashMap::new(),
+            replaced: Vec::new(),
+            loading: true,
+            error: None,
+            zoom: 1.,
+            scroll: ScrollHandle::new(),
+            download: None,
+        }
+    }
+
+    /// Render the pages coming into view at the current zoom, and drop the
+    /// ones scrolled far enough away.
+    fn render_visible(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let width = PAGE_WIDTH * self.zoom;
+        let pixels = (width * window.scale_factor()).round() as i32;
+        let top = -f32::from(self.scroll.offset().y);
+        let screen = f32::from(window.viewport_size().height);
+        let visible = visible_pages(&self.aspects, width, top - screen, top + 2. * screen);
+
+        let hidden = self
+            .pages
+            .keys()
+            .filter(|index| !visible.contains(*index))
+            .copied()
+            .collect::<Vec<_>>();
+        for index in hidden {
+            if let Some((_, image)) = self.pages.remove(&index) {
+                self.replaced.push(image);
+            }
+        }
+        for image in self.replaced.drain(..) {
+            window.drop_image(image).ok();
+        }
+
+        for index in visible {
+            let rendered = self.pages.get(&index).map(|(width, _)| *width);
+            if rendered != Some(pixels) && self.rendering.get(&index) != Some(&pixels) {
+                self.render_page(index, pixels, cx);
+            }
+        }
+    }
+
+    /// Render page `index`, `width` pixels wide, in the background.
+    fn render_page(&mut self, index: usize, width: i32, cx: &mut Context<Self>) {
+        let Some(renderer) = self.renderer.clone() else {
+            return;
+        };
+        self.rendering.insert(index, width);
+
+        let page = cx.background_spawn(async move { renderer.render(index, width) });
+        cx.spawn(async move |this, cx| {
+            let result = page.await;
+            this.update(cx, |this, cx| {
+                if this.rendering.get(&index) == Some(&width) {
+                    this.rendering.remove(&index);
+                }
+
+                match result {
+                    Ok(pixels) => {
+                        let image = Arc::new(RenderImage::new(vec![ImageFrame::new(pixels)]));
+                        if let Some((_, previous)) = this.pages.insert(index, (width, image)) {
+                            this.replaced.push(previous);
+                        }
+                    }
+                    Err(e) => this.error = Some(e.to_string()),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn zoom_by(&mut self, step: f32, cx: &mut Context<Self>) {
+        self.zoom = (self.zoom + step).clamp(MIN_ZOOM, MAX_ZOOM);
+        cx.notify();
+    }
+
+    /// The PDF's file name, from the last segment of its URL.
+    fn name(&self) -> String {
+        Url::parse(&self.file.url)
+            .ok()
+            .and_then(|url| {
+                url.path_segments()?
+                    .next_back()
+                    .filter(|segment| !segment.is_empty())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| String::from("document.pdf"))
+    }
+
+    /// Number of the page at the top of the view, from 1.
+    fn current_page(&self) -> usize {
+        let top = -f32::from(self.scroll.offset().y);
+        let mut bottom = 0.;
+
+        for (index, aspect) in self.aspects.iter().enumerate() {
+            bottom += PAGE_WIDTH * self.zoom * aspect + PAGE_GAP;
+            if bottom > top + PAGE_GAP {
+                return index + 1;
+            }
+        }
+
+        self.aspects.len()
+    }
+
+    /// Ask where to save the PDF and copy it there.
+    fn download(&mut self, cx: &mut Context<Self>) {
+        let directory = cx.global::<Settings>().download_path.clone();
+        let destination = cx.prompt_for_new_path(&directory, Some(&self.name()));
+        let source = self.file.path.clone();
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(destination))) = destination.await else {
+                return;
+            };
+
+            let download = match fs::copy(&source, &destination) {
+                Ok(_) => t!("pdf.saved_to", path = destination.display()).to_string(),
+                Err(e) => t!("pdf.download_failed", error = e).to_string(),
+            };
+            this.update(cx, |this, cx| {
+                this.download = Some(download);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl Drop for PdfView {
+    fn drop(&mut self) {
+        fs::remove_file(&self.file.path).ok();
+    }
+}
+
+impl Render for PdfView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.render_visible(window, cx);
+        let theme = cx.theme();
+
+        let status: SharedString = match (&self.error, self.loading) {
+            (Some(_), _) => "".into(),
+            (None, true) => t!("pdf.loading"),
+            (None, false) => t!(
+                "pdf.page_of",
+                page = self.current_page(),
+                count = self.aspects.len()
+            ),
+        };
+        let width = PAGE_WIDTH * self.zoom;
+
+        div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(theme.surface_raised)
+            .text_xs()
+            .text_color(theme.text)
+            .child(
+                div()
+                    .flex()
+                    .flex_none()
+                    .items_center()
+                    .gap_2()
+                    .h_9()
+                    .px_3()
+                    .bg(theme.surface)
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(self.name()),
+                    )
+                    .child(div().text_color(theme.text_muted).child(status))
+                    .child(
+                        button("pdf-zoom-out", "−", false, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.zoom_by(-ZOOM_STEP, cx))),
+                    )
+                    .child(
+                        div()
+                            .w(px(40.))
+                            .text_center()
+                            .child(format!("{:.0}%", self.zoom * 100.)),
+                    )
+                    .child(
+                        button("pdf-zoom-in", "+", false, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.zoom_by(ZOOM_STEP, cx))),
+                    )
+                    .when_some(self.download.clone(), |this, download| {
+                        this.child(div().text_color(theme.text_muted).child(download))
+                    })
+                    .child(
+                        button("pdf-download", t!("pdf.download"), true, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.download(cx))),
+                    ),
+            )
+            .child(
+                div()
+                    .id("pdf-pages")
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .items_center()
+                    .gap(px(PAGE_GAP))
+                    .py(px(PAGE_GAP))
+                    .overflow_scroll()
+                    .track_scroll(&self.scroll)
+                    // The page indicator follows the scroll position.
+                    .on_scroll_wheel(cx.listener(|_, _, _, cx| cx.notify()))
+                    .when_some(self.error.clone(), |this, error| {
+                        this.child(
+                            div()
+                                .p_4()
+                                .text_color(theme.error)
+                                .child(t!("pdf.error", error = error)),
+                        )
+                    })
+                    .children(self.aspects.iter().enumerate().map(|(index, aspect)| {
+                        div()
+                            .flex_none()
+                            .w(px(width))
+                            .h(px(width * aspect))
+                            .bg(rgb(0xffffff))
+                            .shadow_md()
+                            .when_some(self.pages.get(&index), |this, (_, image)| {
+                                this.child(img(ImageSource::from(image.clone())).size_full())
+                            })
+                    })),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A portrait Letter page with its left half black, then a landscape one
+    /// with its top half black.
+    fn fixture() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/two-pages.pdf")
+    }
+
+    fn pixels(page: &RenderedPage) -> RgbaImage {
+        image::load_from_memory(page.image.bytes())
+            .unwrap()
+            .to_rgba8()
+    }
+
+    /// The color of a pixel, RGBA or BGRA: the black and white of the
+    /// fixture are the same either way round.
+    fn rgb(image: &RgbaImage, x: u32, y: u32) -> [u8; 3] {
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+        [r, g, b]
+    }
+
+    #[test]
+    fn only_pages_near_the_view_are_visible() {
+        // Pages 100 and 50 high, with PAGE_GAP around them.
+        let aspects = [1., 0.5, 1.];
+        let width = 100.;
+
+        assert_eq!(visible_pages(&aspects, width, 0., 50.), 0..1);
+        assert_eq!(visible_pages(&aspects, width, 0., 130.), 0..2);
+        assert_eq!(visible_pages(&aspects, width, 120., 170.), 1..2);
+        assert_eq!(visible_pages(&aspects, width, 150., 400.), 1..3);
+        assert_eq!(visible_pages(&aspects, width, 500., 600.), 0..0);
+        assert_eq!(visible_pages(&[], width, 0., 600.), 0..0);
+    }
+
+    #[test]
+    #[ignore = "needs the PDFium library next to the test binary or installed"]
+    fn renders_every_page() {
+        let pages = render(&fixture(), 800).unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert!((pages[0].aspect - 792. / 612.).abs() < 0.01);
+        assert!((pages[1].aspect - 612. / 792.).abs() < 0.01);
+
+        let first = pixels(&pages[0]);
+        let (width, height) = first.dimensions();
+        assert_eq!(width, 800);
+        assert_eq!(rgb(&first, width / 4, height / 2), [0, 0, 0]);
+        assert_eq!(rgb(&first, width * 3 / 4, height / 2), [255, 255, 255]);
+
+        let second = pixels(&pages[1]);
+        let (width, height) = second.dimensions();
+        assert_eq!(width, 800);
+        assert_eq!(rgb(&second, width / 2, height / 4), [0, 0, 0]);
+        assert_eq!(rgb(&second, width / 2, height * 3 / 4), [255, 255, 255]);
+    }
+
+    #[test]
+    #[ignore = "needs the PDFium library next to the test binary or installed"]
+    fn renders_pages_as_theyre_asked_for() {
+        let (renderer, aspects) = PdfRenderer::open(fixture()).unwrap();
+
+        assert_eq!(aspects.len(), 2);
+        assert!((aspects[0] - 792. / 612.).abs() < 0.01);
+        assert!((aspects[1] - 612. / 792.).abs() < 0.01);
+
+        let second = renderer.render(1, 800).unwrap();
+        let (width, height) = second.dimensions();
+        assert_eq!(width, 800);
+        assert_eq!(rgb(&second, width / 2, height / 4), [0, 0, 0]);
+        assert_eq!(rgb(&second, width / 2, height * 3 / 4), [255, 255, 255]);
+
+        let first = renderer.render(0, 400).unwrap();
+        let (width, height) = first.dimensions();
+        assert_eq!(width, 400);
+        assert_eq!(rgb(&first, width / 4, height / 2), [0, 0, 0]);
+        assert_eq!(rgb(&first, width * 3 / 4, height / 2), [255, 255, 255]);
+
+        assert!(renderer.render(2, 400).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs the PDFium library next to the test binary or installed"]
+    fn fails_on_a_file_that_isnt_a_pdf() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+
+        bind().unwrap();
+        assert!(PdfRenderer::open(path).is_err());
+    }
+}
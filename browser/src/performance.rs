@@ -0,0 +1,418 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::{Browser, DevToolsMessageObserver, DevToolsMessageObserverCallbacks, Registration};
+use gpui::{div, prelude::*, px, App as GpuiApp, Context, Global, Hsla, SharedString, Window};
+use serde::Deserialize;
+
+use crate::{locale::t, theme::ActiveTheme, BrowserState};
+
+/// How often the panel samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Samples kept for the charts, two minutes' worth.
+const MAX_SAMPLES: usize = 60;
+
+/// Height of the performance panel.
+const PANEL_HEIGHT: f32 = 160.;
+
+/// Kernel clock ticks per second that `/proc` CPU times are counted in. It's
+/// 100 on every Linux configuration we run on.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS: f64 = 100.;
+
+/// Memory and CPU use of the browser process.
+#[derive(Clone, Copy, Default)]
+struct ProcessUsage {
+    rss_kb: u64,
+    vsz_kb: u64,
+    /// Share of one core used since the previous sample, in percent.
+    cpu: f64,
+}
+
+/// CPU time the process used, in seconds.
+#[cfg(target_os = "linux")]
+pub fn cpu_time() -> Option<f64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+
+    // The command name may contain spaces, so count fields from after it.
+    let fields = stat[stat.rfind(')')? + 2..]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+
+    Some((utime + stime) as f64 / CLOCK_TICKS)
+}
+
+/// Resident and virtual memory of the process, in kB.
+#[cfg(target_os = "linux")]
+pub fn memory() -> Option<(u64, u64)> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+
+    Some((field("VmRSS:")?, field("VmSize:")?))
+}
+
+// TODO: Read these with task_info on macOS.
+#[cfg(not(target_os = "linux"))]
+pub fn cpu_time() -> Option<f64> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory() -> Option<(u64, u64)> {
+    None
+}
+
+/// Metrics of the active page reported by `Performance.getMetrics`.
+#[derive(Clone, Copy, Default)]
+struct PageMetrics {
+    js_heap_used: f64,
+    js_heap_total: f64,
+    nodes: f64,
+}
+
+#[derive(Deserialize)]
+struct Metric {
+    name: String,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct Metrics {
+    metrics: Vec<Metric>,
+}
+
+impl PageMetrics {
+    fn parse(result: &[u8]) -> Option<Self> {
+        let metrics = serde_json::from_slice::<Metrics>(result).ok()?;
+        let value = |name: &str| {
+            metrics
+                .metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .map(|metric| metric.value)
+                .unwrap_or_default()
+        };
+
+        Some(Self {
+            js_heap_used: value("JSHeapUsedSize"),
+            js_heap_total: value("JSHeapTotalSize"),
+            nodes: value("Nodes"),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Sample {
+    process: ProcessUsage,
+    page: PageMetrics,
+}
+
+#[derive(Default)]
+struct PerformanceInner {
+    samples: VecDeque<Sample>,
+    /// Latest page metrics, taken into the next sample.
+    page: PageMetrics,
+    last_cpu: Option<(Instant, f64)>,
+    /// The observer on the page being measured, by browser id.
+    observer: Option<(i32, Registration)>,
+}
+
+/// Samples shown by the performance panel.
+#[derive(Clone, Default)]
+pub struct Performance(Arc<Mutex<PerformanceInner>>);
+
+impl Global for Performance {}
+
+/// Receives `Performance.getMetrics` results for the active page.
+struct MetricsObserver {
+    performance: Performance,
+}
+
+impl DevToolsMessageObserverCallbacks for MetricsObserver {
+    fn on_dev_tools_message(&mut self, _browser: Browser, _message: &[u8]) -> bool {
+        false
+    }
+
+    fn on_dev_tools_method_result(
+        &mut self,
+        _browser: Browser,
+        _message_id: i32,
+        success: bool,
+        result: &[u8],
+    ) {
+        // Other DevTools results lack the metrics and are skipped.
+        if let Some(page) = success.then(|| PageMetrics::parse(result)).flatten() {
+            self.performance.0.lock().unwrap().page = page;
+        }
+    }
+
+    fn on_dev_tools_event(&mut self, _browser: Browser, _method: &str, _params: &[u8]) {}
+
+    fn on_dev_tools_agent_attached(&mut self, _browser: Browser) {}
+
+    fn on_dev_tools_agent_detached(&mut self, _browser: Browser) {}
+}
+
+impl Performance {
+    /// Record the browser process usage and ask the active page for its
+    /// metrics, which arrive in time for the next sample.
+    fn sample(&self, cx: &GpuiApp) {
+        let now = Instant::now();
+        let cpu_time = cpu_time();
+        let (rss_kb, vsz_kb) = memory().unwrap_or_default();
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            let cpu = match (inner.last_cpu, cpu_time) {
+                (Some((then, last)), Some(time)) => {
+                    (time - last) / now.duration_since(then).as_secs_f64() * 100.
+                }
+                _ => 0.,
+            };
+
+            inner.last_cpu = cpu_time.map(|time| (now, time));
+
+            let sample = Sample {
+                process: ProcessUsage {
+                    rss_kb,
+                    vsz_kb,
+                    cpu,
+                },
+                page: inner.page,
+            };
+
+            if inner.samples.len() == MAX_SAMPLES {
+                inner.samples.pop_front();
+            }
+            inner.samples.push_back(sample);
+        }
+
+        if let Some(browser) = cx.global::<BrowserState>().browser.clone() {
+            self.request_metrics(&browser);
+        }
+    }
+
+    fn request_metrics(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let Ok(host) = browser.get_host() else {
+            return;
+        };
+
+        let observed = self.0.lock().unwrap().observer.as_ref().map(|(id, _)| *id);
+        if observed != Some(id) {
+            let observer = MetricsObserver {
+                performance: self.clone(),
+            };
+
+            let registration =
+                match host.add_dev_tools_message_observer(DevToolsMessageObserver::new(observer)) {
+                    Ok(Some(registration)) => registration,
+                    Ok(None) => return,
+                    Err(e) => {
+                        eprintln!("Error observing DevTools messages: {}", e);
+                        return;
+                    }
+                };
+
+            {
+                let mut inner = self.0.lock().unwrap();
+                inner.observer = Some((id, registration));
+                inner.page = PageMetrics::default();
+            }
+
+            // The page's metrics aren't collected until asked for.
+            host.execute_dev_tools_method(0, "Performance.enable", None)
+                .ok();
+        }
+
+        if let Err(e) = host.execute_dev_tools_method(0, "Performance.getMetrics", None) {
+            eprintln!("Error requesting page metrics: {}", e);
+        }
+    }
+
+    /// Stop observing the page while the panel is closed.
+    fn stop(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.observer = None;
+        inner.samples.clear();
+        inner.last_cpu = None;
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        self.0.lock().unwrap().samples.iter().copied().collect()
+    }
+}
+
+fn megabytes(bytes: f64) -> String {
+    format!("{:.1} MB", bytes / 1048576.)
+}
+
+/// A chart of `values` drawn as one bar per sample, scaled to the largest.
+fn sparkline(
+    label: SharedString,
+    current: String,
+    values: Vec<f64>,
+    color: Hsla,
+    cx: &GpuiApp,
+) -> impl IntoElement {
+    let theme = cx.theme();
+
+    let max = values.iter().copied().fold(0., f64::max);
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_1()
+        .min_w(px(0.))
+        .gap_1()
+        .child(
+            div()
+                .flex()
+                .justify_between()
+                .child(div().text_color(theme.text_muted).child(label))
+                .child(div().text_color(theme.text).child(current)),
+        )
+        .child(
+            div()
+                .flex()
+                .items_end()
+                .gap(px(1.))
+                .h(px(64.))
+                .p_1()
+                .rounded_sm()
+                .bg(theme.element_hover)
+                .children(values.into_iter().map(move |value| {
+                    let height = if max > 0. { value / max } else { 0. };
+
+                    div()
+                        .flex_1()
+                        .h(px((height * 56.) as f32))
+                        .min_h(px(1.))
+                        .bg(color)
+                })),
+        )
+}
+
+/// Collapsible panel below the page charting the browser's CPU and memory use
+/// and the active page's heap and DOM size.
+pub struct PerformancePanel {
+    performance: Performance,
+}
+
+impl PerformancePanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let performance = cx.global::<Performance>().clone();
+        performance.sample(cx);
+
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(SAMPLE_INTERVAL).await;
+
+            let sampled = this.update(cx, |this, cx| {
+                this.performance.sample(cx);
+                cx.notify();
+            });
+
+            if sampled.is_err() {
+                break;
+            }
+        })
+        .detach();
+
+        Self { performance }
+    }
+}
+
+impl Drop for PerformancePanel {
+    fn drop(&mut self) {
+        self.performance.stop();
+    }
+}
+
+impl Render for PerformancePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let samples = self.performance.samples();
+        let latest = samples.last().copied().unwrap_or_default();
+        let series = |f: fn(&Sample) -> f64| samples.iter().map(f).collect::<Vec<_>>();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("performance.title"))
+                    .child(div().text_color(theme.text_placeholder).child(format!(
+                        "VSZ {}",
+                        megabytes(latest.process.vsz_kb as f64 * 1024.)
+                    ))),
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap_3()
+                    .p_2()
+                    .child(sparkline(
+                        "CPU".into(),
+                        format!("{:.0}%", latest.process.cpu),
+                        series(|sample| sample.process.cpu),
+                        theme.info,
+                        cx,
+                    ))
+                    .child(sparkline(
+                        t!("performance.memory"),
+                        megabytes(latest.process.rss_kb as f64 * 1024.),
+                        series(|sample| sample.process.rss_kb as f64),
+                        theme.success,
+                        cx,
+                    ))
+                    .child(sparkline(
+                        t!("performance.js_heap"),
+                        format!(
+                            "{} / {}",
+                            megabytes(latest.page.js_heap_used),
+                            megabytes(latest.page.js_heap_total)
+                        ),
+                        series(|sample| sample.page.js_heap_used),
+                        theme.warning,
+                        cx,
+                    ))
+                    .child(sparkline(
+                        t!("performance.dom_nodes"),
+                        format!("{:.0}", latest.page.nodes),
+                        series(|sample| sample.page.nodes),
+                        theme.error,
+                        cx,
+                    )),
+            )
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    Frame, MediaAccessCallback, MediaAccessPermissions, PermissionPromptCallback,
+    PermissionRequestResult, PermissionRequestTypes,
+};
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, EventEmitter, Global, SharedString, Window,
+};
+use url::Url;
+
+use crate::{button::button, locale::t, theme::ActiveTheme};
+
+/// Allow/block decisions keyed by origin, then by permission name.
+type Decisions = BTreeMap<String, BTreeMap<String, bool>>;
+
+/// Told whether a permission the browser asked for was allowed. Taken once
+/// the user decides.
+type BrowserCallback = Mutex<Option<Box<dyn FnOnce(bool) + Send>>>;
+
+enum PermissionCallback {
+    Media(MediaAccessCallback, MediaAccessPermissions),
+    Prompt(u64, PermissionPromptCallback),
+    /// A permission the browser itself checks on a page's behalf, e.g. the
+    /// microphone for speech recognition.
+    Browser(BrowserCallback),
+}
+
+/// A permission request waiting for the user to decide.
+pub struct PermissionRequest {
+    pub origin: String,
+    pub permissions: Vec<String>,
+    callback: PermissionCallback,
+}
+
+impl PermissionRequest {
+    pub fn prompt_id(&self) -> Option<u64> {
+        match self.callback {
+            PermissionCallback::Prompt(prompt_id, _) => Some(prompt_id),
+            PermissionCallback::Media(..) | PermissionCallback::Browser(_) => None,
+        }
+    }
+
+    fn resolve(&self, allow: bool) {
+        let result = match &self.callback {
+            PermissionCallback::Media(callback, requested) => callback.cont(if allow {
+                *requested
+            } else {
+                MediaAccessPermissions::None
+            }),
+            PermissionCallback::Prompt(_, callback) => callback.cont(if allow {
+                PermissionRequestResult::Accept
+            } else {
+                PermissionRequestResult::Deny
+            }),
+            PermissionCallback::Browser(callback) => {
+                if let Some(callback) = callback.lock().unwrap().take() {
+                    callback(allow);
+                }
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error resolving permission request: {}", e);
+        }
+    }
+}
+
+struct PermissionsStoreInner {
+    path: PathBuf,
+    decisions: Decisions,
+    pending: VecDeque<PermissionRequest>,
+    dismissed: Vec<u64>,
+}
+
+/// Per-origin permission decisions persisted to a JSON file. Shared between
+/// the CEF permission handler and the UI.
+#[derive(Clone)]
+pub struct PermissionsStore(Arc<Mutex<PermissionsStoreInner>>);
+
+impl Global for PermissionsStore {}
+
+impl PermissionsStore {
+    /// Load decisions from disk, starting empty if the file is missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let decisions = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse permissions {:?}: {}", path, e);
+                Decisions::new()
+            }),
+            Err(_) => Decisions::new(),
+        };
+
+        Self(Arc::new(Mutex::new(PermissionsStoreInner {
+            path,
+            decisions,
+            pending: VecDeque::new(),
+            dismissed: Vec::new(),
+        })))
+    }
+
+    /// Handle a getUserMedia style request for camera/microphone access.
+    pub fn request_media(
+        &self,
+        origin: &str,
+        requested: MediaAccessPermissions,
+        callback: MediaAccessCallback,
+    ) {
+        let permissions = media_permission_names(requested);
+
+        self.request(PermissionRequest {
+            origin: origin.to_string(),
+            permissions,
+            callback: PermissionCallback::Media(callback, requested),
+        });
+    }
+
+    /// Handle a permission prompt, e.g. for geolocation.
+    pub fn request_prompt(
+        &self,
+        prompt_id: u64,
+        origin: &str,
+        requested: PermissionRequestTypes,
+        callback: PermissionPromptCallback,
+    ) {
+        let permissions = prompt_permission_names(requested);
+
+        self.request(PermissionRequest {
+            origin: origin.to_string(),
+            permissions,
+            callback: PermissionCallback::Prompt(prompt_id, callback),
+        });
+    }
+
+    /// Ask for `permission` for a feature the browser provides itself,
+    /// calling `callback` with the decision once there is one.
+    pub fn request_browser(
+        &self,
+        origin: &str,
+        permission: &str,
+        callback: impl FnOnce(bool) + Send + 'static,
+    ) {
+        self.request(PermissionRequest {
+            origin: origin.to_string(),
+            permissions: vec![permission.to_string()],
+            callback: PermissionCallback::Browser(Mutex::new(Some(Box::new(callback)))),
+        });
+    }
+
+    fn request(&self, request: PermissionRequest) {
+        let mut inner = self.0.lock().unwrap();
+        let decided = inner.decisions.get(&request.origin).and_then(|decisions| {
+            request
+                .permissions
+                .iter()
+                .map(|permission| decisions.get(permission).copied())
+                .collect::<Option<Vec<bool>>>()
+        });
+
+        match decided {
+            Some(decisions) => request.resolve(decisions.iter().all(|allowed| *allowed)),
+            None => inner.pending.push_back(request),
+        }
+    }
+
+    /// A prompt was dismissed by CEF, e.g. because the page navigated away.
+    pub fn dismiss(&self, prompt_id: u64) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner
+            .pending
+            .retain(|request| request.prompt_id() != Some(prompt_id));
+        inner.dismissed.push(prompt_id);
+    }
+
+    /// Whether `origin` was allowed to use `permission`.
+    pub fn is_allowed(&self, origin: &str, permission: &str) -> bool {
+        self.decision(origin, permission).unwrap_or(false)
+    }
+
+    /// Whether `origin` was allowed or blocked from using `permission`, or
+    /// `None` if the user hasn't decided yet.
+    pub fn decision(&self, origin: &str, permission: &str) -> Option<bool> {
+        self.0
+            .lock()
+            .unwrap()
+            .decisions
+            .get(origin)
+            .and_then(|decisions| decisions.get(permission))
+            .copied()
+    }
+
+    /// Record a decision made outside the permission banner, e.g. in the
+    /// capture sheet, so CEF's own request for it is answered without asking.
+    pub fn remember(&self, origin: &str, permission: &str, allow: bool) {
+        let mut inner = self.0.lock().unwrap();
+        inner
+            .decisions
+            .entry(origin.to_string())
+            .or_default()
+            .insert(permission.to_string(), allow);
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save permissions {:?}: {}", inner.path, e);
+        }
+    }
+
+    pub fn take_pending(&self) -> Option<PermissionRequest> {
+        self.0.lock().unwrap().pending.pop_front()
+    }
+
+    pub fn take_dismissed(&self) -> Vec<u64> {
+        std::mem::take(&mut self.0.lock().unwrap().dismissed)
+    }
+
+    fn decide(&self, request: &PermissionRequest, allow: bool) {
+        let mut inner = self.0.lock().unwrap();
+        let decisions = inner.decisions.entry(request.origin.clone()).or_default();
+
+        for permission in &request.permissions {
+            decisions.insert(permission.clone(), allow);
+        }
+
+        if let Err(e) = inner.save() {
+            eprintln!("Failed to save permissions {:?}: {}", inner.path, e);
+        }
+
+        drop(inner);
+        request.resolve(allow);
+    }
+}
+
+impl PermissionsStoreInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.decisions)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// The origin of `frame`'s page in the form CEF gives permission prompts,
+/// e.g. `https://example.com/`.
+pub fn frame_origin(frame: &Frame) -> Option<String> {
+    let url = Url::parse(&frame.get_url().ok()?).ok()?;
+
+    Some(format!("{}/", url.origin().ascii_serialization()))
+}
+
+fn media_permission_names(requested: MediaAccessPermissions) -> Vec<String> {
+    requested
+        .iter_names()
+        .map(|(name, _)| match name {
+            "DeviceAudioCapture" => "microphone".to_string(),
+            "DeviceVideoCapture" => "camera".to_string(),
+            "DesktopAudioCapture" => "system audio".to_string(),
+            "DesktopVideoCapture" => "screen".to_string(),
+            name => name.to_lowercase(),
+        })
+        .collect()
+}
+
+fn prompt_permission_names(requested: PermissionRequestTypes) -> Vec<String> {
+    requested
+        .iter_names()
+        .map(|(name, _)| match name {
+            "Geolocation" => "location".to_string(),
+            "CameraStream" => "camera".to_string(),
+            "MicStream" => "microphone".to_string(),
+            name => name.to_lowercase(),
+        })
+        .collect()
+}
+
+/// The name `permission` is shown under, in the UI's language.
+fn permission_label(permission: &str) -> SharedString {
+    match permission {
+        "microphone" => t!("permissions.microphone"),
+        "camera" => t!("permissions.camera"),
+        "system audio" => t!("permissions.system_audio"),
+        "screen" => t!("permissions.screen"),
+        "location" => t!("permissions.location"),
+        permission => permission.to_string().into(),
+    }
+}
+
+/// Banner at the top of the page asking to allow or block a permission.
+pub struct PermissionBanner {
+    request: PermissionRequest,
+}
+
+impl PermissionBanner {
+    pub fn new(request: PermissionRequest) -> Self {
+        Self { request }
+    }
+
+    pub fn prompt_id(&self) -> Option<u64> {
+        self.request.prompt_id()
+    }
+
+    fn decide(&mut self, allow: bool, cx: &mut Context<Self>) {
+        cx.global::<PermissionsStore>().decide(&self.request, allow);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for PermissionBanner {}
+
+impl Render for PermissionBanner {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .bg(theme.surface)
+            .border_b_1()
+            .border_color(theme.border)
+            .text_xs()
+            .text_color(theme.text)
+            .child(
+                div()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .child(t!(
+                        "permissions.prompt",
+                        origin = self.request.origin,
+                        permissions = self
+                            .request
+                            .permissions
+                            .iter()
+                            .map(|permission| permission_label(permission).to_string())
+                            .collect::<Vec<_>>()
+                            .join(&t!("permissions.and"))
+                    )),
+            )
+            .child(
+                button("permission-block", t!("permissions.block"), false, cx)
+                    .on_click(cx.listener(|this, _, _, cx| this.decide(false, cx))),
+            )
+            .child(
+                button("permission-allow", t!("permissions.allow"), true, cx)
+                    .on_click(cx.listener(|this, _, _, cx| this.decide(true, cx))),
+            )
+    }
+}
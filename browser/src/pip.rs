@@ -0,0 +1,642 @@
+use std::{collections::HashMap, sync::Arc};
+
+use cef_ui::{Browser, Frame};
+use gpui::{
+    div, img, point, prelude::*, px, rgb, size, svg, App as GpuiApp, Bounds, Context, Global,
+    ImageSource, MouseButton, MouseDownEvent, MouseMoveEvent, Pixels, Point, Size, Subscription,
+    Window, WindowBounds, WindowHandle, WindowKind, WindowOptions,
+};
+use serde::Deserialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    tabs,
+    tabs::TabManager,
+    theme::{ActiveTheme, Theme},
+    BrowserState,
+};
+
+/// Size of a tab's first Picture in Picture window.
+const DEFAULT_SIZE: Size<Pixels> = Size {
+    width: px(400.),
+    height: px(225.),
+};
+
+/// Smallest the window can be resized to.
+const MIN_SIZE: Size<Pixels> = Size {
+    width: px(200.),
+    height: px(112.),
+};
+
+/// Size of the window a video opens in, which is also as small as it goes.
+const VIDEO_SIZE: Size<Pixels> = Size {
+    width: px(180.),
+    height: px(100.),
+};
+
+/// Gap between the window and the corner of the screen it opens in.
+const SCREEN_MARGIN: Pixels = px(24.);
+
+/// Puts a button over playing videos that calls `requestPictureInPicture()`,
+/// which is replaced to report the video instead: CEF's `MediaObserver` only
+/// hears about cast routes, and a windowless browser has nowhere to float a
+/// native player. While a video is out, its place in the page and whether
+/// it's playing are reported as they change. Defines `__cefPip(command)` for
+/// the window's controls. Relies on `window.cefQuery`.
+pub const PIP_SCRIPT: &str = r#"(() => {
+  if (window.__cefPip || !window.HTMLVideoElement) return;
+
+  let current = null;
+  let frame = 0;
+
+  const report = (event) => {
+    const state = { event };
+    if (current && event !== "leave") {
+      const rect = current.getBoundingClientRect();
+      Object.assign(state, {
+        playing: !current.paused,
+        rect: {
+          x: rect.x,
+          y: rect.y,
+          width: rect.width,
+          height: rect.height,
+          viewportWidth: window.innerWidth,
+          viewportHeight: window.innerHeight,
+        },
+      });
+    }
+
+    window.cefQuery({ request: "pip:" + JSON.stringify(state) });
+  };
+
+  const track = () => {
+    if (!current || frame) return;
+    frame = requestAnimationFrame(() => {
+      frame = 0;
+      if (current) report("update");
+    });
+  };
+
+  const leave = () => {
+    const video = current;
+    if (!video) return;
+    current = null;
+    video.dispatchEvent(new Event("leavepictureinpicture"));
+  };
+
+  HTMLVideoElement.prototype.requestPictureInPicture = function () {
+    if (current !== this) {
+      leave();
+      current = this;
+      report("enter");
+      this.dispatchEvent(new Event("enterpictureinpicture"));
+    }
+    return Promise.resolve({ width: this.videoWidth, height: this.videoHeight });
+  };
+
+  Object.defineProperty(Document.prototype, "pictureInPictureElement", {
+    get: () => current,
+  });
+  Object.defineProperty(Document.prototype, "pictureInPictureEnabled", {
+    get: () => true,
+  });
+  Document.prototype.exitPictureInPicture = function () {
+    if (current) {
+      leave();
+      report("leave");
+    }
+    return Promise.resolve();
+  };
+
+  const button = document.createElement("button");
+  button.setAttribute("aria-label", "Picture in Picture");
+  button.innerHTML = '<svg width="16" height="16" viewBox="0 0 24 24" fill="none" ' +
+    'stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">' +
+    '<path d="M21 9V6a2 2 0 0 0-2-2H4a2 2 0 0 0-2 2v10c0 1.1.9 2 2 2h4"/>' +
+    '<rect width="10" height="7" x="12" y="13" rx="2"/></svg>';
+  button.style.cssText = "position:fixed;z-index:2147483647;display:none;width:28px;" +
+    "height:28px;padding:6px;border:0;border-radius:6px;cursor:pointer;color:#fff;" +
+    "background:rgba(0,0,0,0.6)";
+
+  let hovered = null;
+  const place = () => {
+    if (!hovered || hovered.paused || hovered === current) {
+      button.style.display = "none";
+      return;
+    }
+    const rect = hovered.getBoundingClientRect();
+    button.style.left = rect.right - 36 + "px";
+    button.style.top = rect.top + 8 + "px";
+    button.style.display = "block";
+  };
+
+  button.addEventListener("click", (event) => {
+    event.stopPropagation();
+    if (hovered) hovered.requestPictureInPicture();
+    place();
+  });
+
+  document.addEventListener("mouseover", (event) => {
+    if (event.target === button) return;
+    if (event.target instanceof HTMLVideoElement) hovered = event.target;
+    else hovered = null;
+    if (!button.isConnected) document.documentElement.appendChild(button);
+    place();
+  }, true);
+
+  for (const type of ["play", "pause"]) {
+    document.addEventListener(type, (event) => {
+      if (event.target === hovered) place();
+      if (event.target === current) track();
+    }, true);
+  }
+
+  document.addEventListener("emptied", (event) => {
+    if (event.target !== current) return;
+    leave();
+    report("leave");
+  }, true);
+
+  window.addEventListener("scroll", () => { place(); track(); }, true);
+  window.addEventListener("resize", () => { place(); track(); });
+  window.addEventListener("pagehide", () => {
+    if (current) report("leave");
+  });
+
+  Object.defineProperty(window, "__cefPip", {
+    value: (command) => {
+      if (!current) return;
+      if (command === "toggle") current.paused ? current.play() : current.pause();
+      if (command === "leave") leave();
+    },
+  });
+})();"#;
+
+/// Where a video sits in its page, in CSS pixels.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoRect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    viewport_width: f32,
+    viewport_height: f32,
+}
+
+/// What the page reports about the video it has in Picture in Picture.
+#[derive(Deserialize)]
+struct VideoEvent {
+    /// `enter`, `update` or `leave`.
+    event: String,
+    #[serde(default)]
+    playing: bool,
+    rect: Option<VideoRect>,
+}
+
+/// A video shown on its own in the Picture in Picture window, cropped out of
+/// its tab's paint.
+#[derive(Clone)]
+struct PipVideo {
+    frame: Frame,
+    rect: VideoRect,
+    playing: bool,
+}
+
+impl PipVideo {
+    /// Run one of the page's Picture in Picture commands.
+    fn control(&self, command: &str) {
+        let script = format!("window.__cefPip && window.__cefPip({:?});", command);
+        if let Err(e) = self.frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error controlling Picture in Picture: {}", e);
+        }
+    }
+}
+
+/// The tab detached into the floating Picture in Picture window, and the
+/// size that window was last left at for each tab.
+#[derive(Default)]
+pub struct PictureInPicture {
+    /// Identifier of the detached tab's browser.
+    browser: Option<i32>,
+    window: Option<WindowHandle<PipWindow>>,
+    /// The video shown instead of the whole tab, if a page asked for one.
+    video: Option<PipVideo>,
+    sizes: HashMap<i32, Size<Pixels>>,
+}
+
+impl Global for PictureInPicture {}
+
+impl PictureInPicture {
+    /// Whether `browser` is shown in the Picture in Picture window.
+    pub fn is_detached(&self, browser: &Browser) -> bool {
+        self.browser.is_some() && browser.get_identifier().ok() == self.browser
+    }
+}
+
+/// Detach the active tab, or return it if it already is.
+pub fn toggle(cx: &mut GpuiApp) {
+    let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+        return;
+    };
+
+    if cx.global::<PictureInPicture>().is_detached(&browser) {
+        return_to_main(cx);
+    } else {
+        open(cx, &browser, None);
+    }
+}
+
+/// Move `browser` into a small always-on-top window, returning whichever tab
+/// was there before. The window opens with its top left corner at `position`
+/// on screen, or in the corner of the screen if `None`.
+pub fn open(cx: &mut GpuiApp, browser: &Browser, position: Option<Point<Pixels>>) {
+    let Ok(id) = browser.get_identifier() else {
+        return;
+    };
+
+    return_to_main(cx);
+
+    let window_size = cx
+        .global::<PictureInPicture>()
+        .sizes
+        .get(&id)
+        .copied()
+        .unwrap_or(DEFAULT_SIZE);
+    open_window(cx, id, position, window_size, false);
+}
+
+/// Open the window for the tab with browser identifier `id`, showing just
+/// its video if `video` is set.
+fn open_window(
+    cx: &mut GpuiApp,
+    id: i32,
+    position: Option<Point<Pixels>>,
+    window_size: Size<Pixels>,
+    video: bool,
+) {
+    // Open in the bottom right corner of the screen, out of the way.
+    let bounds = match (position, cx.primary_display()) {
+        (Some(position), _) => Bounds::new(position, window_size),
+        (None, Some(display)) => {
+            let corner = display.bounds().bottom_right();
+            Bounds::new(
+                point(
+                    corner.x - window_size.width - SCREEN_MARGIN,
+                    corner.y - window_size.height - SCREEN_MARGIN,
+                ),
+                window_size,
+            )
+        }
+        (None, None) => Bounds::centered(None, window_size, cx),
+    };
+
+    let window = cx.open_window(
+        WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(bounds)),
+            titlebar: None,
+            focus: false,
+            // Pop-ups float above other windows and follow the user across
+            // spaces and full screen apps.
+            kind: WindowKind::PopUp,
+            is_movable: true,
+            is_resizable: true,
+            is_minimizable: false,
+            ..Default::default()
+        },
+        |window, cx| cx.new(|cx| PipWindow::new(id, video, window, cx)),
+    );
+
+    match window {
+        Ok(window) => {
+            let pip = cx.global_mut::<PictureInPicture>();
+            pip.browser = Some(id);
+            pip.window = Some(window);
+        }
+        Err(e) => eprintln!("Error opening Picture in Picture window: {}", e),
+    }
+}
+
+/// Close the Picture in Picture window, showing its tab in the main window
+/// again.
+pub fn return_to_main(cx: &mut GpuiApp) {
+    let pip = cx.global_mut::<PictureInPicture>();
+    let Some(window) = pip.window.take() else {
+        return;
+    };
+    pip.browser = None;
+    if let Some(video) = pip.video.take() {
+        video.control("leave");
+    }
+
+    // The tab may have been painting in the background for the window.
+    let active = cx.global::<TabManager>().active();
+    tabs::activate(cx, active);
+
+    // The window may be the one asking, so close it once it's done.
+    cx.defer(move |cx| {
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .ok();
+    });
+}
+
+/// Detach the tab of the page that asked for Picture in Picture, showing
+/// only the video in a small window, or follow the video while it's out.
+pub fn on_query(cx: &mut GpuiApp, query: Query, argument: &str) {
+    let event = match serde_json::from_str::<VideoEvent>(argument) {
+        Ok(event) => event,
+        Err(e) => {
+            query.failure(
+                ERROR_FAILED,
+                &format!("Invalid Picture in Picture event: {}", e),
+            );
+            return;
+        }
+    };
+
+    let frame = query.frame();
+    query.success("");
+
+    let Ok(browser) = frame.get_browser() else {
+        return;
+    };
+    let is_current = cx.global::<PictureInPicture>().is_detached(&browser)
+        && cx.global::<PictureInPicture>().video.is_some();
+
+    match (event.event.as_str(), event.rect) {
+        ("enter", Some(rect)) => {
+            let Ok(id) = browser.get_identifier() else {
+                return;
+            };
+
+            // A page switching videos already let go of the last one.
+            let previous = cx.global_mut::<PictureInPicture>().video.take();
+            if let Some(previous) = previous.filter(|previous| {
+                previous.frame.get_identifier().ok() != frame.get_identifier().ok()
+            }) {
+                previous.control("leave");
+            }
+
+            return_to_main(cx);
+            open_window(cx, id, None, VIDEO_SIZE, true);
+            cx.global_mut::<PictureInPicture>().video = Some(PipVideo {
+                frame,
+                rect,
+                playing: event.playing,
+            });
+        }
+        ("update", Some(rect)) if is_current => {
+            if let Some(video) = cx.global_mut::<PictureInPicture>().video.as_mut() {
+                video.rect = rect;
+                video.playing = event.playing;
+            }
+        }
+        ("leave", _) if is_current => {
+            // The page already let go of the video.
+            cx.global_mut::<PictureInPicture>().video = None;
+            return_to_main(cx);
+        }
+        _ => {}
+    }
+}
+
+/// Drag in progress on the resize handle: where it started, on screen, and
+/// the window size at the time.
+struct Resize {
+    start: Point<Pixels>,
+    size: Size<Pixels>,
+}
+
+/// Contents of the Picture in Picture window: the page, or just its video,
+/// with a button to return it and a handle to resize the window by.
+pub struct PipWindow {
+    browser: i32,
+    /// Whether the window shows a video rather than the whole page.
+    video: bool,
+    resize: Option<Resize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PipWindow {
+    fn new(browser: i32, video: bool, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut subscriptions = vec![
+            cx.observe_global::<BrowserState>(|_, cx| cx.notify()),
+            cx.observe_global::<PictureInPicture>(|_, cx| cx.notify()),
+        ];
+        // A video's window doesn't change the size the tab opens at.
+        if !video {
+            subscriptions.push(cx.observe_window_bounds(window, move |_, window, cx| {
+                let size = window.bounds().size;
+                cx.global_mut::<PictureInPicture>()
+                    .sizes
+                    .insert(browser, size);
+            }));
+        }
+
+        Self {
+            browser,
+            video,
+            resize: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn start_resize(&mut self, event: &MouseDownEvent, window: &mut Window) {
+        let bounds = window.bounds();
+        self.resize = Some(Resize {
+            start: bounds.origin + event.position,
+            size: bounds.size,
+        });
+    }
+
+    fn drag_resize(&mut self, event: &MouseMoveEvent, window: &mut Window) {
+        let Some(resize) = &self.resize else {
+            return;
+        };
+
+        if event.pressed_button != Some(MouseButton::Left) {
+            self.resize = None;
+            return;
+        }
+
+        // The handle sits in the top right corner, because macOS keeps the
+        // bottom left corner of a window in place when it's resized.
+        let delta = window.bounds().origin + event.position - resize.start;
+        let min_size = match self.video {
+            true => VIDEO_SIZE,
+            false => MIN_SIZE,
+        };
+        window.resize(size(
+            (resize.size.width + delta.x).max(min_size.width),
+            (resize.size.height - delta.y).max(min_size.height),
+        ));
+    }
+}
+
+impl Render for PipWindow {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // The controls sit over video, so they stay dark in either theme.
+        let theme = Theme::dark();
+        let video = cx
+            .global::<PictureInPicture>()
+            .video
+            .clone()
+            .filter(|_| self.video);
+
+        let state = cx.global::<BrowserState>();
+
+        // Only the active tab's paint is kept, so a detached tab shows up
+        // while it's the one selected in the main window.
+        let image = state
+            .browser
+            .as_ref()
+            .filter(|browser| browser.get_identifier().ok() == Some(self.browser))
+            .and(state.image.clone());
+
+        div()
+            .id("pip")
+            .group("pip")
+            .relative()
+            .flex()
+            .items_center()
+            .justify_center()
+            .size_full()
+            .bg(rgb(0x000000))
+            .text_color(theme.text)
+            .text_xs()
+            .on_mouse_move(cx.listener(|this, event, window, _| this.drag_resize(event, window)))
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, _, _| this.resize = None),
+            )
+            .child(match (image, &video) {
+                (Some(image), Some(video)) => {
+                    let bounds = video_bounds(&video.rect, window.viewport_size());
+                    div().relative().size_full().overflow_hidden().child(
+                        img(ImageSource::from(Arc::new(image)))
+                            .absolute()
+                            .left(bounds.origin.x)
+                            .top(bounds.origin.y)
+                            .w(bounds.size.width)
+                            .h(bounds.size.height),
+                    )
+                }
+                (Some(image), None) => div()
+                    .size_full()
+                    .child(img(ImageSource::from(Arc::new(image))).size_full()),
+                (None, _) => div().child(t!("pip.loading")),
+            })
+            .when_some(video, |this, video| {
+                let icon = match video.playing {
+                    true => "pause.svg",
+                    false => "play.svg",
+                };
+                this.child(
+                    div()
+                        .id("pip-toggle")
+                        .absolute()
+                        .bottom_2()
+                        .left_2()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .size(px(22.))
+                        .rounded_full()
+                        .bg(theme.scrim)
+                        .cursor_pointer()
+                        .invisible()
+                        .group_hover("pip", |this| this.visible())
+                        .on_click(move |_, _, _| video.control("toggle"))
+                        .child(svg().path(icon).size(px(10.)).text_color(theme.text)),
+                )
+            })
+            .child(
+                div()
+                    .id("pip-return")
+                    .absolute()
+                    .top_2()
+                    .left_2()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .size(px(22.))
+                    .rounded_full()
+                    .bg(theme.scrim)
+                    .cursor_pointer()
+                    .invisible()
+                    .group_hover("pip", |this| this.visible())
+                    .on_click(|_, _, cx| return_to_main(cx))
+                    .child(svg().path("close.svg").size(px(10.)).text_color(theme.text)),
+            )
+            .child(
+                div()
+                    .id("pip-resize")
+                    .absolute()
+                    .top_0()
+                    .right_0()
+                    .size(px(16.))
+                    .cursor_nesw_resize()
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, event, window, cx| {
+                            cx.stop_propagation();
+                            this.start_resize(event, window);
+                        }),
+                    )
+                    .child(
+                        div()
+                            .absolute()
+                            .top_1()
+                            .right_1()
+                            .size(px(8.))
+                            .border_t_2()
+                            .border_r_2()
+                            .border_color(theme.text_muted),
+                    ),
+            )
+    }
+}
+
+/// Where the whole page has to be drawn, relative to the window, for the
+/// video at `rect` to fill a window of `window_size`, letterboxed to keep its
+/// shape.
+fn video_bounds(rect: &VideoRect, window_size: Size<Pixels>) -> Bounds<Pixels> {
+    let (width, height) = (f32::from(window_size.width), f32::from(window_size.height));
+    let scale = (width / rect.width.max(1.)).min(height / rect.height.max(1.));
+
+    Bounds::new(
+        point(
+            px((width - rect.width * scale) / 2. - rect.x * scale),
+            px((height - rect.height * scale) / 2. - rect.y * scale),
+        ),
+        size(
+            px(rect.viewport_width * scale),
+            px(rect.viewport_height * scale),
+        ),
+    )
+}
+
+/// Shown in place of the page while its tab is in the Picture in Picture
+/// window.
+pub fn placeholder(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .justify_center()
+        .gap_3()
+        .size_full()
+        .bg(theme.background)
+        .text_color(theme.text_secondary)
+        .text_sm()
+        .child(t!("pip.playing_elsewhere"))
+        .child(
+            button("pip-return-main", t!("pip.return"), true, cx)
+                .on_click(|_, _, cx| return_to_main(cx)),
+        )
+}
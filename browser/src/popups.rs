@@ -0,0 +1,333 @@
+use anyhow::Result;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Browser, Frame, PopupFeatures, Rect, RequestContext, WindowInfo};
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, EventEmitter, Global, SharedString, Window,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{button::button, incognito, locale::t, tabs, theme::ActiveTheme, BrowserState};
+
+/// What to do when a page opens a popup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PopupPolicy {
+    Block,
+    #[default]
+    OpenInNewTab,
+    OpenInNewWindow,
+}
+
+impl PopupPolicy {
+    pub const ALL: [PopupPolicy; 3] = [
+        PopupPolicy::Block,
+        PopupPolicy::OpenInNewTab,
+        PopupPolicy::OpenInNewWindow,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            PopupPolicy::Block => t!("popups.block"),
+            PopupPolicy::OpenInNewTab => t!("popups.open_in_new_tab"),
+            PopupPolicy::OpenInNewWindow => t!("popups.open_in_new_window"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PopupsInner {
+    policy: PopupPolicy,
+    pending_tabs: VecDeque<Browser>,
+}
+
+/// Popup policy and popups waiting to become tabs, shared with the life span
+/// handler.
+#[derive(Clone, Default)]
+pub struct Popups(Arc<Mutex<PopupsInner>>);
+
+impl Global for Popups {}
+
+impl Popups {
+    pub fn policy(&self) -> PopupPolicy {
+        self.0.lock().unwrap().policy
+    }
+
+    pub fn set_policy(&self, policy: PopupPolicy) {
+        self.0.lock().unwrap().policy = policy;
+    }
+
+    /// Decide how to open a popup for `target_url`. Returns true if it should
+    /// be blocked.
+    pub fn on_before_popup(
+        &self,
+        target_url: Option<String>,
+        popup_features: &PopupFeatures,
+        window_info: &mut WindowInfo,
+    ) -> bool {
+        match self.policy() {
+            PopupPolicy::Block => true,
+            PopupPolicy::OpenInNewTab => {
+                // Render offscreen like the main browser so no native window
+                // is created; the tab is added once the browser exists.
+                *window_info = WindowInfo::new().windowless_rendering_enabled(true);
+                false
+            }
+            PopupPolicy::OpenInNewWindow => {
+                // GPUI windows only show the global browser state, so let CEF
+                // host the popup in a native window of its own.
+                let info = std::mem::replace(window_info, WindowInfo::new());
+                *window_info = info
+                    .window_name(&target_url.unwrap_or_default())
+                    .bounds(&Rect {
+                        x: popup_features.x.unwrap_or(160),
+                        y: popup_features.y.unwrap_or(160),
+                        width: popup_features.width.unwrap_or(800),
+                        height: popup_features.height.unwrap_or(600),
+                    });
+                false
+            }
+        }
+    }
+
+    /// A browser was created. Offscreen popups are queued to become tabs.
+    pub fn on_after_created(&self, browser: Browser) {
+        let is_tab = browser.is_popup().unwrap_or(false)
+            && browser
+                .get_host()
+                .and_then(|host| host.is_window_rendering_disabled())
+                .unwrap_or(false);
+
+        if is_tab {
+            self.0.lock().unwrap().pending_tabs.push_back(browser);
+        }
+    }
+
+    /// A popup browser ready to be added as a tab.
+    pub fn take_pending_tab(&self) -> Option<Browser> {
+        self.0.lock().unwrap().pending_tabs.pop_front()
+    }
+}
+
+/// A popup the page tried to open while popups are blocked.
+pub struct BlockedPopup {
+    pub url: String,
+    /// Name the page gave the popup's window, or its URL if none.
+    pub title: String,
+    /// Request context of the tab that opened it if not the global one, so an
+    /// incognito or isolated tab's popup stays in it.
+    context: Option<RequestContext>,
+}
+
+struct BlockedPopupsInner {
+    path: PathBuf,
+    /// Popups blocked per domain, over all browsing.
+    counts: HashMap<String, u32>,
+    pending: Vec<BlockedPopup>,
+    /// Set once the main frame navigates, so the bar goes away with the page.
+    navigated: bool,
+}
+
+/// Blocked popups waiting for the UI, and how many each domain tried to open,
+/// persisted to a JSON file.
+#[derive(Clone)]
+pub struct BlockedPopupsState(Arc<Mutex<BlockedPopupsInner>>);
+
+impl Global for BlockedPopupsState {}
+
+impl BlockedPopupsState {
+    /// Load the counts from disk, starting empty if the file is missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let counts = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse blocked popups {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self(Arc::new(Mutex::new(BlockedPopupsInner {
+            path,
+            counts,
+            pending: Vec::new(),
+            navigated: false,
+        })))
+    }
+
+    /// Note a popup `frame` tried to open. Incognito tabs aren't counted.
+    pub fn block(
+        &self,
+        browser: &Browser,
+        frame: &Frame,
+        target_url: Option<String>,
+        target_frame_name: Option<String>,
+    ) {
+        let Some(url) = target_url.filter(|url| !url.is_empty()) else {
+            return;
+        };
+        let incognito = incognito::is_incognito(browser);
+        let context = browser
+            .get_host()
+            .and_then(|host| host.get_request_context())
+            .ok()
+            .filter(|context| !context.is_global().unwrap_or(true));
+
+        let mut inner = self.0.lock().unwrap();
+        inner.pending.push(BlockedPopup {
+            title: target_frame_name
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| url.clone()),
+            url,
+            context,
+        });
+
+        if incognito {
+            return;
+        }
+        if let Some(domain) = frame.get_url().ok().and_then(|url| domain(&url)) {
+            *inner.counts.entry(domain).or_default() += 1;
+
+            if let Err(e) = inner.save() {
+                eprintln!("Failed to save blocked popups {:?}: {}", inner.path, e);
+            }
+        }
+    }
+
+    /// Note the main frame navigating away from the page that was blocked.
+    pub fn on_before_browse(&self, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            let mut inner = self.0.lock().unwrap();
+            inner.pending.clear();
+            inner.navigated = true;
+        }
+    }
+
+    /// How many popups pages on `domain` have had blocked.
+    pub fn count(&self, domain: &str) -> u32 {
+        self.0
+            .lock()
+            .unwrap()
+            .counts
+            .get(domain)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn take_pending(&self) -> Vec<BlockedPopup> {
+        std::mem::take(&mut self.0.lock().unwrap().pending)
+    }
+
+    /// Whether the main frame navigated since the last call.
+    pub fn take_navigated(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().navigated)
+    }
+}
+
+impl BlockedPopupsInner {
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.counts)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// The host of `url`, which blocked popups are counted by.
+fn domain(url: &str) -> Option<String> {
+    Url::parse(url).ok()?.host_str().map(str::to_string)
+}
+
+/// Information bar below the toolbar offering to open the popups the page
+/// tried to open.
+pub struct PopupBlockedBar {
+    popups: Vec<BlockedPopup>,
+}
+
+impl PopupBlockedBar {
+    pub fn new(popups: Vec<BlockedPopup>) -> Self {
+        Self { popups }
+    }
+
+    /// Add popups blocked while the bar is showing.
+    pub fn push(&mut self, popups: Vec<BlockedPopup>, cx: &mut Context<Self>) {
+        self.popups.extend(popups);
+        cx.notify();
+    }
+
+    fn allow(&mut self, cx: &mut Context<Self>) {
+        for popup in std::mem::take(&mut self.popups) {
+            tabs::open_url(cx, &popup.url, popup.context);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for PopupBlockedBar {}
+
+impl Render for PopupBlockedBar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let label = match self.popups.len() {
+            1 => t!("popups.blocked_one"),
+            count => t!("popups.blocked_many", count = count),
+        };
+        let title = self
+            .popups
+            .last()
+            .map(|popup| popup.title.clone())
+            .unwrap_or_default();
+        let blocked_on_site = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.get_main_frame().ok().flatten())
+            .and_then(|frame| frame.get_url().ok())
+            .and_then(|url| domain(&url))
+            .map_or(0, |domain| cx.global::<BlockedPopupsState>().count(&domain));
+
+        div()
+            .flex()
+            .flex_none()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .bg(theme.surface)
+            .border_b_1()
+            .border_color(theme.border)
+            .text_xs()
+            .text_color(theme.text)
+            .child(label)
+            .child(
+                div()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .text_color(theme.text_placeholder)
+                    .child(title),
+            )
+            .when(blocked_on_site > 1, |this| {
+                this.child(
+                    div()
+                        .text_color(theme.text_placeholder)
+                        .child(t!("popups.blocked_on_site", count = blocked_on_site)),
+                )
+            })
+            .child(
+                button("popup-dismiss", t!("popups.dismiss"), false, cx)
+                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+            )
+            .child(
+                button("popup-allow", t!("popups.allow"), true, cx)
+                    .on_click(cx.listener(|this, _, _, cx| this.allow(cx))),
+            )
+    }
+}
@@ -0,0 +1,651 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    process::Command,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cef_ui::{
+    Browser, PdfPrintCallback, PdfPrintCallbacks, PdfPrintSettings, PrintDialogCallback,
+    PrintHandlerCallbacks, PrintJobCallback, PrintSettings, Range, Size,
+};
+use gpui::{
+    div, img, prelude::*, px, rgb, App as GpuiApp, Context, DismissEvent, EventEmitter, Global,
+    ImageSource, SharedString, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button::button,
+    locale::t,
+    pdf::{self, RenderedPage},
+    settings::Settings,
+    tabs::TabManager,
+    theme::ActiveTheme,
+    BrowserState,
+};
+
+/// Width of a page in the print preview.
+const CARD_WIDTH: f32 = 120.;
+
+/// Width preview pages are rendered at, in pixels, so they stay sharp on
+/// HiDPI displays.
+const CARD_RENDER_WIDTH: i32 = 240;
+
+/// Paper the page is laid out on when printing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaperSize {
+    #[default]
+    Letter,
+    Legal,
+    A4,
+    A3,
+}
+
+impl PaperSize {
+    pub const ALL: [PaperSize; 4] = [
+        PaperSize::Letter,
+        PaperSize::Legal,
+        PaperSize::A4,
+        PaperSize::A3,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            PaperSize::Letter => t!("print.letter"),
+            PaperSize::Legal => t!("print.legal"),
+            PaperSize::A4 => "A4".into(),
+            PaperSize::A3 => "A3".into(),
+        }
+    }
+
+    /// Width and height in inches, in portrait.
+    fn inches(&self) -> (f64, f64) {
+        match self {
+            PaperSize::Letter => (8.5, 11.),
+            PaperSize::Legal => (8.5, 14.),
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::A3 => (11.69, 16.54),
+        }
+    }
+}
+
+/// Which way up the paper is when printing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 2] = [Orientation::Portrait, Orientation::Landscape];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            Orientation::Portrait => t!("print.portrait"),
+            Orientation::Landscape => t!("print.landscape"),
+        }
+    }
+}
+
+/// A PDF CEF finished writing, or failed to.
+pub struct PrintedPdf {
+    pub path: PathBuf,
+    pub ok: bool,
+    /// File name to suggest when saving, e.g. `example.com.pdf`.
+    pub suggested_name: String,
+    /// Whether the PDF was made for the print preview rather than to be saved.
+    pub preview: bool,
+}
+
+/// How the next print job is laid out, as chosen in the print preview.
+#[derive(Clone, Default)]
+struct PrintOptions {
+    paper_size: PaperSize,
+    orientation: Orientation,
+    /// Zero-based, inclusive page ranges to print. Empty prints every page.
+    pages: Vec<Range>,
+    /// Set once the user confirms the preview, until CEF asks for the dialog.
+    confirmed: bool,
+}
+
+#[derive(Default)]
+struct PrintJobsInner {
+    finished: VecDeque<PrintedPdf>,
+    options: PrintOptions,
+    /// Set when a page called `window.print()`, for the UI to show a preview.
+    requested: bool,
+}
+
+/// PDFs printed by CEF, queued for the UI to save, and the options of the
+/// print job the user confirmed.
+#[derive(Clone, Default)]
+pub struct PrintJobs(Arc<Mutex<PrintJobsInner>>);
+
+impl Global for PrintJobs {}
+
+impl PrintJobs {
+    pub fn take_finished(&self) -> Option<PrintedPdf> {
+        self.0.lock().unwrap().finished.pop_front()
+    }
+
+    /// Whether a page asked to be printed since the last call.
+    pub fn take_requested(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().requested)
+    }
+}
+
+struct PrintFinished {
+    jobs: PrintJobs,
+    suggested_name: String,
+    preview: bool,
+}
+
+impl PdfPrintCallbacks for PrintFinished {
+    fn on_pdf_print_finished(&mut self, path: &str, ok: bool) {
+        self.jobs.0.lock().unwrap().finished.push_back(PrintedPdf {
+            path: PathBuf::from(path),
+            ok,
+            suggested_name: self.suggested_name.clone(),
+            preview: self.preview,
+        });
+    }
+}
+
+/// Print the active page to a temporary PDF, laid out on the paper from
+/// settings in `orientation` and keeping only `pages` if there are any. The
+/// result shows up in `PrintJobs`.
+fn print_to_pdf(
+    cx: &GpuiApp,
+    orientation: Orientation,
+    pages: &[Range],
+    preview: bool,
+) -> Result<()> {
+    let host = cx
+        .global::<BrowserState>()
+        .host()
+        .ok_or_else(|| anyhow!("No page to print"))?;
+
+    let settings = cx.global::<Settings>();
+    let (width, height) = settings.paper_size.inches();
+    let mut print_settings = PdfPrintSettings::new()
+        .paper_size(width, height)
+        .landscape(orientation == Orientation::Landscape)
+        .print_background(true);
+
+    if !pages.is_empty() {
+        print_settings = print_settings.page_ranges(&page_ranges_label(pages));
+    }
+
+    let manager = cx.global::<TabManager>();
+    let label = manager
+        .tabs()
+        .get(manager.active())
+        .map(|tab| tab.label())
+        .unwrap_or_else(|| String::from("page"));
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let path = std::env::temp_dir().join(format!("print-{}.pdf", timestamp));
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Temporary path isn't valid UTF-8"))?;
+
+    let callback = PdfPrintCallback::new(PrintFinished {
+        jobs: cx.global::<PrintJobs>().clone(),
+        suggested_name: format!("{}.pdf", label),
+        preview,
+    });
+
+    host.print_to_pdf(path, &print_settings, callback)
+}
+
+/// Move a printed PDF from its temporary file to `destination`.
+pub fn save(pdf: &PrintedPdf, destination: &PathBuf) -> Result<()> {
+    // The temporary file may be on another file system, so copy instead of
+    // renaming.
+    fs::copy(&pdf.path, destination)?;
+    discard(pdf);
+    Ok(())
+}
+
+/// Delete a printed PDF's temporary file.
+pub fn discard(pdf: &PrintedPdf) {
+    if !pdf.path.exists() {
+        return;
+    }
+
+    if let Err(e) = fs::remove_file(&pdf.path) {
+        eprintln!("Failed to remove {:?}: {}", pdf.path, e);
+    }
+}
+
+/// Zero-based ranges covering the selected pages.
+fn page_ranges(selected: &[bool]) -> Vec<Range> {
+    let mut ranges: Vec<Range> = Vec::new();
+
+    for (index, _) in selected
+        .iter()
+        .enumerate()
+        .filter(|(_, selected)| **selected)
+    {
+        let index = index as u32;
+        match ranges.last_mut() {
+            Some(range) if range.to + 1 == index => range.to = index,
+            _ => ranges.push(Range {
+                from: index,
+                to: index,
+            }),
+        }
+    }
+
+    ranges
+}
+
+/// Page ranges as `PdfPrintSettings` takes them, one-based, e.g. `1-3, 5`.
+fn page_ranges_label(ranges: &[Range]) -> String {
+    ranges
+        .iter()
+        .map(|range| match range.from == range.to {
+            true => format!("{}", range.from + 1),
+            false => format!("{}-{}", range.from + 1, range.to + 1),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints pages on Linux, with the layout chosen in the print preview, by
+/// handing the PDF CEF renders to the CUPS `lp` command.
+pub struct Printer {
+    jobs: PrintJobs,
+}
+
+impl Printer {
+    pub fn new(jobs: PrintJobs) -> Self {
+        Self { jobs }
+    }
+}
+
+impl PrintHandlerCallbacks for Printer {
+    fn on_print_start(&mut self, _browser: Browser) {}
+
+    fn on_print_settings(
+        &mut self,
+        _browser: Browser,
+        settings: PrintSettings,
+        _get_defaults: bool,
+    ) {
+        let options = self.jobs.0.lock().unwrap().options.clone();
+        if settings.is_read_only().unwrap_or(true) {
+            return;
+        }
+
+        settings
+            .set_orientation(options.orientation == Orientation::Landscape)
+            .ok();
+        settings.set_page_ranges(&options.pages).ok();
+    }
+
+    fn on_print_dialog(
+        &mut self,
+        _browser: Browser,
+        _has_selection: bool,
+        callback: PrintDialogCallback,
+    ) -> bool {
+        let options = {
+            let mut inner = self.jobs.0.lock().unwrap();
+            if !std::mem::take(&mut inner.options.confirmed) {
+                // The page called window.print(), so show our preview instead.
+                inner.requested = true;
+                return false;
+            }
+
+            inner.options.clone()
+        };
+
+        let settings = PrintSettings::new();
+        settings
+            .set_orientation(options.orientation == Orientation::Landscape)
+            .ok();
+        settings.set_page_ranges(&options.pages).ok();
+
+        if let Err(e) = callback.cont(settings) {
+            eprintln!("Error continuing print: {}", e);
+        }
+
+        true
+    }
+
+    fn on_print_job(
+        &mut self,
+        _browser: Browser,
+        document_name: &str,
+        pdf_file_path: &str,
+        callback: PrintJobCallback,
+    ) -> bool {
+        let document_name = document_name.to_string();
+        let pdf_file_path = pdf_file_path.to_string();
+
+        std::thread::spawn(move || {
+            match Command::new("lp")
+                .arg("-t")
+                .arg(&document_name)
+                .arg(&pdf_file_path)
+                .status()
+            {
+                Ok(status) if !status.success() => {
+                    eprintln!(
+                        "Error printing {}: lp exited with {}",
+                        document_name, status
+                    )
+                }
+                Err(e) => eprintln!("Error printing {}: {}", document_name, e),
+                Ok(_) => {}
+            }
+
+            callback.cont().ok();
+        });
+
+        true
+    }
+
+    fn on_print_reset(&mut self, _browser: Browser) {
+        self.jobs.0.lock().unwrap().options.confirmed = false;
+    }
+
+    fn get_pdf_paper_size(&mut self, _browser: Browser, device_units_per_inch: i32) -> Size {
+        let paper_size = self.jobs.0.lock().unwrap().options.paper_size;
+        let (width, height) = paper_size.inches();
+
+        Size {
+            width: (width * device_units_per_inch as f64) as i32,
+            height: (height * device_units_per_inch as f64) as i32,
+        }
+    }
+}
+
+/// Modal previewing the active page laid out for printing. Pages can be left
+/// out and the orientation changed before printing or saving as a PDF.
+pub struct PrintPreview {
+    orientation: Orientation,
+    /// The PDF shown, once CEF has rendered it.
+    pdf: Option<PrintedPdf>,
+    /// The PDF's pages, once PDFium has rendered them.
+    pages: Vec<RenderedPage>,
+    /// Whether each page of the PDF will be printed.
+    selected: Vec<bool>,
+    rendering: bool,
+    error: Option<String>,
+}
+
+impl PrintPreview {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let mut preview = Self {
+            orientation: cx.global::<Settings>().orientation,
+            pdf: None,
+            pages: Vec::new(),
+            selected: Vec::new(),
+            rendering: false,
+            error: None,
+        };
+
+        preview.lay_out(cx);
+        preview
+    }
+
+    /// Lay the page out again for the current orientation.
+    fn lay_out(&mut self, cx: &mut Context<Self>) {
+        if let Some(pdf) = self.pdf.take() {
+            discard(&pdf);
+        }
+
+        self.pages.clear();
+        self.selected.clear();
+        self.error = print_to_pdf(cx, self.orientation, &[], true)
+            .err()
+            .map(|e| e.to_string());
+        cx.notify();
+    }
+
+    /// Show the pages of the PDF CEF rendered for the preview.
+    pub fn set_pdf(&mut self, pdf: PrintedPdf, cx: &mut Context<Self>) {
+        if let Some(previous) = self.pdf.take() {
+            discard(&previous);
+        }
+
+        self.pages.clear();
+        self.selected.clear();
+        self.rendering = pdf.ok;
+        self.error = match pdf.ok {
+            true => None,
+            false => Some(t!("print.layout_failed").to_string()),
+        };
+
+        if pdf.ok {
+            let path = pdf.path.clone();
+            let pages = cx.background_spawn({
+                let path = path.clone();
+                async move { pdf::render(&path, CARD_RENDER_WIDTH) }
+            });
+
+            cx.spawn(async move |this, cx| {
+                let result = pages.await;
+                this.update(cx, |this, cx| {
+                    // The page was laid out again while this one rendered.
+                    if this.pdf.as_ref().map(|pdf| &pdf.path) != Some(&path) {
+                        return;
+                    }
+
+                    match result {
+                        Ok(pages) => {
+                            this.selected = vec![true; pages.len()];
+                            this.pages = pages;
+                        }
+                        Err(e) => this.error = Some(e.to_string()),
+                    }
+
+                    this.rendering = false;
+                    cx.notify();
+                })
+                .ok();
+            })
+            .detach();
+        }
+
+        self.pdf = Some(pdf);
+        cx.notify();
+    }
+
+    fn set_orientation(&mut self, orientation: Orientation, cx: &mut Context<Self>) {
+        if orientation != self.orientation {
+            self.orientation = orientation;
+            self.lay_out(cx);
+        }
+    }
+
+    /// Remember the chosen layout and send the page to the printer.
+    fn print(&mut self, cx: &mut Context<Self>) {
+        let Some(host) = cx.global::<BrowserState>().host() else {
+            return;
+        };
+
+        {
+            let mut inner = cx.global::<PrintJobs>().0.lock().unwrap();
+            inner.options = PrintOptions {
+                paper_size: cx.global::<Settings>().paper_size,
+                orientation: self.orientation,
+                pages: page_ranges(&self.selected),
+                confirmed: true,
+            };
+        }
+
+        if let Err(e) = host.print() {
+            eprintln!("Error printing page: {}", e);
+        }
+
+        cx.emit(DismissEvent);
+    }
+
+    /// Render the selected pages to a PDF to be saved.
+    fn save_as_pdf(&mut self, cx: &mut Context<Self>) {
+        if let Err(e) = print_to_pdf(cx, self.orientation, &page_ranges(&self.selected), false) {
+            eprintln!("Error printing page: {}", e);
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Drop for PrintPreview {
+    fn drop(&mut self) {
+        if let Some(pdf) = self.pdf.take() {
+            discard(&pdf);
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for PrintPreview {}
+
+impl Render for PrintPreview {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let any_selected = self.selected.iter().any(|selected| *selected);
+        let status: Option<SharedString> = match (&self.error, &self.pdf) {
+            (Some(error), _) => Some(t!("print.preview_failed", error = error)),
+            (None, None) => Some(t!("print.preparing")),
+            (None, Some(_)) if self.rendering => Some(t!("print.preparing")),
+            (None, Some(_)) => None,
+        };
+
+        let pages = self
+            .pages
+            .iter()
+            .zip(&self.selected)
+            .enumerate()
+            .map(|(index, (page, selected))| {
+                let selected = *selected;
+
+                div()
+                    .id(("print-page", index))
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_1()
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        if let Some(selected) = this.selected.get_mut(index) {
+                            *selected = !*selected;
+                            cx.notify();
+                        }
+                    }))
+                    .child(
+                        div()
+                            .w(px(CARD_WIDTH))
+                            .h(px(CARD_WIDTH * page.aspect))
+                            .overflow_hidden()
+                            .rounded_sm()
+                            .border_2()
+                            .border_color(match selected {
+                                true => theme.accent,
+                                false => theme.surface_raised,
+                            })
+                            .bg(rgb(0xffffff))
+                            .when(!selected, |this| this.opacity(0.4))
+                            .child(img(ImageSource::from(page.image.clone())).size_full()),
+                    )
+                    .child(div().text_color(theme.text_muted).child(if selected {
+                        t!("print.print")
+                    } else {
+                        t!("print.skip")
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(560.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .child(div().text_sm().child(t!("print.print")))
+                            .child(div().flex().gap_1().children(Orientation::ALL.map(
+                                |orientation| {
+                                    button(
+                                        orientation.label(),
+                                        orientation.label(),
+                                        orientation == self.orientation,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |this, _, _, cx| this.set_orientation(orientation, cx),
+                                    ))
+                                },
+                            ))),
+                    )
+                    .child(
+                        div()
+                            .id("print-pages")
+                            .flex()
+                            .flex_wrap()
+                            .gap_3()
+                            .h(px(360.))
+                            .p_2()
+                            .rounded_md()
+                            .bg(theme.background)
+                            .overflow_y_scroll()
+                            .when_some(status, |this, status| {
+                                this.child(div().text_color(theme.text_disabled).child(status))
+                            })
+                            .children(pages),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("print-cancel", t!("print.cancel"), false, cx)
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                            )
+                            .when(any_selected, |this| {
+                                this.child(
+                                    button("print-save-pdf", t!("print.save_as_pdf"), false, cx)
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.save_as_pdf(cx)),
+                                        ),
+                                )
+                                .child(
+                                    button("print-confirm", t!("print.print"), true, cx)
+                                        .on_click(cx.listener(|this, _, _, cx| this.print(cx))),
+                                )
+                            }),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{CommandLine, ErrorCode, Frame};
+use gpui::{
+    div, prelude::*, px, Context, DismissEvent, EventEmitter, Global, SharedString, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{locale::t, settings::Settings, theme::ActiveTheme, BrowserState, OpenSettings};
+
+/// How pages reach the network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxyMode {
+    /// Whatever the operating system is set to use.
+    #[default]
+    System,
+    /// Straight to the server, ignoring the system's proxy.
+    Direct,
+    /// An HTTP proxy at `Settings::proxy_server`, which HTTPS is tunneled
+    /// through as well.
+    Http,
+    /// A SOCKS5 proxy at `Settings::proxy_server`.
+    Socks5,
+    /// A proxy auto-config script at `Settings::proxy_pac_url`.
+    Pac,
+}
+
+impl ProxyMode {
+    pub const ALL: [ProxyMode; 5] = [
+        ProxyMode::System,
+        ProxyMode::Direct,
+        ProxyMode::Http,
+        ProxyMode::Socks5,
+        ProxyMode::Pac,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            ProxyMode::System => t!("proxy.system"),
+            ProxyMode::Direct => t!("proxy.direct"),
+            ProxyMode::Http => "HTTP/HTTPS".into(),
+            ProxyMode::Socks5 => "SOCKS5".into(),
+            ProxyMode::Pac => t!("proxy.pac_url"),
+        }
+    }
+}
+
+/// A Chromium switch, with its value if it takes one.
+pub type ProxySwitch = (&'static str, Option<String>);
+
+/// The switch selecting the proxy chosen in `settings`, if it isn't the
+/// system's. A proxy with no address set falls back to the system's.
+pub fn switch(settings: &Settings) -> Option<ProxySwitch> {
+    let server = settings.proxy_server.trim();
+    let pac_url = settings.proxy_pac_url.trim();
+
+    match settings.proxy {
+        ProxyMode::System => None,
+        ProxyMode::Direct => Some(("no-proxy-server", None)),
+        ProxyMode::Http if !server.is_empty() => {
+            Some(("proxy-server", Some(format!("http://{}", server))))
+        }
+        ProxyMode::Socks5 if !server.is_empty() => {
+            Some(("proxy-server", Some(format!("socks5://{}", server))))
+        }
+        ProxyMode::Pac if !pac_url.is_empty() => Some(("proxy-pac-url", Some(pac_url.to_string()))),
+        ProxyMode::Http | ProxyMode::Socks5 | ProxyMode::Pac => None,
+    }
+}
+
+/// Route pages through the proxy in `switch`. Chromium only reads these
+/// switches at startup, so a new proxy applies after a restart.
+pub fn configure(command_line: &CommandLine, (name, value): &ProxySwitch) -> Result<()> {
+    match value {
+        Some(value) => command_line.append_switch_with_value(name, Some(value)),
+        None => command_line.append_switch(name),
+    }
+}
+
+/// Whether a load failed because of the proxy rather than the site.
+fn is_proxy_error(error: ErrorCode) -> bool {
+    matches!(
+        error,
+        ErrorCode::ProxyConnectionFailed
+            | ErrorCode::TunnelConnectionFailed
+            | ErrorCode::ProxyAuthUnsupported
+            | ErrorCode::ProxyAuthRequested
+            | ErrorCode::ProxyCertificateInvalid
+            | ErrorCode::SocksConnectionFailed
+            | ErrorCode::SocksConnectionHostUnreachable
+            | ErrorCode::MandatoryProxyConfigurationFailed
+            | ErrorCode::HttpsProxyTunnelResponseRedirect
+    )
+}
+
+/// A page that couldn't load through the proxy.
+pub struct ProxyError {
+    pub url: String,
+    pub error: ErrorCode,
+}
+
+#[derive(Default)]
+struct ProxyErrorsInner {
+    pending: Option<ProxyError>,
+    /// Set once a new navigation starts, so a stale interstitial goes away.
+    browsing: bool,
+}
+
+/// Proxy failures of main frame loads, waiting for the UI to show them.
+#[derive(Clone, Default)]
+pub struct ProxyErrors(Arc<Mutex<ProxyErrorsInner>>);
+
+impl Global for ProxyErrors {}
+
+impl ProxyErrors {
+    /// Note the main frame navigating away from a failed page.
+    pub fn on_before_browse(&self, frame: &Frame) {
+        if frame.is_main().unwrap_or(false) {
+            self.0.lock().unwrap().browsing = true;
+        }
+    }
+
+    pub fn on_load_error(&self, frame: &Frame, error: ErrorCode, failed_url: &str) {
+        if !frame.is_main().unwrap_or(false) || !is_proxy_error(error) {
+            return;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+        inner.pending = Some(ProxyError {
+            url: failed_url.to_string(),
+            error,
+        });
+        inner.browsing = false;
+    }
+
+    pub fn take_pending(&self) -> Option<ProxyError> {
+        self.0.lock().unwrap().pending.take()
+    }
+
+    /// Whether the main frame started another navigation since the last call.
+    pub fn take_browsing(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().browsing)
+    }
+}
+
+/// Interstitial shown in place of the page when the proxy couldn't be used.
+pub struct ProxyInterstitial {
+    error: ProxyError,
+    proxy: String,
+}
+
+impl ProxyInterstitial {
+    pub fn new(error: ProxyError, cx: &mut Context<Self>) -> Self {
+        let settings = cx.global::<Settings>();
+        let proxy = match settings.proxy {
+            ProxyMode::Pac => settings.proxy_pac_url.clone(),
+            _ => settings.proxy_server.clone(),
+        };
+
+        Self { error, proxy }
+    }
+
+    fn describe(&self) -> SharedString {
+        match self.error.error {
+            ErrorCode::ProxyAuthUnsupported | ErrorCode::ProxyAuthRequested => {
+                t!("proxy.auth_unsupported")
+            }
+            ErrorCode::ProxyCertificateInvalid => t!("proxy.certificate_invalid"),
+            ErrorCode::MandatoryProxyConfigurationFailed => {
+                t!("proxy.pac_failed")
+            }
+            ErrorCode::SocksConnectionHostUnreachable => {
+                t!("proxy.socks_failed")
+            }
+            _ => t!("proxy.not_responding"),
+        }
+    }
+
+    fn retry(&mut self, cx: &mut Context<Self>) {
+        if let Some(browser) = cx.global::<BrowserState>().browser.as_ref() {
+            if let Err(e) = browser.reload() {
+                eprintln!("Error reloading: {}", e);
+            }
+        }
+
+        cx.emit(DismissEvent);
+    }
+
+    fn open_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        window.dispatch_action(Box::new(OpenSettings), cx);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ProxyInterstitial {}
+
+impl Render for ProxyInterstitial {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.page_background)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .max_w(px(480.))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .child(
+                        div()
+                            .text_xl()
+                            .text_color(theme.text)
+                            .child(t!("proxy.title")),
+                    )
+                    .child(match self.proxy.trim().is_empty() {
+                        true => t!("proxy.load_failed"),
+                        false => t!("proxy.load_failed_at", proxy = self.proxy.trim()),
+                    })
+                    .child(self.describe())
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.text_placeholder)
+                            .child(format!("{:?} — {}", self.error.error, self.error.url)),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .mt_2()
+                            .child(
+                                div()
+                                    .id("proxy-retry")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .bg(theme.accent)
+                                    .text_color(theme.text_on_accent)
+                                    .hover(|this| this.opacity(0.85))
+                                    .child(t!("proxy.try_again"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.retry(cx))),
+                            )
+                            .child(
+                                div()
+                                    .id("proxy-settings")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded_md()
+                                    .cursor_pointer()
+                                    .text_color(theme.text_muted)
+                                    .hover(|this| this.text_color(theme.text))
+                                    .child(t!("proxy.settings"))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.open_settings(window, cx)
+                                    })),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cef_ui::Frame;
+use gpui::{div, prelude::*, App as GpuiApp, Context, DismissEvent, EventEmitter, Global, Window};
+use serde::Deserialize;
+
+use crate::{button::button, locale::t, theme::ActiveTheme, BrowserState};
+
+/// Prefix of the console message reporting the page's content type.
+const CONTENT_TYPE_PREFIX: &str = "__reader_content_type__";
+
+/// Prefix of the console message reporting the extracted article.
+const ARTICLE_PREFIX: &str = "__reader_article__";
+
+/// Reports the content type of the page, checked after every load.
+const CONTENT_TYPE_SCRIPT: &str = r#"console.log("$PREFIX" + document.contentType);"#;
+
+/// Runs the bundled extractor over a copy of the page and reports the result.
+const EXTRACT_SCRIPT: &str = r#"
+const article = new Readability(document.cloneNode(true), location.href).parse();
+console.log("$PREFIX" + JSON.stringify(article));"#;
+
+/// An article extracted from the page.
+#[derive(Deserialize)]
+pub struct Article {
+    title: String,
+    byline: Option<String>,
+    content: String,
+}
+
+#[derive(Default)]
+struct ReaderModeInner {
+    is_html: bool,
+    reader_url: Option<String>,
+    article: Option<Article>,
+}
+
+/// Reader mode state shared between the CEF handlers and the UI.
+#[derive(Clone, Default)]
+pub struct ReaderMode(Arc<Mutex<ReaderModeInner>>);
+
+impl Global for ReaderMode {}
+
+impl ReaderMode {
+    /// Check the content type of the page once the main frame has loaded.
+    pub fn on_load_end(&self, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+
+        self.0.lock().unwrap().is_html = false;
+
+        let script = CONTENT_TYPE_SCRIPT.replace("$PREFIX", CONTENT_TYPE_PREFIX);
+        if let Err(e) = frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error checking content type: {}", e);
+        }
+    }
+
+    /// Pick up a report from the injected scripts. Returns true if `message`
+    /// was one, so it can be kept out of the console.
+    pub fn on_console_message(&self, message: &str) -> bool {
+        if let Some(content_type) = message.strip_prefix(CONTENT_TYPE_PREFIX) {
+            self.0.lock().unwrap().is_html = content_type == "text/html";
+            return true;
+        }
+
+        let Some(json) = message.strip_prefix(ARTICLE_PREFIX) else {
+            return false;
+        };
+
+        match serde_json::from_str::<Option<Article>>(json) {
+            Ok(Some(article)) => self.0.lock().unwrap().article = Some(article),
+            Ok(None) => eprintln!("Reader mode couldn't find an article on the page"),
+            Err(e) => eprintln!("Error parsing article: {}", e),
+        }
+
+        true
+    }
+
+    /// Whether reader mode can be used on the current page.
+    pub fn is_available(&self) -> bool {
+        self.0.lock().unwrap().is_html
+    }
+
+    /// Whether the current page is the reader view.
+    pub fn is_active(&self, cx: &GpuiApp) -> bool {
+        let reader_url = self.0.lock().unwrap().reader_url.clone();
+
+        reader_url.is_some_and(|reader_url| {
+            cx.global::<BrowserState>()
+                .current_page()
+                .is_some_and(|(url, _)| url == reader_url)
+        })
+    }
+
+    pub fn take_article(&self) -> Option<Article> {
+        self.0.lock().unwrap().article.take()
+    }
+}
+
+/// Switch the current page to the reader view, or back to the original page.
+pub fn toggle(cx: &mut GpuiApp) {
+    let reader = cx.global::<ReaderMode>().clone();
+    let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+        return;
+    };
+
+    if reader.is_active(cx) {
+        if let Err(e) = browser.go_back() {
+            eprintln!("Error leaving reader mode: {}", e);
+        }
+
+        return;
+    }
+
+    if !reader.is_available() {
+        return;
+    }
+
+    let readability = match load_asset(cx, "readability.js") {
+        Some(readability) => readability,
+        None => return,
+    };
+
+    // Scope the extractor so injecting it twice doesn't redeclare the class.
+    let script = format!(
+        "(() => {{\n{}\n{}\n}})();",
+        readability,
+        EXTRACT_SCRIPT.replace("$PREFIX", ARTICLE_PREFIX)
+    );
+
+    match browser.get_main_frame() {
+        Ok(Some(frame)) => {
+            if let Err(e) = frame.execute_java_script(&script, "reader://readability.js", 0) {
+                eprintln!("Error running readability: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Error getting main frame: {}", e),
+    }
+}
+
+/// Popover of the reader mode page action.
+pub struct ReaderPopover;
+
+impl ReaderPopover {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for ReaderPopover {}
+
+impl Render for ReaderPopover {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let reader = cx.global::<ReaderMode>();
+        let active = reader.is_active(cx);
+        let available = reader.is_available();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .child(div().text_color(theme.text_secondary).child(if active {
+                t!("reader.showing")
+            } else if available {
+                t!("reader.available")
+            } else {
+                t!("reader.unavailable")
+            }))
+            .when(active || available, |this| {
+                this.child(
+                    button(
+                        "reader-toggle",
+                        if active {
+                            t!("reader.show_original")
+                        } else {
+                            t!("reader.show_reader")
+                        },
+                        true,
+                        cx,
+                    )
+                    .on_click(cx.listener(|_, _, _, cx| {
+                        toggle(cx);
+                        cx.emit(DismissEvent);
+                    })),
+                )
+            })
+    }
+}
+
+/// Show an extracted article in place of the page.
+pub fn show(cx: &mut GpuiApp, article: Article) {
+    let stylesheet = load_asset(cx, "reader.css").unwrap_or_default();
+    let byline = article
+        .byline
+        .map(|byline| format!("<p class=\"byline\">{}</p>", escape(&byline)))
+        .unwrap_or_default();
+    let html = format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{stylesheet}</style></head><body><article><h1>{title}</h1>{byline}{content}\
+         </article></body></html>",
+        title = escape(&article.title),
+        stylesheet = stylesheet,
+        byline = byline,
+        content = article.content,
+    );
+    let url = format!(
+        "data:text/html;charset=utf-8;base64,{}",
+        STANDARD.encode(html)
+    );
+
+    cx.global::<ReaderMode>().0.lock().unwrap().reader_url = Some(url.clone());
+    crate::navigate(cx, &url);
+}
+
+fn load_asset(cx: &GpuiApp, path: &str) -> Option<String> {
+    match cx.asset_source().load(path) {
+        Ok(Some(data)) => Some(String::from_utf8_lossy(&data).into_owned()),
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Error loading {}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+
+use cef_ui::CommandLine;
+use gpui::{div, prelude::*, px, App as GpuiApp, Div, SharedString, Stateful};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::t,
+    settings::{self, Settings},
+    theme::ActiveTheme,
+};
+
+/// One of the hardening options, with its share of the security score.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SandboxOption {
+    ProcessSandbox,
+    SiteIsolation,
+    BlockMixedContent,
+    DisableWebSql,
+}
+
+impl SandboxOption {
+    const ALL: [SandboxOption; 4] = [
+        SandboxOption::ProcessSandbox,
+        SandboxOption::SiteIsolation,
+        SandboxOption::BlockMixedContent,
+        SandboxOption::DisableWebSql,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            SandboxOption::ProcessSandbox => t!("sandbox.process_sandbox"),
+            SandboxOption::SiteIsolation => t!("sandbox.site_isolation"),
+            SandboxOption::BlockMixedContent => t!("sandbox.block_mixed_content"),
+            SandboxOption::DisableWebSql => t!("sandbox.disable_web_sql"),
+        }
+    }
+
+    /// Points the option adds to the score. They add up to 100.
+    fn weight(&self) -> u32 {
+        match self {
+            SandboxOption::ProcessSandbox => 40,
+            SandboxOption::SiteIsolation => 30,
+            SandboxOption::BlockMixedContent => 20,
+            SandboxOption::DisableWebSql => 10,
+        }
+    }
+}
+
+/// How locked down page processes are. Each option maps to Chromium switches,
+/// so changes take effect after a restart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxPolicy {
+    /// Run renderers in the OS sandbox.
+    pub process_sandbox: bool,
+    /// Give every site its own renderer process.
+    pub site_isolation: bool,
+    /// Block scripts and other active content loaded over HTTP on HTTPS pages.
+    pub block_mixed_content: bool,
+    pub disable_web_sql: bool,
+}
+
+impl SandboxPolicy {
+    fn get(&self, option: SandboxOption) -> bool {
+        match option {
+            SandboxOption::ProcessSandbox => self.process_sandbox,
+            SandboxOption::SiteIsolation => self.site_isolation,
+            SandboxOption::BlockMixedContent => self.block_mixed_content,
+            SandboxOption::DisableWebSql => self.disable_web_sql,
+        }
+    }
+
+    fn get_mut(&mut self, option: SandboxOption) -> &mut bool {
+        match option {
+            SandboxOption::ProcessSandbox => &mut self.process_sandbox,
+            SandboxOption::SiteIsolation => &mut self.site_isolation,
+            SandboxOption::BlockMixedContent => &mut self.block_mixed_content,
+            SandboxOption::DisableWebSql => &mut self.disable_web_sql,
+        }
+    }
+
+    /// From 0 to 100: the weights of the options turned on.
+    pub fn score(&self) -> u32 {
+        SandboxOption::ALL
+            .iter()
+            .filter(|option| self.get(**option))
+            .map(SandboxOption::weight)
+            .sum()
+    }
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            process_sandbox: true,
+            site_isolation: true,
+            block_mixed_content: true,
+            disable_web_sql: true,
+        }
+    }
+}
+
+/// Add the switches for `policy` to the browser process's command line. The
+/// process sandbox is also turned off through CEF's settings, which the
+/// sub-processes follow.
+pub fn configure(command_line: &CommandLine, policy: &SandboxPolicy) -> Result<()> {
+    if !policy.process_sandbox {
+        command_line.append_switch("no-sandbox")?;
+    }
+
+    match policy.site_isolation {
+        true => command_line.append_switch("site-per-process")?,
+        false => command_line.append_switch("disable-site-isolation-trials")?,
+    }
+
+    match policy.block_mixed_content {
+        true => command_line.append_switch("enable-strict-mixed-content-checking")?,
+        false => command_line.append_switch("allow-running-insecure-content")?,
+    }
+
+    if policy.disable_web_sql {
+        command_line.append_switch_with_value("disable-features", Some("WebSQLAccess"))?;
+    }
+
+    Ok(())
+}
+
+/// The security score in the toolbar, colored by how high it is.
+pub fn badge(cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+    let score = cx.global::<Settings>().sandbox.score();
+    let color = match score {
+        80.. => theme.success,
+        50.. => theme.warning,
+        _ => theme.error,
+    };
+
+    div()
+        .id("security-score")
+        .flex()
+        .items_center()
+        .h(px(18.))
+        .px_1p5()
+        .rounded_md()
+        .cursor_pointer()
+        .text_xs()
+        .bg(color.opacity(0.2))
+        .text_color(color)
+        .child(t!("sandbox.score", score = score))
+}
+
+/// The sandbox options, for the settings panel's "Security" section.
+pub fn settings(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+    let policy = cx.global::<Settings>().sandbox;
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .children(
+            SandboxOption::ALL
+                .into_iter()
+                .enumerate()
+                .map(|(i, option)| {
+                    let checked = policy.get(option);
+
+                    div()
+                        .id(("sandbox-option", i))
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .cursor_pointer()
+                        .child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .size(px(14.))
+                                .rounded_sm()
+                                .border_1()
+                                .when(checked, |this| {
+                                    this.border_color(theme.accent)
+                                        .bg(theme.accent)
+                                        .text_color(theme.text_on_accent)
+                                        .child("✓")
+                                })
+                                .when(!checked, |this| this.border_color(theme.border)),
+                        )
+                        .child(option.label())
+                        .on_click(move |_, window, cx| {
+                            settings::update(cx, |settings| {
+                                *settings.sandbox.get_mut(option) = !checked
+                            });
+                            window.refresh();
+                        })
+                }),
+        )
+        .child(
+            div()
+                .text_color(theme.text_disabled)
+                .child(t!("settings.restart_required")),
+        )
+}
@@ -0,0 +1,143 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::VecDeque,
+    fs::create_dir_all,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Local;
+use gpui::{App as GpuiApp, Global};
+use image::ImageFormat;
+use serde::Deserialize;
+
+use crate::BrowserState;
+
+/// Prefix of the console message the capture script reports bounds with.
+const ELEMENT_BOUNDS_PREFIX: &str = "__capture_element__";
+
+/// Measures the element matching `$SELECTOR` relative to the top level page
+/// and reports it through the console, since `execute_java_script` can't
+/// return a value.
+const CAPTURE_SCRIPT: &str = r#"(() => {
+  const element = document.querySelector($SELECTOR);
+  if (!element) return;
+  element.scrollIntoView({ block: "nearest", inline: "nearest" });
+  let { left: x, top: y, width, height } = element.getBoundingClientRect();
+  // Offset by the enclosing frames, as far as the same origin policy allows.
+  for (let view = window; view.frameElement; view = view.parent) {
+    const rect = view.frameElement.getBoundingClientRect();
+    x += rect.left;
+    y += rect.top;
+  }
+  console.log("$PREFIX" + JSON.stringify({ x, y, width, height, scale: window.devicePixelRatio }));
+})();"#;
+
+/// Bounds of a DOM element in CSS pixels of the top level page.
+#[derive(Deserialize)]
+pub struct ElementBounds {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    scale: f64,
+}
+
+/// Element bounds reported back by the capture script, waiting to be saved.
+#[derive(Clone, Default)]
+pub struct ElementCaptures(Arc<Mutex<VecDeque<ElementBounds>>>);
+
+impl Global for ElementCaptures {}
+
+impl ElementCaptures {
+    /// Pick up a report from the capture script. Returns true if `message`
+    /// was one, so it can be kept out of the console.
+    pub fn on_console_message(&self, message: &str) -> bool {
+        let Some(json) = message.strip_prefix(ELEMENT_BOUNDS_PREFIX) else {
+            return false;
+        };
+
+        match serde_json::from_str(json) {
+            Ok(bounds) => self.0.lock().unwrap().push_back(bounds),
+            Err(e) => eprintln!("Error parsing element bounds: {}", e),
+        }
+
+        true
+    }
+
+    pub fn take_pending(&self) -> Option<ElementBounds> {
+        self.0.lock().unwrap().pop_front()
+    }
+}
+
+/// Save the page as a PNG in the Pictures folder.
+pub fn save_screenshot(cx: &GpuiApp) -> Result<PathBuf> {
+    save(cx, None)
+}
+
+/// Save the part of the page covered by an element as a PNG.
+pub fn save_element(cx: &GpuiApp, bounds: &ElementBounds) -> Result<PathBuf> {
+    save(cx, Some(bounds))
+}
+
+fn save(cx: &GpuiApp, bounds: Option<&ElementBounds>) -> Result<PathBuf> {
+    let image = cx
+        .global::<BrowserState>()
+        .image
+        .as_ref()
+        .ok_or_else(|| anyhow!("The page hasn't been rendered yet"))?;
+    let mut pixels = image::load_from_memory(&image.bytes)?;
+
+    if let Some(bounds) = bounds {
+        let x = (bounds.x * bounds.scale).max(0.) as u32;
+        let y = (bounds.y * bounds.scale).max(0.) as u32;
+        let width = (bounds.width * bounds.scale) as u32;
+        let height = (bounds.height * bounds.scale) as u32;
+
+        pixels = pixels.crop_imm(x, y, width, height);
+    }
+
+    let dir = dirs::picture_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| anyhow!("Couldn't find the Pictures folder"))?;
+    create_dir_all(&dir)?;
+
+    let path = dir.join(
+        Local::now()
+            .format("Screenshot %Y-%m-%d at %H.%M.%S.png")
+            .to_string(),
+    );
+    pixels.save_with_format(&path, ImageFormat::Png)?;
+
+    Ok(path)
+}
+
+/// Ask every frame for the bounds of the element matching `selector`. Frames
+/// that have one report back through the display handler, see
+/// [`ElementCaptures::on_console_message`].
+pub fn capture_element(cx: &GpuiApp, selector: &str) {
+    let Some(browser) = cx.global::<BrowserState>().browser.as_ref() else {
+        return;
+    };
+    let script = CAPTURE_SCRIPT
+        .replace(
+            "$SELECTOR",
+            &serde_json::to_string(selector).unwrap_or_default(),
+        )
+        .replace("$PREFIX", ELEMENT_BOUNDS_PREFIX);
+
+    for identifier in browser.get_frame_identifiers().unwrap_or_default() {
+        let frame = match browser.get_frame_by_identifier(identifier) {
+            Ok(Some(frame)) => frame,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Error getting frame {}: {}", identifier, e);
+                continue;
+            }
+        };
+
+        if let Err(e) = frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error running capture script: {}", e);
+        }
+    }
+}
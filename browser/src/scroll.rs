@@ -0,0 +1,104 @@
+use cef_ui::{EventFlags, MouseEvent, Point};
+use gpui::{point, px, App as GpuiApp, Global, ScrollDelta, Window};
+
+use crate::{hang, settings::Settings, BrowserState};
+
+/// Pixels scrolled per line, for mice that scroll a line at a time.
+pub const LINE_HEIGHT: f32 = 40.;
+
+/// Share of the remaining distance scrolled on each frame, so the page eases
+/// out toward where the wheel left it.
+const EASING: f32 = 0.25;
+
+/// Scrolling still to be sent to the page by the running animation.
+pub struct ScrollAnimator {
+    /// Where the wheel was turned, in view coordinates.
+    position: Point,
+    /// Pixels left to scroll.
+    remaining: gpui::Point<f32>,
+    animating: bool,
+}
+
+impl Default for ScrollAnimator {
+    fn default() -> Self {
+        Self {
+            position: Point { x: 0, y: 0 },
+            remaining: point(0., 0.),
+            animating: false,
+        }
+    }
+}
+
+impl Global for ScrollAnimator {}
+
+/// Scroll the page under `position` by `delta`. Wheels that scroll in whole
+/// lines are animated there unless the user turned it off. Trackpads and
+/// other precise devices already scroll smoothly, so they're sent as they
+/// come.
+pub fn scroll_wheel(window: &mut Window, cx: &mut GpuiApp, position: Point, delta: &ScrollDelta) {
+    let smooth = cx.global::<Settings>().smooth_scrolling;
+    let pixels = delta.pixel_delta(px(LINE_HEIGHT));
+    let pixels = point(f32::from(pixels.x).round(), f32::from(pixels.y).round());
+
+    if !smooth || !matches!(delta, ScrollDelta::Lines(_)) {
+        send(cx, position, pixels.x as i32, pixels.y as i32);
+        return;
+    }
+
+    let animator = cx.global_mut::<ScrollAnimator>();
+    animator.position = position;
+    animator.remaining += pixels;
+
+    if !animator.animating {
+        animator.animating = true;
+        window.on_next_frame(step);
+    }
+}
+
+/// Scroll the page a step closer to the target, and schedule the next step
+/// until it's there.
+fn step(window: &mut Window, cx: &mut GpuiApp) {
+    let animator = cx.global_mut::<ScrollAnimator>();
+    let remaining = animator.remaining;
+    let delta = point(ease(remaining.x), ease(remaining.y));
+
+    animator.remaining = remaining - delta;
+    animator.animating = animator.remaining != point(0., 0.);
+
+    let position = animator.position;
+    if animator.animating {
+        window.on_next_frame(step);
+    }
+
+    send(cx, position, delta.x as i32, delta.y as i32);
+}
+
+/// The whole pixels to scroll this frame to cover `EASING` of `remaining`,
+/// and at least one, so the animation always finishes.
+fn ease(remaining: f32) -> f32 {
+    match (remaining * EASING).round() {
+        0. => remaining.clamp(-1., 1.),
+        delta => delta,
+    }
+}
+
+fn send(cx: &GpuiApp, position: Point, delta_x: i32, delta_y: i32) {
+    if delta_x == 0 && delta_y == 0 {
+        return;
+    }
+
+    let Some(host) = cx.global::<BrowserState>().host() else {
+        return;
+    };
+
+    let event = MouseEvent {
+        x: position.x,
+        y: position.y,
+        modifiers: EventFlags::None,
+    };
+
+    if let Err(e) = host.send_mouse_wheel_event(&event, delta_x, delta_y) {
+        eprintln!("Error scrolling page: {}", e);
+    }
+    hang::watch(cx);
+}
@@ -0,0 +1,434 @@
+use anyhow::{anyhow, Result};
+use std::{
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cef_ui::{
+    AuthCallback, CommandLine, Request, UrlRequest, UrlRequestClient, UrlRequestClientCallbacks,
+    UrlRequestFlags, UrlRequestStatus,
+};
+use gpui::{
+    div, prelude::*, px, svg, App as GpuiApp, Context, DismissEvent, Div, EventEmitter, Global,
+    SharedString, Stateful, Window,
+};
+use nix::{ifaddrs::getifaddrs, net::if_::InterfaceFlags};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    button::button, csp::CspInspector, locale::t, settings::Settings, theme::ActiveTheme,
+    BrowserState,
+};
+
+/// Echoes back the resolver that looked its own name up. The random label in
+/// front keeps resolvers from answering out of their cache.
+const LEAK_TEST_URL: &str = "http://{nonce}.edns.ip-api.com/json";
+
+/// Name prefixes of the tunnel interfaces VPN clients bring up.
+const VPN_INTERFACES: [&str; 6] = ["utun", "tun", "tap", "wg", "ppp", "ipsec"];
+
+/// Resolver pages look host names up with, over HTTPS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DohProvider {
+    /// The system's resolver, over plain DNS.
+    #[default]
+    Off,
+    Cloudflare,
+    Google,
+    /// The template in `Settings::secure_dns_template`.
+    Custom,
+}
+
+impl DohProvider {
+    pub const ALL: [DohProvider; 4] = [
+        DohProvider::Off,
+        DohProvider::Cloudflare,
+        DohProvider::Google,
+        DohProvider::Custom,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            DohProvider::Off => t!("secure_dns.off"),
+            DohProvider::Cloudflare => "Cloudflare".into(),
+            DohProvider::Google => "Google".into(),
+            DohProvider::Custom => t!("secure_dns.custom"),
+        }
+    }
+
+    /// The provider's URI template, for the ones built in.
+    fn template(&self) -> Option<&'static str> {
+        match self {
+            DohProvider::Cloudflare => Some("https://cloudflare-dns.com/dns-query"),
+            DohProvider::Google => Some("https://dns.google/dns-query{?dns}"),
+            DohProvider::Off | DohProvider::Custom => None,
+        }
+    }
+
+    /// The network the provider's resolvers are reported on by the leak test.
+    fn network(&self) -> Option<&'static str> {
+        match self {
+            DohProvider::Cloudflare => Some("cloudflare"),
+            DohProvider::Google => Some("google"),
+            DohProvider::Off | DohProvider::Custom => None,
+        }
+    }
+}
+
+/// The URI template of the resolver chosen in `settings`, if any.
+pub fn template(settings: &Settings) -> Option<String> {
+    match settings.secure_dns {
+        DohProvider::Custom => {
+            let template = settings.secure_dns_template.trim();
+            (!template.is_empty()).then(|| template.to_string())
+        }
+        provider => provider.template().map(String::from),
+    }
+}
+
+/// Send host name lookups to the resolver at `template`. Chromium only reads
+/// this switch at startup, so a new resolver applies after a restart.
+pub fn configure(command_line: &CommandLine, template: &str) -> Result<()> {
+    command_line.append_switch_with_value("dns-over-https-templates", Some(template))
+}
+
+/// Whether a VPN looks to be connected: a tunnel interface that's up with an
+/// IPv4 address. macOS keeps a few `utun` interfaces around for its own use,
+/// but those only have link-local IPv6 addresses.
+pub fn vpn_connected() -> bool {
+    let addresses = match getifaddrs() {
+        Ok(addresses) => addresses,
+        Err(e) => {
+            eprintln!("Error listing network interfaces: {}", e);
+            return false;
+        }
+    };
+
+    addresses.into_iter().any(|address| {
+        VPN_INTERFACES
+            .iter()
+            .any(|prefix| address.interface_name.starts_with(prefix))
+            && address.flags.contains(InterfaceFlags::IFF_UP)
+            && address
+                .address
+                .is_some_and(|address| address.as_sockaddr_in().is_some())
+    })
+}
+
+/// Where the DNS leak test is at.
+#[derive(Clone, Default)]
+pub enum LeakTest {
+    #[default]
+    NotRun,
+    Running,
+    Done {
+        /// Network and address of the resolver that did the lookup.
+        resolver: String,
+        address: String,
+        /// Whether the lookup went somewhere other than the chosen provider,
+        /// if that can be told.
+        leaked: Option<bool>,
+    },
+    Failed(String),
+}
+
+struct SecureDnsInner {
+    provider: DohProvider,
+    /// Template the browser was started with, if DoH is on.
+    template: Option<String>,
+    /// Whether DoH was turned off because a VPN was connected.
+    vpn: bool,
+    leak_test: LeakTest,
+    changed: bool,
+}
+
+/// Whether pages resolve host names over HTTPS in this run of the browser,
+/// and the result of the last leak test.
+#[derive(Clone)]
+pub struct SecureDns(Arc<Mutex<SecureDnsInner>>);
+
+impl Global for SecureDns {}
+
+impl SecureDns {
+    /// Decide on DoH for this run from `settings`. A connected VPN resolves
+    /// names its own way, so DoH stays off while there is one.
+    pub fn new(settings: &Settings) -> Self {
+        let template = template(settings);
+        let vpn = template.is_some() && vpn_connected();
+
+        Self(Arc::new(Mutex::new(SecureDnsInner {
+            provider: settings.secure_dns,
+            template: template.filter(|_| !vpn),
+            vpn,
+            leak_test: LeakTest::NotRun,
+            changed: false,
+        })))
+    }
+
+    pub fn template(&self) -> Option<String> {
+        self.0.lock().unwrap().template.clone()
+    }
+
+    /// Whether DoH was turned off for this run because a VPN was connected.
+    pub fn vpn(&self) -> bool {
+        self.0.lock().unwrap().vpn
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.0.lock().unwrap().template.is_some()
+    }
+
+    pub fn leak_test(&self) -> LeakTest {
+        self.0.lock().unwrap().leak_test.clone()
+    }
+
+    /// Look a fresh host name up and find out which resolver answered.
+    pub fn run_leak_test(&self) {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let url = LEAK_TEST_URL.replace("{nonce}", &format!("{:x}", nonce));
+
+        let request = Request::new();
+        let result = request
+            .set_url(&url)
+            .and_then(|_| request.set_method("GET"))
+            .and_then(|_| request.set_flags(UrlRequestFlags::DisableCache));
+        if let Err(e) = result {
+            self.finish_leak_test(LeakTest::Failed(e.to_string()));
+            return;
+        }
+
+        self.finish_leak_test(LeakTest::Running);
+        let client = UrlRequestClient::new(LeakTestClient {
+            secure_dns: self.clone(),
+            body: Vec::new(),
+        });
+        UrlRequest::new(request, client, None);
+    }
+
+    fn finish_leak_test(&self, leak_test: LeakTest) {
+        let mut inner = self.0.lock().unwrap();
+        inner.leak_test = leak_test;
+        inner.changed = true;
+    }
+
+    /// Whether the leak test moved on since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    fn result(&self, body: &[u8]) -> Result<LeakTest> {
+        let body: Value = serde_json::from_slice(body)?;
+        let dns = body
+            .get("dns")
+            .ok_or_else(|| anyhow!("No resolver in the response"))?;
+        let field = |name| dns.get(name).and_then(Value::as_str).unwrap_or_default();
+        let resolver = field("geo").to_string();
+
+        let inner = self.0.lock().unwrap();
+        let leaked = match inner.template {
+            Some(_) => inner
+                .provider
+                .network()
+                .map(|network| !resolver.to_lowercase().contains(network)),
+            None => None,
+        };
+
+        Ok(LeakTest::Done {
+            resolver,
+            address: field("ip").to_string(),
+            leaked,
+        })
+    }
+}
+
+struct LeakTestClient {
+    secure_dns: SecureDns,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for LeakTestClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let leak_test = match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) => self
+                .secure_dns
+                .result(&self.body)
+                .unwrap_or_else(|e| LeakTest::Failed(e.to_string())),
+            Ok(_) => LeakTest::Failed(t!("secure_dns.test_unreachable").to_string()),
+            Err(e) => LeakTest::Failed(e.to_string()),
+        };
+
+        self.secure_dns.finish_leak_test(leak_test);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// The "Secure DNS" shield in the toolbar, green while DoH is on.
+pub fn shield(active: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("secure-dns")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(
+            svg()
+                .path("shield.svg")
+                .size(px(14.0))
+                .text_color(if active { theme.success } else { theme.text }),
+        )
+}
+
+/// Popover below the toolbar telling how host names are resolved, with the
+/// DNS leak test.
+pub struct SecurityPanel;
+
+impl SecurityPanel {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for SecurityPanel {}
+
+impl Render for SecurityPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let secure_dns = cx.global::<SecureDns>().clone();
+        let leak_test = secure_dns.leak_test();
+
+        let status = match (secure_dns.template(), secure_dns.vpn()) {
+            (Some(template), _) => t!("secure_dns.on", template = template),
+            (None, true) => t!("secure_dns.off_for_vpn"),
+            (None, false) => t!("secure_dns.off_description"),
+        };
+
+        let result = match &leak_test {
+            LeakTest::NotRun => None,
+            LeakTest::Running => Some(div().child(t!("secure_dns.testing"))),
+            LeakTest::Done {
+                resolver,
+                address,
+                leaked,
+            } => Some(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .child(t!(
+                        "secure_dns.resolver",
+                        resolver = resolver,
+                        address = address
+                    ))
+                    .when_some(*leaked, |this, leaked| {
+                        this.child(
+                            div()
+                                .text_color(if leaked { theme.error } else { theme.success })
+                                .child(match leaked {
+                                    true => t!("secure_dns.leaking"),
+                                    false => t!("secure_dns.no_leak"),
+                                }),
+                        )
+                    }),
+            ),
+            LeakTest::Failed(error) => Some(
+                div()
+                    .text_color(theme.error)
+                    .child(t!("secure_dns.leak_test_failed", error = error)),
+            ),
+        };
+        let running = matches!(leak_test, LeakTest::Running);
+
+        let violations = cx
+            .global::<BrowserState>()
+            .browser
+            .as_ref()
+            .map(|browser| {
+                cx.global::<CspInspector>()
+                    .trusted_types_violations(browser)
+            })
+            .unwrap_or_default();
+        let badge_color = match violations {
+            0 => theme.success,
+            _ => theme.error,
+        };
+
+        div()
+            .absolute()
+            .top_0()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(320.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(div().text_sm().child(t!("secure_dns.title")))
+            .child(status)
+            .children(result)
+            .child(div().flex().child(
+                button("dns-leak-test", t!("secure_dns.run_leak_test"), false, cx).when(
+                    !running,
+                    |this| {
+                        this.on_click(cx.listener(move |_, _, _, cx| {
+                            secure_dns.run_leak_test();
+                            cx.notify();
+                        }))
+                    },
+                ),
+            ))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(t!("csp.trusted_types"))
+                    .child(
+                        div()
+                            .px_1p5()
+                            .rounded_md()
+                            .bg(badge_color.opacity(0.2))
+                            .text_color(badge_color)
+                            .child(match violations {
+                                0 => t!("csp.no_trusted_types_violations"),
+                                count => t!("csp.trusted_types_violations", count = count),
+                            }),
+                    ),
+            )
+    }
+}
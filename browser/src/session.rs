@@ -0,0 +1,494 @@
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::{Browser, Frame};
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, DismissEvent, EventEmitter, Global, Hsla, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button::button, favicons::FaviconCache, locale::t, tabs, tabs::TabManager, theme::ActiveTheme,
+};
+
+/// How often the open tabs are written out, so a crash loses little.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Prefix of the console message reporting the page's scroll position.
+const SCROLL_PREFIX: &str = "__session_scroll__";
+
+/// Reports the scroll position once the page settles after scrolling.
+const SCROLL_SCRIPT: &str = r#"(() => {
+  if (window.__sessionScroll) return;
+  window.__sessionScroll = true;
+
+  let timer;
+  addEventListener("scroll", () => {
+    clearTimeout(timer);
+    timer = setTimeout(() => console.log("$PREFIX" + scrollX + "," + scrollY), 250);
+  }, { passive: true });
+})();"#;
+
+/// A tab as it was when the session was saved.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SavedTab {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub scroll_x: f64,
+    #[serde(default)]
+    pub scroll_y: f64,
+    #[serde(default)]
+    pub zoom_level: f64,
+    /// Lets the tab show its icon from the cache before the page loads.
+    #[serde(default)]
+    pub favicon_url: Option<String>,
+}
+
+/// A tab group as it was when the session was saved.
+#[derive(Serialize, Deserialize)]
+struct SavedGroup {
+    name: String,
+    color: Hsla,
+    #[serde(default)]
+    collapsed: bool,
+    /// Indices of its tabs in `SessionFile::tabs`.
+    tabs: Vec<usize>,
+}
+
+/// A workspace as it was when the session was saved.
+#[derive(Serialize, Deserialize)]
+struct SavedWorkspace {
+    name: String,
+    /// Indices of its tabs in `SessionFile::tabs`.
+    tabs: Vec<usize>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SessionFile {
+    #[serde(default)]
+    active: usize,
+    #[serde(default)]
+    tabs: Vec<SavedTab>,
+    #[serde(default)]
+    groups: Vec<SavedGroup>,
+    #[serde(default)]
+    workspaces: Vec<SavedWorkspace>,
+}
+
+struct SessionInner {
+    path: PathBuf,
+    lock_path: PathBuf,
+    crashed: bool,
+    saved: Option<SessionFile>,
+    scroll: HashMap<i32, (f64, f64)>,
+    restoring: HashMap<i32, SavedTab>,
+}
+
+/// The open tabs, saved to a JSON file and restored on the next launch. A lock
+/// file that only exists while the browser runs tells a crash from a quit.
+#[derive(Clone)]
+pub struct Session(Arc<Mutex<SessionInner>>);
+
+impl Global for Session {}
+
+impl Session {
+    /// Load the previous session from `dir` and mark this one as running.
+    pub fn load(dir: PathBuf) -> Self {
+        let path = dir.join("session.json");
+        let lock_path = dir.join("session.lock");
+        let file = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse session {:?}: {}", path, e);
+                SessionFile::default()
+            }),
+            Err(_) => SessionFile::default(),
+        };
+
+        let crashed = lock_path.exists();
+        if let Err(e) = fs::write(&lock_path, std::process::id().to_string()) {
+            eprintln!("Failed to write session lock {:?}: {}", lock_path, e);
+        }
+
+        let saved = (!file.tabs.is_empty()).then_some(file);
+
+        Self(Arc::new(Mutex::new(SessionInner {
+            path,
+            lock_path,
+            crashed,
+            saved,
+            scroll: HashMap::new(),
+            restoring: HashMap::new(),
+        })))
+    }
+
+    /// Whether the previous session ended without a clean quit.
+    pub fn crashed(&self) -> bool {
+        self.0.lock().unwrap().crashed
+    }
+
+    /// Whether there are tabs from the previous session left to restore.
+    pub fn has_saved_tabs(&self) -> bool {
+        self.0.lock().unwrap().saved.is_some()
+    }
+
+    /// The previous session's tabs, if they haven't been restored yet.
+    pub fn saved_tabs(&self) -> Vec<SavedTab> {
+        self.0
+            .lock()
+            .unwrap()
+            .saved
+            .as_ref()
+            .map(|file| file.tabs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Forget the previous session's tabs without restoring them.
+    pub fn discard_saved_tabs(&self) {
+        self.0.lock().unwrap().saved = None;
+    }
+
+    /// Track the scroll position and put back the saved one once a restored
+    /// tab has loaded.
+    pub fn on_load_end(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+
+        let script = SCROLL_SCRIPT.replace("$PREFIX", SCROLL_PREFIX);
+        if let Err(e) = frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error tracking scroll position: {}", e);
+        }
+
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+        let Some(tab) = self.0.lock().unwrap().restoring.remove(&id) else {
+            return;
+        };
+
+        if let Err(e) = browser
+            .get_host()
+            .and_then(|host| host.set_zoom_level(tab.zoom_level))
+        {
+            eprintln!("Error restoring zoom level: {}", e);
+        }
+
+        let script = format!("window.scrollTo({}, {});", tab.scroll_x, tab.scroll_y);
+        if let Err(e) = frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error restoring scroll position: {}", e);
+        }
+    }
+
+    /// Pick up a scroll report from the injected script. Returns true if
+    /// `message` was one, so it can be kept out of the console.
+    pub fn on_console_message(&self, browser: &Browser, message: &str) -> bool {
+        let Some(position) = message.strip_prefix(SCROLL_PREFIX) else {
+            return false;
+        };
+
+        let position = position
+            .split_once(',')
+            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+
+        if let (Some(position), Ok(id)) = (position, browser.get_identifier()) {
+            self.0.lock().unwrap().scroll.insert(id, position);
+        }
+
+        true
+    }
+
+    /// Write the open tabs to disk. Incognito tabs aren't saved.
+    pub fn save(&self, cx: &GpuiApp) {
+        let manager = cx.global::<TabManager>();
+        let favicons = cx.global::<FaviconCache>();
+        let mut inner = self.0.lock().unwrap();
+        let (ids, tabs): (Vec<usize>, Vec<SavedTab>) = manager
+            .tabs()
+            .iter()
+            .filter(|tab| !tab.incognito)
+            .filter_map(|tab| {
+                let (url, title) = tab.page()?;
+                let id = tab.browser.get_identifier().ok();
+                let (scroll_x, scroll_y) = id
+                    .and_then(|id| inner.scroll.get(&id).copied())
+                    .unwrap_or_default();
+                let zoom_level = tab
+                    .browser
+                    .get_host()
+                    .and_then(|host| host.get_zoom_level())
+                    .unwrap_or_default();
+
+                let saved = SavedTab {
+                    url,
+                    title,
+                    scroll_x,
+                    scroll_y,
+                    zoom_level,
+                    favicon_url: favicons.url(&tab.browser),
+                };
+                Some((tab.id, saved))
+            })
+            .unzip();
+
+        let groups = manager
+            .groups()
+            .iter()
+            .map(|group| SavedGroup {
+                name: group.name.clone(),
+                color: group.color,
+                collapsed: group.collapsed,
+                tabs: group
+                    .tab_ids
+                    .iter()
+                    .filter_map(|id| ids.iter().position(|saved| saved == id))
+                    .collect(),
+            })
+            .filter(|group| !group.tabs.is_empty())
+            .collect();
+
+        let workspaces = manager
+            .workspaces()
+            .iter()
+            .map(|workspace| SavedWorkspace {
+                name: workspace.name.clone(),
+                tabs: workspace
+                    .tab_ids
+                    .iter()
+                    .filter_map(|id| ids.iter().position(|saved| saved == id))
+                    .collect(),
+            })
+            .filter(|workspace| !workspace.tabs.is_empty())
+            .collect();
+
+        // Saved tabs before the active one, as incognito tabs are left out.
+        let active = manager
+            .tabs()
+            .iter()
+            .take(manager.active())
+            .filter(|tab| !tab.incognito)
+            .count();
+        let file = SessionFile {
+            active,
+            tabs,
+            groups,
+            workspaces,
+        };
+
+        if let Err(e) = write(&inner.path, &file) {
+            eprintln!("Failed to save session {:?}: {}", inner.path, e);
+        }
+
+        // Tabs closed since the last save no longer need their position.
+        let open = manager
+            .tabs()
+            .iter()
+            .filter_map(|tab| tab.browser.get_identifier().ok())
+            .collect::<Vec<_>>();
+        inner.scroll.retain(|id, _| open.contains(id));
+    }
+
+    /// Save the session and mark the quit as clean.
+    pub fn end(&self, cx: &GpuiApp) {
+        self.save(cx);
+
+        let lock_path = self.0.lock().unwrap().lock_path.clone();
+        if let Err(e) = fs::remove_file(&lock_path) {
+            eprintln!("Failed to remove session lock {:?}: {}", lock_path, e);
+        }
+    }
+
+    fn take_saved_tabs(&self) -> Option<SessionFile> {
+        self.0.lock().unwrap().saved.take()
+    }
+
+    fn restoring(&self, browser: &Browser, tab: SavedTab) {
+        if let Ok(id) = browser.get_identifier() {
+            let mut inner = self.0.lock().unwrap();
+
+            inner.scroll.insert(id, (tab.scroll_x, tab.scroll_y));
+            inner.restoring.insert(id, tab);
+        }
+    }
+}
+
+fn write(path: &PathBuf, file: &SessionFile) -> Result<()> {
+    let json = serde_json::to_string_pretty(file)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Reopen the previous session's tabs, switching to the one that was active.
+pub fn restore(cx: &mut GpuiApp) {
+    let session = cx.global::<Session>().clone();
+    let Some(file) = session.take_saved_tabs() else {
+        return;
+    };
+
+    let first = cx.global::<TabManager>().tabs().len();
+    // Where each saved tab was reopened, if it could be.
+    let mut reopened = Vec::new();
+    for tab in file.tabs {
+        reopened.push(crate::new_browser(cx, &tab.url).map(|browser| {
+            if let Some(favicon_url) = &tab.favicon_url {
+                cx.global::<FaviconCache>().restoring(&browser, favicon_url);
+            }
+            session.restoring(&browser, tab);
+            tabs::add(cx, browser, false);
+            cx.global::<TabManager>().tabs().len() - 1
+        }));
+    }
+
+    let manager = cx.global_mut::<TabManager>();
+    for group in file.groups {
+        let indices = group
+            .tabs
+            .iter()
+            .filter_map(|&tab| reopened.get(tab).copied().flatten())
+            .collect::<Vec<_>>();
+        manager.restore_group(group.name, group.color, group.collapsed, &indices);
+    }
+    for workspace in file.workspaces {
+        let indices = workspace
+            .tabs
+            .iter()
+            .filter_map(|&tab| reopened.get(tab).copied().flatten())
+            .collect::<Vec<_>>();
+        manager.restore_workspace(workspace.name, &indices);
+    }
+
+    tabs::activate(cx, first + file.active);
+}
+
+/// Save the session every so often while the browser runs.
+pub fn autosave(cx: &mut GpuiApp) {
+    cx.spawn(async move |cx| loop {
+        cx.background_executor().timer(AUTOSAVE_INTERVAL).await;
+
+        let saved = cx.update(|cx| {
+            let session = cx.global::<Session>().clone();
+            session.save(cx);
+        });
+
+        if saved.is_err() {
+            break;
+        }
+    })
+    .detach();
+}
+
+/// Asks whether to bring back the tabs of a session that ended unexpectedly,
+/// listing them. Declining starts fresh.
+pub struct RestoreSessionDialog {
+    tabs: Vec<SavedTab>,
+}
+
+impl RestoreSessionDialog {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            tabs: cx.global::<Session>().saved_tabs(),
+        }
+    }
+
+    fn decide(&mut self, accept: bool, cx: &mut Context<Self>) {
+        if accept {
+            restore(cx);
+        } else {
+            cx.global::<Session>().discard_saved_tabs();
+        }
+
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for RestoreSessionDialog {}
+
+impl Render for RestoreSessionDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let rows = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let title = match tab.title.is_empty() {
+                    true => tab.url.clone(),
+                    false => tab.title.clone(),
+                };
+
+                div()
+                    .flex()
+                    .flex_col()
+                    .py_1p5()
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .child(div().overflow_hidden().text_ellipsis().child(title))
+                    .child(
+                        div()
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.text_muted)
+                            .child(tab.url.clone()),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(400.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("session.title")))
+                    .child(
+                        div()
+                            .text_color(theme.text_secondary)
+                            .child(t!("session.crashed")),
+                    )
+                    .child(
+                        div()
+                            .id("session-tabs")
+                            .flex()
+                            .flex_col()
+                            .max_h(px(240.))
+                            .overflow_y_scroll()
+                            .children(rows),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("session-start-fresh", t!("session.start_fresh"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.decide(false, cx))),
+                            )
+                            .child(
+                                button("session-restore", t!("session.restore"), true, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.decide(true, cx))),
+                            ),
+                    ),
+            )
+    }
+}
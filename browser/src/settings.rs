@@ -0,0 +1,1115 @@
+use anyhow::Result;
+use std::{fs, path::PathBuf};
+
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, DismissEvent, Div, Entity, EventEmitter, Global,
+    PathPromptOptions, SharedString, Subscription, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    adblock::AdBlock,
+    autofill,
+    button::button,
+    extensions,
+    lifecycle::LifecyclePanel,
+    locale::{self, t},
+    mailto::MailAction,
+    mouse_gestures::{self, MouseGestureSettings},
+    passwords::SavedPasswords,
+    popups::{PopupPolicy, Popups},
+    print::{Orientation, PaperSize},
+    proxy::ProxyMode,
+    sandbox::{self, SandboxPolicy},
+    secure_dns::DohProvider,
+    shortcuts::{ShortcutsConfig, ShortcutsPanel},
+    site_isolation::SiteIsolationPolicy,
+    spellcheck::SpellCheckLanguage,
+    telemetry,
+    text_input::{TextInput, TextInputEvent},
+    theme::{self, ActiveTheme, ThemeMode},
+    transitions::TransitionStyle,
+    translate, user_scripts, BrowserState,
+};
+
+/// Zoom levels offered for pages, in percent.
+const ZOOM_LEVELS: [u32; 6] = [75, 90, 100, 110, 125, 150];
+
+/// Search engines offered, as URL templates with `%s` for the query.
+const SEARCH_ENGINES: [(&str, &str); 3] = [
+    ("https://www.google.com/search?q=%s", "Google"),
+    ("https://duckduckgo.com/?q=%s", "DuckDuckGo"),
+    ("https://www.bing.com/search?q=%s", "Bing"),
+];
+
+/// Languages pages and selections can be translated into, as the model is
+/// asked for them, with their names as shown.
+const TRANSLATION_LANGUAGES: [(&str, &str); 6] = [
+    ("English", "English"),
+    ("Chinese", "中文"),
+    ("Spanish", "Español"),
+    ("French", "Français"),
+    ("German", "Deutsch"),
+    ("Japanese", "日本語"),
+];
+
+/// Swipe distances offered for going back or forward, in pixels, with the
+/// keys of their names.
+const GESTURE_THRESHOLDS: [(u32, &str); 3] = [
+    (60, "settings.swipe_short"),
+    (120, "settings.swipe_medium"),
+    (200, "settings.swipe_long"),
+];
+
+/// User preferences persisted to a TOML file. Not to be confused with CEF's
+/// `Settings`, which configures CEF itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub homepage: String,
+    /// URL searches are sent to, with `%s` standing for the query.
+    pub search_engine: String,
+    /// Zoom for pages, in percent.
+    pub default_zoom: u32,
+    pub popup_policy: PopupPolicy,
+    /// What clicking an email link does.
+    pub mail_action: MailAction,
+    /// Web mail page `MailAction::WebMail` opens, with `%s` standing for the
+    /// escaped `mailto:` URL.
+    pub webmail_url: String,
+    /// Language pages and selections are translated into.
+    pub translation_language: String,
+    /// Chat completions endpoint of the llama.cpp server translating pages.
+    pub translation_endpoint: String,
+    /// Domains allowed to autoplay media with sound.
+    pub autoplay_allowed: Vec<String>,
+    pub ad_block: bool,
+    /// Whether wheel scrolling eases toward its target instead of jumping.
+    pub smooth_scrolling: bool,
+    /// How far to swipe sideways on a trackpad to go back or forward, in
+    /// pixels.
+    pub gesture_threshold: u32,
+    /// How the page going back or forward replaces the one before it.
+    pub page_transition: TransitionStyle,
+    pub download_path: PathBuf,
+    pub theme: ThemeMode,
+    /// Locale the browser chrome's strings are in, or `None` to follow the
+    /// OS's.
+    pub language: Option<String>,
+    pub restore_session: bool,
+    pub paper_size: PaperSize,
+    pub orientation: Orientation,
+    pub spellcheck_language: SpellCheckLanguage,
+    /// Resolver for DNS over HTTPS.
+    pub secure_dns: DohProvider,
+    /// URI template of the resolver used when `secure_dns` is `Custom`.
+    pub secure_dns_template: String,
+    pub proxy: ProxyMode,
+    /// `host:port` of the proxy used when `proxy` is `Http` or `Socks5`.
+    pub proxy_server: String,
+    /// Script used when `proxy` is `Pac`.
+    pub proxy_pac_url: String,
+    /// Which tabs share cookies, cache and storage. Applies to tabs opened
+    /// after it changes.
+    pub site_isolation: SiteIsolationPolicy,
+    /// Folder holding unpacked extensions, one per subfolder.
+    pub extensions_path: PathBuf,
+    /// Extensions in `extensions_path` the user turned off.
+    pub disabled_extensions: Vec<PathBuf>,
+    /// Where "Report broken site" sends reports.
+    pub webcompat_endpoint: String,
+    /// Whether crash reports may be sent.
+    pub telemetry_enabled: bool,
+    /// Whether the user was asked about crash reports yet.
+    pub telemetry_asked: bool,
+    pub mouse_gestures: MouseGestureSettings,
+    /// Hardening of page processes. Takes effect after a restart.
+    pub sandbox: SandboxPolicy,
+    /// Kept last: TOML tables have to follow the plain values.
+    pub shortcuts: ShortcutsConfig,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            homepage: String::from("https://www.google.com"),
+            search_engine: String::from(SEARCH_ENGINES[0].0),
+            default_zoom: 100,
+            popup_policy: PopupPolicy::default(),
+            mail_action: MailAction::default(),
+            webmail_url: String::from("https://mail.google.com/mail/?extsrc=mailto&url=%s"),
+            translation_language: String::from(TRANSLATION_LANGUAGES[0].0),
+            translation_endpoint: String::from("http://127.0.0.1:8080/v1/chat/completions"),
+            autoplay_allowed: Vec::new(),
+            ad_block: false,
+            smooth_scrolling: true,
+            gesture_threshold: 120,
+            page_transition: TransitionStyle::default(),
+            download_path: dirs::download_dir().unwrap_or_default(),
+            theme: ThemeMode::default(),
+            language: None,
+            restore_session: true,
+            paper_size: PaperSize::default(),
+            orientation: Orientation::default(),
+            spellcheck_language: SpellCheckLanguage::default(),
+            secure_dns: DohProvider::default(),
+            secure_dns_template: String::new(),
+            proxy: ProxyMode::default(),
+            proxy_server: String::new(),
+            proxy_pac_url: String::new(),
+            site_isolation: SiteIsolationPolicy::default(),
+            extensions_path: crate::get_root_cache_dir()
+                .map(|dir| dir.join("extensions"))
+                .unwrap_or_default(),
+            disabled_extensions: Vec::new(),
+            webcompat_endpoint: String::from("https://webcompat.com/api/2/issues/"),
+            telemetry_enabled: false,
+            telemetry_asked: false,
+            mouse_gestures: MouseGestureSettings::default(),
+            sandbox: SandboxPolicy::default(),
+            shortcuts: ShortcutsConfig::default(),
+            path: PathBuf::new(),
+        }
+    }
+}
+
+impl Global for Settings {}
+
+impl Settings {
+    /// Load settings from disk, using the defaults if the file is missing or invalid.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Failed to parse settings {:?}: {}", path, e);
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+
+        Self { path, ..settings }
+    }
+
+    fn save(&self) -> Result<()> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(&self.path, contents)?;
+        Ok(())
+    }
+
+    /// The URL searching for `query` with the search engine goes to.
+    pub fn search_url(&self, query: &str) -> String {
+        self.search_engine.replace(
+            "%s",
+            &url::form_urlencoded::byte_serialize(query.as_bytes()).collect::<String>(),
+        )
+    }
+
+    /// The CEF zoom level for `default_zoom`. CEF zooms in 20% steps, with 0
+    /// at 100%.
+    pub fn zoom_level(&self) -> f64 {
+        (self.default_zoom as f64 / 100.).ln() / 1.2f64.ln()
+    }
+}
+
+/// Load settings from `path` and hand the ones CEF handlers need to their
+/// stores.
+pub fn init(cx: &mut GpuiApp, path: PathBuf) {
+    let settings = Settings::load(path);
+
+    cx.global::<Popups>().set_policy(settings.popup_policy);
+    cx.global::<AdBlock>().set_enabled(settings.ad_block);
+    cx.set_global(settings);
+    theme::init(cx);
+    locale::init(cx);
+}
+
+/// Change settings, save them and apply whatever changed right away.
+pub fn update(cx: &mut GpuiApp, f: impl FnOnce(&mut Settings)) {
+    let old = cx.global::<Settings>().clone();
+    let settings = cx.global_mut::<Settings>();
+    f(settings);
+
+    if let Err(e) = settings.save() {
+        eprintln!("Failed to save settings {:?}: {}", settings.path, e);
+    }
+
+    let settings = settings.clone();
+    cx.global::<Popups>().set_policy(settings.popup_policy);
+
+    if settings.ad_block != old.ad_block {
+        cx.global::<AdBlock>().set_enabled(settings.ad_block);
+
+        // Reload so the page picks up, or drops, the blocked requests.
+        if let Some(browser) = cx.global::<BrowserState>().browser.clone() {
+            if let Err(e) = browser.reload() {
+                eprintln!("Error reloading page: {}", e);
+            }
+        }
+    }
+
+    if settings.default_zoom != old.default_zoom {
+        if let Some(host) = cx.global::<BrowserState>().host() {
+            if let Err(e) = host.set_zoom_level(settings.zoom_level()) {
+                eprintln!("Error setting zoom level: {}", e);
+            }
+        }
+    }
+
+    if settings.extensions_path != old.extensions_path {
+        extensions::load_all(cx);
+    }
+
+    if settings.theme != old.theme {
+        theme::init(cx);
+    }
+
+    if settings.language != old.language {
+        locale::init(cx);
+    }
+
+    if settings.telemetry_enabled != old.telemetry_enabled {
+        telemetry::init(cx);
+    }
+}
+
+/// Modal for changing browser preferences.
+pub struct SettingsPanel {
+    homepage: Entity<TextInput>,
+    secure_dns_template: Entity<TextInput>,
+    proxy_server: Entity<TextInput>,
+    proxy_pac_url: Entity<TextInput>,
+    webmail_url: Entity<TextInput>,
+    translation_endpoint: Entity<TextInput>,
+    shortcuts: Option<(Entity<ShortcutsPanel>, Subscription)>,
+    lifecycle: Option<(Entity<LifecyclePanel>, Subscription)>,
+    saved_passwords: Entity<SavedPasswords>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SettingsPanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let homepage = cx.global::<Settings>().homepage.clone();
+        let homepage = cx.new(|cx| TextInput::new(homepage, cx));
+        let template = cx.global::<Settings>().secure_dns_template.clone();
+        let secure_dns_template = cx.new(|cx| TextInput::new(template, cx));
+        let server = cx.global::<Settings>().proxy_server.clone();
+        let proxy_server = cx.new(|cx| TextInput::new(server, cx));
+        let pac_url = cx.global::<Settings>().proxy_pac_url.clone();
+        let proxy_pac_url = cx.new(|cx| TextInput::new(pac_url, cx));
+        let webmail = cx.global::<Settings>().webmail_url.clone();
+        let webmail_url = cx.new(|cx| TextInput::new(webmail, cx));
+        let endpoint = cx.global::<Settings>().translation_endpoint.clone();
+        let translation_endpoint = cx.new(|cx| TextInput::new(endpoint, cx));
+
+        let subscriptions = vec![
+            cx.subscribe(&homepage, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_homepage(cx),
+                TextInputEvent::Cancel => {}
+            }),
+            cx.subscribe(&secure_dns_template, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_secure_dns_template(cx),
+                TextInputEvent::Cancel => {}
+            }),
+            cx.subscribe(&proxy_server, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_proxy_server(cx),
+                TextInputEvent::Cancel => {}
+            }),
+            cx.subscribe(&proxy_pac_url, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_proxy_pac_url(cx),
+                TextInputEvent::Cancel => {}
+            }),
+            cx.subscribe(&webmail_url, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_webmail_url(cx),
+                TextInputEvent::Cancel => {}
+            }),
+            cx.subscribe(&translation_endpoint, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.save_translation_endpoint(cx),
+                TextInputEvent::Cancel => {}
+            }),
+        ];
+
+        Self {
+            homepage,
+            secure_dns_template,
+            proxy_server,
+            proxy_pac_url,
+            webmail_url,
+            translation_endpoint,
+            shortcuts: None,
+            lifecycle: None,
+            saved_passwords: cx.new(SavedPasswords::new),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn open_shortcuts(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = cx.new(|cx| ShortcutsPanel::new(window, cx));
+        let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+            this.shortcuts = None;
+            cx.notify();
+        });
+
+        self.shortcuts = Some((panel, subscription));
+        cx.notify();
+    }
+
+    fn open_lifecycle(&mut self, cx: &mut Context<Self>) {
+        let panel = cx.new(LifecyclePanel::new);
+        let subscription = cx.subscribe(&panel, |this, _, _: &DismissEvent, cx| {
+            this.lifecycle = None;
+            cx.notify();
+        });
+
+        self.lifecycle = Some((panel, subscription));
+        cx.notify();
+    }
+
+    fn save_homepage(&mut self, cx: &mut Context<Self>) {
+        let homepage = self.homepage.read(cx).text().trim().to_string();
+
+        if !homepage.is_empty() {
+            update(cx, |settings| settings.homepage = homepage);
+        }
+    }
+
+    fn save_secure_dns_template(&mut self, cx: &mut Context<Self>) {
+        let template = self.secure_dns_template.read(cx).text().trim().to_string();
+        update(cx, |settings| settings.secure_dns_template = template);
+    }
+
+    fn save_proxy_server(&mut self, cx: &mut Context<Self>) {
+        let server = self.proxy_server.read(cx).text().trim().to_string();
+        update(cx, |settings| settings.proxy_server = server);
+    }
+
+    fn save_proxy_pac_url(&mut self, cx: &mut Context<Self>) {
+        let pac_url = self.proxy_pac_url.read(cx).text().trim().to_string();
+        update(cx, |settings| settings.proxy_pac_url = pac_url);
+    }
+
+    fn save_webmail_url(&mut self, cx: &mut Context<Self>) {
+        let webmail_url = self.webmail_url.read(cx).text().trim().to_string();
+
+        if !webmail_url.is_empty() {
+            update(cx, |settings| settings.webmail_url = webmail_url);
+        }
+    }
+
+    fn save_translation_endpoint(&mut self, cx: &mut Context<Self>) {
+        let endpoint = self.translation_endpoint.read(cx).text().trim().to_string();
+
+        if translate::is_local_endpoint(&endpoint) {
+            update(cx, |settings| settings.translation_endpoint = endpoint);
+        }
+    }
+
+    fn choose_download_path(&mut self, cx: &mut Context<Self>) {
+        self.choose_folder(cx, |settings, path| settings.download_path = path);
+    }
+
+    fn choose_extensions_path(&mut self, cx: &mut Context<Self>) {
+        self.choose_folder(cx, |settings, path| settings.extensions_path = path);
+    }
+
+    /// Ask for a folder and store it with `set`.
+    fn choose_folder(
+        &mut self,
+        cx: &mut Context<Self>,
+        set: impl FnOnce(&mut Settings, PathBuf) + 'static,
+    ) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(t!("settings.choose")),
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(mut paths))) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.pop() else {
+                return;
+            };
+
+            cx.update(|cx| update(cx, |settings| set(settings, path)))
+                .ok();
+            this.update(cx, |_, cx| cx.notify()).ok();
+        })
+        .detach();
+    }
+}
+
+/// A labelled group of controls.
+fn section(label: SharedString, content: impl IntoElement, cx: &GpuiApp) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(div().text_color(cx.theme().text_secondary).child(label))
+        .child(content)
+}
+
+impl EventEmitter<DismissEvent> for SettingsPanel {}
+
+impl Render for SettingsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let settings = cx.global::<Settings>().clone();
+        let download_path: SharedString = settings.download_path.display().to_string().into();
+        let extensions_path: SharedString = settings.extensions_path.display().to_string().into();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(440.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("settings.title")))
+                    .child(section(t!("settings.homepage"), self.homepage.clone(), cx))
+                    .child(section(
+                        t!("settings.search_engine"),
+                        div()
+                            .flex()
+                            .gap_2()
+                            .children(SEARCH_ENGINES.map(|(template, label)| {
+                                button(label, label, template == settings.search_engine, cx)
+                                    .on_click(cx.listener(move |_, _, _, cx| {
+                                        update(cx, |settings| {
+                                            settings.search_engine = template.to_string()
+                                        });
+                                        cx.notify();
+                                    }))
+                            })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.on_startup"),
+                        div().flex().gap_2().children(
+                            [
+                                (true, t!("settings.restore_tabs")),
+                                (false, t!("settings.open_homepage")),
+                            ]
+                            .map(|(restore, label)| {
+                                button(
+                                    ("settings-restore-session", restore as usize),
+                                    label,
+                                    restore == settings.restore_session,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.restore_session = restore);
+                                        cx.notify();
+                                    },
+                                ))
+                            }),
+                        ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.default_zoom"),
+                        div().flex().gap_2().children(ZOOM_LEVELS.map(|zoom| {
+                            button(
+                                ("zoom", zoom as usize),
+                                format!("{}%", zoom),
+                                zoom == settings.default_zoom,
+                                cx,
+                            )
+                            .on_click(cx.listener(
+                                move |_, _, _, cx| {
+                                    update(cx, |settings| settings.default_zoom = zoom);
+                                    cx.notify();
+                                },
+                            ))
+                        })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.popups"),
+                        div()
+                            .flex()
+                            .gap_2()
+                            .children(PopupPolicy::ALL.map(|policy| {
+                                button(
+                                    policy.label(),
+                                    policy.label(),
+                                    policy == settings.popup_policy,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.popup_policy = policy);
+                                        cx.notify();
+                                    },
+                                ))
+                            })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.email_links"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().gap_2().children(MailAction::ALL.map(|action| {
+                                button(
+                                    action.label(),
+                                    action.label(),
+                                    action == settings.mail_action,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.mail_action = action);
+                                        cx.notify();
+                                    },
+                                ))
+                            })))
+                            .when(settings.mail_action == MailAction::WebMail, |this| {
+                                this.child(self.webmail_url.clone()).child(
+                                    div()
+                                        .text_color(theme.text_disabled)
+                                        .child(t!("settings.webmail_url_description")),
+                                )
+                            }),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.translate_to"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().flex_wrap().gap_2().children(
+                                TRANSLATION_LANGUAGES.map(|(language, label)| {
+                                    button(
+                                        label,
+                                        label,
+                                        language == settings.translation_language,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| {
+                                                settings.translation_language = language.to_string()
+                                            });
+                                            cx.notify();
+                                        },
+                                    ))
+                                }),
+                            ))
+                            .child(self.translation_endpoint.clone())
+                            .child(
+                                div()
+                                    .text_color(theme.text_disabled)
+                                    .child(t!("settings.translation_endpoint_description")),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.autoplay"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .when(settings.autoplay_allowed.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .text_color(theme.text_disabled)
+                                        .child(t!("settings.autoplay_description")),
+                                )
+                            })
+                            .children(settings.autoplay_allowed.iter().enumerate().map(
+                                |(index, domain)| {
+                                    let domain = domain.clone();
+
+                                    div()
+                                        .flex()
+                                        .items_center()
+                                        .gap_2()
+                                        .child(
+                                            div()
+                                                .flex_1()
+                                                .min_w(px(0.))
+                                                .overflow_hidden()
+                                                .text_ellipsis()
+                                                .child(domain.clone()),
+                                        )
+                                        .child(
+                                            button(
+                                                ("autoplay-remove", index),
+                                                t!("settings.remove"),
+                                                false,
+                                                cx,
+                                            )
+                                            .on_click(
+                                                cx.listener(move |_, _, _, cx| {
+                                                    update(cx, |settings| {
+                                                        settings
+                                                            .autoplay_allowed
+                                                            .retain(|allowed| *allowed != domain)
+                                                    });
+                                                    cx.notify();
+                                                }),
+                                            ),
+                                        )
+                                },
+                            )),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.ads"),
+                        div().flex().gap_2().children(
+                            [
+                                (true, t!("settings.block_ads")),
+                                (false, t!("settings.allow_ads")),
+                            ]
+                            .map(|(block, label)| {
+                                button(
+                                    ("settings-ad-block", block as usize),
+                                    label,
+                                    block == settings.ad_block,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.ad_block = block);
+                                        cx.notify();
+                                    },
+                                ))
+                            }),
+                        ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.crash_reports"),
+                        div().flex().gap_2().children(
+                            [
+                                (true, t!("settings.send_crash_reports")),
+                                (false, t!("settings.dont_send_crash_reports")),
+                            ]
+                            .map(|(enabled, label)| {
+                                button(
+                                    ("settings-telemetry", enabled as usize),
+                                    label,
+                                    enabled == settings.telemetry_enabled,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| {
+                                            settings.telemetry_asked = true;
+                                            settings.telemetry_enabled = enabled;
+                                        });
+                                        cx.notify();
+                                    },
+                                ))
+                            }),
+                        ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.scrolling"),
+                        div().flex().gap_2().children(
+                            [
+                                (true, t!("settings.smooth")),
+                                (false, t!("settings.instant")),
+                            ]
+                            .map(|(smooth, label)| {
+                                button(
+                                    ("settings-smooth-scrolling", smooth as usize),
+                                    label,
+                                    smooth == settings.smooth_scrolling,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.smooth_scrolling = smooth);
+                                        cx.notify();
+                                    },
+                                ))
+                            }),
+                        ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.swipe"),
+                        div().flex().gap_2().children(GESTURE_THRESHOLDS.map(
+                            |(threshold, label)| {
+                                button(
+                                    label,
+                                    t!(label),
+                                    threshold == settings.gesture_threshold,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| {
+                                            settings.gesture_threshold = threshold
+                                        });
+                                        cx.notify();
+                                    },
+                                ))
+                            },
+                        )),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.mouse_gestures"),
+                        mouse_gestures::settings(cx),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.back_and_forward"),
+                        div()
+                            .flex()
+                            .gap_2()
+                            .children(TransitionStyle::ALL.map(|style| {
+                                button(
+                                    style.label(),
+                                    style.label(),
+                                    style == settings.page_transition,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        update(cx, |settings| settings.page_transition = style);
+                                        cx.notify();
+                                    },
+                                ))
+                            })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.downloads"),
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w(px(0.))
+                                    .overflow_hidden()
+                                    .text_ellipsis()
+                                    .child(download_path),
+                            )
+                            .child(
+                                button("settings-download-path", t!("settings.change"), false, cx)
+                                    .on_click(
+                                        cx.listener(|this, _, _, cx| this.choose_download_path(cx)),
+                                    ),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.saved_form_data"),
+                        autofill::saved_data(cx),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.passwords"),
+                        self.saved_passwords.clone(),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.extensions"),
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w(px(0.))
+                                    .overflow_hidden()
+                                    .text_ellipsis()
+                                    .child(extensions_path),
+                            )
+                            .child(
+                                button(
+                                    "settings-extensions-path",
+                                    t!("settings.change"),
+                                    false,
+                                    cx,
+                                )
+                                .on_click(
+                                    cx.listener(|this, _, _, cx| this.choose_extensions_path(cx)),
+                                ),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.user_scripts"),
+                        user_scripts::active_scripts(cx),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.printing"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_2()
+                            .child(div().flex().gap_2().children(PaperSize::ALL.map(
+                                |paper_size| {
+                                    button(
+                                        paper_size.label(),
+                                        paper_size.label(),
+                                        paper_size == settings.paper_size,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| settings.paper_size = paper_size);
+                                            cx.notify();
+                                        },
+                                    ))
+                                },
+                            )))
+                            .child(div().flex().gap_2().children(Orientation::ALL.map(
+                                |orientation| {
+                                    button(
+                                        orientation.label(),
+                                        orientation.label(),
+                                        orientation == settings.orientation,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| {
+                                                settings.orientation = orientation
+                                            });
+                                            cx.notify();
+                                        },
+                                    ))
+                                },
+                            ))),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.spell_check"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().flex_wrap().gap_2().children(
+                                SpellCheckLanguage::ALL.map(|language| {
+                                    button(
+                                        language.label(),
+                                        language.label(),
+                                        language == settings.spellcheck_language,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| {
+                                                settings.spellcheck_language = language
+                                            });
+                                            cx.notify();
+                                        },
+                                    ))
+                                }),
+                            ))
+                            .child(
+                                div()
+                                    .text_color(theme.text_disabled)
+                                    .child(t!("settings.restart_required")),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.secure_dns"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().gap_2().children(DohProvider::ALL.map(
+                                |provider| {
+                                    button(
+                                        provider.label(),
+                                        provider.label(),
+                                        provider == settings.secure_dns,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| settings.secure_dns = provider);
+                                            cx.notify();
+                                        },
+                                    ))
+                                },
+                            )))
+                            .when(settings.secure_dns == DohProvider::Custom, |this| {
+                                this.child(self.secure_dns_template.clone())
+                            })
+                            .child(
+                                div()
+                                    .text_color(theme.text_disabled)
+                                    .child(t!("settings.restart_required")),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.proxy"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().gap_2().children(ProxyMode::ALL.map(|mode| {
+                                button(mode.label(), mode.label(), mode == settings.proxy, cx)
+                                    .on_click(cx.listener(move |_, _, _, cx| {
+                                        update(cx, |settings| settings.proxy = mode);
+                                        cx.notify();
+                                    }))
+                            })))
+                            .when(
+                                matches!(settings.proxy, ProxyMode::Http | ProxyMode::Socks5),
+                                |this| this.child(self.proxy_server.clone()),
+                            )
+                            .when(settings.proxy == ProxyMode::Pac, |this| {
+                                this.child(self.proxy_pac_url.clone())
+                            })
+                            .child(
+                                div()
+                                    .text_color(theme.text_disabled)
+                                    .child(t!("settings.restart_required")),
+                            ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.site_isolation"),
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(div().flex().gap_2().children(SiteIsolationPolicy::ALL.map(
+                                |policy| {
+                                    button(
+                                        policy.label(),
+                                        policy.label(),
+                                        policy == settings.site_isolation,
+                                        cx,
+                                    )
+                                    .on_click(cx.listener(
+                                        move |_, _, _, cx| {
+                                            update(cx, |settings| settings.site_isolation = policy);
+                                            cx.notify();
+                                        },
+                                    ))
+                                },
+                            )))
+                            .child(
+                                div()
+                                    .text_color(theme.text_disabled)
+                                    .child(t!("settings.site_isolation_description")),
+                            ),
+                        cx,
+                    ))
+                    .child(section(t!("settings.security"), sandbox::settings(cx), cx))
+                    .child(section(
+                        t!("settings.keyboard_shortcuts"),
+                        div().flex().child(
+                            button("settings-shortcuts", t!("settings.customize"), false, cx)
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.open_shortcuts(window, cx)
+                                })),
+                        ),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.theme"),
+                        div().flex().gap_3().children(ThemeMode::ALL.map(|mode| {
+                            theme::preview(mode, mode == settings.theme, cx).on_click(cx.listener(
+                                move |_, _, _, cx| {
+                                    update(cx, |settings| settings.theme = mode);
+                                    cx.notify();
+                                },
+                            ))
+                        })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.language"),
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap_2()
+                            .child(
+                                button(
+                                    "settings-language-system",
+                                    t!("settings.language_system"),
+                                    settings.language.is_none(),
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    |_, _, _, cx| {
+                                        update(cx, |settings| settings.language = None);
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .children(locale::available().into_iter().map(|(code, name)| {
+                                let selected = settings.language.as_deref() == Some(code.as_str());
+                                button(
+                                    SharedString::from(format!("settings-language-{}", code)),
+                                    name,
+                                    selected,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    move |_, _, _, cx| {
+                                        let code = code.clone();
+                                        update(cx, |settings| settings.language = Some(code));
+                                        cx.notify();
+                                    },
+                                ))
+                            })),
+                        cx,
+                    ))
+                    .child(section(
+                        t!("settings.debug"),
+                        div().flex().child(
+                            button(
+                                "settings-lifecycle",
+                                t!("settings.startup_timing"),
+                                false,
+                                cx,
+                            )
+                            .on_click(cx.listener(|this, _, _, cx| this.open_lifecycle(cx))),
+                        ),
+                        cx,
+                    ))
+                    .child(div().flex().justify_end().child(
+                        button("settings-done", t!("settings.done"), true, cx).on_click(
+                            cx.listener(|this, _, _, cx| {
+                                this.save_homepage(cx);
+                                cx.emit(DismissEvent);
+                            }),
+                        ),
+                    )),
+            )
+            .when_some(self.shortcuts.as_ref(), |this, (panel, _)| {
+                this.child(panel.clone())
+            })
+            .when_some(self.lifecycle.as_ref(), |this, (panel, _)| {
+                this.child(panel.clone())
+            })
+    }
+}
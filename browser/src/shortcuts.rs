@@ -0,0 +1,658 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, DismissEvent, EventEmitter, FocusHandle,
+    KeyBinding, KeyDownEvent, Keystroke, Subscription, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button::button,
+    locale::t,
+    settings::{self, Settings},
+    theme::ActiveTheme,
+    workspaces::SwitchWorkspace,
+    CaptureFocusedElement, CloseTab, GoBack, GoForward, InspectElement, NewIncognitoTab, NewTab,
+    NewWindow, OpenDevTools, OpenExtensions, OpenSettings, Print, Quit, Reload, SavePage,
+    Screenshot, ToggleBookmarksBar, ToggleCommandPalette, ToggleConsole, ToggleCspInspector,
+    ToggleNetworkPanel, TogglePerformancePanel, TogglePictureInPicture, ToggleReaderMode,
+    ToggleSplitView, ToggleTabSearch, ViewSource,
+};
+
+/// Keys that only modify other keys and can't be bound on their own.
+const MODIFIER_KEYS: [&str; 7] = [
+    "shift", "control", "ctrl", "alt", "platform", "cmd", "function",
+];
+
+/// An action the user can bind a key combination to.
+struct Shortcut {
+    /// Key in the `[shortcuts]` table of the settings file.
+    name: &'static str,
+    /// Key of its name in the locale files.
+    label: &'static str,
+    default: &'static str,
+    binding: fn(&str) -> KeyBinding,
+}
+
+const SHORTCUTS: [Shortcut; 36] = [
+    Shortcut {
+        name: "quit",
+        label: "shortcuts.quit",
+        default: "cmd-q",
+        binding: |keys| KeyBinding::new(keys, Quit, None),
+    },
+    Shortcut {
+        name: "toggle-bookmarks-bar",
+        label: "shortcuts.toggle_bookmarks_bar",
+        default: "cmd-shift-b",
+        binding: |keys| KeyBinding::new(keys, ToggleBookmarksBar, None),
+    },
+    Shortcut {
+        name: "open-dev-tools",
+        label: "shortcuts.open_dev_tools",
+        default: "cmd-alt-i",
+        binding: |keys| KeyBinding::new(keys, OpenDevTools, None),
+    },
+    Shortcut {
+        name: "inspect-element",
+        label: "shortcuts.inspect_element",
+        default: "cmd-shift-c",
+        binding: |keys| KeyBinding::new(keys, InspectElement, None),
+    },
+    Shortcut {
+        name: "screenshot",
+        label: "shortcuts.screenshot",
+        default: "cmd-shift-s",
+        binding: |keys| KeyBinding::new(keys, Screenshot, None),
+    },
+    Shortcut {
+        name: "capture-focused-element",
+        label: "shortcuts.capture_focused_element",
+        default: "cmd-alt-shift-s",
+        binding: |keys| KeyBinding::new(keys, CaptureFocusedElement, None),
+    },
+    Shortcut {
+        name: "toggle-reader-mode",
+        label: "shortcuts.toggle_reader_mode",
+        default: "cmd-shift-r",
+        binding: |keys| KeyBinding::new(keys, ToggleReaderMode, None),
+    },
+    Shortcut {
+        name: "toggle-console",
+        label: "shortcuts.toggle_console",
+        default: "cmd-`",
+        binding: |keys| KeyBinding::new(keys, ToggleConsole, None),
+    },
+    Shortcut {
+        name: "toggle-network-panel",
+        label: "shortcuts.toggle_network_panel",
+        default: "cmd-alt-n",
+        binding: |keys| KeyBinding::new(keys, ToggleNetworkPanel, None),
+    },
+    Shortcut {
+        name: "toggle-performance-panel",
+        label: "shortcuts.toggle_performance_panel",
+        default: "cmd-alt-p",
+        binding: |keys| KeyBinding::new(keys, TogglePerformancePanel, None),
+    },
+    Shortcut {
+        name: "toggle-csp-inspector",
+        label: "shortcuts.toggle_csp_inspector",
+        default: "cmd-alt-s",
+        binding: |keys| KeyBinding::new(keys, ToggleCspInspector, None),
+    },
+    Shortcut {
+        name: "print",
+        label: "shortcuts.print",
+        default: "cmd-p",
+        binding: |keys| KeyBinding::new(keys, Print, None),
+    },
+    Shortcut {
+        name: "save-page",
+        label: "shortcuts.save_page",
+        default: "cmd-s",
+        binding: |keys| KeyBinding::new(keys, SavePage, None),
+    },
+    Shortcut {
+        name: "open-settings",
+        label: "shortcuts.open_settings",
+        default: "cmd-,",
+        binding: |keys| KeyBinding::new(keys, OpenSettings, None),
+    },
+    Shortcut {
+        name: "open-extensions",
+        label: "shortcuts.open_extensions",
+        default: "cmd-shift-x",
+        binding: |keys| KeyBinding::new(keys, OpenExtensions, None),
+    },
+    Shortcut {
+        name: "new-incognito-tab",
+        label: "shortcuts.new_incognito_tab",
+        default: "cmd-shift-n",
+        binding: |keys| KeyBinding::new(keys, NewIncognitoTab, None),
+    },
+    Shortcut {
+        name: "toggle-picture-in-picture",
+        label: "shortcuts.toggle_picture_in_picture",
+        default: "cmd-alt-shift-p",
+        binding: |keys| KeyBinding::new(keys, TogglePictureInPicture, None),
+    },
+    Shortcut {
+        name: "toggle-split-view",
+        label: "shortcuts.toggle_split_view",
+        default: "cmd-shift-[",
+        binding: |keys| KeyBinding::new(keys, ToggleSplitView, None),
+    },
+    Shortcut {
+        name: "view-source",
+        label: "shortcuts.view_source",
+        default: "cmd-u",
+        binding: |keys| KeyBinding::new(keys, ViewSource, None),
+    },
+    Shortcut {
+        name: "toggle-command-palette",
+        label: "shortcuts.toggle_command_palette",
+        default: "cmd-shift-p",
+        binding: |keys| KeyBinding::new(keys, ToggleCommandPalette, None),
+    },
+    Shortcut {
+        name: "toggle-tab-search",
+        label: "shortcuts.toggle_tab_search",
+        default: "cmd-shift-a",
+        binding: |keys| KeyBinding::new(keys, ToggleTabSearch, None),
+    },
+    Shortcut {
+        name: "go-back",
+        label: "shortcuts.go_back",
+        default: "cmd-[",
+        binding: |keys| KeyBinding::new(keys, GoBack, None),
+    },
+    Shortcut {
+        name: "go-forward",
+        label: "shortcuts.go_forward",
+        default: "cmd-]",
+        binding: |keys| KeyBinding::new(keys, GoForward, None),
+    },
+    Shortcut {
+        name: "reload",
+        label: "shortcuts.reload",
+        default: "cmd-r",
+        binding: |keys| KeyBinding::new(keys, Reload, None),
+    },
+    Shortcut {
+        name: "new-tab",
+        label: "shortcuts.new_tab",
+        default: "cmd-t",
+        binding: |keys| KeyBinding::new(keys, NewTab, None),
+    },
+    Shortcut {
+        name: "new-window",
+        label: "shortcuts.new_window",
+        default: "cmd-n",
+        binding: |keys| KeyBinding::new(keys, NewWindow, None),
+    },
+    Shortcut {
+        name: "close-tab",
+        label: "shortcuts.close_tab",
+        default: "cmd-w",
+        binding: |keys| KeyBinding::new(keys, CloseTab, None),
+    },
+    Shortcut {
+        name: "switch-workspace-1",
+        label: "shortcuts.switch_workspace_1",
+        default: "cmd-1",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(0), None),
+    },
+    Shortcut {
+        name: "switch-workspace-2",
+        label: "shortcuts.switch_workspace_2",
+        default: "cmd-2",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(1), None),
+    },
+    Shortcut {
+        name: "switch-workspace-3",
+        label: "shortcuts.switch_workspace_3",
+        default: "cmd-3",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(2), None),
+    },
+    Shortcut {
+        name: "switch-workspace-4",
+        label: "shortcuts.switch_workspace_4",
+        default: "cmd-4",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(3), None),
+    },
+    Shortcut {
+        name: "switch-workspace-5",
+        label: "shortcuts.switch_workspace_5",
+        default: "cmd-5",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(4), None),
+    },
+    Shortcut {
+        name: "switch-workspace-6",
+        label: "shortcuts.switch_workspace_6",
+        default: "cmd-6",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(5), None),
+    },
+    Shortcut {
+        name: "switch-workspace-7",
+        label: "shortcuts.switch_workspace_7",
+        default: "cmd-7",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(6), None),
+    },
+    Shortcut {
+        name: "switch-workspace-8",
+        label: "shortcuts.switch_workspace_8",
+        default: "cmd-8",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(7), None),
+    },
+    Shortcut {
+        name: "switch-workspace-9",
+        label: "shortcuts.switch_workspace_9",
+        default: "cmd-9",
+        binding: |keys| KeyBinding::new(keys, SwitchWorkspace(8), None),
+    },
+];
+
+/// `keys` in the form GPUI writes them, so the same combination always
+/// compares equal, or `None` if it isn't a valid key sequence.
+fn normalize(keys: &str) -> Option<String> {
+    let keystrokes = keys
+        .split_whitespace()
+        .map(|keystroke| Keystroke::parse(keystroke).ok().map(|k| k.unparse()))
+        .collect::<Option<Vec<_>>>()?;
+
+    (!keystrokes.is_empty()).then(|| keystrokes.join(" "))
+}
+
+/// Key combinations for the browser's actions, keyed by shortcut name.
+/// Stored as the `[shortcuts]` table of the settings file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ShortcutsConfig(BTreeMap<String, String>);
+
+impl Default for ShortcutsConfig {
+    fn default() -> Self {
+        Self(
+            SHORTCUTS
+                .iter()
+                .map(|shortcut| (shortcut.name.to_string(), shortcut.default.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl ShortcutsConfig {
+    /// The keys bound to `shortcut`, or its default if none or invalid ones
+    /// are set, normalized either way so they compare with the others.
+    fn keys(&self, shortcut: &Shortcut) -> String {
+        self.0
+            .get(shortcut.name)
+            .and_then(|keys| normalize(keys))
+            .or_else(|| normalize(shortcut.default))
+            .unwrap_or_else(|| shortcut.default.to_string())
+    }
+
+    fn set(&mut self, shortcut: &Shortcut, keys: String) {
+        self.0.insert(shortcut.name.to_string(), keys);
+    }
+
+    /// Names of the shortcuts sharing their keys with another one.
+    fn conflicts(&self) -> HashSet<&'static str> {
+        let mut by_keys = HashMap::<String, Vec<&'static str>>::new();
+        for shortcut in &SHORTCUTS {
+            by_keys
+                .entry(self.keys(shortcut))
+                .or_default()
+                .push(shortcut.name);
+        }
+
+        by_keys
+            .into_values()
+            .filter(|names| names.len() > 1)
+            .flatten()
+            .collect()
+    }
+
+    fn bindings(&self) -> Vec<KeyBinding> {
+        SHORTCUTS
+            .iter()
+            .map(|shortcut| (shortcut.binding)(&self.keys(shortcut)))
+            .collect()
+    }
+}
+
+/// Replace the key bindings with the ones in settings.
+pub fn bind(cx: &mut GpuiApp) {
+    let bindings = cx.global::<Settings>().shortcuts.bindings();
+
+    cx.clear_key_bindings();
+    cx.bind_keys(bindings);
+}
+
+/// Modal listing every shortcut. Click one, then press the keys to bind to it.
+pub struct ShortcutsPanel {
+    focus: FocusHandle,
+    draft: ShortcutsConfig,
+    /// Index of the shortcut waiting for keys.
+    recording: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ShortcutsPanel {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus = cx.focus_handle();
+        // The key bindings are cleared while recording, so they're restored
+        // however the panel stops getting keys: clicking away, or closing
+        // it or its window.
+        let subscriptions = vec![
+            cx.on_blur(&focus, window, |this, _, cx| this.stop_recording(cx)),
+            cx.on_release(|this, cx| {
+                if this.recording.is_some() {
+                    bind(cx);
+                }
+            }),
+        ];
+
+        Self {
+            focus,
+            draft: cx.global::<Settings>().shortcuts.clone(),
+            recording: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn record(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        // Unbind everything so the keys reach the panel instead of running
+        // whatever they're bound to now.
+        cx.clear_key_bindings();
+        self.recording = Some(index);
+        window.focus(&self.focus);
+        cx.notify();
+    }
+
+    fn stop_recording(&mut self, cx: &mut Context<Self>) {
+        if self.recording.take().is_some() {
+            bind(cx);
+            cx.notify();
+        }
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let Some(index) = self.recording else {
+            return;
+        };
+
+        let keystroke = &event.keystroke;
+        if MODIFIER_KEYS.contains(&keystroke.key.as_str()) {
+            return;
+        }
+
+        cx.stop_propagation();
+
+        if keystroke.key != "escape" || keystroke.modifiers.modified() {
+            self.draft.set(&SHORTCUTS[index], keystroke.unparse());
+        }
+
+        self.stop_recording(cx);
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        if !self.draft.conflicts().is_empty() {
+            return;
+        }
+
+        let shortcuts = self.draft.clone();
+        settings::update(cx, |settings| settings.shortcuts = shortcuts);
+        bind(cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        self.stop_recording(cx);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ShortcutsPanel {}
+
+impl Render for ShortcutsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let conflicts = self.draft.conflicts();
+
+        let rows = SHORTCUTS
+            .iter()
+            .enumerate()
+            .map(|(index, shortcut)| {
+                let recording = self.recording == Some(index);
+                let conflicted = conflicts.contains(shortcut.name);
+
+                div()
+                    .id(("shortcut", index))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py_1p5()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.element_hover))
+                    .when(recording, |this| this.bg(theme.accent.opacity(0.2)))
+                    .when(conflicted, |this| this.text_color(theme.error))
+                    .on_click(
+                        cx.listener(move |this, _, window, cx| this.record(index, window, cx)),
+                    )
+                    .child(div().flex_1().child(t!(shortcut.label)))
+                    .child(
+                        div()
+                            .px_1p5()
+                            .rounded_sm()
+                            .border_1()
+                            .border_color(if conflicted {
+                                theme.error
+                            } else {
+                                theme.border
+                            })
+                            .child(if recording {
+                                t!("shortcuts.press_keys").to_string()
+                            } else {
+                                self.draft.keys(shortcut)
+                            }),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .track_focus(&self.focus)
+                    .on_key_down(cx.listener(|this, event, _, cx| this.key_down(event, cx)))
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .w(px(400.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(div().text_sm().child(t!("shortcuts.title")))
+                    .child(
+                        div()
+                            .id("shortcuts-list")
+                            .flex()
+                            .flex_col()
+                            .max_h(px(360.))
+                            .overflow_y_scroll()
+                            .children(rows),
+                    )
+                    .when(!conflicts.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_color(theme.error)
+                                .child(t!("shortcuts.conflicts")),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(
+                                button(
+                                    "shortcuts-reset",
+                                    t!("shortcuts.restore_defaults"),
+                                    false,
+                                    cx,
+                                )
+                                .on_click(cx.listener(
+                                    |this, _, _, cx| {
+                                        this.stop_recording(cx);
+                                        this.draft = ShortcutsConfig::default();
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .child(div().flex_1())
+                            .child(
+                                button("shortcuts-cancel", t!("shortcuts.cancel"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.cancel(cx))),
+                            )
+                            .child(
+                                button(
+                                    "shortcuts-save",
+                                    t!("shortcuts.save"),
+                                    conflicts.is_empty(),
+                                    cx,
+                                )
+                                .when(!conflicts.is_empty(), |this| {
+                                    this.opacity(0.5).cursor_default()
+                                })
+                                .on_click(cx.listener(|this, _, _, cx| this.save(cx))),
+                            ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(name: &str) -> &'static Shortcut {
+        SHORTCUTS
+            .iter()
+            .find(|shortcut| shortcut.name == name)
+            .unwrap()
+    }
+
+    fn config(keys: &[(&str, &str)]) -> ShortcutsConfig {
+        let mut config = ShortcutsConfig::default();
+        for (name, keys) in keys {
+            config.set(shortcut(name), keys.to_string());
+        }
+        config
+    }
+
+    #[test]
+    fn defaults_are_valid_and_distinct() {
+        for shortcut in &SHORTCUTS {
+            assert!(normalize(shortcut.default).is_some(), "{}", shortcut.name);
+        }
+        assert!(ShortcutsConfig::default().conflicts().is_empty());
+        assert_eq!(ShortcutsConfig::default().bindings().len(), SHORTCUTS.len());
+    }
+
+    #[test]
+    fn shortcut_names_are_unique() {
+        let names = SHORTCUTS
+            .iter()
+            .map(|shortcut| shortcut.name)
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), SHORTCUTS.len());
+    }
+
+    #[test]
+    fn shared_keys_conflict() {
+        let config = config(&[("print", "cmd-q")]);
+
+        assert_eq!(config.conflicts(), HashSet::from(["print", "quit"]));
+    }
+
+    #[test]
+    fn keys_written_differently_still_conflict() {
+        // The default is `cmd-alt-i`.
+        let reordered = config(&[("print", "alt-cmd-i")]);
+        assert_eq!(
+            reordered.conflicts(),
+            HashSet::from(["print", "open-dev-tools"])
+        );
+
+        // Also when the settings file predates the shortcut, which then
+        // takes its default.
+        let older = ShortcutsConfig(BTreeMap::from([(
+            String::from("print"),
+            String::from("alt-cmd-i"),
+        )]));
+        assert_eq!(
+            older.conflicts(),
+            HashSet::from(["print", "open-dev-tools"])
+        );
+
+        let both_set = config(&[("view-source", "shift-cmd-u"), ("reload", "cmd-shift-u")]);
+        assert_eq!(
+            both_set.conflicts(),
+            HashSet::from(["view-source", "reload"])
+        );
+    }
+
+    #[test]
+    fn workspace_keys_conflict_like_any_other() {
+        let config = config(&[("reload", "cmd-1")]);
+
+        assert_eq!(
+            config.conflicts(),
+            HashSet::from(["reload", "switch-workspace-1"])
+        );
+    }
+
+    #[test]
+    fn moving_a_shortcut_away_clears_the_conflict() {
+        let mut config = config(&[("print", "cmd-q")]);
+        config.set(shortcut("print"), String::from("ctrl-alt-p"));
+
+        assert!(config.conflicts().is_empty());
+    }
+
+    #[test]
+    fn invalid_keys_fall_back_to_the_default() {
+        let config = config(&[("print", ""), ("reload", "   ")]);
+
+        assert_eq!(
+            config.keys(shortcut("print")),
+            normalize(shortcut("print").default).unwrap()
+        );
+        assert_eq!(
+            config.keys(shortcut("reload")),
+            normalize(shortcut("reload").default).unwrap()
+        );
+        assert!(config.conflicts().is_empty());
+    }
+
+    #[test]
+    fn normalize_keeps_sequences() {
+        assert_eq!(
+            normalize("ctrl-k  ctrl-s"),
+            Some(String::from("ctrl-k ctrl-s"))
+        );
+        assert_eq!(normalize(""), None);
+    }
+}
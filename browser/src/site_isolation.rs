@@ -0,0 +1,286 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, remove_dir_all},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{Browser, CompletionCallback, RequestContext, RequestContextSettings};
+use gpui::{App as GpuiApp, Global, SharedString};
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+
+use crate::{locale::t, settings::Settings, tabs::TabManager};
+
+/// Which tabs share cookies, cache and storage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SiteIsolationPolicy {
+    /// All tabs share the global request context.
+    #[default]
+    None,
+    /// Tabs opened on the same site share a request context of their own.
+    PerSite,
+    /// Every tab gets a request context of its own.
+    PerTab,
+}
+
+impl SiteIsolationPolicy {
+    pub const ALL: [SiteIsolationPolicy; 3] = [
+        SiteIsolationPolicy::None,
+        SiteIsolationPolicy::PerSite,
+        SiteIsolationPolicy::PerTab,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            SiteIsolationPolicy::None => t!("site_isolation.off"),
+            SiteIsolationPolicy::PerSite => t!("site_isolation.per_site"),
+            SiteIsolationPolicy::PerTab => t!("site_isolation.per_tab"),
+        }
+    }
+}
+
+struct SiteIsolationInner {
+    /// Folder the isolated contexts keep their cookie jars and caches in.
+    root: PathBuf,
+    /// Contexts of tabs opened under `PerSite`, by eTLD+1.
+    sites: HashMap<String, RequestContext>,
+    /// Contexts of tabs opened under `PerTab`.
+    tabs: Vec<RequestContext>,
+    next_tab: u32,
+}
+
+/// Request contexts tabs are isolated in, each with a cookie jar of its own.
+/// A tab keeps the context it was opened in for as long as it's open.
+#[derive(Clone)]
+pub struct SiteIsolation(Arc<Mutex<SiteIsolationInner>>);
+
+impl Global for SiteIsolation {}
+
+impl SiteIsolation {
+    /// Isolate contexts under `root`. Sites keep their cookies across
+    /// restarts; the previous session's per-tab jars are removed.
+    pub fn new(root: PathBuf) -> Self {
+        let _ = remove_dir_all(root.join("tabs"));
+
+        Self(Arc::new(Mutex::new(SiteIsolationInner {
+            root,
+            sites: HashMap::new(),
+            tabs: Vec::new(),
+            next_tab: 0,
+        })))
+    }
+
+    /// The context a new tab showing `url` should be created in, or `None`
+    /// for the global one.
+    pub fn context_for(&self, policy: SiteIsolationPolicy, url: &str) -> Option<RequestContext> {
+        let mut inner = self.0.lock().unwrap();
+        match policy {
+            SiteIsolationPolicy::None => None,
+            SiteIsolationPolicy::PerSite => {
+                // Pages without a host, like about:blank, aren't on any site.
+                let site = site(url)?;
+                if let Some(context) = inner.sites.get(&site) {
+                    return Some(context.clone());
+                }
+
+                let context = create_context(inner.root.join("sites").join(&site))?;
+                inner.sites.insert(site, context.clone());
+                Some(context)
+            }
+            SiteIsolationPolicy::PerTab => {
+                let id = inner.next_tab;
+                inner.next_tab += 1;
+
+                let context = create_context(inner.root.join("tabs").join(id.to_string()))?;
+                inner.tabs.push(context.clone());
+                Some(context)
+            }
+        }
+    }
+
+    /// Remove the contexts none of `browsers` are using, returning them.
+    fn take_unused(&self, browsers: &[Browser]) -> Vec<RequestContext> {
+        let contexts: Vec<RequestContext> = browsers
+            .iter()
+            .filter_map(|browser| {
+                browser
+                    .get_host()
+                    .and_then(|host| host.get_request_context())
+                    .ok()
+            })
+            .collect();
+        let in_use = |context: &RequestContext| {
+            contexts
+                .iter()
+                .any(|other| context.is_same(other.clone()).unwrap_or(false))
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        let mut unused = Vec::new();
+        inner.sites.retain(|_, context| {
+            let keep = in_use(context);
+            if !keep {
+                unused.push(context.clone());
+            }
+            keep
+        });
+        inner.tabs.retain(|context| {
+            let keep = in_use(context);
+            if !keep {
+                unused.push(context.clone());
+            }
+            keep
+        });
+
+        unused
+    }
+}
+
+/// A request context keeping its cookies and cache in `path`, apart from
+/// every other context.
+fn create_context(path: PathBuf) -> Option<RequestContext> {
+    if let Err(e) = create_dir_all(&path) {
+        eprintln!("Error creating isolated cache {:?}: {}", path, e);
+        return None;
+    }
+
+    let settings = match RequestContextSettings::new()
+        .persist_session_cookies(true)
+        .cache_path(&path)
+    {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error setting isolated cache {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    let context = RequestContext::create_context(&settings, None);
+    if context.is_none() {
+        eprintln!("Error creating isolated request context {:?}", path);
+    }
+
+    context
+}
+
+/// The site of `url`: its registrable domain, or eTLD+1, by the Public
+/// Suffix List, such as `example.co.uk` for `https://www.example.co.uk/`. IP
+/// addresses are sites of their own.
+fn site(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let domain = match url.host()? {
+        Host::Domain(domain) => domain.trim_end_matches('.').to_ascii_lowercase(),
+        Host::Ipv4(address) => return Some(address.to_string()),
+        Host::Ipv6(address) => return Some(address.to_string()),
+    };
+
+    // Hosts that are a public suffix themselves, like `localhost`, are sites
+    // of their own.
+    Some(psl::domain_str(&domain).unwrap_or(&domain).to_string())
+}
+
+/// The context a new tab showing `url` should be created in under the
+/// current policy, or `None` for the global one.
+pub fn context_for(cx: &GpuiApp, url: &str) -> Option<RequestContext> {
+    let policy = cx.global::<Settings>().site_isolation;
+    cx.global::<SiteIsolation>().context_for(policy, url)
+}
+
+/// Shut down the isolated contexts whose last tab has closed. A site's cookie
+/// jar stays on disk for the next tab on it; per-tab jars go on restart.
+pub fn close_if_unused(cx: &mut GpuiApp) {
+    let browsers: Vec<Browser> = cx
+        .global::<TabManager>()
+        .tabs()
+        .iter()
+        .map(|tab| tab.browser.clone())
+        .collect();
+
+    for context in cx.global::<SiteIsolation>().take_unused(&browsers) {
+        if let Err(e) = context.close_all_connections(CompletionCallback::new(|| {})) {
+            eprintln!("Error closing isolated connections: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn site_is_the_registrable_domain() {
+        assert_eq!(site("https://example.com/").as_deref(), Some("example.com"));
+        assert_eq!(
+            site("https://www.example.com/a").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            site("http://a.b.example.com:8080/").as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn site_keeps_public_suffixes_whole() {
+        assert_eq!(
+            site("https://www.example.co.uk/").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            site("https://shop.example.com.au/").as_deref(),
+            Some("example.com.au")
+        );
+        assert_eq!(
+            site("https://user.github.io/repo").as_deref(),
+            Some("user.github.io")
+        );
+        assert_eq!(
+            site("https://example.uk.com/").as_deref(),
+            Some("example.uk.com")
+        );
+        assert_eq!(
+            site("https://www.school.pvt.k12.ma.us/").as_deref(),
+            Some("school.pvt.k12.ma.us")
+        );
+    }
+
+    #[test]
+    fn sites_under_private_suffixes_are_apart() {
+        assert_ne!(
+            site("https://alice.blogspot.com/"),
+            site("https://bob.blogspot.com/")
+        );
+        assert_ne!(
+            site("https://alice.github.io/"),
+            site("https://bob.github.io/")
+        );
+    }
+
+    #[test]
+    fn site_ignores_case_and_a_trailing_dot() {
+        assert_eq!(
+            site("https://WWW.Example.COM./").as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn site_of_short_hosts_and_addresses() {
+        assert_eq!(site("http://localhost:3000/").as_deref(), Some("localhost"));
+        assert_eq!(site("https://co.uk/").as_deref(), Some("co.uk"));
+        assert_eq!(
+            site("http://192.168.1.10/").as_deref(),
+            Some("192.168.1.10")
+        );
+        assert_eq!(site("http://[::1]:8080/").as_deref(), Some("::1"));
+    }
+
+    #[test]
+    fn site_needs_a_host() {
+        assert_eq!(site("about:blank").as_deref(), None);
+        assert_eq!(site("data:text/html,hi").as_deref(), None);
+        assert_eq!(site("not a url").as_deref(), None);
+    }
+}
@@ -0,0 +1,510 @@
+use std::{
+    collections::VecDeque,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::{
+    register_scheme_handler_factory, AuthCallback, Browser, Callback, Frame, Request,
+    ResourceHandler, ResourceHandlerCallbacks, ResourceReadCallback, ResourceSkipCallback,
+    Response, SchemeHandlerFactory, SchemeHandlerFactoryCallbacks, UrlRequest, UrlRequestClient,
+    UrlRequestClientCallbacks, UrlRequestStatus,
+};
+use gpui::{
+    div, prelude::*, px, rgb, uniform_list, App as GpuiApp, Context, DismissEvent, Entity,
+    EventEmitter, Global, ScrollStrategy, SharedString, Subscription, UniformListScrollHandle,
+    Window,
+};
+use once_cell::sync::Lazy;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::{
+    button::button,
+    locale::t,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+/// Scheme of the URLs showing a page's source, e.g.
+/// `view-source:https://example.com`.
+pub const SCHEME: &str = "view-source";
+
+/// Built-in theme the source is colored with.
+const THEME: &str = "base16-ocean.dark";
+
+/// Width of a digit of the line numbers, in pixels.
+const DIGIT_WIDTH: f32 = 7.5;
+
+/// Spaces a tab is shown as.
+const TAB: &str = "    ";
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEMES: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// A run of source text in one color.
+pub struct Span {
+    pub color: u32,
+    pub text: SharedString,
+}
+
+/// Split `text` into lines of colored spans, highlighted as the language of
+/// files ending in `extension`.
+pub fn highlight_lines(text: &str, extension: &str) -> Vec<Vec<Span>> {
+    let syntax = SYNTAXES
+        .find_syntax_by_extension(extension)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let theme: &Theme = &THEMES.themes[THEME];
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    LinesWithEndings::from(text)
+        .map(|line| {
+            let ranges = highlighter
+                .highlight_line(line, &SYNTAXES)
+                .unwrap_or_else(|e| {
+                    eprintln!("Error highlighting source: {}", e);
+                    Vec::new()
+                });
+
+            ranges
+                .into_iter()
+                .map(|(style, text)| Span {
+                    color: u32::from_be_bytes([
+                        0,
+                        style.foreground.r,
+                        style.foreground.g,
+                        style.foreground.b,
+                    ]),
+                    text: text
+                        .trim_end_matches(['\r', '\n'])
+                        .replace('\t', TAB)
+                        .into(),
+                })
+                .filter(|span| !span.text.is_empty())
+                .collect()
+        })
+        .collect()
+}
+
+/// A page's source, split into colored lines.
+pub struct SourceDocument {
+    url: String,
+    lines: Vec<Vec<Span>>,
+    /// The text of each line, lowercased for searching.
+    search_text: Vec<String>,
+}
+
+impl SourceDocument {
+    fn highlight(url: String, html: &str) -> Self {
+        let lines = highlight_lines(html, "html");
+        let search_text = LinesWithEndings::from(html)
+            .map(|line| line.trim_end_matches(['\r', '\n']).to_lowercase())
+            .collect();
+
+        Self {
+            url,
+            lines,
+            search_text,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SourceViewerInner {
+    /// Pages asked for by navigating to `view-source:` URLs.
+    requested: VecDeque<String>,
+    /// Sources fetched and highlighted, or why they couldn't be.
+    loaded: VecDeque<Result<SourceDocument, String>>,
+}
+
+/// Page sources on their way to the source panel. CEF fetches and
+/// highlights them on its own threads.
+#[derive(Clone, Default)]
+pub struct SourceViewer(Arc<Mutex<SourceViewerInner>>);
+
+impl Global for SourceViewer {}
+
+impl SourceViewer {
+    /// Queue the page a `view-source:` URL points at, if it's one.
+    pub fn request(&self, url: &str) -> bool {
+        let Some(page) = url
+            .strip_prefix(SCHEME)
+            .and_then(|url| url.strip_prefix(':'))
+        else {
+            return false;
+        };
+
+        self.0.lock().unwrap().requested.push_back(page.to_string());
+        true
+    }
+
+    pub fn take_requested(&self) -> Option<String> {
+        self.0.lock().unwrap().requested.pop_front()
+    }
+
+    /// Download the HTML of the page at `url`, as the server sends it.
+    pub fn fetch(&self, url: &str) {
+        let request = Request::new();
+        let result = request.set_url(url).and_then(|_| request.set_method("GET"));
+        if let Err(e) = result {
+            self.finish(Err(e.to_string()));
+            return;
+        }
+
+        let client = UrlRequestClient::new(SourceRequestClient {
+            viewer: self.clone(),
+            url: url.to_string(),
+            body: Vec::new(),
+        });
+        UrlRequest::new(request, client, None);
+    }
+
+    fn finish(&self, document: Result<SourceDocument, String>) {
+        self.0.lock().unwrap().loaded.push_back(document);
+    }
+
+    pub fn take_loaded(&self) -> Option<Result<SourceDocument, String>> {
+        self.0.lock().unwrap().loaded.pop_front()
+    }
+}
+
+/// Show the source of the page at `url` once it's fetched.
+pub fn view(cx: &GpuiApp, url: &str) {
+    cx.global::<SourceViewer>().fetch(url);
+}
+
+struct SourceRequestClient {
+    viewer: SourceViewer,
+    url: String,
+    body: Vec<u8>,
+}
+
+impl UrlRequestClientCallbacks for SourceRequestClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let document = match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) => {
+                let html = String::from_utf8_lossy(&self.body);
+                Ok(SourceDocument::highlight(
+                    std::mem::take(&mut self.url),
+                    &html,
+                ))
+            }
+            Ok(_) => Err(t!("source.load_failed", url = self.url).to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        self.viewer.finish(document);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Answer `view-source:` URLs by opening the source panel. Call once CEF
+/// has been initialized.
+pub fn register_handler(viewer: SourceViewer) {
+    let factory = SchemeHandlerFactory::new(SourceSchemeHandlerFactory { viewer });
+
+    if !register_scheme_handler_factory(SCHEME, None, factory) {
+        eprintln!("Error registering {}: scheme handler", SCHEME);
+    }
+}
+
+struct SourceSchemeHandlerFactory {
+    viewer: SourceViewer,
+}
+
+impl SchemeHandlerFactoryCallbacks for SourceSchemeHandlerFactory {
+    fn create(
+        &mut self,
+        _browser: Option<Browser>,
+        _frame: Option<Frame>,
+        _scheme_name: &str,
+        request: Request,
+    ) -> Option<ResourceHandler> {
+        if let Ok(url) = request.get_url() {
+            self.viewer.request(&url);
+        }
+
+        Some(ResourceHandler::new(NoContentResourceHandler))
+    }
+}
+
+/// Answers with "204 No Content", which leaves the page where it was.
+struct NoContentResourceHandler;
+
+impl ResourceHandlerCallbacks for NoContentResourceHandler {
+    fn open(&mut self, _request: Request, handle_request: &mut bool, _callback: Callback) -> bool {
+        *handle_request = true;
+        true
+    }
+
+    fn get_response_headers(
+        &mut self,
+        response: Response,
+        response_length: &mut i64,
+        _redirect_url: &mut Option<String>,
+    ) {
+        *response_length = 0;
+
+        if let Err(e) = response.set_status(204) {
+            eprintln!("Error setting response headers: {}", e);
+        }
+    }
+
+    fn skip(
+        &mut self,
+        _bytes_to_skip: i64,
+        bytes_skipped: &mut i64,
+        _callback: ResourceSkipCallback,
+    ) -> bool {
+        *bytes_skipped = 0;
+        true
+    }
+
+    fn read(
+        &mut self,
+        _data_out: &mut [u8],
+        bytes_read: &mut i32,
+        _callback: ResourceReadCallback,
+    ) -> bool {
+        *bytes_read = 0;
+        false
+    }
+
+    fn cancel(&mut self) {}
+}
+
+/// The source of a page, with line numbers and search, over the page.
+pub struct SourcePanel {
+    document: Result<SourceDocument, String>,
+    search: Entity<TextInput>,
+    /// Lines holding the search text.
+    matches: Vec<usize>,
+    /// Index in `matches` of the one scrolled to.
+    current: usize,
+    scroll_handle: UniformListScrollHandle,
+    _subscription: Subscription,
+}
+
+impl SourcePanel {
+    pub fn new(document: Result<SourceDocument, String>, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| TextInput::new("", cx).placeholder(t!("source.find")));
+        let subscription = cx.subscribe(&search, |this, _, event, cx| match event {
+            TextInputEvent::Confirm => this.find_next(cx),
+            TextInputEvent::Cancel => cx.emit(DismissEvent),
+        });
+
+        Self {
+            document,
+            search,
+            matches: Vec::new(),
+            current: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+            _subscription: subscription,
+        }
+    }
+
+    /// Show another page's source in place of this one.
+    pub fn set_document(
+        &mut self,
+        document: Result<SourceDocument, String>,
+        cx: &mut Context<Self>,
+    ) {
+        self.document = document;
+        self.matches.clear();
+        self.current = 0;
+        self.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    /// Search for the text typed in the search field, or move on to the next
+    /// match if it hasn't changed.
+    fn find_next(&mut self, cx: &mut Context<Self>) {
+        let query = self.search.read(cx).text().to_lowercase();
+        let Ok(document) = &self.document else {
+            return;
+        };
+
+        let matches = match query.is_empty() {
+            true => Vec::new(),
+            false => document
+                .search_text
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.contains(&query))
+                .map(|(index, _)| index)
+                .collect(),
+        };
+
+        if matches == self.matches && !matches.is_empty() {
+            self.current = (self.current + 1) % matches.len();
+        } else {
+            self.matches = matches;
+            self.current = 0;
+        }
+
+        self.scroll_to_current(cx);
+    }
+
+    fn find_previous(&mut self, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        self.scroll_to_current(cx);
+    }
+
+    fn scroll_to_current(&mut self, cx: &mut Context<Self>) {
+        if let Some(&line) = self.matches.get(self.current) {
+            self.scroll_handle
+                .scroll_to_item(line, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn render_lines(&self, range: Range<usize>, cx: &GpuiApp) -> Vec<impl IntoElement> {
+        let theme = cx.theme();
+
+        let Ok(document) = &self.document else {
+            return Vec::new();
+        };
+
+        let gutter = document.lines.len().to_string().len() as f32 * DIGIT_WIDTH;
+        let current = self.matches.get(self.current).copied();
+
+        range
+            .filter_map(|index| Some((index, document.lines.get(index)?)))
+            .map(|(index, spans)| {
+                div()
+                    .flex()
+                    .whitespace_nowrap()
+                    .when(self.matches.binary_search(&index).is_ok(), |this| {
+                        this.bg(theme.warning.opacity(0.15))
+                    })
+                    .when(current == Some(index), |this| {
+                        this.bg(theme.warning.opacity(0.4))
+                    })
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(gutter + 16.))
+                            .pr_2()
+                            .text_right()
+                            .text_color(theme.text_placeholder)
+                            .child((index + 1).to_string()),
+                    )
+                    .children(
+                        spans
+                            .iter()
+                            .map(|span| div().text_color(rgb(span.color)).child(span.text.clone())),
+                    )
+            })
+            .collect()
+    }
+}
+
+impl EventEmitter<DismissEvent> for SourcePanel {}
+
+impl Render for SourcePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let (title, line_count) = match &self.document {
+            Ok(document) => (document.url.clone(), document.lines.len()),
+            Err(_) => (t!("source.title").to_string(), 0),
+        };
+        let match_count: SharedString = match self.matches.len() {
+            0 => SharedString::default(),
+            count => t!(
+                "source.match_count",
+                current = self.current + 1,
+                count = count
+            ),
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .flex_col()
+            .bg(theme.background)
+            .text_xs()
+            .text_color(theme.text_secondary)
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_9()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(t!("source.source_of", title = title)),
+                    )
+                    .child(div().w(px(200.)).child(self.search.clone()))
+                    .child(
+                        div()
+                            .w(px(56.))
+                            .text_color(theme.text_placeholder)
+                            .child(match_count),
+                    )
+                    .child(
+                        button("source-previous", t!("source.previous"), false, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.find_previous(cx))),
+                    )
+                    .child(
+                        button("source-next", t!("source.next"), false, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.find_next(cx))),
+                    )
+                    .child(
+                        button("source-close", t!("source.close"), false, cx)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+            .child(match &self.document {
+                Ok(_) => uniform_list(
+                    "source-lines",
+                    line_count,
+                    cx.processor(|this, range, _, cx| this.render_lines(range, cx)),
+                )
+                .track_scroll(self.scroll_handle.clone())
+                .flex_1()
+                .py_1()
+                .font_family("Menlo")
+                .into_any_element(),
+                Err(error) => div()
+                    .p_4()
+                    .child(t!("source.error", error = error))
+                    .into_any_element(),
+            })
+    }
+}
@@ -0,0 +1,386 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::Frame;
+use gpui::{App as GpuiApp, Global};
+use serde::Deserialize;
+
+use crate::message_router::{Query, ERROR_FAILED};
+
+/// Replaces `window.speechSynthesis` with one spoken by the platform's
+/// synthesizer. Utterances are queued here and sent one at a time; the
+/// browser answers `speech.speak` once speaking starts and calls
+/// `__cefSpeech` when it ends. Relies on `window.cefQuery`.
+pub const SPEECH_SCRIPT: &str = r#"(() => {
+  if (window.__cefSpeech) return;
+
+  const native = window.speechSynthesis;
+  const queue = [];
+  const spoken = new Map();
+  let current = null;
+  let paused = false;
+
+  const send = (cmd) => window.cefQuery({ request: JSON.stringify({ cmd }) });
+
+  const fire = (utterance, type, error) => {
+    let event;
+    try {
+      event = type === "error"
+        ? new SpeechSynthesisErrorEvent(type, { utterance, error })
+        : new SpeechSynthesisEvent(type, { utterance });
+    } catch (_) {
+      event = new Event(type);
+    }
+    utterance.dispatchEvent(event);
+  };
+
+  const next = () => {
+    paused = false;
+    current = queue.shift() || null;
+    if (!current) return;
+
+    const utterance = current;
+    window.cefQuery({
+      request: JSON.stringify({
+        cmd: "speech.speak",
+        text: String(utterance.text),
+        lang: utterance.lang || document.documentElement.lang || "",
+        pitch: utterance.pitch,
+        rate: utterance.rate,
+        volume: utterance.volume,
+      }),
+      onSuccess: (id) => {
+        spoken.set(Number(id), utterance);
+        fire(utterance, "start");
+      },
+      onFailure: () => {
+        fire(utterance, "error", "synthesis-unavailable");
+        next();
+      },
+    });
+  };
+
+  const synthesis = {
+    speak(utterance) {
+      queue.push(utterance);
+      if (!current) next();
+    },
+    cancel() {
+      queue.length = 0;
+      if (current) send("speech.cancel");
+    },
+    pause() {
+      if (!current || paused) return;
+      paused = true;
+      send("speech.pause");
+    },
+    resume() {
+      if (!paused) return;
+      paused = false;
+      send("speech.resume");
+    },
+    getVoices: () => (native ? native.getVoices() : []),
+    addEventListener: (...args) => native && native.addEventListener(...args),
+    removeEventListener: (...args) => native && native.removeEventListener(...args),
+    get speaking() {
+      return current !== null;
+    },
+    get pending() {
+      return queue.length > 0;
+    },
+    get paused() {
+      return paused;
+    },
+  };
+
+  Object.defineProperty(window, "speechSynthesis", { value: synthesis, configurable: true });
+
+  Object.defineProperty(window, "__cefSpeech", {
+    value: (id, type) => {
+      const utterance = spoken.get(id);
+      if (!utterance) return;
+      spoken.delete(id);
+      fire(utterance, type);
+      next();
+    },
+  });
+})();"#;
+
+/// A `SpeechSynthesisUtterance` as the page sends it.
+#[derive(Deserialize)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct Utterance {
+    text: String,
+    /// BCP 47 language tag, e.g. `en-US`. Empty for the default voice.
+    #[serde(default)]
+    lang: String,
+    /// From 0 to 2, 1 being the voice's own.
+    pitch: f32,
+    /// From 0.1 to 10, 1 being the voice's own.
+    rate: f32,
+    /// From 0 to 1.
+    volume: f32,
+}
+
+#[derive(Default)]
+struct SpeechSynthesisInner {
+    next_id: u64,
+    /// The utterance being spoken and the frame that asked, for its `end`
+    /// event.
+    speaking: Option<(u64, Frame)>,
+}
+
+/// Speaks pages' `speechSynthesis` utterances with the platform synthesizer,
+/// one at a time.
+#[derive(Clone, Default)]
+pub struct SpeechSynthesis(Arc<Mutex<SpeechSynthesisInner>>);
+
+impl Global for SpeechSynthesis {}
+
+/// Handle a `speech.*` query from a page.
+pub fn on_query(cx: &GpuiApp, query: Query, command: &str, argument: &str) {
+    match command {
+        "speech.speak" => speak(cx, query, argument),
+        "speech.cancel" => {
+            platform::stop();
+            query.success("");
+        }
+        "speech.pause" => {
+            platform::pause();
+            query.success("");
+        }
+        "speech.resume" => {
+            platform::resume();
+            query.success("");
+        }
+        _ => query.failure(ERROR_FAILED, "Unknown speech command"),
+    }
+}
+
+fn speak(cx: &GpuiApp, query: Query, argument: &str) {
+    let utterance = match serde_json::from_str::<Utterance>(argument) {
+        Ok(utterance) => utterance,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid utterance: {}", e));
+            return;
+        }
+    };
+
+    if !platform::speak(&utterance) {
+        query.failure(ERROR_FAILED, "Speech synthesis is unavailable");
+        return;
+    }
+
+    let mut inner = cx.global::<SpeechSynthesis>().0.lock().unwrap();
+    inner.next_id += 1;
+    let id = inner.next_id;
+    inner.speaking = Some((id, query.frame()));
+    drop(inner);
+
+    query.success(&id.to_string());
+}
+
+/// Fire `end` on the utterance that finished speaking, if one did, so the
+/// page speaks the next.
+pub fn poll(cx: &GpuiApp) {
+    if !platform::take_finished() {
+        return;
+    }
+    let Some((id, frame)) = cx
+        .global::<SpeechSynthesis>()
+        .0
+        .lock()
+        .unwrap()
+        .speaking
+        .take()
+    else {
+        return;
+    };
+
+    let script = format!("window.__cefSpeech && window.__cefSpeech({}, \"end\");", id);
+    if let Err(e) = frame.execute_java_script(&script, "", 0) {
+        eprintln!("Error sending speech event: {}", e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{cell::Cell, ffi::CStr, os::raw::c_char, ptr, sync::Once};
+
+    use core_foundation::{base::TCFType, string::CFString};
+    use objc::{
+        class,
+        declare::ClassDecl,
+        msg_send,
+        runtime::{Class, Object, Sel, BOOL, YES},
+        sel, sel_impl,
+    };
+
+    use super::Utterance;
+
+    /// Words per minute at a rate of 1, about what voices speak at by
+    /// default.
+    const WORDS_PER_MINUTE: f32 = 180.;
+
+    /// `NSSpeechWordBoundary`.
+    const WORD_BOUNDARY: usize = 1;
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSVoiceLocaleIdentifier: *mut Object;
+        static NSSpeechPitchBaseProperty: *mut Object;
+    }
+
+    thread_local! {
+        /// Set when the synthesizer finished or was stopped. AppKit calls the
+        /// delegate on the main thread.
+        static FINISHED: Cell<bool> = const { Cell::new(false) };
+
+        /// The one synthesizer, which lives as long as the browser.
+        static SYNTHESIZER: *mut Object = unsafe {
+            let synthesizer: *mut Object = msg_send![class!(NSSpeechSynthesizer), alloc];
+            let synthesizer: *mut Object =
+                msg_send![synthesizer, initWithVoice: ptr::null_mut::<Object>()];
+            let delegate: *mut Object = msg_send![delegate_class(), new];
+            let _: () = msg_send![synthesizer, setDelegate: delegate];
+            synthesizer
+        };
+    }
+
+    fn delegate_class() -> &'static Class {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let mut decl = ClassDecl::new("BrowserSpeechDelegate", class!(NSObject))
+                .expect("speech delegate class already registered");
+            unsafe {
+                decl.add_method(
+                    sel!(speechSynthesizer:didFinishSpeaking:),
+                    did_finish_speaking as extern "C" fn(&Object, Sel, *mut Object, BOOL),
+                );
+            }
+            decl.register();
+        });
+
+        Class::get("BrowserSpeechDelegate").unwrap()
+    }
+
+    extern "C" fn did_finish_speaking(_this: &Object, _cmd: Sel, _sender: *mut Object, _: BOOL) {
+        FINISHED.with(|finished| finished.set(true));
+    }
+
+    unsafe fn to_string(string: *mut Object) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![string, UTF8String];
+        (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+
+    /// The installed voice for `lang`, preferring one of the same region, or
+    /// null for the default voice.
+    unsafe fn voice_for(lang: &str) -> *mut Object {
+        if lang.is_empty() {
+            return ptr::null_mut();
+        }
+        let wanted = lang.replace('-', "_").to_ascii_lowercase();
+        let language = wanted.split('_').next().unwrap_or_default().to_string();
+
+        let voices: *mut Object = msg_send![class!(NSSpeechSynthesizer), availableVoices];
+        let count: usize = msg_send![voices, count];
+        let mut fallback = ptr::null_mut();
+        for index in 0..count {
+            let voice: *mut Object = msg_send![voices, objectAtIndex: index];
+            let attributes: *mut Object =
+                msg_send![class!(NSSpeechSynthesizer), attributesForVoice: voice];
+            let locale: *mut Object = msg_send![attributes, objectForKey: NSVoiceLocaleIdentifier];
+            let Some(locale) = to_string(locale).map(|locale| locale.to_ascii_lowercase()) else {
+                continue;
+            };
+
+            if locale == wanted {
+                return voice;
+            }
+            if fallback.is_null() && locale.split('_').next() == Some(&language) {
+                fallback = voice;
+            }
+        }
+
+        fallback
+    }
+
+    /// Start speaking `utterance`, returning whether the synthesizer did.
+    pub fn speak(utterance: &Utterance) -> bool {
+        SYNTHESIZER.with(|&synthesizer| unsafe {
+            FINISHED.with(|finished| finished.set(false));
+
+            // Changing the voice resets the other settings, so it goes first.
+            let _: BOOL = msg_send![synthesizer, setVoice: voice_for(&utterance.lang)];
+            let _: () = msg_send![synthesizer, setRate: WORDS_PER_MINUTE * utterance.rate];
+            let _: () = msg_send![synthesizer, setVolume: utterance.volume];
+
+            let error = ptr::null_mut::<*mut Object>();
+            let base: *mut Object =
+                msg_send![synthesizer, objectForProperty: NSSpeechPitchBaseProperty error: error];
+            if !base.is_null() {
+                let base: f32 = msg_send![base, floatValue];
+                let pitch: *mut Object =
+                    msg_send![class!(NSNumber), numberWithFloat: base * utterance.pitch];
+                let _: BOOL = msg_send![
+                    synthesizer,
+                    setObject: pitch
+                    forProperty: NSSpeechPitchBaseProperty
+                    error: error
+                ];
+            }
+
+            let text = CFString::new(&utterance.text);
+            let started: BOOL = msg_send![
+                synthesizer,
+                startSpeakingString: text.as_concrete_TypeRef() as *mut Object
+            ];
+            started == YES
+        })
+    }
+
+    pub fn stop() {
+        SYNTHESIZER.with(|&synthesizer| unsafe {
+            let _: () = msg_send![synthesizer, stopSpeaking];
+        });
+    }
+
+    pub fn pause() {
+        SYNTHESIZER.with(|&synthesizer| unsafe {
+            let _: () = msg_send![synthesizer, pauseSpeakingAtBoundary: WORD_BOUNDARY];
+        });
+    }
+
+    pub fn resume() {
+        SYNTHESIZER.with(|&synthesizer| unsafe {
+            let _: () = msg_send![synthesizer, continueSpeaking];
+        });
+    }
+
+    pub fn take_finished() -> bool {
+        FINISHED.with(|finished| finished.replace(false))
+    }
+}
+
+/// Only macOS has a synthesizer hooked up; elsewhere pages get an error
+/// event for every utterance.
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::Utterance;
+
+    pub fn speak(_utterance: &Utterance) -> bool {
+        false
+    }
+
+    pub fn stop() {}
+
+    pub fn pause() {}
+
+    pub fn resume() {}
+
+    pub fn take_finished() -> bool {
+        false
+    }
+}
@@ -0,0 +1,625 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::Frame;
+use gpui::{div, prelude::*, px, svg, App as GpuiApp, Div, Global, Stateful};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    message_router::{Query, ERROR_FAILED},
+    permissions::{frame_origin, PermissionsStore},
+    theme::ActiveTheme,
+};
+
+/// How long a session that isn't continuous listens on after the last thing
+/// it heard before it stops, like Chromium's.
+const SILENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Defines `SpeechRecognition` and `webkitSpeechRecognition`, recognized by
+/// the platform's speech recognizer. The browser answers
+/// `recognition.start` with the session's id, then sends its events through
+/// `__cefRecognition`. Relies on `window.cefQuery`.
+pub const RECOGNITION_SCRIPT: &str = r#"(() => {
+  if (window.__cefRecognition) return;
+
+  const sessions = new Map();
+  const send = (cmd) => window.cefQuery({ request: JSON.stringify({ cmd }) });
+
+  class SpeechRecognition extends EventTarget {
+    #id = null;
+
+    constructor() {
+      super();
+      this.lang = "";
+      this.continuous = false;
+      this.interimResults = false;
+      this.maxAlternatives = 1;
+      this.onstart = this.onaudiostart = this.onresult = this.onerror = this.onend = null;
+    }
+
+    start() {
+      if (this.#id !== null) {
+        throw new DOMException("Recognition has already started.", "InvalidStateError");
+      }
+
+      this.#id = 0;
+      window.cefQuery({
+        request: JSON.stringify({
+          cmd: "recognition.start",
+          lang: this.lang || document.documentElement.lang || navigator.language,
+          continuous: this.continuous,
+          interimResults: this.interimResults,
+        }),
+        onSuccess: (id) => {
+          this.#id = Number(id);
+          sessions.set(this.#id, (type, data) => this.#receive(type, data));
+        },
+        onFailure: () => {
+          this.#id = null;
+          this.#receive("error", { error: "service-not-allowed" });
+          this.#dispatch(new Event("end"));
+        },
+      });
+    }
+
+    stop() {
+      if (this.#id) send("recognition.stop");
+    }
+
+    abort() {
+      if (this.#id) send("recognition.abort");
+    }
+
+    #receive(type, data) {
+      let event;
+      if (type === "result") {
+        const alternative = { transcript: data.transcript, confidence: data.confidence };
+        const result = Object.assign([alternative], { isFinal: data.isFinal });
+        result.item = (index) => result[index];
+        const results = [result];
+        results.item = (index) => results[index];
+        event = Object.assign(new Event("result"), { resultIndex: 0, results });
+      } else {
+        event = new Event(type);
+        if (type === "error") Object.assign(event, { error: data.error, message: "" });
+        if (type === "end") {
+          sessions.delete(this.#id);
+          this.#id = null;
+        }
+      }
+      this.#dispatch(event);
+    }
+
+    #dispatch(event) {
+      this.dispatchEvent(event);
+      const handler = this["on" + event.type];
+      if (typeof handler === "function") handler.call(this, event);
+    }
+  }
+
+  window.SpeechRecognition = SpeechRecognition;
+  window.webkitSpeechRecognition = SpeechRecognition;
+
+  Object.defineProperty(window, "__cefRecognition", {
+    value: (id, type, data) => {
+      const receive = sessions.get(id);
+      if (receive) receive(type, data);
+    },
+  });
+})();"#;
+
+/// What a page asks for when it starts recognition.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct RecognitionRequest {
+    /// BCP 47 language tag, e.g. `en-US`.
+    lang: String,
+    continuous: bool,
+    interim_results: bool,
+}
+
+/// What the platform recognizer reports, from whichever thread it runs on.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+enum RecognitionEvent {
+    /// The user answered the system prompt to allow speech recognition.
+    Authorized(bool),
+    /// What was heard so far.
+    Result {
+        transcript: String,
+        confidence: f32,
+        is_final: bool,
+    },
+    /// A `SpeechRecognitionErrorEvent` error code.
+    Error(&'static str),
+    End,
+}
+
+struct Session {
+    id: u64,
+    frame: Frame,
+    request: RecognitionRequest,
+    /// Whether the microphone is open.
+    listening: bool,
+    /// When something was last heard, for sessions that stop on silence.
+    last_heard: Option<Instant>,
+    stopping: bool,
+}
+
+impl Session {
+    fn dispatch(&self, event: &str, data: serde_json::Value) {
+        let script = format!(
+            "window.__cefRecognition && window.__cefRecognition({}, {:?}, {});",
+            self.id, event, data
+        );
+        if let Err(e) = self.frame.execute_java_script(&script, "", 0) {
+            eprintln!("Error sending speech recognition event: {}", e);
+        }
+    }
+
+    fn fail(&self, error: &str) {
+        self.dispatch("error", json!({ "error": error }));
+        self.dispatch("end", json!({}));
+    }
+}
+
+#[derive(Default)]
+struct SpeechRecognizerInner {
+    next_id: u64,
+    /// There's one microphone, so one page listens at a time.
+    session: Option<Session>,
+    /// Whether the session with this id may use the microphone.
+    permitted: Option<(u64, bool)>,
+}
+
+/// Recognizes speech for pages' `SpeechRecognition` sessions with the
+/// platform recognizer, once the page is allowed the microphone.
+#[derive(Clone, Default)]
+pub struct SpeechRecognizer(Arc<Mutex<SpeechRecognizerInner>>);
+
+impl Global for SpeechRecognizer {}
+
+impl SpeechRecognizer {
+    /// Whether a page is listening, for the toolbar's indicator.
+    pub fn is_listening(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .session
+            .as_ref()
+            .is_some_and(|session| session.listening)
+    }
+
+    fn permit(&self, id: u64, allow: bool) {
+        self.0.lock().unwrap().permitted = Some((id, allow));
+    }
+}
+
+/// Handle a `recognition.*` query from a page.
+pub fn on_query(cx: &GpuiApp, query: Query, command: &str, argument: &str) {
+    match command {
+        "recognition.start" => start(cx, query, argument),
+        "recognition.stop" => {
+            platform::stop();
+            query.success("");
+        }
+        "recognition.abort" => {
+            platform::abort();
+            query.success("");
+        }
+        _ => query.failure(ERROR_FAILED, "Unknown speech recognition command"),
+    }
+}
+
+/// Start a session, once the page is allowed the microphone.
+fn start(cx: &GpuiApp, query: Query, argument: &str) {
+    let request = match serde_json::from_str::<RecognitionRequest>(argument) {
+        Ok(request) => request,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid recognition request: {}", e));
+            return;
+        }
+    };
+
+    let frame = query.frame();
+    let Some(origin) = frame_origin(&frame) else {
+        query.failure(ERROR_FAILED, "Speech recognition needs a page origin");
+        return;
+    };
+
+    let recognizer = cx.global::<SpeechRecognizer>().clone();
+    let mut inner = recognizer.0.lock().unwrap();
+    if inner.session.is_some() {
+        query.failure(ERROR_FAILED, "Another page is listening");
+        return;
+    }
+
+    inner.next_id += 1;
+    let id = inner.next_id;
+    inner.session = Some(Session {
+        id,
+        frame,
+        request,
+        listening: false,
+        last_heard: None,
+        stopping: false,
+    });
+    drop(inner);
+    query.success(&id.to_string());
+
+    let permit = recognizer.clone();
+    cx.global::<PermissionsStore>()
+        .request_browser(&origin, "microphone", move |allow| permit.permit(id, allow));
+}
+
+/// Open the microphone for `session`, or tell the page why it can't be.
+fn listen(session: &mut Session) -> bool {
+    match platform::start(&session.request) {
+        Ok(true) => {
+            session.listening = true;
+            session.last_heard = Some(Instant::now());
+            session.dispatch("start", json!({}));
+            session.dispatch("audiostart", json!({}));
+            true
+        }
+        // Waiting for the user to allow speech recognition.
+        Ok(false) => true,
+        Err(error) => {
+            session.fail(error);
+            false
+        }
+    }
+}
+
+/// Start sessions that were allowed the microphone and send what the
+/// recognizer heard to the page. Returns whether the toolbar's indicator
+/// should change.
+pub fn poll(cx: &GpuiApp) -> bool {
+    let recognizer = cx.global::<SpeechRecognizer>().clone();
+    let mut inner = recognizer.0.lock().unwrap();
+    let was_listening = inner
+        .session
+        .as_ref()
+        .is_some_and(|session| session.listening);
+
+    if let Some((id, allow)) = inner.permitted.take() {
+        if let Some(session) = inner.session.as_mut().filter(|session| session.id == id) {
+            let started = match allow {
+                true => listen(session),
+                false => {
+                    session.fail("not-allowed");
+                    false
+                }
+            };
+            if !started {
+                inner.session = None;
+            }
+        }
+    }
+
+    for event in platform::take_events() {
+        let Some(session) = inner.session.as_mut() else {
+            break;
+        };
+
+        match event {
+            RecognitionEvent::Authorized(true) => {
+                if !listen(session) {
+                    inner.session = None;
+                }
+            }
+            RecognitionEvent::Authorized(false) => {
+                session.fail("service-not-allowed");
+                inner.session = None;
+            }
+            RecognitionEvent::Result {
+                transcript,
+                confidence,
+                is_final,
+            } => {
+                session.last_heard = Some(Instant::now());
+                if is_final || session.request.interim_results {
+                    session.dispatch(
+                        "result",
+                        json!({
+                            "transcript": transcript,
+                            "confidence": confidence,
+                            "isFinal": is_final,
+                        }),
+                    );
+                }
+            }
+            RecognitionEvent::Error(error) => {
+                session.dispatch("error", json!({ "error": error }));
+            }
+            RecognitionEvent::End => {
+                platform::finish();
+                session.dispatch("end", json!({}));
+                inner.session = None;
+            }
+        }
+    }
+
+    // Only continuous sessions keep listening through pauses.
+    if let Some(session) = inner.session.as_mut() {
+        let silent = session
+            .last_heard
+            .is_some_and(|heard| heard.elapsed() >= SILENCE_TIMEOUT);
+        if !session.request.continuous && session.listening && silent && !session.stopping {
+            session.stopping = true;
+            platform::stop();
+        }
+    }
+
+    let listening = inner
+        .session
+        .as_ref()
+        .is_some_and(|session| session.listening);
+    listening != was_listening
+}
+
+/// The red microphone in the toolbar while a page is listening. Clicking it
+/// stops the page listening.
+pub fn indicator(cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("speech-recognition")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(svg().path("mic.svg").size(px(14.0)).text_color(theme.error))
+        .on_click(|_, _, _| platform::abort())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{cell::RefCell, ptr, sync::Mutex};
+
+    use block::ConcreteBlock;
+    use core_foundation::{base::TCFType, string::CFString};
+    use objc::{
+        class, msg_send,
+        runtime::{Object, BOOL, NO, YES},
+        sel, sel_impl,
+    };
+
+    use super::{RecognitionEvent, RecognitionRequest};
+
+    /// `SFSpeechRecognizerAuthorizationStatus`.
+    const NOT_DETERMINED: isize = 0;
+    const AUTHORIZED: isize = 3;
+
+    /// Codes of the recognizer's errors, in `kAFAssistantErrorDomain`.
+    const CANCELED: isize = 216;
+    const NO_SPEECH: isize = 1110;
+
+    #[link(name = "Speech", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {}
+
+    /// Reported by the recognizer's handlers, which run on its own queue.
+    static EVENTS: Mutex<Vec<RecognitionEvent>> = Mutex::new(Vec::new());
+
+    /// The objects of the session listening, all owned.
+    struct Listening {
+        recognizer: *mut Object,
+        engine: *mut Object,
+        request: *mut Object,
+        task: *mut Object,
+    }
+
+    thread_local! {
+        static LISTENING: RefCell<Option<Listening>> = const { RefCell::new(None) };
+    }
+
+    fn push(event: RecognitionEvent) {
+        EVENTS.lock().unwrap().push(event);
+    }
+
+    pub fn take_events() -> Vec<RecognitionEvent> {
+        std::mem::take(&mut *EVENTS.lock().unwrap())
+    }
+
+    /// Start listening, returning `false` if the user is first asked to allow
+    /// speech recognition, in which case an `Authorized` event follows.
+    pub fn start(request: &RecognitionRequest) -> Result<bool, &'static str> {
+        unsafe {
+            let status: isize = msg_send![class!(SFSpeechRecognizer), authorizationStatus];
+            match status {
+                AUTHORIZED => listen(request).map(|_| true),
+                NOT_DETERMINED => {
+                    let handler = ConcreteBlock::new(|status: isize| {
+                        push(RecognitionEvent::Authorized(status == AUTHORIZED));
+                    })
+                    .copy();
+                    let _: () =
+                        msg_send![class!(SFSpeechRecognizer), requestAuthorization: &*handler];
+                    Ok(false)
+                }
+                _ => Err("service-not-allowed"),
+            }
+        }
+    }
+
+    /// Stream the microphone into a recognition task for `request`'s
+    /// language. Partial results are always asked for, to tell when the
+    /// user stops talking.
+    unsafe fn listen(request: &RecognitionRequest) -> Result<(), &'static str> {
+        let identifier = CFString::new(&request.lang.replace('-', "_"));
+        let locale: *mut Object = msg_send![
+            class!(NSLocale),
+            localeWithLocaleIdentifier: identifier.as_concrete_TypeRef() as *mut Object
+        ];
+        let recognizer: *mut Object = msg_send![class!(SFSpeechRecognizer), alloc];
+        let recognizer: *mut Object = msg_send![recognizer, initWithLocale: locale];
+        if recognizer.is_null() {
+            return Err("language-not-supported");
+        }
+        let available: BOOL = msg_send![recognizer, isAvailable];
+        if available == NO {
+            let _: () = msg_send![recognizer, release];
+            return Err("service-not-allowed");
+        }
+
+        let audio: *mut Object = msg_send![class!(SFSpeechAudioBufferRecognitionRequest), new];
+        let _: () = msg_send![audio, setShouldReportPartialResults: YES];
+
+        let engine: *mut Object = msg_send![class!(AVAudioEngine), new];
+        let input: *mut Object = msg_send![engine, inputNode];
+        let format: *mut Object = msg_send![input, outputFormatForBus: 0usize];
+        let tap = ConcreteBlock::new(move |buffer: *mut Object, _when: *mut Object| {
+            let _: () = msg_send![audio, appendAudioPCMBuffer: buffer];
+        })
+        .copy();
+        let _: () = msg_send![
+            input,
+            installTapOnBus: 0usize
+            bufferSize: 1024u32
+            format: format
+            block: &*tap
+        ];
+        let _: () = msg_send![engine, prepare];
+        let started: BOOL = msg_send![engine, startAndReturnError: ptr::null_mut::<*mut Object>()];
+        if started == NO {
+            let _: () = msg_send![input, removeTapOnBus: 0usize];
+            for object in [engine, audio, recognizer] {
+                let _: () = msg_send![object, release];
+            }
+            return Err("audio-capture");
+        }
+
+        let handler = ConcreteBlock::new(|result: *mut Object, error: *mut Object| {
+            if !result.is_null() {
+                let (transcript, confidence, is_final) = read_result(result);
+                push(RecognitionEvent::Result {
+                    transcript,
+                    confidence,
+                    is_final,
+                });
+                if is_final {
+                    push(RecognitionEvent::End);
+                }
+            } else if !error.is_null() {
+                let code: isize = msg_send![error, code];
+                push(RecognitionEvent::Error(match code {
+                    CANCELED => "aborted",
+                    NO_SPEECH => "no-speech",
+                    _ => "network",
+                }));
+                push(RecognitionEvent::End);
+            }
+        })
+        .copy();
+        let task: *mut Object =
+            msg_send![recognizer, recognitionTaskWithRequest: audio resultHandler: &*handler];
+        let _: *mut Object = msg_send![task, retain];
+
+        LISTENING.with(|listening| {
+            *listening.borrow_mut() = Some(Listening {
+                recognizer,
+                engine,
+                request: audio,
+                task,
+            })
+        });
+        Ok(())
+    }
+
+    /// The best transcription of `result`, how confident the recognizer is
+    /// of it on average over its words, and whether it's final.
+    unsafe fn read_result(result: *mut Object) -> (String, f32, bool) {
+        let transcription: *mut Object = msg_send![result, bestTranscription];
+        let text: *mut Object = msg_send![transcription, formattedString];
+        let utf8: *const std::os::raw::c_char = msg_send![text, UTF8String];
+        let transcript = match utf8.is_null() {
+            true => String::new(),
+            false => std::ffi::CStr::from_ptr(utf8)
+                .to_string_lossy()
+                .into_owned(),
+        };
+
+        let segments: *mut Object = msg_send![transcription, segments];
+        let count: usize = msg_send![segments, count];
+        let mut confidence = 0.;
+        for index in 0..count {
+            let segment: *mut Object = msg_send![segments, objectAtIndex: index];
+            let segment_confidence: f32 = msg_send![segment, confidence];
+            confidence += segment_confidence;
+        }
+        if count > 0 {
+            confidence /= count as f32;
+        }
+
+        let is_final: BOOL = msg_send![result, isFinal];
+        (transcript, confidence, is_final == YES)
+    }
+
+    /// Stop recording. The final result follows.
+    pub fn stop() {
+        LISTENING.with(|listening| unsafe {
+            if let Some(listening) = listening.borrow().as_ref() {
+                let input: *mut Object = msg_send![listening.engine, inputNode];
+                let _: () = msg_send![listening.engine, stop];
+                let _: () = msg_send![input, removeTapOnBus: 0usize];
+                let _: () = msg_send![listening.request, endAudio];
+            }
+        });
+    }
+
+    /// Stop recording and throw away what was heard.
+    pub fn abort() {
+        stop();
+        LISTENING.with(|listening| unsafe {
+            if let Some(listening) = listening.borrow().as_ref() {
+                let _: () = msg_send![listening.task, cancel];
+            }
+        });
+    }
+
+    /// Let go of the ended session.
+    pub fn finish() {
+        stop();
+        LISTENING.with(|listening| unsafe {
+            if let Some(listening) = listening.borrow_mut().take() {
+                for object in [
+                    listening.task,
+                    listening.request,
+                    listening.engine,
+                    listening.recognizer,
+                ] {
+                    let _: () = msg_send![object, release];
+                }
+            }
+        });
+    }
+}
+
+/// Only macOS has a recognizer hooked up; elsewhere sessions fail to start.
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::{RecognitionEvent, RecognitionRequest};
+
+    pub fn start(_request: &RecognitionRequest) -> Result<bool, &'static str> {
+        Err("service-not-allowed")
+    }
+
+    pub fn take_events() -> Vec<RecognitionEvent> {
+        Vec::new()
+    }
+
+    pub fn stop() {}
+
+    pub fn abort() {}
+
+    pub fn finish() {}
+}
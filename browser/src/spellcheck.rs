@@ -0,0 +1,156 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+
+use cef_ui::CommandLine;
+use gpui::SharedString;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::t;
+
+/// Language pages are spell checked in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpellCheckLanguage {
+    Off,
+    #[default]
+    EnUs,
+    EnGb,
+    De,
+    Fr,
+    Es,
+}
+
+impl SpellCheckLanguage {
+    pub const ALL: [SpellCheckLanguage; 6] = [
+        SpellCheckLanguage::Off,
+        SpellCheckLanguage::EnUs,
+        SpellCheckLanguage::EnGb,
+        SpellCheckLanguage::De,
+        SpellCheckLanguage::Fr,
+        SpellCheckLanguage::Es,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            SpellCheckLanguage::Off => t!("spellcheck.off"),
+            SpellCheckLanguage::EnUs => t!("spellcheck.english_us"),
+            SpellCheckLanguage::EnGb => t!("spellcheck.english_uk"),
+            SpellCheckLanguage::De => t!("spellcheck.german"),
+            SpellCheckLanguage::Fr => t!("spellcheck.french"),
+            SpellCheckLanguage::Es => t!("spellcheck.spanish"),
+        }
+    }
+
+    /// The language code Chromium names its dictionaries by.
+    fn code(&self) -> Option<&'static str> {
+        match self {
+            SpellCheckLanguage::Off => None,
+            SpellCheckLanguage::EnUs => Some("en-US"),
+            SpellCheckLanguage::EnGb => Some("en-GB"),
+            SpellCheckLanguage::De => Some("de-DE"),
+            SpellCheckLanguage::Fr => Some("fr-FR"),
+            SpellCheckLanguage::Es => Some("es-ES"),
+        }
+    }
+}
+
+/// Turn spell checking on in `language`, or off. Chromium only reads these
+/// switches at startup, so a new language applies after a restart.
+pub fn configure(command_line: &CommandLine, language: SpellCheckLanguage) -> Result<()> {
+    match language.code() {
+        Some(code) => {
+            command_line.append_switch("enable-spell-checking")?;
+            command_line.append_switch_with_value("spellcheck-language", Some(code))
+        }
+        None => command_line.append_switch("disable-spell-checking"),
+    }
+}
+
+/// Copy the dictionaries bundled in `assets_dir/dictionaries` to
+/// `cache_dir/Dictionaries`, where Chromium looks for them before downloading
+/// its own. Dictionaries are Hunspell `.dic`/`.aff` pairs converted to
+/// Chromium's `.bdic` format, e.g. `en-US-10-1.bdic`, see the README there.
+pub fn install_dictionaries(assets_dir: &Path, cache_dir: &Path) -> Result<()> {
+    let Ok(bundled) = fs::read_dir(assets_dir.join("dictionaries")) else {
+        return Ok(());
+    };
+
+    let installed = cache_dir.join("Dictionaries");
+    fs::create_dir_all(&installed)?;
+
+    for entry in bundled.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("bdic") {
+            continue;
+        }
+
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+
+        let destination = installed.join(name);
+        if !destination.exists() {
+            fs::copy(&path, destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// Empty assets and cache directories of their own for each test, removed
+    /// when the `TempDir` is dropped.
+    fn dirs() -> (TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("assets/dictionaries")).unwrap();
+        let (assets, cache) = (dir.path().join("assets"), dir.path().join("cache"));
+        (dir, assets, cache)
+    }
+
+    #[test]
+    fn installs_bundled_dictionaries_where_chromium_looks() {
+        let (_dir, assets, cache) = dirs();
+        let bundled = assets.join("dictionaries");
+        fs::write(bundled.join("en-US-10-1.bdic"), b"BDic en-US").unwrap();
+        fs::write(bundled.join("en_US.dic"), b"1\nword").unwrap();
+
+        install_dictionaries(&assets, &cache).unwrap();
+
+        let installed = cache.join("Dictionaries");
+        assert_eq!(
+            fs::read(installed.join("en-US-10-1.bdic")).unwrap(),
+            b"BDic en-US"
+        );
+        // Chromium can't read Hunspell's own files.
+        assert!(!installed.join("en_US.dic").exists());
+    }
+
+    #[test]
+    fn keeps_installed_dictionaries() {
+        let (_dir, assets, cache) = dirs();
+        fs::write(assets.join("dictionaries/en-US-10-1.bdic"), b"bundled").unwrap();
+        fs::create_dir_all(cache.join("Dictionaries")).unwrap();
+        fs::write(cache.join("Dictionaries/en-US-10-1.bdic"), b"updated").unwrap();
+
+        install_dictionaries(&assets, &cache).unwrap();
+
+        assert_eq!(
+            fs::read(cache.join("Dictionaries/en-US-10-1.bdic")).unwrap(),
+            b"updated"
+        );
+    }
+
+    #[test]
+    fn installs_nothing_without_bundled_dictionaries() {
+        let (_dir, assets, cache) = dirs();
+        fs::remove_dir_all(assets.join("dictionaries")).unwrap();
+
+        install_dictionaries(&assets, &cache).unwrap();
+        assert!(!cache.join("Dictionaries").exists());
+    }
+}
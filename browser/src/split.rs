@@ -0,0 +1,297 @@
+use std::{cell::Cell, ops::Deref, rc::Rc, sync::Arc};
+
+use cef_ui::{Browser, EventFlags, MouseEvent};
+use gpui::{
+    canvas, div, img, prelude::*, px, svg, App as GpuiApp, Bounds, Context, DismissEvent, Div,
+    Entity, EventEmitter, Global, ImageSource, Pixels, RenderImage, ScrollWheelEvent, Stateful,
+    Window,
+};
+
+use crate::{
+    locale::t, omnibox::Omnibox, pip::PictureInPicture, scroll, settings::Settings, tabs,
+    tabs::TabManager, theme::ActiveTheme, view::ViewSlot, BrowserState,
+};
+
+/// Width of the bar between the panes that's dragged to resize them.
+pub const DIVIDER_WIDTH: Pixels = px(4.);
+
+/// Narrowest share of the window either pane can be dragged down to.
+const MIN_RATIO: f32 = 0.2;
+
+/// The tab shown in the right pane of split view, next to the active tab.
+#[derive(Clone)]
+pub struct SplitView(ViewSlot);
+
+impl Default for SplitView {
+    fn default() -> Self {
+        Self(ViewSlot::new(512, 768))
+    }
+}
+
+impl Global for SplitView {}
+
+impl Deref for SplitView {
+    type Target = ViewSlot;
+
+    fn deref(&self) -> &ViewSlot {
+        &self.0
+    }
+}
+
+/// Show the tab next to the active one in a right pane, opening a new tab
+/// for it if there's no other. Returns the right pane's browser.
+pub fn open(cx: &mut GpuiApp) -> Option<Browser> {
+    let tabs = cx.global::<TabManager>();
+    let pip = cx.global::<PictureInPicture>();
+    let active = tabs.active();
+    let other = [active + 1, active.wrapping_sub(1)]
+        .into_iter()
+        .filter_map(|index| tabs.tabs().get(index))
+        .find(|tab| !pip.is_detached(&tab.browser))
+        .map(|tab| tab.browser.clone());
+
+    let browser = match other {
+        Some(browser) => browser,
+        None => {
+            let homepage = cx.global::<Settings>().homepage.clone();
+            let browser = crate::new_browser_in_context(cx, &homepage, None)?;
+            tabs::add(cx, browser.clone(), false);
+            browser
+        }
+    };
+
+    cx.global::<SplitView>().set_browser(Some(&browser));
+    cx.global_mut::<BrowserState>().split_ratio = 0.5;
+
+    // Show the tab again, laid out to the pane.
+    tabs::activate(cx, active);
+    if let Err(e) = browser.get_host().and_then(|host| host.was_resized()) {
+        eprintln!("Error resizing page: {}", e);
+    }
+
+    Some(browser)
+}
+
+/// Go back to a single page, keeping the left pane's tab active.
+pub fn close(cx: &mut GpuiApp) {
+    let split = cx.global::<SplitView>();
+    if split.is_empty() {
+        return;
+    }
+    split.set_browser(None);
+
+    // The right pane's tab goes back to the background.
+    let active = cx.global::<TabManager>().active();
+    tabs::activate(cx, active);
+}
+
+/// Whether the right pane's tab is still open apart from the active tab.
+/// Closes split view once it isn't, like when it was closed or switched to.
+pub fn check(cx: &mut GpuiApp) -> bool {
+    let split = cx.global::<SplitView>();
+    let tabs = cx.global::<TabManager>();
+    let showing = tabs
+        .tabs()
+        .iter()
+        .enumerate()
+        .any(|(index, tab)| index != tabs.active() && split.contains(&tab.browser));
+
+    if !showing {
+        close(cx);
+    }
+    showing
+}
+
+/// Move the divider to `ratio` of the way across, keeping both panes usable.
+pub fn set_ratio(cx: &mut GpuiApp, ratio: f32) {
+    cx.global_mut::<BrowserState>().split_ratio = ratio.clamp(MIN_RATIO, 1. - MIN_RATIO);
+}
+
+/// The bar between the panes.
+pub fn divider(cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("split-divider")
+        .flex_none()
+        .w(DIVIDER_WIDTH)
+        .h_full()
+        .bg(theme.border)
+        .cursor_col_resize()
+        .hover(|this| this.bg(theme.accent))
+}
+
+/// The right pane of split view: its own navigation controls and omnibox
+/// above the page. Scrolling goes to its page; the keyboard stays with the
+/// active tab.
+pub struct SplitPane {
+    browser: Browser,
+    omnibox: Entity<Omnibox>,
+    bounds: Rc<Cell<Bounds<Pixels>>>,
+    image: Option<Arc<RenderImage>>,
+}
+
+impl SplitPane {
+    pub fn new(browser: Browser, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let target = browser.clone();
+        let omnibox = cx.new(|cx| Omnibox::new(window, cx).with_browser(target));
+
+        Self {
+            browser,
+            omnibox,
+            bounds: Rc::new(Cell::new(Bounds::default())),
+            image: None,
+        }
+    }
+
+    /// Where the pane's page is drawn in the window.
+    pub fn bounds(&self) -> Bounds<Pixels> {
+        self.bounds.get()
+    }
+
+    /// Pick up the page's URL, size and latest paint.
+    pub fn poll(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self
+            .browser
+            .get_host()
+            .and_then(|host| host.get_visible_navigation_entry())
+            .and_then(|entry| entry.get_url())
+            .unwrap_or_default();
+        self.omnibox
+            .update(cx, |omnibox, cx| omnibox.set_page_url(url, cx));
+
+        // Lay the page out again when the pane's size changed.
+        if cx.global::<SplitView>().set_size(self.bounds.get().size) {
+            if let Err(e) = self.browser.get_host().and_then(|host| host.was_resized()) {
+                eprintln!("Error resizing page: {}", e);
+            }
+        }
+
+        if let Some(frame) = cx.global::<SplitView>().take_frame() {
+            if let Some(previous) = self.image.replace(frame) {
+                window.drop_image(previous).ok();
+            }
+            cx.notify();
+        }
+    }
+
+    fn scroll_wheel(&self, event: &ScrollWheelEvent) {
+        let position = event.position - self.bounds.get().origin;
+        let delta = event.delta.pixel_delta(px(scroll::LINE_HEIGHT));
+        let event = MouseEvent {
+            x: f32::from(position.x) as i32,
+            y: f32::from(position.y) as i32,
+            modifiers: EventFlags::None,
+        };
+
+        if let Err(e) = self.browser.get_host().and_then(|host| {
+            host.send_mouse_wheel_event(
+                &event,
+                f32::from(delta.x) as i32,
+                f32::from(delta.y) as i32,
+            )
+        }) {
+            eprintln!("Error scrolling page: {}", e);
+        }
+    }
+
+    fn icon_button(&self, id: &'static str, path: &'static str, cx: &GpuiApp) -> Stateful<Div> {
+        let theme = cx.theme();
+
+        div()
+            .id(id)
+            .flex()
+            .items_center()
+            .justify_center()
+            .size(px(14.0))
+            .rounded_md()
+            .cursor_pointer()
+            .hover(|this| this.bg(theme.ghost_hover))
+            .child(svg().path(path).size(px(12.0)).text_color(theme.text))
+    }
+}
+
+impl EventEmitter<DismissEvent> for SplitPane {}
+
+impl Render for SplitPane {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let bounds = self.bounds.clone();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .min_w(px(0.))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .bg(theme.surface)
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        self.icon_button("split-back", "back.svg", cx)
+                            .on_click(cx.listener(|this, _, _, _| {
+                                if let Err(e) = this.browser.go_back() {
+                                    eprintln!("Error going back: {}", e);
+                                }
+                            })),
+                    )
+                    .child(
+                        self.icon_button("split-forward", "forward.svg", cx)
+                            .on_click(cx.listener(|this, _, _, _| {
+                                if let Err(e) = this.browser.go_forward() {
+                                    eprintln!("Error going forward: {}", e);
+                                }
+                            })),
+                    )
+                    .child(
+                        self.icon_button("split-reload", "rotate-cw.svg", cx)
+                            .on_click(cx.listener(|this, _, _, _| {
+                                if let Err(e) = this.browser.reload() {
+                                    eprintln!("Error reloading page: {}", e);
+                                }
+                            })),
+                    )
+                    .child(self.omnibox.clone())
+                    .child(div().flex_1())
+                    .child(
+                        self.icon_button("split-close", "close.svg", cx)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+            .child(
+                div()
+                    .id("split-page")
+                    .relative()
+                    .flex()
+                    .flex_1()
+                    .items_center()
+                    .justify_center()
+                    .bg(theme.viewport)
+                    .on_scroll_wheel(
+                        cx.listener(|this, event: &ScrollWheelEvent, _, _| {
+                            this.scroll_wheel(event)
+                        }),
+                    )
+                    .child(match self.image.clone() {
+                        Some(image) => div()
+                            .size_full()
+                            .child(img(ImageSource::Render(image)).size_full()),
+                        None => div().child(t!("main.loading")),
+                    })
+                    .child(
+                        canvas(
+                            move |page_bounds, _, _| bounds.set(page_bounds),
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    ),
+            )
+    }
+}
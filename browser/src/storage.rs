@@ -0,0 +1,477 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use cef_ui::{
+    Browser, DevToolsMessageObserver, DevToolsMessageObserverCallbacks, DictionaryValue,
+    Registration,
+};
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, Entity, Focusable, Global, SharedString,
+    Subscription, Window,
+};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    locale::t,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+    BrowserState,
+};
+
+/// How often the open panel reads the page's storage again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Height of the storage panel.
+const PANEL_HEIGHT: f32 = 220.;
+
+/// Message ids of the DevTools calls the panel makes, so their results can be
+/// told apart from other observers' calls.
+const LOCAL_ITEMS_ID: i32 = 0x7374_0001;
+const SESSION_ITEMS_ID: i32 = 0x7374_0002;
+const EDIT_ID: i32 = 0x7374_0003;
+
+/// `localStorage` or `sessionStorage`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StorageArea {
+    Local,
+    Session,
+}
+
+impl StorageArea {
+    const ALL: [StorageArea; 2] = [StorageArea::Local, StorageArea::Session];
+
+    fn label(&self) -> SharedString {
+        match self {
+            StorageArea::Local => t!("storage.local"),
+            StorageArea::Session => t!("storage.session"),
+        }
+    }
+
+    fn items_id(&self) -> i32 {
+        match self {
+            StorageArea::Local => LOCAL_ITEMS_ID,
+            StorageArea::Session => SESSION_ITEMS_ID,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StorageItems {
+    entries: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct StorageInspectorInner {
+    /// Origin of the page the items are from.
+    origin: String,
+    local: Vec<(String, String)>,
+    session: Vec<(String, String)>,
+    /// The observer on the page being inspected, by browser id.
+    observer: Option<(i32, Registration)>,
+    changed: bool,
+}
+
+/// The active page's `localStorage` and `sessionStorage`, read and written
+/// through the DevTools protocol's `DOMStorage` domain.
+#[derive(Clone, Default)]
+pub struct StorageInspector(Arc<Mutex<StorageInspectorInner>>);
+
+impl Global for StorageInspector {}
+
+/// Receives the storage items of the inspected page.
+struct StorageObserver {
+    inspector: StorageInspector,
+}
+
+impl DevToolsMessageObserverCallbacks for StorageObserver {
+    fn on_dev_tools_message(&mut self, _browser: Browser, _message: &[u8]) -> bool {
+        false
+    }
+
+    fn on_dev_tools_method_result(
+        &mut self,
+        browser: Browser,
+        message_id: i32,
+        success: bool,
+        result: &[u8],
+    ) {
+        let area = match message_id {
+            LOCAL_ITEMS_ID => StorageArea::Local,
+            SESSION_ITEMS_ID => StorageArea::Session,
+            // Show an edit as soon as it's made.
+            EDIT_ID => {
+                self.inspector.request_items(&browser);
+                return;
+            }
+            _ => return,
+        };
+
+        let entries = success
+            .then(|| serde_json::from_slice::<StorageItems>(result).ok())
+            .flatten()
+            .map(|items| items.entries)
+            .unwrap_or_default();
+
+        let mut inner = self.inspector.0.lock().unwrap();
+        match area {
+            StorageArea::Local => inner.local = entries,
+            StorageArea::Session => inner.session = entries,
+        }
+        inner.changed = true;
+    }
+
+    fn on_dev_tools_event(&mut self, _browser: Browser, _method: &str, _params: &[u8]) {}
+
+    fn on_dev_tools_agent_attached(&mut self, _browser: Browser) {}
+
+    fn on_dev_tools_agent_detached(&mut self, _browser: Browser) {}
+}
+
+impl StorageInspector {
+    /// Read the active page's storage again. The items arrive on CEF's
+    /// thread.
+    fn refresh(&self, cx: &GpuiApp) {
+        let state = cx.global::<BrowserState>();
+        let (Some(browser), Some((url, _))) = (state.browser.as_ref(), state.current_page()) else {
+            return;
+        };
+        let origin = Url::parse(&url)
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_default();
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        {
+            let mut inner = self.0.lock().unwrap();
+            let observed = inner.observer.as_ref().map(|(id, _)| *id);
+            if observed != Some(id) || inner.origin != origin {
+                inner.observer = None;
+                inner.origin = origin;
+                inner.local.clear();
+                inner.session.clear();
+                inner.changed = true;
+            }
+        }
+
+        if self.0.lock().unwrap().observer.is_none() {
+            let Ok(host) = browser.get_host() else {
+                return;
+            };
+            let observer = StorageObserver {
+                inspector: self.clone(),
+            };
+
+            match host.add_dev_tools_message_observer(DevToolsMessageObserver::new(observer)) {
+                Ok(Some(registration)) => {
+                    self.0.lock().unwrap().observer = Some((id, registration));
+                }
+                Ok(None) => return,
+                Err(e) => {
+                    eprintln!("Error observing DevTools messages: {}", e);
+                    return;
+                }
+            }
+        }
+
+        self.request_items(browser);
+    }
+
+    fn request_items(&self, browser: &Browser) {
+        for area in StorageArea::ALL {
+            if let Err(e) = self.call(browser, area, area.items_id(), "getDOMStorageItems", &[]) {
+                eprintln!("Error reading page storage: {}", e);
+            }
+        }
+    }
+
+    /// Call `DOMStorage.<method>` on `area` of the inspected origin, with
+    /// `params` besides the storage id.
+    fn call(
+        &self,
+        browser: &Browser,
+        area: StorageArea,
+        message_id: i32,
+        method: &str,
+        params: &[(&str, &str)],
+    ) -> Result<()> {
+        let origin = self.0.lock().unwrap().origin.clone();
+
+        let storage_id = DictionaryValue::new();
+        storage_id.set_string("securityOrigin", &origin)?;
+        storage_id.set_bool("isLocalStorage", area == StorageArea::Local)?;
+        let dictionary = DictionaryValue::new();
+        dictionary.set_dictionary("storageId", storage_id)?;
+        for (key, value) in params {
+            dictionary.set_string(key, value)?;
+        }
+
+        browser.get_host()?.execute_dev_tools_method(
+            message_id,
+            &format!("DOMStorage.{}", method),
+            Some(dictionary),
+        )?;
+        Ok(())
+    }
+
+    /// Set `key` of `area` to `value` on the active page.
+    fn set_item(&self, cx: &GpuiApp, area: StorageArea, key: &str, value: &str) {
+        let Some(browser) = cx.global::<BrowserState>().browser.as_ref() else {
+            return;
+        };
+        let params = [("key", key), ("value", value)];
+        if let Err(e) = self.call(browser, area, EDIT_ID, "setDOMStorageItem", &params) {
+            eprintln!("Error writing page storage: {}", e);
+        }
+    }
+
+    /// Remove `key` from `area` on the active page.
+    fn remove_item(&self, cx: &GpuiApp, area: StorageArea, key: &str) {
+        let Some(browser) = cx.global::<BrowserState>().browser.as_ref() else {
+            return;
+        };
+        let params = [("key", key)];
+        if let Err(e) = self.call(browser, area, EDIT_ID, "removeDOMStorageItem", &params) {
+            eprintln!("Error writing page storage: {}", e);
+        }
+    }
+
+    fn items(&self, area: StorageArea) -> Vec<(String, String)> {
+        let inner = self.0.lock().unwrap();
+        match area {
+            StorageArea::Local => inner.local.clone(),
+            StorageArea::Session => inner.session.clone(),
+        }
+    }
+
+    fn origin(&self) -> String {
+        self.0.lock().unwrap().origin.clone()
+    }
+
+    /// Whether the items changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// Stop observing the page while the panel is closed.
+    fn stop(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.observer = None;
+        inner.local.clear();
+        inner.session.clear();
+    }
+}
+
+/// The value being edited, and the field it's edited in.
+struct Editing {
+    area: StorageArea,
+    key: String,
+    input: Entity<TextInput>,
+    _subscription: Subscription,
+}
+
+/// Collapsible panel below the page listing the active page's
+/// `localStorage` and `sessionStorage`. Clicking a value edits it.
+pub struct StoragePanel {
+    inspector: StorageInspector,
+    editing: Option<Editing>,
+}
+
+impl StoragePanel {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let inspector = cx.global::<StorageInspector>().clone();
+        inspector.refresh(cx);
+
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(REFRESH_INTERVAL).await;
+
+            let refreshed = this.update(cx, |this, cx| this.inspector.refresh(cx));
+
+            if refreshed.is_err() {
+                break;
+            }
+        })
+        .detach();
+
+        Self {
+            inspector,
+            editing: None,
+        }
+    }
+
+    fn edit(
+        &mut self,
+        area: StorageArea,
+        key: String,
+        value: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let input = cx.new(|cx| TextInput::new(value, cx));
+        let subscription = cx.subscribe(&input, |this, input, event, cx| {
+            if let (TextInputEvent::Confirm, Some(editing)) = (event, this.editing.as_ref()) {
+                let value = input.read(cx).text().to_string();
+                this.inspector
+                    .set_item(cx, editing.area, &editing.key, &value);
+            }
+            this.editing = None;
+            cx.notify();
+        });
+        window.focus(&input.focus_handle(cx));
+
+        self.editing = Some(Editing {
+            area,
+            key,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn rows(&self, area: StorageArea, cx: &mut Context<Self>) -> Vec<gpui::AnyElement> {
+        let theme = cx.theme();
+
+        self.inspector
+            .items(area)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (key, value))| {
+                let editing = self
+                    .editing
+                    .as_ref()
+                    .filter(|editing| editing.area == area && editing.key == key)
+                    .map(|editing| editing.input.clone());
+                let id = (area == StorageArea::Session) as usize * 1_000_000 + index;
+                let edit_key = key.clone();
+                let remove_key = key.clone();
+
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.))
+                    .border_b_1()
+                    .border_color(theme.border_subtle)
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(200.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.info)
+                            .child(key),
+                    )
+                    .child(match editing {
+                        Some(input) => div().flex_1().min_w(px(0.)).child(input),
+                        None => div().flex_1().min_w(px(0.)).child(
+                            div()
+                                .id(("storage-value", id))
+                                .overflow_hidden()
+                                .text_ellipsis()
+                                .text_color(theme.text)
+                                .cursor_text()
+                                .child(value.clone())
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.edit(area, edit_key.clone(), value.clone(), window, cx)
+                                })),
+                        ),
+                    })
+                    .child(
+                        div()
+                            .id(("storage-remove", id))
+                            .flex_none()
+                            .px_1()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .text_color(theme.text_muted)
+                            .hover(|this| this.bg(theme.ghost_hover))
+                            .child("×")
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.inspector.remove_item(cx, area, &remove_key);
+                            })),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+}
+
+impl Drop for StoragePanel {
+    fn drop(&mut self) {
+        self.inspector.stop();
+    }
+}
+
+impl Render for StoragePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let sections = StorageArea::ALL
+            .into_iter()
+            .map(|area| (area, self.rows(area, cx)))
+            .collect::<Vec<_>>();
+        let theme = cx.theme();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .h(px(PANEL_HEIGHT))
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .h_7()
+                    .px_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .text_color(theme.text_secondary)
+                    .child(t!("storage.title"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .text_color(theme.text_placeholder)
+                            .child(self.inspector.origin()),
+                    ),
+            )
+            .child(
+                div()
+                    .id("storage-items")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .font_family("Menlo")
+                    .children(sections.into_iter().map(|(area, rows)| {
+                        div()
+                            .child(
+                                div()
+                                    .px_2()
+                                    .py_1()
+                                    .bg(theme.surface)
+                                    .text_color(theme.text_secondary)
+                                    .child(area.label()),
+                            )
+                            .when(rows.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .text_color(theme.text_placeholder)
+                                        .child(t!("storage.empty")),
+                                )
+                            })
+                            .children(rows)
+                    })),
+            )
+    }
+}
@@ -0,0 +1,436 @@
+use std::sync::Arc;
+
+use gpui::{
+    div, img, prelude::*, px, svg, App as GpuiApp, Context, DismissEvent, EventEmitter,
+    FocusHandle, Focusable, Hsla, Image, ImageSource, KeyDownEvent, MouseButton, SharedString,
+    Subscription, Window,
+};
+
+use crate::{
+    crashes::ProcessMonitor, favicons::FaviconCache, locale::t, omnibox::highlighted, tabs,
+    tabs::TabManager, theme::ActiveTheme, thumbnails::ThumbnailCache,
+};
+
+/// Lowercase `c` without changing how many characters the text has, so
+/// match positions line up with the original.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Characters of `text` where `query`, already folded, first appears in it,
+/// ignoring case.
+fn find(text: &str, query: &[char]) -> Option<Vec<usize>> {
+    let chars = text.chars().map(fold).collect::<Vec<_>>();
+    let last = chars.len().checked_sub(query.len())?;
+    let start = (0..=last).find(|&start| chars[start..start + query.len()] == *query)?;
+
+    Some((start..start + query.len()).collect())
+}
+
+/// A row of the panel.
+struct Item {
+    /// Index into the open tabs.
+    tab: usize,
+    title: String,
+    url: String,
+    favicon: Option<Arc<Image>>,
+    /// Characters of the title and URL matching what was typed.
+    title_matches: Vec<usize>,
+    url_matches: Vec<usize>,
+    muted: bool,
+    loading: bool,
+    crashed: bool,
+}
+
+/// Modal at the top of the window listing the tabs open in every workspace.
+/// Typing filters them by title and URL, and Enter switches to the selected
+/// one. Hovering a tab shows its thumbnail.
+pub struct TabSearch {
+    focus_handle: FocusHandle,
+    query: String,
+    items: Vec<Item>,
+    selected: usize,
+    /// Tab whose row is hovered, with its thumbnail if it has painted.
+    hovered: Option<(usize, Option<Image>)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TabSearch {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let subscriptions =
+            vec![cx.on_blur(&focus_handle, window, |_, _, cx| cx.emit(DismissEvent))];
+
+        let mut search = Self {
+            focus_handle,
+            query: String::new(),
+            items: Vec::new(),
+            selected: 0,
+            hovered: None,
+            _subscriptions: subscriptions,
+        };
+        search.filter(window, cx);
+        search
+    }
+
+    /// List the tabs whose title or URL contains what's typed, in tab order.
+    fn filter(&mut self, window: &Window, cx: &GpuiApp) {
+        self.items.clear();
+        self.selected = 0;
+        self.hovered = None;
+
+        let query = self.query.trim().chars().map(fold).collect::<Vec<_>>();
+        let favicons = cx.global::<FaviconCache>();
+        let monitor = cx.global::<ProcessMonitor>();
+
+        for (index, tab) in cx.global::<TabManager>().tabs().iter().enumerate() {
+            let (url, title) = tab.page().unwrap_or_default();
+            let title = match title.is_empty() {
+                true => t!("tabs.new_tab").to_string(),
+                false => title,
+            };
+
+            let (title_matches, url_matches) = match query.is_empty() {
+                true => (Vec::new(), Vec::new()),
+                false => {
+                    let title_matches = find(&title, &query);
+                    let url_matches = find(&url, &query);
+                    if title_matches.is_none() && url_matches.is_none() {
+                        continue;
+                    }
+                    (
+                        title_matches.unwrap_or_default(),
+                        url_matches.unwrap_or_default(),
+                    )
+                }
+            };
+
+            self.items.push(Item {
+                tab: index,
+                favicon: favicons
+                    .favicon(&tab.browser)
+                    .map(|favicon| favicon.at_scale(window.scale_factor())),
+                muted: tab
+                    .browser
+                    .get_host()
+                    .and_then(|host| host.is_audio_muted())
+                    .unwrap_or(false),
+                loading: tab.browser.is_loading().unwrap_or(false),
+                crashed: monitor.crashed_page(&tab.browser).is_some(),
+                title,
+                url,
+                title_matches,
+                url_matches,
+            });
+        }
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+
+        match keystroke.key.as_str() {
+            "up" => self.selected = self.selected.saturating_sub(1),
+            "down" => {
+                self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1));
+            }
+            "enter" => self.switch(self.selected, window, cx),
+            "escape" => cx.emit(DismissEvent),
+            "backspace" => {
+                self.query.pop();
+                self.filter(window, cx);
+            }
+            _ => {
+                // Leave the browser's shortcuts alone.
+                if keystroke.modifiers.platform || keystroke.modifiers.control {
+                    return;
+                }
+
+                match &keystroke.key_char {
+                    Some(key_char) => {
+                        self.query.push_str(key_char);
+                        self.filter(window, cx);
+                    }
+                    None => return,
+                }
+            }
+        }
+
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    /// Switch to the tab in the row at `index`, and close the panel.
+    fn switch(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(item) = self.items.get(index) else {
+            return;
+        };
+
+        tabs::activate(cx, item.tab);
+        window.refresh();
+        cx.emit(DismissEvent);
+    }
+
+    fn hover(&mut self, tab: usize, hovered: bool, cx: &mut Context<Self>) {
+        if hovered {
+            let thumbnail = cx
+                .global::<TabManager>()
+                .tabs()
+                .get(tab)
+                .and_then(|tab| cx.global::<ThumbnailCache>().thumbnail(&tab.browser));
+            self.hovered = Some((tab, thumbnail));
+        } else if self
+            .hovered
+            .as_ref()
+            .is_some_and(|(other, _)| *other == tab)
+        {
+            self.hovered = None;
+        }
+        cx.notify();
+    }
+
+    fn render_item(&self, index: usize, item: &Item, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let badge = |label: SharedString, color: Hsla| {
+            div()
+                .flex_none()
+                .px_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(color)
+                .text_color(color)
+                .child(label)
+        };
+        let tab = item.tab;
+
+        div()
+            .id(("tab-search-item", index))
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .when(self.selected == index, |this| {
+                this.bg(theme.element_selected)
+            })
+            .hover(|this| this.bg(theme.element_hover))
+            .on_click(cx.listener(move |this, _, window, cx| this.switch(index, window, cx)))
+            .on_hover(cx.listener(move |this, hovered: &bool, _, cx| this.hover(tab, *hovered, cx)))
+            .child(
+                div()
+                    .flex()
+                    .flex_none()
+                    .items_center()
+                    .justify_center()
+                    .size(px(16.))
+                    .map(|this| match item.favicon.clone() {
+                        Some(favicon) => this.child(img(ImageSource::from(favicon)).size(px(16.))),
+                        None => this.child(
+                            svg()
+                                .path("vercel.svg")
+                                .size(px(10.))
+                                .text_color(theme.text),
+                        ),
+                    }),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .child(
+                        div()
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_ellipsis()
+                            .child(highlighted(&item.title, &item.title_matches, cx)),
+                    )
+                    .child(
+                        div()
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_ellipsis()
+                            .text_color(theme.text_placeholder)
+                            .child(highlighted(&item.url, &item.url_matches, cx)),
+                    ),
+            )
+            .when(item.loading, |this| {
+                this.child(badge(t!("tab_search.loading"), theme.info))
+            })
+            .when(item.muted, |this| {
+                this.child(badge(t!("tab_search.muted"), theme.text_muted))
+            })
+            .when(item.crashed, |this| {
+                this.child(badge(t!("tab_search.crashed"), theme.error))
+            })
+    }
+
+    fn render_thumbnail(&self, cx: &GpuiApp) -> Option<impl IntoElement> {
+        let theme = cx.theme();
+        let (_, thumbnail) = self.hovered.as_ref()?;
+
+        Some(
+            div()
+                .flex()
+                .flex_none()
+                .items_center()
+                .justify_center()
+                .w(px(248.))
+                .h(px(168.))
+                .p_1()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .shadow_lg()
+                .text_xs()
+                .text_color(theme.text_muted)
+                .child(match thumbnail.clone() {
+                    Some(thumbnail) => div()
+                        .size_full()
+                        .child(img(ImageSource::from(Arc::new(thumbnail))).size_full()),
+                    None => div().child(t!("thumbnails.no_preview")),
+                }),
+        )
+    }
+}
+
+impl EventEmitter<DismissEvent> for TabSearch {}
+
+impl Focusable for TabSearch {
+    fn focus_handle(&self, _: &GpuiApp) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TabSearch {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| self.render_item(index, item, cx).into_any_element())
+            .collect::<Vec<_>>();
+        let thumbnail = self.render_thumbnail(cx);
+        let theme = cx.theme();
+        let (query, color) = match self.query.is_empty() {
+            true => (
+                t!("tab_search.placeholder").to_string(),
+                theme.text_placeholder,
+            ),
+            false => (self.query.clone(), theme.text),
+        };
+        let empty = items.is_empty();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_start()
+            .justify_center()
+            .gap_2()
+            .pt(px(48.))
+            .px_4()
+            .bg(theme.scrim)
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| cx.emit(DismissEvent)),
+            )
+            .child(
+                div()
+                    .id("tab-search")
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(Self::on_key_down))
+                    // Clicks inside the panel don't close it.
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .flex()
+                    .flex_col()
+                    .w(px(480.))
+                    .max_h(px(420.))
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .shadow_lg()
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .h_8()
+                            .px_3()
+                            .border_b_1()
+                            .border_color(theme.border)
+                            .text_sm()
+                            .text_color(color)
+                            .child(query),
+                    )
+                    .child(
+                        div()
+                            .id("tab-search-items")
+                            .flex()
+                            .flex_col()
+                            .p_1()
+                            .overflow_y_scroll()
+                            .children(items)
+                            .when(empty, |this| {
+                                this.child(
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .text_color(theme.text_placeholder)
+                                        .child(t!("tab_search.no_matches")),
+                                )
+                            }),
+                    ),
+            )
+            .children(thumbnail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> Vec<char> {
+        text.chars().map(fold).collect()
+    }
+
+    #[test]
+    fn fold_keeps_one_character() {
+        assert_eq!(fold('A'), 'a');
+        assert_eq!(fold('é'), 'é');
+        assert_eq!(fold('É'), 'é');
+        // Lowercases to two characters, so only the first is kept.
+        assert_eq!(fold('İ'), 'i');
+        assert_eq!(fold('7'), '7');
+    }
+
+    #[test]
+    fn find_ignores_case() {
+        assert_eq!(find("GitHub", &query("hub")), Some(vec![3, 4, 5]));
+        assert_eq!(find("github", &query("HUB")), Some(vec![3, 4, 5]));
+    }
+
+    #[test]
+    fn find_takes_the_first_match() {
+        assert_eq!(find("abcabc", &query("bc")), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn find_counts_characters_not_bytes() {
+        assert_eq!(find("Café Noir", &query("noir")), Some(vec![5, 6, 7, 8]));
+        assert_eq!(find("İstanbul", &query("stan")), Some(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn find_without_a_match() {
+        assert_eq!(find("rust", &query("go")), None);
+        assert_eq!(find("go", &query("gopher")), None);
+        assert_eq!(find("", &query("a")), None);
+    }
+}
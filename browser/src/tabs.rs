@@ -0,0 +1,888 @@
+use std::sync::Arc;
+
+use cef_ui::{Browser, RequestContext};
+use gpui::{
+    anchored, deferred, div, img, linear_color_stop, linear_gradient, prelude::*, px, rgb, svg,
+    AnyElement, App as GpuiApp, Context, DismissEvent, Div, EventEmitter, Global, Hsla, Image,
+    ImageSource, Pixels, Point, Stateful, Window,
+};
+use url::Url;
+
+use crate::{
+    favicons::FaviconCache,
+    incognito,
+    locale::t,
+    pip::{self, PictureInPicture},
+    settings::Settings,
+    site_isolation,
+    split::SplitView,
+    theme::ActiveTheme,
+    thumbnails::ThumbnailCache,
+    window_manager, BrowserState,
+};
+
+/// Colors given to new tab groups in turn.
+const GROUP_COLORS: [u32; 6] = [0x0a84ff, 0xff453a, 0xffd60a, 0x30d158, 0xbf5af2, 0xff9f0a];
+
+/// A page open in the browser.
+pub struct Tab {
+    /// Stays the same as tabs move, unlike the tab's index.
+    pub id: usize,
+    pub browser: Browser,
+    /// Whether the tab keeps its cookies and storage apart, in memory.
+    pub incognito: bool,
+}
+
+impl Tab {
+    /// The URL and title of the page shown in this tab.
+    pub fn page(&self) -> Option<(String, String)> {
+        let entry = self
+            .browser
+            .get_host()
+            .and_then(|host| host.get_visible_navigation_entry())
+            .ok()?;
+        let url = entry.get_url().ok()?;
+        let title = entry
+            .get_title()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| url.clone());
+
+        Some((url, title))
+    }
+
+    /// Short label for the tab, e.g. `vercel.com`.
+    pub fn label(&self) -> String {
+        self.page()
+            .and_then(|(url, title)| {
+                Url::parse(&url)
+                    .ok()
+                    .and_then(|url| {
+                        url.host_str()
+                            .map(|host| host.trim_start_matches("www.").to_string())
+                    })
+                    .or(Some(title))
+            })
+            .unwrap_or_else(|| t!("tabs.new_tab").to_string())
+    }
+}
+
+/// Tabs clustered under a name and color in the tab strip.
+#[derive(Clone)]
+pub struct TabGroup {
+    pub id: usize,
+    pub name: String,
+    pub color: Hsla,
+    /// Ids of the tabs in the group, which sit next to each other.
+    pub tab_ids: Vec<usize>,
+    /// Whether the group's tabs are hidden, leaving just its name.
+    pub collapsed: bool,
+}
+
+/// Tabs shown in the tab strip together, with the tabs of other workspaces
+/// hidden but still loaded.
+#[derive(Clone)]
+pub struct Workspace {
+    pub id: usize,
+    pub name: String,
+    /// Ids of the tabs in the workspace. Never empty.
+    pub tab_ids: Vec<usize>,
+    /// Id of the tab that was active last in the workspace, to go back to.
+    pub active_tab_id: Option<usize>,
+}
+
+/// The open tabs. The active tab's browser is mirrored in `BrowserState`.
+#[derive(Default)]
+pub struct TabManager {
+    tabs: Vec<Tab>,
+    active: usize,
+    groups: Vec<TabGroup>,
+    /// Made once the first tab opens, so every tab is in one.
+    workspaces: Vec<Workspace>,
+    /// Id of the workspace whose tabs are shown.
+    active_workspace: usize,
+    next_tab_id: usize,
+    next_group_id: usize,
+    next_workspace_id: usize,
+}
+
+impl Global for TabManager {}
+
+impl TabManager {
+    pub fn tabs(&self) -> &[Tab] {
+        &self.tabs
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn groups(&self) -> &[TabGroup] {
+        &self.groups
+    }
+
+    pub fn workspaces(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    pub fn active_workspace(&self) -> usize {
+        self.active_workspace
+    }
+
+    /// Whether the tab at `index` is in the workspace shown.
+    pub fn is_shown(&self, index: usize) -> bool {
+        let Some(tab_id) = self.tabs.get(index).map(|tab| tab.id) else {
+            return false;
+        };
+
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.id == self.active_workspace)
+            .is_none_or(|workspace| workspace.tab_ids.contains(&tab_id))
+    }
+
+    /// Add an empty workspace and show it, returning its id. A tab has to
+    /// be added to it right away, as workspaces aren't left empty.
+    pub fn new_workspace(&mut self) -> usize {
+        self.ensure_workspace();
+        let id = self.add_workspace(Vec::new());
+        self.active_workspace = id;
+        id
+    }
+
+    pub fn rename_workspace(&mut self, id: usize, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        if let Some(workspace) = self.workspaces.iter_mut().find(|w| w.id == id) {
+            workspace.name = name.to_string();
+        }
+    }
+
+    /// Remove the workspace `id`, moving its tabs into the one before it, or
+    /// after it if it's the first. The last workspace can't be removed.
+    pub fn delete_workspace(&mut self, id: usize) {
+        if self.workspaces.len() < 2 {
+            return;
+        }
+        let Some(index) = self.workspaces.iter().position(|w| w.id == id) else {
+            return;
+        };
+
+        let removed = self.workspaces.remove(index);
+        let target = &mut self.workspaces[index.saturating_sub(1)];
+        target.tab_ids.extend(removed.tab_ids);
+        if self.active_workspace == id {
+            self.active_workspace = target.id;
+            target.active_tab_id = self.tabs.get(self.active).map(|tab| tab.id);
+        }
+    }
+
+    /// Recreate a saved workspace of the tabs at `indices`, taking them out
+    /// of the workspaces they're in.
+    pub fn restore_workspace(&mut self, name: String, indices: &[usize]) {
+        let tab_ids = indices
+            .iter()
+            .filter_map(|&index| Some(self.tabs.get(index)?.id))
+            .collect::<Vec<_>>();
+        if tab_ids.is_empty() {
+            return;
+        }
+
+        for workspace in &mut self.workspaces {
+            workspace.tab_ids.retain(|id| !tab_ids.contains(id));
+        }
+        self.workspaces
+            .retain(|workspace| !workspace.tab_ids.is_empty());
+
+        let id = self.add_workspace(tab_ids);
+        if let Some(workspace) = self.workspaces.iter_mut().find(|w| w.id == id) {
+            workspace.name = name;
+        }
+    }
+
+    /// Add a workspace of `tab_ids`, returning its id.
+    fn add_workspace(&mut self, tab_ids: Vec<usize>) -> usize {
+        let id = self.next_workspace_id;
+        self.next_workspace_id += 1;
+        self.workspaces.push(Workspace {
+            id,
+            name: t!("workspaces.default_name", number = id + 1).to_string(),
+            tab_ids,
+            active_tab_id: None,
+        });
+        id
+    }
+
+    /// Put the tabs open so far in a first workspace, if there's none yet.
+    fn ensure_workspace(&mut self) {
+        if self.workspaces.is_empty() {
+            let tab_ids = self.tabs.iter().map(|tab| tab.id).collect();
+            self.active_workspace = self.add_workspace(tab_ids);
+        }
+    }
+
+    /// Show the workspace of the tab at `index`, noting the tab as the one
+    /// active in it.
+    fn enter_workspace_of(&mut self, index: usize) {
+        let Some(tab_id) = self.tabs.get(index).map(|tab| tab.id) else {
+            return;
+        };
+
+        if let Some(workspace) = self
+            .workspaces
+            .iter_mut()
+            .find(|workspace| workspace.tab_ids.contains(&tab_id))
+        {
+            workspace.active_tab_id = Some(tab_id);
+            self.active_workspace = workspace.id;
+        }
+    }
+
+    /// The workspace the tab at `index` is in, if any.
+    fn workspace_of(&self, index: usize) -> Option<&Workspace> {
+        let id = self.tabs.get(index)?.id;
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.tab_ids.contains(&id))
+    }
+
+    /// The group the tab at `index` is in, if any.
+    pub fn group_of(&self, index: usize) -> Option<&TabGroup> {
+        let id = self.tabs.get(index)?.id;
+        self.groups.iter().find(|group| group.tab_ids.contains(&id))
+    }
+
+    /// Put the tab at `index` in a group of its own.
+    pub fn new_group(&mut self, index: usize) {
+        let Some(tab_id) = self.tabs.get(index).map(|tab| tab.id) else {
+            return;
+        };
+
+        let color = GROUP_COLORS[self.next_group_id % GROUP_COLORS.len()];
+        let group_id = self.add_group(
+            t!("tabs.group_name", number = self.next_group_id + 1).to_string(),
+            rgb(color).into(),
+            false,
+        );
+
+        self.leave_group(index);
+        if let Some(group) = self.groups.iter_mut().find(|group| group.id == group_id) {
+            group.tab_ids.push(tab_id);
+        }
+    }
+
+    /// Move the tab at `index` into `group_id`, next to the group's other tabs.
+    pub fn add_to_group(&mut self, index: usize, group_id: usize) {
+        let Some(last) = (0..self.tabs.len()).rev().find(|&i| {
+            self.group_of(i)
+                .is_some_and(|group| group.id == group_id && i != index)
+        }) else {
+            return;
+        };
+
+        self.leave_group(index);
+        let index = self.move_tab(index, if index > last { last + 1 } else { last });
+        self.join_group(index, group_id);
+    }
+
+    /// Take the tab at `index` out of its group, moving it past the group's
+    /// other tabs.
+    pub fn remove_from_group(&mut self, index: usize) {
+        let Some(group_id) = self.group_of(index).map(|group| group.id) else {
+            return;
+        };
+
+        self.leave_group(index);
+        let last = (0..self.tabs.len())
+            .rev()
+            .find(|&i| self.group_of(i).is_some_and(|group| group.id == group_id));
+        if let Some(last) = last.filter(|&last| last > index) {
+            self.move_tab(index, last);
+        }
+    }
+
+    /// Move the tab at `from` to where the tab at `to` is, into that tab's
+    /// group or out of any group.
+    pub fn drop_tab(&mut self, from: usize, to: usize) {
+        if from == to || to >= self.tabs.len() {
+            return;
+        }
+
+        let group_id = self.group_of(to).map(|group| group.id);
+        self.leave_group(from);
+        let index = self.move_tab(from, to);
+        if let Some(group_id) = group_id {
+            self.join_group(index, group_id);
+        }
+    }
+
+    /// Hide or show the tabs of `group_id`.
+    pub fn toggle_collapsed(&mut self, group_id: usize) {
+        if let Some(group) = self.groups.iter_mut().find(|group| group.id == group_id) {
+            group.collapsed = !group.collapsed;
+        }
+    }
+
+    /// Add an empty group, returning its id.
+    fn add_group(&mut self, name: String, color: Hsla, collapsed: bool) -> usize {
+        let id = self.next_group_id;
+        self.next_group_id += 1;
+        self.groups.push(TabGroup {
+            id,
+            name,
+            color,
+            tab_ids: Vec::new(),
+            collapsed,
+        });
+        id
+    }
+
+    /// Recreate a saved group of the tabs at `indices`.
+    pub fn restore_group(&mut self, name: String, color: Hsla, collapsed: bool, indices: &[usize]) {
+        let tab_ids = indices
+            .iter()
+            .filter_map(|&index| Some(self.tabs.get(index)?.id))
+            .collect::<Vec<_>>();
+        if tab_ids.is_empty() {
+            return;
+        }
+
+        let id = self.add_group(name, color, collapsed);
+        if let Some(group) = self.groups.iter_mut().find(|group| group.id == id) {
+            group.tab_ids = tab_ids;
+        }
+    }
+
+    fn join_group(&mut self, index: usize, group_id: usize) {
+        let Some(tab_id) = self.tabs.get(index).map(|tab| tab.id) else {
+            return;
+        };
+
+        if let Some(group) = self.groups.iter_mut().find(|group| group.id == group_id) {
+            group.tab_ids.push(tab_id);
+        }
+    }
+
+    /// Take the tab at `index` out of its group, dropping the group once it's
+    /// empty.
+    fn leave_group(&mut self, index: usize) {
+        let Some(tab_id) = self.tabs.get(index).map(|tab| tab.id) else {
+            return;
+        };
+
+        for group in &mut self.groups {
+            group.tab_ids.retain(|id| *id != tab_id);
+        }
+        self.groups.retain(|group| !group.tab_ids.is_empty());
+    }
+
+    /// Move the tab at `from` to `to`, keeping the same tab active. Returns
+    /// where it ended up.
+    fn move_tab(&mut self, from: usize, to: usize) -> usize {
+        let to = to.min(self.tabs.len() - 1);
+        let active = self.tabs[self.active].id;
+
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+
+        if let Some(index) = self.tabs.iter().position(|tab| tab.id == active) {
+            self.active = index;
+        }
+        to
+    }
+}
+
+/// Add a tab for `browser`, optionally switching to it.
+pub fn add(cx: &mut GpuiApp, browser: Browser, activate: bool) {
+    let zoom_level = cx.global::<Settings>().zoom_level();
+    if let Err(e) = browser
+        .get_host()
+        .and_then(|host| host.set_zoom_level(zoom_level))
+    {
+        eprintln!("Error setting zoom level: {}", e);
+    }
+
+    // Popups share the request context of the tab that opened them.
+    let incognito = incognito::is_incognito(&browser);
+    let tabs = cx.global_mut::<TabManager>();
+    let id = tabs.next_tab_id;
+    tabs.next_tab_id += 1;
+
+    // New tabs open in the workspace shown.
+    tabs.ensure_workspace();
+    let workspace = tabs.active_workspace;
+    if let Some(workspace) = tabs.workspaces.iter_mut().find(|w| w.id == workspace) {
+        workspace.tab_ids.push(id);
+    }
+
+    tabs.tabs.push(Tab {
+        id,
+        browser,
+        incognito,
+    });
+
+    let index = tabs.tabs.len() - 1;
+    if activate || index == 0 {
+        self::activate(cx, index);
+    } else if let Err(e) = tabs.tabs[index]
+        .browser
+        .get_host()
+        .and_then(|host| host.was_hidden(true))
+    {
+        eprintln!("Error hiding tab: {}", e);
+    }
+}
+
+/// Open `url` in a new tab and switch to it. The tab uses `request_context`,
+/// or the global context if `None`.
+pub fn open_url(cx: &mut GpuiApp, url: &str, request_context: Option<RequestContext>) {
+    if let Some(browser) = crate::new_browser_in_context(cx, url, request_context) {
+        add(cx, browser, true);
+    }
+}
+
+/// Switch to the tab at `index`.
+pub fn activate(cx: &mut GpuiApp, index: usize) {
+    let tabs = cx.global::<TabManager>();
+    if index >= tabs.tabs.len() {
+        return;
+    }
+
+    // Only the visible tab, and the ones in Picture in Picture and split
+    // view, need to paint.
+    let pip = cx.global::<PictureInPicture>();
+    let split = cx.global::<SplitView>();
+    for (i, tab) in tabs.tabs.iter().enumerate() {
+        let hidden = i != index && !pip.is_detached(&tab.browser) && !split.contains(&tab.browser);
+        if let Err(e) = tab
+            .browser
+            .get_host()
+            .and_then(|host| host.was_hidden(hidden))
+        {
+            eprintln!("Error showing tab: {}", e);
+        }
+    }
+
+    let tabs = cx.global_mut::<TabManager>();
+    tabs.active = index;
+    tabs.enter_workspace_of(index);
+    let browser = tabs.tabs[index].browser.clone();
+    cx.global_mut::<BrowserState>().browser = Some(browser);
+}
+
+/// Close the tab at `index`. The last tab of a workspace can't be closed.
+pub fn close(cx: &mut GpuiApp, index: usize) {
+    let Some(tab) = remove(cx, index) else {
+        return;
+    };
+    if let Err(e) = tab
+        .browser
+        .get_host()
+        .and_then(|host| host.close_browser(true))
+    {
+        eprintln!("Error closing tab: {}", e);
+    }
+
+    cx.global::<ThumbnailCache>().remove(&tab.browser);
+    cx.global::<FaviconCache>().remove(&tab.browser);
+
+    if cx.global::<PictureInPicture>().is_detached(&tab.browser) {
+        pip::return_to_main(cx);
+    }
+
+    if tab.incognito {
+        incognito::close_if_unused(cx);
+    }
+    site_isolation::close_if_unused(cx);
+}
+
+/// Take the tab at `index` out of the strip, leaving its page open to be
+/// added elsewhere. The last tab of a workspace can't be taken.
+pub fn detach(cx: &mut GpuiApp, index: usize) -> Option<Browser> {
+    let tab = remove(cx, index)?;
+    if cx.global::<PictureInPicture>().is_detached(&tab.browser) {
+        pip::return_to_main(cx);
+    }
+
+    Some(tab.browser)
+}
+
+/// Remove the tab at `index` from the strip and its workspace and group,
+/// switching to a neighbour if it was active.
+fn remove(cx: &mut GpuiApp, index: usize) -> Option<Tab> {
+    let tabs = cx.global_mut::<TabManager>();
+    let last_in_workspace = tabs
+        .workspace_of(index)
+        .is_some_and(|workspace| workspace.tab_ids.len() < 2);
+    if tabs.tabs.len() < 2 || index >= tabs.tabs.len() || last_in_workspace {
+        return None;
+    }
+
+    tabs.leave_group(index);
+    let tab_id = tabs.tabs[index].id;
+    for workspace in &mut tabs.workspaces {
+        workspace.tab_ids.retain(|id| *id != tab_id);
+    }
+    let tab = tabs.tabs.remove(index);
+
+    let active = if tabs.active > index || tabs.active == tabs.tabs.len() {
+        tabs.active - 1
+    } else {
+        tabs.active
+    };
+    // Stay in the workspace the closed tab was in.
+    let active = match tabs.is_shown(active) {
+        true => active,
+        false => (0..tabs.tabs.len())
+            .filter(|&i| tabs.is_shown(i))
+            .min_by_key(|&i| i.abs_diff(active))
+            .unwrap_or(active),
+    };
+    activate(cx, active);
+
+    Some(tab)
+}
+
+/// Put `replacement` in place of `browser` in its tab, keeping the tab where
+/// it is. Returns whether `browser` had a tab.
+pub fn replace(cx: &mut GpuiApp, browser: &Browser, replacement: Browser) -> bool {
+    let zoom_level = cx.global::<Settings>().zoom_level();
+    if let Err(e) = replacement
+        .get_host()
+        .and_then(|host| host.set_zoom_level(zoom_level))
+    {
+        eprintln!("Error setting zoom level: {}", e);
+    }
+
+    let tabs = cx.global_mut::<TabManager>();
+    let Some(index) = tabs
+        .tabs
+        .iter()
+        .position(|tab| tab.browser.is_same(browser.clone()).unwrap_or(false))
+    else {
+        return false;
+    };
+
+    tabs.tabs[index].browser = replacement;
+    let active = tabs.active;
+    if index == active {
+        activate(cx, active);
+    }
+    true
+}
+
+/// The pill shown for a tab in the toolbar, with the page's favicon if it has
+/// one. Incognito tabs are tinted purple.
+pub fn tab_pill(
+    index: usize,
+    label: String,
+    favicon: Option<Arc<Image>>,
+    active: bool,
+    incognito: bool,
+    cx: &GpuiApp,
+) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id(("tab", index))
+        .relative()
+        .flex()
+        .flex_shrink()
+        .border_1()
+        .border_color(match incognito {
+            true => theme.border_incognito,
+            false => theme.border,
+        })
+        .rounded_md()
+        .h_8()
+        .w_64()
+        .min_w(px(96.))
+        .items_center()
+        .cursor_pointer()
+        .when(!active, |this| this.opacity(0.6))
+        .on_click(move |_, _, cx| activate(cx, index))
+        .on_drag(
+            DraggedTab {
+                index,
+                label: label.clone(),
+            },
+            |tab, _, _, cx| cx.new(|_| tab.clone()),
+        )
+        // The dragged tab lands after this one when coming from the left,
+        // before it otherwise.
+        .drag_over::<DraggedTab>({
+            let color = theme.accent;
+            move |style, tab: &DraggedTab, _, _| match tab.index < index {
+                true => style.border_r_2().border_color(color),
+                false => style.border_l_2().border_color(color),
+            }
+        })
+        .on_drop(move |tab: &DraggedTab, window, cx| {
+            cx.global_mut::<TabManager>().drop_tab(tab.index, index);
+            window.refresh();
+        })
+        .child(
+            div()
+                .flex()
+                .p_3()
+                .h_full()
+                .items_center()
+                .justify_start()
+                .bg(linear_gradient(
+                    150.,
+                    linear_color_stop(theme.control_gradient.0, 0.1),
+                    linear_color_stop(theme.control_gradient.1, 0.8),
+                ))
+                .w_full()
+                .rounded_md()
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .h_full()
+                        .justify_center()
+                        .pr_2p5()
+                        .map(|this| match favicon {
+                            Some(favicon) => {
+                                this.child(img(ImageSource::from(favicon)).size(px(16.0)))
+                            }
+                            None => this.child(
+                                svg()
+                                    .path("vercel.svg")
+                                    .size(px(10.0))
+                                    .text_color(theme.text),
+                            ),
+                        }),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .h_full()
+                        .justify_center()
+                        .overflow_hidden()
+                        .text_color(theme.text_secondary)
+                        .text_xs()
+                        .text_center()
+                        .line_height(px(10.0))
+                        .mt(px(1.0))
+                        .child(label),
+                )
+                .child(
+                    div()
+                        .id(("tab-close", index))
+                        .flex()
+                        .items_center()
+                        .h_full()
+                        .justify_center()
+                        .ml_auto()
+                        .on_click(move |_, _, cx| {
+                            cx.stop_propagation();
+                            close(cx, index);
+                        })
+                        .child(
+                            svg()
+                                .path("close.svg")
+                                .size(px(10.0))
+                                .text_color(theme.text_muted),
+                        ),
+                ),
+        )
+}
+
+/// A tab being dragged along the tab strip.
+#[derive(Clone)]
+pub struct DraggedTab {
+    index: usize,
+    label: String,
+}
+
+impl Render for DraggedTab {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .px_3()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .text_xs()
+            .text_color(theme.text_secondary)
+            .child(self.label.clone())
+    }
+}
+
+/// Tear the dragged tab out of the tab strip into a browser window of its
+/// own, with its top left corner at `position` on screen.
+pub fn tear_out(cx: &mut GpuiApp, tab: &DraggedTab, position: Point<Pixels>) {
+    window_manager::tear_out(cx, tab.index, position);
+}
+
+/// Lay out the pills of the tabs in the workspace shown, in order,
+/// clustering the tabs of a group behind its name and over a line in its
+/// color. A collapsed group shows just its name, and the active tab if it's
+/// in the group.
+pub fn strip(manager: &TabManager, pills: Vec<Stateful<Div>>, cx: &GpuiApp) -> Vec<AnyElement> {
+    let in_group = |index: usize, id: usize| manager.group_of(index).is_some_and(|g| g.id == id);
+
+    let mut elements = Vec::new();
+    let mut pills = pills
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| manager.is_shown(*index))
+        .peekable();
+    while let Some((index, pill)) = pills.next() {
+        let Some(group) = manager.group_of(index).cloned() else {
+            elements.push(pill.into_any_element());
+            continue;
+        };
+
+        let mut members = vec![(index, pill)];
+        while let Some(member) = pills.next_if(|(index, _)| in_group(*index, group.id)) {
+            members.push(member);
+        }
+
+        let collapsed = group.collapsed;
+        let members = members
+            .into_iter()
+            .filter(|(index, _)| !collapsed || *index == manager.active())
+            .map(|(_, pill)| pill);
+        elements.push(group_cluster(group, members, cx).into_any_element());
+    }
+
+    elements
+}
+
+fn group_cluster(group: TabGroup, pills: impl Iterator<Item = Stateful<Div>>, cx: &GpuiApp) -> Div {
+    let theme = cx.theme();
+
+    let id = group.id;
+
+    div()
+        .flex()
+        .flex_shrink()
+        .min_w(px(0.))
+        .items_center()
+        .gap_1()
+        .pb_0p5()
+        .border_b_2()
+        .border_color(group.color)
+        .child(
+            div()
+                .id(("tab-group", id))
+                .flex_none()
+                .px_2()
+                .py_0p5()
+                .rounded_md()
+                .cursor_pointer()
+                .bg(group.color)
+                .text_xs()
+                .text_color(theme.background)
+                .hover(|this| this.opacity(0.85))
+                .child(group.name)
+                .on_click(move |_, window, cx| {
+                    cx.global_mut::<TabManager>().toggle_collapsed(id);
+                    window.refresh();
+                })
+                .drag_over::<DraggedTab>(|style, _, _, _| style.opacity(0.7))
+                .on_drop(move |tab: &DraggedTab, window, cx| {
+                    cx.global_mut::<TabManager>().add_to_group(tab.index, id);
+                    window.refresh();
+                }),
+        )
+        .children(pills)
+}
+
+/// Right-click menu of a tab, for grouping it.
+pub struct TabMenu {
+    index: usize,
+    position: Point<Pixels>,
+}
+
+impl TabMenu {
+    pub fn new(index: usize, position: Point<Pixels>) -> Self {
+        Self { index, position }
+    }
+
+    fn run(&mut self, cx: &mut Context<Self>, action: impl FnOnce(&mut TabManager, usize)) {
+        action(cx.global_mut::<TabManager>(), self.index);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for TabMenu {}
+
+impl Render for TabMenu {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let manager = cx.global::<TabManager>();
+        let current = manager.group_of(self.index).map(|group| group.id);
+        let others = manager
+            .groups()
+            .iter()
+            .filter(|group| Some(group.id) != current)
+            .map(|group| (group.id, group.name.clone()))
+            .collect::<Vec<_>>();
+
+        let item = |id: (&'static str, usize), label: String| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_sm()
+                .cursor_pointer()
+                .hover(|this| this.bg(theme.element_selected))
+                .child(label)
+        };
+
+        deferred(
+            anchored().position(self.position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .p_1()
+                    .min_w(px(160.))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                    .child(
+                        item(("tab-menu-new-group", 0), t!("tabs.new_group").to_string()).on_click(
+                            cx.listener(|this, _, _, cx| {
+                                this.run(cx, |manager, index| manager.new_group(index))
+                            }),
+                        ),
+                    )
+                    .children(others.into_iter().map(|(group_id, name)| {
+                        item(
+                            ("tab-menu-add", group_id),
+                            t!("tabs.add_to_group", name = name).to_string(),
+                        )
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.run(cx, |manager, index| manager.add_to_group(index, group_id))
+                        }))
+                    }))
+                    .when(current.is_some(), |this| {
+                        this.child(
+                            item(
+                                ("tab-menu-remove", 0),
+                                t!("tabs.remove_from_group").to_string(),
+                            )
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.run(cx, |manager, index| manager.remove_from_group(index))
+                            })),
+                        )
+                    }),
+            ),
+        )
+    }
+}
@@ -0,0 +1,98 @@
+use cef_ui::TerminationStatus;
+use gpui::{App as GpuiApp, Global, PromptLevel, Window};
+use sentry::{ClientInitGuard, ClientOptions, Level};
+
+use crate::{
+    about,
+    locale::t,
+    settings::{self, Settings},
+};
+
+/// Where crash reports go, set when the browser is built. Without it nothing
+/// is ever sent, whatever the setting says.
+const DSN: Option<&str> = option_env!("SENTRY_DSN");
+
+/// Opt-in crash reporting. While the user allows it, panics and renderer
+/// crashes are sent to Sentry, tagged with the browser and CEF versions. The
+/// OS and its version come with every event from Sentry's own contexts.
+#[derive(Default)]
+pub struct BrowserTelemetry {
+    /// Keeps the client bound while reporting is on.
+    _guard: Option<ClientInitGuard>,
+}
+
+impl Global for BrowserTelemetry {}
+
+/// Start or stop reporting to match the setting. Called at startup, before
+/// CEF, and whenever the setting changes.
+pub fn init(cx: &mut GpuiApp) {
+    let enabled = cx.global::<Settings>().telemetry_enabled;
+
+    // Dropping the guard sends what's queued and unbinds the client.
+    let guard = match (enabled, DSN) {
+        (true, Some(dsn)) => {
+            let guard = sentry::init((
+                dsn,
+                ClientOptions {
+                    release: Some(concat!("browser@", env!("CARGO_PKG_VERSION")).into()),
+                    ..Default::default()
+                },
+            ));
+            sentry::configure_scope(|scope| {
+                scope.set_tag("browser.version", env!("CARGO_PKG_VERSION"));
+                scope.set_tag("cef.version", about::built_against());
+            });
+            Some(guard)
+        }
+        _ => None,
+    };
+
+    cx.set_global(BrowserTelemetry { _guard: guard });
+}
+
+/// Report an error that stopped the browser from starting. Does nothing
+/// unless reporting is on.
+pub fn capture_error(error: &anyhow::Error) {
+    sentry::integrations::anyhow::capture_anyhow(error);
+}
+
+/// Report a renderer that went away, without the page's URL. Called on the
+/// CEF UI thread.
+pub fn capture_renderer_crash(status: TerminationStatus) {
+    sentry::capture_message(
+        &format!("Renderer process terminated: {:?}", status),
+        Level::Error,
+    );
+}
+
+/// Ask once, at first launch, whether crash reports may be sent.
+pub fn ask(window: &mut Window, cx: &mut GpuiApp) {
+    if cx.global::<Settings>().telemetry_asked {
+        return;
+    }
+
+    let allow = t!("telemetry.allow");
+    let deny = t!("telemetry.deny");
+    let answer = window.prompt(
+        PromptLevel::Info,
+        &t!("telemetry.title"),
+        Some(&t!("telemetry.description")),
+        &[allow.as_ref(), deny.as_ref()],
+        cx,
+    );
+
+    cx.spawn(async move |cx| {
+        let Ok(answer) = answer.await else {
+            return;
+        };
+
+        cx.update(|cx| {
+            settings::update(cx, |settings| {
+                settings.telemetry_asked = true;
+                settings.telemetry_enabled = answer == 0;
+            })
+        })
+        .ok();
+    })
+    .detach();
+}
@@ -0,0 +1,333 @@
+use gpui::{
+    div, prelude::*, px, App as GpuiApp, Context, Div, EventEmitter, FocusHandle, Focusable,
+    KeyDownEvent, SharedString, Window,
+};
+
+use crate::theme::ActiveTheme;
+
+/// Events emitted by a text input.
+pub enum TextInputEvent {
+    Confirm,
+    Cancel,
+}
+
+/// Text being edited, and where the caret is in it as a byte offset on a
+/// character boundary.
+struct Buffer {
+    text: String,
+    cursor: usize,
+}
+
+impl Buffer {
+    /// `text` with the caret at its end.
+    fn new(text: String) -> Self {
+        let cursor = text.len();
+        Self { text, cursor }
+    }
+
+    /// Type `text` at the caret, leaving the caret after it.
+    fn insert(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    fn backspace(&mut self) {
+        let start = self.previous();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    fn delete(&mut self) {
+        let end = self.next();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    fn move_left(&mut self) {
+        self.cursor = self.previous();
+    }
+
+    fn move_right(&mut self) {
+        self.cursor = self.next();
+    }
+
+    fn move_to_line_start(&mut self) {
+        self.cursor = line_start(&self.text, self.cursor);
+    }
+
+    fn move_to_line_end(&mut self) {
+        self.cursor = line_end(&self.text, self.cursor);
+    }
+
+    /// Move to the line above, keeping the column if it's long enough.
+    fn move_up(&mut self) {
+        let start = line_start(&self.text, self.cursor);
+        if start == 0 {
+            self.cursor = 0;
+            return;
+        }
+
+        let column = self.text[start..self.cursor].chars().count();
+        self.cursor = column_in_line(&self.text, line_start(&self.text, start - 1), column);
+    }
+
+    /// Move to the line below, keeping the column if it's long enough.
+    fn move_down(&mut self) {
+        let end = line_end(&self.text, self.cursor);
+        if end == self.text.len() {
+            self.cursor = end;
+            return;
+        }
+
+        let column = self.text[line_start(&self.text, self.cursor)..self.cursor]
+            .chars()
+            .count();
+        self.cursor = column_in_line(&self.text, end + 1, column);
+    }
+
+    /// Offset of the character before the caret.
+    fn previous(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// Offset of the character after the caret.
+    fn next(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+}
+
+/// Offset of the start of the line `offset` is on.
+fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map_or(0, |index| index + 1)
+}
+
+/// Offset of the end of the line `offset` is on, before its newline.
+fn line_end(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .find('\n')
+        .map_or(text.len(), |index| offset + index)
+}
+
+/// Offset of the `column`th character of the line starting at `start`, or
+/// of its end if it's shorter.
+fn column_in_line(text: &str, start: usize, column: usize) -> usize {
+    let line = &text[start..line_end(text, start)];
+    start
+        + line
+            .char_indices()
+            .nth(column)
+            .map_or(line.len(), |(index, _)| index)
+}
+
+/// A minimal text field, on a single line unless made `multi_line`.
+pub struct TextInput {
+    buffer: Buffer,
+    placeholder: SharedString,
+    masked: bool,
+    multi_line: bool,
+    focus_handle: FocusHandle,
+}
+
+impl TextInput {
+    pub fn new(text: impl Into<String>, cx: &mut Context<Self>) -> Self {
+        Self {
+            buffer: Buffer::new(text.into()),
+            placeholder: SharedString::default(),
+            masked: false,
+            multi_line: false,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    /// Text shown while the field is empty.
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Hide the contents, e.g. for passwords.
+    pub fn masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
+
+    /// Take several lines, Enter starting a new one instead of confirming.
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.buffer.text
+    }
+
+    pub fn set_text(&mut self, text: String, cx: &mut Context<Self>) {
+        self.buffer = Buffer::new(text);
+        cx.notify();
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        let secondary = keystroke.modifiers.secondary();
+
+        match keystroke.key.as_str() {
+            "enter" if self.multi_line => self.buffer.insert("\n"),
+            "enter" => cx.emit(TextInputEvent::Confirm),
+            "escape" => cx.emit(TextInputEvent::Cancel),
+            "backspace" => self.buffer.backspace(),
+            "delete" => self.buffer.delete(),
+            "left" if secondary => self.buffer.move_to_line_start(),
+            "right" if secondary => self.buffer.move_to_line_end(),
+            "left" => self.buffer.move_left(),
+            "right" => self.buffer.move_right(),
+            "up" if self.multi_line => self.buffer.move_up(),
+            "down" if self.multi_line => self.buffer.move_down(),
+            "home" => self.buffer.move_to_line_start(),
+            "end" => self.buffer.move_to_line_end(),
+            "v" if secondary => {
+                let pasted = cx.read_from_clipboard().and_then(|item| item.text());
+                if let Some(pasted) = pasted {
+                    let pasted = pasted.replace("\r\n", "\n");
+                    match self.multi_line {
+                        true => self.buffer.insert(&pasted),
+                        false => self.buffer.insert(&pasted.replace('\n', " ")),
+                    }
+                }
+            }
+            _ => {
+                if keystroke.modifiers.platform || keystroke.modifiers.control {
+                    return;
+                }
+
+                if let Some(key_char) = &keystroke.key_char {
+                    self.buffer.insert(key_char);
+                }
+            }
+        }
+
+        cx.stop_propagation();
+        cx.notify();
+    }
+}
+
+impl EventEmitter<TextInputEvent> for TextInput {}
+
+impl Focusable for TextInput {
+    fn focus_handle(&self, _: &GpuiApp) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TextInput {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let focused = self.focus_handle.is_focused(window);
+        let text = &self.buffer.text;
+        let (content, color, cursor) = if text.is_empty() {
+            (self.placeholder.to_string(), theme.text_placeholder, 0)
+        } else if self.masked {
+            let before = text[..self.buffer.cursor].chars().count();
+            (
+                "•".repeat(text.chars().count()),
+                theme.text,
+                before * '•'.len_utf8(),
+            )
+        } else {
+            (text.clone(), theme.text, self.buffer.cursor)
+        };
+
+        // One row per line, the caret drawn between the text either side of
+        // it on its line.
+        let mut lines = Vec::<Div>::new();
+        let mut start = 0;
+        for line in content.split('\n') {
+            let end = start + line.len();
+            let row = div().flex().min_h(px(16.)).items_center();
+            lines.push(match focused && (start..=end).contains(&cursor) {
+                true => row
+                    .child(line[..cursor - start].to_string())
+                    .child(div().flex_none().w(px(1.)).h(px(14.)).bg(theme.text))
+                    .child(line[cursor - start..].to_string()),
+                false => row.child(line.to_string()),
+            });
+            start = end + 1;
+        }
+
+        div()
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::on_key_down))
+            .flex()
+            .map(|this| match self.multi_line {
+                true => this.items_start().min_h(px(96.)).py_1().whitespace_normal(),
+                false => this.items_center().h_6(),
+            })
+            .px_2()
+            .min_w(px(120.))
+            .rounded_md()
+            .border_1()
+            .border_color(if focused {
+                theme.text_placeholder
+            } else {
+                theme.border
+            })
+            .bg(theme.background)
+            .text_xs()
+            .text_color(color)
+            .child(div().flex().flex_col().min_w(px(0.)).children(lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_happen_at_the_caret() {
+        let mut buffer = Buffer::new(String::from("héllo"));
+        buffer.move_left();
+        buffer.move_left();
+        buffer.move_left();
+        assert_eq!(buffer.cursor, "hé".len());
+
+        buffer.backspace();
+        buffer.insert("e");
+        buffer.delete();
+        assert_eq!(buffer.text, "helo");
+        assert_eq!(buffer.cursor, 2);
+
+        buffer.move_to_line_start();
+        buffer.move_left();
+        buffer.insert("ok, ");
+        buffer.move_to_line_end();
+        buffer.move_right();
+        buffer.delete();
+        buffer.insert("!");
+        assert_eq!(buffer.text, "ok, helo!");
+    }
+
+    #[test]
+    fn the_caret_moves_between_lines_keeping_its_column() {
+        let mut buffer = Buffer::new(String::from("first line\nab\nthird line"));
+        buffer.move_up();
+        assert_eq!(buffer.cursor, "first line\nab".len());
+
+        buffer.move_up();
+        assert_eq!(buffer.cursor, "fi".len());
+        buffer.move_to_line_start();
+        buffer.move_right();
+        buffer.move_down();
+        assert_eq!(buffer.cursor, "first line\na".len());
+
+        buffer.move_down();
+        buffer.move_down();
+        assert_eq!(buffer.cursor, buffer.text.len());
+        buffer.move_to_line_start();
+        assert_eq!(buffer.cursor, "first line\nab\n".len());
+    }
+}
@@ -0,0 +1,299 @@
+use gpui::{
+    div, prelude::*, px, rgb, rgba, App as GpuiApp, Div, Global, Hsla, SharedString, Stateful,
+    WindowAppearance,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{locale::t, settings::Settings};
+
+/// Which theme the browser chrome is drawn in, as saved in settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeMode {
+    /// Follow the system's light or dark appearance.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::System, ThemeMode::Light, ThemeMode::Dark];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            ThemeMode::System => t!("theme.system"),
+            ThemeMode::Light => t!("theme.light"),
+            ThemeMode::Dark => t!("theme.dark"),
+        }
+    }
+}
+
+/// Colors the browser chrome is drawn with. Pages, paper in print previews
+/// and video keep their own colors.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    /// Whether pages asking for the browser's appearance are told it's dark.
+    pub dark: bool,
+
+    /// Panels and sheets laid over the page.
+    pub background: Hsla,
+    /// Menus, popovers and dialogs.
+    pub surface: Hsla,
+    /// Controls and frames inside a surface.
+    pub surface_raised: Hsla,
+    /// Browser pages standing in for a website, like certificate errors.
+    pub page_background: Hsla,
+    /// Behind the page before it paints.
+    pub viewport: Hsla,
+    /// Tint over the blurred window behind the tab strip and toolbar.
+    pub toolbar: Hsla,
+    /// The toolbar while the active tab is incognito.
+    pub toolbar_incognito: Hsla,
+    /// Top and bottom of the gradient filling tabs and the omnibox.
+    pub control_gradient: (Hsla, Hsla),
+
+    pub text: Hsla,
+    /// Text standing out from the rest, like matched characters.
+    pub text_emphasis: Hsla,
+    /// Text on an accent-colored background.
+    pub text_on_accent: Hsla,
+    /// Headings and labels.
+    pub text_secondary: Hsla,
+    /// Details next to the main text.
+    pub text_muted: Hsla,
+    /// Placeholders and empty states.
+    pub text_placeholder: Hsla,
+    /// Controls that can't be used right now.
+    pub text_disabled: Hsla,
+
+    pub border: Hsla,
+    /// Dividers between rows.
+    pub border_subtle: Hsla,
+    /// Borders of incognito tabs.
+    pub border_incognito: Hsla,
+
+    /// Rows and list items under the mouse.
+    pub element_hover: Hsla,
+    /// The selected row or item.
+    pub element_selected: Hsla,
+    /// Toolbar icons under the mouse.
+    pub ghost_hover: Hsla,
+    /// Dims the page behind modals.
+    pub scrim: Hsla,
+
+    pub accent: Hsla,
+    pub info: Hsla,
+    pub success: Hsla,
+    pub warning: Hsla,
+    pub error: Hsla,
+    /// Media requests and other badges apart from the status colors.
+    pub media: Hsla,
+}
+
+impl Global for Theme {}
+
+impl Theme {
+    /// The browser's original look.
+    pub fn dark() -> Self {
+        Self {
+            dark: true,
+            background: rgb(0x1c1c1e).into(),
+            surface: rgb(0x2a2a2c).into(),
+            surface_raised: rgb(0x3a3a3c).into(),
+            page_background: rgb(0x202124).into(),
+            viewport: rgb(0xffffff).into(),
+            toolbar: rgba(0x0404055e).into(),
+            toolbar_incognito: rgba(0x3b1a5ccc).into(),
+            control_gradient: (rgba(0x2e2e2e1c).into(), rgba(0x6161621c).into()),
+            text: rgb(0xf2f2f2).into(),
+            text_emphasis: rgb(0xffffff).into(),
+            text_on_accent: rgb(0xffffff).into(),
+            text_secondary: rgb(0xd1d1d1).into(),
+            text_muted: rgba(0xffffff99).into(),
+            text_placeholder: rgba(0xffffff66).into(),
+            text_disabled: rgb(0x7a7a7a).into(),
+            border: rgba(0xd3d9d92b).into(),
+            border_subtle: rgba(0xd3d9d914).into(),
+            border_incognito: rgba(0x9b6bd66b).into(),
+            element_hover: rgba(0xffffff10).into(),
+            element_selected: rgba(0xffffff1a).into(),
+            ghost_hover: rgba(0x00000010).into(),
+            scrim: rgba(0x00000066).into(),
+            accent: rgb(0x0a84ff).into(),
+            info: rgb(0x7cb7ff).into(),
+            success: rgb(0x30d158).into(),
+            warning: rgb(0xf5c518).into(),
+            error: rgb(0xff6b6b).into(),
+            media: rgb(0xbf5af2).into(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            dark: false,
+            background: rgb(0xf2f2f7).into(),
+            surface: rgb(0xffffff).into(),
+            surface_raised: rgb(0xe5e5ea).into(),
+            page_background: rgb(0xf8f9fa).into(),
+            viewport: rgb(0xffffff).into(),
+            toolbar: rgba(0xf6f6f8c0).into(),
+            toolbar_incognito: rgba(0xe4d6f5cc).into(),
+            control_gradient: (rgba(0xffffff99).into(), rgba(0xe5e5ea99).into()),
+            text: rgb(0x1c1c1e).into(),
+            text_emphasis: rgb(0x000000).into(),
+            text_on_accent: rgb(0xffffff).into(),
+            text_secondary: rgb(0x3a3a3c).into(),
+            text_muted: rgba(0x0000008c).into(),
+            text_placeholder: rgba(0x00000061).into(),
+            text_disabled: rgb(0x8e8e93).into(),
+            border: rgba(0x0000001f).into(),
+            border_subtle: rgba(0x0000000f).into(),
+            border_incognito: rgba(0x8e5cc96b).into(),
+            element_hover: rgba(0x0000000a).into(),
+            element_selected: rgba(0x00000014).into(),
+            ghost_hover: rgba(0x0000000d).into(),
+            scrim: rgba(0x00000040).into(),
+            accent: rgb(0x007aff).into(),
+            info: rgb(0x0a6cd6).into(),
+            success: rgb(0x248a3d).into(),
+            warning: rgb(0xb25000).into(),
+            error: rgb(0xd70015).into(),
+            media: rgb(0x8944ab).into(),
+        }
+    }
+
+    /// The theme `mode` picks while the system appearance is `appearance`.
+    pub fn resolve(mode: ThemeMode, appearance: WindowAppearance) -> Self {
+        match (mode, appearance) {
+            (ThemeMode::Dark, _) => Self::dark(),
+            (ThemeMode::Light, _) => Self::light(),
+            (ThemeMode::System, WindowAppearance::Dark | WindowAppearance::VibrantDark) => {
+                Self::dark()
+            }
+            (ThemeMode::System, WindowAppearance::Light | WindowAppearance::VibrantLight) => {
+                Self::light()
+            }
+        }
+    }
+}
+
+/// Access to the theme the chrome is currently drawn in.
+pub trait ActiveTheme {
+    fn theme(&self) -> &Theme;
+}
+
+impl ActiveTheme for GpuiApp {
+    fn theme(&self) -> &Theme {
+        self.global::<Theme>()
+    }
+}
+
+/// Start in the theme picked in settings, for the system appearance at
+/// startup.
+pub fn init(cx: &mut GpuiApp) {
+    let appearance = cx.window_appearance();
+    apply(cx, appearance);
+}
+
+/// Switch to the theme picked in settings, for the system appearance
+/// `appearance`, and redraw the windows in it.
+pub fn apply(cx: &mut GpuiApp, appearance: WindowAppearance) {
+    let mode = cx.global::<Settings>().theme;
+    cx.set_global(Theme::resolve(mode, appearance));
+    cx.refresh_windows();
+}
+
+/// The system appearance changed. Only matters when following it.
+pub fn on_appearance_changed(cx: &mut GpuiApp, appearance: WindowAppearance) {
+    if cx.global::<Settings>().theme == ThemeMode::System {
+        apply(cx, appearance);
+    }
+}
+
+/// A miniature of the browser chrome drawn in `theme`.
+fn miniature(theme: Theme) -> Div {
+    let line = |width: f32, color: Hsla| div().w(px(width)).h(px(3.)).rounded_sm().bg(color);
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_1()
+        .min_w(px(0.))
+        .overflow_hidden()
+        .bg(theme.background)
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .gap_1()
+                .h(px(14.))
+                .px_1()
+                .bg(theme.surface)
+                .border_b_1()
+                .border_color(theme.border)
+                .child(div().size(px(4.)).rounded_full().bg(theme.text_muted))
+                .child(
+                    div()
+                        .flex_1()
+                        .h(px(7.))
+                        .rounded_sm()
+                        .border_1()
+                        .border_color(theme.border)
+                        .bg(theme.element_selected),
+                ),
+        )
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .p_1p5()
+                .child(line(48., theme.text))
+                .child(line(64., theme.text_muted))
+                .child(line(36., theme.text_placeholder))
+                .child(line(20., theme.accent)),
+        )
+}
+
+/// A card in the settings' theme grid previewing `mode`, picked when
+/// `selected`. System previews light and dark side by side.
+pub fn preview(mode: ThemeMode, selected: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+    let swatch = match mode {
+        ThemeMode::System => div()
+            .flex()
+            .child(miniature(Theme::light()))
+            .child(miniature(Theme::dark())),
+        ThemeMode::Light => div().flex().child(miniature(Theme::light())),
+        ThemeMode::Dark => div().flex().child(miniature(Theme::dark())),
+    };
+
+    div()
+        .id(("theme-preview", mode as usize))
+        .flex()
+        .flex_col()
+        .gap_1()
+        .flex_1()
+        .cursor_pointer()
+        .child(
+            swatch
+                .h(px(64.))
+                .rounded_md()
+                .overflow_hidden()
+                .border_2()
+                .border_color(match selected {
+                    true => theme.accent,
+                    false => theme.border,
+                }),
+        )
+        .child(
+            div()
+                .text_color(match selected {
+                    true => theme.text,
+                    false => theme.text_muted,
+                })
+                .child(mode.label()),
+        )
+        .hover(|this| this.opacity(0.85))
+}
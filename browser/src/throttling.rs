@@ -0,0 +1,617 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use cef_ui::{
+    AuthCallback, Browser, Callback, ErrorCode, Request, RequestContext, ResourceHandler,
+    ResourceHandlerCallbacks, ResourceReadCallback, ResourceSkipCallback, Response, UrlRequest,
+    UrlRequestClient, UrlRequestClientCallbacks, UrlRequestFlags, UrlRequestStatus,
+};
+use gpui::{
+    deferred, div, prelude::*, px, App as GpuiApp, Context, Div, Entity, Global, SharedString,
+    Stateful, Subscription, Window,
+};
+use url::Url;
+
+use crate::{
+    locale::t,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+/// Reads shorter than this are answered straight away rather than after a
+/// pause.
+const MIN_DELAY: Duration = Duration::from_millis(1);
+
+/// Headers describing the body as it came over the wire, which no longer
+/// hold for the decoded body handed on.
+const STALE_HEADERS: [&str; 3] = ["Content-Encoding", "Content-Length", "Transfer-Encoding"];
+
+/// Bandwidth and latency a throttled connection is limited to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NetworkConditions {
+    /// Kilobytes a second.
+    pub download: u32,
+    /// Kilobytes a second.
+    pub upload: u32,
+    /// Milliseconds before the response starts.
+    pub latency: u32,
+}
+
+impl NetworkConditions {
+    /// How long moving `bytes` at `kilobytes` a second takes.
+    fn transfer_time(bytes: usize, kilobytes: u32) -> Duration {
+        Duration::from_secs_f64(bytes as f64 / (kilobytes.max(1) as f64 * 1024.))
+    }
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        Self {
+            download: 500,
+            upload: 250,
+            latency: 100,
+        }
+    }
+}
+
+/// A way of limiting the network.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThrottlePreset {
+    #[default]
+    Off,
+    Slow4G,
+    ThreeG,
+    Offline,
+    Custom,
+}
+
+impl ThrottlePreset {
+    const ALL: [ThrottlePreset; 5] = [
+        ThrottlePreset::Off,
+        ThrottlePreset::Slow4G,
+        ThrottlePreset::ThreeG,
+        ThrottlePreset::Offline,
+        ThrottlePreset::Custom,
+    ];
+
+    fn label(&self) -> SharedString {
+        match self {
+            ThrottlePreset::Off => t!("throttling.presets.off"),
+            ThrottlePreset::Slow4G => t!("throttling.presets.slow_4g"),
+            ThrottlePreset::ThreeG => t!("throttling.presets.three_g"),
+            ThrottlePreset::Offline => t!("throttling.presets.offline"),
+            ThrottlePreset::Custom => t!("throttling.presets.custom"),
+        }
+    }
+
+    /// The conditions requests are held to, `None` when they fail as if
+    /// there were no network.
+    fn conditions(&self, custom: NetworkConditions) -> Option<NetworkConditions> {
+        match self {
+            ThrottlePreset::Off | ThrottlePreset::Offline => None,
+            ThrottlePreset::Slow4G => Some(NetworkConditions {
+                download: 180,
+                upload: 84,
+                latency: 150,
+            }),
+            ThrottlePreset::ThreeG => Some(NetworkConditions {
+                download: 50,
+                upload: 50,
+                latency: 400,
+            }),
+            ThrottlePreset::Custom => Some(custom),
+        }
+    }
+}
+
+#[derive(Default)]
+struct NetworkThrottlingInner {
+    preset: ThrottlePreset,
+    custom: NetworkConditions,
+    changed: bool,
+}
+
+/// The simulated network conditions pages load under, for seeing how they
+/// behave on slow connections.
+#[derive(Clone, Default)]
+pub struct NetworkThrottling(Arc<Mutex<NetworkThrottlingInner>>);
+
+impl Global for NetworkThrottling {}
+
+impl NetworkThrottling {
+    pub fn preset(&self) -> ThrottlePreset {
+        self.0.lock().unwrap().preset
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.preset() != ThrottlePreset::Off
+    }
+
+    fn set_preset(&self, preset: ThrottlePreset) {
+        let mut inner = self.0.lock().unwrap();
+        inner.preset = preset;
+        inner.changed = true;
+    }
+
+    fn custom(&self) -> NetworkConditions {
+        self.0.lock().unwrap().custom
+    }
+
+    fn set_custom(&self, custom: NetworkConditions) {
+        let mut inner = self.0.lock().unwrap();
+        inner.custom = custom;
+        inner.changed = true;
+    }
+
+    /// Whether the preset changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// While throttled, fetch `request` in the handler so its response can be
+    /// held back. Unthrottled, `None` lets it load.
+    pub fn resource_handler(
+        &self,
+        browser: Option<&Browser>,
+        request: &Request,
+    ) -> Option<ResourceHandler> {
+        let (preset, custom) = {
+            let inner = self.0.lock().unwrap();
+            (inner.preset, inner.custom)
+        };
+        if preset == ThrottlePreset::Off {
+            return None;
+        }
+
+        let url = request.get_url().ok()?;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return None;
+        }
+
+        // Fetched in the page's own context so it gets the page's cookies.
+        let context = browser
+            .and_then(|browser| browser.get_host().ok())
+            .and_then(|host| host.get_request_context().ok());
+
+        Some(ResourceHandler::new(ThrottledResourceHandler {
+            conditions: preset.conditions(custom),
+            context,
+            url,
+            fetch: Arc::default(),
+            request: None,
+            offset: 0,
+        }))
+    }
+}
+
+/// A response fetched on behalf of the page.
+#[derive(Default)]
+struct Fetch {
+    response: Option<Response>,
+    body: Vec<u8>,
+    error: Option<ErrorCode>,
+    cancelled: bool,
+}
+
+/// Run `f` after `delay` unless the request was cancelled in the meantime.
+fn after(delay: Duration, fetch: &Arc<Mutex<Fetch>>, f: impl FnOnce() + Send + 'static) {
+    let fetch = fetch.clone();
+
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        if !fetch.lock().unwrap().cancelled {
+            f();
+        }
+    });
+}
+
+/// Collects the response for the page's request, letting the page have it
+/// once the latency and upload time have passed.
+struct FetchClient {
+    fetch: Arc<Mutex<Fetch>>,
+    delay: Duration,
+    open: Callback,
+}
+
+impl UrlRequestClientCallbacks for FetchClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        {
+            let mut fetch = self.fetch.lock().unwrap();
+            fetch.response = request.get_response().ok().flatten();
+
+            // Redirects stop the fetch too, so the page can follow them.
+            let redirected = fetch
+                .response
+                .as_ref()
+                .and_then(|response| response.get_status().ok())
+                .is_some_and(|status| (300..400).contains(&status));
+            if !redirected && request.get_request_status().ok() != Some(UrlRequestStatus::Success) {
+                fetch.error = Some(request.get_request_error().unwrap_or(ErrorCode::Failed));
+            }
+        }
+
+        let open = self.open.clone();
+        after(self.delay, &self.fetch, move || {
+            open.cont().ok();
+        });
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.fetch.lock().unwrap().body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Fetches a request itself and hands the response to the page no faster
+/// than the throttled connection would.
+struct ThrottledResourceHandler {
+    /// `None` while offline.
+    conditions: Option<NetworkConditions>,
+    context: Option<RequestContext>,
+    url: String,
+    fetch: Arc<Mutex<Fetch>>,
+    request: Option<UrlRequest>,
+    offset: usize,
+}
+
+impl ThrottledResourceHandler {
+    /// A copy of the page's request to fetch, and the size of its body.
+    fn copy_request(request: &Request) -> Result<(Request, usize)> {
+        let copy = Request::new();
+        copy.set_url(&request.get_url()?)?;
+        copy.set_method(&request.get_method()?)?;
+        copy.set_header_map(&request.get_header_map()?)?;
+        copy.set_referrer(&request.get_referrer_url()?, request.get_referrer_policy()?)?;
+        copy.set_flags(UrlRequestFlags::StopOnRedirect)?;
+
+        let mut upload = 0;
+        if let Some(post_data) = request.get_post_data()? {
+            for element in post_data.get_elements()? {
+                upload += element.get_bytes_count()?;
+            }
+            copy.set_post_data(post_data)?;
+        }
+
+        Ok((copy, upload))
+    }
+
+    fn set_headers(&self, response: &Response, redirect_url: &mut Option<String>) -> Result<usize> {
+        let fetch = self.fetch.lock().unwrap();
+        if let Some(error) = fetch.error {
+            response.set_error(error)?;
+            return Ok(0);
+        }
+        let Some(fetched) = fetch.response.as_ref() else {
+            response.set_error(ErrorCode::Failed)?;
+            return Ok(0);
+        };
+
+        let status = fetched.get_status()?;
+        response.set_status(status)?;
+        response.set_status_text(&fetched.get_status_text()?)?;
+        response.set_mime_type(&fetched.get_mime_type()?)?;
+        response.set_charset(&fetched.get_charset()?)?;
+
+        let mut headers: HashMap<String, Vec<String>> = fetched.get_header_map()?;
+        headers.retain(|name, _| {
+            !STALE_HEADERS
+                .iter()
+                .any(|stale| stale.eq_ignore_ascii_case(name))
+        });
+        response.set_header_map(&headers)?;
+
+        if (300..400).contains(&status) {
+            let location = fetched.get_header_by_name("Location")?;
+            *redirect_url = Url::parse(&self.url)
+                .and_then(|url| url.join(&location))
+                .ok()
+                .map(String::from);
+        }
+
+        Ok(fetch.body.len())
+    }
+}
+
+impl ResourceHandlerCallbacks for ThrottledResourceHandler {
+    fn open(&mut self, request: Request, handle_request: &mut bool, callback: Callback) -> bool {
+        let Some(conditions) = self.conditions else {
+            self.fetch.lock().unwrap().error = Some(ErrorCode::InternetDisconnected);
+            *handle_request = true;
+            return true;
+        };
+
+        let (copy, upload) = match Self::copy_request(&request) {
+            Ok(copy) => copy,
+            Err(e) => {
+                eprintln!("Error copying throttled request: {}", e);
+                return false;
+            }
+        };
+
+        let delay = Duration::from_millis(conditions.latency as u64)
+            + NetworkConditions::transfer_time(upload, conditions.upload);
+        let client = UrlRequestClient::new(FetchClient {
+            fetch: self.fetch.clone(),
+            delay,
+            open: callback,
+        });
+        self.request = Some(UrlRequest::new(copy, client, self.context.clone()));
+
+        // Continued once the fetch completes and the delay has passed.
+        *handle_request = false;
+        true
+    }
+
+    fn get_response_headers(
+        &mut self,
+        response: Response,
+        response_length: &mut i64,
+        redirect_url: &mut Option<String>,
+    ) {
+        match self.set_headers(&response, redirect_url) {
+            Ok(length) => *response_length = length as i64,
+            Err(e) => {
+                *response_length = 0;
+                eprintln!("Error setting response headers: {}", e);
+            }
+        }
+    }
+
+    fn skip(
+        &mut self,
+        bytes_to_skip: i64,
+        bytes_skipped: &mut i64,
+        _callback: ResourceSkipCallback,
+    ) -> bool {
+        let length = self.fetch.lock().unwrap().body.len();
+        let skipped = (bytes_to_skip.max(0) as usize).min(length - self.offset);
+
+        self.offset += skipped;
+        *bytes_skipped = skipped as i64;
+
+        true
+    }
+
+    fn read(
+        &mut self,
+        data_out: &mut [u8],
+        bytes_read: &mut i32,
+        callback: ResourceReadCallback,
+    ) -> bool {
+        let count = {
+            let fetch = self.fetch.lock().unwrap();
+            let remaining = &fetch.body[self.offset.min(fetch.body.len())..];
+            let count = remaining.len().min(data_out.len());
+
+            data_out[..count].copy_from_slice(&remaining[..count]);
+            count
+        };
+        self.offset += count;
+
+        let delay = self.conditions.map_or(Duration::ZERO, |conditions| {
+            NetworkConditions::transfer_time(count, conditions.download)
+        });
+        if count == 0 || delay < MIN_DELAY {
+            *bytes_read = count as i32;
+            return count > 0;
+        }
+
+        // `data_out` stays valid until the callback runs, so the bytes are
+        // already in place and only their arrival is held back.
+        *bytes_read = 0;
+        after(delay, &self.fetch, move || {
+            callback.cont(count as i32).ok();
+        });
+        true
+    }
+
+    fn cancel(&mut self) {
+        self.fetch.lock().unwrap().cancelled = true;
+        if let Some(request) = self.request.take() {
+            request.cancel().ok();
+        }
+    }
+}
+
+/// The custom conditions being entered.
+struct CustomFields {
+    download: Entity<TextInput>,
+    upload: Entity<TextInput>,
+    latency: Entity<TextInput>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// Picks the network conditions, from the network panel's header.
+pub struct ThrottlingSelector {
+    throttling: NetworkThrottling,
+    /// Whether the list of presets is open.
+    choosing: bool,
+    custom: CustomFields,
+}
+
+impl ThrottlingSelector {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let throttling = cx.global::<NetworkThrottling>().clone();
+        let custom = throttling.custom();
+
+        let download = cx.new(|cx| {
+            TextInput::new(custom.download.to_string(), cx).placeholder(t!("throttling.download"))
+        });
+        let upload = cx.new(|cx| {
+            TextInput::new(custom.upload.to_string(), cx).placeholder(t!("throttling.upload"))
+        });
+        let latency = cx.new(|cx| {
+            TextInput::new(custom.latency.to_string(), cx).placeholder(t!("throttling.latency"))
+        });
+        let subscriptions = [&download, &upload, &latency]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe(input, |this, _, event, cx| {
+                    if let TextInputEvent::Confirm = event {
+                        this.apply_custom(cx);
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            throttling,
+            choosing: false,
+            custom: CustomFields {
+                download,
+                upload,
+                latency,
+                _subscriptions: subscriptions,
+            },
+        }
+    }
+
+    /// Use the entered conditions, keeping the previous value of any field
+    /// that isn't a number.
+    fn apply_custom(&mut self, cx: &mut Context<Self>) {
+        let previous = self.throttling.custom();
+        let field = |input: &Entity<TextInput>, previous: u32| {
+            input.read(cx).text().trim().parse().unwrap_or(previous)
+        };
+
+        self.throttling.set_custom(NetworkConditions {
+            download: field(&self.custom.download, previous.download).max(1),
+            upload: field(&self.custom.upload, previous.upload).max(1),
+            latency: field(&self.custom.latency, previous.latency),
+        });
+        cx.notify();
+    }
+
+    fn choose(&mut self, preset: ThrottlePreset, cx: &mut Context<Self>) {
+        if preset == ThrottlePreset::Custom {
+            self.apply_custom(cx);
+        }
+        self.throttling.set_preset(preset);
+        self.choosing = false;
+        cx.notify();
+    }
+}
+
+impl Render for ThrottlingSelector {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let preset = self.throttling.preset();
+
+        let field = |label: SharedString, input: &Entity<TextInput>| {
+            div()
+                .flex()
+                .items_center()
+                .gap_1()
+                .child(div().w(px(56.)).child(input.clone()))
+                .child(div().text_color(theme.text_muted).child(label))
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .when(preset == ThrottlePreset::Custom, |this| {
+                this.child(field(t!("throttling.download"), &self.custom.download))
+                    .child(field(t!("throttling.upload"), &self.custom.upload))
+                    .child(field(t!("throttling.latency"), &self.custom.latency))
+            })
+            .child(
+                div()
+                    .relative()
+                    .child(
+                        div()
+                            .id("throttling-preset")
+                            .flex()
+                            .gap_1()
+                            .px_1p5()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .when(preset != ThrottlePreset::Off, |this| {
+                                this.text_color(theme.error)
+                            })
+                            .hover(|this| this.bg(theme.ghost_hover))
+                            .child(preset.label())
+                            .child("▾")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.choosing = !this.choosing;
+                                cx.notify();
+                            })),
+                    )
+                    .when(self.choosing, |this| {
+                        this.child(deferred(
+                            div()
+                                .absolute()
+                                .top_full()
+                                .right_0()
+                                .mt_1()
+                                .p_1()
+                                .min_w(px(140.))
+                                .rounded_md()
+                                .border_1()
+                                .border_color(theme.border)
+                                .bg(theme.surface)
+                                .text_color(theme.text)
+                                .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                                    this.choosing = false;
+                                    cx.notify();
+                                }))
+                                .children(ThrottlePreset::ALL.into_iter().enumerate().map(
+                                    |(index, option)| {
+                                        div()
+                                            .id(("throttling-preset-option", index))
+                                            .px_2()
+                                            .py_1()
+                                            .rounded_sm()
+                                            .cursor_pointer()
+                                            .hover(|this| this.bg(theme.element_selected))
+                                            .when(option == preset, |this| {
+                                                this.text_color(theme.accent)
+                                            })
+                                            .child(option.label())
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.choose(option, cx)
+                                            }))
+                                    },
+                                )),
+                        ))
+                    }),
+            )
+    }
+}
+
+/// The red badge in the toolbar while the network is throttled.
+pub fn badge(cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("throttled")
+        .flex()
+        .items_center()
+        .h(px(18.))
+        .px_1p5()
+        .rounded_md()
+        .cursor_pointer()
+        .text_xs()
+        .bg(theme.error.opacity(0.2))
+        .text_color(theme.error)
+        .child(t!("throttling.badge"))
+}
@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_ui::Browser;
+use gpui::{
+    div, ease_in_out, img, prelude::*, px, Animation, AnimationExt, App as GpuiApp, Global, Image,
+    ImageFormat, ImageSource,
+};
+use image::{DynamicImage, RgbaImage};
+
+use crate::{locale::t, memory::MemoryPressureLevel, theme::ActiveTheme};
+
+/// Largest size of a tab preview, in pixels. Previews keep the page's shape.
+const THUMBNAIL_WIDTH: u32 = 240;
+const THUMBNAIL_HEIGHT: u32 = 160;
+
+/// How long a tab has to be hovered before its preview shows.
+pub const HOVER_DELAY: Duration = Duration::from_millis(500);
+
+/// How long the preview takes to fade in.
+const FADE_IN: Duration = Duration::from_millis(200);
+
+/// The last frame painted for a tab, in CEF's BGRA layout.
+struct PaintedFrame {
+    bgra: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default)]
+struct ThumbnailCacheInner {
+    frames: HashMap<i32, PaintedFrame>,
+    thumbnails: HashMap<i32, Image>,
+    pressure_level: MemoryPressureLevel,
+}
+
+/// Scaled down pictures of the tabs, keyed by browser identifier. The render
+/// handler keeps the last frame of every tab, and a thumbnail is made from it
+/// the first time it's asked for after a paint. Under memory pressure, frames
+/// are dropped once their thumbnail is made, and under critical pressure
+/// they aren't kept at all, leaving the last thumbnail standing.
+#[derive(Clone, Default)]
+pub struct ThumbnailCache(Arc<Mutex<ThumbnailCacheInner>>);
+
+impl Global for ThumbnailCache {}
+
+impl ThumbnailCache {
+    /// Keep the frame just painted for `browser`, dropping its stale
+    /// thumbnail.
+    pub fn on_paint(&self, browser: &Browser, buffer: &[u8], width: usize, height: usize) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        if inner.pressure_level == MemoryPressureLevel::Critical {
+            return;
+        }
+        inner.thumbnails.remove(&id);
+
+        let frame = inner.frames.entry(id).or_insert_with(|| PaintedFrame {
+            bgra: Vec::new(),
+            width: 0,
+            height: 0,
+        });
+        frame.bgra.clear();
+        frame.bgra.extend_from_slice(buffer);
+        frame.width = width as u32;
+        frame.height = height as u32;
+    }
+
+    /// The thumbnail of `browser`, or `None` if it hasn't painted yet.
+    pub fn thumbnail(&self, browser: &Browser) -> Option<Image> {
+        let id = browser.get_identifier().ok()?;
+        let mut inner = self.0.lock().unwrap();
+
+        if let Some(thumbnail) = inner.thumbnails.get(&id) {
+            return Some(thumbnail.clone());
+        }
+
+        let thumbnail = match scale_down(inner.frames.get(&id)?) {
+            Ok(thumbnail) => thumbnail,
+            Err(e) => {
+                eprintln!("Error making tab thumbnail: {}", e);
+                return None;
+            }
+        };
+
+        inner.thumbnails.insert(id, thumbnail.clone());
+        if inner.pressure_level > MemoryPressureLevel::Normal {
+            inner.frames.remove(&id);
+        }
+        Some(thumbnail)
+    }
+
+    pub fn set_pressure_level(&self, level: MemoryPressureLevel) {
+        self.0.lock().unwrap().pressure_level = level;
+    }
+
+    /// Drop the last frame of `browser`, keeping its thumbnail if it has
+    /// one.
+    pub fn discard_frame(&self, browser: &Browser) {
+        if let Ok(id) = browser.get_identifier() {
+            self.0.lock().unwrap().frames.remove(&id);
+        }
+    }
+
+    /// Forget a closed tab.
+    pub fn remove(&self, browser: &Browser) {
+        let Ok(id) = browser.get_identifier() else {
+            return;
+        };
+
+        let mut inner = self.0.lock().unwrap();
+        inner.frames.remove(&id);
+        inner.thumbnails.remove(&id);
+    }
+}
+
+fn scale_down(frame: &PaintedFrame) -> Result<Image> {
+    let mut rgba = frame.bgra.clone();
+    for pixel in rgba.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    let pixels = RgbaImage::from_raw(frame.width, frame.height, rgba)
+        .ok_or_else(|| anyhow!("Frame doesn't match its size"))?;
+    let thumbnail = DynamicImage::ImageRgba8(pixels).thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+
+    let mut png = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut png, image::ImageFormat::Png)?;
+
+    Ok(Image::from_bytes(ImageFormat::Png, png.into_inner()))
+}
+
+/// Popover below a tab showing its thumbnail, fading in.
+pub fn preview(index: usize, thumbnail: Option<Image>, cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    div()
+        .absolute()
+        .top(px(36.))
+        .left_0()
+        .flex()
+        .items_center()
+        .justify_center()
+        .w(px(THUMBNAIL_WIDTH as f32 + 8.))
+        .h(px(THUMBNAIL_HEIGHT as f32 + 8.))
+        .p_1()
+        .rounded_lg()
+        .border_1()
+        .border_color(theme.border)
+        .bg(theme.surface)
+        .shadow_lg()
+        .text_xs()
+        .text_color(theme.text_muted)
+        .child(match thumbnail {
+            Some(thumbnail) => div()
+                .size_full()
+                .child(img(ImageSource::from(Arc::new(thumbnail))).size_full()),
+            None => div().child(t!("thumbnails.no_preview")),
+        })
+        .with_animation(
+            ("tab-preview", index),
+            Animation::new(FADE_IN).with_easing(ease_in_out),
+            |this, delta| this.opacity(delta),
+        )
+}
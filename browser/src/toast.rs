@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use gpui::{
+    div, prelude::*, App as GpuiApp, Context, DismissEvent, EventEmitter, SharedString, Window,
+};
+
+use crate::{button::button, theme::ActiveTheme};
+
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+type ToastAction = Box<dyn Fn(&mut Window, &mut GpuiApp)>;
+
+/// Short lived notification in the bottom right of the page.
+pub struct Toast {
+    message: SharedString,
+    action: Option<(SharedString, ToastAction)>,
+}
+
+impl Toast {
+    pub fn new(message: impl Into<SharedString>, cx: &mut Context<Self>) -> Self {
+        cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(TOAST_DURATION).await;
+            this.update(cx, |_, cx| cx.emit(DismissEvent)).ok();
+        })
+        .detach();
+
+        Self {
+            message: message.into(),
+            action: None,
+        }
+    }
+
+    /// Add a button that runs `action` and dismisses the toast.
+    pub fn action(
+        mut self,
+        label: impl Into<SharedString>,
+        action: impl Fn(&mut Window, &mut GpuiApp) + 'static,
+    ) -> Self {
+        self.action = Some((label.into(), Box::new(action)));
+        self
+    }
+}
+
+impl EventEmitter<DismissEvent> for Toast {}
+
+impl Render for Toast {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        div()
+            .absolute()
+            .bottom_3()
+            .right_3()
+            .flex()
+            .items_center()
+            .gap_3()
+            .px_3()
+            .py_2()
+            .rounded_lg()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(self.message.clone())
+            .when_some(self.action.as_ref(), |this, (label, _)| {
+                this.child(
+                    button("toast-action", label.clone(), true, cx).on_click(cx.listener(
+                        |this, _, window, cx| {
+                            if let Some((_, action)) = &this.action {
+                                action(window, cx);
+                            }
+                            cx.emit(DismissEvent);
+                        },
+                    )),
+                )
+            })
+    }
+}
@@ -0,0 +1,340 @@
+// Only MacBooks have a Touch Bar.
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use gpui::{App as GpuiApp, Global, NavigationDirection, Window};
+
+use crate::{gestures, tabs, tabs::TabManager, BrowserState};
+
+/// Most tabs the Touch Bar's tab strip has room for.
+const MAX_TABS: usize = 5;
+
+/// Something tapped on the Touch Bar.
+#[derive(Clone, Copy)]
+enum Tap {
+    Back,
+    Forward,
+    ReloadOrStop,
+    /// The tab strip's segment at this position.
+    Tab(usize),
+    Url,
+}
+
+/// What the Touch Bar shows: back and forward, reload or stop while loading,
+/// the tabs around the active one, and the page's URL.
+#[derive(Clone, Default, PartialEq)]
+struct TouchBarState {
+    can_go_back: bool,
+    can_go_forward: bool,
+    loading: bool,
+    /// Index of the first tab in the strip.
+    first_tab: usize,
+    tabs: Vec<String>,
+    /// Position of the active tab in the strip.
+    active: usize,
+    url: String,
+}
+
+impl TouchBarState {
+    fn new(cx: &GpuiApp) -> Self {
+        let state = cx.global::<BrowserState>();
+        let browser = state.browser.as_ref();
+        let tabs = cx.global::<TabManager>();
+        let first_tab = tabs
+            .active()
+            .saturating_sub(MAX_TABS / 2)
+            .min(tabs.tabs().len().saturating_sub(MAX_TABS));
+
+        Self {
+            can_go_back: browser.is_some_and(|browser| browser.can_go_back().unwrap_or(false)),
+            can_go_forward: browser
+                .is_some_and(|browser| browser.can_go_forward().unwrap_or(false)),
+            loading: browser.is_some_and(|browser| browser.is_loading().unwrap_or(false)),
+            first_tab,
+            tabs: tabs
+                .tabs()
+                .iter()
+                .skip(first_tab)
+                .take(MAX_TABS)
+                .map(|tab| tab.label())
+                .collect(),
+            active: tabs.active().saturating_sub(first_tab),
+            url: state.current_page().map(|(url, _)| url).unwrap_or_default(),
+        }
+    }
+}
+
+/// The Touch Bar as last shown, kept to tell when it needs building again.
+#[derive(Default)]
+pub struct TouchBar(Option<TouchBarState>);
+
+impl Global for TouchBar {}
+
+/// Show the navigation and tabs on `window`'s Touch Bar, and act on what was
+/// tapped on it since the last call. Returns whether the URL was tapped, for
+/// the omnibox to take focus.
+pub fn poll(window: &mut Window, cx: &mut GpuiApp) -> bool {
+    let mut url_tapped = false;
+    for tap in platform::take_taps() {
+        match tap {
+            Tap::Back => {
+                gestures::navigate(cx, NavigationDirection::Back);
+            }
+            Tap::Forward => {
+                gestures::navigate(cx, NavigationDirection::Forward);
+            }
+            Tap::ReloadOrStop => {
+                let state = cx.global::<BrowserState>();
+                match state.browser.as_ref() {
+                    Some(browser) if browser.is_loading().unwrap_or(false) => {
+                        if let Err(e) = browser.stop_load() {
+                            eprintln!("Error stopping load: {}", e);
+                        }
+                    }
+                    _ => crate::reload(cx),
+                }
+            }
+            Tap::Tab(segment) => {
+                let first_tab = cx
+                    .global::<TouchBar>()
+                    .0
+                    .as_ref()
+                    .map_or(0, |state| state.first_tab);
+                tabs::activate(cx, first_tab + segment);
+            }
+            Tap::Url => url_tapped = true,
+        }
+    }
+
+    let state = TouchBarState::new(cx);
+    let touch_bar = cx.global_mut::<TouchBar>();
+    if touch_bar.0.as_ref() != Some(&state) {
+        platform::show(window, &state);
+        touch_bar.0 = Some(state);
+    }
+
+    url_tapped
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{cell::RefCell, sync::Once};
+
+    use core_foundation::{base::TCFType, string::CFString};
+    use gpui::Window;
+    use objc::{
+        class,
+        declare::ClassDecl,
+        msg_send,
+        runtime::{Class, Object, Sel, NO, YES},
+        sel, sel_impl,
+    };
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    use super::{Tap, TouchBarState};
+
+    /// Tags telling the target which control was tapped.
+    const NAVIGATION_TAG: isize = 1;
+    const RELOAD_TAG: isize = 2;
+    const TABS_TAG: isize = 3;
+    const URL_TAG: isize = 4;
+
+    /// `NSSegmentSwitchTracking*`.
+    const TRACKING_SELECT_ONE: isize = 0;
+    const TRACKING_MOMENTARY: isize = 2;
+
+    /// Longest tab label shown before it's cut short.
+    const MAX_LABEL_CHARS: usize = 14;
+
+    thread_local! {
+        /// Taps since the last poll. AppKit sends actions on the main thread.
+        static TAPS: RefCell<Vec<Tap>> = const { RefCell::new(Vec::new()) };
+
+        /// Receives every control's action. Lives as long as the browser.
+        static TARGET: *mut Object = unsafe { msg_send![target_class(), new] };
+    }
+
+    fn target_class() -> &'static Class {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let mut decl = ClassDecl::new("BrowserTouchBarTarget", class!(NSObject))
+                .expect("Touch Bar target class already registered");
+            unsafe {
+                decl.add_method(
+                    sel!(tapped:),
+                    tapped as extern "C" fn(&Object, Sel, *mut Object),
+                );
+            }
+            decl.register();
+        });
+
+        Class::get("BrowserTouchBarTarget").unwrap()
+    }
+
+    extern "C" fn tapped(_this: &Object, _cmd: Sel, sender: *mut Object) {
+        let tap = unsafe {
+            let tag: isize = msg_send![sender, tag];
+            let segment: isize = match tag {
+                NAVIGATION_TAG | TABS_TAG => msg_send![sender, selectedSegment],
+                _ => 0,
+            };
+            if segment < 0 {
+                return;
+            }
+
+            match tag {
+                NAVIGATION_TAG if segment == 0 => Tap::Back,
+                NAVIGATION_TAG => Tap::Forward,
+                RELOAD_TAG => Tap::ReloadOrStop,
+                TABS_TAG => Tap::Tab(segment as usize),
+                _ => Tap::Url,
+            }
+        };
+
+        TAPS.with(|taps| taps.borrow_mut().push(tap));
+    }
+
+    pub fn take_taps() -> Vec<Tap> {
+        TAPS.with(|taps| std::mem::take(&mut *taps.borrow_mut()))
+    }
+
+    unsafe fn image(name: &'static str) -> *mut Object {
+        let name = CFString::from_static_string(name);
+        msg_send![class!(NSImage), imageNamed: name.as_concrete_TypeRef() as *mut Object]
+    }
+
+    /// An `NSArray` of `strings`, autoreleased.
+    unsafe fn string_array(strings: &[String]) -> *mut Object {
+        let array: *mut Object = msg_send![class!(NSMutableArray), array];
+        for string in strings {
+            let string = CFString::new(string);
+            let _: () = msg_send![array, addObject: string.as_concrete_TypeRef() as *mut Object];
+        }
+        array
+    }
+
+    /// Add an item showing `view` to `items`, and its identifier to
+    /// `identifiers`.
+    unsafe fn add_item(
+        items: *mut Object,
+        identifiers: *mut Object,
+        identifier: &'static str,
+        view: *mut Object,
+    ) {
+        let identifier = CFString::from_static_string(identifier);
+        let identifier = identifier.as_concrete_TypeRef() as *mut Object;
+
+        let item: *mut Object = msg_send![class!(NSCustomTouchBarItem), alloc];
+        let item: *mut Object = msg_send![item, initWithIdentifier: identifier];
+        let _: () = msg_send![item, setView: view];
+        let _: () = msg_send![items, addObject: item];
+        let _: () = msg_send![item, release];
+        let _: () = msg_send![identifiers, addObject: identifier];
+    }
+
+    /// Give the window's view a Touch Bar built from `state`.
+    pub fn show(window: &mut Window, state: &TouchBarState) {
+        let Ok(handle) = HasWindowHandle::window_handle(window) else {
+            return;
+        };
+        let RawWindowHandle::AppKit(handle) = handle.as_raw() else {
+            return;
+        };
+        let view = handle.ns_view.as_ptr() as *mut Object;
+
+        TARGET.with(|target| unsafe {
+            let target = *target;
+            let action = sel!(tapped:);
+            let items: *mut Object = msg_send![class!(NSMutableArray), array];
+            let identifiers: *mut Object = msg_send![class!(NSMutableArray), array];
+
+            let images: *mut Object = msg_send![class!(NSMutableArray), array];
+            let _: () = msg_send![images, addObject: image("NSTouchBarGoBackTemplate")];
+            let _: () = msg_send![images, addObject: image("NSTouchBarGoForwardTemplate")];
+            let navigation: *mut Object = msg_send![
+                class!(NSSegmentedControl),
+                segmentedControlWithImages: images
+                trackingMode: TRACKING_MOMENTARY
+                target: target
+                action: action
+            ];
+            let _: () = msg_send![navigation, setTag: NAVIGATION_TAG];
+            for (segment, enabled) in [state.can_go_back, state.can_go_forward]
+                .into_iter()
+                .enumerate()
+            {
+                let enabled = if enabled { YES } else { NO };
+                let _: () = msg_send![navigation, setEnabled: enabled forSegment: segment as isize];
+            }
+            add_item(
+                items,
+                identifiers,
+                "browser.touch-bar.navigation",
+                navigation,
+            );
+
+            let reload: *mut Object = msg_send![
+                class!(NSButton),
+                buttonWithImage: image(match state.loading {
+                    true => "NSStopProgressTemplate",
+                    false => "NSTouchBarRefreshTemplate",
+                })
+                target: target
+                action: action
+            ];
+            let _: () = msg_send![reload, setTag: RELOAD_TAG];
+            add_item(items, identifiers, "browser.touch-bar.reload", reload);
+
+            let labels = state
+                .tabs
+                .iter()
+                .map(|label| match label.chars().count() > MAX_LABEL_CHARS {
+                    true => {
+                        let short: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+                        format!("{}…", short)
+                    }
+                    false => label.clone(),
+                })
+                .collect::<Vec<_>>();
+            let tabs: *mut Object = msg_send![
+                class!(NSSegmentedControl),
+                segmentedControlWithLabels: string_array(&labels)
+                trackingMode: TRACKING_SELECT_ONE
+                target: target
+                action: action
+            ];
+            let _: () = msg_send![tabs, setTag: TABS_TAG];
+            let _: () = msg_send![tabs, setSelectedSegment: state.active as isize];
+            add_item(items, identifiers, "browser.touch-bar.tabs", tabs);
+
+            let url = CFString::new(&state.url);
+            let url: *mut Object = msg_send![
+                class!(NSButton),
+                buttonWithTitle: url.as_concrete_TypeRef() as *mut Object
+                target: target
+                action: action
+            ];
+            let _: () = msg_send![url, setTag: URL_TAG];
+            add_item(items, identifiers, "browser.touch-bar.url", url);
+
+            let touch_bar: *mut Object = msg_send![class!(NSTouchBar), new];
+            let template_items: *mut Object = msg_send![class!(NSSet), setWithArray: items];
+            let _: () = msg_send![touch_bar, setTemplateItems: template_items];
+            let _: () = msg_send![touch_bar, setDefaultItemIdentifiers: identifiers];
+            let _: () = msg_send![view, setTouchBar: touch_bar];
+            let _: () = msg_send![touch_bar, release];
+        });
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use gpui::Window;
+
+    use super::{Tap, TouchBarState};
+
+    pub fn take_taps() -> Vec<Tap> {
+        Vec::new()
+    }
+
+    pub fn show(_window: &mut Window, _state: &TouchBarState) {}
+}
@@ -0,0 +1,206 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_ui::{Browser, Frame};
+use gpui::{
+    div, ease_in_out, img, prelude::*, relative, Animation, AnimationExt, Global, Image,
+    ImageSource, NavigationDirection, SharedString,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::locale::t;
+
+/// How long the new page takes to replace the old one.
+pub const TRANSITION_DURATION: Duration = Duration::from_millis(200);
+
+/// A navigation that hasn't painted within this long has no transition.
+const PAINT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How the page going back or forward replaces the one before it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionStyle {
+    /// The new page pushes the old one out to the side.
+    #[default]
+    Slide,
+    /// The old page fades out over the new one.
+    CrossFade,
+}
+
+impl TransitionStyle {
+    pub const ALL: [TransitionStyle; 2] = [TransitionStyle::Slide, TransitionStyle::CrossFade];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            TransitionStyle::Slide => t!("transitions.slide"),
+            TransitionStyle::CrossFade => t!("transitions.cross_fade"),
+        }
+    }
+}
+
+/// A back or forward navigation waiting for the new page to paint.
+struct PendingTransition {
+    browser_id: i32,
+    direction: NavigationDirection,
+    snapshot: Option<Image>,
+    started_at: Instant,
+    /// Set once the new page starts loading, so paints of the old page
+    /// before that aren't taken for the new one.
+    committed: bool,
+}
+
+#[derive(Default)]
+struct PageTransitionsInner {
+    pending: Option<PendingTransition>,
+    /// A transition whose new page has painted, ready to animate.
+    ready: Option<PendingTransition>,
+}
+
+/// Back and forward navigations in progress, shared with the load and render
+/// handlers so the transition starts on the new page's first paint.
+#[derive(Clone, Default)]
+pub struct PageTransitions(Arc<Mutex<PageTransitionsInner>>);
+
+impl Global for PageTransitions {}
+
+impl PageTransitions {
+    /// Note `browser` is about to go back or forward from the page in
+    /// `snapshot`. Called before the navigation starts, so the snapshot is
+    /// still of the page being left.
+    pub fn begin(
+        &self,
+        browser: &Browser,
+        direction: NavigationDirection,
+        snapshot: Option<Image>,
+    ) {
+        let Ok(browser_id) = browser.get_identifier() else {
+            return;
+        };
+
+        self.0.lock().unwrap().pending = Some(PendingTransition {
+            browser_id,
+            direction,
+            snapshot,
+            started_at: Instant::now(),
+            committed: false,
+        });
+    }
+
+    pub fn on_load_start(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+        if let Some(pending) = inner.pending.as_mut() {
+            if browser.get_identifier().ok() == Some(pending.browser_id) {
+                pending.committed = true;
+            }
+        }
+    }
+
+    /// The page painted. The first paint after the new page starts loading
+    /// starts its transition.
+    pub fn on_paint(&self, browser: &Browser) {
+        let mut inner = self.0.lock().unwrap();
+        let Some(pending) = inner.pending.take() else {
+            return;
+        };
+
+        if pending.started_at.elapsed() >= PAINT_TIMEOUT {
+            return;
+        }
+
+        if pending.committed && browser.get_identifier().ok() == Some(pending.browser_id) {
+            inner.ready = Some(pending);
+        } else {
+            inner.pending = Some(pending);
+        }
+    }
+
+    /// The direction `browser` navigated and the page it left, once the new
+    /// page has painted.
+    pub fn take_ready(&self, browser: &Browser) -> Option<(NavigationDirection, Option<Image>)> {
+        let ready = self.0.lock().unwrap().ready.take()?;
+        (browser.get_identifier().ok() == Some(ready.browser_id))
+            .then_some((ready.direction, ready.snapshot))
+    }
+}
+
+/// The new page replacing the old one after going back or forward.
+pub struct PageTransition {
+    /// Tells transitions apart, so each one animates from the start.
+    pub id: usize,
+    style: TransitionStyle,
+    direction: NavigationDirection,
+    /// The last frame of the page navigated away from.
+    previous: Option<Image>,
+}
+
+impl PageTransition {
+    pub fn new(
+        id: usize,
+        style: TransitionStyle,
+        direction: NavigationDirection,
+        previous: Option<Image>,
+    ) -> Self {
+        Self {
+            id,
+            style,
+            direction,
+            previous,
+        }
+    }
+
+    /// Bring `page` in over the previous page, in the transition's style.
+    pub fn render(&self, page: impl IntoElement + 'static) -> impl IntoElement {
+        // Going back brings the page in from the left.
+        let side = match (self.style, self.direction) {
+            (TransitionStyle::CrossFade, _) => 0.,
+            (TransitionStyle::Slide, NavigationDirection::Back) => -1.,
+            (TransitionStyle::Slide, NavigationDirection::Forward) => 1.,
+        };
+        let fade = self.style == TransitionStyle::CrossFade;
+        let animation = || Animation::new(TRANSITION_DURATION).with_easing(ease_in_out);
+
+        div()
+            .relative()
+            .size_full()
+            .overflow_hidden()
+            .child(
+                div()
+                    .absolute()
+                    .top_0()
+                    .size_full()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(page)
+                    .with_animation(
+                        ("page-transition-current", self.id),
+                        animation(),
+                        move |this, delta| this.left(relative(side * (1. - delta))),
+                    ),
+            )
+            // Drawn last so the old page fades out on top of the new one.
+            .when_some(self.previous.clone(), |this, image| {
+                this.child(
+                    div()
+                        .absolute()
+                        .top_0()
+                        .size_full()
+                        .child(img(ImageSource::from(Arc::new(image))).size_full())
+                        .with_animation(
+                            ("page-transition-previous", self.id),
+                            animation(),
+                            move |this, delta| match fade {
+                                true => this.opacity(1. - delta),
+                                false => this.left(relative(-side * delta)),
+                            },
+                        ),
+                )
+            })
+    }
+}
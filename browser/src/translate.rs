@@ -0,0 +1,550 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Result};
+use cef_ui::{
+    AuthCallback, Browser, Frame, PostData, PostDataElement, Request, UrlRequest, UrlRequestClient,
+    UrlRequestClientCallbacks, UrlRequestFlags, UrlRequestStatus,
+};
+use gpui::{
+    div, prelude::*, px, svg, App as GpuiApp, ClipboardItem, Context, DismissEvent, Div,
+    EventEmitter, Global, Stateful, Window,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    button::button,
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+    settings::Settings,
+    theme::ActiveTheme,
+    BrowserState,
+};
+
+/// Defines `__cefTranslate`, which translates the page's text nodes in
+/// chunks of about `CHUNK` characters through `translate.chunk` queries, and
+/// puts the original text back. Relies on `window.cefQuery`.
+pub const TRANSLATE_SCRIPT: &str = r#"(() => {
+  if (window.__cefTranslate) return;
+
+  const CHUNK = 1500;
+  const SKIPPED = new Set(["SCRIPT", "STYLE", "NOSCRIPT", "TEXTAREA", "CODE", "PRE"]);
+  const originals = new Map();
+  let run = 0;
+
+  const textNodes = () => {
+    const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT, {
+      acceptNode: (node) =>
+        !node.data.trim() || SKIPPED.has(node.parentElement?.tagName) || originals.has(node)
+          ? NodeFilter.FILTER_REJECT
+          : NodeFilter.FILTER_ACCEPT,
+    });
+    const nodes = [];
+    while (walker.nextNode()) nodes.push(walker.currentNode);
+    return nodes;
+  };
+
+  const translate = (nodes, current) =>
+    new Promise((resolve) => {
+      window.cefQuery({
+        request: JSON.stringify({ cmd: "translate.chunk", texts: nodes.map((node) => node.data.trim()) }),
+        onSuccess: (response) => {
+          if (current === run) {
+            JSON.parse(response).forEach((translation, index) => {
+              const node = nodes[index];
+              if (!translation || !node) return;
+              const [, leading, , trailing] = node.data.match(/^(\s*)([\s\S]*?)(\s*)$/);
+              originals.set(node, node.data);
+              node.data = leading + translation + trailing;
+            });
+          }
+          resolve(true);
+        },
+        onFailure: () => resolve(false),
+      });
+    });
+
+  Object.defineProperty(window, "__cefTranslate", {
+    value: {
+      async start() {
+        const current = ++run;
+        let chunk = [];
+        let length = 0;
+        for (const node of textNodes()) {
+          if (chunk.length && length + node.data.length > CHUNK) {
+            if (!(await translate(chunk, current)) || current !== run) return;
+            chunk = [];
+            length = 0;
+          }
+          chunk.push(node);
+          length += node.data.length;
+        }
+        if (chunk.length) await translate(chunk, current);
+      },
+      restore() {
+        run++;
+        for (const [node, text] of originals) node.data = text;
+        originals.clear();
+      },
+    },
+  });
+})();"#;
+
+/// The prompt asking for `texts` in `language`. Each text goes on a line of
+/// its own, numbered so the translations can be matched back up.
+fn prompt(texts: &[String], language: &str) -> serde_json::Value {
+    let numbered = texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| format!("[{}] {}", index + 1, text.replace('\n', " ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    json!({
+        "messages": [
+            {
+                "role": "system",
+                "content": format!(
+                    "Translate each numbered line into {}. Keep the [n] markers and reply \
+                     with the translated lines only.",
+                    language
+                ),
+            },
+            { "role": "user", "content": numbered },
+        ],
+        "temperature": 0.2,
+    })
+}
+
+/// Match the model's numbered lines back up with the `count` texts asked
+/// for. Lines it dropped come back empty, so the original stays.
+fn parse_translations(reply: &str, count: usize) -> Vec<String> {
+    let mut translations = vec![String::new(); count];
+    for line in reply.lines() {
+        let Some((number, text)) = line
+            .trim()
+            .strip_prefix('[')
+            .and_then(|line| line.split_once(']'))
+        else {
+            continue;
+        };
+        if let Some(translation) = number
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| translations.get_mut(number.wrapping_sub(1)))
+        {
+            *translation = text.trim().to_string();
+        }
+    }
+
+    // A single text may well come back without its marker.
+    if count == 1 && translations[0].is_empty() {
+        translations[0] = reply.trim().to_string();
+    }
+    translations
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+/// The part of an OpenAI-style chat completion we read.
+#[derive(Deserialize)]
+struct Completion {
+    choices: Vec<Choice>,
+}
+
+/// Whether `endpoint` is served from this machine, so the page text sent to
+/// it doesn't leave it.
+pub fn is_local_endpoint(endpoint: &str) -> bool {
+    let Ok(url) = url::Url::parse(endpoint) else {
+        return false;
+    };
+
+    matches!(url.scheme(), "http" | "https")
+        && match url.host() {
+            Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        }
+}
+
+fn build_request(endpoint: &str, body: &serde_json::Value) -> Result<Request> {
+    if !is_local_endpoint(endpoint) {
+        bail!(
+            "The translation endpoint isn't on this machine: {}",
+            endpoint
+        );
+    }
+
+    let element = PostDataElement::new();
+    element.set_to_bytes(&serde_json::to_vec(body)?)?;
+    let post_data = PostData::new();
+    post_data.add_element(element)?;
+
+    let request = Request::new();
+    request.set_url(endpoint)?;
+    request.set_method("POST")?;
+    request.set_header_by_name("Content-Type", "application/json", true)?;
+    request.set_post_data(post_data)?;
+    request.set_flags(UrlRequestFlags::DisableCache)?;
+    Ok(request)
+}
+
+type Done = Box<dyn FnOnce(Result<Vec<String>, String>) + Send + Sync>;
+
+/// Receives the model's reply and hands the translations to `done`.
+struct TranslationClient {
+    count: usize,
+    body: Vec<u8>,
+    done: Option<Done>,
+}
+
+impl UrlRequestClientCallbacks for TranslationClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let Some(done) = self.done.take() else {
+            return;
+        };
+
+        let code = request
+            .get_response()
+            .ok()
+            .flatten()
+            .and_then(|response| response.get_status().ok())
+            .unwrap_or_default();
+        let result = match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) if (200..300).contains(&code) => {
+                serde_json::from_slice::<Completion>(&self.body)
+                    .map_err(|e| e.to_string())
+                    .and_then(|completion| {
+                        completion
+                            .choices
+                            .into_iter()
+                            .next()
+                            .map(|choice| parse_translations(&choice.message.content, self.count))
+                            .ok_or_else(|| t!("translate.empty_reply").to_string())
+                    })
+            }
+            Ok(UrlRequestStatus::Success) => Err(t!("translate.rejected", code = code).to_string()),
+            Ok(_) => Err(t!("translate.unreachable").to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        done(result);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, data: &[u8]) {
+        self.body.extend_from_slice(data);
+    }
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Ask the local model for `texts` in the language picked in settings.
+/// `done` gets one translation per text, on CEF's thread.
+fn translate(
+    cx: &GpuiApp,
+    texts: Vec<String>,
+    done: impl FnOnce(Result<Vec<String>, String>) + Send + Sync + 'static,
+) {
+    let settings = cx.global::<Settings>();
+    let body = prompt(&texts, &settings.translation_language);
+    let request = match build_request(&settings.translation_endpoint, &body) {
+        Ok(request) => request,
+        Err(e) => {
+            done(Err(e.to_string()));
+            return;
+        }
+    };
+
+    let client = UrlRequestClient::new(TranslationClient {
+        count: texts.len(),
+        body: Vec::new(),
+        done: Some(Box::new(done)),
+    });
+    UrlRequest::new(request, client, None);
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    texts: Vec<String>,
+}
+
+/// Translate a chunk of the page's text for a `translate.chunk` query,
+/// answering with the translations as a JSON array. Only pages the user
+/// asked to translate get to send text to the service.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    let translating = query
+        .frame()
+        .get_browser()
+        .is_ok_and(|browser| cx.global::<Translator>().is_translated(&browser));
+    if !translating {
+        query.failure(ERROR_FAILED, "Page isn't being translated");
+        return;
+    }
+
+    let chunk = match serde_json::from_str::<Chunk>(argument) {
+        Ok(chunk) => chunk,
+        Err(e) => {
+            query.failure(ERROR_FAILED, &format!("Invalid chunk: {}", e));
+            return;
+        }
+    };
+
+    translate(cx, chunk.texts, move |result| match result {
+        Ok(translations) => query.success(&json!(translations).to_string()),
+        Err(error) => query.failure(ERROR_FAILED, &error),
+    });
+}
+
+/// Where the translation of a selection has got to.
+#[derive(Clone, Default)]
+pub enum SelectionTranslation {
+    #[default]
+    Translating,
+    Done(String),
+    Failed(String),
+}
+
+#[derive(Default)]
+struct TranslatorInner {
+    /// The selected text being translated, and how far it got.
+    selection: Option<(String, SelectionTranslation)>,
+    /// Set when a selection starts translating, for the UI to show it.
+    opened: bool,
+    /// Browsers whose page is translated.
+    pages: HashSet<i32>,
+    changed: bool,
+}
+
+/// Translates selections and whole pages with a llama.cpp server on this
+/// machine, through its OpenAI-compatible chat API.
+#[derive(Clone, Default)]
+pub struct Translator(Arc<Mutex<TranslatorInner>>);
+
+impl Global for Translator {}
+
+impl Translator {
+    /// Start translating `text` selected in the page.
+    pub fn translate_selection(&self, cx: &GpuiApp, text: String) {
+        {
+            let mut inner = self.0.lock().unwrap();
+            inner.selection = Some((text.clone(), SelectionTranslation::Translating));
+            inner.opened = true;
+        }
+
+        let translator = self.clone();
+        translate(cx, vec![text.clone()], move |result| {
+            let mut inner = translator.0.lock().unwrap();
+            // A later selection took its place.
+            if inner.selection.as_ref().map(|(selection, _)| selection) != Some(&text) {
+                return;
+            }
+
+            let state = match result {
+                Ok(mut translations) => SelectionTranslation::Done(translations.remove(0)),
+                Err(error) => SelectionTranslation::Failed(error),
+            };
+            inner.selection = Some((text, state));
+            inner.changed = true;
+        });
+    }
+
+    fn selection(&self) -> Option<(String, SelectionTranslation)> {
+        self.0.lock().unwrap().selection.clone()
+    }
+
+    /// Whether a selection started translating since the last call.
+    pub fn take_opened(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().opened)
+    }
+
+    /// Whether the selection's translation came in since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    pub fn is_translated(&self, browser: &Browser) -> bool {
+        browser
+            .get_identifier()
+            .is_ok_and(|id| self.0.lock().unwrap().pages.contains(&id))
+    }
+
+    /// A new page starts out untranslated.
+    pub fn on_load_start(&self, browser: &Browser, frame: &Frame) {
+        if !frame.is_main().unwrap_or(false) {
+            return;
+        }
+        if let Ok(id) = browser.get_identifier() {
+            self.0.lock().unwrap().pages.remove(&id);
+        }
+    }
+}
+
+/// Translate the active page, or put its text back if it's translated.
+pub fn toggle_page(cx: &GpuiApp) {
+    let Some(browser) = cx.global::<BrowserState>().browser.clone() else {
+        return;
+    };
+    let (Ok(id), Ok(Some(frame))) = (browser.get_identifier(), browser.get_main_frame()) else {
+        return;
+    };
+
+    let translate = {
+        let pages = &mut cx.global::<Translator>().0.lock().unwrap().pages;
+        !pages.remove(&id) && pages.insert(id)
+    };
+
+    let script = match translate {
+        true => "window.__cefTranslate && window.__cefTranslate.start();",
+        false => "window.__cefTranslate && window.__cefTranslate.restore();",
+    };
+    if let Err(e) = frame.execute_java_script(script, "", 0) {
+        eprintln!("Error translating page: {}", e);
+    }
+}
+
+/// The toolbar button translating the page, highlighted while it's
+/// translated.
+pub fn icon(active: bool, cx: &GpuiApp) -> Stateful<Div> {
+    let theme = cx.theme();
+
+    div()
+        .id("translate")
+        .flex()
+        .items_center()
+        .justify_center()
+        .size(px(14.0))
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.ghost_hover))
+        .child(
+            svg()
+                .path("languages.svg")
+                .size(px(14.0))
+                .text_color(if active { theme.accent } else { theme.text }),
+        )
+}
+
+/// Popover below the toolbar with the translation of the selected text.
+pub struct TranslationPopover;
+
+impl TranslationPopover {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self
+    }
+}
+
+impl EventEmitter<DismissEvent> for TranslationPopover {}
+
+impl Render for TranslationPopover {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let (original, state) = cx
+            .global::<Translator>()
+            .selection()
+            .unwrap_or((String::new(), SelectionTranslation::Translating));
+        let translation = match &state {
+            SelectionTranslation::Done(translation) => Some(translation.clone()),
+            _ => None,
+        };
+
+        div()
+            .absolute()
+            .top_0()
+            .right_3()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(320.))
+            .p_4()
+            .rounded_b_xl()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .shadow_lg()
+            .text_xs()
+            .text_color(theme.text)
+            .child(div().text_sm().child(t!("translate.title")))
+            .child(div().text_color(theme.text_secondary).child(original))
+            .child(match state {
+                SelectionTranslation::Translating => div()
+                    .text_color(theme.text_placeholder)
+                    .child(t!("translate.translating")),
+                SelectionTranslation::Done(translation) => div().child(translation),
+                SelectionTranslation::Failed(error) => div()
+                    .text_color(theme.error)
+                    .child(t!("translate.failed", error = error)),
+            })
+            .child(
+                div()
+                    .flex()
+                    .justify_end()
+                    .gap_2()
+                    .when_some(translation, |this, translation| {
+                        this.child(
+                            button("translate-copy", t!("translate.copy"), false, cx).on_click(
+                                cx.listener(move |_, _, _, cx| {
+                                    cx.write_to_clipboard(ClipboardItem::new_string(
+                                        translation.clone(),
+                                    ));
+                                    cx.emit(DismissEvent);
+                                }),
+                            ),
+                        )
+                    })
+                    .child(
+                        button("translate-done", t!("translate.done"), true, cx)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_endpoints_on_this_machine_are_used() {
+        assert!(is_local_endpoint(
+            "http://127.0.0.1:8080/v1/chat/completions"
+        ));
+        assert!(is_local_endpoint(
+            "http://localhost:8080/v1/chat/completions"
+        ));
+        assert!(is_local_endpoint("https://[::1]/v1/chat/completions"));
+        assert!(is_local_endpoint("http://127.1.2.3/"));
+
+        assert!(!is_local_endpoint("https://translate.example.com/v1"));
+        assert!(!is_local_endpoint("http://localhost.example.com/"));
+        assert!(!is_local_endpoint("http://192.168.1.10:8080/"));
+        assert!(!is_local_endpoint("file:///tmp/socket"));
+        assert!(!is_local_endpoint("not a url"));
+    }
+}
@@ -0,0 +1,194 @@
+use std::{fs, path::PathBuf};
+
+use cef_ui::Frame;
+use gpui::{div, prelude::*, px, App as GpuiApp, Global};
+use url::Url;
+
+use crate::{locale::t, theme::ActiveTheme, BrowserState};
+
+/// Whether a user script is a style sheet or a script.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum UserScriptKind {
+    Css,
+    Js,
+}
+
+/// A file in the user scripts folder. `*.vercel.com.css` styles
+/// `vercel.com` and its subdomains, `vercel.com.js` runs on `vercel.com`
+/// only, and `*.css` applies everywhere.
+struct UserScript {
+    path: PathBuf,
+    kind: UserScriptKind,
+    /// The domain pattern from the file name, like `*.vercel.com`.
+    pattern: String,
+}
+
+impl UserScript {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let kind = match path.extension()?.to_str()? {
+            "css" => UserScriptKind::Css,
+            "js" => UserScriptKind::Js,
+            _ => return None,
+        };
+        let pattern = path.file_stem()?.to_str()?.to_ascii_lowercase();
+
+        Some(Self {
+            path,
+            kind,
+            pattern,
+        })
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        match self.pattern.strip_prefix("*") {
+            Some("") => true,
+            Some(suffix) => match suffix.strip_prefix('.') {
+                Some(domain) => host == domain || host.ends_with(suffix),
+                None => false,
+            },
+            None => host == self.pattern,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Style sheets and scripts from `~/.config/browser/user-scripts/`, applied to
+/// pages on the domains their file names match. The folder is read on every
+/// load, so edits apply on the next one.
+#[derive(Clone)]
+pub struct UserScripts {
+    dir: PathBuf,
+}
+
+impl Global for UserScripts {}
+
+impl Default for UserScripts {
+    fn default() -> Self {
+        let dir = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".config/browser/user-scripts");
+
+        Self { dir }
+    }
+}
+
+impl UserScripts {
+    /// The scripts applying to `url`, style sheets first, by file name.
+    fn matching(&self, url: &str) -> Vec<UserScript> {
+        let Some(host) = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut scripts: Vec<UserScript> = entries
+            .filter_map(|entry| UserScript::from_path(entry.ok()?.path()))
+            .filter(|script| script.matches(&host))
+            .collect();
+        scripts.sort_by_key(|script| (script.kind == UserScriptKind::Js, script.file_name()));
+        scripts
+    }
+
+    /// Apply the scripts matching the page `frame` finished loading.
+    pub fn on_load_end(&self, frame: &Frame) {
+        let Ok(url) = frame.get_url() else {
+            return;
+        };
+
+        for script in self.matching(&url) {
+            let contents = match fs::read_to_string(&script.path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    eprintln!("Failed to read user script {:?}: {}", script.path, e);
+                    continue;
+                }
+            };
+            let script_url = Url::from_file_path(&script.path)
+                .map(String::from)
+                .unwrap_or_default();
+
+            let result = match script.kind {
+                UserScriptKind::Css => {
+                    let html = format!("<style data-user-style>{}</style>", contents);
+                    let script = format!(
+                        "document.head.insertAdjacentHTML('beforeend', {});",
+                        serde_json::to_string(&html).unwrap_or_default()
+                    );
+                    frame.execute_java_script(&script, &script_url, 0)
+                }
+                UserScriptKind::Js => frame.execute_java_script(&contents, &script_url, 0),
+            };
+            if let Err(e) = result {
+                eprintln!("Error applying user script {:?}: {}", script.path, e);
+            }
+        }
+    }
+}
+
+/// The settings panel's list of the user scripts applied to the current page.
+pub fn active_scripts(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    let user_scripts = cx.global::<UserScripts>();
+    let scripts = cx
+        .global::<BrowserState>()
+        .current_page()
+        .map(|(url, _)| user_scripts.matching(&url))
+        .unwrap_or_default();
+    let empty = scripts.is_empty();
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .overflow_hidden()
+                .text_ellipsis()
+                .text_color(theme.text_disabled)
+                .child(user_scripts.dir.display().to_string()),
+        )
+        .children(scripts.into_iter().map(|script| {
+            let kind = match script.kind {
+                UserScriptKind::Css => t!("user_scripts.style"),
+                UserScriptKind::Js => t!("user_scripts.script"),
+            };
+
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(
+                    div()
+                        .flex_none()
+                        .w(px(60.))
+                        .text_color(theme.text_muted)
+                        .child(kind),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w(px(0.))
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .child(script.file_name()),
+                )
+        }))
+        .when(empty, |this| {
+            this.child(
+                div()
+                    .text_color(theme.text_placeholder)
+                    .child(t!("user_scripts.none")),
+            )
+        })
+}
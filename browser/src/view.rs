@@ -0,0 +1,187 @@
+use std::sync::{Arc, Mutex};
+
+use cef_ui::{Browser, Rect, Size};
+use gpui::{App as GpuiApp, Global, Pixels, RenderImage};
+use image::{Frame, RgbaImage};
+
+use crate::tabs::TabManager;
+
+struct PageViewInner {
+    /// Size of the page area in logical pixels.
+    size: Size,
+    /// Physical pixels per logical pixel of the display the window is on.
+    scale_factor: f32,
+}
+
+/// Size and scale of the area the page is drawn into, shared with the render
+/// handler. CEF lays the page out in logical pixels and paints it at
+/// `scale_factor` times that, so it stays crisp on HiDPI displays.
+#[derive(Clone)]
+pub struct PageView(Arc<Mutex<PageViewInner>>);
+
+impl Default for PageView {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(PageViewInner {
+            size: Size {
+                width: 1024,
+                height: 768,
+            },
+            scale_factor: 1.,
+        })))
+    }
+}
+
+impl Global for PageView {}
+
+impl PageView {
+    /// The page area in logical pixels.
+    pub fn rect(&self) -> Rect {
+        let size = self.0.lock().unwrap().size;
+
+        Rect {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.0.lock().unwrap().scale_factor
+    }
+}
+
+/// Tell the open pages about a new page area size or display scale, so they
+/// lay out and paint again to match.
+pub fn update(cx: &GpuiApp, size: gpui::Size<Pixels>, scale_factor: f32) {
+    let size = Size {
+        width: f32::from(size.width) as i32,
+        height: f32::from(size.height) as i32,
+    };
+
+    // The page area isn't laid out until the first frame.
+    if size.width <= 0 || size.height <= 0 {
+        return;
+    }
+
+    {
+        let mut inner = cx.global::<PageView>().0.lock().unwrap();
+        let resized = (inner.size.width, inner.size.height) != (size.width, size.height);
+        let rescaled = inner.scale_factor != scale_factor;
+
+        if !resized && !rescaled {
+            return;
+        }
+
+        inner.size = size;
+        inner.scale_factor = scale_factor;
+    }
+
+    for tab in cx.global::<TabManager>().tabs() {
+        let Ok(host) = tab.browser.get_host() else {
+            continue;
+        };
+
+        if let Err(e) = host.notify_screen_info_changed() {
+            eprintln!("Error updating screen info: {}", e);
+        }
+
+        if let Err(e) = host.was_resized() {
+            eprintln!("Error resizing page: {}", e);
+        }
+    }
+}
+
+struct ViewSlotInner {
+    /// Identifier of the browser drawn in the slot.
+    browser: Option<i32>,
+    /// Size of the slot in logical pixels.
+    size: Size,
+    /// The browser's page as last painted, until the slot's view picks it up.
+    frame: Option<Arc<RenderImage>>,
+}
+
+/// A place other than the page area that a browser is drawn into, like split
+/// view's right pane, shared with the render handler. Its browser is laid
+/// out to the slot's size and its paints kept for the view drawing it.
+#[derive(Clone)]
+pub struct ViewSlot(Arc<Mutex<ViewSlotInner>>);
+
+impl ViewSlot {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self(Arc::new(Mutex::new(ViewSlotInner {
+            browser: None,
+            size: Size { width, height },
+            frame: None,
+        })))
+    }
+
+    /// Whether `browser` is drawn in the slot.
+    pub fn contains(&self, browser: &Browser) -> bool {
+        let id = self.0.lock().unwrap().browser;
+        id.is_some() && browser.get_identifier().ok() == id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().browser.is_none()
+    }
+
+    /// Draw `browser` in the slot, or nothing if `None`.
+    pub fn set_browser(&self, browser: Option<&Browser>) {
+        let mut inner = self.0.lock().unwrap();
+        inner.browser = browser.and_then(|browser| browser.get_identifier().ok());
+        inner.frame = None;
+    }
+
+    /// The slot's area in logical pixels.
+    pub fn rect(&self) -> Rect {
+        let size = self.0.lock().unwrap().size;
+
+        Rect {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    /// Resize the slot to `size`, returning whether it changed. The browser
+    /// has to be told to lay out again when it did.
+    pub fn set_size(&self, size: gpui::Size<Pixels>) -> bool {
+        let size = Size {
+            width: f32::from(size.width) as i32,
+            height: f32::from(size.height) as i32,
+        };
+
+        // The slot isn't laid out until the first frame.
+        if size.width <= 0 || size.height <= 0 {
+            return false;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+        if (inner.size.width, inner.size.height) == (size.width, size.height) {
+            return false;
+        }
+        inner.size = size;
+        true
+    }
+
+    /// Keep the frame just painted for `browser` if it's the slot's.
+    pub fn on_paint(&self, browser: &Browser, buffer: &[u8], width: usize, height: usize) {
+        if !self.contains(browser) {
+            return;
+        }
+
+        // GPUI draws images from BGRA, the layout CEF paints in.
+        let Some(pixels) = RgbaImage::from_raw(width as u32, height as u32, buffer.to_vec()) else {
+            return;
+        };
+        let image = RenderImage::new(vec![Frame::new(pixels)]);
+        self.0.lock().unwrap().frame = Some(Arc::new(image));
+    }
+
+    /// The frame painted since the last call, if any.
+    pub fn take_frame(&self) -> Option<Arc<RenderImage>> {
+        self.0.lock().unwrap().frame.take()
+    }
+}
@@ -0,0 +1,270 @@
+use cef_ui::{EventFlags, KeyEvent, KeyEventType, TextInputMode, WindowsKeyCode};
+use gpui::{div, prelude::*, px, App as GpuiApp, MouseButton, SharedString};
+
+use crate::{theme::ActiveTheme, BrowserState};
+
+/// Keys of the email keyboard, by row.
+const EMAIL_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// The on-screen keyboard for the kind of text a field takes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Numeric,
+    Decimal,
+    Tel,
+    Email,
+}
+
+impl KeyboardLayout {
+    /// The layout for fields of `input_mode`, if it has one.
+    fn for_mode(input_mode: TextInputMode) -> Option<Self> {
+        match input_mode {
+            TextInputMode::Numeric => Some(KeyboardLayout::Numeric),
+            TextInputMode::Decimal => Some(KeyboardLayout::Decimal),
+            TextInputMode::Tel => Some(KeyboardLayout::Tel),
+            TextInputMode::Email => Some(KeyboardLayout::Email),
+            _ => None,
+        }
+    }
+
+    /// The keys of the keyboard, by row.
+    fn rows(&self) -> Vec<Vec<Key>> {
+        let keypad = |extra: Option<&'static str>| {
+            let mut rows = ["123", "456", "789"].map(Key::row).to_vec();
+            rows.push(
+                extra
+                    .map(Key::Text)
+                    .into_iter()
+                    .chain([Key::Text("0"), Key::Backspace])
+                    .collect(),
+            );
+            rows
+        };
+
+        match self {
+            KeyboardLayout::Numeric => keypad(None),
+            KeyboardLayout::Decimal => keypad(Some(".")),
+            KeyboardLayout::Tel => keypad(Some("+")),
+            KeyboardLayout::Email => {
+                let mut rows = EMAIL_ROWS.map(Key::row).to_vec();
+                rows[3].push(Key::Backspace);
+                rows.push(vec![
+                    Key::Text("@"),
+                    Key::Text("."),
+                    Key::Text("_"),
+                    Key::Text("-"),
+                    Key::Text(".com"),
+                    Key::Enter,
+                ]);
+                rows
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Key {
+    Text(&'static str),
+    Backspace,
+    Enter,
+}
+
+impl Key {
+    /// A key for each character of `row`, which is ASCII.
+    fn row(row: &'static str) -> Vec<Key> {
+        (0..row.len()).map(|i| Key::Text(&row[i..i + 1])).collect()
+    }
+
+    fn label(&self) -> SharedString {
+        match self {
+            Key::Text(text) => (*text).into(),
+            Key::Backspace => "⌫".into(),
+            Key::Enter => "⏎".into(),
+        }
+    }
+
+    /// Type the key into the focused field of the page shown.
+    fn press(&self, cx: &GpuiApp) {
+        let Some(host) = cx.global::<BrowserState>().host() else {
+            return;
+        };
+
+        let key_code = match self {
+            Key::Text(text) => {
+                if let Err(e) = host.ime_commit_text(text, None, 0) {
+                    eprintln!("Error typing {}: {}", text, e);
+                }
+                return;
+            }
+            Key::Backspace => WindowsKeyCode::Back,
+            Key::Enter => WindowsKeyCode::Return,
+        };
+
+        for event_type in [KeyEventType::RawKeyDown, KeyEventType::KeyUp] {
+            let event = KeyEvent {
+                event_type,
+                modifiers: EventFlags::None,
+                windows_key_code: key_code,
+                native_key_code: 0,
+                is_system_key: false,
+                character: 0,
+                unmodified_character: 0,
+                focus_on_editable_field: true,
+            };
+            if let Err(e) = host.send_key_event(event) {
+                eprintln!("Error sending key to page: {}", e);
+            }
+        }
+
+        // Enter also types a carriage return, which submits forms.
+        if matches!(self, Key::Enter) {
+            let event = KeyEvent {
+                event_type: KeyEventType::Char,
+                modifiers: EventFlags::None,
+                windows_key_code: key_code,
+                native_key_code: 0,
+                is_system_key: false,
+                character: '\r' as u16,
+                unmodified_character: '\r' as u16,
+                focus_on_editable_field: true,
+            };
+            if let Err(e) = host.send_key_event(event) {
+                eprintln!("Error sending key to page: {}", e);
+            }
+        }
+    }
+}
+
+/// The on-screen keyboard to show for a field of `input_mode`. Only kiosks
+/// get one: machines with no hardware keyboard attached.
+pub fn layout_for(input_mode: TextInputMode) -> Option<KeyboardLayout> {
+    let layout = KeyboardLayout::for_mode(input_mode)?;
+    (!keyboard_attached()).then_some(layout)
+}
+
+/// Whether the machine has a keyboard. Only known on macOS; elsewhere one is
+/// assumed.
+fn keyboard_attached() -> bool {
+    #[cfg(target_os = "macos")]
+    return macos::keyboard_attached();
+
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::c_char;
+
+    use core_foundation::{
+        base::TCFType,
+        dictionary::{CFDictionaryRef, CFMutableDictionary, CFMutableDictionaryRef},
+        number::CFNumber,
+        string::CFString,
+    };
+
+    /// HID usage of keyboards, on the generic desktop page.
+    const GENERIC_DESKTOP_PAGE: i32 = 0x01;
+    const KEYBOARD_USAGE: i32 = 0x06;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> CFMutableDictionaryRef;
+        fn IOServiceGetMatchingServices(
+            main_port: u32,
+            matching: CFDictionaryRef,
+            existing: *mut u32,
+        ) -> i32;
+        fn IOIteratorNext(iterator: u32) -> u32;
+        fn IOObjectRelease(object: u32) -> i32;
+    }
+
+    /// Whether IOKit knows of a HID device that is a keyboard, built in or
+    /// plugged in.
+    pub fn keyboard_attached() -> bool {
+        unsafe {
+            let matching = IOServiceMatching(b"IOHIDDevice\0".as_ptr().cast());
+            if matching.is_null() {
+                return true;
+            }
+
+            let mut matching =
+                CFMutableDictionary::<CFString, CFNumber>::wrap_under_create_rule(matching);
+            matching.set(
+                CFString::from_static_string("PrimaryUsagePage"),
+                CFNumber::from(GENERIC_DESKTOP_PAGE),
+            );
+            matching.set(
+                CFString::from_static_string("PrimaryUsage"),
+                CFNumber::from(KEYBOARD_USAGE),
+            );
+
+            // The lookup takes over the dictionary's reference.
+            let mut iterator = 0;
+            let result = IOServiceGetMatchingServices(
+                0,
+                matching.as_concrete_TypeRef() as CFDictionaryRef,
+                &mut iterator,
+            );
+            std::mem::forget(matching);
+            if result != 0 {
+                return true;
+            }
+
+            let keyboard = IOIteratorNext(iterator);
+            if keyboard != 0 {
+                IOObjectRelease(keyboard);
+            }
+            IOObjectRelease(iterator);
+            keyboard != 0
+        }
+    }
+}
+
+/// The on-screen keyboard along the bottom of the page. Its keys aren't
+/// focusable, so the page's field keeps focus while typing.
+pub fn panel(layout: KeyboardLayout, cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+    let key_width = match layout {
+        KeyboardLayout::Email => px(32.),
+        _ => px(72.),
+    };
+
+    div()
+        .absolute()
+        .bottom_0()
+        .left_0()
+        .right_0()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_1()
+        .p_2()
+        .bg(theme.surface)
+        .border_t_1()
+        .border_color(theme.border)
+        // Keep clicks between the keys from reaching the page below.
+        .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+        .children(layout.rows().into_iter().enumerate().map(|(row, keys)| {
+            div()
+                .flex()
+                .gap_1()
+                .children(keys.into_iter().enumerate().map(|(column, key)| {
+                    div()
+                        .id(("virtual-key", row * 16 + column))
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .min_w(key_width)
+                        .h(px(36.))
+                        .px_2()
+                        .rounded_md()
+                        .cursor_pointer()
+                        .bg(theme.surface_raised)
+                        .hover(|this| this.bg(theme.ghost_hover))
+                        .text_color(theme.text)
+                        .child(key.label())
+                        .on_click(move |_, _, cx| key.press(cx))
+                }))
+        }))
+}
@@ -0,0 +1,414 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use cef_ui::{
+    AuthCallback, PostData, PostDataElement, Request, UrlRequest, UrlRequestClient,
+    UrlRequestClientCallbacks, UrlRequestFlags, UrlRequestStatus,
+};
+use gpui::{
+    deferred, div, prelude::*, px, Context, DismissEvent, Entity, EventEmitter, Focusable, Global,
+    SharedString, Subscription, Window,
+};
+use serde::Serialize;
+
+use crate::{
+    button::button,
+    locale::t,
+    settings::Settings,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+    BrowserState,
+};
+
+/// What's wrong with a reported site.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakageCategory {
+    Layout,
+    Login,
+    Video,
+    Slow,
+    Other,
+}
+
+impl BreakageCategory {
+    pub const ALL: [BreakageCategory; 5] = [
+        BreakageCategory::Layout,
+        BreakageCategory::Login,
+        BreakageCategory::Video,
+        BreakageCategory::Slow,
+        BreakageCategory::Other,
+    ];
+
+    pub fn label(&self) -> SharedString {
+        match self {
+            BreakageCategory::Layout => t!("webcompat.categories.layout"),
+            BreakageCategory::Login => t!("webcompat.categories.login"),
+            BreakageCategory::Video => t!("webcompat.categories.video"),
+            BreakageCategory::Slow => t!("webcompat.categories.slow"),
+            BreakageCategory::Other => t!("webcompat.categories.other"),
+        }
+    }
+}
+
+/// The body of a report.
+#[derive(Serialize)]
+struct Report {
+    url: String,
+    category: BreakageCategory,
+    description: String,
+    browser: String,
+    os: String,
+}
+
+/// Where the report being sent has got to.
+#[derive(Clone, Default, PartialEq)]
+pub enum ReportStatus {
+    #[default]
+    Editing,
+    Sending,
+    Sent,
+    Failed(String),
+}
+
+#[derive(Default)]
+struct WebCompatReportsInner {
+    status: ReportStatus,
+    changed: bool,
+}
+
+/// Broken site reports sent to the endpoint from settings, with the status of
+/// the latest, which CEF reports on its own thread.
+#[derive(Clone, Default)]
+pub struct WebCompatReports(Arc<Mutex<WebCompatReportsInner>>);
+
+impl Global for WebCompatReports {}
+
+impl WebCompatReports {
+    pub fn status(&self) -> ReportStatus {
+        self.0.lock().unwrap().status.clone()
+    }
+
+    fn set_status(&self, status: ReportStatus) {
+        let mut inner = self.0.lock().unwrap();
+        inner.status = status;
+        inner.changed = true;
+    }
+
+    /// Whether the report's status changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    /// POST `report` as JSON to `endpoint`.
+    fn send(&self, endpoint: &str, report: &Report) {
+        let request = match build_request(endpoint, report) {
+            Ok(request) => request,
+            Err(e) => {
+                self.set_status(ReportStatus::Failed(e.to_string()));
+                return;
+            }
+        };
+
+        self.set_status(ReportStatus::Sending);
+        let client = UrlRequestClient::new(ReportClient {
+            reports: self.clone(),
+        });
+        UrlRequest::new(request, client, None);
+    }
+}
+
+fn build_request(endpoint: &str, report: &Report) -> Result<Request> {
+    let body = PostDataElement::new();
+    body.set_to_bytes(&serde_json::to_vec(report)?)?;
+    let post_data = PostData::new();
+    post_data.add_element(body)?;
+
+    let request = Request::new();
+    request.set_url(endpoint)?;
+    request.set_method("POST")?;
+    request.set_header_by_name("Content-Type", "application/json", true)?;
+    request.set_post_data(post_data)?;
+    request.set_flags(UrlRequestFlags::DisableCache)?;
+    Ok(request)
+}
+
+struct ReportClient {
+    reports: WebCompatReports,
+}
+
+impl UrlRequestClientCallbacks for ReportClient {
+    fn on_request_complete(&mut self, request: UrlRequest) {
+        let code = request
+            .get_response()
+            .ok()
+            .flatten()
+            .and_then(|response| response.get_status().ok())
+            .unwrap_or_default();
+
+        let status = match request.get_request_status() {
+            Ok(UrlRequestStatus::Success) if (200..300).contains(&code) => ReportStatus::Sent,
+            Ok(UrlRequestStatus::Success) => {
+                ReportStatus::Failed(t!("webcompat.rejected", code = code).to_string())
+            }
+            Ok(_) => ReportStatus::Failed(t!("webcompat.unreachable").to_string()),
+            Err(e) => ReportStatus::Failed(e.to_string()),
+        };
+
+        self.reports.set_status(status);
+    }
+
+    fn on_upload_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_progress(&mut self, _request: UrlRequest, _current: i64, _total: i64) {}
+
+    fn on_download_data(&mut self, _request: UrlRequest, _data: &[u8]) {}
+
+    fn get_auth_credentials(
+        &mut self,
+        _is_proxy: bool,
+        _host: &str,
+        _port: u16,
+        _realm: &str,
+        _scheme: &str,
+        _callback: AuthCallback,
+    ) -> bool {
+        false
+    }
+}
+
+/// Sheet for reporting the current page as broken: its URL, what's wrong
+/// with it and, optionally, more detail.
+pub struct ReportSheet {
+    url: Entity<TextInput>,
+    category: Option<BreakageCategory>,
+    /// Whether the category list is open.
+    choosing: bool,
+    description: Entity<TextInput>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReportSheet {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let page_url = cx
+            .global::<BrowserState>()
+            .current_page()
+            .map(|(url, _)| url)
+            .unwrap_or_default();
+        let url = cx.new(|cx| TextInput::new(page_url, cx).placeholder(t!("webcompat.url")));
+        let description = cx
+            .new(|cx| TextInput::new("", cx).placeholder(t!("webcompat.description_placeholder")));
+
+        let subscriptions = vec![
+            cx.subscribe(&url, |this, _, event, cx| {
+                if let TextInputEvent::Cancel = event {
+                    this.dismiss(cx);
+                }
+            }),
+            cx.subscribe(&description, |this, _, event, cx| match event {
+                TextInputEvent::Confirm => this.submit(cx),
+                TextInputEvent::Cancel => this.dismiss(cx),
+            }),
+        ];
+
+        cx.global::<WebCompatReports>()
+            .set_status(ReportStatus::Editing);
+        window.focus(&description.focus_handle(cx));
+
+        Self {
+            url,
+            category: None,
+            choosing: false,
+            description,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn submit(&mut self, cx: &mut Context<Self>) {
+        let Some(category) = self.category else {
+            self.choosing = true;
+            cx.notify();
+            return;
+        };
+        let reports = cx.global::<WebCompatReports>().clone();
+        if reports.status() == ReportStatus::Sending {
+            return;
+        }
+
+        let report = Report {
+            url: self.url.read(cx).text().trim().to_string(),
+            category,
+            description: self.description.read(cx).text().trim().to_string(),
+            browser: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            os: std::env::consts::OS.to_string(),
+        };
+        let endpoint = cx.global::<Settings>().webcompat_endpoint.clone();
+        reports.send(&endpoint, &report);
+        cx.notify();
+    }
+
+    fn dismiss(&mut self, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ReportSheet {}
+
+impl Render for ReportSheet {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let status = cx.global::<WebCompatReports>().status();
+
+        let content = match status {
+            ReportStatus::Sent => div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(div().text_sm().child(t!("webcompat.thanks")))
+                .child(
+                    div()
+                        .text_color(theme.text_secondary)
+                        .child(t!("webcompat.thanks_description")),
+                )
+                .child(
+                    div().flex().justify_end().child(
+                        button("webcompat-done", t!("webcompat.done"), true, cx)
+                            .on_click(cx.listener(|this, _, _, cx| this.dismiss(cx))),
+                    ),
+                ),
+            status => {
+                let sending = status == ReportStatus::Sending;
+                let category = match self.category {
+                    Some(category) => category.label(),
+                    None => t!("webcompat.choose_category"),
+                };
+
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(div().text_sm().child(t!("webcompat.title")))
+                    .child(self.url.clone())
+                    .child(
+                        div()
+                            .relative()
+                            .child(
+                                div()
+                                    .id("webcompat-category")
+                                    .flex()
+                                    .justify_between()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_md()
+                                    .border_1()
+                                    .border_color(theme.border)
+                                    .cursor_pointer()
+                                    .when(self.category.is_none(), |this| {
+                                        this.text_color(theme.text_placeholder)
+                                    })
+                                    .child(category)
+                                    .child("▾")
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.choosing = !this.choosing;
+                                        cx.notify();
+                                    })),
+                            )
+                            .when(self.choosing, |this| {
+                                this.child(deferred(
+                                    div()
+                                        .absolute()
+                                        .top_full()
+                                        .left_0()
+                                        .right_0()
+                                        .mt_1()
+                                        .p_1()
+                                        .rounded_md()
+                                        .border_1()
+                                        .border_color(theme.border)
+                                        .bg(theme.surface)
+                                        .children(
+                                            BreakageCategory::ALL.into_iter().enumerate().map(
+                                                |(index, category)| {
+                                                    div()
+                                                        .id(("webcompat-category-option", index))
+                                                        .px_2()
+                                                        .py_1()
+                                                        .rounded_sm()
+                                                        .cursor_pointer()
+                                                        .hover(|this| {
+                                                            this.bg(theme.element_selected)
+                                                        })
+                                                        .child(category.label())
+                                                        .on_click(cx.listener(
+                                                            move |this, _, _, cx| {
+                                                                this.category = Some(category);
+                                                                this.choosing = false;
+                                                                cx.notify();
+                                                            },
+                                                        ))
+                                                },
+                                            ),
+                                        ),
+                                ))
+                            }),
+                    )
+                    .child(self.description.clone())
+                    .when_some(
+                        match status {
+                            ReportStatus::Failed(error) => Some(error),
+                            _ => None,
+                        },
+                        |this, error| {
+                            this.child(
+                                div()
+                                    .text_color(theme.error)
+                                    .child(t!("webcompat.failed", error = error)),
+                            )
+                        },
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                button("webcompat-cancel", t!("webcompat.cancel"), false, cx)
+                                    .on_click(cx.listener(|this, _, _, cx| this.dismiss(cx))),
+                            )
+                            .child(
+                                button(
+                                    "webcompat-submit",
+                                    match sending {
+                                        true => t!("webcompat.sending"),
+                                        false => t!("webcompat.submit"),
+                                    },
+                                    self.category.is_some() && !sending,
+                                    cx,
+                                )
+                                .on_click(cx.listener(|this, _, _, cx| this.submit(cx))),
+                            ),
+                    )
+            }
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.scrim)
+            .child(
+                div()
+                    .w(px(360.))
+                    .p_4()
+                    .rounded_xl()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .child(content),
+            )
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cef_ui::Request;
+use gpui::{App as GpuiApp, Global};
+use serde::Deserialize;
+
+use crate::{
+    locale::t,
+    message_router::{Query, ERROR_FAILED},
+};
+
+/// Oldest connections are dropped beyond this many.
+const MAX_CONNECTIONS: usize = 100;
+
+/// Oldest messages of a connection are dropped beyond this many.
+const MAX_MESSAGES: usize = 500;
+
+/// Longest payload kept for a message, in characters.
+const MAX_PAYLOAD: usize = 4096;
+
+/// Wraps `window.WebSocket` so every connection reports its lifecycle and
+/// messages. Frames never pass through CEF's resource handlers, so they're
+/// copied from the page instead. Relies on `window.cefQuery`.
+pub const WEBSOCKET_SCRIPT: &str = r#"(() => {
+  if (window.__cefWebSocket || !window.WebSocket) return;
+  Object.defineProperty(window, "__cefWebSocket", { value: true });
+
+  const Native = window.WebSocket;
+  const page = Math.random().toString(36).slice(2);
+  let nextId = 1;
+
+  const payload = (data) => {
+    if (typeof data === "string") return { text: data };
+    if (data instanceof Blob) return { binary: data.size };
+    if (data && "byteLength" in data) return { binary: data.byteLength };
+    return { text: String(data) };
+  };
+
+  const report = (id, event, fields) => {
+    window.cefQuery({
+      request: "websocket:" + JSON.stringify({ id, event, ...fields }),
+    });
+  };
+
+  window.WebSocket = class WebSocket extends Native {
+    constructor(url, protocols) {
+      super(url, protocols);
+      const id = page + ":" + nextId++;
+
+      report(id, "connecting", { url: this.url });
+      this.addEventListener("open", () => report(id, "open", {}));
+      this.addEventListener("message", (event) =>
+        report(id, "received", payload(event.data)));
+      this.addEventListener("close", (event) =>
+        report(id, "close", { code: event.code }));
+
+      const send = this.send.bind(this);
+      this.send = (data) => {
+        send(data);
+        report(id, "sent", payload(data));
+      };
+    }
+  };
+})();"#;
+
+/// A message sent or received over a WebSocket.
+#[derive(Clone)]
+pub struct WebSocketMessage {
+    pub outgoing: bool,
+    /// The text sent, or a description of binary data.
+    pub payload: String,
+}
+
+/// A WebSocket opened by the page.
+#[derive(Clone)]
+pub struct WebSocketConnection {
+    pub id: String,
+    pub url: String,
+    pub open: bool,
+    pub closed: Option<u16>,
+    pub messages: VecDeque<WebSocketMessage>,
+}
+
+/// What the page reports about a connection.
+#[derive(Deserialize)]
+struct WebSocketEvent {
+    id: String,
+    event: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    binary: Option<u64>,
+    #[serde(default)]
+    code: u16,
+}
+
+/// Prefix of the ids of connections only known from their handshake.
+const HANDSHAKE_PREFIX: &str = "handshake:";
+
+#[derive(Default)]
+struct WebSocketLogInner {
+    connections: VecDeque<WebSocketConnection>,
+    changed: bool,
+}
+
+impl WebSocketLogInner {
+    fn push(&mut self, connection: WebSocketConnection) {
+        if self.connections.len() == MAX_CONNECTIONS {
+            self.connections.pop_front();
+        }
+
+        self.connections.push_back(connection);
+        self.changed = true;
+    }
+}
+
+/// WebSocket connections and their messages, recorded for the network panel.
+#[derive(Clone, Default)]
+pub struct WebSocketLog(Arc<Mutex<WebSocketLogInner>>);
+
+impl Global for WebSocketLog {}
+
+impl WebSocketLog {
+    /// Record `request` if it's a WebSocket handshake, so connections show up
+    /// even when the page doesn't report them, e.g. from a worker. Returns
+    /// true if it was one.
+    pub fn on_upgrade(&self, request: &Request) -> bool {
+        let url = request.get_url().unwrap_or_default();
+        let upgrade = request
+            .get_header_by_name("Upgrade")
+            .ok()
+            .flatten()
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+
+        if !upgrade && !url.starts_with("ws://") && !url.starts_with("wss://") {
+            return false;
+        }
+
+        let mut inner = self.0.lock().unwrap();
+
+        // The page may have reported the connection first.
+        let reported = inner.connections.iter().any(|connection| {
+            connection.url == url && !connection.open && connection.closed.is_none()
+        });
+        if reported {
+            return true;
+        }
+
+        let id = request.get_identifier().unwrap_or_default();
+        inner.push(WebSocketConnection {
+            id: format!("{}{}", HANDSHAKE_PREFIX, id),
+            url,
+            open: false,
+            closed: None,
+            messages: VecDeque::new(),
+        });
+
+        true
+    }
+
+    pub fn connections(&self) -> Vec<WebSocketConnection> {
+        self.0.lock().unwrap().connections.iter().cloned().collect()
+    }
+
+    /// Connections that haven't closed.
+    pub fn open_count(&self) -> usize {
+        let inner = self.0.lock().unwrap();
+        inner
+            .connections
+            .iter()
+            .filter(|c| c.closed.is_none())
+            .count()
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner
+            .connections
+            .retain(|connection| connection.closed.is_none());
+        for connection in inner.connections.iter_mut() {
+            connection.messages.clear();
+        }
+        inner.changed = true;
+    }
+
+    /// Whether connections or messages were added since the last call.
+    pub fn take_changed(&self) -> bool {
+        std::mem::take(&mut self.0.lock().unwrap().changed)
+    }
+
+    fn record(&self, event: WebSocketEvent) {
+        let mut inner = self.0.lock().unwrap();
+        inner.changed = true;
+
+        if event.event == "connecting" {
+            // Take over the handshake recorded for this connection, if any.
+            let handshake = inner.connections.iter_mut().find(|connection| {
+                connection.id.starts_with(HANDSHAKE_PREFIX) && connection.url == event.url
+            });
+
+            match handshake {
+                Some(connection) => connection.id = event.id,
+                None => inner.push(WebSocketConnection {
+                    id: event.id,
+                    url: event.url,
+                    open: false,
+                    closed: None,
+                    messages: VecDeque::new(),
+                }),
+            }
+            return;
+        }
+
+        let Some(connection) = inner
+            .connections
+            .iter_mut()
+            .rev()
+            .find(|connection| connection.id == event.id)
+        else {
+            return;
+        };
+
+        match event.event.as_str() {
+            "open" => connection.open = true,
+            "close" => connection.closed = Some(event.code),
+            "sent" | "received" => {
+                let payload = match (event.text, event.binary) {
+                    (Some(text), _) => text.chars().take(MAX_PAYLOAD).collect(),
+                    (None, Some(size)) => t!("network.binary_message", size = size).to_string(),
+                    (None, None) => String::new(),
+                };
+
+                if connection.messages.len() == MAX_MESSAGES {
+                    connection.messages.pop_front();
+                }
+
+                connection.messages.push_back(WebSocketMessage {
+                    outgoing: event.event == "sent",
+                    payload,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Record a connection event reported by the page.
+pub fn on_query(cx: &GpuiApp, query: Query, argument: &str) {
+    match serde_json::from_str::<WebSocketEvent>(argument) {
+        Ok(event) => {
+            cx.global::<WebSocketLog>().record(event);
+            query.success("");
+        }
+        Err(e) => query.failure(ERROR_FAILED, &format!("Invalid WebSocket event: {}", e)),
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use cef_ui::Browser;
+use gpui::{
+    div, prelude::*, Action, App as GpuiApp, Global, Image, Menu, MenuItem, Pixels, Point,
+    PromptLevel, SharedString, Window, WindowHandle, WindowId,
+};
+
+use crate::{
+    locale::t, settings::Settings, site_isolation, tabs, tabs::TabManager, theme::ActiveTheme,
+    BrowserState, NewWindow, Quit, ShowAbout, WindowDemo,
+};
+
+/// Bring the open window at this index in the "Window" menu to the front.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = window, no_json)]
+pub struct ActivateWindow(pub usize);
+
+/// What a window shows while another one is in front: its tabs and the
+/// per-window part of `BrowserState`.
+#[derive(Default)]
+struct ParkedWindow {
+    tabs: TabManager,
+    browser: Option<Browser>,
+    image: Option<Image>,
+    split_ratio: f32,
+    focus_mode: bool,
+}
+
+struct BrowserWindow {
+    handle: WindowHandle<WindowDemo>,
+    /// Title of the page shown in the window, as listed in the "Window"
+    /// menu.
+    title: SharedString,
+}
+
+/// The open browser windows. Tabs live in the `TabManager` and
+/// `BrowserState` globals, which hold the window in front; the others are
+/// parked here by window ID and swapped back in when activated.
+#[derive(Default)]
+pub struct WindowManager {
+    windows: Vec<BrowserWindow>,
+    /// The window whose tabs are in the globals.
+    live: Option<WindowId>,
+    parked: HashMap<WindowId, ParkedWindow>,
+}
+
+impl Global for WindowManager {}
+
+impl WindowManager {
+    /// Whether the globals hold the tabs of the window `id`.
+    pub fn is_live(&self, id: WindowId) -> bool {
+        self.live == Some(id)
+    }
+}
+
+/// Start tracking a freshly opened window. The first one takes the tabs
+/// already in the globals.
+pub fn register(cx: &mut GpuiApp, handle: WindowHandle<WindowDemo>) {
+    let manager = cx.global_mut::<WindowManager>();
+    manager.live.get_or_insert(handle.window_id());
+    manager.windows.push(BrowserWindow {
+        handle,
+        title: t!("tabs.new_tab"),
+    });
+    set_menus(cx);
+}
+
+/// Hide the tabs of the window in front and put them away.
+fn park(cx: &mut GpuiApp) -> Option<(WindowId, ParkedWindow)> {
+    let id = cx.global::<WindowManager>().live?;
+
+    let tabs = std::mem::take(cx.global_mut::<TabManager>());
+    for tab in tabs.tabs() {
+        if let Err(e) = tab
+            .browser
+            .get_host()
+            .and_then(|host| host.was_hidden(true))
+        {
+            eprintln!("Error hiding tab: {}", e);
+        }
+    }
+
+    let state = cx.global_mut::<BrowserState>();
+    let parked = ParkedWindow {
+        tabs,
+        browser: state.browser.take(),
+        image: state.image.take(),
+        split_ratio: state.split_ratio,
+        focus_mode: std::mem::take(&mut state.focus_mode),
+    };
+
+    Some((id, parked))
+}
+
+/// Swap the tabs of the window `id` into the globals, parking those of the
+/// window that was in front. A window without parked tabs starts empty.
+pub fn switch_to(cx: &mut GpuiApp, id: WindowId) {
+    if cx.global::<WindowManager>().is_live(id) {
+        return;
+    }
+
+    if let Some((previous, parked)) = park(cx) {
+        cx.global_mut::<WindowManager>()
+            .parked
+            .insert(previous, parked);
+    }
+
+    let manager = cx.global_mut::<WindowManager>();
+    manager.live = Some(id);
+    let parked = manager.parked.remove(&id).unwrap_or(ParkedWindow {
+        split_ratio: 0.5,
+        ..Default::default()
+    });
+
+    let active = parked.tabs.active();
+    cx.set_global(parked.tabs);
+    let state = cx.global_mut::<BrowserState>();
+    state.image = parked.image;
+    state.split_ratio = parked.split_ratio;
+    state.focus_mode = parked.focus_mode;
+    state.browser = parked.browser;
+    tabs::activate(cx, active);
+}
+
+/// Open another browser window on the homepage and bring it to the front.
+/// The mock browser only has the one page, so it stays in one window.
+pub fn new_window(cx: &mut GpuiApp) {
+    if cx.global::<BrowserState>().mock.is_some() {
+        return;
+    }
+
+    let Some(handle) = crate::open_browser_window(cx, None) else {
+        return;
+    };
+    switch_to(cx, handle.window_id());
+
+    let homepage = cx.global::<Settings>().homepage.clone();
+    let context = site_isolation::context_for(cx, &homepage);
+    if let Some(browser) = crate::new_browser_in_context(cx, &homepage, context) {
+        tabs::add(cx, browser, true);
+    }
+}
+
+/// Move the tab at `index` of the window in front into a new window with its
+/// top left corner at `position` on screen. The last tab of a workspace
+/// stays where it is.
+pub fn tear_out(cx: &mut GpuiApp, index: usize, position: Point<Pixels>) {
+    if cx.global::<BrowserState>().mock.is_some() {
+        return;
+    }
+
+    let Some(browser) = tabs::detach(cx, index) else {
+        return;
+    };
+
+    // If the window can't open, the tab goes back to the end of the strip.
+    if let Some(handle) = crate::open_browser_window(cx, Some(position)) {
+        switch_to(cx, handle.window_id());
+    }
+    tabs::add(cx, browser, true);
+}
+
+/// Bring the window at `index` in the "Window" menu to the front. Its tabs
+/// are swapped in once it's activated.
+pub fn activate(cx: &mut GpuiApp, index: usize) {
+    let Some(handle) = cx
+        .global::<WindowManager>()
+        .windows
+        .get(index)
+        .map(|window| window.handle)
+    else {
+        return;
+    };
+
+    handle
+        .update(cx, |_, window, _| window.activate_window())
+        .ok();
+}
+
+/// Keep the window's entry in the "Window" menu in step with the page it
+/// shows. Called from the poll loop of the window in front.
+pub fn set_title(window: &mut Window, cx: &mut GpuiApp) {
+    let title: SharedString = cx
+        .global::<BrowserState>()
+        .current_page()
+        .map(|(_, title)| title.into())
+        .unwrap_or_else(|| t!("tabs.new_tab"));
+
+    let id = window.window_handle().window_id();
+    let Some(entry) = cx
+        .global_mut::<WindowManager>()
+        .windows
+        .iter_mut()
+        .find(|entry| entry.handle.window_id() == id)
+    else {
+        return;
+    };
+    if entry.title == title {
+        return;
+    }
+
+    entry.title = title.clone();
+    window.set_window_title(&title);
+    set_menus(cx);
+}
+
+/// Whether `window` may close. Closing the last window asks whether to
+/// quit; closing any other closes its tabs and brings another forward.
+pub fn should_close(window: &mut Window, cx: &mut GpuiApp) -> bool {
+    let id = window.window_handle().window_id();
+    let manager = cx.global::<WindowManager>();
+    let Some(next) = manager
+        .windows
+        .iter()
+        .map(|entry| entry.handle)
+        .find(|handle| handle.window_id() != id)
+    else {
+        let quit = t!("window_manager.quit");
+        let cancel = t!("window_manager.cancel");
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &t!("window_manager.quit_title"),
+            Some(&t!("window_manager.quit_description")),
+            &[quit.as_ref(), cancel.as_ref()],
+            cx,
+        );
+
+        cx.spawn(async move |cx| {
+            if answer.await == Ok(0) {
+                cx.update(|cx| cx.dispatch_action(&Quit)).ok();
+            }
+        })
+        .detach();
+        return false;
+    };
+
+    if manager.is_live(id) {
+        switch_to(cx, next.window_id());
+    }
+
+    let manager = cx.global_mut::<WindowManager>();
+    manager
+        .windows
+        .retain(|entry| entry.handle.window_id() != id);
+    if let Some(parked) = manager.parked.remove(&id) {
+        for tab in parked.tabs.tabs() {
+            if let Err(e) = tab
+                .browser
+                .get_host()
+                .and_then(|host| host.close_browser(true))
+            {
+                eprintln!("Error closing tab: {}", e);
+            }
+        }
+    }
+    set_menus(cx);
+
+    // The closing window is still being updated, so bring the next one up
+    // once it's done.
+    cx.defer(move |cx| {
+        next.update(cx, |_, window, _| window.activate_window())
+            .ok();
+    });
+    true
+}
+
+/// The application menu, and the "Window" menu listing the open windows by
+/// the title of the page they show.
+fn set_menus(cx: &GpuiApp) {
+    let windows = cx
+        .global::<WindowManager>()
+        .windows
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| MenuItem::action(entry.title.clone(), ActivateWindow(index)))
+        .collect();
+
+    cx.set_menus(vec![
+        Menu {
+            name: "Browser".into(),
+            items: vec![
+                MenuItem::action(t!("window_manager.about"), ShowAbout),
+                MenuItem::separator(),
+                MenuItem::action(t!("window_manager.new_window"), NewWindow),
+                MenuItem::separator(),
+                MenuItem::action(t!("window_manager.quit"), Quit),
+            ],
+        },
+        Menu {
+            name: t!("window_manager.window"),
+            items: windows,
+        },
+    ]);
+}
+
+/// What a window shows while its tabs are parked: a reminder to click in,
+/// which activates it and brings its tabs back.
+pub fn parked(cx: &GpuiApp) -> impl IntoElement {
+    let theme = cx.theme();
+
+    div()
+        .flex()
+        .items_center()
+        .justify_center()
+        .size_full()
+        .bg(theme.background)
+        .text_color(theme.text_secondary)
+        .text_sm()
+        .child(t!("window_manager.parked"))
+}
@@ -0,0 +1,254 @@
+use gpui::{
+    anchored, deferred, div, prelude::*, px, Action, App as GpuiApp, Context, Entity, Focusable,
+    MouseButton, MouseDownEvent, Pixels, Point, SharedString, Subscription, Window,
+};
+
+use crate::{
+    locale::t,
+    settings::Settings,
+    tabs,
+    tabs::TabManager,
+    text_input::{TextInput, TextInputEvent},
+    theme::ActiveTheme,
+};
+
+/// Show the workspace at this index in the sidebar.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = window, no_json)]
+pub struct SwitchWorkspace(pub usize);
+
+/// Show the workspace at `index`, on the tab that was active in it last.
+pub fn switch(cx: &mut GpuiApp, index: usize) {
+    let manager = cx.global::<TabManager>();
+    let Some(workspace) = manager.workspaces().get(index) else {
+        return;
+    };
+
+    let tab_id = workspace
+        .active_tab_id
+        .filter(|id| workspace.tab_ids.contains(id))
+        .or(workspace.tab_ids.first().copied());
+    let Some(tab_index) = tab_id.and_then(|id| manager.tabs().iter().position(|tab| tab.id == id))
+    else {
+        return;
+    };
+
+    tabs::activate(cx, tab_index);
+}
+
+/// Add a workspace, with a tab on the homepage, and show it.
+pub fn new_workspace(cx: &mut GpuiApp) {
+    let previous = cx.global::<TabManager>().active();
+    let id = cx.global_mut::<TabManager>().new_workspace();
+
+    let homepage = cx.global::<Settings>().homepage.clone();
+    match crate::new_browser(cx, &homepage) {
+        Some(browser) => tabs::add(cx, browser, true),
+        None => {
+            cx.global_mut::<TabManager>().delete_workspace(id);
+            tabs::activate(cx, previous);
+        }
+    }
+}
+
+/// The workspaces down the left of the window, for switching between them.
+pub struct WorkspaceSidebar {
+    /// Workspace whose right-click menu is open, and where.
+    menu: Option<(usize, Point<Pixels>)>,
+    renaming: Option<(usize, Entity<TextInput>, Subscription)>,
+}
+
+impl WorkspaceSidebar {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<TabManager>(|_, cx| cx.notify())
+            .detach();
+
+        Self {
+            menu: None,
+            renaming: None,
+        }
+    }
+
+    fn start_rename(&mut self, id: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(name) = cx
+            .global::<TabManager>()
+            .workspaces()
+            .iter()
+            .find(|workspace| workspace.id == id)
+            .map(|workspace| workspace.name.clone())
+        else {
+            return;
+        };
+
+        let input = cx.new(|cx| TextInput::new(name, cx));
+        let subscription = cx.subscribe(&input, move |this, input, event, cx| {
+            if let TextInputEvent::Confirm = event {
+                let name = input.read(cx).text().to_string();
+                cx.global_mut::<TabManager>().rename_workspace(id, &name);
+            }
+
+            this.renaming = None;
+            cx.notify();
+        });
+
+        window.focus(&input.focus_handle(cx));
+        self.renaming = Some((id, input, subscription));
+        self.menu = None;
+        cx.notify();
+    }
+
+    fn render_menu(
+        &self,
+        id: usize,
+        position: Point<Pixels>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let can_delete = cx.global::<TabManager>().workspaces().len() > 1;
+        let item = |element_id: &'static str, label: SharedString| {
+            div()
+                .id(element_id)
+                .px_3()
+                .py_1()
+                .rounded_sm()
+                .cursor_pointer()
+                .hover(|this| this.bg(theme.element_selected))
+                .child(label)
+        };
+
+        deferred(
+            anchored().position(position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .p_1()
+                    .min_w(px(140.))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                        this.menu = None;
+                        cx.notify();
+                    }))
+                    .child(item("workspace-rename", t!("workspaces.rename")).on_click(
+                        cx.listener(move |this, _, window, cx| this.start_rename(id, window, cx)),
+                    ))
+                    .when(can_delete, |this| {
+                        this.child(item("workspace-delete", t!("workspaces.delete")).on_click(
+                            cx.listener(move |this, _, window, cx| {
+                                cx.global_mut::<TabManager>().delete_workspace(id);
+                                this.menu = None;
+                                window.refresh();
+                            }),
+                        ))
+                    }),
+            ),
+        )
+    }
+}
+
+impl Render for WorkspaceSidebar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let manager = cx.global::<TabManager>();
+        let active = manager.active_workspace();
+        let workspaces = manager.workspaces().to_vec();
+        let renaming = self
+            .renaming
+            .as_ref()
+            .map(|(id, input, _)| (*id, input.clone()));
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .gap_0p5()
+            .w(px(150.))
+            .p_2()
+            .border_r_1()
+            .border_color(theme.border)
+            .text_xs()
+            .text_color(theme.text_secondary)
+            .child(
+                div()
+                    .px_2()
+                    .pb_1()
+                    .text_color(theme.text_muted)
+                    .child(t!("workspaces.title")),
+            )
+            .children(
+                workspaces
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, workspace)| {
+                        let id = workspace.id;
+                        if let Some((_, input)) = renaming.as_ref().filter(|(i, _)| *i == id) {
+                            return div().child(input.clone()).into_any_element();
+                        }
+
+                        div()
+                            .id(("workspace", id))
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .when(id == active, |this| {
+                                this.bg(theme.element_selected).text_color(theme.text)
+                            })
+                            .hover(|this| this.bg(theme.ghost_hover))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .overflow_hidden()
+                                    .whitespace_nowrap()
+                                    .text_ellipsis()
+                                    .child(workspace.name),
+                            )
+                            .child(
+                                div()
+                                    .flex_none()
+                                    .text_color(theme.text_muted)
+                                    .child(workspace.tab_ids.len().to_string()),
+                            )
+                            .on_click(move |_, window, cx| {
+                                switch(cx, index);
+                                window.refresh();
+                            })
+                            .on_mouse_down(
+                                MouseButton::Right,
+                                cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                                    this.menu = Some((id, event.position));
+                                    cx.notify();
+                                }),
+                            )
+                            .into_any_element()
+                    }),
+            )
+            .child(
+                div()
+                    .id("workspace-new")
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .text_color(theme.text_muted)
+                    .hover(|this| this.bg(theme.ghost_hover).text_color(theme.text))
+                    .child(t!("workspaces.new_workspace"))
+                    .on_click(|_, window, cx| {
+                        new_workspace(cx);
+                        window.refresh();
+                    }),
+            )
+            .when_some(self.menu, |this, (id, position)| {
+                this.child(self.render_menu(id, position, cx))
+            })
+    }
+}
@@ -21,7 +21,14 @@ const CEF_PATH: &str = "../../../Chromium Embedded Framework.framework/Chromium
 
 /// Returns the CEF error code or 1 if an error occurred.
 pub fn run(sandbox: bool) {
-    let ret = try_run(sandbox).unwrap_or_else(|e| {
+    run_with_app(sandbox, null_mut);
+}
+
+/// Same as run, but passes the cef_app_t returned by |app| to CEF so that the
+/// subprocess gets the app's custom schemes and render process handler. |app|
+/// is called after the CEF framework has been loaded.
+pub fn run_with_app(sandbox: bool, app: impl FnOnce() -> *mut c_void) {
+    let ret = try_run(sandbox, app).unwrap_or_else(|e| {
         error!("An error occurred: {}", e);
 
         1
@@ -33,7 +40,7 @@ pub fn run(sandbox: bool) {
 }
 
 /// Try and run the helper, returning the CEF error code if successful.
-fn try_run(sandbox: bool) -> Result<i32> {
+fn try_run(sandbox: bool, app: impl FnOnce() -> *mut c_void) -> Result<i32> {
     // This routes log macros through tracing.
     LogTracer::init()?;
 
@@ -69,7 +76,7 @@ fn try_run(sandbox: bool) -> Result<i32> {
         info!("Executing CEF subprocess ..");
 
         // Execute the CEF subprocess.
-        let ret = cef_execute_process(main_args.as_raw(), null_mut(), null_mut()) as i32;
+        let ret = cef_execute_process(main_args.as_raw(), app(), null_mut()) as i32;
 
         info!("CEF exited with code: {}", ret);
 
@@ -1,6 +1,6 @@
 use crate::{
-    ref_counted_ptr, BrowserProcessHandler, CefString, CommandLine, RefCountedPtr, Wrappable,
-    Wrapped
+    ref_counted_ptr, BrowserProcessHandler, CefString, CommandLine, RefCountedPtr,
+    RenderProcessHandler, SchemeRegistrar, Wrappable, Wrapped
 };
 use cef_ui_sys::{
     cef_app_t, cef_browser_process_handler_t, cef_command_line_t, cef_render_process_handler_t,
@@ -27,13 +27,13 @@ pub trait AppCallbacks: Send + Sync + 'static {
         command_line: Option<CommandLine>
     );
 
-    // TODO: Fix this!
+    /// Provides an opportunity to register custom schemes. Do not keep a
+    /// reference to the |registrar| object. This function is called on the main
+    /// thread for each process and the registered schemes should be the same
+    /// across all processes.
+    fn on_register_custom_schemes(&mut self, registrar: SchemeRegistrar);
 
-    // /// Provides an opportunity to register custom schemes. Do not keep a
-    // /// reference to the |registrar| object. This function is called on the main
-    // /// thread for each process and the registered schemes should be the same
-    // /// across all processes.
-    // fn on_register_custom_schemes(&mut self, registrar: SchemeRegistrar);
+    // TODO: Fix this!
 
     // /// Return the handler for resource bundle events. If
     // /// cef_settings_t.pack_loading_disabled is true (1) a handler must be
@@ -46,11 +46,9 @@ pub trait AppCallbacks: Send + Sync + 'static {
     /// function is called on multiple threads in the browser process.
     fn get_browser_process_handler(&mut self) -> Option<BrowserProcessHandler>;
 
-    // TODO: Fix this!
-
-    // /// Return the handler for functionality specific to the render process. This
-    // /// function is called on the render process main thread.
-    // fn get_render_process_handler(&mut self) -> Option<RenderProcessHandler>;
+    /// Return the handler for functionality specific to the render process. This
+    /// function is called on the render process main thread.
+    fn get_render_process_handler(&mut self) -> Option<RenderProcessHandler>;
 }
 
 // Implement this structure to provide handler implementations. Methods will be
@@ -99,8 +97,6 @@ impl AppWrapper {
             .on_before_command_line_processing(process_type, command_line);
     }
 
-    // TODO: Fix this!
-
     /// Provides an opportunity to register custom schemes. Do not keep a
     /// reference to the |registrar| object. This function is called on the main
     /// thread for each process and the registered schemes should be the same
@@ -109,9 +105,15 @@ impl AppWrapper {
         this: *mut cef_app_t,
         registrar: *mut cef_scheme_registrar_t
     ) {
-        todo!();
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        if let Some(registrar) = SchemeRegistrar::from_ptr(registrar) {
+            this.0.on_register_custom_schemes(registrar);
+        }
     }
 
+    // TODO: Fix this!
+
     /// Return the handler for resource bundle events. If
     /// cef_settings_t.pack_loading_disabled is true (1) a handler must be
     /// returned. If no handler is returned resources will be loaded from pack
@@ -141,7 +143,12 @@ impl AppWrapper {
     unsafe extern "C" fn c_get_render_process_handler(
         this: *mut cef_app_t
     ) -> *mut cef_render_process_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_render_process_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or_else(null_mut)
     }
 }
 
@@ -156,10 +163,10 @@ impl Wrappable for AppWrapper {
 
                 // TODO: Fix this!
                 on_before_command_line_processing: Some(Self::c_on_before_command_line_processing),
-                on_register_custom_schemes:        None,
+                on_register_custom_schemes:        Some(Self::c_on_register_custom_schemes),
                 get_resource_bundle_handler:       None,
                 get_browser_process_handler:       Some(Self::c_get_browser_process_handler),
-                get_render_process_handler:        None
+                get_render_process_handler:        Some(Self::c_get_render_process_handler)
             },
             self
         )
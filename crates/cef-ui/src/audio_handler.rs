@@ -0,0 +1,416 @@
+use crate::{ref_counted_ptr, Browser, CefString, RefCountedPtr, Wrappable, Wrapped};
+use cef_ui_sys::{
+    cef_audio_handler_t, cef_audio_parameters_t, cef_browser_t, cef_channel_layout_t, cef_string_t
+};
+use std::{ffi::c_int, mem::zeroed, slice::from_raw_parts};
+
+/// Enumerates the various representations of the ordering of audio channels.
+/// Must be kept synchronized with media::ChannelLayout from Chromium.
+/// See media\base\channel_layout.h
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// None
+    None,
+
+    /// Unsupported
+    Unsupported,
+
+    /// Front C
+    Mono,
+
+    /// Front L, Front R
+    Stereo,
+
+    /// Front L, Front R, Back C
+    Layout2_1,
+
+    /// Front L, Front R, Front C
+    Surround,
+
+    /// Front L, Front R, Front C, Back C
+    Layout4_0,
+
+    /// Front L, Front R, Side L, Side R
+    Layout2_2,
+
+    /// Front L, Front R, Back L, Back R
+    Quad,
+
+    /// Front L, Front R, Front C, Side L, Side R
+    Layout5_0,
+
+    /// Front L, Front R, Front C, LFE, Side L, Side R
+    Layout5_1,
+
+    /// Front L, Front R, Front C, Back L, Back R
+    Layout5_0Back,
+
+    /// Front L, Front R, Front C, LFE, Back L, Back R
+    Layout5_1Back,
+
+    /// Front L, Front R, Front C, Side L, Side R, Back L, Back R
+    Layout7_0,
+
+    /// Front L, Front R, Front C, LFE, Side L, Side R, Back L, Back R
+    Layout7_1,
+
+    /// Front L, Front R, Front C, LFE, Side L, Side R, Front LofC, Front RofC
+    Layout7_1Wide,
+
+    /// Stereo L, Stereo R
+    StereoDownmix,
+
+    /// Stereo L, Stereo R, LFE
+    Layout2Point1,
+
+    /// Stereo L, Stereo R, Front C, LFE
+    Layout3_1,
+
+    /// Stereo L, Stereo R, Front C, Rear C, LFE
+    Layout4_1,
+
+    /// Stereo L, Stereo R, Front C, Side L, Side R, Rear C
+    Layout6_0,
+
+    /// Stereo L, Stereo R, Side L, Side R, Front LofC, Front RofC
+    Layout6_0Front,
+
+    /// Stereo L, Stereo R, Front C, Rear L, Rear R, Rear C
+    Hexagonal,
+
+    /// Stereo L, Stereo R, Front C, LFE, Side L, Side R, Rear C
+    Layout6_1,
+
+    /// Stereo L, Stereo R, Front C, LFE, Back L, Back R, Rear C
+    Layout6_1Back,
+
+    /// Stereo L, Stereo R, Side L, Side R, Front LofC, Front RofC, LFE
+    Layout6_1Front,
+
+    /// Front L, Front R, Front C, Side L, Side R, Front LofC, Front RofC
+    Layout7_0Front,
+
+    /// Front L, Front R, Front C, LFE, Back L, Back R, Front LofC, Front RofC
+    Layout7_1WideBack,
+
+    /// Front L, Front R, Front C, Side L, Side R, Rear L, Back R, Back C.
+    Octagonal,
+
+    /// Channels are not explicitly mapped to speakers.
+    Discrete,
+
+    /// Deprecated, but keeping the enum value for UMA consistency.
+    /// Front L, Front R, Front C. Front C contains the keyboard mic audio. This
+    /// layout is only intended for input for WebRTC. The Front C channel
+    /// is stripped away in the WebRTC audio input pipeline and never seen outside
+    /// of that.
+    StereoAndKeyboardMic,
+
+    /// Front L, Front R, Side L, Side R, LFE
+    Layout4_1QuadSide,
+
+    /// Actual channel layout is specified in the bitstream and the actual channel
+    /// count is unknown at Chromium media pipeline level (useful for audio
+    /// pass-through mode).
+    Bitstream,
+
+    /// Front L, Front R, Front C, LFE, Side L, Side R,
+    /// Front Height L, Front Height R, Rear Height L, Rear Height R
+    /// Will be represented as six channels (5.1) due to eight channel limit
+    /// kMaxConcurrentChannels
+    Layout5_1_4Downmix
+}
+
+impl From<cef_channel_layout_t> for ChannelLayout {
+    fn from(value: cef_channel_layout_t) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&cef_channel_layout_t> for ChannelLayout {
+    fn from(value: &cef_channel_layout_t) -> Self {
+        match value {
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_NONE => Self::None,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_UNSUPPORTED => Self::Unsupported,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_MONO => Self::Mono,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_STEREO => Self::Stereo,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_2_1 => Self::Layout2_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_SURROUND => Self::Surround,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_4_0 => Self::Layout4_0,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_2_2 => Self::Layout2_2,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_QUAD => Self::Quad,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_5_0 => Self::Layout5_0,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_5_1 => Self::Layout5_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_5_0_BACK => Self::Layout5_0Back,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_5_1_BACK => Self::Layout5_1Back,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_7_0 => Self::Layout7_0,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_7_1 => Self::Layout7_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_7_1_WIDE => Self::Layout7_1Wide,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_STEREO_DOWNMIX => Self::StereoDownmix,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_2POINT1 => Self::Layout2Point1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_3_1 => Self::Layout3_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_4_1 => Self::Layout4_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_6_0 => Self::Layout6_0,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_6_0_FRONT => Self::Layout6_0Front,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_HEXAGONAL => Self::Hexagonal,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_6_1 => Self::Layout6_1,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_6_1_BACK => Self::Layout6_1Back,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_6_1_FRONT => Self::Layout6_1Front,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_7_0_FRONT => Self::Layout7_0Front,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_7_1_WIDE_BACK => Self::Layout7_1WideBack,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_OCTAGONAL => Self::Octagonal,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_DISCRETE => Self::Discrete,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_STEREO_AND_KEYBOARD_MIC => Self::StereoAndKeyboardMic,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_4_1_QUAD_SIDE => Self::Layout4_1QuadSide,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_BITSTREAM => Self::Bitstream,
+            cef_channel_layout_t::CEF_CHANNEL_LAYOUT_5_1_4_DOWNMIX => Self::Layout5_1_4Downmix
+        }
+    }
+}
+
+impl From<ChannelLayout> for cef_channel_layout_t {
+    fn from(value: ChannelLayout) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&ChannelLayout> for cef_channel_layout_t {
+    fn from(value: &ChannelLayout) -> Self {
+        match value {
+            ChannelLayout::None => Self::CEF_CHANNEL_LAYOUT_NONE,
+            ChannelLayout::Unsupported => Self::CEF_CHANNEL_LAYOUT_UNSUPPORTED,
+            ChannelLayout::Mono => Self::CEF_CHANNEL_LAYOUT_MONO,
+            ChannelLayout::Stereo => Self::CEF_CHANNEL_LAYOUT_STEREO,
+            ChannelLayout::Layout2_1 => Self::CEF_CHANNEL_LAYOUT_2_1,
+            ChannelLayout::Surround => Self::CEF_CHANNEL_LAYOUT_SURROUND,
+            ChannelLayout::Layout4_0 => Self::CEF_CHANNEL_LAYOUT_4_0,
+            ChannelLayout::Layout2_2 => Self::CEF_CHANNEL_LAYOUT_2_2,
+            ChannelLayout::Quad => Self::CEF_CHANNEL_LAYOUT_QUAD,
+            ChannelLayout::Layout5_0 => Self::CEF_CHANNEL_LAYOUT_5_0,
+            ChannelLayout::Layout5_1 => Self::CEF_CHANNEL_LAYOUT_5_1,
+            ChannelLayout::Layout5_0Back => Self::CEF_CHANNEL_LAYOUT_5_0_BACK,
+            ChannelLayout::Layout5_1Back => Self::CEF_CHANNEL_LAYOUT_5_1_BACK,
+            ChannelLayout::Layout7_0 => Self::CEF_CHANNEL_LAYOUT_7_0,
+            ChannelLayout::Layout7_1 => Self::CEF_CHANNEL_LAYOUT_7_1,
+            ChannelLayout::Layout7_1Wide => Self::CEF_CHANNEL_LAYOUT_7_1_WIDE,
+            ChannelLayout::StereoDownmix => Self::CEF_CHANNEL_LAYOUT_STEREO_DOWNMIX,
+            ChannelLayout::Layout2Point1 => Self::CEF_CHANNEL_LAYOUT_2POINT1,
+            ChannelLayout::Layout3_1 => Self::CEF_CHANNEL_LAYOUT_3_1,
+            ChannelLayout::Layout4_1 => Self::CEF_CHANNEL_LAYOUT_4_1,
+            ChannelLayout::Layout6_0 => Self::CEF_CHANNEL_LAYOUT_6_0,
+            ChannelLayout::Layout6_0Front => Self::CEF_CHANNEL_LAYOUT_6_0_FRONT,
+            ChannelLayout::Hexagonal => Self::CEF_CHANNEL_LAYOUT_HEXAGONAL,
+            ChannelLayout::Layout6_1 => Self::CEF_CHANNEL_LAYOUT_6_1,
+            ChannelLayout::Layout6_1Back => Self::CEF_CHANNEL_LAYOUT_6_1_BACK,
+            ChannelLayout::Layout6_1Front => Self::CEF_CHANNEL_LAYOUT_6_1_FRONT,
+            ChannelLayout::Layout7_0Front => Self::CEF_CHANNEL_LAYOUT_7_0_FRONT,
+            ChannelLayout::Layout7_1WideBack => Self::CEF_CHANNEL_LAYOUT_7_1_WIDE_BACK,
+            ChannelLayout::Octagonal => Self::CEF_CHANNEL_LAYOUT_OCTAGONAL,
+            ChannelLayout::Discrete => Self::CEF_CHANNEL_LAYOUT_DISCRETE,
+            ChannelLayout::StereoAndKeyboardMic => Self::CEF_CHANNEL_LAYOUT_STEREO_AND_KEYBOARD_MIC,
+            ChannelLayout::Layout4_1QuadSide => Self::CEF_CHANNEL_LAYOUT_4_1_QUAD_SIDE,
+            ChannelLayout::Bitstream => Self::CEF_CHANNEL_LAYOUT_BITSTREAM,
+            ChannelLayout::Layout5_1_4Downmix => Self::CEF_CHANNEL_LAYOUT_5_1_4_DOWNMIX
+        }
+    }
+}
+
+/// Structure representing the audio parameters for setting up the audio
+/// handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AudioParameters {
+    /// Layout of the audio channels
+    pub channel_layout: ChannelLayout,
+
+    /// Sample rate
+    pub sample_rate: i32,
+
+    /// Number of frames per buffer
+    pub frames_per_buffer: i32
+}
+
+impl From<&cef_audio_parameters_t> for AudioParameters {
+    fn from(value: &cef_audio_parameters_t) -> Self {
+        Self {
+            channel_layout:    value.channel_layout.into(),
+            sample_rate:       value.sample_rate,
+            frames_per_buffer: value.frames_per_buffer
+        }
+    }
+}
+
+impl From<&AudioParameters> for cef_audio_parameters_t {
+    fn from(value: &AudioParameters) -> Self {
+        Self {
+            channel_layout:    value.channel_layout.into(),
+            sample_rate:       value.sample_rate,
+            frames_per_buffer: value.frames_per_buffer
+        }
+    }
+}
+
+/// Implement this structure to handle audio events.
+pub trait AudioHandlerCallbacks: Send + Sync + 'static {
+    /// Called on the UI thread to allow configuration of audio stream
+    /// parameters. Return true (1) to proceed with audio stream capture, or
+    /// false (0) to cancel it. All members of |params| can optionally be
+    /// configured here, but they are also pre-filled with some sensible
+    /// defaults.
+    fn get_audio_parameters(&mut self, browser: Browser, params: &mut AudioParameters) -> bool;
+
+    /// Called on a browser audio capture thread when the browser starts
+    /// streaming audio. OnAudioStreamStopped will always be called after
+    /// OnAudioStreamStarted; both functions may be called multiple times for
+    /// the same browser. |params| contains the audio parameters like sample
+    /// rate and channel layout. |channels| is the number of channels.
+    fn on_audio_stream_started(&mut self, browser: Browser, params: &AudioParameters, channels: i32);
+
+    /// Called on the audio stream thread when a PCM packet is received for the
+    /// stream. |data| is an array representing the raw PCM data as a floating
+    /// point type, i.e. 4-byte value(s). |frames| is the number of frames in the
+    /// PCM packet. |pts| is the presentation timestamp (in milliseconds since the
+    /// Unix Epoch) and represents the time at which the decompressed packet
+    /// should be presented to the user. Based on |frames| and the
+    /// |channel_layout| value passed to OnAudioStreamStarted you can calculate
+    /// the size of the |data| array in bytes.
+    fn on_audio_stream_packet(&mut self, browser: Browser, data: &[&[f32]], pts: i64);
+
+    /// Called on the UI thread when the stream has stopped. OnAudioSteamStopped
+    /// will always be called after OnAudioStreamStarted; both functions may be
+    /// called multiple times for the same stream.
+    fn on_audio_stream_stopped(&mut self, browser: Browser);
+
+    /// Called on the UI or audio stream thread when an error occurred. During the
+    /// stream creation phase this callback will be called on the UI thread while
+    /// in the capturing phase it will be called on the audio stream thread. The
+    /// stream will be stopped immediately.
+    fn on_audio_stream_error(&mut self, browser: Browser, message: &str);
+}
+
+// Implement this structure to handle audio events.
+ref_counted_ptr!(AudioHandler, cef_audio_handler_t);
+
+impl AudioHandler {
+    pub fn new<C: AudioHandlerCallbacks>(delegate: C) -> Self {
+        Self(AudioHandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct AudioHandlerWrapper {
+    delegate: Box<dyn AudioHandlerCallbacks>,
+    channels: usize
+}
+
+impl AudioHandlerWrapper {
+    pub fn new<C: AudioHandlerCallbacks>(delegate: C) -> Self {
+        Self {
+            delegate: Box::new(delegate),
+            channels: 0
+        }
+    }
+
+    /// Called on the UI thread to allow configuration of audio stream
+    /// parameters.
+    unsafe extern "C" fn c_get_audio_parameters(
+        this: *mut cef_audio_handler_t,
+        browser: *mut cef_browser_t,
+        params: *mut cef_audio_parameters_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let mut audio_params = AudioParameters::from(&*params);
+        let proceed = this
+            .delegate
+            .get_audio_parameters(browser, &mut audio_params);
+
+        *params = (&audio_params).into();
+
+        proceed as c_int
+    }
+
+    /// Called on a browser audio capture thread when the browser starts
+    /// streaming audio.
+    unsafe extern "C" fn c_on_audio_stream_started(
+        this: *mut cef_audio_handler_t,
+        browser: *mut cef_browser_t,
+        params: *const cef_audio_parameters_t,
+        channels: c_int
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let params = AudioParameters::from(&*params);
+
+        this.channels = channels.max(0) as usize;
+        this.delegate
+            .on_audio_stream_started(browser, &params, channels);
+    }
+
+    /// Called on the audio stream thread when a PCM packet is received for the
+    /// stream.
+    unsafe extern "C" fn c_on_audio_stream_packet(
+        this: *mut cef_audio_handler_t,
+        browser: *mut cef_browser_t,
+        data: *mut *const f32,
+        frames: c_int,
+        pts: i64
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let frames = frames.max(0) as usize;
+        let data = match data.is_null() {
+            true => Vec::new(),
+            false => from_raw_parts(data, this.channels)
+                .iter()
+                .map(|channel| from_raw_parts(*channel, frames))
+                .collect()
+        };
+
+        this.delegate
+            .on_audio_stream_packet(browser, &data, pts);
+    }
+
+    /// Called on the UI thread when the stream has stopped.
+    unsafe extern "C" fn c_on_audio_stream_stopped(
+        this: *mut cef_audio_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.delegate.on_audio_stream_stopped(browser);
+    }
+
+    /// Called on the UI or audio stream thread when an error occurred.
+    unsafe extern "C" fn c_on_audio_stream_error(
+        this: *mut cef_audio_handler_t,
+        browser: *mut cef_browser_t,
+        message: *const cef_string_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let message: String = CefString::from_ptr_unchecked(message).into();
+
+        this.delegate
+            .on_audio_stream_error(browser, message.as_str());
+    }
+}
+
+impl Wrappable for AudioHandlerWrapper {
+    type Cef = cef_audio_handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_audio_handler_t> {
+        RefCountedPtr::wrap(
+            cef_audio_handler_t {
+                base:                    unsafe { zeroed() },
+                get_audio_parameters:    Some(Self::c_get_audio_parameters),
+                on_audio_stream_started: Some(Self::c_on_audio_stream_started),
+                on_audio_stream_packet:  Some(Self::c_on_audio_stream_packet),
+                on_audio_stream_stopped: Some(Self::c_on_audio_stream_stopped),
+                on_audio_stream_error:   Some(Self::c_on_audio_stream_error)
+            },
+            self
+        )
+    }
+}
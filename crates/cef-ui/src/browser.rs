@@ -1,8 +1,8 @@
 use crate::{
     free_cef_string, ref_counted_ptr, try_c, CefString, CefStringList, Client, Color, CommandId,
-    CompositionUnderline, DictionaryValue, DragData, DragOperations, Extension, Frame, KeyEvent,
+    CompositionUnderline, DevToolsMessageObserver, DictionaryValue, DragData, DragOperations, Extension, Frame, KeyEvent,
     MouseButtonType, MouseEvent, NativeWindowHandle, NavigationEntry, NavigationEntryVisitor,
-    PaintElementType, Point, Range, RequestContext, Size, State, TouchEvent, WindowInfo,
+    PaintElementType, PdfPrintCallback, PdfPrintSettings, Point, Range, Registration, RequestContext, Size, State, TouchEvent, WindowInfo,
     WindowOpenDisposition, ZoomCommand
 };
 use anyhow::Result;
@@ -621,20 +621,27 @@ impl BrowserHost {
         try_c!(self, print, { Ok(print(self.as_ptr())) })
     }
 
-    // TODO: Fix this!
+    /// Print the current browser contents to the PDF file specified by |path| and
+    /// execute |callback| on completion. The caller is responsible for deleting
+    /// |path| when done. For PDF printing to work on Linux you must implement the
+    /// cef_print_handler_t::GetPdfPaperSize function.
+    pub fn print_to_pdf(
+        &self,
+        path: &str,
+        settings: &PdfPrintSettings,
+        callback: PdfPrintCallback
+    ) -> Result<()> {
+        try_c!(self, print_to_pdf, {
+            let path = CefString::new(path);
 
-    // ///
-    // /// Print the current browser contents to the PDF file specified by |path| and
-    // /// execute |callback| on completion. The caller is responsible for deleting
-    // /// |path| when done. For PDF printing to work on Linux you must implement the
-    // /// cef_print_handler_t::GetPdfPaperSize function.
-    // ///
-    // void(CEF_CALLBACK* print_to_pdf)(
-    // struct _cef_browser_host_t* self,
-    // const cef_string_t* path,
-    // const struct _cef_pdf_print_settings_t* settings,
-    // struct _cef_pdf_print_callback_t* callback);
-    //
+            Ok(print_to_pdf(
+                self.as_ptr(),
+                path.as_ptr(),
+                settings.as_raw(),
+                callback.into_raw()
+            ))
+        })
+    }
 
     /// Search for |searchText|. |forward| indicates whether to search forward or
     /// backward within the page. |matchCase| indicates whether the search should
@@ -788,17 +795,21 @@ impl BrowserHost {
         })
     }
 
-    // TODO: Fix this!
-
-    // ///
-    // /// Add an observer for DevTools protocol messages (function results and
-    // /// events). The observer will remain registered until the returned
-    // /// Registration object is destroyed. See the SendDevToolsMessage
-    // /// documentation for additional usage information.
-    // ///
-    // struct _cef_registration_t*(CEF_CALLBACK* add_dev_tools_message_observer)(
-    // struct _cef_browser_host_t* self,
-    // struct _cef_dev_tools_message_observer_t* observer);
+    /// Add an observer for DevTools protocol messages (function results and
+    /// events). The observer will remain registered until the returned
+    /// Registration object is destroyed. See the SendDevToolsMessage
+    /// documentation for additional usage information.
+    pub fn add_dev_tools_message_observer(
+        &self,
+        observer: DevToolsMessageObserver
+    ) -> Result<Option<Registration>> {
+        try_c!(self, add_dev_tools_message_observer, {
+            Ok(Registration::from_ptr(add_dev_tools_message_observer(
+                self.as_ptr(),
+                observer.into_raw()
+            )))
+        })
+    }
 
     /// Retrieve a snapshot of current navigation entries as values sent to the
     /// specified visitor. If |current_only| is true (1) only the current
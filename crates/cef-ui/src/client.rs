@@ -1,6 +1,8 @@
 use crate::{
-    keyboard_handler::KeyboardHandler, ref_counted_ptr, ContextMenuHandler, LifeSpanHandler,
-    RefCountedPtr, RenderHandler, Wrappable, Wrapped
+    keyboard_handler::KeyboardHandler, ref_counted_ptr, AudioHandler, Browser, ContextMenuHandler,
+    DisplayHandler, FocusHandler, Frame, JsDialogHandler, LifeSpanHandler, LoadHandler,
+    PermissionHandler, PrintHandler, ProcessId, ProcessMessage, RefCountedPtr, RenderHandler,
+    RequestHandler, Wrappable, Wrapped
 };
 use cef_ui_sys::{
     cef_audio_handler_t, cef_browser_t, cef_client_t, cef_command_handler_t,
@@ -14,11 +16,10 @@ use std::{ffi::c_int, mem::zeroed, ptr::null_mut};
 
 /// Implement this structure to provide handler implementations.
 pub trait ClientCallbacks: Send + Sync + 'static {
-    // TODO: Fix this!
+    /// Return the handler for audio rendering events.
+    fn get_audio_handler(&mut self) -> Option<AudioHandler>;
 
-    // /// Return the handler for audio rendering events.
-    // struct _cef_audio_handler_t*(CEF_CALLBACK* get_audio_handler)(
-    // struct _cef_client_t* self);
+    // TODO: Fix this!
 
     // /// Return the handler for commands. If no handler is provided the default
     // /// implementation will be used.
@@ -34,9 +35,8 @@ pub trait ClientCallbacks: Send + Sync + 'static {
     // struct _cef_dialog_handler_t*(CEF_CALLBACK* get_dialog_handler)(
     // struct _cef_client_t* self);
 
-    // /// Return the handler for browser display state events.
-    // struct _cef_display_handler_t*(CEF_CALLBACK* get_display_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for browser display state events.
+    fn get_display_handler(&mut self) -> Option<DisplayHandler>;
 
     // /// Return the handler for download events. If no handler is returned
     // /// downloads will not be allowed.
@@ -51,9 +51,8 @@ pub trait ClientCallbacks: Send + Sync + 'static {
     // struct _cef_find_handler_t*(CEF_CALLBACK* get_find_handler)(
     // struct _cef_client_t* self);
 
-    // /// Return the handler for focus events.
-    // struct _cef_focus_handler_t*(CEF_CALLBACK* get_focus_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for focus events.
+    fn get_focus_handler(&mut self) -> Option<FocusHandler>;
 
     // /// Return the handler for events related to cef_frame_t lifespan. This
     // /// function will be called once during cef_browser_t creation and the result
@@ -61,14 +60,12 @@ pub trait ClientCallbacks: Send + Sync + 'static {
     // struct _cef_frame_handler_t*(CEF_CALLBACK* get_frame_handler)(
     // struct _cef_client_t* self);
 
-    // /// Return the handler for permission requests.
-    // struct _cef_permission_handler_t*(CEF_CALLBACK* get_permission_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for permission requests.
+    fn get_permission_handler(&mut self) -> Option<PermissionHandler>;
 
-    // /// Return the handler for JavaScript dialogs. If no handler is provided the
-    // /// default implementation will be used.
-    // struct _cef_jsdialog_handler_t*(CEF_CALLBACK* get_jsdialog_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for JavaScript dialogs. If no handler is provided the
+    /// default implementation will be used.
+    fn get_jsdialog_handler(&mut self) -> Option<JsDialogHandler>;
 
     /// Return the handler for keyboard events.
     fn get_keyboard_handler(&mut self) -> Option<KeyboardHandler>;
@@ -76,31 +73,29 @@ pub trait ClientCallbacks: Send + Sync + 'static {
     /// Return the handler for browser life span events.
     fn get_life_span_handler(&mut self) -> Option<LifeSpanHandler>;
 
-    // /// Return the handler for browser load status events.
-    // struct _cef_load_handler_t*(CEF_CALLBACK* get_load_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for browser load status events.
+    fn get_load_handler(&mut self) -> Option<LoadHandler>;
 
-    // /// Return the handler for printing on Linux. If a print handler is not
-    // /// provided then printing will not be supported on the Linux platform.
-    // struct _cef_print_handler_t*(CEF_CALLBACK* get_print_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for printing on Linux. If a print handler is not
+    /// provided then printing will not be supported on the Linux platform.
+    fn get_print_handler(&mut self) -> Option<PrintHandler>;
 
     /// Return the handler for off-screen rendering events.
     fn get_render_handler(&mut self) -> Option<RenderHandler>;
 
-    // /// Return the handler for browser request events.
-    // struct _cef_request_handler_t*(CEF_CALLBACK* get_request_handler)(
-    // struct _cef_client_t* self);
+    /// Return the handler for browser request events.
+    fn get_request_handler(&mut self) -> Option<RequestHandler>;
 
-    // /// Called when a new message is received from a different process. Return
-    // /// true (1) if the message was handled or false (0) otherwise.  It is safe to
-    // /// keep a reference to |message| outside of this callback.
-    // int(CEF_CALLBACK* on_process_message_received)(
-    // struct _cef_client_t* self,
-    // struct _cef_browser_t* browser,
-    // struct _cef_frame_t* frame,
-    // cef_process_id_t source_process,
-    // struct _cef_process_message_t* message);
+    /// Called when a new message is received from a different process. Return
+    /// true (1) if the message was handled or false (0) otherwise.  It is safe to
+    /// keep a reference to |message| outside of this callback.
+    fn on_process_message_received(
+        &mut self,
+        browser: Browser,
+        frame: Frame,
+        source_process: ProcessId,
+        message: ProcessMessage
+    ) -> bool;
 }
 
 // Implement this structure to provide handler implementations.
@@ -126,7 +121,12 @@ impl ClientWrapper {
 
     /// Return the handler for audio rendering events.
     unsafe extern "C" fn c_get_audio_handler(this: *mut cef_client_t) -> *mut cef_audio_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_audio_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for commands. If no handler is provided the default
@@ -162,7 +162,12 @@ impl ClientWrapper {
     unsafe extern "C" fn c_get_display_handler(
         this: *mut cef_client_t
     ) -> *mut cef_display_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_display_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for download events. If no handler is returned
@@ -185,7 +190,12 @@ impl ClientWrapper {
 
     /// Return the handler for focus events.
     unsafe extern "C" fn c_get_focus_handler(this: *mut cef_client_t) -> *mut cef_focus_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_focus_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for events related to cef_frame_t lifespan. This
@@ -199,7 +209,12 @@ impl ClientWrapper {
     unsafe extern "C" fn c_get_permission_handler(
         this: *mut cef_client_t
     ) -> *mut cef_permission_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_permission_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for JavaScript dialogs. If no handler is provided the
@@ -207,7 +222,12 @@ impl ClientWrapper {
     unsafe extern "C" fn c_get_jsdialog_handler(
         this: *mut cef_client_t
     ) -> *mut cef_jsdialog_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_jsdialog_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for keyboard events.
@@ -236,13 +256,23 @@ impl ClientWrapper {
 
     /// Return the handler for browser load status events.
     unsafe extern "C" fn c_get_load_handler(this: *mut cef_client_t) -> *mut cef_load_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_load_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for printing on Linux. If a print handler is not
     /// provided then printing will not be supported on the Linux platform.
     unsafe extern "C" fn c_get_print_handler(this: *mut cef_client_t) -> *mut cef_print_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_print_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Return the handler for off-screen rendering events.
@@ -261,7 +291,12 @@ impl ClientWrapper {
     unsafe extern "C" fn c_get_request_handler(
         this: *mut cef_client_t
     ) -> *mut cef_request_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0
+            .get_request_handler()
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Called when a new message is received from a different process. Return
@@ -274,7 +309,14 @@ impl ClientWrapper {
         source_process: cef_process_id_t,
         message: *mut cef_process_message_t
     ) -> c_int {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let frame = Frame::from_ptr_unchecked(frame);
+        let message = ProcessMessage::from_ptr_unchecked(message);
+
+        this.0
+            .on_process_message_received(browser, frame, source_process.into(), message)
+            as c_int
     }
 }
 
@@ -288,25 +330,25 @@ impl Wrappable for ClientWrapper {
                 base: unsafe { zeroed() },
 
                 // TODO: Fix this!
-                get_audio_handler:           None,
+                get_audio_handler:           Some(Self::c_get_audio_handler),
                 get_command_handler:         None,
                 get_context_menu_handler:    Some(Self::c_get_context_menu_handler),
                 get_dialog_handler:          None,
-                get_display_handler:         None,
+                get_display_handler:         Some(Self::c_get_display_handler),
                 get_download_handler:        None,
                 get_drag_handler:            None,
                 get_find_handler:            None,
-                get_focus_handler:           None,
+                get_focus_handler:           Some(Self::c_get_focus_handler),
                 get_frame_handler:           None,
-                get_permission_handler:      None,
-                get_jsdialog_handler:        None,
+                get_permission_handler:      Some(Self::c_get_permission_handler),
+                get_jsdialog_handler:        Some(Self::c_get_jsdialog_handler),
                 get_keyboard_handler:        Some(Self::c_get_keyboard_handler),
                 get_life_span_handler:       Some(Self::c_get_life_span_handler),
-                get_load_handler:            None,
-                get_print_handler:           None,
+                get_load_handler:            Some(Self::c_get_load_handler),
+                get_print_handler:           Some(Self::c_get_print_handler),
                 get_render_handler:          Some(Self::c_get_render_handler),
-                get_request_handler:         None,
-                on_process_message_received: None
+                get_request_handler:         Some(Self::c_get_request_handler),
+                on_process_message_received: Some(Self::c_process_message_received)
             },
             self
         )
@@ -0,0 +1,39 @@
+use crate::{ref_counted_ptr, try_c, CompletionCallback};
+use anyhow::Result;
+use cef_ui_sys::{cef_cookie_manager_get_global_manager, cef_cookie_manager_t};
+use std::ptr::null_mut;
+
+// Structure used for managing cookies. The functions of this structure may be
+// called on any thread unless otherwise indicated.
+ref_counted_ptr!(CookieManager, cef_cookie_manager_t);
+
+impl CookieManager {
+    /// Returns the global cookie manager. By default data will be stored at
+    /// cef_settings_t.cache_path if specified or in memory otherwise. If
+    /// |callback| is non-NULL it will be executed asnychronously on the UI thread
+    /// after the manager's storage has been initialized. Using this function is
+    /// equivalent to calling
+    /// cef_request_context_t::cef_request_context_get_global_context()->GetDefaultCookieManager().
+    pub fn get_global_manager(callback: Option<CompletionCallback>) -> Option<Self> {
+        unsafe {
+            let callback = callback
+                .map(|callback| callback.into_raw())
+                .unwrap_or_else(null_mut);
+
+            Self::from_ptr(cef_cookie_manager_get_global_manager(callback))
+        }
+    }
+
+    /// Flush the backing store (if any) to disk. If |callback| is non-NULL it
+    /// will be executed asnychronously on the UI thread after the flush is
+    /// complete. Returns false (0) if cookies cannot be accessed.
+    pub fn flush_store(&self, callback: Option<CompletionCallback>) -> Result<bool> {
+        try_c!(self, flush_store, {
+            let callback = callback
+                .map(|callback| callback.into_raw())
+                .unwrap_or_else(null_mut);
+
+            Ok(flush_store(self.as_ptr(), callback) != 0)
+        })
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{ref_counted_ptr, Browser, RefCountedPtr, Wrappable, Wrapped};
+use cef_ui_sys::{cef_browser_t, cef_focus_handler_t, cef_focus_source_t};
+use std::{ffi::c_int, mem::zeroed};
+
+/// Focus sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FocusSource {
+    /// The source is explicit navigation via the API (LoadURL(), etc).
+    Navigation,
+
+    /// The source is a system-generated focus event.
+    System
+}
+
+impl From<cef_focus_source_t> for FocusSource {
+    fn from(value: cef_focus_source_t) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&cef_focus_source_t> for FocusSource {
+    fn from(value: &cef_focus_source_t) -> Self {
+        match value {
+            cef_focus_source_t::FOCUS_SOURCE_NAVIGATION => Self::Navigation,
+            cef_focus_source_t::FOCUS_SOURCE_SYSTEM => Self::System
+        }
+    }
+}
+
+impl From<FocusSource> for cef_focus_source_t {
+    fn from(value: FocusSource) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&FocusSource> for cef_focus_source_t {
+    fn from(value: &FocusSource) -> Self {
+        match value {
+            FocusSource::Navigation => Self::FOCUS_SOURCE_NAVIGATION,
+            FocusSource::System => Self::FOCUS_SOURCE_SYSTEM
+        }
+    }
+}
+
+/// Implement this structure to handle events related to focus. The functions
+/// of this structure will be called on the UI thread.
+pub trait FocusHandlerCallbacks: Send + Sync + 'static {
+    /// Called when the browser component is about to loose focus. For instance,
+    /// if focus was on the last HTML element and the user pressed the TAB key.
+    /// |next| will be true (1) if the browser is giving focus to the next
+    /// component and false (0) if the browser is giving focus to the previous
+    /// component.
+    fn on_take_focus(&mut self, browser: Browser, next: bool);
+
+    /// Called when the browser component is requesting focus. |source|
+    /// indicates where the focus request is originating from. Return false (0)
+    /// to allow the focus to be set or true (1) to cancel setting the focus.
+    fn on_set_focus(&mut self, browser: Browser, source: FocusSource) -> bool;
+
+    /// Called when the browser component has received focus.
+    fn on_got_focus(&mut self, browser: Browser);
+}
+
+// Implement this structure to handle events related to focus. The functions
+// of this structure will be called on the UI thread.
+ref_counted_ptr!(FocusHandler, cef_focus_handler_t);
+
+impl FocusHandler {
+    pub fn new<C: FocusHandlerCallbacks>(delegate: C) -> Self {
+        Self(FocusHandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct FocusHandlerWrapper(Box<dyn FocusHandlerCallbacks>);
+
+impl FocusHandlerWrapper {
+    pub fn new<C: FocusHandlerCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Called when the browser component is about to loose focus.
+    unsafe extern "C" fn c_on_take_focus(
+        this: *mut cef_focus_handler_t,
+        browser: *mut cef_browser_t,
+        next: c_int
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_take_focus(browser, next != 0);
+    }
+
+    /// Called when the browser component is requesting focus.
+    unsafe extern "C" fn c_on_set_focus(
+        this: *mut cef_focus_handler_t,
+        browser: *mut cef_browser_t,
+        source: cef_focus_source_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_set_focus(browser, source.into()) as c_int
+    }
+
+    /// Called when the browser component has received focus.
+    unsafe extern "C" fn c_on_got_focus(
+        this: *mut cef_focus_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_got_focus(browser);
+    }
+}
+
+impl Wrappable for FocusHandlerWrapper {
+    type Cef = cef_focus_handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_focus_handler_t> {
+        RefCountedPtr::wrap(
+            cef_focus_handler_t {
+                base:          unsafe { zeroed() },
+                on_take_focus: Some(Self::c_on_take_focus),
+                on_set_focus:  Some(Self::c_on_set_focus),
+                on_got_focus:  Some(Self::c_on_got_focus)
+            },
+            self
+        )
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{ref_counted_ptr, try_c, Browser, CefString, RefCountedPtr, Wrappable, Wrapped};
+use anyhow::Result;
+use cef_ui_sys::{
+    cef_browser_t, cef_jsdialog_callback_t, cef_jsdialog_handler_t, cef_jsdialog_type_t,
+    cef_string_t
+};
+use std::{ffi::c_int, mem::zeroed, ptr::null};
+
+/// Supported JavaScript dialog types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JsDialogType {
+    Alert,
+    Confirm,
+    Prompt
+}
+
+impl From<cef_jsdialog_type_t> for JsDialogType {
+    fn from(value: cef_jsdialog_type_t) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&cef_jsdialog_type_t> for JsDialogType {
+    fn from(value: &cef_jsdialog_type_t) -> Self {
+        match value {
+            cef_jsdialog_type_t::JSDIALOGTYPE_ALERT => Self::Alert,
+            cef_jsdialog_type_t::JSDIALOGTYPE_CONFIRM => Self::Confirm,
+            cef_jsdialog_type_t::JSDIALOGTYPE_PROMPT => Self::Prompt
+        }
+    }
+}
+
+impl From<JsDialogType> for cef_jsdialog_type_t {
+    fn from(value: JsDialogType) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&JsDialogType> for cef_jsdialog_type_t {
+    fn from(value: &JsDialogType) -> Self {
+        match value {
+            JsDialogType::Alert => Self::JSDIALOGTYPE_ALERT,
+            JsDialogType::Confirm => Self::JSDIALOGTYPE_CONFIRM,
+            JsDialogType::Prompt => Self::JSDIALOGTYPE_PROMPT
+        }
+    }
+}
+
+// Callback structure used for asynchronous continuation of JavaScript dialog
+// requests.
+ref_counted_ptr!(JsDialogCallback, cef_jsdialog_callback_t);
+
+impl JsDialogCallback {
+    /// Continue the JS dialog request. Set |success| to true (1) if the OK button
+    /// was pressed. The |user_input| value should be specified for prompt
+    /// dialogs.
+    pub fn cont(&self, success: bool, user_input: Option<&str>) -> Result<()> {
+        try_c!(self, cont, {
+            let user_input = user_input.map(CefString::new);
+            let user_input = user_input
+                .as_ref()
+                .map_or(null(), |user_input| user_input.as_ptr());
+
+            Ok(cont(self.as_ptr(), success as c_int, user_input))
+        })
+    }
+}
+
+/// Implement this structure to handle events related to JavaScript dialogs. The
+/// functions of this structure will be called on the UI thread.
+pub trait JsDialogHandlerCallbacks: Send + Sync + 'static {
+    /// Called to run a JavaScript dialog. If |origin_url| is non-NULL it can be
+    /// passed to the CefFormatUrlForSecurityDisplay function to retrieve a secure
+    /// and user-friendly display string. The |default_prompt_text| value will be
+    /// specified for prompt dialogs only. Set |suppress_message| to true (1) and
+    /// return false (0) to suppress the message (suppressing messages is
+    /// preferable to immediately executing the callback as this is used to detect
+    /// presumably malicious behavior like spamming alert messages in
+    /// onbeforeunload). Set |suppress_message| to false (0) and return false (0)
+    /// to use the default implementation (the default implementation will show
+    /// one modal dialog at a time and suppress any additional dialog requests
+    /// until the displayed dialog is dismissed). Return true (1) if the
+    /// application will use a custom dialog or if the callback has been executed
+    /// immediately. Custom dialogs may be either modal or modeless. If a custom
+    /// dialog is used the application must execute |callback| once the custom
+    /// dialog is dismissed.
+    fn on_jsdialog(
+        &mut self,
+        browser: Browser,
+        origin_url: Option<String>,
+        dialog_type: JsDialogType,
+        message_text: Option<String>,
+        default_prompt_text: Option<String>,
+        callback: JsDialogCallback,
+        suppress_message: &mut bool
+    ) -> bool;
+
+    /// Called to run a dialog asking the user if they want to leave a page.
+    /// Return false (0) to use the default dialog implementation. Return true (1)
+    /// if the application will use a custom dialog or if the callback has been
+    /// executed immediately. Custom dialogs may be either modal or modeless. If a
+    /// custom dialog is used the application must execute |callback| once the
+    /// custom dialog is dismissed.
+    fn on_before_unload_dialog(
+        &mut self,
+        browser: Browser,
+        message_text: Option<String>,
+        is_reload: bool,
+        callback: JsDialogCallback
+    ) -> bool;
+
+    /// Called to cancel any pending dialogs and reset any saved dialog state.
+    /// Will be called due to events like page navigation irregardless of whether
+    /// any dialogs are currently pending.
+    fn on_reset_dialog_state(&mut self, browser: Browser);
+
+    /// Called when the dialog is closed.
+    fn on_dialog_closed(&mut self, browser: Browser);
+}
+
+// Implement this structure to handle events related to JavaScript dialogs. The
+// functions of this structure will be called on the UI thread.
+ref_counted_ptr!(JsDialogHandler, cef_jsdialog_handler_t);
+
+impl JsDialogHandler {
+    pub fn new<C: JsDialogHandlerCallbacks>(delegate: C) -> Self {
+        Self(JsDialogHandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct JsDialogHandlerWrapper(Box<dyn JsDialogHandlerCallbacks>);
+
+impl JsDialogHandlerWrapper {
+    pub fn new<C: JsDialogHandlerCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Called to run a JavaScript dialog.
+    unsafe extern "C" fn c_on_jsdialog(
+        this: *mut cef_jsdialog_handler_t,
+        browser: *mut cef_browser_t,
+        origin_url: *const cef_string_t,
+        dialog_type: cef_jsdialog_type_t,
+        message_text: *const cef_string_t,
+        default_prompt_text: *const cef_string_t,
+        callback: *mut cef_jsdialog_callback_t,
+        suppress_message: *mut c_int
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let origin_url: Option<String> = CefString::from_ptr(origin_url).map(|s| s.into());
+        let dialog_type = dialog_type.into();
+        let message_text: Option<String> = CefString::from_ptr(message_text).map(|s| s.into());
+        let default_prompt_text: Option<String> =
+            CefString::from_ptr(default_prompt_text).map(|s| s.into());
+        let callback = JsDialogCallback::from_ptr_unchecked(callback);
+        let mut local_suppress_message = *suppress_message != 0;
+
+        let ret = this.0.on_jsdialog(
+            browser,
+            origin_url,
+            dialog_type,
+            message_text,
+            default_prompt_text,
+            callback,
+            &mut local_suppress_message
+        );
+
+        *suppress_message = local_suppress_message as c_int;
+
+        ret as c_int
+    }
+
+    /// Called to run a dialog asking the user if they want to leave a page.
+    unsafe extern "C" fn c_on_before_unload_dialog(
+        this: *mut cef_jsdialog_handler_t,
+        browser: *mut cef_browser_t,
+        message_text: *const cef_string_t,
+        is_reload: c_int,
+        callback: *mut cef_jsdialog_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let message_text: Option<String> = CefString::from_ptr(message_text).map(|s| s.into());
+        let callback = JsDialogCallback::from_ptr_unchecked(callback);
+
+        this.0
+            .on_before_unload_dialog(browser, message_text, is_reload != 0, callback)
+            as c_int
+    }
+
+    /// Called to cancel any pending dialogs and reset any saved dialog state.
+    unsafe extern "C" fn c_on_reset_dialog_state(
+        this: *mut cef_jsdialog_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_reset_dialog_state(browser);
+    }
+
+    /// Called when the dialog is closed.
+    unsafe extern "C" fn c_on_dialog_closed(
+        this: *mut cef_jsdialog_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_dialog_closed(browser);
+    }
+}
+
+impl Wrappable for JsDialogHandlerWrapper {
+    type Cef = cef_jsdialog_handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_jsdialog_handler_t> {
+        RefCountedPtr::wrap(
+            cef_jsdialog_handler_t {
+                base:                    unsafe { zeroed() },
+                on_jsdialog:             Some(Self::c_on_jsdialog),
+                on_before_unload_dialog: Some(Self::c_on_before_unload_dialog),
+                on_reset_dialog_state:   Some(Self::c_on_reset_dialog_state),
+                on_dialog_closed:        Some(Self::c_on_dialog_closed)
+            },
+            self
+        )
+    }
+}
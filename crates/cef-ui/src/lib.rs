@@ -1,5 +1,6 @@
 mod accessibility_handler;
 mod app;
+mod audio_handler;
 mod browser;
 mod browser_process_handler;
 mod callbacks;
@@ -8,26 +9,39 @@ mod color;
 mod command_line;
 mod context;
 mod context_menu_handler;
+mod cookie;
+mod dev_tools_message_observer;
+mod display_handler;
 mod drag;
 mod events;
 mod extension;
 mod extension_handler;
+mod focus_handler;
 mod frame;
 mod ime;
+mod jsdialog_handler;
 mod keyboard_handler;
 mod life_span_handler;
+mod load_handler;
 mod macros;
 mod navigation_entry;
+mod permission_handler;
 mod platform;
+mod print;
 mod process;
 mod refcounted;
+mod registration;
 mod render_handler;
+mod render_process_handler;
 mod request;
 mod request_context;
 mod request_context_handler;
 mod request_handler;
+mod resource_handler;
 mod resource_request_handler;
 mod response;
+mod response_filter;
+mod scheme;
 mod settings;
 mod shared_memory_region;
 mod ssl;
@@ -35,11 +49,13 @@ mod string;
 mod time;
 mod types;
 mod url_request;
+mod v8;
 mod values;
 mod x509_certificate;
 
 pub use accessibility_handler::*;
 pub use app::*;
+pub use audio_handler::*;
 pub use browser::*;
 pub use browser_process_handler::*;
 pub use callbacks::*;
@@ -48,26 +64,39 @@ pub use color::*;
 pub use command_line::*;
 pub use context::*;
 pub use context_menu_handler::*;
+pub use cookie::*;
+pub use dev_tools_message_observer::*;
+pub use display_handler::*;
 pub use drag::*;
 pub use events::*;
 pub use extension::*;
 pub use extension_handler::*;
+pub use focus_handler::*;
 pub use frame::*;
 pub use ime::*;
+pub use jsdialog_handler::*;
 pub use keyboard_handler::*;
 pub use life_span_handler::*;
+pub use load_handler::*;
 pub use macros::*;
 pub use navigation_entry::*;
+pub use permission_handler::*;
 pub use platform::*;
+pub use print::*;
 pub use process::*;
 pub use refcounted::*;
+pub use registration::*;
 pub use render_handler::*;
+pub use render_process_handler::*;
 pub use request::*;
 pub use request_context::*;
 pub use request_context_handler::*;
 pub use request_handler::*;
+pub use resource_handler::*;
 pub use resource_request_handler::*;
 pub use response::*;
+pub use response_filter::*;
+pub use scheme::*;
 pub use settings::*;
 pub use shared_memory_region::*;
 pub use ssl::*;
@@ -75,6 +104,7 @@ pub use string::*;
 pub use time::*;
 pub use types::*;
 pub use url_request::*;
+pub use v8::*;
 pub use values::*;
 pub use x509_certificate::*;
 
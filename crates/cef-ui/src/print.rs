@@ -0,0 +1,640 @@
+use crate::{
+    free_cef_string, ref_counted_ptr, try_c, Browser, CefString, Range, RefCountedPtr, Size,
+    Wrappable, Wrapped
+};
+use anyhow::Result;
+use cef_ui_sys::{
+    cef_browser_t, cef_pdf_print_callback_t, cef_pdf_print_margin_type_t,
+    cef_pdf_print_settings_t, cef_print_dialog_callback_t, cef_print_handler_t,
+    cef_print_job_callback_t, cef_print_settings_create, cef_print_settings_t, cef_range_t,
+    cef_size_t, cef_string_t
+};
+use std::{ffi::c_int, mem::zeroed};
+
+/// Margin type for PDF printing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PdfPrintMarginType {
+    /// Default margins of 1cm (~0.4 inches).
+    Default,
+
+    /// No margins.
+    None,
+
+    /// Custom margins using the |margin_*| values from cef_pdf_print_settings_t.
+    Custom
+}
+
+impl From<cef_pdf_print_margin_type_t> for PdfPrintMarginType {
+    fn from(value: cef_pdf_print_margin_type_t) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&cef_pdf_print_margin_type_t> for PdfPrintMarginType {
+    fn from(value: &cef_pdf_print_margin_type_t) -> Self {
+        match value {
+            cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_DEFAULT => Self::Default,
+            cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_NONE => Self::None,
+            cef_pdf_print_margin_type_t::PDF_PRINT_MARGIN_CUSTOM => Self::Custom
+        }
+    }
+}
+
+impl From<PdfPrintMarginType> for cef_pdf_print_margin_type_t {
+    fn from(value: PdfPrintMarginType) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&PdfPrintMarginType> for cef_pdf_print_margin_type_t {
+    fn from(value: &PdfPrintMarginType) -> Self {
+        match value {
+            PdfPrintMarginType::Default => Self::PDF_PRINT_MARGIN_DEFAULT,
+            PdfPrintMarginType::None => Self::PDF_PRINT_MARGIN_NONE,
+            PdfPrintMarginType::Custom => Self::PDF_PRINT_MARGIN_CUSTOM
+        }
+    }
+}
+
+/// Structure representing PDF print settings. These values match the
+/// parameters supported by the DevTools Page.printToPDF function. See
+/// https://chromedevtools.github.io/devtools-protocol/tot/Page/#method-printToPDF
+#[derive(Debug)]
+pub struct PdfPrintSettings(cef_pdf_print_settings_t);
+
+impl PdfPrintSettings {
+    pub fn new() -> Self {
+        Self(unsafe { zeroed() })
+    }
+
+    /// Set to true (1) for landscape mode or false (0) for portrait mode.
+    pub fn landscape(mut self, value: bool) -> Self {
+        self.0.landscape = value as c_int;
+        self
+    }
+
+    /// Set to true (1) to print background graphics.
+    pub fn print_background(mut self, value: bool) -> Self {
+        self.0.print_background = value as c_int;
+        self
+    }
+
+    /// The percentage to scale the PDF by before printing (e.g. .5 is 50%).
+    /// If this value is less than or equal to zero the default value of 1.0
+    /// will be used.
+    pub fn scale(mut self, value: f64) -> Self {
+        self.0.scale = value;
+        self
+    }
+
+    /// Output paper size in inches. If either of these values is less than or
+    /// equal to zero then the default paper size (letter, 8.5 x 11 inches) will
+    /// be used.
+    pub fn paper_size(mut self, width: f64, height: f64) -> Self {
+        self.0.paper_width = width;
+        self.0.paper_height = height;
+        self
+    }
+
+    /// Set to true (1) to prefer page size as defined by css. Defaults to false
+    /// (0), in which case the content will be scaled to fit the paper size.
+    pub fn prefer_css_page_size(mut self, value: bool) -> Self {
+        self.0.prefer_css_page_size = value as c_int;
+        self
+    }
+
+    /// Margin type.
+    pub fn margin_type(mut self, value: PdfPrintMarginType) -> Self {
+        self.0.margin_type = value.into();
+        self
+    }
+
+    /// Margins in inches. Only used if |margin_type| is set to
+    /// PDF_PRINT_MARGIN_CUSTOM.
+    pub fn margins(mut self, top: f64, right: f64, bottom: f64, left: f64) -> Self {
+        self.0.margin_top = top;
+        self.0.margin_right = right;
+        self.0.margin_bottom = bottom;
+        self.0.margin_left = left;
+        self
+    }
+
+    /// Paper ranges to print, one based, e.g., '1-5, 8, 11-13'. Pages are printed
+    /// in the document order, not in the order specified, and no more than once.
+    /// Defaults to empty string, which implies the entire document is printed.
+    /// The page numbers are quietly capped to actual page count of the document,
+    /// and ranges beyond the end of the document are ignored. If this results in
+    /// no pages to print, an error is reported. It is an error to specify a range
+    /// with start greater than end.
+    pub fn page_ranges(mut self, value: &str) -> Self {
+        Self::set_string(value, &mut self.0.page_ranges);
+        self
+    }
+
+    /// Set to true (1) to display the header and/or footer. Modify
+    /// |header_template| and/or |footer_template| to customize the display.
+    pub fn display_header_footer(mut self, value: bool) -> Self {
+        self.0.display_header_footer = value as c_int;
+        self
+    }
+
+    /// HTML template for the print header. Only displayed if
+    /// |display_header_footer| is true (1). Should be valid HTML markup with
+    /// the following classes used to inject printing values into them:
+    ///
+    /// - date: formatted print date
+    /// - title: document title
+    /// - url: document location
+    /// - pageNumber: current page number
+    /// - totalPages: total pages in the document
+    ///
+    /// For example, "<span class=title></span>" would generate a span containing
+    /// the title.
+    pub fn header_template(mut self, value: &str) -> Self {
+        Self::set_string(value, &mut self.0.header_template);
+        self
+    }
+
+    /// HTML template for the print footer. Only displayed if
+    /// |display_header_footer| is true (1). Uses the same format as
+    /// |header_template|.
+    pub fn footer_template(mut self, value: &str) -> Self {
+        Self::set_string(value, &mut self.0.footer_template);
+        self
+    }
+
+    /// Set to true (1) to generate tagged (accessible) PDF.
+    pub fn generate_tagged_pdf(mut self, value: bool) -> Self {
+        self.0.generate_tagged_pdf = value as c_int;
+        self
+    }
+
+    /// Set to true (1) to generate a document outline.
+    pub fn generate_document_outline(mut self, value: bool) -> Self {
+        self.0.generate_document_outline = value as c_int;
+        self
+    }
+
+    /// Converts to the raw cef type.
+    pub fn as_raw(&self) -> &cef_pdf_print_settings_t {
+        &self.0
+    }
+
+    /// Assigns a str to a cef_string_t, freeing the previous value.
+    fn set_string(s: &str, cef: &mut cef_string_t) {
+        free_cef_string(cef);
+
+        *cef = CefString::new(s).into_raw();
+    }
+}
+
+impl Drop for PdfPrintSettings {
+    fn drop(&mut self) {
+        free_cef_string(&mut self.0.page_ranges);
+        free_cef_string(&mut self.0.header_template);
+        free_cef_string(&mut self.0.footer_template);
+    }
+}
+
+/// Callback structure for cef_browser_host_t::PrintToPDF. The functions of
+/// this structure will be called on the browser process UI thread.
+pub trait PdfPrintCallbacks: Send + Sync + 'static {
+    /// Method that will be executed when the PDF printing has completed. |path|
+    /// is the output path. |ok| will be true (1) if the printing completed
+    /// successfully or false (0) otherwise.
+    fn on_pdf_print_finished(&mut self, path: &str, ok: bool);
+}
+
+// Callback structure for cef_browser_host_t::PrintToPDF. The functions of
+// this structure will be called on the browser process UI thread.
+ref_counted_ptr!(PdfPrintCallback, cef_pdf_print_callback_t);
+
+impl PdfPrintCallback {
+    pub fn new<C: PdfPrintCallbacks>(delegate: C) -> Self {
+        Self(PdfPrintCallbackWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct PdfPrintCallbackWrapper(Box<dyn PdfPrintCallbacks>);
+
+impl PdfPrintCallbackWrapper {
+    pub fn new<C: PdfPrintCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Method that will be executed when the PDF printing has completed.
+    unsafe extern "C" fn c_on_pdf_print_finished(
+        this: *mut cef_pdf_print_callback_t,
+        path: *const cef_string_t,
+        ok: c_int
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let path: String = CefString::from_ptr_unchecked(path).into();
+
+        this.0.on_pdf_print_finished(path.as_str(), ok != 0);
+    }
+}
+
+impl Wrappable for PdfPrintCallbackWrapper {
+    type Cef = cef_pdf_print_callback_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_pdf_print_callback_t> {
+        RefCountedPtr::wrap(
+            cef_pdf_print_callback_t {
+                base:                  unsafe { zeroed() },
+                on_pdf_print_finished: Some(Self::c_on_pdf_print_finished)
+            },
+            self
+        )
+    }
+}
+
+// Structure representing print settings.
+ref_counted_ptr!(PrintSettings, cef_print_settings_t);
+
+impl PrintSettings {
+    /// Create a new cef_print_settings_t object.
+    pub fn new() -> Self {
+        unsafe { Self::from_ptr_unchecked(cef_print_settings_create()) }
+    }
+
+    /// Returns true (1) if this object is valid. Do not call any other functions
+    /// if this function returns false (0).
+    pub fn is_valid(&self) -> Result<bool> {
+        try_c!(self, is_valid, { Ok(is_valid(self.as_ptr()) != 0) })
+    }
+
+    /// Returns true (1) if the values of this object are read-only. Some APIs may
+    /// expose read-only objects.
+    pub fn is_read_only(&self) -> Result<bool> {
+        try_c!(self, is_read_only, { Ok(is_read_only(self.as_ptr()) != 0) })
+    }
+
+    /// Set the page orientation.
+    pub fn set_orientation(&self, landscape: bool) -> Result<()> {
+        try_c!(self, set_orientation, {
+            Ok(set_orientation(self.as_ptr(), landscape as c_int))
+        })
+    }
+
+    /// Returns true (1) if the orientation is landscape.
+    pub fn is_landscape(&self) -> Result<bool> {
+        try_c!(self, is_landscape, { Ok(is_landscape(self.as_ptr()) != 0) })
+    }
+
+    // TODO: Fix this!
+
+    // ///
+    // /// Set the printer printable area in device units. Some platforms already
+    // /// provide flipped area. Set |landscape_needs_flip| to false (0) on those
+    // /// platforms to avoid double flipping.
+    // ///
+    // void(CEF_CALLBACK* set_printer_printable_area)(
+    // struct _cef_print_settings_t* self,
+    // const cef_size_t* physical_size_device_units,
+    // const cef_rect_t* printable_area_device_units,
+    // int landscape_needs_flip);
+
+    /// Set the device name.
+    pub fn set_device_name(&self, name: Option<&str>) -> Result<()> {
+        try_c!(self, set_device_name, {
+            let name = name.map(CefString::new);
+
+            Ok(set_device_name(
+                self.as_ptr(),
+                name.as_ref()
+                    .map(|name| name.as_ptr())
+                    .unwrap_or(std::ptr::null())
+            ))
+        })
+    }
+
+    /// Get the device name.
+    pub fn get_device_name(&self) -> Result<String> {
+        try_c!(self, get_device_name, {
+            let s = get_device_name(self.as_ptr());
+
+            Ok(CefString::from_userfree_ptr_unchecked(s).into())
+        })
+    }
+
+    /// Set the DPI (dots per inch).
+    pub fn set_dpi(&self, dpi: i32) -> Result<()> {
+        try_c!(self, set_dpi, { Ok(set_dpi(self.as_ptr(), dpi as c_int)) })
+    }
+
+    /// Get the DPI (dots per inch).
+    pub fn get_dpi(&self) -> Result<i32> {
+        try_c!(self, get_dpi, { Ok(get_dpi(self.as_ptr()) as i32) })
+    }
+
+    /// Set the page ranges.
+    pub fn set_page_ranges(&self, ranges: &[Range]) -> Result<()> {
+        try_c!(self, set_page_ranges, {
+            let ranges: Vec<cef_range_t> = ranges
+                .iter()
+                .map(|range| range.into())
+                .collect();
+
+            Ok(set_page_ranges(
+                self.as_ptr(),
+                ranges.len(),
+                ranges.as_ptr()
+            ))
+        })
+    }
+
+    /// Returns the number of page ranges that currently exist.
+    pub fn get_page_ranges_count(&self) -> Result<usize> {
+        try_c!(self, get_page_ranges_count, {
+            Ok(get_page_ranges_count(self.as_ptr()))
+        })
+    }
+
+    /// Retrieve the page ranges.
+    pub fn get_page_ranges(&self) -> Result<Vec<Range>> {
+        try_c!(self, get_page_ranges, {
+            let mut count = self.get_page_ranges_count()?;
+            let mut ranges = vec![cef_range_t { from: 0, to: 0 }; count];
+
+            get_page_ranges(self.as_ptr(), &mut count, ranges.as_mut_ptr());
+
+            Ok(ranges
+                .iter()
+                .take(count)
+                .map(|range| range.into())
+                .collect())
+        })
+    }
+
+    /// Set whether only the selection will be printed.
+    pub fn set_selection_only(&self, selection_only: bool) -> Result<()> {
+        try_c!(self, set_selection_only, {
+            Ok(set_selection_only(
+                self.as_ptr(),
+                selection_only as c_int
+            ))
+        })
+    }
+
+    /// Returns true (1) if only the selection will be printed.
+    pub fn is_selection_only(&self) -> Result<bool> {
+        try_c!(self, is_selection_only, {
+            Ok(is_selection_only(self.as_ptr()) != 0)
+        })
+    }
+
+    /// Set whether pages will be collated.
+    pub fn set_collate(&self, collate: bool) -> Result<()> {
+        try_c!(self, set_collate, {
+            Ok(set_collate(self.as_ptr(), collate as c_int))
+        })
+    }
+
+    /// Returns true (1) if pages will be collated.
+    pub fn will_collate(&self) -> Result<bool> {
+        try_c!(self, will_collate, { Ok(will_collate(self.as_ptr()) != 0) })
+    }
+
+    // TODO: Fix this!
+
+    // ///
+    // /// Set the color model.
+    // ///
+    // void(CEF_CALLBACK* set_color_model)(struct _cef_print_settings_t* self,
+    // cef_color_model_t model);
+
+    // ///
+    // /// Get the color model.
+    // ///
+    // cef_color_model_t(CEF_CALLBACK* get_color_model)(
+    // struct _cef_print_settings_t* self);
+
+    /// Set the number of copies.
+    pub fn set_copies(&self, copies: i32) -> Result<()> {
+        try_c!(self, set_copies, {
+            Ok(set_copies(self.as_ptr(), copies as c_int))
+        })
+    }
+
+    /// Get the number of copies.
+    pub fn get_copies(&self) -> Result<i32> {
+        try_c!(self, get_copies, { Ok(get_copies(self.as_ptr()) as i32) })
+    }
+
+    // TODO: Fix this!
+
+    // ///
+    // /// Set the duplex mode.
+    // ///
+    // void(CEF_CALLBACK* set_duplex_mode)(struct _cef_print_settings_t* self,
+    // cef_duplex_mode_t mode);
+
+    // ///
+    // /// Get the duplex mode.
+    // ///
+    // cef_duplex_mode_t(CEF_CALLBACK* get_duplex_mode)(
+    // struct _cef_print_settings_t* self);
+}
+
+// Callback structure for asynchronous continuation of print dialog requests.
+ref_counted_ptr!(PrintDialogCallback, cef_print_dialog_callback_t);
+
+impl PrintDialogCallback {
+    /// Continue printing with the specified |settings|.
+    pub fn cont(&self, settings: PrintSettings) -> Result<()> {
+        try_c!(self, cont, {
+            Ok(cont(self.as_ptr(), settings.into_raw()))
+        })
+    }
+
+    /// Cancel the printing.
+    pub fn cancel(&self) -> Result<()> {
+        try_c!(self, cancel, { Ok(cancel(self.as_ptr())) })
+    }
+}
+
+// Callback structure for asynchronous continuation of print job requests.
+ref_counted_ptr!(PrintJobCallback, cef_print_job_callback_t);
+
+impl PrintJobCallback {
+    /// Indicate completion of the print job.
+    pub fn cont(&self) -> Result<()> {
+        try_c!(self, cont, { Ok(cont(self.as_ptr())) })
+    }
+}
+
+/// Implement this structure to handle printing on Linux. Each browser will
+/// have only one print job in progress at a time. The functions of this
+/// structure will be called on the browser process UI thread.
+pub trait PrintHandlerCallbacks: Send + Sync + 'static {
+    /// Called when printing has started for the specified |browser|. This
+    /// function will be called before the other OnPrint*() functions and
+    /// irrespective of how printing was initiated (e.g.
+    /// cef_browser_host_t::print(), JavaScript window.print() or PDF extension
+    /// print button).
+    fn on_print_start(&mut self, browser: Browser);
+
+    /// Synchronize |settings| with client state. If |get_defaults| is true (1)
+    /// then populate |settings| with the default print settings. Do not keep a
+    /// reference to |settings| outside of this callback.
+    fn on_print_settings(&mut self, browser: Browser, settings: PrintSettings, get_defaults: bool);
+
+    /// Show the print dialog. Execute |callback| once the dialog is dismissed.
+    /// Return true (1) if the dialog will be displayed or false (0) to cancel
+    /// the printing immediately.
+    fn on_print_dialog(
+        &mut self,
+        browser: Browser,
+        has_selection: bool,
+        callback: PrintDialogCallback
+    ) -> bool;
+
+    /// Send the print job to the printer. Execute |callback| once the job is
+    /// completed. Return true (1) if the job will proceed or false (0) to cancel
+    /// the job immediately.
+    fn on_print_job(
+        &mut self,
+        browser: Browser,
+        document_name: &str,
+        pdf_file_path: &str,
+        callback: PrintJobCallback
+    ) -> bool;
+
+    /// Reset client state related to printing.
+    fn on_print_reset(&mut self, browser: Browser);
+
+    /// Return the PDF paper size in device units. Used in combination with
+    /// cef_browser_host_t::print_to_pdf().
+    fn get_pdf_paper_size(&mut self, browser: Browser, device_units_per_inch: i32) -> Size;
+}
+
+// Implement this structure to handle printing on Linux. Each browser will
+// have only one print job in progress at a time. The functions of this
+// structure will be called on the browser process UI thread.
+ref_counted_ptr!(PrintHandler, cef_print_handler_t);
+
+impl PrintHandler {
+    pub fn new<C: PrintHandlerCallbacks>(delegate: C) -> Self {
+        Self(PrintHandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct PrintHandlerWrapper(Box<dyn PrintHandlerCallbacks>);
+
+impl PrintHandlerWrapper {
+    pub fn new<C: PrintHandlerCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Called when printing has started for the specified |browser|.
+    unsafe extern "C" fn c_on_print_start(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_print_start(browser);
+    }
+
+    /// Synchronize |settings| with client state.
+    unsafe extern "C" fn c_on_print_settings(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t,
+        settings: *mut cef_print_settings_t,
+        get_defaults: c_int
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let settings = PrintSettings::from_ptr_unchecked(settings);
+
+        this.0
+            .on_print_settings(browser, settings, get_defaults != 0);
+    }
+
+    /// Show the print dialog.
+    unsafe extern "C" fn c_on_print_dialog(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t,
+        has_selection: c_int,
+        callback: *mut cef_print_dialog_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let callback = PrintDialogCallback::from_ptr_unchecked(callback);
+
+        this.0
+            .on_print_dialog(browser, has_selection != 0, callback) as c_int
+    }
+
+    /// Send the print job to the printer.
+    unsafe extern "C" fn c_on_print_job(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t,
+        document_name: *const cef_string_t,
+        pdf_file_path: *const cef_string_t,
+        callback: *mut cef_print_job_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+        let document_name: String = CefString::from_ptr_unchecked(document_name).into();
+        let pdf_file_path: String = CefString::from_ptr_unchecked(pdf_file_path).into();
+        let callback = PrintJobCallback::from_ptr_unchecked(callback);
+
+        this.0.on_print_job(
+            browser,
+            document_name.as_str(),
+            pdf_file_path.as_str(),
+            callback
+        ) as c_int
+    }
+
+    /// Reset client state related to printing.
+    unsafe extern "C" fn c_on_print_reset(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0.on_print_reset(browser);
+    }
+
+    /// Return the PDF paper size in device units.
+    unsafe extern "C" fn c_get_pdf_paper_size(
+        this: *mut cef_print_handler_t,
+        browser: *mut cef_browser_t,
+        device_units_per_inch: c_int
+    ) -> cef_size_t {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr_unchecked(browser);
+
+        this.0
+            .get_pdf_paper_size(browser, device_units_per_inch as i32)
+            .into()
+    }
+}
+
+impl Wrappable for PrintHandlerWrapper {
+    type Cef = cef_print_handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_print_handler_t> {
+        RefCountedPtr::wrap(
+            cef_print_handler_t {
+                base:               unsafe { zeroed() },
+                on_print_start:     Some(Self::c_on_print_start),
+                on_print_settings:  Some(Self::c_on_print_settings),
+                on_print_dialog:    Some(Self::c_on_print_dialog),
+                on_print_job:       Some(Self::c_on_print_job),
+                on_print_reset:     Some(Self::c_on_print_reset),
+                get_pdf_paper_size: Some(Self::c_get_pdf_paper_size)
+            },
+            self
+        )
+    }
+}
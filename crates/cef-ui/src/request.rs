@@ -283,9 +283,9 @@ impl Request {
     }
 
     /// Get the post data.
-    pub fn get_post_data(&self) -> Result<PostData> {
+    pub fn get_post_data(&self) -> Result<Option<PostData>> {
         try_c!(self, get_post_data, {
-            Ok(PostData::from_ptr_unchecked(get_post_data(self.as_ptr())))
+            Ok(PostData::from_ptr(get_post_data(self.as_ptr())))
         })
     }
 
@@ -1,13 +1,127 @@
 use crate::{
-    ref_counted_ptr, try_c, CefString, CefStringList, CompletionCallback, ErrorCode, RefCountedPtr,
-    RequestContextHandler, Wrappable, Wrapped
+    free_cef_string, ref_counted_ptr, try_c, CefString, CefStringList, CompletionCallback,
+    DictionaryValue, ErrorCode, Extension, ExtensionHandler, RefCountedPtr, RequestContextHandler,
+    Wrappable, Wrapped
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use cef_ui_sys::{
-    cef_errorcode_t, cef_request_context_t, cef_resolve_callback_t, cef_string_list_t
+    cef_errorcode_t, cef_request_context_create_context, cef_request_context_get_global_context,
+    cef_request_context_settings_t, cef_request_context_t, cef_resolve_callback_t, cef_string_t,
+    cef_string_list_t
 };
+use dunce::canonicalize;
 use parking_lot::Mutex;
-use std::mem::zeroed;
+use std::{
+    ffi::c_int,
+    mem::{size_of, zeroed},
+    path::PathBuf,
+    ptr::null_mut
+};
+
+/// Request context initialization settings. Specify NULL or 0 to get the
+/// recommended default values.
+#[derive(Debug)]
+pub struct RequestContextSettings(cef_request_context_settings_t);
+
+impl RequestContextSettings {
+    pub fn new() -> Self {
+        let mut cef: cef_request_context_settings_t = unsafe { zeroed() };
+
+        cef.size = size_of::<cef_request_context_settings_t>();
+
+        Self(cef)
+    }
+
+    /// The directory where cache data for this request context will be stored on
+    /// disk. If this value is non-empty then it must be an absolute path that is
+    /// either equal to or a child directory of CefSettings.root_cache_path. If
+    /// this value is empty then browsers will be created in "incognito mode"
+    /// where in-memory caches are used for storage and no profile-specific data
+    /// is persisted to disk (installation-specific data will still be persisted
+    /// in root_cache_path). HTML5 databases such as localStorage will only
+    /// persist across sessions if a cache path is specified. To share the global
+    /// browser cache and related configuration set this value to match the
+    /// CefSettings.cache_path value.
+    pub fn cache_path(mut self, path: &PathBuf) -> Result<Self> {
+        let path = canonicalize(path)?;
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("Failed to convert path to utf8."))?;
+
+        self.0.cache_path = CefString::new(path).into_raw();
+
+        Ok(self)
+    }
+
+    /// To persist session cookies (cookies without an expiry date or validity
+    /// interval) by default when using the global cookie manager set this value
+    /// to true (1). Session cookies are generally intended to be transient and
+    /// most Web browsers do not persist them. Can be set globally using the
+    /// CefSettings.persist_session_cookies value. This value will be ignored if
+    /// |cache_path| is empty or if it matches the CefSettings.cache_path value.
+    pub fn persist_session_cookies(mut self, value: bool) -> Self {
+        self.0.persist_session_cookies = value as c_int;
+        self
+    }
+
+    /// To persist user preferences as a JSON file in the cache path directory set
+    /// this value to true (1). Can be set globally using the
+    /// CefSettings.persist_user_preferences value. This value will be ignored if
+    /// |cache_path| is empty or if it matches the CefSettings.cache_path value.
+    pub fn persist_user_preferences(mut self, value: bool) -> Self {
+        self.0.persist_user_preferences = value as c_int;
+        self
+    }
+
+    /// Comma delimited ordered list of language codes without any whitespace that
+    /// will be used in the "Accept-Language" HTTP request header and
+    /// "navigator.language" JS attribute. Can be set globally using the
+    /// CefSettings.accept_language_list value. If all values are empty then
+    /// "en-US,en" will be used. This value will be ignored if |cache_path|
+    /// matches the CefSettings.cache_path value.
+    pub fn accept_language_list(mut self, value: &String) -> Self {
+        Self::set_string(value, &mut self.0.accept_language_list);
+
+        self
+    }
+
+    /// Comma delimited list of schemes supported by the associated
+    /// CefCookieManager. If |cookieable_schemes_exclude_defaults| is false (0)
+    /// the default schemes ("http", "https", "ws" and "wss") will also be
+    /// supported. Not specifying a |cookieable_schemes_list| value and setting
+    /// |cookieable_schemes_exclude_defaults| to true (1) will disable all loading
+    /// and saving of cookies. These values will be ignored if |cache_path|
+    /// matches the CefSettings.cache_path value.
+    pub fn cookieable_schemes_list(mut self, value: &String) -> Self {
+        Self::set_string(value, &mut self.0.cookieable_schemes_list);
+
+        self
+    }
+
+    pub fn cookieable_schemes_exclude_defaults(mut self, value: bool) -> Self {
+        self.0
+            .cookieable_schemes_exclude_defaults = value as c_int;
+        self
+    }
+
+    /// Converts to the raw cef type.
+    pub fn as_raw(&self) -> &cef_request_context_settings_t {
+        &self.0
+    }
+
+    /// Tries to assign a String to a cef_string_t.
+    fn set_string(s: &String, cef: &mut cef_string_t) {
+        *cef = CefString::new(s.as_str()).into_raw();
+    }
+}
+
+impl Drop for RequestContextSettings {
+    fn drop(&mut self) {
+        free_cef_string(&mut self.0.cache_path);
+        free_cef_string(&mut self.0.accept_language_list);
+        free_cef_string(&mut self.0.cookieable_schemes_list);
+    }
+}
 
 // Callback structure for cef_request_context_t::ResolveHost.
 // Called on the UI thread after the ResolveHost request has completed.
@@ -78,6 +192,29 @@ impl Wrappable for ResolveCallbackWrapper {
 ref_counted_ptr!(RequestContext, cef_request_context_t);
 
 impl RequestContext {
+    /// Returns the global context object.
+    pub fn get_global_context() -> Option<Self> {
+        unsafe { Self::from_ptr(cef_request_context_get_global_context()) }
+    }
+
+    /// Creates a new context object with the specified |settings| and optional
+    /// |handler|.
+    pub fn create_context(
+        settings: &RequestContextSettings,
+        handler: Option<RequestContextHandler>
+    ) -> Option<Self> {
+        unsafe {
+            let handler = handler
+                .map(|handler| handler.into_raw())
+                .unwrap_or(null_mut());
+
+            Self::from_ptr(cef_request_context_create_context(
+                settings.as_raw(),
+                handler
+            ))
+        }
+    }
+
     /// Returns true (1) if this object is pointing to the same context as |that|
     /// object.
     pub fn is_same(&self, other: RequestContext) -> Result<bool> {
@@ -207,60 +344,75 @@ impl RequestContext {
         })
     }
 
-    // TODO: Fix this!
-
-    //     ///
-    //     /// Load an extension.
-    //     ///
-    //     /// If extension resources will be read from disk using the default load
-    //     /// implementation then |root_directory| should be the absolute path to the
-    //     /// extension resources directory and |manifest| should be NULL. If extension
-    //     /// resources will be provided by the client (e.g. via cef_request_handler_t
-    //     /// and/or cef_extension_handler_t) then |root_directory| should be a path
-    //     /// component unique to the extension (if not absolute this will be internally
-    //     /// prefixed with the PK_DIR_RESOURCES path) and |manifest| should contain the
-    //     /// contents that would otherwise be read from the "manifest.json" file on
-    //     /// disk.
-    //     ///
-    //     /// The loaded extension will be accessible in all contexts sharing the same
-    //     /// storage (HasExtension returns true (1)). However, only the context on
-    //     /// which this function was called is considered the loader (DidLoadExtension
-    //     /// returns true (1)) and only the loader will receive
-    //     /// cef_request_context_handler_t callbacks for the extension.
-    //     ///
-    //     /// cef_extension_handler_t::OnExtensionLoaded will be called on load success
-    //     /// or cef_extension_handler_t::OnExtensionLoadFailed will be called on load
-    //     /// failure.
-    //     ///
-    //     /// If the extension specifies a background script via the "background"
-    //     /// manifest key then cef_extension_handler_t::OnBeforeBackgroundBrowser will
-    //     /// be called to create the background browser. See that function for
-    //     /// additional information about background scripts.
-    //     ///
-    //     /// For visible extension views the client application should evaluate the
-    //     /// manifest to determine the correct extension URL to load and then pass that
-    //     /// URL to the cef_browser_host_t::CreateBrowser* function after the extension
-    //     /// has loaded. For example, the client can look for the "browser_action"
-    //     /// manifest key as documented at
-    //     /// https://developer.chrome.com/extensions/browserAction. Extension URLs take
-    //     /// the form "chrome-extension://<extension_id>/<path>".
-    //     ///
-    //     /// Browsers that host extensions differ from normal browsers as follows:
-    //     ///  - Can access chrome.* JavaScript APIs if allowed by the manifest. Visit
-    //     ///    chrome://extensions-support for the list of extension APIs currently
-    //     ///    supported by CEF.
-    //     ///  - Main frame navigation to non-extension content is blocked.
-    //     ///  - Pinch-zooming is disabled.
-    //     ///  - CefBrowserHost::GetExtension returns the hosted extension.
-    //     ///  - CefBrowserHost::IsBackgroundHost returns true for background hosts.
-    //     ///
-    //     /// See https://developer.chrome.com/extensions for extension implementation
-    //     /// and usage documentation.
-    //     ///
-    //     void(CEF_CALLBACK* load_extension)(struct _cef_request_context_t* self,
-    //     const cef_string_t* root_directory,
-    //     struct _cef_dictionary_value_t* manifest,
-    //     struct _cef_extension_handler_t* handler);
+    /// Load an extension.
+    ///
+    /// If extension resources will be read from disk using the default load
+    /// implementation then |root_directory| should be the absolute path to the
+    /// extension resources directory and |manifest| should be NULL. If extension
+    /// resources will be provided by the client (e.g. via cef_request_handler_t
+    /// and/or cef_extension_handler_t) then |root_directory| should be a path
+    /// component unique to the extension (if not absolute this will be internally
+    /// prefixed with the PK_DIR_RESOURCES path) and |manifest| should contain the
+    /// contents that would otherwise be read from the "manifest.json" file on
+    /// disk.
+    ///
+    /// The loaded extension will be accessible in all contexts sharing the same
+    /// storage (HasExtension returns true (1)). However, only the context on
+    /// which this function was called is considered the loader (DidLoadExtension
+    /// returns true (1)) and only the loader will receive
+    /// cef_request_context_handler_t callbacks for the extension.
+    ///
+    /// cef_extension_handler_t::OnExtensionLoaded will be called on load success
+    /// or cef_extension_handler_t::OnExtensionLoadFailed will be called on load
+    /// failure.
+    ///
+    /// If the extension specifies a background script via the "background"
+    /// manifest key then cef_extension_handler_t::OnBeforeBackgroundBrowser will
+    /// be called to create the background browser. See that function for
+    /// additional information about background scripts.
+    ///
+    /// For visible extension views the client application should evaluate the
+    /// manifest to determine the correct extension URL to load and then pass that
+    /// URL to the cef_browser_host_t::CreateBrowser* function after the extension
+    /// has loaded. For example, the client can look for the "browser_action"
+    /// manifest key as documented at
+    /// https://developer.chrome.com/extensions/browserAction. Extension URLs take
+    /// the form "chrome-extension://<extension_id>/<path>".
+    ///
+    /// Browsers that host extensions differ from normal browsers as follows:
+    ///  - Can access chrome.* JavaScript APIs if allowed by the manifest. Visit
+    ///    chrome://extensions-support for the list of extension APIs currently
+    ///    supported by CEF.
+    ///  - Main frame navigation to non-extension content is blocked.
+    ///  - Pinch-zooming is disabled.
+    ///  - CefBrowserHost::GetExtension returns the hosted extension.
+    ///  - CefBrowserHost::IsBackgroundHost returns true for background hosts.
+    ///
+    /// See https://developer.chrome.com/extensions for extension implementation
+    /// and usage documentation.
+    pub fn load_extension(
+        &self,
+        root_directory: &str,
+        manifest: Option<DictionaryValue>,
+        handler: Option<ExtensionHandler>
+    ) -> Result<()> {
+        try_c!(self, load_extension, {
+            let root_directory = CefString::new(root_directory);
+            let manifest = manifest
+                .map(|manifest| manifest.into_raw())
+                .unwrap_or(null_mut());
+            let handler = handler
+                .map(|handler| handler.into_raw())
+                .unwrap_or(null_mut());
+
+            Ok(load_extension(
+                self.as_ptr(),
+                root_directory.as_ptr(),
+                manifest,
+                handler
+            ))
+        })
+    }
 
     /// Returns true (1) if this context was used to load the extension identified
     /// by |extension_id|. Other contexts sharing the same storage will also have
@@ -303,18 +455,22 @@ impl RequestContext {
         })
     }
 
+    /// Returns the extension matching |extension_id| or NULL if no matching
+    /// extension is accessible in this context (see HasExtension). This function
+    /// must be called on the browser process UI thread.
+    pub fn get_extension(&self, extension_id: &str) -> Result<Option<Extension>> {
+        try_c!(self, get_extension, {
+            let extension_id = CefString::new(extension_id);
+
+            Ok(Extension::from_ptr(get_extension(
+                self.as_ptr(),
+                extension_id.as_ptr()
+            )))
+        })
+    }
+
     // TODO: Fix this!
 
-    //
-    //     ///
-    //     /// Returns the extension matching |extension_id| or NULL if no matching
-    //     /// extension is accessible in this context (see HasExtension). This function
-    //     /// must be called on the browser process UI thread.
-    //     ///
-    //     struct _cef_extension_t*(CEF_CALLBACK* get_extension)(
-    //     struct _cef_request_context_t* self,
-    //     const cef_string_t* extension_id);
-    //
     //     ///
     //     /// Returns the MediaRouter object associated with this context.  If
     //     /// |callback| is non-NULL it will be executed asnychronously on the UI thread
@@ -0,0 +1,231 @@
+use crate::{
+    ref_counted_ptr, try_c, Callback, CefString, RefCountedPtr, Request, Response, Wrappable,
+    Wrapped
+};
+use anyhow::Result;
+use cef_ui_sys::{
+    cef_callback_t, cef_request_t, cef_resource_handler_t, cef_resource_read_callback_t,
+    cef_resource_skip_callback_t, cef_response_t, cef_string_t
+};
+use std::{
+    ffi::{c_int, c_void},
+    mem::zeroed,
+    slice::from_raw_parts_mut
+};
+
+// Callback for asynchronous continuation of cef_resource_handler_t::skip().
+ref_counted_ptr!(ResourceSkipCallback, cef_resource_skip_callback_t);
+
+impl ResourceSkipCallback {
+    /// Callback for asynchronous continuation of skip(). If |bytes_skipped| > 0
+    /// then either skip() will be called again until the requested number of
+    /// bytes have been skipped or the request will proceed. If |bytes_skipped| <=
+    /// 0 the request will fail with ERR_REQUEST_RANGE_NOT_SATISFIABLE.
+    pub fn cont(&self, bytes_skipped: i64) -> Result<()> {
+        try_c!(self, cont, { Ok(cont(self.as_ptr(), bytes_skipped)) })
+    }
+}
+
+// Callback for asynchronous continuation of cef_resource_handler_t::read().
+ref_counted_ptr!(ResourceReadCallback, cef_resource_read_callback_t);
+
+impl ResourceReadCallback {
+    /// Callback for asynchronous continuation of read(). If |bytes_read| == 0 the
+    /// response will be considered complete. If |bytes_read| > 0 then read() will
+    /// be called again until the request is complete (based on either the result
+    /// or the expected content length). If |bytes_read| < 0 then the request will
+    /// fail and the |bytes_read| value will be treated as the error code.
+    pub fn cont(&self, bytes_read: i32) -> Result<()> {
+        try_c!(self, cont, { Ok(cont(self.as_ptr(), bytes_read)) })
+    }
+}
+
+/// Structure used to implement a custom request handler structure. The
+/// functions of this structure will be called on the IO thread unless
+/// otherwise indicated.
+pub trait ResourceHandlerCallbacks: Send + Sync + 'static {
+    /// Open the response stream. To handle the request immediately set
+    /// |handle_request| to true (1) and return true (1). To decide at a later
+    /// time set |handle_request| to false (0), return true (1), and execute
+    /// |callback| to continue or cancel the request. To cancel the request
+    /// immediately set |handle_request| to true (1) and return false (0). This
+    /// function will be called in sequence but not from a dedicated thread. For
+    /// backwards compatibility set |handle_request| to false (0) and return false
+    /// (0) and the ProcessRequest function will be called.
+    fn open(&mut self, request: Request, handle_request: &mut bool, callback: Callback) -> bool;
+
+    /// Retrieve response header information. If the response length is not known
+    /// set |response_length| to -1 and read_response() will be called until it
+    /// returns false (0). If the response length is known set |response_length|
+    /// to a positive value and read_response() will be called until it returns
+    /// false (0) or the specified number of bytes have been read. Use the
+    /// |response| object to set the mime type, http status code and other
+    /// optional header values. To redirect the request to a new URL set
+    /// |redirect_url| to the new URL. |redirect_url| can be either a relative or
+    /// fully qualified URL. It is also possible to set |response| to a redirect
+    /// http status code and pass the new URL via a Location header. Likewise with
+    /// |redirect_url| it is valid to set a relative or fully qualified URL as the
+    /// Location header value. If an error occured while setting up the request
+    /// you can call set_error() on |response| to indicate the error condition.
+    fn get_response_headers(
+        &mut self,
+        response: Response,
+        response_length: &mut i64,
+        redirect_url: &mut Option<String>
+    );
+
+    /// Skip response data when requested by a Range header. Skip over and discard
+    /// |bytes_to_skip| bytes of response data. If data is available immediately
+    /// set |bytes_skipped| to the number of bytes skipped and return true (1). To
+    /// read the data at a later time set |bytes_skipped| to 0, return true (1)
+    /// and execute |callback| when the data is available. To indicate failure set
+    /// |bytes_skipped| to < 0 (e.g. -2 for ERR_FAILED) and return false (0). This
+    /// function will be called in sequence but not from a dedicated thread.
+    fn skip(
+        &mut self,
+        bytes_to_skip: i64,
+        bytes_skipped: &mut i64,
+        callback: ResourceSkipCallback
+    ) -> bool;
+
+    /// Read response data. If data is available immediately copy up to
+    /// |data_out|.len() bytes into |data_out|, set |bytes_read| to the number of
+    /// bytes copied, and return true (1). To read the data at a later time keep a
+    /// pointer to |data_out|, set |bytes_read| to 0, return true (1) and execute
+    /// |callback| when the data is available (|data_out| will remain valid until
+    /// the callback is executed). To indicate response completion set
+    /// |bytes_read| to 0 and return false (0). To indicate failure set
+    /// |bytes_read| to < 0 (e.g. -2 for ERR_FAILED) and return false (0). This
+    /// function will be called in sequence but not from a dedicated thread. For
+    /// backwards compatibility set |bytes_read| to -1 and return false (0) and
+    /// the ReadResponse function will be called.
+    fn read(
+        &mut self,
+        data_out: &mut [u8],
+        bytes_read: &mut i32,
+        callback: ResourceReadCallback
+    ) -> bool;
+
+    /// Request processing has been canceled.
+    fn cancel(&mut self);
+}
+
+// Structure used to implement a custom request handler structure. The
+// functions of this structure will be called on the IO thread unless
+// otherwise indicated.
+ref_counted_ptr!(ResourceHandler, cef_resource_handler_t);
+
+impl ResourceHandler {
+    pub fn new<C: ResourceHandlerCallbacks>(delegate: C) -> Self {
+        Self(ResourceHandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct ResourceHandlerWrapper(Box<dyn ResourceHandlerCallbacks>);
+
+impl ResourceHandlerWrapper {
+    pub fn new<C: ResourceHandlerCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Open the response stream.
+    unsafe extern "C" fn c_open(
+        this: *mut cef_resource_handler_t,
+        request: *mut cef_request_t,
+        handle_request: *mut c_int,
+        callback: *mut cef_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let request = Request::from_ptr_unchecked(request);
+        let callback = Callback::from_ptr_unchecked(callback);
+        let mut local_handle_request = *handle_request != 0;
+
+        let ret = this
+            .0
+            .open(request, &mut local_handle_request, callback);
+
+        *handle_request = local_handle_request as c_int;
+
+        ret as c_int
+    }
+
+    /// Retrieve response header information.
+    unsafe extern "C" fn c_get_response_headers(
+        this: *mut cef_resource_handler_t,
+        response: *mut cef_response_t,
+        response_length: *mut i64,
+        redirect_url: *mut cef_string_t
+    ) {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let response = Response::from_ptr_unchecked(response);
+        let mut local_redirect_url = None;
+
+        this.0
+            .get_response_headers(response, &mut *response_length, &mut local_redirect_url);
+
+        if let (Some(url), Some(redirect_url)) =
+            (local_redirect_url, CefString::from_ptr_mut(redirect_url))
+        {
+            redirect_url.set(&url);
+        }
+    }
+
+    /// Skip response data when requested by a Range header.
+    unsafe extern "C" fn c_skip(
+        this: *mut cef_resource_handler_t,
+        bytes_to_skip: i64,
+        bytes_skipped: *mut i64,
+        callback: *mut cef_resource_skip_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let callback = ResourceSkipCallback::from_ptr_unchecked(callback);
+
+        this.0
+            .skip(bytes_to_skip, &mut *bytes_skipped, callback) as c_int
+    }
+
+    /// Read response data.
+    unsafe extern "C" fn c_read(
+        this: *mut cef_resource_handler_t,
+        data_out: *mut c_void,
+        bytes_to_read: c_int,
+        bytes_read: *mut c_int,
+        callback: *mut cef_resource_read_callback_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let data_out = from_raw_parts_mut(data_out as *mut u8, bytes_to_read.max(0) as usize);
+        let callback = ResourceReadCallback::from_ptr_unchecked(callback);
+
+        this.0
+            .read(data_out, &mut *bytes_read, callback) as c_int
+    }
+
+    /// Request processing has been canceled.
+    unsafe extern "C" fn c_cancel(this: *mut cef_resource_handler_t) {
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0.cancel();
+    }
+}
+
+impl Wrappable for ResourceHandlerWrapper {
+    type Cef = cef_resource_handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_resource_handler_t> {
+        RefCountedPtr::wrap(
+            cef_resource_handler_t {
+                base:                 unsafe { zeroed() },
+                open:                 Some(Self::c_open),
+                process_request:      None,
+                get_response_headers: Some(Self::c_get_response_headers),
+                skip:                 Some(Self::c_skip),
+                read:                 Some(Self::c_read),
+                read_response:        None,
+                cancel:               Some(Self::c_cancel)
+            },
+            self
+        )
+    }
+}
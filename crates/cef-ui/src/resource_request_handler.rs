@@ -1,10 +1,58 @@
-use crate::{ref_counted_ptr, RefCountedPtr, Wrappable};
+use crate::{
+    ref_counted_ptr, Browser, Callback, Frame, RefCountedPtr, Request, ResourceHandler, Response,
+    ResponseFilter, UrlRequestStatus, Wrappable, Wrapped
+};
 use cef_ui_sys::{
     cef_browser_t, cef_callback_t, cef_cookie_access_filter_t, cef_frame_t, cef_request_t,
     cef_resource_handler_t, cef_resource_request_handler_t, cef_response_filter_t, cef_response_t,
     cef_return_value_t, cef_string_t, cef_urlrequest_status_t
 };
-use std::{ffi::c_int, mem::zeroed};
+use std::{ffi::c_int, mem::zeroed, ptr::null_mut};
+
+/// Return value types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReturnValue {
+    /// Cancel immediately.
+    Cancel,
+
+    /// Continue immediately.
+    Continue,
+
+    /// Continue asynchronously (usually via a callback).
+    ContinueAsync
+}
+
+impl From<cef_return_value_t> for ReturnValue {
+    fn from(value: cef_return_value_t) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&cef_return_value_t> for ReturnValue {
+    fn from(value: &cef_return_value_t) -> Self {
+        match value {
+            cef_return_value_t::RV_CANCEL => Self::Cancel,
+            cef_return_value_t::RV_CONTINUE => Self::Continue,
+            cef_return_value_t::RV_CONTINUE_ASYNC => Self::ContinueAsync
+        }
+    }
+}
+
+impl From<ReturnValue> for cef_return_value_t {
+    fn from(value: ReturnValue) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&ReturnValue> for cef_return_value_t {
+    fn from(value: &ReturnValue) -> Self {
+        match value {
+            ReturnValue::Cancel => Self::RV_CANCEL,
+            ReturnValue::Continue => Self::RV_CONTINUE,
+            ReturnValue::ContinueAsync => Self::RV_CONTINUE_ASYNC
+        }
+    }
+}
 
 /// Implement this structure to handle events related to browser requests. The
 /// functions of this structure will be called on the IO thread unless otherwise
@@ -23,35 +71,37 @@ pub trait ResourceRequestHandlerCallbacks: Send + Sync + 'static {
     // // struct _cef_browser_t* browser,
     // // struct _cef_frame_t* frame,
     // // struct _cef_request_t* request);
-    //
-    // /// Called on the IO thread before a resource request is loaded. The |browser|
-    // /// and |frame| values represent the source of the request, and may be NULL
-    // /// for requests originating from service workers or cef_urlrequest_t. To
-    // /// redirect or change the resource load optionally modify |request|.
-    // /// Modification of the request URL will be treated as a redirect. Return
-    // /// RV_CONTINUE to continue the request immediately. Return RV_CONTINUE_ASYNC
-    // /// and call cef_callback_t functions at a later time to continue or cancel
-    // /// the request asynchronously. Return RV_CANCEL to cancel the request
-    // /// immediately.
-    // // cef_return_value_t(CEF_CALLBACK* on_before_resource_load)(
-    // // struct _cef_resource_request_handler_t* self,
-    // // struct _cef_browser_t* browser,
-    // // struct _cef_frame_t* frame,
-    // // struct _cef_request_t* request,
-    // // struct _cef_callback_t* callback);
-    //
-    // /// Called on the IO thread before a resource is loaded. The |browser| and
-    // /// |frame| values represent the source of the request, and may be NULL for
-    // /// requests originating from service workers or cef_urlrequest_t. To allow
-    // /// the resource to load using the default network loader return NULL. To
-    // /// specify a handler for the resource return a cef_resource_handler_t object.
-    // /// The |request| object cannot not be modified in this callback.
-    // // struct _cef_resource_handler_t*(CEF_CALLBACK* get_resource_handler)(
-    // // struct _cef_resource_request_handler_t* self,
-    // // struct _cef_browser_t* browser,
-    // // struct _cef_frame_t* frame,
-    // // struct _cef_request_t* request);
-    //
+
+    /// Called on the IO thread before a resource request is loaded. The |browser|
+    /// and |frame| values represent the source of the request, and may be NULL
+    /// for requests originating from service workers or cef_urlrequest_t. To
+    /// redirect or change the resource load optionally modify |request|.
+    /// Modification of the request URL will be treated as a redirect. Return
+    /// RV_CONTINUE to continue the request immediately. Return RV_CONTINUE_ASYNC
+    /// and call cef_callback_t functions at a later time to continue or cancel
+    /// the request asynchronously. Return RV_CANCEL to cancel the request
+    /// immediately.
+    fn on_before_resource_load(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        request: Request,
+        callback: Callback
+    ) -> ReturnValue;
+
+    /// Called on the IO thread before a resource is loaded. The |browser| and
+    /// |frame| values represent the source of the request, and may be NULL for
+    /// requests originating from service workers or cef_urlrequest_t. To allow
+    /// the resource to load using the default network loader return NULL. To
+    /// specify a handler for the resource return a cef_resource_handler_t object.
+    /// The |request| object cannot not be modified in this callback.
+    fn get_resource_handler(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        request: Request
+    ) -> Option<ResourceHandler>;
+
     // /// Called on the IO thread when a resource load is redirected. The |browser|
     // /// and |frame| values represent the source of the request, and may be NULL
     // /// for requests originating from service workers or cef_urlrequest_t. The
@@ -67,61 +117,64 @@ pub trait ResourceRequestHandlerCallbacks: Send + Sync + 'static {
     // // struct _cef_request_t* request,
     // // struct _cef_response_t* response,
     // // cef_string_t* new_url);
-    //
-    // /// Called on the IO thread when a resource response is received. The
-    // /// |browser| and |frame| values represent the source of the request, and may
-    // /// be NULL for requests originating from service workers or cef_urlrequest_t.
-    // /// To allow the resource load to proceed without modification return false
-    // /// (0). To redirect or retry the resource load optionally modify |request|
-    // /// and return true (1). Modification of the request URL will be treated as a
-    // /// redirect. Requests handled using the default network loader cannot be
-    // /// redirected in this callback. The |response| object cannot be modified in
-    // /// this callback.
-    // ///
-    // /// WARNING: Redirecting using this function is deprecated. Use
-    // /// OnBeforeResourceLoad or GetResourceHandler to perform redirects.
-    // // int(CEF_CALLBACK* on_resource_response)(
-    // // struct _cef_resource_request_handler_t* self,
-    // // struct _cef_browser_t* browser,
-    // // struct _cef_frame_t* frame,
-    // // struct _cef_request_t* request,
-    // // struct _cef_response_t* response);
-    //
-    // /// Called on the IO thread to optionally filter resource response content.
-    // /// The |browser| and |frame| values represent the source of the request, and
-    // /// may be NULL for requests originating from service workers or
-    // /// cef_urlrequest_t. |request| and |response| represent the request and
-    // /// response respectively and cannot be modified in this callback.
-    // // struct _cef_response_filter_t*(CEF_CALLBACK* get_resource_response_filter)(
-    // // struct _cef_resource_request_handler_t* self,
-    // // struct _cef_browser_t* browser,
-    // // struct _cef_frame_t* frame,
-    // // struct _cef_request_t* request,
-    // // struct _cef_response_t* response);
-    //
-    // /// Called on the IO thread when a resource load has completed. The |browser|
-    // /// and |frame| values represent the source of the request, and may be NULL
-    // /// for requests originating from service workers or cef_urlrequest_t.
-    // /// |request| and |response| represent the request and response respectively
-    // /// and cannot be modified in this callback. |status| indicates the load
-    // /// completion status. |received_content_length| is the number of response
-    // /// bytes actually read. This function will be called for all requests,
-    // /// including requests that are aborted due to CEF shutdown or destruction of
-    // /// the associated browser. In cases where the associated browser is destroyed
-    // /// this callback may arrive after the cef_life_span_handler_t::OnBeforeClose
-    // /// callback for that browser. The cef_frame_t::IsValid function can be used
-    // /// to test for this situation, and care should be taken not to call |browser|
-    // /// or |frame| functions that modify state (like LoadURL, SendProcessMessage,
-    // /// etc.) if the frame is invalid.
-    // // void(CEF_CALLBACK* on_resource_load_complete)(
-    // // struct _cef_resource_request_handler_t* self,
-    // // struct _cef_browser_t* browser,
-    // // struct _cef_frame_t* frame,
-    // // struct _cef_request_t* request,
-    // // struct _cef_response_t* response,
-    // // cef_urlrequest_status_t status,
-    // // int64_t received_content_length);
-    //
+
+    /// Called on the IO thread when a resource response is received. The
+    /// |browser| and |frame| values represent the source of the request, and may
+    /// be NULL for requests originating from service workers or cef_urlrequest_t.
+    /// To allow the resource load to proceed without modification return false
+    /// (0). To redirect or retry the resource load optionally modify |request|
+    /// and return true (1). Modification of the request URL will be treated as a
+    /// redirect. Requests handled using the default network loader cannot be
+    /// redirected in this callback. The |response| object cannot be modified in
+    /// this callback.
+    ///
+    /// WARNING: Redirecting using this function is deprecated. Use
+    /// OnBeforeResourceLoad or GetResourceHandler to perform redirects.
+    fn on_resource_response(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        request: Request,
+        response: Response
+    ) -> bool;
+
+    /// Called on the IO thread to optionally filter resource response content.
+    /// The |browser| and |frame| values represent the source of the request, and
+    /// may be NULL for requests originating from service workers or
+    /// cef_urlrequest_t. |request| and |response| represent the request and
+    /// response respectively and cannot be modified in this callback.
+    fn get_resource_response_filter(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        request: Request,
+        response: Response
+    ) -> Option<ResponseFilter>;
+
+    /// Called on the IO thread when a resource load has completed. The |browser|
+    /// and |frame| values represent the source of the request, and may be NULL
+    /// for requests originating from service workers or cef_urlrequest_t.
+    /// |request| and |response| represent the request and response respectively
+    /// and cannot be modified in this callback. |status| indicates the load
+    /// completion status. |received_content_length| is the number of response
+    /// bytes actually read. This function will be called for all requests,
+    /// including requests that are aborted due to CEF shutdown or destruction of
+    /// the associated browser. In cases where the associated browser is destroyed
+    /// this callback may arrive after the cef_life_span_handler_t::OnBeforeClose
+    /// callback for that browser. The cef_frame_t::IsValid function can be used
+    /// to test for this situation, and care should be taken not to call |browser|
+    /// or |frame| functions that modify state (like LoadURL, SendProcessMessage,
+    /// etc.) if the frame is invalid.
+    fn on_resource_load_complete(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        request: Request,
+        response: Response,
+        status: UrlRequestStatus,
+        received_content_length: i64
+    );
+
     // /// Called on the IO thread to handle requests for URLs with an unknown
     // /// protocol component. The |browser| and |frame| values represent the source
     // /// of the request, and may be NULL for requests originating from service
@@ -197,7 +250,15 @@ impl ResourceRequestHandlerWrapper {
         request: *mut cef_request_t,
         callback: *mut cef_callback_t
     ) -> cef_return_value_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let request = Request::from_ptr_unchecked(request);
+        let callback = Callback::from_ptr_unchecked(callback);
+
+        this.0
+            .on_before_resource_load(browser, frame, request, callback)
+            .into()
     }
 
     /// Called on the IO thread before a resource is loaded. The |browser| and
@@ -212,7 +273,15 @@ impl ResourceRequestHandlerWrapper {
         frame: *mut cef_frame_t,
         request: *mut cef_request_t
     ) -> *mut cef_resource_handler_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let request = Request::from_ptr_unchecked(request);
+
+        this.0
+            .get_resource_handler(browser, frame, request)
+            .map(|resource_handler| resource_handler.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Called on the IO thread when a resource load is redirected. The |browser|
@@ -253,7 +322,14 @@ impl ResourceRequestHandlerWrapper {
         request: *mut cef_request_t,
         response: *mut cef_response_t
     ) -> c_int {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let request = Request::from_ptr_unchecked(request);
+        let response = Response::from_ptr_unchecked(response);
+
+        this.0
+            .on_resource_response(browser, frame, request, response) as c_int
     }
 
     /// Called on the IO thread to optionally filter resource response content.
@@ -268,7 +344,16 @@ impl ResourceRequestHandlerWrapper {
         request: *mut cef_request_t,
         response: *mut cef_response_t
     ) -> *mut cef_response_filter_t {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let request = Request::from_ptr_unchecked(request);
+        let response = Response::from_ptr_unchecked(response);
+
+        this.0
+            .get_resource_response_filter(browser, frame, request, response)
+            .map(|response_filter| response_filter.into_raw())
+            .unwrap_or(null_mut())
     }
 
     /// Called on the IO thread when a resource load has completed. The |browser|
@@ -294,7 +379,20 @@ impl ResourceRequestHandlerWrapper {
         status: cef_urlrequest_status_t,
         received_content_length: i64
     ) {
-        todo!()
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let request = Request::from_ptr_unchecked(request);
+        let response = Response::from_ptr_unchecked(response);
+
+        this.0.on_resource_load_complete(
+            browser,
+            frame,
+            request,
+            response,
+            status.into(),
+            received_content_length
+        );
     }
 
     /// Called on the IO thread to handle requests for URLs with an unknown
@@ -326,12 +424,12 @@ impl Wrappable for ResourceRequestHandlerWrapper {
 
                 // TODO: Fix this!
                 get_cookie_access_filter:     None,
-                on_before_resource_load:      None,
-                get_resource_handler:         None,
+                on_before_resource_load:      Some(Self::c_on_before_resource_load),
+                get_resource_handler:         Some(Self::c_get_resource_handler),
                 on_resource_redirect:         None,
-                on_resource_response:         None,
-                get_resource_response_filter: None,
-                on_resource_load_complete:    None,
+                on_resource_response:         Some(Self::c_on_resource_response),
+                get_resource_response_filter: Some(Self::c_get_resource_response_filter),
+                on_resource_load_complete:    Some(Self::c_on_resource_load_complete),
                 on_protocol_execution:        None
             },
             self
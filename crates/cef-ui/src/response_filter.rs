@@ -0,0 +1,130 @@
+use crate::{ref_counted_ptr, RefCountedPtr, Wrappable, Wrapped};
+use cef_ui_sys::{cef_response_filter_status_t, cef_response_filter_t};
+use std::{
+    ffi::{c_int, c_void},
+    mem::zeroed,
+    slice::{from_raw_parts, from_raw_parts_mut}
+};
+
+/// Return values for cef_response_filter_t::Filter().
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResponseFilterStatus {
+    /// Some or all of the pre-filter data was read successfully but more data is
+    /// needed in order to continue filtering (filtered output is pending).
+    NeedMoreData,
+
+    /// Some or all of the pre-filter data was read successfully and all
+    /// available filtered output has been written.
+    Done,
+
+    /// An error occurred during filtering.
+    Error
+}
+
+impl From<ResponseFilterStatus> for cef_response_filter_status_t {
+    fn from(value: ResponseFilterStatus) -> Self {
+        match value {
+            ResponseFilterStatus::NeedMoreData => Self::RESPONSE_FILTER_NEED_MORE_DATA,
+            ResponseFilterStatus::Done => Self::RESPONSE_FILTER_DONE,
+            ResponseFilterStatus::Error => Self::RESPONSE_FILTER_ERROR
+        }
+    }
+}
+
+/// Implement this structure to filter resource response content. The functions
+/// of this structure will be called on the browser process IO thread.
+pub trait ResponseFilterCallbacks: Send + Sync + 'static {
+    /// Initialize the response filter. Will only be called a single time. The
+    /// filter will not be installed if this function returns false (0).
+    fn init_filter(&mut self) -> bool;
+
+    /// Called to filter a chunk of data. Expected usage is as follows:
+    ///
+    ///  1. Read input data from |data_in| and set |data_in_read| to the number of
+    ///     bytes that were read up to a maximum of the length of |data_in|.
+    ///     |data_in| will be None if the length of |data_in| is zero.
+    ///  2. Write filtered output data to |data_out| and set |data_out_written| to
+    ///     the number of bytes that were written up to a maximum of the length of
+    ///     |data_out|. If no output data was written then all data must be read
+    ///     from |data_in| (user must set |data_in_read| = length of |data_in|).
+    ///  3. Return Done if all output data was written or NeedMoreData if output
+    ///     data is still pending.
+    ///
+    /// This function will be called repeatedly until the input buffer has been
+    /// fully read (user sets |data_in_read| = length of |data_in|) and there is
+    /// no more input data to filter (the resource response is complete). This
+    /// function may then be called an additional time with an empty input buffer
+    /// if the user returned NeedMoreData from a previous call.
+    ///
+    /// Do not keep a reference to the buffers passed to this function.
+    fn filter(
+        &mut self,
+        data_in: Option<&[u8]>,
+        data_in_read: &mut usize,
+        data_out: &mut [u8],
+        data_out_written: &mut usize
+    ) -> ResponseFilterStatus;
+}
+
+// Implement this structure to filter resource response content.
+ref_counted_ptr!(ResponseFilter, cef_response_filter_t);
+
+impl ResponseFilter {
+    pub fn new<C: ResponseFilterCallbacks>(delegate: C) -> Self {
+        Self(ResponseFilterWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct ResponseFilterWrapper(Box<dyn ResponseFilterCallbacks>);
+
+impl ResponseFilterWrapper {
+    pub fn new<C: ResponseFilterCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Initialize the response filter.
+    unsafe extern "C" fn c_init_filter(this: *mut cef_response_filter_t) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+
+        this.0.init_filter() as c_int
+    }
+
+    /// Called to filter a chunk of data.
+    unsafe extern "C" fn c_filter(
+        this: *mut cef_response_filter_t,
+        data_in: *mut c_void,
+        data_in_size: usize,
+        data_in_read: *mut usize,
+        data_out: *mut c_void,
+        data_out_size: usize,
+        data_out_written: *mut usize
+    ) -> cef_response_filter_status_t {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let data_in = match data_in.is_null() || data_in_size == 0 {
+            true => None,
+            false => Some(from_raw_parts(data_in as *const u8, data_in_size))
+        };
+        let data_out = from_raw_parts_mut(data_out as *mut u8, data_out_size);
+
+        this.0
+            .filter(data_in, &mut *data_in_read, data_out, &mut *data_out_written)
+            .into()
+    }
+}
+
+impl Wrappable for ResponseFilterWrapper {
+    type Cef = cef_response_filter_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_response_filter_t> {
+        RefCountedPtr::wrap(
+            cef_response_filter_t {
+                base:        unsafe { zeroed() },
+                init_filter: Some(Self::c_init_filter),
+                filter:      Some(Self::c_filter)
+            },
+            self
+        )
+    }
+}
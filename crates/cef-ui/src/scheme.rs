@@ -0,0 +1,161 @@
+use crate::{
+    ref_counted_ptr, try_c, Browser, CefString, Frame, RefCountedPtr, Request, ResourceHandler,
+    Wrappable, Wrapped
+};
+use anyhow::Result;
+use bitflags::bitflags;
+use cef_ui_sys::{
+    cef_browser_t, cef_frame_t, cef_register_scheme_handler_factory, cef_request_t,
+    cef_resource_handler_t, cef_scheme_handler_factory_t, cef_scheme_options_t,
+    cef_scheme_registrar_t, cef_string_t
+};
+use std::{
+    ffi::c_int,
+    mem::zeroed,
+    ptr::{null, null_mut}
+};
+
+bitflags! {
+    /// Configuration options for registering a custom scheme. These values are
+    /// used when calling add_custom_scheme.
+    #[allow(non_upper_case_globals)]
+    #[derive(Default, Clone, Copy, Debug)]
+    pub struct SchemeOptions: c_int {
+        const None = cef_scheme_options_t::CEF_SCHEME_OPTION_NONE as c_int;
+        const Standard = cef_scheme_options_t::CEF_SCHEME_OPTION_STANDARD as c_int;
+        const Local = cef_scheme_options_t::CEF_SCHEME_OPTION_LOCAL as c_int;
+        const DisplayIsolated = cef_scheme_options_t::CEF_SCHEME_OPTION_DISPLAY_ISOLATED as c_int;
+        const Secure = cef_scheme_options_t::CEF_SCHEME_OPTION_SECURE as c_int;
+        const CorsEnabled = cef_scheme_options_t::CEF_SCHEME_OPTION_CORS_ENABLED as c_int;
+        const CspBypassing = cef_scheme_options_t::CEF_SCHEME_OPTION_CSP_BYPASSING as c_int;
+        const FetchEnabled = cef_scheme_options_t::CEF_SCHEME_OPTION_FETCH_ENABLED as c_int;
+    }
+}
+
+/// Structure that manages custom scheme registrations.
+pub struct SchemeRegistrar<'a>(&'a mut cef_scheme_registrar_t);
+
+impl<'a> SchemeRegistrar<'a> {
+    /// Convert from a pointer.
+    pub unsafe fn from_ptr(ptr: *mut cef_scheme_registrar_t) -> Option<Self> {
+        ptr.as_mut().map(Self)
+    }
+
+    /// Register a custom scheme. This function should not be called for the
+    /// built-in HTTP, HTTPS, FILE, FTP, ABOUT and DATA schemes.
+    ///
+    /// See cef_scheme_options_t for possible values for |options|.
+    ///
+    /// This function may be called on any thread. It should only be called once
+    /// per unique |scheme_name| value. If |scheme_name| is already registered or
+    /// if an error occurs this function will return false (0).
+    pub fn add_custom_scheme(&mut self, scheme_name: &str, options: SchemeOptions) -> Result<bool> {
+        try_c!(self, add_custom_scheme, {
+            let scheme_name = CefString::new(scheme_name);
+
+            Ok(add_custom_scheme(self.0, scheme_name.as_ptr(), options.bits()) != 0)
+        })
+    }
+}
+
+/// Structure that creates cef_resource_handler_t instances for handling scheme
+/// requests. The functions of this structure will always be called on the IO
+/// thread.
+pub trait SchemeHandlerFactoryCallbacks: Send + Sync + 'static {
+    /// Return a new resource handler instance to handle the request or an NULL
+    /// reference to allow default handling of the request. |browser| and |frame|
+    /// will be the browser window and frame respectively that originated the
+    /// request or NULL if the request did not originate from a browser window
+    /// (for example, if the request came from cef_urlrequest_t). The |request|
+    /// object passed to this function cannot be modified.
+    fn create(
+        &mut self,
+        browser: Option<Browser>,
+        frame: Option<Frame>,
+        scheme_name: &str,
+        request: Request
+    ) -> Option<ResourceHandler>;
+}
+
+// Structure that creates cef_resource_handler_t instances for handling scheme
+// requests. The functions of this structure will always be called on the IO
+// thread.
+ref_counted_ptr!(SchemeHandlerFactory, cef_scheme_handler_factory_t);
+
+impl SchemeHandlerFactory {
+    pub fn new<C: SchemeHandlerFactoryCallbacks>(delegate: C) -> Self {
+        Self(SchemeHandlerFactoryWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct SchemeHandlerFactoryWrapper(Box<dyn SchemeHandlerFactoryCallbacks>);
+
+impl SchemeHandlerFactoryWrapper {
+    pub fn new<C: SchemeHandlerFactoryCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Return a new resource handler instance to handle the request.
+    unsafe extern "C" fn c_create(
+        this: *mut cef_scheme_handler_factory_t,
+        browser: *mut cef_browser_t,
+        frame: *mut cef_frame_t,
+        scheme_name: *const cef_string_t,
+        request: *mut cef_request_t
+    ) -> *mut cef_resource_handler_t {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let browser = Browser::from_ptr(browser);
+        let frame = Frame::from_ptr(frame);
+        let scheme_name: String = CefString::from_ptr_unchecked(scheme_name).into();
+        let request = Request::from_ptr_unchecked(request);
+
+        this.0
+            .create(browser, frame, &scheme_name, request)
+            .map(|handler| handler.into_raw())
+            .unwrap_or(null_mut())
+    }
+}
+
+impl Wrappable for SchemeHandlerFactoryWrapper {
+    type Cef = cef_scheme_handler_factory_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_scheme_handler_factory_t> {
+        RefCountedPtr::wrap(
+            cef_scheme_handler_factory_t {
+                base:   unsafe { zeroed() },
+                create: Some(Self::c_create)
+            },
+            self
+        )
+    }
+}
+
+/// Register a scheme handler factory with the global request context. An NULL
+/// |domain_name| value for a standard scheme will cause the factory to match
+/// all domain names. The |domain_name| value will be ignored for non-standard
+/// schemes. If |scheme_name| is a built-in scheme and no handler is returned by
+/// |factory| then the built-in scheme handler factory will be called. If
+/// |scheme_name| is a custom scheme then you must also implement the
+/// cef_app_t::on_register_custom_schemes() function in all processes. This
+/// function may be called multiple times to change or remove the factory that
+/// matches the specified |scheme_name| and optional |domain_name|. Returns
+/// false (0) if an error occurs. This function may be called on any thread in
+/// the browser process.
+pub fn register_scheme_handler_factory(
+    scheme_name: &str,
+    domain_name: Option<&str>,
+    factory: SchemeHandlerFactory
+) -> bool {
+    let scheme_name = CefString::new(scheme_name);
+    let domain_name = domain_name.map(CefString::new);
+    let domain_name = domain_name
+        .as_ref()
+        .map_or(null(), |domain_name| domain_name.as_ptr());
+
+    unsafe {
+        cef_register_scheme_handler_factory(scheme_name.as_ptr(), domain_name, factory.into_raw())
+            != 0
+    }
+}
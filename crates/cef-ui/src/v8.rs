@@ -0,0 +1,291 @@
+use crate::{
+    ref_counted_ptr, try_c, Browser, CefString, Frame, RefCountedPtr, Wrappable, Wrapped
+};
+use anyhow::Result;
+use cef_ui_sys::{
+    cef_string_t, cef_v8_propertyattribute_t, cef_v8context_t, cef_v8handler_t,
+    cef_v8value_create_function, cef_v8value_create_undefined, cef_v8value_t
+};
+use std::{ffi::c_int, mem::zeroed, slice::from_raw_parts};
+
+/// V8 property attribute values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum V8PropertyAttribute {
+    /// Writeable, Enumerable, Configurable
+    None,
+
+    /// Not writeable
+    ReadOnly,
+
+    /// Not enumerable
+    DontEnum,
+
+    /// Not configurable
+    DontDelete
+}
+
+impl From<V8PropertyAttribute> for cef_v8_propertyattribute_t {
+    fn from(value: V8PropertyAttribute) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&V8PropertyAttribute> for cef_v8_propertyattribute_t {
+    fn from(value: &V8PropertyAttribute) -> Self {
+        match value {
+            V8PropertyAttribute::None => cef_v8_propertyattribute_t::V8_PROPERTY_ATTRIBUTE_NONE,
+            V8PropertyAttribute::ReadOnly => {
+                cef_v8_propertyattribute_t::V8_PROPERTY_ATTRIBUTE_READONLY
+            },
+            V8PropertyAttribute::DontEnum => {
+                cef_v8_propertyattribute_t::V8_PROPERTY_ATTRIBUTE_DONTENUM
+            },
+            V8PropertyAttribute::DontDelete => {
+                cef_v8_propertyattribute_t::V8_PROPERTY_ATTRIBUTE_DONTDELETE
+            },
+        }
+    }
+}
+
+// Structure representing a V8 context handle. V8 handles can only be accessed
+// from the thread on which they are created. Valid threads for creating a V8
+// handle include the render process main thread (TID_RENDERER) and WebWorker
+// threads. A task runner for posting tasks on the associated thread can be
+// retrieved via the cef_v8context_t::get_task_runner() function.
+ref_counted_ptr!(V8Context, cef_v8context_t);
+
+impl V8Context {
+    /// Returns true (1) if the underlying handle is valid and it can be accessed
+    /// on the current thread. Do not call any other functions if this function
+    /// returns false (0).
+    pub fn is_valid(&self) -> Result<bool> {
+        try_c!(self, is_valid, { Ok(is_valid(self.as_ptr()) != 0) })
+    }
+
+    /// Returns the browser for this context. This function will return an NULL
+    /// reference for WebWorker contexts.
+    pub fn get_browser(&self) -> Result<Option<Browser>> {
+        try_c!(self, get_browser, {
+            Ok(Browser::from_ptr(get_browser(self.as_ptr())))
+        })
+    }
+
+    /// Returns the frame for this context. This function will return an NULL
+    /// reference for WebWorker contexts.
+    pub fn get_frame(&self) -> Result<Option<Frame>> {
+        try_c!(self, get_frame, { Ok(Frame::from_ptr(get_frame(self.as_ptr()))) })
+    }
+
+    /// Returns the global object for this context. The context must be entered
+    /// before calling this function.
+    pub fn get_global(&self) -> Result<Option<V8Value>> {
+        try_c!(self, get_global, {
+            Ok(V8Value::from_ptr(get_global(self.as_ptr())))
+        })
+    }
+
+    /// Enter this context. A context must be explicitly entered before creating a
+    /// V8 Object, Array, Function or Date asynchronously. exit() must be called
+    /// the same number of times as enter() before releasing this context. V8
+    /// objects belong to the context in which they are created. Returns true (1)
+    /// if the scope was entered successfully.
+    pub fn enter(&self) -> Result<bool> {
+        try_c!(self, enter, { Ok(enter(self.as_ptr()) != 0) })
+    }
+
+    /// Exit this context. Call this function only after calling enter(). Returns
+    /// true (1) if the scope was exited successfully.
+    pub fn exit(&self) -> Result<bool> {
+        try_c!(self, exit, { Ok(exit(self.as_ptr()) != 0) })
+    }
+
+    /// Returns true (1) if this object is pointing to the same handle as |that|
+    /// object.
+    pub fn is_same(&self, that: V8Context) -> Result<bool> {
+        try_c!(self, is_same, {
+            Ok(is_same(self.as_ptr(), that.into_raw()) != 0)
+        })
+    }
+}
+
+// Structure representing a V8 value handle. V8 handles can only be accessed
+// from the thread on which they are created. Valid threads for creating a V8
+// handle include the render process main thread (TID_RENDERER) and WebWorker
+// threads. A task runner for posting tasks on the associated thread can be
+// retrieved via the cef_v8context_t::get_task_runner() function.
+ref_counted_ptr!(V8Value, cef_v8value_t);
+
+impl V8Value {
+    /// Create a new cef_v8value_t object of type undefined.
+    pub fn new_undefined() -> Self {
+        unsafe { Self::from_ptr_unchecked(cef_v8value_create_undefined()) }
+    }
+
+    /// Create a new cef_v8value_t object of type function. This function should
+    /// only be called from within the scope of a cef_render_process_handler_t,
+    /// cef_v8handler_t or cef_v8accessor_t callback, or in combination with
+    /// calling enter() and exit() on a stored cef_v8context_t reference.
+    pub fn new_function(name: &str, handler: V8Handler) -> Self {
+        unsafe {
+            let name = CefString::new(name);
+
+            Self::from_ptr_unchecked(cef_v8value_create_function(
+                name.as_ptr(),
+                handler.into_raw()
+            ))
+        }
+    }
+
+    /// Returns true (1) if the underlying handle is valid and it can be accessed
+    /// on the current thread. Do not call any other functions if this function
+    /// returns false (0).
+    pub fn is_valid(&self) -> Result<bool> {
+        try_c!(self, is_valid, { Ok(is_valid(self.as_ptr()) != 0) })
+    }
+
+    /// True if the value type is int.
+    pub fn is_int(&self) -> Result<bool> {
+        try_c!(self, is_int, { Ok(is_int(self.as_ptr()) != 0) })
+    }
+
+    /// True if the value type is string.
+    pub fn is_string(&self) -> Result<bool> {
+        try_c!(self, is_string, { Ok(is_string(self.as_ptr()) != 0) })
+    }
+
+    /// Return an int value.
+    pub fn get_int_value(&self) -> Result<i32> {
+        try_c!(self, get_int_value, { Ok(get_int_value(self.as_ptr())) })
+    }
+
+    /// Return a string value.
+    pub fn get_string_value(&self) -> Result<String> {
+        try_c!(self, get_string_value, {
+            let s = get_string_value(self.as_ptr());
+
+            Ok(CefString::from_userfree_ptr(s)
+                .map(|s| s.into())
+                .unwrap_or_default())
+        })
+    }
+
+    /// Associates a value with the specified identifier and returns true (1) on
+    /// success. Returns false (0) if this function is called incorrectly or an
+    /// exception is thrown. For read-only values this function will return true
+    /// (1) even though assignment failed.
+    pub fn set_value_bykey(
+        &self,
+        key: &str,
+        value: V8Value,
+        attribute: V8PropertyAttribute
+    ) -> Result<bool> {
+        try_c!(self, set_value_bykey, {
+            let key = CefString::new(key);
+
+            Ok(set_value_bykey(
+                self.as_ptr(),
+                key.as_ptr(),
+                value.into_raw(),
+                attribute.into()
+            ) != 0)
+        })
+    }
+}
+
+/// Structure that should be implemented to handle V8 function calls. The
+/// functions of this structure will be called on the thread associated with the
+/// V8 function.
+pub trait V8HandlerCallbacks: Send + Sync + 'static {
+    /// Handle execution of the function identified by |name|. |object| is the
+    /// receiver ('this' object) of the function. |arguments| is the list of
+    /// arguments passed to the function. If execution succeeds set |retval| to
+    /// the function return value. If execution fails set |exception| to the
+    /// exception that will be thrown. Return true (1) if execution was handled.
+    fn execute(
+        &mut self,
+        name: &str,
+        object: V8Value,
+        arguments: &[V8Value],
+        retval: &mut Option<V8Value>,
+        exception: &mut Option<String>
+    ) -> bool;
+}
+
+// Structure that should be implemented to handle V8 function calls. The
+// functions of this structure will be called on the thread associated with the
+// V8 function.
+ref_counted_ptr!(V8Handler, cef_v8handler_t);
+
+impl V8Handler {
+    pub fn new<C: V8HandlerCallbacks>(delegate: C) -> Self {
+        Self(V8HandlerWrapper::new(delegate).wrap())
+    }
+}
+
+/// Translates CEF -> Rust callbacks.
+struct V8HandlerWrapper(Box<dyn V8HandlerCallbacks>);
+
+impl V8HandlerWrapper {
+    pub fn new<C: V8HandlerCallbacks>(delegate: C) -> Self {
+        Self(Box::new(delegate))
+    }
+
+    /// Handle execution of the function identified by |name|.
+    unsafe extern "C" fn c_execute(
+        this: *mut cef_v8handler_t,
+        name: *const cef_string_t,
+        object: *mut cef_v8value_t,
+        arguments_count: usize,
+        arguments: *const *mut cef_v8value_t,
+        retval: *mut *mut cef_v8value_t,
+        exception: *mut cef_string_t
+    ) -> c_int {
+        let this: &mut Self = Wrapped::wrappable(this);
+        let name: String = CefString::from_ptr_unchecked(name).into();
+        let object = V8Value::from_ptr_unchecked(object);
+        let arguments = match arguments.is_null() {
+            true => Vec::new(),
+            false => from_raw_parts(arguments, arguments_count)
+                .iter()
+                .map(|&ptr| V8Value::from_ptr_unchecked(ptr))
+                .collect::<Vec<V8Value>>()
+        };
+        let mut local_retval = None;
+        let mut local_exception = None;
+
+        let ret = this.0.execute(
+            &name,
+            object,
+            &arguments,
+            &mut local_retval,
+            &mut local_exception
+        );
+
+        if let Some(value) = local_retval {
+            *retval = value.into_raw();
+        }
+
+        if let (Some(message), Some(exception)) =
+            (local_exception, CefString::from_ptr_mut(exception))
+        {
+            exception.set(&message);
+        }
+
+        ret as c_int
+    }
+}
+
+impl Wrappable for V8HandlerWrapper {
+    type Cef = cef_v8handler_t;
+
+    /// Converts this to a smart pointer.
+    fn wrap(self) -> RefCountedPtr<cef_v8handler_t> {
+        RefCountedPtr::wrap(
+            cef_v8handler_t {
+                base:    unsafe { zeroed() },
+                execute: Some(Self::c_execute)
+            },
+            self
+        )
+    }
+}
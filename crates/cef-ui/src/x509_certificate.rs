@@ -2,6 +2,7 @@ use crate::{ref_counted_ptr, try_c, BinaryValue, CefString, CefStringList, CefTi
 use anyhow::Result;
 use cef_ui_sys::{cef_x509cert_principal_t, cef_x509certificate_t};
 use chrono::{DateTime, Utc};
+use std::ptr::null_mut;
 
 // Structure representing the issuer or subject field of an X.509 certificate.
 ref_counted_ptr!(X509CertPrincipal, cef_x509cert_principal_t);
@@ -156,9 +157,22 @@ impl X509Certificate {
 
     /// Returns the DER encoded data for the certificate issuer chain. If we
     /// failed to encode a certificate in the chain it is still present in the
-    /// array but is an NULL string.
-    pub fn get_derencoded_issuer_chain(&self, _chain: &mut Vec<BinaryValue>) -> Result<()> {
-        unimplemented!("This is not easy to implement safely.")
+    /// array but is None.
+    pub fn get_derencoded_issuer_chain(&self) -> Result<Vec<Option<BinaryValue>>> {
+        let size = self.get_issuer_chain_size()?;
+
+        try_c!(self, get_derencoded_issuer_chain, {
+            let mut count = size;
+            let mut chain = vec![null_mut(); size];
+
+            get_derencoded_issuer_chain(self.as_ptr(), &mut count, chain.as_mut_ptr());
+
+            Ok(chain
+                .into_iter()
+                .take(count)
+                .map(|value| BinaryValue::from_ptr(value))
+                .collect())
+        })
     }
 
     /// Returns the PEM encoded data for the certificate issuer chain. If we